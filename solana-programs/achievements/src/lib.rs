@@ -0,0 +1,368 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::state::metaplex_adapter::MetadataArgs;
+use agent_registry::{AgentData, Bubblegum};
+use relationship::RelationshipData;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use tournament::{Entry, Tournament, TournamentStatus};
+
+declare_id!("Ach1evementsPr0graMxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const MAX_ACHIEVEMENTS: u8 = 64;  // Earned achievements are tracked in a u64 bitmap
+const MAX_NAME_LEN: usize = 32;
+const MAX_URI_LEN: usize = 200;
+
+#[program]
+pub mod achievements {
+    use super::*;
+
+    // Create the achievements config. The config PDA must be the tree
+    // delegate of `merkle_tree`, which holds every badge.
+    pub fn initialize_achievements(
+        ctx: Context<InitializeAchievements>,
+        merkle_tree: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.merkle_tree = merkle_tree;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Define a new achievement (admin)
+    pub fn create_achievement_type(
+        ctx: Context<CreateAchievementType>,
+        achievement_id: u8,
+        kind: AchievementKind,
+        name: String,
+        uri: String,
+    ) -> Result<()> {
+        require!(achievement_id < MAX_ACHIEVEMENTS, AchievementError::InvalidAchievementId);
+        require!(
+            !name.is_empty() && name.len() <= MAX_NAME_LEN && uri.len() <= MAX_URI_LEN,
+            AchievementError::InvalidMetadata
+        );
+
+        let achievement_type = &mut ctx.accounts.achievement_type;
+        achievement_type.achievement_id = achievement_id;
+        achievement_type.kind = kind.clone();
+        achievement_type.name = name.clone();
+        achievement_type.uri = uri;
+        achievement_type.active = true;
+        achievement_type.bump = *ctx.bumps.get("achievement_type").unwrap();
+
+        emit!(AchievementTypeCreated {
+            achievement_id,
+            kind,
+            name,
+        });
+
+        Ok(())
+    }
+
+    // Enable or retire an achievement (admin). Badges already earned are kept.
+    pub fn set_achievement_type_active(
+        ctx: Context<UpdateAchievementType>,
+        active: bool,
+    ) -> Result<()> {
+        let achievement_type = &mut ctx.accounts.achievement_type;
+        achievement_type.active = active;
+
+        emit!(AchievementTypeUpdated {
+            achievement_id: achievement_type.achievement_id,
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Verify an agent meets an achievement's condition, record it and mint
+    // its badge. The badge's leaf owner is the agent's AchievementState PDA,
+    // which never signs a transfer, so the badge stays bound to the agent.
+    pub fn claim_achievement(ctx: Context<ClaimAchievement>) -> Result<()> {
+        let achievement_type = &ctx.accounts.achievement_type;
+        let agent_data = &ctx.accounts.agent_data;
+        let agent = agent_data.key();
+
+        require!(achievement_type.active, AchievementError::AchievementInactive);
+        let achievement_bit = 1u64 << achievement_type.achievement_id;
+        require!(
+            ctx.accounts.achievement_state.earned_mask & achievement_bit == 0,
+            AchievementError::AlreadyEarned
+        );
+
+        match achievement_type.kind {
+            AchievementKind::FirstMatch => {
+                require!(agent_data.match_count >= 1, AchievementError::ConditionNotMet);
+            }
+            AchievementKind::Interactions { threshold } => {
+                require!(
+                    agent_data.interaction_count >= threshold,
+                    AchievementError::ConditionNotMet
+                );
+            }
+            AchievementKind::TournamentWinner => {
+                let (Some(tournament), Some(entry)) =
+                    (&ctx.accounts.tournament, &ctx.accounts.entry)
+                else {
+                    return err!(AchievementError::MissingProofAccounts);
+                };
+                require!(
+                    tournament.status == TournamentStatus::Completed
+                        && tournament.placements[0] == entry.key()
+                        && entry.tournament == tournament.key()
+                        && entry.agent == agent,
+                    AchievementError::ConditionNotMet
+                );
+            }
+            AchievementKind::RelationshipMilestones { threshold } => {
+                let Some(relationship) = &ctx.accounts.relationship else {
+                    return err!(AchievementError::MissingProofAccounts);
+                };
+                let relationship_data = relationship.load()?;
+                require!(
+                    (relationship_data.agent_one == agent || relationship_data.agent_two == agent)
+                        && relationship_data.milestone_count >= threshold,
+                    AchievementError::ConditionNotMet
+                );
+            }
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let achievement_state = &mut ctx.accounts.achievement_state;
+        achievement_state.agent = agent;
+        achievement_state.earned_mask |= achievement_bit;
+        achievement_state.badge_count = achievement_state.badge_count.checked_add(1).unwrap_or(u16::MAX);
+        achievement_state.last_earned_at = now;
+        achievement_state.bump = *ctx.bumps.get("achievement_state").unwrap();
+
+        mint_badge(&ctx, achievement_type)?;
+
+        emit!(AchievementEarned {
+            agent,
+            owner: ctx.accounts.owner.key(),
+            achievement_id: achievement_type.achievement_id,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct AchievementsConfig {
+    pub admin: Pubkey,
+    pub merkle_tree: Pubkey,  // Badge tree; the config PDA is its tree delegate
+    pub bump: u8,
+}
+
+#[account]
+pub struct AchievementType {
+    pub achievement_id: u8,
+    pub kind: AchievementKind,
+    pub name: String,
+    pub uri: String,
+    pub active: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct AchievementState {
+    pub agent: Pubkey,
+    pub earned_mask: u64,  // Bit n set once achievement n is earned
+    pub badge_count: u16,
+    pub last_earned_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeAchievements<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<AchievementsConfig>(),
+        seeds = [b"achievements_config"],
+        bump
+    )]
+    pub config: Account<'info, AchievementsConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(achievement_id: u8)]
+pub struct CreateAchievementType<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"achievements_config"],
+        bump = config.bump,
+        has_one = admin @ AchievementError::Unauthorized,
+    )]
+    pub config: Account<'info, AchievementsConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<AchievementType>() + MAX_NAME_LEN + MAX_URI_LEN,
+        seeds = [b"achievement_type", achievement_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub achievement_type: Account<'info, AchievementType>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAchievementType<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"achievements_config"],
+        bump = config.bump,
+        has_one = admin @ AchievementError::Unauthorized,
+    )]
+    pub config: Account<'info, AchievementsConfig>,
+
+    #[account(mut)]
+    pub achievement_type: Account<'info, AchievementType>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAchievement<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"achievements_config"], bump = config.bump)]
+    pub config: Account<'info, AchievementsConfig>,
+
+    #[account(
+        seeds = [b"achievement_type", achievement_type.achievement_id.to_le_bytes().as_ref()],
+        bump = achievement_type.bump,
+    )]
+    pub achievement_type: Account<'info, AchievementType>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ AchievementError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<AchievementState>(),
+        seeds = [b"achievement_state", agent_data.key().as_ref()],
+        bump
+    )]
+    pub achievement_state: Account<'info, AchievementState>,
+
+    // Proof accounts, required only by the achievement kinds that check them
+    pub relationship: Option<AccountLoader<'info, RelationshipData>>,
+    pub tournament: Option<Account<'info, Tournament>>,
+    pub entry: Option<Account<'info, Entry>>,
+
+    // Badge tree accounts
+    /// CHECK: validated by Bubblegum
+    #[account(mut)]
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut, address = config.merkle_tree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum AchievementKind {
+    FirstMatch,                               // Registry match_count >= 1
+    Interactions { threshold: u32 },          // Registry interaction_count >= threshold
+    TournamentWinner,                         // First place in a completed tournament
+    RelationshipMilestones { threshold: u32 },  // A relationship with >= threshold milestones
+}
+
+// Events
+#[event]
+pub struct AchievementTypeCreated {
+    pub achievement_id: u8,
+    pub kind: AchievementKind,
+    pub name: String,
+}
+
+#[event]
+pub struct AchievementTypeUpdated {
+    pub achievement_id: u8,
+    pub active: bool,
+}
+
+#[event]
+pub struct AchievementEarned {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub achievement_id: u8,
+    pub timestamp: i64,
+}
+
+// Custom errors
+#[error_code]
+pub enum AchievementError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Achievement id is out of range")]
+    InvalidAchievementId,
+    #[msg("Achievement name or URI is invalid")]
+    InvalidMetadata,
+    #[msg("Achievement is not active")]
+    AchievementInactive,
+    #[msg("Agent has already earned this achievement")]
+    AlreadyEarned,
+    #[msg("Agent does not meet the achievement's condition")]
+    ConditionNotMet,
+    #[msg("Accounts proving the achievement's condition are missing")]
+    MissingProofAccounts,
+}
+
+// Helper functions
+// Mint the badge to the agent's AchievementState PDA, signing as the tree delegate
+fn mint_badge(ctx: &Context<ClaimAchievement>, achievement_type: &AchievementType) -> Result<()> {
+    let metadata = MetadataArgs {
+        name: achievement_type.name.clone(),
+        symbol: "BADGE".to_string(),
+        uri: achievement_type.uri.clone(),
+        seller_fee_basis_points: 0,
+        creators: vec![],
+        collection: None,
+        uses: None,
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: None,
+        token_program_version: None,
+    };
+
+    let config_seeds: &[&[u8]] = &[b"achievements_config", &[ctx.accounts.config.bump]];
+    mpl_bubblegum::cpi::mint_v1(
+        CpiContext::new_with_signer(
+            ctx.accounts.bubblegum_program.to_account_info(),
+            mpl_bubblegum::accounts::MintV1 {
+                tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                leaf_owner: ctx.accounts.achievement_state.to_account_info(),
+                leaf_delegate: ctx.accounts.achievement_state.to_account_info(),
+                merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                payer: ctx.accounts.owner.to_account_info(),
+                tree_delegate: ctx.accounts.config.to_account_info(),
+                log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: ctx.accounts.compression_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[config_seeds],
+        ),
+        metadata,
+    )
+}
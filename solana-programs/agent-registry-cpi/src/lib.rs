@@ -0,0 +1,758 @@
+// Interface for programs and clients that integrate with the agent registry:
+// the program id and account layouts, PDA finders, and instruction builders.
+// The `cpi` feature adds the registry's Anchor CPI module for on-chain callers.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+
+pub use agent_registry::{
+    accounts, calculate_weighted_compatibility, canonical_pair, id, instruction, program, AgentData,
+    AgentError, AgentRegistration, Bubblegum, CnftArgs, CollectionConfig, CompatibilityAlgorithm,
+    CompatibilityConfig, CompatibilityResult, CompatibilityWeights, CustomTraitWeight, InteractionHookPayload,
+    LegacyAgentData, LegacyMatchData, LevelConfig, LevelParams, MatchData, MatchEndReason, MatchProposal,
+    MatchQueue, Normalization, PairState, PendingTransfer, PersonalityTraits, QueueEntry, QueuePreferences,
+    RegistryConfig, RegistryState, TraitEntry, TraitWeights, ANALYTICS_HOOK_AUTHORITY_SEED, ANALYTICS_PROGRAM_ID,
+    COLLECTION_AUTHORITY_SEED, COMPATIBILITY_RESULT_MAX_AGE, DEFAULT_MATCH_COOLDOWN, ID, LEGACY_AGENT_SPACE,
+    LEGACY_MATCH_SPACE, MATCH_CLOSE_GRACE_PERIOD, MAX_CUSTOM_TRAITS, MAX_LEVELS, MAX_QUEUED_PER_OWNER,
+    MAX_QUEUE_LEN, MAX_TRAIT_KEY_LEN, MAX_WEIGHTED_TRAITS, PROFILE_HOOK_AUTHORITY_SEED, QUEST_HOOK_AUTHORITY_SEED,
+    QUEUE_ENTRY_TTL, TOKEN_METADATA_PROGRAM_ID,
+};
+
+pub use referrals_cpi::ReferralEdge;
+
+#[cfg(feature = "cpi")]
+pub use agent_registry::cpi;
+
+pub mod pda {
+    use super::*;
+
+    pub fn find_agent_address(owner: &Pubkey, name: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"agent", owner.as_ref(), name.as_bytes()], &ID)
+    }
+
+    // A pair's match history, in either agent order
+    pub fn find_pair_state_address(agent_a: &Pubkey, agent_b: &Pubkey) -> (Pubkey, u8) {
+        let (agent_one, agent_two) = canonical_pair(*agent_a, *agent_b);
+        Pubkey::find_program_address(&[b"pair_state", agent_one.as_ref(), agent_two.as_ref()], &ID)
+    }
+
+    // The pair's match with the given nonce, counting from 0
+    pub fn find_match_address(agent_a: &Pubkey, agent_b: &Pubkey, match_nonce: u64) -> (Pubkey, u8) {
+        let pair_state = find_pair_state_address(agent_a, agent_b).0;
+        Pubkey::find_program_address(&[b"match", pair_state.as_ref(), &match_nonce.to_le_bytes()], &ID)
+    }
+
+    pub fn find_match_proposal_address(agent_one: &Pubkey, agent_two: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"match_proposal", agent_one.as_ref(), agent_two.as_ref()], &ID)
+    }
+
+    pub fn find_match_queue_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"match_queue"], &ID)
+    }
+
+    pub fn find_pending_transfer_address(agent_data: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"pending_transfer", agent_data.as_ref()], &ID)
+    }
+
+    pub fn find_registry_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"registry_config"], &ID)
+    }
+
+    pub fn find_registry_state_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"registry_state"], &ID)
+    }
+
+    // Agents in canonical order (lower key first)
+    pub fn find_compatibility_result_address(agent_one: &Pubkey, agent_two: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"compatibility", agent_one.as_ref(), agent_two.as_ref()], &ID)
+    }
+
+    pub fn find_compatibility_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"compatibility_config"], &ID)
+    }
+
+    pub fn find_level_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"level_config"], &ID)
+    }
+
+    pub fn find_trait_weights_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"trait_weights"], &ID)
+    }
+
+    // The agent collection and the PDA that signs as its update authority
+    pub fn find_collection_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"collection_config"], &ID)
+    }
+
+    pub fn find_collection_authority() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[COLLECTION_AUTHORITY_SEED], &ID)
+    }
+
+    // Token Metadata accounts of the collection NFT
+    pub fn find_collection_metadata_address(collection_mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), collection_mint.as_ref()],
+            &TOKEN_METADATA_PROGRAM_ID,
+        )
+    }
+
+    pub fn find_collection_edition_address(collection_mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), collection_mint.as_ref(), b"edition"],
+            &TOKEN_METADATA_PROGRAM_ID,
+        )
+    }
+
+    // Signers other programs derive under their own ids to call into the registry
+    pub fn find_quest_hook_authority(quests_program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[QUEST_HOOK_AUTHORITY_SEED], quests_program)
+    }
+
+    pub fn find_profile_hook_authority(profiles_program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROFILE_HOOK_AUTHORITY_SEED], profiles_program)
+    }
+
+    // The guardian whose pause flags gate state-changing instructions
+    pub fn find_guardian_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[guardian::GUARDIAN_SEED], &guardian::ID)
+    }
+
+    // The rate limiter config and a wallet's quota, for instructions that
+    // consume rate-limited actions
+    pub fn find_rate_limiter_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[rate_limiter::RATE_LIMITER_CONFIG_SEED], &rate_limiter::ID)
+    }
+
+    pub fn find_wallet_quota_address(wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[rate_limiter::WALLET_QUOTA_SEED, wallet.as_ref()], &rate_limiter::ID)
+    }
+
+    // A wallet's attestation credential, which gated builds require for registration
+    pub fn find_credential_address(wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[attestation::CREDENTIAL_SEED, wallet.as_ref()], &attestation::ID)
+    }
+
+    // The analytics hook signer and the analytics state it updates
+    pub fn find_analytics_hook_authority() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ANALYTICS_HOOK_AUTHORITY_SEED], &ID)
+    }
+
+    pub fn find_analytics_state_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"analytics"], &ANALYTICS_PROGRAM_ID)
+    }
+
+    // The signer of the registry's referral reports
+    pub fn find_referral_reporter_address() -> (Pubkey, u8) {
+        referrals_cpi::pda::find_reporter_address(&ID)
+    }
+}
+
+pub mod builders {
+    use super::*;
+
+    // The collection accounts registration instructions carry
+    pub fn collection_accounts(collection_mint: Pubkey) -> accounts::AgentCollection {
+        accounts::AgentCollection {
+            collection_config: pda::find_collection_config_address().0,
+            collection_authority: pda::find_collection_authority().0,
+            collection_mint,
+            collection_metadata: pda::find_collection_metadata_address(&collection_mint).0,
+            collection_edition: pda::find_collection_edition_address(&collection_mint).0,
+            bubblegum_signer: Pubkey::find_program_address(&[b"collection_cpi"], &Bubblegum::id()).0,
+            log_wrapper: spl_account_compression::Noop::id(),
+            token_metadata_program: TOKEN_METADATA_PROGRAM_ID,
+        }
+    }
+
+    pub fn initialize_registry_state(admin: Pubkey) -> Instruction {
+        build(
+            accounts::InitializeRegistryState {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                registry_state: pda::find_registry_state_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::InitializeRegistryState {},
+        )
+    }
+
+    pub fn initialize_compatibility_config(admin: Pubkey, weights: CompatibilityWeights) -> Instruction {
+        build(
+            accounts::InitializeCompatibilityConfig {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                compatibility_config: pda::find_compatibility_config_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::InitializeCompatibilityConfig { weights },
+        )
+    }
+
+    pub fn set_compatibility_config(admin: Pubkey, weights: CompatibilityWeights) -> Instruction {
+        build(
+            accounts::SetCompatibilityConfig {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                compatibility_config: pda::find_compatibility_config_address().0,
+            },
+            instruction::SetCompatibilityConfig { weights },
+        )
+    }
+
+    pub fn initialize_level_config(admin: Pubkey, params: LevelParams) -> Instruction {
+        build(
+            accounts::InitializeLevelConfig {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                level_config: pda::find_level_config_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::InitializeLevelConfig { params },
+        )
+    }
+
+    pub fn set_level_config(admin: Pubkey, params: LevelParams) -> Instruction {
+        build(
+            accounts::SetLevelConfig {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                level_config: pda::find_level_config_address().0,
+            },
+            instruction::SetLevelConfig { params },
+        )
+    }
+
+    pub fn initialize_trait_weights(admin: Pubkey, base_weight: u16, weights: Vec<CustomTraitWeight>) -> Instruction {
+        build(
+            accounts::InitializeTraitWeights {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                trait_weights: pda::find_trait_weights_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::InitializeTraitWeights { base_weight, weights },
+        )
+    }
+
+    pub fn set_trait_weights(admin: Pubkey, base_weight: u16, weights: Vec<CustomTraitWeight>) -> Instruction {
+        build(
+            accounts::SetTraitWeights {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                trait_weights: pda::find_trait_weights_address().0,
+            },
+            instruction::SetTraitWeights { base_weight, weights },
+        )
+    }
+
+    // Signed by the registry admin. The collection's update authority must
+    // already be `pda::find_collection_authority()`.
+    pub fn initialize_collection(admin: Pubkey, collection_mint: Pubkey) -> Instruction {
+        build(
+            accounts::InitializeCollection {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                collection_config: pda::find_collection_config_address().0,
+                collection_authority: pda::find_collection_authority().0,
+                collection_mint,
+                collection_metadata: pda::find_collection_metadata_address(&collection_mint).0,
+                collection_edition: pda::find_collection_edition_address(&collection_mint).0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::InitializeCollection {},
+        )
+    }
+
+    pub fn set_collection(admin: Pubkey, collection_mint: Pubkey) -> Instruction {
+        build(
+            accounts::SetCollection {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                collection_config: pda::find_collection_config_address().0,
+                collection_authority: pda::find_collection_authority().0,
+                collection_mint,
+                collection_metadata: pda::find_collection_metadata_address(&collection_mint).0,
+                collection_edition: pda::find_collection_edition_address(&collection_mint).0,
+            },
+            instruction::SetCollection {},
+        )
+    }
+
+    // Signed by the owner, the agent's delegate, or a session key holder when
+    // `session` is given
+    pub fn update_agent_status(
+        owner: Pubkey,
+        agent_data: Pubkey,
+        session: Option<Pubkey>,
+        is_active: bool,
+    ) -> Instruction {
+        build(
+            accounts::UpdateAgent {
+                owner,
+                agent_data,
+                session,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::UpdateAgentStatus { is_active },
+        )
+    }
+
+    pub fn update_personality_traits(
+        owner: Pubkey,
+        agent_data: Pubkey,
+        personality_traits: PersonalityTraits,
+    ) -> Instruction {
+        build(
+            accounts::UpdateAgent {
+                owner,
+                agent_data,
+                session: None,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::UpdatePersonalityTraits { personality_traits },
+        )
+    }
+
+    fn custom_traits_accounts(owner: Pubkey, agent_data: Pubkey) -> accounts::UpdateCustomTraits {
+        accounts::UpdateCustomTraits {
+            owner,
+            agent_data,
+            guardian: pda::find_guardian_address().0,
+            system_program: anchor_lang::system_program::ID,
+        }
+    }
+
+    pub fn add_custom_trait(owner: Pubkey, agent_data: Pubkey, key: String, value: u8) -> Instruction {
+        build(
+            custom_traits_accounts(owner, agent_data),
+            instruction::AddCustomTrait { key, value },
+        )
+    }
+
+    pub fn update_custom_trait(owner: Pubkey, agent_data: Pubkey, key: String, value: u8) -> Instruction {
+        build(
+            custom_traits_accounts(owner, agent_data),
+            instruction::UpdateCustomTrait { key, value },
+        )
+    }
+
+    pub fn remove_custom_trait(owner: Pubkey, agent_data: Pubkey, key: String) -> Instruction {
+        build(
+            custom_traits_accounts(owner, agent_data),
+            instruction::RemoveCustomTrait { key },
+        )
+    }
+
+    // Signed by the owner or the agent's delegate
+    pub fn record_agent_activity(signer: Pubkey, agent_data: Pubkey, interactions: u32) -> Instruction {
+        build(
+            accounts::RecordAgentActivity {
+                signer,
+                agent_data,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::RecordAgentActivity { interactions },
+        )
+    }
+
+    // Signed by the level config's XP authority
+    pub fn grant_xp(xp_authority: Pubkey, agent_data: Pubkey, amount: u64) -> Instruction {
+        build(
+            accounts::GrantXp {
+                xp_authority,
+                agent_data,
+                level_config: pda::find_level_config_address().0,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::GrantXp { amount },
+        )
+    }
+
+    pub fn set_delegate(owner: Pubkey, agent_data: Pubkey, delegate: Pubkey) -> Instruction {
+        build(
+            accounts::SetDelegate {
+                owner,
+                agent_data,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::SetDelegate { delegate },
+        )
+    }
+
+    pub fn revoke_delegate(owner: Pubkey, agent_data: Pubkey) -> Instruction {
+        build(
+            accounts::SetDelegate {
+                owner,
+                agent_data,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::RevokeDelegate {},
+        )
+    }
+
+    // Custom traits are scored only when `weighted` is set, which requires
+    // the trait weights to have been initialized
+    pub fn calculate_compatibility(
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+        algorithm: CompatibilityAlgorithm,
+        weighted: bool,
+    ) -> Instruction {
+        build(
+            accounts::CalculateCompatibility {
+                agent_one,
+                agent_two,
+                compatibility_config: pda::find_compatibility_config_address().0,
+                trait_weights: weighted.then(|| pda::find_trait_weights_address().0),
+            },
+            instruction::CalculateCompatibility {
+                algorithm_version: algorithm.version(),
+            },
+        )
+    }
+
+    // Agents in canonical order (lower key first)
+    pub fn store_compatibility(
+        payer: Pubkey,
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+        algorithm: CompatibilityAlgorithm,
+        weighted: bool,
+    ) -> Instruction {
+        build(
+            accounts::StoreCompatibility {
+                payer,
+                agent_one,
+                agent_two,
+                compatibility_config: pda::find_compatibility_config_address().0,
+                trait_weights: weighted.then(|| pda::find_trait_weights_address().0),
+                compatibility_result: pda::find_compatibility_result_address(&agent_one, &agent_two).0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::StoreCompatibility {
+                algorithm_version: algorithm.version(),
+            },
+        )
+    }
+
+    // Signed by agent one's owner or delegate
+    pub fn propose_match(proposer: Pubkey, agent_one: Pubkey, agent_two: Pubkey) -> Instruction {
+        build(
+            accounts::ProposeMatch {
+                proposer,
+                agent_one,
+                agent_two,
+                proposal: pda::find_match_proposal_address(&agent_one, &agent_two).0,
+                guardian: pda::find_guardian_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::ProposeMatch {},
+        )
+    }
+
+    // Signed by agent two's owner or delegate, after `store_compatibility`
+    // for the pair. `match_nonce` is the pair state's current nonce, 0 for
+    // a first match. The match earns XP only when `award_xp` is set, which
+    // requires the level config to have been initialized. `referrals` are
+    // the agents' owners' referral edges, for those who were referred.
+    pub fn accept_match(
+        authority: Pubkey,
+        proposer: Pubkey,
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+        match_nonce: u64,
+        award_xp: bool,
+        referrals: [Option<ReferralEdge>; 2],
+    ) -> Instruction {
+        let (lower, higher) = canonical_pair(agent_one, agent_two);
+        let [referral_one, referral_two] = referrals.map(|edge| edge.map(|edge| edge.addresses()));
+        let reporting = referral_one.is_some() || referral_two.is_some();
+        build(
+            accounts::AcceptMatch {
+                authority,
+                agent_one,
+                agent_two,
+                proposal: pda::find_match_proposal_address(&agent_one, &agent_two).0,
+                proposer,
+                registry_config: pda::find_registry_config_address().0,
+                pair_state: pda::find_pair_state_address(&agent_one, &agent_two).0,
+                match_data: pda::find_match_address(&agent_one, &agent_two, match_nonce).0,
+                compatibility_result: pda::find_compatibility_result_address(&lower, &higher).0,
+                system_program: anchor_lang::system_program::ID,
+                guardian: pda::find_guardian_address().0,
+                rate_limiter_config: pda::find_rate_limiter_config_address().0,
+                wallet_quota: pda::find_wallet_quota_address(&authority).0,
+                rate_limiter_program: rate_limiter::ID,
+                analytics_hook_authority: pda::find_analytics_hook_authority().0,
+                analytics_state: pda::find_analytics_state_address().0,
+                analytics_program: ANALYTICS_PROGRAM_ID,
+                level_config: award_xp.then(|| pda::find_level_config_address().0),
+                referral_reporter: reporting.then(|| pda::find_referral_reporter_address().0),
+                referrals_config: reporting.then(|| referrals_cpi::pda::find_config_address().0),
+                referral_one: referral_one.map(|(referral, _)| referral),
+                referrer_rewards_one: referral_one.map(|(_, rewards)| rewards),
+                referral_two: referral_two.map(|(referral, _)| referral),
+                referrer_rewards_two: referral_two.map(|(_, rewards)| rewards),
+                referrals_program: reporting.then_some(referrals_cpi::ID),
+            },
+            instruction::AcceptMatch {},
+        )
+    }
+
+    // Signed by either agent's owner or delegate
+    pub fn decline_match(signer: Pubkey, proposer: Pubkey, agent_one: Pubkey, agent_two: Pubkey) -> Instruction {
+        build(
+            accounts::DeclineMatch {
+                signer,
+                agent_one,
+                agent_two,
+                proposal: pda::find_match_proposal_address(&agent_one, &agent_two).0,
+                proposer,
+            },
+            instruction::DeclineMatch {},
+        )
+    }
+
+    // Signed by either agent's owner or delegate
+    pub fn dissolve_match(
+        signer: Pubkey,
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+        match_nonce: u64,
+        reason: MatchEndReason,
+    ) -> Instruction {
+        build(
+            accounts::DissolveMatch {
+                signer,
+                agent_one,
+                agent_two,
+                match_data: pda::find_match_address(&agent_one, &agent_two, match_nonce).0,
+                pair_state: pda::find_pair_state_address(&agent_one, &agent_two).0,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::DissolveMatch { reason },
+        )
+    }
+
+    // `rent_payer` is the match's `rent_payer`
+    pub fn close_match(agent_one: Pubkey, agent_two: Pubkey, match_nonce: u64, rent_payer: Pubkey) -> Instruction {
+        build(
+            accounts::CloseMatch {
+                agent_one,
+                agent_two,
+                match_data: pda::find_match_address(&agent_one, &agent_two, match_nonce).0,
+                rent_payer,
+            },
+            instruction::CloseMatch {},
+        )
+    }
+
+    pub fn initialize_match_queue(admin: Pubkey) -> Instruction {
+        build(
+            accounts::InitializeMatchQueue {
+                admin,
+                registry_config: pda::find_registry_config_address().0,
+                match_queue: pda::find_match_queue_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::InitializeMatchQueue {},
+        )
+    }
+
+    // Signed by the agent's owner or delegate
+    pub fn enter_queue(signer: Pubkey, agent_data: Pubkey, preferences: QueuePreferences) -> Instruction {
+        build(
+            accounts::EnterQueue {
+                signer,
+                agent_data,
+                match_queue: pda::find_match_queue_address().0,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::EnterQueue { preferences },
+        )
+    }
+
+    // Signed by the agent's owner or delegate
+    pub fn leave_queue(signer: Pubkey, agent_data: Pubkey) -> Instruction {
+        build(
+            accounts::LeaveQueue {
+                signer,
+                agent_data,
+                match_queue: pda::find_match_queue_address().0,
+            },
+            instruction::LeaveQueue {},
+        )
+    }
+
+    // `queued` is every queued agent in queue order. `pairs` are the pairs
+    // the crank will try, best first: those it will skip, then the one it
+    // will match, whose current nonce is `match_nonce`. Custom traits are
+    // scored only when `weighted` is set and XP is awarded only when
+    // `award_xp` is set, which require the trait weights and level config
+    // to have been initialized.
+    pub fn process_queue(
+        payer: Pubkey,
+        queued: &[Pubkey],
+        pairs: &[(Pubkey, Pubkey)],
+        match_nonce: u64,
+        weighted: bool,
+        award_xp: bool,
+    ) -> Instruction {
+        let mut instruction = build(
+            accounts::ProcessQueue {
+                payer,
+                match_queue: pda::find_match_queue_address().0,
+                registry_config: pda::find_registry_config_address().0,
+                compatibility_config: pda::find_compatibility_config_address().0,
+                trait_weights: weighted.then(|| pda::find_trait_weights_address().0),
+                level_config: award_xp.then(|| pda::find_level_config_address().0),
+                guardian: pda::find_guardian_address().0,
+                system_program: anchor_lang::system_program::ID,
+            },
+            instruction::ProcessQueue {},
+        );
+        instruction
+            .accounts
+            .extend(queued.iter().map(|agent| AccountMeta::new(*agent, false)));
+        instruction.accounts.extend(
+            pairs
+                .iter()
+                .map(|(agent_a, agent_b)| AccountMeta::new(pda::find_pair_state_address(agent_a, agent_b).0, false)),
+        );
+        if let Some((agent_a, agent_b)) = pairs.last() {
+            instruction
+                .accounts
+                .push(AccountMeta::new(pda::find_match_address(agent_a, agent_b, match_nonce).0, false));
+        }
+        instruction
+    }
+
+    // Both owners sign. Append the profile hook accounts to move the agent
+    // between user profiles.
+    pub fn transfer_ownership(owner: Pubkey, new_owner: Pubkey, agent_data: Pubkey) -> Instruction {
+        let mut instruction = build(
+            accounts::TransferOwnership {
+                owner,
+                new_owner,
+                agent_data,
+                system_program: anchor_lang::system_program::ID,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::TransferOwnership {},
+        );
+        instruction.accounts[1].is_signer = true;
+        instruction
+    }
+
+    pub fn propose_ownership_transfer(owner: Pubkey, agent_data: Pubkey, new_owner: Pubkey) -> Instruction {
+        build(
+            accounts::ProposeOwnershipTransfer {
+                owner,
+                agent_data,
+                pending_transfer: pda::find_pending_transfer_address(&agent_data).0,
+                system_program: anchor_lang::system_program::ID,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::ProposeOwnershipTransfer { new_owner },
+        )
+    }
+
+    // `owner` is the proposer recorded in the pending transfer. Append the
+    // profile hook accounts to move the agent between user profiles.
+    pub fn accept_ownership(new_owner: Pubkey, owner: Pubkey, agent_data: Pubkey) -> Instruction {
+        build(
+            accounts::AcceptOwnership {
+                new_owner,
+                owner,
+                agent_data,
+                pending_transfer: pda::find_pending_transfer_address(&agent_data).0,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::AcceptOwnership {},
+        )
+    }
+
+    pub fn cancel_ownership_transfer(signer: Pubkey, owner: Pubkey, agent_data: Pubkey) -> Instruction {
+        build(
+            accounts::CancelOwnershipTransfer {
+                signer,
+                owner,
+                agent_data,
+                pending_transfer: pda::find_pending_transfer_address(&agent_data).0,
+            },
+            instruction::CancelOwnershipTransfer {},
+        )
+    }
+
+    // `proof` is the leaf's proof path from the DAS API, excluding the
+    // canopy. Append the profile hook accounts to unlink the agent from the
+    // owner's user profile.
+    pub fn close_agent(
+        owner: Pubkey,
+        agent_data: Pubkey,
+        merkle_tree: Pubkey,
+        cnft: CnftArgs,
+        proof: &[Pubkey],
+    ) -> Instruction {
+        let mut instruction = build(
+            accounts::CloseAgent {
+                owner,
+                agent_data,
+                tree_authority: Pubkey::find_program_address(&[merkle_tree.as_ref()], &Bubblegum::id()).0,
+                merkle_tree,
+                log_wrapper: spl_account_compression::Noop::id(),
+                compression_program: spl_account_compression::ID,
+                bubblegum_program: Bubblegum::id(),
+                system_program: anchor_lang::system_program::ID,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::CloseAgent {
+                cnft,
+                proof_len: proof.len() as u8,
+            },
+        );
+        instruction
+            .accounts
+            .extend(proof.iter().map(|node| AccountMeta::new_readonly(*node, false)));
+        instruction
+    }
+
+    // Moves an agent on the original layout to the current one; anyone may
+    // pay for it
+    pub fn migrate_agent(payer: Pubkey, agent_data: Pubkey) -> Instruction {
+        build(
+            accounts::MigrateAgent {
+                payer,
+                agent_data,
+                registry_state: pda::find_registry_state_address().0,
+                system_program: anchor_lang::system_program::ID,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::MigrateAgent {},
+        )
+    }
+
+    // Moves a match on the original layout to the current one, once its
+    // first agent has been migrated
+    pub fn migrate_match(payer: Pubkey, match_data: Pubkey, agent_one: Pubkey) -> Instruction {
+        build(
+            accounts::MigrateMatch {
+                payer,
+                match_data,
+                agent_one,
+                system_program: anchor_lang::system_program::ID,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::MigrateMatch {},
+        )
+    }
+
+    // Any instruction from its Anchor account and argument structs
+    pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+}
@@ -1,30 +1,117 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program::{
+    allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Mint, Token, TokenAccount},
 };
-use mpl_bubblegum::state::{metaplex_adapter::MetadataArgs, TreeConfig};
+use mpl_bubblegum::state::{
+    metaplex_adapter::{Collection, MetadataArgs},
+    TreeConfig,
+};
 use spl_account_compression::{program::SplAccountCompression, Noop};
+use sessions::{SessionKey, SCOPE_AGENT_STATUS};
+use guardian::Guardian;
+use rate_limiter::{program::RateLimiter, RateLimitedAction};
+use referrals_cpi::{program::Referrals, Reporter, ValueEvent, REFERRAL_REPORTER_SEED};
+
+pub use armour_core::{
+    calculate_compatibility_score, calculate_compatibility_score_with, calculate_cosine_compatibility,
+    CompatibilityAlgorithm, CompatibilityWeights, InteractionType, Normalization, PersonalityTraits,
+    MAX_TRAIT_VALUE,
+};
 
 declare_id!("Ag3ntReg1strYpR0gRaMxXxXxXxXxXxXxXxXxXxXxX");
 
+// Relationship program, whose hook authority PDA signs `on_interaction` calls
+pub const RELATIONSHIP_PROGRAM_ID: Pubkey = solana_program::pubkey!("Re1ati0nsh1pPr0graMxXxXxXxXxXxXxXxXxXxXxX");
+// Breeding program, whose breeding authority PDA records offspring lineage
+pub const BREEDING_PROGRAM_ID: Pubkey = solana_program::pubkey!("Breed1ngPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+// Tournament program, whose tournament authority PDA records win/loss results
+pub const TOURNAMENT_PROGRAM_ID: Pubkey = solana_program::pubkey!("T0urnamentPr0graMxXxXxXxXxXxXxXxXxXxXxXxXx");
+// Quests program, notified of recorded matches through `on_match`
+pub const QUESTS_PROGRAM_ID: Pubkey = solana_program::pubkey!("Qu3stsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+pub const QUEST_HOOK_AUTHORITY_SEED: &[u8] = b"quest_hook_authority";
+// Profiles program, notified of registrations and transfers to keep linked agent counts
+pub const PROFILES_PROGRAM_ID: Pubkey = solana_program::pubkey!("Pr0f1lesPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+pub const PROFILE_HOOK_AUTHORITY_SEED: &[u8] = b"profile_hook_authority";
+// Analytics program, notified of registrations and matches for its epoch metrics
+pub const ANALYTICS_PROGRAM_ID: Pubkey = solana_program::pubkey!("Ana1yt1csPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+pub const ANALYTICS_HOOK_AUTHORITY_SEED: &[u8] = b"analytics_hook_authority";
+
+// Metaplex Token Metadata, which owns the collection NFT's metadata and edition
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+// PDA that must be the agent collection's update authority; signs collection mints
+pub const COLLECTION_AUTHORITY_SEED: &[u8] = b"collection_authority";
+
+const MAX_BATCH_REGISTRATIONS: usize = 16;
+
+// Limits on custom traits, per agent and in the compatibility weights
+pub const MAX_CUSTOM_TRAITS: usize = 16;
+pub const MAX_WEIGHTED_TRAITS: usize = 16;
+pub const MAX_TRAIT_KEY_LEN: usize = 32;
+
+pub const MAX_LEVELS: usize = 32;
+
+// Accounts created before the current layouts were allocated with these sizes
+pub const LEGACY_AGENT_SPACE: usize = 8 + std::mem::size_of::<LegacyAgentData>();
+pub const LEGACY_MATCH_SPACE: usize = 8 + std::mem::size_of::<LegacyMatchData>();
+
+// Minimum time between consecutive matches of the same pair, until the
+// admin sets another
+pub const DEFAULT_MATCH_COOLDOWN: i64 = 24 * 60 * 60;
+
+pub const MAX_QUEUE_LEN: usize = 16;
+
+// Queued agents one owner may have at a time
+pub const MAX_QUEUED_PER_OWNER: usize = 2;
+
+// How long an agent stays queued before `process_queue` or a new entrant
+// can evict it
+pub const QUEUE_ENTRY_TTL: i64 = 3 * 24 * 60 * 60;
+
+// How long a dissolved match stays open before its rent can be reclaimed
+pub const MATCH_CLOSE_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
+
+// How long a stored compatibility result can back a recorded match
+pub const COMPATIBILITY_RESULT_MAX_AGE: i64 = 60 * 60;
+
 #[program]
 pub mod agent_registry {
     use super::*;
 
-    // Create a new AI agent as a compressed NFT
-    pub fn register_agent(
-        ctx: Context<RegisterAgent>,
+    // Create a new AI agent as a compressed NFT. Passing the profile hook
+    // accounts as remaining accounts also links the agent to the owner's
+    // user profile.
+    pub fn register_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterAgent<'info>>,
         metadata_uri: String,
         name: String,
         personality_traits: PersonalityTraits,
     ) -> Result<()> {
+        rate_limiter::consume(
+            &ctx.accounts.rate_limiter_program.to_account_info(),
+            &ctx.accounts.rate_limiter_config,
+            &ctx.accounts.wallet_quota,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateLimitedAction::Registration,
+            1,
+        )?;
+        #[cfg(feature = "gated")]
+        attestation::require_credential(ctx.accounts.credential.as_deref(), &ctx.accounts.owner.key())?;
+
         // Generate metadata for the compressed NFT
         let metadata = generate_agent_metadata(
             &ctx.accounts.owner.key(),
             &name,
             &metadata_uri,
             &personality_traits,
+            ctx.accounts.collection.collection_mint.key(),
         )?;
 
         // Store agent data in program state
@@ -38,29 +125,169 @@ pub mod agent_registry {
         agent_data.match_count = 0;
         agent_data.interaction_count = 0;
         agent_data.last_active = Clock::get()?.unix_timestamp;
+        agent_data.is_frozen = false;
+        agent_data.parent_one = Pubkey::default();
+        agent_data.parent_two = Pubkey::default();
+        agent_data.generation = 0;
+        agent_data.wins = 0;
+        agent_data.losses = 0;
+        agent_data.delegate = None;
+        agent_data.transfer_nonce = 0;
+        agent_data.agent_index = ctx.accounts.registry_state.next_agent_index();
+
+        let owner = agent_data.owner;
+        notify_profile_hook(ctx.remaining_accounts, "on_agent_registered", &[owner])?;
+        notify_analytics_hook(
+            &ctx.accounts.analytics_program,
+            &ctx.accounts.analytics_hook_authority,
+            *ctx.bumps.get("analytics_hook_authority").unwrap(),
+            &ctx.accounts.analytics_state,
+            "on_agent_registered",
+        )?;
+        if let (Some(reporter), Some(config), Some(referral), Some(referrer_rewards), Some(referrals_program)) = (
+            &ctx.accounts.referral_reporter,
+            &ctx.accounts.referrals_config,
+            &ctx.accounts.referral,
+            &ctx.accounts.referrer_rewards,
+            &ctx.accounts.referrals_program,
+        ) {
+            referrals_cpi::report_value_event(
+                &crate::ID,
+                &Reporter {
+                    reporter,
+                    config,
+                    referrals_program,
+                },
+                &owner,
+                referral,
+                referrer_rewards,
+                ValueEvent::Registration,
+            )?;
+        }
 
         // Mint compressed NFT using Bubblegum
-        mint_agent_cnft(ctx, metadata)?;
+        mint_agent_cnft(
+            &ctx.accounts.owner,
+            &ctx.accounts.tree_authority,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.bubblegum_program,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.system_program,
+            &ctx.accounts.collection,
+            metadata,
+        )?;
 
         emit!(AgentRegistered {
             agent_id: agent_data.key(),
             owner: agent_data.owner,
             name: agent_data.name.clone(),
+            agent_index: agent_data.agent_index,
         });
 
         Ok(())
     }
 
-    // Update an AI agent's status (active/inactive)
+    // Register several agents for the signer in one transaction, minting a
+    // cNFT for each. Remaining accounts are the new AgentData PDAs, one per
+    // registration in order, optionally followed by the profile hook
+    // accounts. The whole batch counts against the owner's registration quota.
+    pub fn register_agents_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterAgentsBatch<'info>>,
+        registrations: Vec<AgentRegistration>,
+    ) -> Result<()> {
+        let count = registrations.len();
+        require!(
+            count > 0 && count <= MAX_BATCH_REGISTRATIONS,
+            AgentError::BatchTooLarge
+        );
+        require!(
+            ctx.remaining_accounts.len() >= count,
+            AgentError::BatchAccountsMismatch
+        );
+        let (agent_accounts, hook_accounts) = ctx.remaining_accounts.split_at(count);
+
+        rate_limiter::consume(
+            &ctx.accounts.rate_limiter_program.to_account_info(),
+            &ctx.accounts.rate_limiter_config,
+            &ctx.accounts.wallet_quota,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateLimitedAction::Registration,
+            count as u16,
+        )?;
+        #[cfg(feature = "gated")]
+        attestation::require_credential(ctx.accounts.credential.as_deref(), &ctx.accounts.owner.key())?;
+
+        let owner = ctx.accounts.owner.key();
+        let now = Clock::get()?.unix_timestamp;
+        for (registration, agent_account) in registrations.into_iter().zip(agent_accounts) {
+            let metadata = generate_agent_metadata(
+                &owner,
+                &registration.name,
+                &registration.metadata_uri,
+                &registration.personality_traits,
+                ctx.accounts.collection.collection_mint.key(),
+            )?;
+            let agent_index = ctx.accounts.registry_state.next_agent_index();
+            create_agent_account(
+                &ctx.accounts.owner,
+                agent_account,
+                &ctx.accounts.system_program,
+                registration.name.clone(),
+                registration.metadata_uri,
+                registration.personality_traits,
+                agent_index,
+                now,
+            )?;
+
+            notify_profile_hook(hook_accounts, "on_agent_registered", &[owner])?;
+            notify_analytics_hook(
+                &ctx.accounts.analytics_program,
+                &ctx.accounts.analytics_hook_authority,
+                *ctx.bumps.get("analytics_hook_authority").unwrap(),
+                &ctx.accounts.analytics_state,
+                "on_agent_registered",
+            )?;
+            mint_agent_cnft(
+                &ctx.accounts.owner,
+                &ctx.accounts.tree_authority,
+                &ctx.accounts.merkle_tree,
+                &ctx.accounts.bubblegum_program,
+                &ctx.accounts.compression_program,
+                &ctx.accounts.system_program,
+                &ctx.accounts.collection,
+                metadata,
+            )?;
+
+            emit!(AgentRegistered {
+                agent_id: agent_account.key(),
+                owner,
+                name: registration.name,
+                agent_index,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Update an AI agent's status (active/inactive). A session key with the
+    // agent status scope, or the agent's delegate, may sign in place of the
+    // owner.
     pub fn update_agent_status(
         ctx: Context<UpdateAgent>,
         is_active: bool,
     ) -> Result<()> {
+        let signer = ctx.accounts.owner.key();
+        let owner = sessions::acting_owner(
+            &ctx.accounts.session,
+            &signer,
+            SCOPE_AGENT_STATUS,
+        )?;
         let agent_data = &mut ctx.accounts.agent_data;
 
-        // Only the owner can update status
+        // Only the owner or its delegate can update status
         require!(
-            agent_data.owner == ctx.accounts.owner.key(),
+            agent_data.owner == owner || agent_data.is_delegate(&signer),
             AgentError::Unauthorized
         );
 
@@ -87,6 +314,7 @@ pub mod agent_registry {
             agent_data.owner == ctx.accounts.owner.key(),
             AgentError::Unauthorized
         );
+        validate_traits(&personality_traits)?;
 
         agent_data.personality_traits = personality_traits;
         agent_data.last_active = Clock::get()?.unix_timestamp;
@@ -102,33 +330,306 @@ pub mod agent_registry {
         Ok(())
     }
 
-    // Calculate compatibility between two agents
+    // Add a game-specific trait alongside the eight personality traits. The
+    // owner pays for the extra account space.
+    pub fn add_custom_trait(ctx: Context<UpdateCustomTraits>, key: String, value: u8) -> Result<()> {
+        validate_custom_trait(&key, value)?;
+        let agent_data = &mut ctx.accounts.agent_data;
+        require!(
+            agent_data.custom_traits.len() < MAX_CUSTOM_TRAITS,
+            AgentError::TooManyCustomTraits
+        );
+        require!(
+            agent_data.custom_trait(&key).is_none(),
+            AgentError::CustomTraitExists
+        );
+
+        agent_data.custom_traits.push(TraitEntry { key: key.clone(), value });
+        agent_data.last_active = Clock::get()?.unix_timestamp;
+        resize_agent_account(
+            agent_data,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        emit!(CustomTraitUpdated {
+            agent_id: agent_data.key(),
+            key,
+            value: Some(value),
+        });
+
+        Ok(())
+    }
+
+    pub fn update_custom_trait(ctx: Context<UpdateCustomTraits>, key: String, value: u8) -> Result<()> {
+        validate_custom_trait(&key, value)?;
+        let agent_data = &mut ctx.accounts.agent_data;
+        let entry = agent_data
+            .custom_traits
+            .iter_mut()
+            .find(|entry| entry.key == key)
+            .ok_or(AgentError::CustomTraitNotFound)?;
+
+        entry.value = value;
+        agent_data.last_active = Clock::get()?.unix_timestamp;
+
+        emit!(CustomTraitUpdated {
+            agent_id: agent_data.key(),
+            key,
+            value: Some(value),
+        });
+
+        Ok(())
+    }
+
+    // Remove a custom trait, refunding the rent of the space it used
+    pub fn remove_custom_trait(ctx: Context<UpdateCustomTraits>, key: String) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        let position = agent_data
+            .custom_traits
+            .iter()
+            .position(|entry| entry.key == key)
+            .ok_or(AgentError::CustomTraitNotFound)?;
+
+        agent_data.custom_traits.remove(position);
+        agent_data.last_active = Clock::get()?.unix_timestamp;
+        resize_agent_account(
+            agent_data,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        emit!(CustomTraitUpdated {
+            agent_id: agent_data.key(),
+            key,
+            value: None,
+        });
+
+        Ok(())
+    }
+
+    // Record activity that happened off-chain (owner or delegate): refreshes
+    // `last_active` and adds `interactions` to the agent's interaction count
+    pub fn record_agent_activity(
+        ctx: Context<RecordAgentActivity>,
+        interactions: u32,
+    ) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            agent_data.owner == signer || agent_data.is_delegate(&signer),
+            AgentError::Unauthorized
+        );
+
+        agent_data.interaction_count = agent_data.interaction_count.saturating_add(interactions);
+        agent_data.last_active = Clock::get()?.unix_timestamp;
+
+        emit!(AgentActivityRecorded {
+            agent_id: agent_data.key(),
+            signer,
+            interactions,
+            interaction_count: agent_data.interaction_count,
+        });
+
+        Ok(())
+    }
+
+    // Grant an agent experience, levelling it up as it crosses the
+    // configured thresholds. Signed by the XP authority (the game backend).
+    pub fn grant_xp(ctx: Context<GrantXp>, amount: u64) -> Result<()> {
+        let level_config = &ctx.accounts.level_config;
+        let agent_data = &mut ctx.accounts.agent_data;
+
+        award_xp(agent_data, level_config, amount);
+
+        emit!(AgentXpGranted {
+            agent_id: agent_data.key(),
+            signer: ctx.accounts.xp_authority.key(),
+            amount,
+            xp: agent_data.xp,
+        });
+
+        Ok(())
+    }
+
+    // Let another key, such as an AI backend, update the agent's status and
+    // activity. The delegate cannot transfer, close or otherwise change the
+    // agent, and is cleared when the agent changes owner.
+    pub fn set_delegate(ctx: Context<SetDelegate>, delegate: Pubkey) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        agent_data.delegate = Some(delegate);
+
+        emit!(AgentDelegateUpdated {
+            agent_id: agent_data.key(),
+            delegate: Some(delegate),
+        });
+
+        Ok(())
+    }
+
+    // Remove the agent's delegate
+    pub fn revoke_delegate(ctx: Context<SetDelegate>) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        agent_data.delegate = None;
+
+        emit!(AgentDelegateUpdated {
+            agent_id: agent_data.key(),
+            delegate: None,
+        });
+
+        Ok(())
+    }
+
+    // Calculate compatibility between two agents with the algorithm of
+    // `algorithm_version` (see CompatibilityAlgorithm)
     pub fn calculate_compatibility(
         ctx: Context<CalculateCompatibility>,
+        algorithm_version: u8,
     ) -> Result<u8> {
+        let algorithm = compatibility_algorithm(algorithm_version)?;
         let agent_one = &ctx.accounts.agent_one;
         let agent_two = &ctx.accounts.agent_two;
 
-        // Calculate compatibility score based on personality traits
-        let score = calculate_compatibility_score(
-            &agent_one.personality_traits,
-            &agent_two.personality_traits,
-        )?;
+        // Personality traits under the configured weights, blended with
+        // shared custom traits when the trait weights are given
+        let score = calculate_weighted_compatibility(
+            agent_one,
+            agent_two,
+            algorithm,
+            &ctx.accounts.compatibility_config.weights,
+            ctx.accounts.trait_weights.as_deref(),
+        );
+
+        emit!(CompatibilityCalculated {
+            agent_one: agent_one.key(),
+            agent_two: agent_two.key(),
+            score,
+            algorithm_version,
+        });
+
+        Ok(score)
+    }
+
+    // Calculate compatibility like `calculate_compatibility` and store it in
+    // the pair's CompatibilityResult (permissionless), which `accept_match`
+    // and other instructions can trust. Agents are given in canonical order.
+    pub fn store_compatibility(
+        ctx: Context<StoreCompatibility>,
+        algorithm_version: u8,
+    ) -> Result<u8> {
+        let algorithm = compatibility_algorithm(algorithm_version)?;
+        let agent_one = &ctx.accounts.agent_one;
+        let agent_two = &ctx.accounts.agent_two;
+
+        let score = calculate_weighted_compatibility(
+            agent_one,
+            agent_two,
+            algorithm,
+            &ctx.accounts.compatibility_config.weights,
+            ctx.accounts.trait_weights.as_deref(),
+        );
+
+        let result = &mut ctx.accounts.compatibility_result;
+        result.agent_one = agent_one.key();
+        result.agent_two = agent_two.key();
+        result.score = score;
+        result.algorithm_version = algorithm_version;
+        result.computed_at = Clock::get()?.unix_timestamp;
+        result.bump = *ctx.bumps.get("compatibility_result").unwrap();
 
         emit!(CompatibilityCalculated {
             agent_one: agent_one.key(),
             agent_two: agent_two.key(),
             score,
+            algorithm_version,
         });
 
         Ok(score)
     }
 
-    // Record a match between two agents
-    pub fn record_match(
-        ctx: Context<RecordMatch>,
-        compatibility_score: u8,
+    // Propose a match from agent one to agent two (agent one's owner or
+    // delegate). The match is recorded once agent two accepts.
+    pub fn propose_match(ctx: Context<ProposeMatch>) -> Result<()> {
+        let agent_one = &ctx.accounts.agent_one;
+        let agent_two = &ctx.accounts.agent_two;
+        let proposer = ctx.accounts.proposer.key();
+        require!(agent_one.is_owner_or_delegate(&proposer), AgentError::Unauthorized);
+        require!(agent_one.key() != agent_two.key(), AgentError::InvalidMatch);
+        require!(agent_one.is_active && agent_two.is_active, AgentError::AgentInactive);
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.agent_one = agent_one.key();
+        proposal.agent_two = agent_two.key();
+        proposal.proposer = proposer;
+        proposal.proposed_at = Clock::get()?.unix_timestamp;
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+        emit!(AgentMatchProposed {
+            proposal: proposal.key(),
+            agent_one: proposal.agent_one,
+            agent_two: proposal.agent_two,
+            proposer,
+        });
+
+        Ok(())
+    }
+
+    // Accept a match proposal (agent two's owner or delegate), recording the
+    // match scored by the pair's CompatibilityResult, which must have been
+    // stored within COMPATIBILITY_RESULT_MAX_AGE. The proposal's rent goes
+    // back to the proposer. Passing the quest hook accounts as remaining
+    // accounts also advances both agents' match quests.
+    pub fn accept_match<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptMatch<'info>>,
     ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_two.is_owner_or_delegate(&ctx.accounts.authority.key()),
+            AgentError::Unauthorized
+        );
+        let compatibility_result = &ctx.accounts.compatibility_result;
+        require!(
+            compatibility_result.is_for(&ctx.accounts.agent_one.key(), &ctx.accounts.agent_two.key()),
+            AgentError::CompatibilityResultMismatch
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time.saturating_sub(compatibility_result.computed_at) <= COMPATIBILITY_RESULT_MAX_AGE,
+            AgentError::StaleCompatibilityResult
+        );
+        let compatibility_score = compatibility_result.score;
+        let algorithm_version = compatibility_result.algorithm_version;
+        // Either agent may have been deactivated or frozen since the proposal
+        for agent in [&ctx.accounts.agent_one, &ctx.accounts.agent_two] {
+            require!(agent.is_active && !agent.is_frozen, AgentError::AgentInactive);
+        }
+
+        let pair_state = &mut ctx.accounts.pair_state;
+        require!(!pair_state.has_active_match, AgentError::PairAlreadyMatched);
+        require!(
+            pair_state.match_nonce == 0
+                || current_time
+                    >= pair_state.last_matched_at.saturating_add(ctx.accounts.registry_config.match_cooldown),
+            AgentError::MatchCooldown
+        );
+        let match_nonce = pair_state.match_nonce;
+        let (lower, higher) = canonical_pair(ctx.accounts.agent_one.key(), ctx.accounts.agent_two.key());
+        pair_state.agent_one = lower;
+        pair_state.agent_two = higher;
+        pair_state.match_nonce += 1;
+        pair_state.last_matched_at = current_time;
+        pair_state.has_active_match = true;
+        pair_state.bump = *ctx.bumps.get("pair_state").unwrap();
+
+        rate_limiter::consume(
+            &ctx.accounts.rate_limiter_program.to_account_info(),
+            &ctx.accounts.rate_limiter_config,
+            &ctx.accounts.wallet_quota,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateLimitedAction::Match,
+            1,
+        )?;
+
         let agent_one = &mut ctx.accounts.agent_one;
         let agent_two = &mut ctx.accounts.agent_two;
 
@@ -137,10 +638,14 @@ pub mod agent_registry {
         agent_two.match_count = agent_two.match_count.checked_add(1).unwrap_or(u32::MAX);
 
         // Update last active timestamp
-        let current_time = Clock::get()?.unix_timestamp;
         agent_one.last_active = current_time;
         agent_two.last_active = current_time;
 
+        if let Some(level_config) = &ctx.accounts.level_config {
+            award_xp(agent_one, level_config, level_config.match_xp);
+            award_xp(agent_two, level_config, level_config.match_xp);
+        }
+
         // Record the match in program state
         let match_data = &mut ctx.accounts.match_data;
         match_data.agent_one = agent_one.key();
@@ -148,156 +653,2263 @@ pub mod agent_registry {
         match_data.compatibility_score = compatibility_score;
         match_data.match_date = current_time;
         match_data.is_active = true;
+        match_data.algorithm_version = algorithm_version;
+        match_data.rent_payer = ctx.accounts.authority.key();
+        match_data.match_nonce = match_nonce;
 
         emit!(AgentMatchRecorded {
             match_id: match_data.key(),
             agent_one: agent_one.key(),
             agent_two: agent_two.key(),
             compatibility_score,
+            algorithm_version,
         });
 
+        notify_quest_hook(ctx.remaining_accounts, agent_one.key(), agent_two.key())?;
+        notify_analytics_hook(
+            &ctx.accounts.analytics_program,
+            &ctx.accounts.analytics_hook_authority,
+            *ctx.bumps.get("analytics_hook_authority").unwrap(),
+            &ctx.accounts.analytics_state,
+            "on_match",
+        )?;
+        if let (Some(reporter), Some(config), Some(referrals_program)) = (
+            &ctx.accounts.referral_reporter,
+            &ctx.accounts.referrals_config,
+            &ctx.accounts.referrals_program,
+        ) {
+            let reporter = Reporter {
+                reporter,
+                config,
+                referrals_program,
+            };
+            for (referee, referral, referrer_rewards) in [
+                (ctx.accounts.agent_one.owner, &ctx.accounts.referral_one, &ctx.accounts.referrer_rewards_one),
+                (ctx.accounts.agent_two.owner, &ctx.accounts.referral_two, &ctx.accounts.referrer_rewards_two),
+            ] {
+                if let (Some(referral), Some(referrer_rewards)) = (referral, referrer_rewards) {
+                    referrals_cpi::report_value_event(
+                        &crate::ID,
+                        &reporter,
+                        &referee,
+                        referral,
+                        referrer_rewards,
+                        ValueEvent::FirstMatch,
+                    )?;
+                }
+            }
+        }
+
         Ok(())
     }
 
-    // Transfer ownership of an agent to a new owner
-    pub fn transfer_ownership(
-        ctx: Context<TransferOwnership>,
-        new_owner: Pubkey,
-    ) -> Result<()> {
-        let agent_data = &mut ctx.accounts.agent_data;
-
-        // Only the current owner can transfer ownership
+    // Turn down a match proposal (agent two's owner or delegate), or withdraw
+    // it (agent one's owner or delegate). The proposal's rent goes back to
+    // the proposer.
+    pub fn decline_match(ctx: Context<DeclineMatch>) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
         require!(
-            agent_data.owner == ctx.accounts.owner.key(),
+            ctx.accounts.agent_one.is_owner_or_delegate(&signer)
+                || ctx.accounts.agent_two.is_owner_or_delegate(&signer),
             AgentError::Unauthorized
         );
 
-        // Update owner
-        let previous_owner = agent_data.owner;
-        agent_data.owner = new_owner;
-        agent_data.last_active = Clock::get()?.unix_timestamp;
-
-        emit!(AgentOwnershipTransferred {
-            agent_id: agent_data.key(),
-            previous_owner,
-            new_owner,
+        let proposal = &ctx.accounts.proposal;
+        emit!(AgentMatchDeclined {
+            proposal: proposal.key(),
+            agent_one: proposal.agent_one,
+            agent_two: proposal.agent_two,
+            declined_by: signer,
         });
 
         Ok(())
     }
-}
 
-// Account structures
-#[account]
-pub struct AgentData {
-    pub owner: Pubkey,
-    pub name: String,
-    pub metadata_uri: String,
-    pub is_active: bool,
-    pub personality_traits: PersonalityTraits,
-    pub creation_date: i64,
-    pub match_count: u32,
-    pub interaction_count: u32,
-    pub last_active: i64,
-}
+    // End an active match (either agent's owner or delegate). The match
+    // account stays readable for MATCH_CLOSE_GRACE_PERIOD, after which
+    // `close_match` can reclaim its rent.
+    pub fn dissolve_match(ctx: Context<DissolveMatch>, reason: MatchEndReason) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        require!(
+            ctx.accounts.agent_one.is_owner_or_delegate(&signer)
+                || ctx.accounts.agent_two.is_owner_or_delegate(&signer),
+            AgentError::Unauthorized
+        );
 
-#[account]
-pub struct MatchData {
-    pub agent_one: Pubkey,
-    pub agent_two: Pubkey,
-    pub compatibility_score: u8,
-    pub match_date: i64,
-    pub is_active: bool,
-}
+        let match_data = &mut ctx.accounts.match_data;
+        require!(match_data.is_active, AgentError::MatchNotActive);
+        match_data.is_active = false;
+        ctx.accounts.pair_state.has_active_match = false;
+        match_data.ended_at = Clock::get()?.unix_timestamp;
+        match_data.end_reason = Some(reason);
 
-// Context structs for instructions
-#[derive(Accounts)]
-pub struct RegisterAgent<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+        emit!(AgentMatchDissolved {
+            match_id: match_data.key(),
+            agent_one: match_data.agent_one,
+            agent_two: match_data.agent_two,
+            reason,
+            dissolved_by: signer,
+        });
 
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + std::mem::size_of::<AgentData>(),
-        seeds = [b"agent", owner.key().as_ref(), name.as_bytes()],
-        bump
-    )]
-    pub agent_data: Account<'info, AgentData>,
+        Ok(())
+    }
 
-    // Accounts needed for minting compressed NFT
-    pub tree_authority: AccountInfo<'info>,
-    #[account(mut)]
-    pub merkle_tree: AccountInfo<'info>,
-    pub bubblegum_program: Program<'info, Bubblegum>,
-    pub compression_program: Program<'info, SplAccountCompression>,
-    pub system_program: Program<'info, System>,
+    // Close a dissolved match once its grace period has passed
+    // (permissionless), returning the rent to whoever paid for it. Both
+    // agents count the closed match so their live matches can be reconciled.
+    pub fn close_match(ctx: Context<CloseMatch>) -> Result<()> {
+        let match_data = &ctx.accounts.match_data;
+        require!(!match_data.is_active, AgentError::MatchNotDissolved);
+        require!(
+            Clock::get()?.unix_timestamp >= match_data.ended_at.saturating_add(MATCH_CLOSE_GRACE_PERIOD),
+            AgentError::MatchGracePeriod
+        );
 
-    // Additional accounts may be needed based on Bubblegum implementation
-}
+        let agent_one = &mut ctx.accounts.agent_one;
+        let agent_two = &mut ctx.accounts.agent_two;
+        agent_one.closed_match_count = agent_one.closed_match_count.checked_add(1).unwrap_or(u32::MAX);
+        agent_two.closed_match_count = agent_two.closed_match_count.checked_add(1).unwrap_or(u32::MAX);
 
-#[derive(Accounts)]
-pub struct UpdateAgent<'info> {
-    pub owner: Signer<'info>,
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub agent_data: Account<'info, AgentData>,
-}
+    // Create the matchmaking queue (registry admin)
+    pub fn initialize_match_queue(ctx: Context<InitializeMatchQueue>) -> Result<()> {
+        let match_queue = &mut ctx.accounts.match_queue;
+        match_queue.entries = Vec::new();
+        match_queue.bump = *ctx.bumps.get("match_queue").unwrap();
 
-#[derive(Accounts)]
-pub struct CalculateCompatibility<'info> {
-    pub agent_one: Account<'info, AgentData>,
-    pub agent_two: Account<'info, AgentData>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct RecordMatch<'info> {
-    pub authority: Signer<'info>,
+    // Queue an agent for matchmaking (owner or delegate). Entering the queue
+    // consents to a match with any queued agent that meets `preferences`
+    // and whose own preferences this agent meets. An owner may have at most
+    // MAX_QUEUED_PER_OWNER agents queued; entries older than QUEUE_ENTRY_TTL
+    // are evicted first, so a full queue of stale entries does not turn the
+    // agent away.
+    pub fn enter_queue(ctx: Context<EnterQueue>, preferences: QueuePreferences) -> Result<()> {
+        let agent_data = &ctx.accounts.agent_data;
+        let signer = ctx.accounts.signer.key();
+        require!(agent_data.is_owner_or_delegate(&signer), AgentError::Unauthorized);
+        require!(agent_data.is_active && !agent_data.is_frozen, AgentError::AgentInactive);
+        require!(preferences.min_level <= preferences.max_level, AgentError::InvalidQueuePreferences);
+
+        let now = Clock::get()?.unix_timestamp;
+        let match_queue = &mut ctx.accounts.match_queue;
+        require!(match_queue.position(&agent_data.key()).is_none(), AgentError::AlreadyQueued);
+        for entry in match_queue.evict_expired(now) {
+            emit!(AgentLeftQueue { agent_id: entry.agent });
+        }
+        require!(match_queue.entries.len() < MAX_QUEUE_LEN, AgentError::QueueFull);
+        require!(
+            match_queue.queued_by(&agent_data.owner) < MAX_QUEUED_PER_OWNER,
+            AgentError::OwnerQueueLimit
+        );
+        match_queue.entries.push(QueueEntry {
+            agent: agent_data.key(),
+            preferences: preferences.clone(),
+            entered_at: now,
+            owner: agent_data.owner,
+        });
 
-    #[account(mut)]
-    pub agent_one: Account<'info, AgentData>,
+        emit!(AgentQueued {
+            agent_id: agent_data.key(),
+            preferences,
+        });
 
-    #[account(mut)]
-    pub agent_two: Account<'info, AgentData>,
+        Ok(())
+    }
 
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<MatchData>(),
-        seeds = [b"match", agent_one.key().as_ref(), agent_two.key().as_ref()],
-        bump
-    )]
-    pub match_data: Account<'info, MatchData>,
+    // Withdraw an agent from the matchmaking queue (owner or delegate)
+    pub fn leave_queue(ctx: Context<LeaveQueue>) -> Result<()> {
+        let agent_data = &ctx.accounts.agent_data;
+        require!(
+            agent_data.is_owner_or_delegate(&ctx.accounts.signer.key()),
+            AgentError::Unauthorized
+        );
 
-    pub system_program: Program<'info, System>,
-}
+        let match_queue = &mut ctx.accounts.match_queue;
+        let position = match_queue.position(&agent_data.key()).ok_or(AgentError::NotQueued)?;
+        match_queue.entries.remove(position);
 
-#[derive(Accounts)]
+        emit!(AgentLeftQueue {
+            agent_id: agent_data.key(),
+        });
+
+        Ok(())
+    }
+
+    // Match the most compatible eligible pair in the queue (permissionless
+    // crank), scoring with the heuristic algorithm under the compatibility
+    // config. Pairs are tried best first: one that already has an active
+    // match or is inside its re-match cooldown is skipped with
+    // QueuePairSkipped, its agents staying queued, and the first other pair
+    // is matched. Remaining accounts are every queued agent's AgentData in
+    // queue order, then the PairState of each pair tried, then the MatchData
+    // PDA of the pair matched, all writable; clients rank the pairs with
+    // `calculate_weighted_compatibility` and check their pair states. The
+    // crank pays the rent of new accounts and is the match's rent payer.
+    // Agents that no longer exist, are inactive or frozen, or were queued
+    // longer than QUEUE_ENTRY_TTL ago are dropped from the queue. Queue
+    // matches do not notify the quests or analytics hooks.
+    pub fn process_queue<'info>(ctx: Context<'_, '_, '_, 'info, ProcessQueue<'info>>) -> Result<()> {
+        let entries = ctx.accounts.match_queue.entries.clone();
+        require!(
+            ctx.remaining_accounts.len() >= entries.len(),
+            AgentError::QueueAccountsMismatch
+        );
+        let (agent_accounts, pair_accounts) = ctx.remaining_accounts.split_at(entries.len());
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut agents = Vec::with_capacity(entries.len());
+        let mut dropped = Vec::new();
+        for (index, (entry, account)) in entries.iter().zip(agent_accounts).enumerate() {
+            require_keys_eq!(account.key(), entry.agent, AgentError::QueueAccountsMismatch);
+            let agent = Account::<AgentData>::try_from(account)
+                .ok()
+                .filter(|agent| agent.is_active && !agent.is_frozen && !entry.is_expired(now));
+            if agent.is_none() {
+                dropped.push(index);
+            }
+            agents.push(agent);
+        }
+
+        let weights = &ctx.accounts.compatibility_config.weights;
+        let trait_weights = ctx.accounts.trait_weights.as_deref();
+        let mut candidates = Vec::new();
+        for i in 0..entries.len() {
+            for j in i + 1..entries.len() {
+                let (Some(one), Some(two)) = (&agents[i], &agents[j]) else { continue };
+                if !entries[i].preferences.accepts_level(two.level)
+                    || !entries[j].preferences.accepts_level(one.level)
+                {
+                    continue;
+                }
+                let score = calculate_weighted_compatibility(
+                    one,
+                    two,
+                    CompatibilityAlgorithm::Heuristic,
+                    weights,
+                    trait_weights,
+                );
+                if score < entries[i].preferences.min_score || score < entries[j].preferences.min_score {
+                    continue;
+                }
+                candidates.push((i, j, score));
+            }
+        }
+        // Best first; the stable sort keeps earlier-queued pairs first on ties
+        candidates.sort_by(|a, b| b.2.cmp(&a.2));
+
+        for (i, j, compatibility_score) in candidates {
+            let matched = record_queue_match(
+                &ctx.accounts.payer,
+                &ctx.accounts.system_program,
+                pair_accounts,
+                &entries[i].agent,
+                &entries[j].agent,
+                ctx.accounts.registry_config.match_cooldown,
+                compatibility_score,
+                now,
+            )?;
+            if matched {
+                for index in [i, j] {
+                    let agent = agents[index].as_mut().unwrap();
+                    agent.match_count = agent.match_count.checked_add(1).unwrap_or(u32::MAX);
+                    agent.last_active = now;
+                    if let Some(level_config) = &ctx.accounts.level_config {
+                        award_xp(agent, level_config, level_config.match_xp);
+                    }
+                    agent.exit(&crate::ID)?;
+                }
+                dropped.extend([i, j]);
+                break;
+            }
+        }
+
+        dropped.sort_unstable();
+        dropped.dedup();
+        let match_queue = &mut ctx.accounts.match_queue;
+        for index in dropped.into_iter().rev() {
+            let entry = match_queue.entries.remove(index);
+            emit!(AgentLeftQueue { agent_id: entry.agent });
+        }
+
+        Ok(())
+    }
+
+    // Transfer ownership of an agent to a new owner in one step, signed by
+    // both. Programs holding agents in escrow (marketplace, auction, bridge)
+    // use this, signing for their escrow accounts; an agent held by a
+    // program-owned account can be released to a new owner who does not
+    // sign, as only that program can sign for the account. Passing the
+    // profile hook accounts as remaining accounts also moves the agent
+    // between the two owners' user profiles.
+    pub fn transfer_ownership<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferOwnership<'info>>,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.to_account_info();
+        let new_owner = ctx.accounts.new_owner.key();
+        require!(
+            ctx.accounts.new_owner.is_signer || *owner.owner != anchor_lang::system_program::ID,
+            AgentError::NewOwnerMustSign
+        );
+        require!(new_owner != owner.key(), AgentError::InvalidTransfer);
+
+        let agent_data = &mut ctx.accounts.agent_data;
+        let previous_owner = agent_data.owner;
+        agent_data.set_owner(new_owner);
+        agent_data.last_active = Clock::get()?.unix_timestamp;
+
+        emit!(AgentOwnershipTransferred {
+            agent_id: agent_data.key(),
+            previous_owner,
+            new_owner,
+        });
+
+        notify_profile_hook(
+            ctx.remaining_accounts,
+            "on_agent_transferred",
+            &[previous_owner, new_owner],
+        )?;
+
+        Ok(())
+    }
+
+    // Offer an agent to a new owner, who takes it with `accept_ownership`.
+    // Only one transfer can be pending per agent; cancel it to propose another.
+    pub fn propose_ownership_transfer(
+        ctx: Context<ProposeOwnershipTransfer>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        require!(
+            new_owner != ctx.accounts.owner.key(),
+            AgentError::InvalidTransfer
+        );
+
+        let pending_transfer = &mut ctx.accounts.pending_transfer;
+        pending_transfer.agent = ctx.accounts.agent_data.key();
+        pending_transfer.owner = ctx.accounts.owner.key();
+        pending_transfer.new_owner = new_owner;
+        pending_transfer.proposed_at = Clock::get()?.unix_timestamp;
+        pending_transfer.transfer_nonce = ctx.accounts.agent_data.transfer_nonce;
+        pending_transfer.bump = *ctx.bumps.get("pending_transfer").unwrap();
+
+        emit!(OwnershipTransferProposed {
+            agent_id: pending_transfer.agent,
+            owner: pending_transfer.owner,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    // Take ownership of an agent offered to the signer. The pending transfer
+    // is closed and its rent returned to the proposer. Passing the profile
+    // hook accounts as remaining accounts also moves the agent between the
+    // two owners' user profiles.
+    pub fn accept_ownership<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptOwnership<'info>>,
+    ) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+
+        // The agent may have changed hands since the proposal, even if it
+        // has come back to the proposer
+        let pending_transfer = &ctx.accounts.pending_transfer;
+        require!(
+            agent_data.owner == pending_transfer.owner
+                && agent_data.transfer_nonce == pending_transfer.transfer_nonce,
+            AgentError::StaleTransfer
+        );
+
+        let previous_owner = agent_data.owner;
+        let new_owner = ctx.accounts.new_owner.key();
+        agent_data.set_owner(new_owner);
+        agent_data.last_active = Clock::get()?.unix_timestamp;
+
+        emit!(AgentOwnershipTransferred {
+            agent_id: agent_data.key(),
+            previous_owner,
+            new_owner,
+        });
+
+        notify_profile_hook(
+            ctx.remaining_accounts,
+            "on_agent_transferred",
+            &[previous_owner, new_owner],
+        )?;
+
+        Ok(())
+    }
+
+    // Withdraw a pending transfer (the proposer, the proposed owner to
+    // decline it, or the agent's current owner to clear a stale one). Rent
+    // goes back to the proposer.
+    pub fn cancel_ownership_transfer(ctx: Context<CancelOwnershipTransfer>) -> Result<()> {
+        let pending_transfer = &ctx.accounts.pending_transfer;
+        let signer = ctx.accounts.signer.key();
+        // A closed agent no longer has a current owner
+        let current_owner = Account::<AgentData>::try_from(&ctx.accounts.agent_data)
+            .map(|agent_data| agent_data.owner)
+            .ok();
+        require!(
+            signer == pending_transfer.owner
+                || signer == pending_transfer.new_owner
+                || Some(signer) == current_owner,
+            AgentError::Unauthorized
+        );
+
+        emit!(OwnershipTransferCancelled {
+            agent_id: pending_transfer.agent,
+            owner: pending_transfer.owner,
+            new_owner: pending_transfer.new_owner,
+            cancelled_by: signer,
+        });
+
+        Ok(())
+    }
+
+    // Permanently retire an agent: burn its cNFT, which the owner must hold,
+    // close its AgentData and refund the rent to the owner. `cnft` and the
+    // proof accounts describe the leaf; pass the proof as remaining accounts,
+    // followed by the profile hook accounts to unlink the agent from the
+    // owner's user profile.
+    pub fn close_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseAgent<'info>>,
+        cnft: CnftArgs,
+        proof_len: u8,
+    ) -> Result<()> {
+        let agent_data = &ctx.accounts.agent_data;
+        require!(!agent_data.is_frozen, AgentError::AgentFrozen);
+        let proof_len = proof_len as usize;
+        require!(
+            ctx.remaining_accounts.len() >= proof_len,
+            AgentError::InvalidProof
+        );
+        let (proof, hook_accounts) = ctx.remaining_accounts.split_at(proof_len);
+
+        let cpi_accounts = mpl_bubblegum::accounts::Burn {
+            tree_authority: ctx.accounts.tree_authority.to_account_info(),
+            leaf_owner: ctx.accounts.owner.to_account_info(),
+            leaf_delegate: ctx.accounts.owner.to_account_info(),
+            merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+            log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+            compression_program: ctx.accounts.compression_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        mpl_bubblegum::cpi::burn(
+            CpiContext::new(ctx.accounts.bubblegum_program.to_account_info(), cpi_accounts)
+                .with_remaining_accounts(proof.to_vec()),
+            cnft.root,
+            cnft.data_hash,
+            cnft.creator_hash,
+            cnft.nonce,
+            cnft.index,
+        )?;
+
+        let owner = agent_data.owner;
+        emit!(AgentClosed {
+            agent_id: agent_data.key(),
+            owner,
+            name: agent_data.name.clone(),
+            asset_index: cnft.index,
+        });
+
+        notify_profile_hook(hook_accounts, "on_agent_closed", &[owner])?;
+
+        Ok(())
+    }
+
+    // Interaction hook invoked by the relationship program for every recorded
+    // interaction; bumps both agents' interaction counters. When the hook is
+    // registered with the level config as a third extra account, both agents
+    // also earn interaction XP.
+    pub fn on_interaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, OnInteraction<'info>>,
+        payload: InteractionHookPayload,
+    ) -> Result<()> {
+        let agent_one = &mut ctx.accounts.agent_one;
+        let agent_two = &mut ctx.accounts.agent_two;
+
+        require!(
+            payload.agent_one == agent_one.key() && payload.agent_two == agent_two.key(),
+            AgentError::Unauthorized
+        );
+
+        agent_one.interaction_count = agent_one.interaction_count.checked_add(1).unwrap_or(u32::MAX);
+        agent_two.interaction_count = agent_two.interaction_count.checked_add(1).unwrap_or(u32::MAX);
+        agent_one.last_active = payload.timestamp;
+        agent_two.last_active = payload.timestamp;
+
+        if let Some(account) = ctx.remaining_accounts.first() {
+            let level_config = Account::<LevelConfig>::try_from(account)?;
+            award_xp(agent_one, &level_config, level_config.interaction_xp);
+            award_xp(agent_two, &level_config, level_config.interaction_xp);
+        }
+
+        Ok(())
+    }
+
+    // Create the registry config and set its moderation authority
+    pub fn initialize_registry_config(
+        ctx: Context<InitializeRegistryConfig>,
+        moderator: Pubkey,
+    ) -> Result<()> {
+        let registry_config = &mut ctx.accounts.registry_config;
+        registry_config.admin = ctx.accounts.admin.key();
+        registry_config.moderator = moderator;
+        registry_config.match_cooldown = DEFAULT_MATCH_COOLDOWN;
+        registry_config.bump = *ctx.bumps.get("registry_config").unwrap();
+
+        emit!(ModeratorUpdated {
+            previous_moderator: Pubkey::default(),
+            moderator,
+        });
+
+        Ok(())
+    }
+
+    // Create the registry-wide agent counter (registry admin), which
+    // registration requires
+    pub fn initialize_registry_state(ctx: Context<InitializeRegistryState>) -> Result<()> {
+        let registry_state = &mut ctx.accounts.registry_state;
+        registry_state.total_agents = 0;
+        registry_state.bump = *ctx.bumps.get("registry_state").unwrap();
+
+        Ok(())
+    }
+
+    // Record the verified collection agents are minted into (registry
+    // admin). The collection NFT is created with Metaplex beforehand and its
+    // update authority handed to the registry's collection authority PDA.
+    pub fn initialize_collection(ctx: Context<InitializeCollection>) -> Result<()> {
+        verify_collection(
+            &ctx.accounts.collection_mint,
+            &ctx.accounts.collection_metadata,
+            &ctx.accounts.collection_edition,
+            &ctx.accounts.collection_authority.key(),
+        )?;
+
+        let collection_config = &mut ctx.accounts.collection_config;
+        collection_config.collection_mint = ctx.accounts.collection_mint.key();
+        collection_config.collection_metadata = ctx.accounts.collection_metadata.key();
+        collection_config.collection_edition = ctx.accounts.collection_edition.key();
+        collection_config.authority_bump = *ctx.bumps.get("collection_authority").unwrap();
+        collection_config.bump = *ctx.bumps.get("collection_config").unwrap();
+
+        emit!(CollectionUpdated {
+            previous_collection_mint: Pubkey::default(),
+            collection_mint: collection_config.collection_mint,
+        });
+
+        Ok(())
+    }
+
+    // Mint new agents into a different collection (registry admin). Agents
+    // already minted stay in the previous one.
+    pub fn set_collection(ctx: Context<SetCollection>) -> Result<()> {
+        verify_collection(
+            &ctx.accounts.collection_mint,
+            &ctx.accounts.collection_metadata,
+            &ctx.accounts.collection_edition,
+            &ctx.accounts.collection_authority.key(),
+        )?;
+
+        let collection_config = &mut ctx.accounts.collection_config;
+        let previous_collection_mint = collection_config.collection_mint;
+        collection_config.collection_mint = ctx.accounts.collection_mint.key();
+        collection_config.collection_metadata = ctx.accounts.collection_metadata.key();
+        collection_config.collection_edition = ctx.accounts.collection_edition.key();
+
+        emit!(CollectionUpdated {
+            previous_collection_mint,
+            collection_mint: collection_config.collection_mint,
+        });
+
+        Ok(())
+    }
+
+    // Create the personality trait weights `calculate_compatibility` scores
+    // with (registry admin)
+    pub fn initialize_compatibility_config(
+        ctx: Context<InitializeCompatibilityConfig>,
+        weights: CompatibilityWeights,
+    ) -> Result<()> {
+        weights.validate().map_err(|_| error!(AgentError::InvalidCompatibilityWeights))?;
+        let compatibility_config = &mut ctx.accounts.compatibility_config;
+        compatibility_config.weights = weights.clone();
+        compatibility_config.bump = *ctx.bumps.get("compatibility_config").unwrap();
+
+        emit!(CompatibilityConfigUpdated { weights });
+
+        Ok(())
+    }
+
+    // Retune matching without a program upgrade (registry admin). Scores
+    // already recorded are not recomputed.
+    pub fn set_compatibility_config(
+        ctx: Context<SetCompatibilityConfig>,
+        weights: CompatibilityWeights,
+    ) -> Result<()> {
+        weights.validate().map_err(|_| error!(AgentError::InvalidCompatibilityWeights))?;
+        ctx.accounts.compatibility_config.weights = weights.clone();
+
+        emit!(CompatibilityConfigUpdated { weights });
+
+        Ok(())
+    }
+
+    // Create the weights custom traits carry in `calculate_compatibility`
+    // (registry admin)
+    pub fn initialize_trait_weights(
+        ctx: Context<InitializeTraitWeights>,
+        base_weight: u16,
+        weights: Vec<CustomTraitWeight>,
+    ) -> Result<()> {
+        validate_trait_weights(&weights)?;
+        let trait_weights = &mut ctx.accounts.trait_weights;
+        trait_weights.base_weight = base_weight;
+        trait_weights.weights = weights;
+        trait_weights.bump = *ctx.bumps.get("trait_weights").unwrap();
+
+        emit!(TraitWeightsUpdated {
+            base_weight,
+            weights: trait_weights.weights.clone(),
+        });
+
+        Ok(())
+    }
+
+    // Replace the compatibility weights (registry admin)
+    pub fn set_trait_weights(
+        ctx: Context<SetTraitWeights>,
+        base_weight: u16,
+        weights: Vec<CustomTraitWeight>,
+    ) -> Result<()> {
+        validate_trait_weights(&weights)?;
+        let trait_weights = &mut ctx.accounts.trait_weights;
+        trait_weights.base_weight = base_weight;
+        trait_weights.weights = weights;
+
+        emit!(TraitWeightsUpdated {
+            base_weight,
+            weights: trait_weights.weights.clone(),
+        });
+
+        Ok(())
+    }
+
+    // Create the XP rewards and level thresholds (registry admin)
+    pub fn initialize_level_config(ctx: Context<InitializeLevelConfig>, params: LevelParams) -> Result<()> {
+        validate_level_params(&params)?;
+        let level_config = &mut ctx.accounts.level_config;
+        level_config.set_params(&params);
+        level_config.bump = *ctx.bumps.get("level_config").unwrap();
+
+        emit!(LevelConfigUpdated { params });
+
+        Ok(())
+    }
+
+    // Replace the XP rewards and level thresholds (registry admin). Agents
+    // keep their current level if the thresholds are raised.
+    pub fn set_level_config(ctx: Context<SetLevelConfig>, params: LevelParams) -> Result<()> {
+        validate_level_params(&params)?;
+        ctx.accounts.level_config.set_params(&params);
+
+        emit!(LevelConfigUpdated { params });
+
+        Ok(())
+    }
+
+    // Replace the moderation authority
+    pub fn set_moderator(
+        ctx: Context<UpdateRegistryConfig>,
+        moderator: Pubkey,
+    ) -> Result<()> {
+        let registry_config = &mut ctx.accounts.registry_config;
+        let previous_moderator = registry_config.moderator;
+        registry_config.moderator = moderator;
+
+        emit!(ModeratorUpdated {
+            previous_moderator,
+            moderator,
+        });
+
+        Ok(())
+    }
+
+    // Set the minimum time between consecutive matches of the same pair
+    pub fn set_match_cooldown(
+        ctx: Context<UpdateRegistryConfig>,
+        match_cooldown: i64,
+    ) -> Result<()> {
+        require!(match_cooldown >= 0, AgentError::InvalidMatchCooldown);
+        let registry_config = &mut ctx.accounts.registry_config;
+        let previous_match_cooldown = registry_config.match_cooldown;
+        registry_config.match_cooldown = match_cooldown;
+
+        emit!(MatchCooldownUpdated {
+            previous_match_cooldown,
+            match_cooldown,
+        });
+
+        Ok(())
+    }
+
+    // Hand the registry admin role to another key, such as the admin
+    // multisig's authority PDA
+    pub fn set_registry_admin(
+        ctx: Context<UpdateRegistryConfig>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        let registry_config = &mut ctx.accounts.registry_config;
+        let previous_admin = registry_config.admin;
+        registry_config.admin = admin;
+
+        emit!(RegistryAdminUpdated {
+            previous_admin,
+            admin,
+        });
+
+        Ok(())
+    }
+
+    // Freeze or unfreeze an agent. Frozen agents cannot form relationships
+    // or record interactions in the relationship program.
+    pub fn set_agent_frozen(
+        ctx: Context<ModerateAgent>,
+        is_frozen: bool,
+    ) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        agent_data.is_frozen = is_frozen;
+
+        emit!(AgentFrozenUpdated {
+            agent_id: agent_data.key(),
+            is_frozen,
+            moderator: ctx.accounts.moderator.key(),
+        });
+
+        Ok(())
+    }
+
+    // Record the parents of an agent bred by the breeding program. Lineage
+    // can only be set once, right after the child is registered.
+    pub fn set_lineage(
+        ctx: Context<SetLineage>,
+        parent_one: Pubkey,
+        parent_two: Pubkey,
+        generation: u16,
+    ) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        require!(
+            agent_data.generation == 0 && agent_data.parent_one == Pubkey::default(),
+            AgentError::LineageAlreadySet
+        );
+        require!(generation > 0, AgentError::InvalidLineage);
+
+        agent_data.parent_one = parent_one;
+        agent_data.parent_two = parent_two;
+        agent_data.generation = generation;
+
+        emit!(AgentLineageSet {
+            agent_id: agent_data.key(),
+            parent_one,
+            parent_two,
+            generation,
+        });
+
+        Ok(())
+    }
+
+    // Add an agent's match results from a settled tournament
+    pub fn record_tournament_result(
+        ctx: Context<RecordTournamentResult>,
+        wins: u32,
+        losses: u32,
+    ) -> Result<()> {
+        let agent_data = &mut ctx.accounts.agent_data;
+        agent_data.wins = agent_data.wins.saturating_add(wins);
+        agent_data.losses = agent_data.losses.saturating_add(losses);
+
+        emit!(AgentRecordUpdated {
+            agent_id: agent_data.key(),
+            wins: agent_data.wins,
+            losses: agent_data.losses,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite an agent created with the original layout into the current
+    // one, growing the account (anyone; the payer covers the extra rent).
+    // Fields the original layout lacked start at their registration
+    // defaults, and the agent takes the next agent index.
+    pub fn migrate_agent(ctx: Context<MigrateAgent>) -> Result<()> {
+        let account = ctx.accounts.agent_data.to_account_info();
+        require!(account.owner == &crate::ID, AgentError::NotLegacyAccount);
+
+        let legacy = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.len() == LEGACY_AGENT_SPACE && data[..8] == <AgentData as anchor_lang::Discriminator>::DISCRIMINATOR,
+                AgentError::NotLegacyAccount
+            );
+            LegacyAgentData::deserialize(&mut &data[8..])?
+        };
+
+        let agent_data = AgentData {
+            owner: legacy.owner,
+            name: legacy.name,
+            metadata_uri: legacy.metadata_uri,
+            is_active: legacy.is_active,
+            personality_traits: legacy.personality_traits,
+            creation_date: legacy.creation_date,
+            match_count: legacy.match_count,
+            interaction_count: legacy.interaction_count,
+            last_active: legacy.last_active,
+            is_frozen: false,
+            parent_one: Pubkey::default(),
+            parent_two: Pubkey::default(),
+            generation: 0,
+            wins: 0,
+            losses: 0,
+            delegate: None,
+            agent_index: ctx.accounts.registry_state.next_agent_index(),
+            xp: 0,
+            level: 0,
+            closed_match_count: 0,
+            transfer_nonce: 0,
+            custom_traits: Vec::new(),
+        };
+        let mut data = Vec::new();
+        agent_data.try_serialize(&mut data)?;
+        rewrite_legacy_account(
+            &account,
+            &data,
+            8 + std::mem::size_of::<AgentData>(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        emit!(AgentLayoutMigrated {
+            agent_id: account.key(),
+            agent_index: agent_data.agent_index,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite a match created with the original layout into the current one
+    // (anyone; the payer covers the extra rent). The original layout kept
+    // neither the rent payer nor the end time: agent one's owner, whose
+    // agent proposed the match, receives the rent when it is closed, and an
+    // ended match counts as ended at its match date.
+    pub fn migrate_match(ctx: Context<MigrateMatch>) -> Result<()> {
+        let account = ctx.accounts.match_data.to_account_info();
+        require!(account.owner == &crate::ID, AgentError::NotLegacyAccount);
+
+        let legacy = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.len() == LEGACY_MATCH_SPACE && data[..8] == <MatchData as anchor_lang::Discriminator>::DISCRIMINATOR,
+                AgentError::NotLegacyAccount
+            );
+            LegacyMatchData::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(ctx.accounts.agent_one.key(), legacy.agent_one, AgentError::AgentMismatch);
+
+        let match_data = MatchData {
+            agent_one: legacy.agent_one,
+            agent_two: legacy.agent_two,
+            compatibility_score: legacy.compatibility_score,
+            match_date: legacy.match_date,
+            is_active: legacy.is_active,
+            algorithm_version: CompatibilityAlgorithm::Heuristic.version(),
+            rent_payer: ctx.accounts.agent_one.owner,
+            ended_at: if legacy.is_active { 0 } else { legacy.match_date },
+            end_reason: None,
+            match_nonce: 0,
+        };
+        let mut data = Vec::new();
+        match_data.try_serialize(&mut data)?;
+        rewrite_legacy_account(
+            &account,
+            &data,
+            8 + std::mem::size_of::<MatchData>(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        emit!(MatchLayoutMigrated {
+            match_id: account.key(),
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct AgentData {
+    pub owner: Pubkey,
+    pub name: String,
+    pub metadata_uri: String,
+    pub is_active: bool,
+    pub personality_traits: PersonalityTraits,
+    pub creation_date: i64,
+    pub match_count: u32,
+    pub interaction_count: u32,
+    pub last_active: i64,
+    pub is_frozen: bool,  // Set by the moderator
+    pub parent_one: Pubkey,  // Pubkey::default() for agents that were not bred
+    pub parent_two: Pubkey,
+    pub generation: u16,     // 0 for registered agents, parents' max + 1 for offspring
+    pub wins: u32,           // Tournament match record
+    pub losses: u32,
+    pub delegate: Option<Pubkey>,  // May update status and activity; cleared on transfer
+    pub agent_index: u64,          // Registration order, from 0; never reused
+    pub xp: u64,
+    pub level: u16,                // Levels reached under the level config; never decreases
+    pub closed_match_count: u32,   // Matches closed by `close_match`; match_count never decreases
+    pub transfer_nonce: u64,       // Owner changes so far; pending transfers from before one are stale
+    pub custom_traits: Vec<TraitEntry>,  // Up to MAX_CUSTOM_TRAITS; the account grows with them
+}
+
+// Borsh layouts deployed agents and matches were created with, read only by
+// `migrate_agent` and `migrate_match`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyAgentData {
+    pub owner: Pubkey,
+    pub name: String,
+    pub metadata_uri: String,
+    pub is_active: bool,
+    pub personality_traits: PersonalityTraits,
+    pub creation_date: i64,
+    pub match_count: u32,
+    pub interaction_count: u32,
+    pub last_active: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyMatchData {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub compatibility_score: u8,
+    pub match_date: i64,
+    pub is_active: bool,
+}
+
+impl AgentData {
+    pub fn is_delegate(&self, signer: &Pubkey) -> bool {
+        self.delegate == Some(*signer)
+    }
+
+    pub fn is_owner_or_delegate(&self, signer: &Pubkey) -> bool {
+        self.owner == *signer || self.is_delegate(signer)
+    }
+
+    // Hand the agent to `new_owner`, clearing the delegate and invalidating
+    // any pending transfer
+    fn set_owner(&mut self, new_owner: Pubkey) {
+        self.owner = new_owner;
+        self.delegate = None;
+        self.transfer_nonce = self.transfer_nonce.wrapping_add(1);
+    }
+
+    pub fn custom_trait(&self, key: &str) -> Option<u8> {
+        self.custom_traits
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.value)
+    }
+}
+
+#[account]
+pub struct MatchData {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub compatibility_score: u8,
+    pub match_date: i64,
+    pub is_active: bool,
+    pub algorithm_version: u8,  // Algorithm the score was calculated with
+    pub rent_payer: Pubkey,     // Refunded when the match is closed
+    pub ended_at: i64,          // 0 while active
+    pub end_reason: Option<MatchEndReason>,
+    pub match_nonce: u64,       // The pair's match count before this one
+}
+
+// Match history of an agent pair, in canonical order. Each match of the
+// pair gets its own MatchData, seeded by the nonce.
+#[account]
+pub struct PairState {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub match_nonce: u64,        // Matches recorded for the pair
+    pub last_matched_at: i64,
+    pub has_active_match: bool,
+    pub bump: u8,
+}
+
+impl PairState {
+    pub fn is_for(&self, agent_a: &Pubkey, agent_b: &Pubkey) -> bool {
+        canonical_pair(*agent_a, *agent_b) == (self.agent_one, self.agent_two)
+    }
+}
+
+// Agents waiting to be paired by `process_queue`, oldest first
+#[account]
+pub struct MatchQueue {
+    pub entries: Vec<QueueEntry>,
+    pub bump: u8,
+}
+
+impl MatchQueue {
+    pub const SPACE: usize = 8 + 4 + MAX_QUEUE_LEN * (32 + 1 + 2 + 2 + 8 + 32) + 1;
+
+    pub fn position(&self, agent: &Pubkey) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.agent == *agent)
+    }
+
+    pub fn queued_by(&self, owner: &Pubkey) -> usize {
+        self.entries.iter().filter(|entry| entry.owner == *owner).count()
+    }
+
+    // Remove and return the entries queued QUEUE_ENTRY_TTL or longer ago
+    pub fn evict_expired(&mut self, now: i64) -> Vec<QueueEntry> {
+        let (expired, live) = self.entries.drain(..).partition(|entry| entry.is_expired(now));
+        self.entries = live;
+        expired
+    }
+}
+
+// A match awaiting agent two's acceptance
+#[account]
+pub struct MatchProposal {
+    pub agent_one: Pubkey,   // Proposing agent
+    pub agent_two: Pubkey,
+    pub proposer: Pubkey,    // Signer of the proposal, refunded the rent
+    pub proposed_at: i64,
+    pub bump: u8,
+}
+
+// An ownership transfer awaiting the new owner's signature
+#[account]
+pub struct PendingTransfer {
+    pub agent: Pubkey,
+    pub owner: Pubkey,      // Owner at proposal time, refunded the rent
+    pub new_owner: Pubkey,
+    pub proposed_at: i64,
+    pub transfer_nonce: u64,  // The agent's transfer_nonce at proposal time
+    pub bump: u8,
+}
+
+// Registry-wide counters
+#[account]
+pub struct RegistryState {
+    pub total_agents: u64,  // Agents ever registered; the next agent's index
+    pub bump: u8,
+}
+
+impl RegistryState {
+    // Assign the next sequential agent index
+    pub fn next_agent_index(&mut self) -> u64 {
+        let agent_index = self.total_agents;
+        self.total_agents = self.total_agents.saturating_add(1);
+        agent_index
+    }
+}
+
+// The verified collection agents are minted into
+#[account]
+pub struct CollectionConfig {
+    pub collection_mint: Pubkey,
+    pub collection_metadata: Pubkey,
+    pub collection_edition: Pubkey,  // Master edition
+    pub authority_bump: u8,          // Bump of the collection authority PDA
+    pub bump: u8,
+}
+
+// The last stored compatibility of an agent pair, in canonical order
+#[account]
+pub struct CompatibilityResult {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub score: u8,
+    pub algorithm_version: u8,
+    pub computed_at: i64,
+    pub bump: u8,
+}
+
+impl CompatibilityResult {
+    // Whether this is the result for the two agents, in either order
+    pub fn is_for(&self, agent_a: &Pubkey, agent_b: &Pubkey) -> bool {
+        (self.agent_one == *agent_a && self.agent_two == *agent_b)
+            || (self.agent_one == *agent_b && self.agent_two == *agent_a)
+    }
+}
+
+// Personality trait weights used by `calculate_compatibility`
+#[account]
+pub struct CompatibilityConfig {
+    pub weights: CompatibilityWeights,
+    pub bump: u8,
+}
+
+// XP rewards and the XP needed for each level
+#[account]
+pub struct LevelConfig {
+    pub xp_authority: Pubkey,   // Game backend allowed to grant XP
+    pub match_xp: u64,          // Per agent per recorded match
+    pub interaction_xp: u64,    // Per agent per relationship interaction
+    pub thresholds: Vec<u64>,   // Total XP for levels 1, 2, ...; strictly increasing
+    pub bump: u8,
+}
+
+impl LevelConfig {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 4 + MAX_LEVELS * 8 + 1;
+
+    pub fn level_for(&self, xp: u64) -> u16 {
+        self.thresholds.iter().take_while(|threshold| **threshold <= xp).count() as u16
+    }
+
+    fn set_params(&mut self, params: &LevelParams) {
+        self.xp_authority = params.xp_authority;
+        self.match_xp = params.match_xp;
+        self.interaction_xp = params.interaction_xp;
+        self.thresholds = params.thresholds.clone();
+    }
+}
+
+// How much shared custom traits count in compatibility, relative to the
+// personality trait score
+#[account]
+pub struct TraitWeights {
+    pub base_weight: u16,  // Weight of the personality trait score
+    pub weights: Vec<CustomTraitWeight>,
+    pub bump: u8,
+}
+
+impl TraitWeights {
+    pub const SPACE: usize = 8 + 2 + 4 + MAX_WEIGHTED_TRAITS * (4 + MAX_TRAIT_KEY_LEN + 2) + 1;
+}
+
+#[account]
+pub struct RegistryConfig {
+    pub admin: Pubkey,
+    pub moderator: Pubkey,  // May freeze and unfreeze agents
+    pub bump: u8,
+    pub match_cooldown: i64,  // Seconds between consecutive matches of a pair
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct RegisterAgent<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<AgentData>(),
+        seeds = [b"agent", owner.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(mut, seeds = [b"registry_state"], bump = registry_state.bump)]
+    pub registry_state: Account<'info, RegistryState>,
+
+    // Accounts needed for minting compressed NFT
+    pub tree_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+
+    /// CHECK: validated by the rate limiter program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the rate limiter program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+
+    pub rate_limiter_program: Program<'info, RateLimiter>,
+
+    /// CHECK: the owner's attestation credential, required only in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
+
+    // Referral hook accounts, required only to credit the owner's referrer
+    // for the registration; `referral` is the owner's, which need not exist
+    /// CHECK: PDA that signs referral reports
+    #[account(seeds = [REFERRAL_REPORTER_SEED], bump)]
+    pub referral_reporter: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    pub referrals_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the owner before reporting
+    #[account(mut)]
+    pub referral: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    #[account(mut)]
+    pub referrer_rewards: Option<UncheckedAccount<'info>>,
+
+    pub referrals_program: Option<Program<'info, Referrals>>,
+
+    pub collection: AgentCollection<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAgentsBatch<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"registry_state"], bump = registry_state.bump)]
+    pub registry_state: Account<'info, RegistryState>,
+
+    // Accounts needed for minting compressed NFTs
+    /// CHECK: Bubblegum tree config; validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+
+    /// CHECK: validated by the rate limiter program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the rate limiter program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+
+    pub rate_limiter_program: Program<'info, RateLimiter>,
+
+    /// CHECK: the owner's attestation credential, required only in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
+
+    pub collection: AgentCollection<'info>,
+}
+
+// The collection accounts Bubblegum needs to mint an agent into the
+// registry's verified collection
+#[derive(Accounts)]
+pub struct AgentCollection<'info> {
+    #[account(seeds = [b"collection_config"], bump = collection_config.bump)]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    /// CHECK: PDA that signs as the collection's update authority
+    #[account(seeds = [COLLECTION_AUTHORITY_SEED], bump = collection_config.authority_bump)]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the collection config
+    #[account(address = collection_config.collection_mint)]
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the collection config; Bubblegum updates its size
+    #[account(mut, address = collection_config.collection_metadata)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the collection config
+    #[account(address = collection_config.collection_edition)]
+    pub collection_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum's signer for Token Metadata collection CPIs
+    #[account(seeds = [b"collection_cpi"], bump, seeds::program = mpl_bubblegum::ID)]
+    pub bubblegum_signer: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+
+    /// CHECK: the Token Metadata program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAgent<'info> {
+    // The owner, or for status updates a session key holder or the delegate
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    // Session the signer acts under; only honoured by `update_agent_status`
+    pub session: Option<Account<'info, SessionKey>>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAgentActivity<'info> {
+    // The owner or the agent's delegate
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct GrantXp<'info> {
+    pub xp_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        seeds = [b"level_config"],
+        bump = level_config.bump,
+        has_one = xp_authority @ AgentError::Unauthorized,
+    )]
+    pub level_config: Account<'info, LevelConfig>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == owner.key() @ AgentError::Unauthorized
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCustomTraits<'info> {
+    // Pays for added traits and is refunded for removed ones
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == owner.key() @ AgentError::Unauthorized
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CalculateCompatibility<'info> {
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(seeds = [b"compatibility_config"], bump = compatibility_config.bump)]
+    pub compatibility_config: Account<'info, CompatibilityConfig>,
+
+    // Without it, only personality traits are scored
+    #[account(seeds = [b"trait_weights"], bump = trait_weights.bump)]
+    pub trait_weights: Option<Account<'info, TraitWeights>>,
+}
+
+#[derive(Accounts)]
+pub struct StoreCompatibility<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(constraint = agent_one.key() < agent_two.key() @ AgentError::NonCanonicalPair)]
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(seeds = [b"compatibility_config"], bump = compatibility_config.bump)]
+    pub compatibility_config: Account<'info, CompatibilityConfig>,
+
+    // Without it, only personality traits are scored
+    #[account(seeds = [b"trait_weights"], bump = trait_weights.bump)]
+    pub trait_weights: Option<Account<'info, TraitWeights>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<CompatibilityResult>(),
+        seeds = [b"compatibility", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump
+    )]
+    pub compatibility_result: Account<'info, CompatibilityResult>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeMatch<'info> {
+    // Agent one's owner or delegate
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + std::mem::size_of::<MatchProposal>(),
+        seeds = [b"match_proposal", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, MatchProposal>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeclineMatch<'info> {
+    // Either agent's owner or delegate
+    pub signer: Signer<'info>,
+
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"match_proposal", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer,
+    )]
+    pub proposal: Account<'info, MatchProposal>,
+
+    /// CHECK: receives the proposal's rent
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptMatch<'info> {
+    // Agent two's owner or delegate; pays for the match account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(mut)]
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"match_proposal", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer,
+    )]
+    pub proposal: Account<'info, MatchProposal>,
+
+    /// CHECK: receives the proposal's rent
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"registry_config"], bump = registry_config.bump)]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PairState>(),
+        seeds = [
+            b"pair_state",
+            canonical_pair(agent_one.key(), agent_two.key()).0.as_ref(),
+            canonical_pair(agent_one.key(), agent_two.key()).1.as_ref(),
+        ],
+        bump
+    )]
+    pub pair_state: Account<'info, PairState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<MatchData>(),
+        seeds = [b"match", pair_state.key().as_ref(), &pair_state.match_nonce.to_le_bytes()],
+        bump
+    )]
+    pub match_data: Account<'info, MatchData>,
+
+    // Checked against the pair in the handler, since it is seeded in
+    // canonical order and the match is not
+    pub compatibility_result: Account<'info, CompatibilityResult>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+
+    /// CHECK: validated by the rate limiter program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the rate limiter program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+
+    pub rate_limiter_program: Program<'info, RateLimiter>,
+
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
+
+    // Without it, the match earns no XP
+    #[account(seeds = [b"level_config"], bump = level_config.bump)]
+    pub level_config: Option<Account<'info, LevelConfig>>,
+
+    // Referral hook accounts, required only to credit the agents' owners'
+    // referrers for the match; each owner's `referral` need not exist
+    /// CHECK: PDA that signs referral reports
+    #[account(seeds = [REFERRAL_REPORTER_SEED], bump)]
+    pub referral_reporter: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    pub referrals_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: agent one's owner's referral; checked before reporting
+    #[account(mut)]
+    pub referral_one: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    #[account(mut)]
+    pub referrer_rewards_one: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: agent two's owner's referral; checked before reporting
+    #[account(mut)]
+    pub referral_two: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    #[account(mut)]
+    pub referrer_rewards_two: Option<UncheckedAccount<'info>>,
+
+    pub referrals_program: Option<Program<'info, Referrals>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMatchQueue<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MatchQueue::SPACE,
+        seeds = [b"match_queue"],
+        bump
+    )]
+    pub match_queue: Account<'info, MatchQueue>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EnterQueue<'info> {
+    // The agent's owner or delegate
+    pub signer: Signer<'info>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(mut, seeds = [b"match_queue"], bump = match_queue.bump)]
+    pub match_queue: Account<'info, MatchQueue>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveQueue<'info> {
+    // The agent's owner or delegate
+    pub signer: Signer<'info>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(mut, seeds = [b"match_queue"], bump = match_queue.bump)]
+    pub match_queue: Account<'info, MatchQueue>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessQueue<'info> {
+    // Pays for the pair state and match accounts
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"match_queue"], bump = match_queue.bump)]
+    pub match_queue: Account<'info, MatchQueue>,
+
+    #[account(seeds = [b"registry_config"], bump = registry_config.bump)]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(seeds = [b"compatibility_config"], bump = compatibility_config.bump)]
+    pub compatibility_config: Account<'info, CompatibilityConfig>,
+
+    // Without it, only personality traits are scored
+    #[account(seeds = [b"trait_weights"], bump = trait_weights.bump)]
+    pub trait_weights: Option<Account<'info, TraitWeights>>,
+
+    // Without it, queue matches earn no XP
+    #[account(seeds = [b"level_config"], bump = level_config.bump)]
+    pub level_config: Option<Account<'info, LevelConfig>>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DissolveMatch<'info> {
+    // Either agent's owner or delegate
+    pub signer: Signer<'info>,
+
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        constraint = match_data.agent_one == agent_one.key() && match_data.agent_two == agent_two.key()
+    )]
+    pub match_data: Account<'info, MatchData>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_state", pair_state.agent_one.as_ref(), pair_state.agent_two.as_ref()],
+        bump = pair_state.bump,
+        constraint = pair_state.is_for(&agent_one.key(), &agent_two.key())
+    )]
+    pub pair_state: Account<'info, PairState>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMatch<'info> {
+    #[account(mut)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(mut)]
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = rent_payer,
+        constraint = match_data.agent_one == agent_one.key() && match_data.agent_two == agent_two.key()
+    )]
+    pub match_data: Account<'info, MatchData>,
+
+    /// CHECK: receives the match account's rent
+    #[account(mut)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
 pub struct TransferOwnership<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub owner: Signer<'info>,
+
+    /// CHECK: must sign unless `owner` is a program-owned escrow account; checked in the handler
+    pub new_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == owner.key() @ AgentError::Unauthorized
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeOwnershipTransfer<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ AgentError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<PendingTransfer>(),
+        seeds = [b"pending_transfer", agent_data.key().as_ref()],
+        bump
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    pub new_owner: Signer<'info>,
+
+    /// CHECK: the proposer, refunded the pending transfer's rent
+    #[account(mut, address = pending_transfer.owner)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"pending_transfer", agent_data.key().as_ref()],
+        bump = pending_transfer.bump,
+        constraint = pending_transfer.new_owner == new_owner.key() @ AgentError::Unauthorized,
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOwnershipTransfer<'info> {
+    // The proposer, the proposed owner or the agent's current owner
+    pub signer: Signer<'info>,
+
+    /// CHECK: the proposer, refunded the pending transfer's rent
+    #[account(mut, address = pending_transfer.owner)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: the agent, which may have been closed since the proposal
+    pub agent_data: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"pending_transfer", agent_data.key().as_ref()],
+        bump = pending_transfer.bump,
+    )]
+    pub pending_transfer: Account<'info, PendingTransfer>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgent<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        constraint = agent_data.owner == owner.key() @ AgentError::Unauthorized
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    /// CHECK: Bubblegum tree config; validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct OnInteraction<'info> {
+    #[account(
+        seeds = [b"hook_authority"],
+        bump,
+        seeds::program = RELATIONSHIP_PROGRAM_ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: described by the payload
+    pub relationship: UncheckedAccount<'info>,
+    /// CHECK: described by the payload
+    pub interaction: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(mut)]
+    pub agent_two: Account<'info, AgentData>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistryConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<RegistryConfig>(),
+        seeds = [b"registry_config"],
+        bump
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRegistryState<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<RegistryState>(),
+        seeds = [b"registry_state"],
+        bump
+    )]
+    pub registry_state: Account<'info, RegistryState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCompatibilityConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<CompatibilityConfig>(),
+        seeds = [b"compatibility_config"],
+        bump
+    )]
+    pub compatibility_config: Account<'info, CompatibilityConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCompatibilityConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"compatibility_config"],
+        bump = compatibility_config.bump,
+    )]
+    pub compatibility_config: Account<'info, CompatibilityConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLevelConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = LevelConfig::SPACE,
+        seeds = [b"level_config"],
+        bump
+    )]
+    pub level_config: Account<'info, LevelConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLevelConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"level_config"],
+        bump = level_config.bump,
+    )]
+    pub level_config: Account<'info, LevelConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTraitWeights<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = TraitWeights::SPACE,
+        seeds = [b"trait_weights"],
+        bump
+    )]
+    pub trait_weights: Account<'info, TraitWeights>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTraitWeights<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"trait_weights"],
+        bump = trait_weights.bump,
+    )]
+    pub trait_weights: Account<'info, TraitWeights>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCollection<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<CollectionConfig>(),
+        seeds = [b"collection_config"],
+        bump
+    )]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    /// CHECK: PDA that must be the collection's update authority
+    #[account(seeds = [COLLECTION_AUTHORITY_SEED], bump)]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    pub collection_mint: Account<'info, Mint>,
+    /// CHECK: checked in `verify_collection`
+    pub collection_metadata: UncheckedAccount<'info>,
+    /// CHECK: checked in `verify_collection`
+    pub collection_edition: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCollection<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(mut, seeds = [b"collection_config"], bump = collection_config.bump)]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    /// CHECK: PDA that must be the collection's update authority
+    #[account(seeds = [COLLECTION_AUTHORITY_SEED], bump = collection_config.authority_bump)]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    pub collection_mint: Account<'info, Mint>,
+    /// CHECK: checked in `verify_collection`
+    pub collection_metadata: UncheckedAccount<'info>,
+    /// CHECK: checked in `verify_collection`
+    pub collection_edition: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRegistryConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = admin @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateAgent<'info> {
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"registry_config"],
+        bump = registry_config.bump,
+        has_one = moderator @ AgentError::Unauthorized,
+    )]
+    pub registry_config: Account<'info, RegistryConfig>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+}
+
+#[derive(Accounts)]
+pub struct SetLineage<'info> {
+    #[account(
+        seeds = [b"breeding_authority"],
+        bump,
+        seeds::program = BREEDING_PROGRAM_ID
+    )]
+    pub breeding_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct RecordTournamentResult<'info> {
+    #[account(
+        seeds = [b"tournament_authority"],
+        bump,
+        seeds::program = TOURNAMENT_PROGRAM_ID
+    )]
+    pub tournament_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAgent<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: an agent on the original layout; owner, discriminator and size are checked in the handler
+    #[account(mut)]
+    pub agent_data: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"registry_state"], bump = registry_state.bump)]
+    pub registry_state: Account<'info, RegistryState>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: a match on the original layout; owner, discriminator and size are checked in the handler
+    #[account(mut)]
+    pub match_data: UncheckedAccount<'info>,
 
-    #[account(
-        mut,
-        constraint = agent_data.owner == owner.key()
-    )]
-    pub agent_data: Account<'info, AgentData>,
+    // The match's first agent, already migrated, whose owner becomes the
+    // rent payer; checked against the legacy account in the handler
+    pub agent_one: Account<'info, AgentData>,
 
     pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ AgentError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
 }
 
 // Data structures
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
-pub struct PersonalityTraits {
-    pub openness: u8,           // 0-100 scale
-    pub conscientiousness: u8,  // 0-100 scale
-    pub extraversion: u8,       // 0-100 scale
-    pub agreeableness: u8,      // 0-100 scale
-    pub neuroticism: u8,        // 0-100 scale
-    pub intelligence: u8,       // 0-100 scale
-    pub creativity: u8,         // 0-100 scale
-    pub humor: u8,              // 0-100 scale
-    // Additional traits could be added
+// What a queued agent accepts in a partner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct QueuePreferences {
+    pub min_score: u8,
+    pub min_level: u16,
+    pub max_level: u16,
+}
+
+impl QueuePreferences {
+    pub fn accepts_level(&self, level: u16) -> bool {
+        self.min_level <= level && level <= self.max_level
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct QueueEntry {
+    pub agent: Pubkey,
+    pub preferences: QueuePreferences,
+    pub entered_at: i64,
+    pub owner: Pubkey,  // The agent's owner when it was queued
+}
+
+impl QueueEntry {
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.entered_at.saturating_add(QUEUE_ENTRY_TTL)
+    }
+}
+
+// Why a match was dissolved
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MatchEndReason {
+    Mutual,
+    Incompatible,
+    Inactive,
+    Other,
+}
+
+// A game-specific trait, scored on the same 0-100 scale as personality traits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TraitEntry {
+    pub key: String,
+    pub value: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CustomTraitWeight {
+    pub key: String,
+    pub weight: u16,
+}
+
+// Settings of the level config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LevelParams {
+    pub xp_authority: Pubkey,
+    pub match_xp: u64,
+    pub interaction_xp: u64,
+    pub thresholds: Vec<u64>,
+}
+
+// One agent to create with `register_agents_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AgentRegistration {
+    pub name: String,
+    pub metadata_uri: String,
+    pub personality_traits: PersonalityTraits,
+}
+
+// Leaf fields of an agent's cNFT, as returned by the DAS API
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CnftArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+// Mirrors relationship::InteractionHookPayload
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InteractionHookPayload {
+    pub relationship: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub interaction: Pubkey,
+    pub interaction_index: u64,
+    pub interaction_type: InteractionType,
+    pub timestamp: i64,
 }
 
 // Events
@@ -306,6 +2918,7 @@ pub struct AgentRegistered {
     pub agent_id: Pubkey,
     pub owner: Pubkey,
     pub name: String,
+    pub agent_index: u64,
 }
 
 #[event]
@@ -324,6 +2937,56 @@ pub struct CompatibilityCalculated {
     pub agent_one: Pubkey,
     pub agent_two: Pubkey,
     pub score: u8,
+    pub algorithm_version: u8,
+}
+
+#[event]
+pub struct AgentMatchProposed {
+    pub proposal: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub proposer: Pubkey,
+}
+
+#[event]
+pub struct AgentMatchDeclined {
+    pub proposal: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub declined_by: Pubkey,
+}
+
+#[event]
+pub struct AgentMatchDissolved {
+    pub match_id: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub reason: MatchEndReason,
+    pub dissolved_by: Pubkey,
+}
+
+#[event]
+pub struct AgentQueued {
+    pub agent_id: Pubkey,
+    pub preferences: QueuePreferences,
+}
+
+// Emitted when an agent leaves the queue on its own, is matched by
+// `process_queue`, or is dropped from it because its account no longer
+// exists, it is inactive or frozen, or its entry expired
+#[event]
+pub struct AgentLeftQueue {
+    pub agent_id: Pubkey,
+}
+
+// A queued pair `process_queue` passed over because it already has an
+// active match or is inside its re-match cooldown
+#[event]
+pub struct QueuePairSkipped {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub has_active_match: bool,
+    pub cooldown_ends_at: i64,
 }
 
 #[event]
@@ -332,6 +2995,7 @@ pub struct AgentMatchRecorded {
     pub agent_one: Pubkey,
     pub agent_two: Pubkey,
     pub compatibility_score: u8,
+    pub algorithm_version: u8,
 }
 
 #[event]
@@ -341,6 +3005,139 @@ pub struct AgentOwnershipTransferred {
     pub new_owner: Pubkey,
 }
 
+#[event]
+pub struct AgentActivityRecorded {
+    pub agent_id: Pubkey,
+    pub signer: Pubkey,
+    pub interactions: u32,
+    pub interaction_count: u32,
+}
+
+#[event]
+pub struct AgentDelegateUpdated {
+    pub agent_id: Pubkey,
+    pub delegate: Option<Pubkey>,
+}
+
+#[event]
+pub struct AgentClosed {
+    pub agent_id: Pubkey,
+    pub owner: Pubkey,
+    pub name: String,
+    pub asset_index: u32,  // Leaf index of the burned cNFT
+}
+
+#[event]
+pub struct OwnershipTransferProposed {
+    pub agent_id: Pubkey,
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct OwnershipTransferCancelled {
+    pub agent_id: Pubkey,
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct CollectionUpdated {
+    pub previous_collection_mint: Pubkey,
+    pub collection_mint: Pubkey,
+}
+
+#[event]
+pub struct ModeratorUpdated {
+    pub previous_moderator: Pubkey,
+    pub moderator: Pubkey,
+}
+
+#[event]
+pub struct MatchCooldownUpdated {
+    pub previous_match_cooldown: i64,
+    pub match_cooldown: i64,
+}
+
+#[event]
+pub struct RegistryAdminUpdated {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct AgentFrozenUpdated {
+    pub agent_id: Pubkey,
+    pub is_frozen: bool,
+    pub moderator: Pubkey,
+}
+
+#[event]
+pub struct AgentLineageSet {
+    pub agent_id: Pubkey,
+    pub parent_one: Pubkey,
+    pub parent_two: Pubkey,
+    pub generation: u16,
+}
+
+#[event]
+pub struct AgentRecordUpdated {
+    pub agent_id: Pubkey,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+#[event]
+pub struct CustomTraitUpdated {
+    pub agent_id: Pubkey,
+    pub key: String,
+    pub value: Option<u8>,  // None when the trait was removed
+}
+
+#[event]
+pub struct AgentXpGranted {
+    pub agent_id: Pubkey,
+    pub signer: Pubkey,
+    pub amount: u64,
+    pub xp: u64,
+}
+
+#[event]
+pub struct AgentLeveledUp {
+    pub agent_id: Pubkey,
+    pub previous_level: u16,
+    pub level: u16,
+    pub xp: u64,
+}
+
+#[event]
+pub struct CompatibilityConfigUpdated {
+    pub weights: CompatibilityWeights,
+}
+
+#[event]
+pub struct LevelConfigUpdated {
+    pub params: LevelParams,
+}
+
+#[event]
+pub struct TraitWeightsUpdated {
+    pub base_weight: u16,
+    pub weights: Vec<CustomTraitWeight>,
+}
+
+#[event]
+pub struct AgentLayoutMigrated {
+    pub agent_id: Pubkey,
+    pub agent_index: u64,
+}
+
+#[event]
+pub struct MatchLayoutMigrated {
+    pub match_id: Pubkey,
+}
+
 // Custom errors
 #[error_code]
 pub enum AgentError {
@@ -350,28 +3147,549 @@ pub enum AgentError {
     InvalidTraitValue,
     #[msg("Agent is not active")]
     AgentInactive,
+    #[msg("Agent lineage has already been set")]
+    LineageAlreadySet,
+    #[msg("Offspring generation must be at least 1")]
+    InvalidLineage,
+    #[msg("Remaining accounts do not match the quest hook")]
+    QuestHookAccountsMismatch,
+    #[msg("Remaining accounts do not match the profile hook")]
+    ProfileHookAccountsMismatch,
+    #[msg("The registry is paused by the guardian")]
+    ProgramPaused,
+    #[msg("An agent cannot be transferred to its current owner")]
+    InvalidTransfer,
+    #[msg("The agent has changed owner since this transfer was proposed")]
+    StaleTransfer,
+    #[msg("The new owner must sign the transfer")]
+    NewOwnerMustSign,
+    #[msg("Frozen agents cannot be closed")]
+    AgentFrozen,
+    #[msg("Not enough remaining accounts for the cNFT proof")]
+    InvalidProof,
+    #[msg("Too many agents in one batch")]
+    BatchTooLarge,
+    #[msg("Remaining accounts do not match the batch")]
+    BatchAccountsMismatch,
+    #[msg("An agent with this name already exists for the owner")]
+    AgentAlreadyExists,
+    #[msg("The collection accounts are not a master edition NFT under the registry's collection authority")]
+    InvalidCollection,
+    #[msg("Custom trait keys must be 1-32 bytes")]
+    InvalidTraitKey,
+    #[msg("The agent already has the maximum number of custom traits")]
+    TooManyCustomTraits,
+    #[msg("The agent already has a custom trait with this key")]
+    CustomTraitExists,
+    #[msg("The agent has no custom trait with this key")]
+    CustomTraitNotFound,
+    #[msg("Too many or duplicate custom trait weights")]
+    InvalidTraitWeights,
+    #[msg("Level thresholds must be strictly increasing and at most 32")]
+    InvalidLevelThresholds,
+    #[msg("At least one compatibility weight must be non-zero")]
+    InvalidCompatibilityWeights,
+    #[msg("Unknown compatibility algorithm version")]
+    InvalidAlgorithmVersion,
+    #[msg("Agents must be given in canonical order")]
+    NonCanonicalPair,
+    #[msg("The compatibility result is for a different pair of agents")]
+    CompatibilityResultMismatch,
+    #[msg("The compatibility result is too old; store it again")]
+    StaleCompatibilityResult,
+    #[msg("An agent cannot be matched with itself")]
+    InvalidMatch,
+    #[msg("The match has already been dissolved")]
+    MatchNotActive,
+    #[msg("Only dissolved matches can be closed")]
+    MatchNotDissolved,
+    #[msg("The match's grace period has not passed yet")]
+    MatchGracePeriod,
+    #[msg("The pair already has an active match")]
+    PairAlreadyMatched,
+    #[msg("The pair matched too recently")]
+    MatchCooldown,
+    #[msg("The match cooldown cannot be negative")]
+    InvalidMatchCooldown,
+    #[msg("The matchmaking queue is full")]
+    QueueFull,
+    #[msg("The agent is already queued")]
+    AlreadyQueued,
+    #[msg("The agent is not queued")]
+    NotQueued,
+    #[msg("Queue preferences need min_level <= max_level")]
+    InvalidQueuePreferences,
+    #[msg("Remaining accounts do not match the queue")]
+    QueueAccountsMismatch,
+    #[msg("The account is not on the original layout")]
+    NotLegacyAccount,
+    #[msg("The agent is not the match's first agent")]
+    AgentMismatch,
+    #[msg("The owner already has the maximum number of agents queued")]
+    OwnerQueueLimit,
 }
 
 // Helper functions
+// Forward a recorded match to the quests program. Remaining accounts, when
+// given, are [quests program, quest hook authority, progress_one, progress_two].
+fn notify_quest_hook<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    agent_one: Pubkey,
+    agent_two: Pubkey,
+) -> Result<()> {
+    if remaining_accounts.is_empty() {
+        return Ok(());
+    }
+    let [program, hook_authority, progress_one, progress_two] = remaining_accounts else {
+        return err!(AgentError::QuestHookAccountsMismatch);
+    };
+    let (expected_authority, bump) =
+        Pubkey::find_program_address(&[QUEST_HOOK_AUTHORITY_SEED], &crate::ID);
+    require!(
+        program.key() == QUESTS_PROGRAM_ID
+            && program.executable
+            && hook_authority.key() == expected_authority,
+        AgentError::QuestHookAccountsMismatch
+    );
+
+    let mut data = hash(b"global:on_match").to_bytes()[..8].to_vec();
+    agent_one.serialize(&mut data)?;
+    agent_two.serialize(&mut data)?;
+    let instruction = Instruction {
+        program_id: QUESTS_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(hook_authority.key(), true),
+            AccountMeta::new(progress_one.key(), false),
+            AccountMeta::new(progress_two.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[
+            hook_authority.clone(),
+            progress_one.clone(),
+            progress_two.clone(),
+            program.clone(),
+        ],
+        &[&[QUEST_HOOK_AUTHORITY_SEED, &[bump]]],
+    )?;
+
+    Ok(())
+}
+
+// Forward a registration or transfer to the profiles program. Remaining
+// accounts, when given, are [profiles program, profile hook authority,
+// one UserProfile per owner in `owners`].
+fn notify_profile_hook<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    instruction_name: &str,
+    owners: &[Pubkey],
+) -> Result<()> {
+    if remaining_accounts.is_empty() {
+        return Ok(());
+    }
+    let [program, hook_authority, profiles @ ..] = remaining_accounts else {
+        return err!(AgentError::ProfileHookAccountsMismatch);
+    };
+    let (expected_authority, bump) =
+        Pubkey::find_program_address(&[PROFILE_HOOK_AUTHORITY_SEED], &crate::ID);
+    require!(
+        program.key() == PROFILES_PROGRAM_ID
+            && program.executable
+            && hook_authority.key() == expected_authority
+            && profiles.len() == owners.len(),
+        AgentError::ProfileHookAccountsMismatch
+    );
+
+    let mut data = hash(format!("global:{}", instruction_name).as_bytes()).to_bytes()[..8].to_vec();
+    for owner in owners {
+        owner.serialize(&mut data)?;
+    }
+    let mut accounts = vec![AccountMeta::new_readonly(hook_authority.key(), true)];
+    accounts.extend(profiles.iter().map(|p| AccountMeta::new(p.key(), false)));
+    let mut account_infos = vec![hook_authority.clone()];
+    account_infos.extend(profiles.iter().cloned());
+    account_infos.push(program.clone());
+    invoke_signed(
+        &Instruction {
+            program_id: PROFILES_PROGRAM_ID,
+            accounts,
+            data,
+        },
+        &account_infos,
+        &[&[PROFILE_HOOK_AUTHORITY_SEED, &[bump]]],
+    )?;
+
+    Ok(())
+}
+
+// Report a registration or match to the analytics program's `instruction_name` hook
+fn notify_analytics_hook<'info>(
+    program: &AccountInfo<'info>,
+    hook_authority: &AccountInfo<'info>,
+    hook_authority_bump: u8,
+    analytics_state: &AccountInfo<'info>,
+    instruction_name: &str,
+) -> Result<()> {
+    let data = hash(format!("global:{}", instruction_name).as_bytes()).to_bytes()[..8].to_vec();
+    invoke_signed(
+        &Instruction {
+            program_id: ANALYTICS_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(hook_authority.key(), true),
+                AccountMeta::new(analytics_state.key(), false),
+            ],
+            data,
+        },
+        &[hook_authority.clone(), analytics_state.clone(), program.clone()],
+        &[&[ANALYTICS_HOOK_AUTHORITY_SEED, &[hook_authority_bump]]],
+    )?;
+
+    Ok(())
+}
+
+// Every entry point that writes traits goes through this, so out-of-range
+// values fail with the same error everywhere
+fn validate_traits(traits: &PersonalityTraits) -> Result<()> {
+    traits.validate().map_err(|_| error!(AgentError::InvalidTraitValue))
+}
+
+fn validate_custom_trait(key: &str, value: u8) -> Result<()> {
+    require!(
+        !key.is_empty() && key.len() <= MAX_TRAIT_KEY_LEN,
+        AgentError::InvalidTraitKey
+    );
+    require!(value <= MAX_TRAIT_VALUE, AgentError::InvalidTraitValue);
+    Ok(())
+}
+
+fn validate_trait_weights(weights: &[CustomTraitWeight]) -> Result<()> {
+    require!(weights.len() <= MAX_WEIGHTED_TRAITS, AgentError::InvalidTraitWeights);
+    for (index, weight) in weights.iter().enumerate() {
+        validate_custom_trait(&weight.key, 0)?;
+        require!(
+            weights[..index].iter().all(|other| other.key != weight.key),
+            AgentError::InvalidTraitWeights
+        );
+    }
+    Ok(())
+}
+
+fn validate_level_params(params: &LevelParams) -> Result<()> {
+    require!(
+        params.thresholds.len() <= MAX_LEVELS
+            && params.thresholds.windows(2).all(|pair| pair[0] < pair[1]),
+        AgentError::InvalidLevelThresholds
+    );
+    Ok(())
+}
+
+// Add XP to an agent and raise its level to match
+fn award_xp(agent_data: &mut Account<AgentData>, level_config: &LevelConfig, amount: u64) {
+    agent_data.xp = agent_data.xp.saturating_add(amount);
+    let level = level_config.level_for(agent_data.xp);
+    if level > agent_data.level {
+        emit!(AgentLeveledUp {
+            agent_id: agent_data.key(),
+            previous_level: agent_data.level,
+            level,
+            xp: agent_data.xp,
+        });
+        agent_data.level = level;
+    }
+}
+
+// Grow a legacy account to `space` (at least `data`'s length), topping up
+// its rent from `payer`, and replace its contents with `data`
+fn rewrite_legacy_account<'info>(
+    account: &AccountInfo<'info>,
+    data: &[u8],
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let space = space.max(data.len());
+    let rent_due = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if rent_due > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    account.realloc(space, true)?;
+    account.try_borrow_mut_data()?[..data.len()].copy_from_slice(data);
+    Ok(())
+}
+
+// Fit an agent account to its current contents after custom traits change,
+// never below the size it was created with. The owner pays the rent for
+// growth and receives the excess when it shrinks.
+fn resize_agent_account<'info>(
+    agent_data: &Account<'info, AgentData>,
+    owner: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let account = agent_data.to_account_info();
+    let space = (8 + agent_data.try_to_vec()?.len()).max(8 + std::mem::size_of::<AgentData>());
+    if space == account.data_len() {
+        return Ok(());
+    }
+
+    let minimum_balance = Rent::get()?.minimum_balance(space);
+    if space > account.data_len() {
+        let rent_due = minimum_balance.saturating_sub(account.lamports());
+        if rent_due > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: owner.clone(),
+                        to: account.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+    } else {
+        let excess = account.lamports().saturating_sub(minimum_balance);
+        **account.try_borrow_mut_lamports()? -= excess;
+        **owner.try_borrow_mut_lamports()? += excess;
+    }
+    account.realloc(space, false)?;
+    Ok(())
+}
+
+// Record a `process_queue` match between `agent_one` and `agent_two`,
+// creating the pair state if needed and the pair's next MatchData, which is
+// the last of `pair_accounts`; the pair state is looked up among the rest.
+// Returns false without recording anything, after emitting
+// QueuePairSkipped, if the pair already has an active match or is inside
+// its re-match cooldown.
+fn record_queue_match<'info>(
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    pair_accounts: &[AccountInfo<'info>],
+    agent_one: &Pubkey,
+    agent_two: &Pubkey,
+    match_cooldown: i64,
+    compatibility_score: u8,
+    now: i64,
+) -> Result<bool> {
+    let (lower, higher) = canonical_pair(*agent_one, *agent_two);
+    let (expected, pair_bump) = Pubkey::find_program_address(
+        &[b"pair_state", lower.as_ref(), higher.as_ref()],
+        &crate::ID,
+    );
+    let pair_state_account = pair_accounts
+        .iter()
+        .find(|account| account.key() == expected)
+        .ok_or(AgentError::QueueAccountsMismatch)?;
+
+    let mut pair_state = if !is_initialized::<PairState>(pair_state_account)? {
+        create_pda_account(
+            payer,
+            pair_state_account,
+            system_program,
+            &[b"pair_state", lower.as_ref(), higher.as_ref(), &[pair_bump]],
+            8 + std::mem::size_of::<PairState>(),
+        )?;
+        PairState {
+            agent_one: lower,
+            agent_two: higher,
+            match_nonce: 0,
+            last_matched_at: 0,
+            has_active_match: false,
+            bump: pair_bump,
+        }
+    } else {
+        PairState::try_deserialize(&mut &pair_state_account.try_borrow_data()?[..])?
+    };
+    let cooldown_ends_at = pair_state.last_matched_at.saturating_add(match_cooldown);
+    if pair_state.has_active_match || (pair_state.match_nonce > 0 && now < cooldown_ends_at) {
+        emit!(QueuePairSkipped {
+            agent_one: *agent_one,
+            agent_two: *agent_two,
+            has_active_match: pair_state.has_active_match,
+            cooldown_ends_at,
+        });
+        return Ok(false);
+    }
+
+    let match_account = pair_accounts.last().unwrap();
+    let match_nonce = pair_state.match_nonce;
+    let nonce_bytes = match_nonce.to_le_bytes();
+    let (expected, match_bump) = Pubkey::find_program_address(
+        &[b"match", pair_state_account.key.as_ref(), &nonce_bytes],
+        &crate::ID,
+    );
+    require_keys_eq!(match_account.key(), expected, AgentError::QueueAccountsMismatch);
+    create_pda_account(
+        payer,
+        match_account,
+        system_program,
+        &[b"match", pair_state_account.key.as_ref(), &nonce_bytes, &[match_bump]],
+        8 + std::mem::size_of::<MatchData>(),
+    )?;
+    let match_data = MatchData {
+        agent_one: *agent_one,
+        agent_two: *agent_two,
+        compatibility_score,
+        match_date: now,
+        is_active: true,
+        algorithm_version: CompatibilityAlgorithm::Heuristic.version(),
+        rent_payer: payer.key(),
+        ended_at: 0,
+        end_reason: None,
+        match_nonce,
+    };
+    match_data.try_serialize(&mut &mut match_account.try_borrow_mut_data()?[..])?;
+
+    pair_state.match_nonce += 1;
+    pair_state.last_matched_at = now;
+    pair_state.has_active_match = true;
+    pair_state.try_serialize(&mut &mut pair_state_account.try_borrow_mut_data()?[..])?;
+
+    emit!(AgentMatchRecorded {
+        match_id: match_account.key(),
+        agent_one: *agent_one,
+        agent_two: *agent_two,
+        compatibility_score,
+        algorithm_version: match_data.algorithm_version,
+    });
+
+    Ok(true)
+}
+
+// Create a program-owned PDA with `space` bytes, paid by `payer`. An
+// address someone has already sent lamports to is topped up to the rent
+// minimum, then allocated and assigned, so pre-funding cannot block it.
+fn create_pda_account<'info>(
+    payer: &Signer<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    seeds: &[&[u8]],
+    space: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    if account.lamports() == 0 {
+        return create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+                &[seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    let shortfall = rent.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            Allocate { account_to_allocate: account.clone() },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            Assign { account_to_assign: account.clone() },
+            &[seeds],
+        ),
+        &crate::ID,
+    )
+}
+
+// Whether `account` already holds a `T` owned by this program
+fn is_initialized<T: anchor_lang::Discriminator>(account: &AccountInfo) -> Result<bool> {
+    Ok(account.owner == &crate::ID && account.try_borrow_data()?.starts_with(&T::DISCRIMINATOR))
+}
+
+// Two agents with the lower key first, the order pair accounts are stored in
+pub fn canonical_pair(agent_a: Pubkey, agent_b: Pubkey) -> (Pubkey, Pubkey) {
+    if agent_a < agent_b {
+        (agent_a, agent_b)
+    } else {
+        (agent_b, agent_a)
+    }
+}
+
+fn compatibility_algorithm(algorithm_version: u8) -> Result<CompatibilityAlgorithm> {
+    CompatibilityAlgorithm::try_from(algorithm_version)
+        .map_err(|_| error!(AgentError::InvalidAlgorithmVersion))
+}
+
+// The personality trait score under `algorithm`, averaged with the
+// similarity of each weighted custom trait both agents have. Without trait
+// weights, or when no weighted trait is shared, this is the personality
+// trait score.
+pub fn calculate_weighted_compatibility(
+    agent_one: &AgentData,
+    agent_two: &AgentData,
+    algorithm: CompatibilityAlgorithm,
+    weights: &CompatibilityWeights,
+    trait_weights: Option<&TraitWeights>,
+) -> u8 {
+    let base_score = algorithm.score(
+        weights,
+        &agent_one.personality_traits,
+        &agent_two.personality_traits,
+    );
+    let Some(trait_weights) = trait_weights else {
+        return base_score;
+    };
+
+    let mut total = base_score as u32 * trait_weights.base_weight as u32;
+    let mut total_weight = trait_weights.base_weight as u32;
+    for weight in &trait_weights.weights {
+        if let (Some(one), Some(two)) = (
+            agent_one.custom_trait(&weight.key),
+            agent_two.custom_trait(&weight.key),
+        ) {
+            let similarity = MAX_TRAIT_VALUE.saturating_sub(one.abs_diff(two));
+            total += similarity as u32 * weight.weight as u32;
+            total_weight += weight.weight as u32;
+        }
+    }
+    if total_weight == 0 {
+        return base_score;
+    }
+    (total / total_weight) as u8
+}
+
 fn generate_agent_metadata(
     owner: &Pubkey,
     name: &str,
     uri: &str,
     traits: &PersonalityTraits,
+    collection_mint: Pubkey,
 ) -> Result<MetadataArgs> {
-    // Validate trait values
-    for trait_value in [
-        traits.openness,
-        traits.conscientiousness,
-        traits.extraversion,
-        traits.agreeableness,
-        traits.neuroticism,
-        traits.intelligence,
-        traits.creativity,
-        traits.humor,
-    ].iter() {
-        require!(*trait_value <= 100, AgentError::InvalidTraitValue);
-    }
+    validate_traits(traits)?;
 
     // Create metadata for compressed NFT
     let metadata = MetadataArgs {
@@ -380,7 +3698,11 @@ fn generate_agent_metadata(
         uri: uri.to_string(),
         seller_fee_basis_points: 0,
         creators: vec![],
-        collection: None,
+        // Verified by Bubblegum's mint_to_collection_v1
+        collection: Some(Collection {
+            verified: false,
+            key: collection_mint,
+        }),
         uses: None,
         primary_sale_happened: false,
         is_mutable: true,
@@ -392,74 +3714,142 @@ fn generate_agent_metadata(
     Ok(metadata)
 }
 
-fn calculate_compatibility_score(
-    traits_one: &PersonalityTraits,
-    traits_two: &PersonalityTraits,
-) -> Result<u8> {
-    // This is a simplified compatibility calculation algorithm
-    // A real implementation would have more sophisticated matching logic
-
-    // Calculate similarity in some traits
-    let openness_diff = (traits_one.openness as i16 - traits_two.openness as i16).abs() as u16;
-    let conscientiousness_diff = (traits_one.conscientiousness as i16 - traits_two.conscientiousness as i16).abs() as u16;
-    let extraversion_diff = (traits_one.extraversion as i16 - traits_two.extraversion as i16).abs() as u16;
-    let agreeableness_diff = (traits_one.agreeableness as i16 - traits_two.agreeableness as i16).abs() as u16;
-    let neuroticism_diff = (traits_one.neuroticism as i16 - traits_two.neuroticism as i16).abs() as u16;
-
-    // For some traits, complementary values work better (opposites attract)
-    // For others, similarity is better
+// Create an AgentData PDA for `register_agents_batch`, failing if it
+// already exists
+fn create_agent_account<'info>(
+    owner: &Signer<'info>,
+    agent_account: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    name: String,
+    metadata_uri: String,
+    personality_traits: PersonalityTraits,
+    agent_index: u64,
+    now: i64,
+) -> Result<()> {
+    let owner_key = owner.key();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"agent", owner_key.as_ref(), name.as_bytes()],
+        &crate::ID,
+    );
+    require_keys_eq!(agent_account.key(), expected, AgentError::BatchAccountsMismatch);
+    require!(!is_initialized::<AgentData>(agent_account)?, AgentError::AgentAlreadyExists);
+
+    let agent_seeds: &[&[u8]] = &[b"agent", owner_key.as_ref(), name.as_bytes(), &[bump]];
+    create_pda_account(
+        owner,
+        agent_account,
+        system_program,
+        agent_seeds,
+        8 + std::mem::size_of::<AgentData>(),
+    )?;
 
-    // Calculate weighted score
-    let similarity_score = (100 - openness_diff / 2) + // Some similarity is good
-                          (100 - conscientiousness_diff / 2) + // Some similarity is good
-                          (100 - extraversion_diff); // Complementary is good
+    let agent_data = AgentData {
+        owner: owner_key,
+        name,
+        metadata_uri,
+        is_active: true,
+        personality_traits,
+        creation_date: now,
+        match_count: 0,
+        interaction_count: 0,
+        last_active: now,
+        is_frozen: false,
+        parent_one: Pubkey::default(),
+        parent_two: Pubkey::default(),
+        generation: 0,
+        wins: 0,
+        losses: 0,
+        delegate: None,
+        agent_index,
+        xp: 0,
+        level: 0,
+        closed_match_count: 0,
+        transfer_nonce: 0,
+        custom_traits: Vec::new(),
+    };
+    let mut data = agent_account.try_borrow_mut_data()?;
+    agent_data.try_serialize(&mut &mut data[..])
+}
 
-    let complementary_score = extraversion_diff / 2 + // Some difference is good
-                             agreeableness_diff / 3 + // Some difference is good
-                             neuroticism_diff / 3; // Some difference is good
+// Check that the collection accounts are a one-of-one NFT with a master
+// edition, whose metadata names `collection_authority` as update authority.
+// Metadata starts with a key byte, then the update authority and the mint.
+fn verify_collection(
+    collection_mint: &Account<Mint>,
+    collection_metadata: &AccountInfo,
+    collection_edition: &AccountInfo,
+    collection_authority: &Pubkey,
+) -> Result<()> {
+    let mint = collection_mint.key();
+    let (expected_metadata, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    let (expected_edition, _) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref(), b"edition"],
+        &TOKEN_METADATA_PROGRAM_ID,
+    );
+    require!(
+        collection_mint.decimals == 0
+            && collection_mint.supply == 1
+            && collection_metadata.key() == expected_metadata
+            && collection_edition.key() == expected_edition
+            && collection_metadata.owner == &TOKEN_METADATA_PROGRAM_ID
+            && collection_edition.owner == &TOKEN_METADATA_PROGRAM_ID,
+        AgentError::InvalidCollection
+    );
 
-    // Combine scores and normalize to 0-100
-    let raw_score = (similarity_score * 2 + complementary_score) / 5;
-    let normalized_score = if raw_score > 100 { 100 } else { raw_score as u8 };
+    let data = collection_metadata.try_borrow_data()?;
+    require!(data.len() >= 65, AgentError::InvalidCollection);
+    require!(
+        data[1..33] == collection_authority.to_bytes() && data[33..65] == mint.to_bytes(),
+        AgentError::InvalidCollection
+    );
 
-    Ok(normalized_score)
+    Ok(())
 }
 
+// Mint an agent's cNFT to its owner inside the registry's collection, with
+// the collection authority PDA signing the collection verification
 fn mint_agent_cnft<'info>(
-    ctx: Context<RegisterAgent<'info>>,
+    owner: &Signer<'info>,
+    tree_authority: &AccountInfo<'info>,
+    merkle_tree: &AccountInfo<'info>,
+    bubblegum_program: &Program<'info, Bubblegum>,
+    compression_program: &Program<'info, SplAccountCompression>,
+    system_program: &Program<'info, System>,
+    collection: &AgentCollection<'info>,
     metadata: MetadataArgs,
 ) -> Result<()> {
-    // Create the instruction to mint a compressed NFT using Bubblegum
     let cpi_accounts = mpl_bubblegum::accounts::MintToCollectionV1 {
-        tree_authority: ctx.accounts.tree_authority.to_account_info(),
-        leaf_owner: ctx.accounts.owner.to_account_info(),
-        leaf_delegate: ctx.accounts.owner.to_account_info(),
-        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
-        payer: ctx.accounts.owner.to_account_info(),
-        tree_delegate: ctx.accounts.owner.to_account_info(),
-        collection_authority: ctx.accounts.owner.to_account_info(),
-        collection_authority_record_pda: ctx.accounts.owner.to_account_info(), // Optional, depends on setup
-        collection_mint: ctx.accounts.owner.to_account_info(), // Replace with actual collection mint
-        collection_metadata: ctx.accounts.owner.to_account_info(), // Replace with actual collection metadata
-        edition_account: ctx.accounts.owner.to_account_info(), // Replace with actual edition account
-        bubblegum_signer: ctx.accounts.owner.to_account_info(), // Replace with actual bubblegum signer
-        log_wrapper: ctx.accounts.owner.to_account_info(), // Replace with actual log wrapper
-        compression_program: ctx.accounts.compression_program.to_account_info(),
-        token_metadata_program: ctx.accounts.owner.to_account_info(), // Replace with actual token metadata program
-        system_program: ctx.accounts.system_program.to_account_info(),
+        tree_authority: tree_authority.clone(),
+        leaf_owner: owner.to_account_info(),
+        leaf_delegate: owner.to_account_info(),
+        merkle_tree: merkle_tree.clone(),
+        payer: owner.to_account_info(),
+        tree_delegate: owner.to_account_info(), // Agent trees are public
+        collection_authority: collection.collection_authority.to_account_info(),
+        // Bubblegum's id stands in for "no authority record": the update authority signs
+        collection_authority_record_pda: bubblegum_program.to_account_info(),
+        collection_mint: collection.collection_mint.to_account_info(),
+        collection_metadata: collection.collection_metadata.to_account_info(),
+        edition_account: collection.collection_edition.to_account_info(),
+        bubblegum_signer: collection.bubblegum_signer.to_account_info(),
+        log_wrapper: collection.log_wrapper.to_account_info(),
+        compression_program: compression_program.to_account_info(),
+        token_metadata_program: collection.token_metadata_program.to_account_info(),
+        system_program: system_program.to_account_info(),
     };
-
-    // Create the CPI context
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.bubblegum_program.to_account_info(),
-        cpi_accounts,
-    );
-
-    // Execute the CPI call to mint the compressed NFT
-    // Note: This is a simplified example. The actual implementation would need to
-    // match the specific version of Bubblegum being used.
+    let authority_seeds: &[&[u8]] = &[
+        COLLECTION_AUTHORITY_SEED,
+        &[collection.collection_config.authority_bump],
+    ];
     mpl_bubblegum::cpi::mint_to_collection_v1(
-        cpi_ctx,
+        CpiContext::new_with_signer(
+            bubblegum_program.to_account_info(),
+            cpi_accounts,
+            &[authority_seeds],
+        ),
         metadata,
     )?;
 
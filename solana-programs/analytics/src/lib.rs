@@ -0,0 +1,334 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use relationship_cpi::RelationshipStats;
+
+declare_id!("Ana1yt1csPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+pub const ANALYTICS_STATE_SEED: &[u8] = b"analytics";
+pub const EPOCH_METRICS_SEED: &[u8] = b"epoch_metrics";
+const BPS_DENOMINATOR: u128 = 10_000;
+
+#[program]
+pub mod analytics {
+    use super::*;
+
+    // Create the analytics state and open epoch 0. Relationship metrics are
+    // measured from the relationship program's stats as of now.
+    pub fn initialize_analytics(
+        ctx: Context<InitializeAnalytics>,
+        epoch_length: i64,
+    ) -> Result<()> {
+        require!(epoch_length > 0, AnalyticsError::InvalidEpochLength);
+        let stats = ctx.accounts.relationship_stats.load()?;
+
+        let state = &mut ctx.accounts.state;
+        state.authority = ctx.accounts.authority.key();
+        state.influence_mint = ctx.accounts.influence_mint.key();
+        state.epoch_length = epoch_length;
+        state.next_epoch_length = 0;
+        state.current_epoch = 0;
+        state.epoch_start = Clock::get()?.unix_timestamp;
+        state.pending = EpochTotals::default();
+        state.relationships_baseline = stats.total_relationships;
+        state.interactions_baseline = stats.total_interactions;
+        state.bump = *ctx.bumps.get("state").unwrap();
+
+        Ok(())
+    }
+
+    // Change the epoch length, from the next epoch on
+    pub fn set_epoch_length(
+        ctx: Context<UpdateAnalytics>,
+        epoch_length: i64,
+    ) -> Result<()> {
+        require!(epoch_length > 0, AnalyticsError::InvalidEpochLength);
+        ctx.accounts.state.next_epoch_length = epoch_length;
+
+        emit!(EpochLengthUpdated { epoch_length });
+
+        Ok(())
+    }
+
+    // Hand the analytics state to another key, such as the admin multisig authority
+    pub fn set_analytics_authority(
+        ctx: Context<UpdateAnalytics>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let previous_authority = state.authority;
+        state.authority = authority;
+
+        emit!(AnalyticsAuthorityUpdated {
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Registration hook invoked by the registry's `register_agent`
+    pub fn on_agent_registered(ctx: Context<RegistryHook>) -> Result<()> {
+        let pending = &mut ctx.accounts.state.pending;
+        pending.new_agents = pending.new_agents.saturating_add(1);
+        Ok(())
+    }
+
+    // Match hook invoked by the registry's `accept_match`
+    pub fn on_match(ctx: Context<RegistryHook>) -> Result<()> {
+        let pending = &mut ctx.accounts.state.pending;
+        pending.matches = pending.matches.saturating_add(1);
+        Ok(())
+    }
+
+    // Supply hooks invoked by the influence program's `mint_tokens` and `burn_tokens`
+    pub fn on_tokens_minted(ctx: Context<InfluenceHook>, amount: u64) -> Result<()> {
+        let pending = &mut ctx.accounts.state.pending;
+        pending.tokens_minted = pending.tokens_minted.saturating_add(amount);
+        Ok(())
+    }
+
+    pub fn on_tokens_burned(ctx: Context<InfluenceHook>, amount: u64) -> Result<()> {
+        let pending = &mut ctx.accounts.state.pending;
+        pending.tokens_burned = pending.tokens_burned.saturating_add(amount);
+        Ok(())
+    }
+
+    // Transfer hook invoked by the influence program's `transfer_tokens` and
+    // `transfer_tokens_light`
+    pub fn on_tokens_transferred(ctx: Context<InfluenceHook>, amount: u64) -> Result<()> {
+        let pending = &mut ctx.accounts.state.pending;
+        pending.tokens_transferred = pending.tokens_transferred.saturating_add(amount);
+        pending.transfers = pending.transfers.saturating_add(1);
+        Ok(())
+    }
+
+    // Crank: once the current epoch is over, write its metrics to a new
+    // EpochMetrics account and open the next epoch. Anyone may call it and
+    // pays the account's rent. Epochs with no crank are folded into the next
+    // one that closes, which then spans several epoch lengths.
+    pub fn close_epoch(ctx: Context<CloseEpoch>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.state;
+        let elapsed = now.saturating_sub(state.epoch_start) / state.epoch_length;
+        require!(elapsed > 0, AnalyticsError::EpochNotOver);
+        let end = state.epoch_start.saturating_add(elapsed.saturating_mul(state.epoch_length));
+
+        let stats = ctx.accounts.relationship_stats.load()?;
+        let token_supply = ctx.accounts.influence_mint.supply;
+        let totals = state.pending;
+        let velocity_bps = if token_supply == 0 {
+            0
+        } else {
+            (totals.tokens_transferred as u128 * BPS_DENOMINATOR / token_supply as u128) as u64
+        };
+
+        let metrics = &mut ctx.accounts.epoch_metrics;
+        metrics.epoch = state.current_epoch;
+        metrics.start = state.epoch_start;
+        metrics.end = end;
+        metrics.totals = totals;
+        metrics.new_relationships = stats.total_relationships.saturating_sub(state.relationships_baseline);
+        metrics.interactions = stats.total_interactions.saturating_sub(state.interactions_baseline);
+        metrics.active_relationships = stats.active_relationships;
+        metrics.token_supply = token_supply;
+        metrics.velocity_bps = velocity_bps;
+        metrics.bump = *ctx.bumps.get("epoch_metrics").unwrap();
+
+        emit!(EpochClosed {
+            epoch: metrics.epoch,
+            start: metrics.start,
+            end,
+            new_agents: totals.new_agents,
+            matches: totals.matches,
+            new_relationships: metrics.new_relationships,
+            interactions: metrics.interactions,
+            active_relationships: metrics.active_relationships,
+            tokens_transferred: totals.tokens_transferred,
+            velocity_bps,
+        });
+
+        state.current_epoch = state.current_epoch.saturating_add(1);
+        state.epoch_start = end;
+        state.pending = EpochTotals::default();
+        state.relationships_baseline = stats.total_relationships;
+        state.interactions_baseline = stats.total_interactions;
+        if state.next_epoch_length > 0 {
+            state.epoch_length = state.next_epoch_length;
+            state.next_epoch_length = 0;
+        }
+
+        Ok(())
+    }
+}
+
+// Account structures
+// Running totals for the open epoch, written to by the hooks
+#[account]
+pub struct AnalyticsState {
+    pub authority: Pubkey,
+    pub influence_mint: Pubkey,       // Mint whose supply velocity is measured against
+    pub epoch_length: i64,            // Seconds
+    pub next_epoch_length: i64,       // Applied when the current epoch closes; 0 for no change
+    pub current_epoch: u64,
+    pub epoch_start: i64,
+    pub pending: EpochTotals,
+    pub relationships_baseline: u64,  // Relationship stats counters at epoch start
+    pub interactions_baseline: u64,
+    pub bump: u8,
+}
+
+// One closed epoch's metrics
+#[account]
+pub struct EpochMetrics {
+    pub epoch: u64,
+    pub start: i64,
+    pub end: i64,
+    pub totals: EpochTotals,
+    pub new_relationships: u64,
+    pub interactions: u64,
+    pub active_relationships: u64,  // At close
+    pub token_supply: u64,          // At close
+    pub velocity_bps: u64,          // Transfer volume over supply, in basis points
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeAnalytics<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<AnalyticsState>(),
+        seeds = [ANALYTICS_STATE_SEED],
+        bump
+    )]
+    pub state: Account<'info, AnalyticsState>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(seeds = [b"stats"], bump, seeds::program = relationship_cpi::ID)]
+    pub relationship_stats: AccountLoader<'info, RelationshipStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAnalytics<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ANALYTICS_STATE_SEED],
+        bump = state.bump,
+        has_one = authority @ AnalyticsError::Unauthorized,
+    )]
+    pub state: Account<'info, AnalyticsState>,
+}
+
+#[derive(Accounts)]
+pub struct RegistryHook<'info> {
+    #[account(
+        seeds = [agent_registry_cpi::ANALYTICS_HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = agent_registry_cpi::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    #[account(mut, seeds = [ANALYTICS_STATE_SEED], bump = state.bump)]
+    pub state: Account<'info, AnalyticsState>,
+}
+
+#[derive(Accounts)]
+pub struct InfluenceHook<'info> {
+    #[account(
+        seeds = [influence_cpi::ANALYTICS_HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = influence_cpi::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    #[account(mut, seeds = [ANALYTICS_STATE_SEED], bump = state.bump)]
+    pub state: Account<'info, AnalyticsState>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEpoch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ANALYTICS_STATE_SEED],
+        bump = state.bump,
+        has_one = influence_mint,
+    )]
+    pub state: Account<'info, AnalyticsState>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<EpochMetrics>(),
+        seeds = [EPOCH_METRICS_SEED, &state.current_epoch.to_le_bytes()],
+        bump
+    )]
+    pub epoch_metrics: Account<'info, EpochMetrics>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(seeds = [b"stats"], bump, seeds::program = relationship_cpi::ID)]
+    pub relationship_stats: AccountLoader<'info, RelationshipStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+// Metrics reported through hooks, summed over an epoch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct EpochTotals {
+    pub new_agents: u64,
+    pub matches: u64,
+    pub tokens_minted: u64,
+    pub tokens_burned: u64,
+    pub tokens_transferred: u64,
+    pub transfers: u64,
+}
+
+// Events
+#[event]
+pub struct EpochClosed {
+    pub epoch: u64,
+    pub start: i64,
+    pub end: i64,
+    pub new_agents: u64,
+    pub matches: u64,
+    pub new_relationships: u64,
+    pub interactions: u64,
+    pub active_relationships: u64,
+    pub tokens_transferred: u64,
+    pub velocity_bps: u64,
+}
+
+#[event]
+pub struct EpochLengthUpdated {
+    pub epoch_length: i64,
+}
+
+#[event]
+pub struct AnalyticsAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum AnalyticsError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Epoch length must be positive")]
+    InvalidEpochLength,
+    #[msg("The current epoch has not ended yet")]
+    EpochNotOver,
+}
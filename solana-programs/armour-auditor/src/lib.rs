@@ -0,0 +1,427 @@
+// Cross-checks state the registry, influence and relationship programs keep
+// in more than one place, for monitoring and post-incident forensics.
+// `Auditor` loads a `Snapshot` of the accounts involved and `Snapshot::audit`
+// reports every `Discrepancy` between them, with the offending keys:
+//
+// - an agent's open matches (match_count less closed_match_count) against
+//   the MatchData accounts naming it
+// - an influence mint's supply against its mint and burn records
+// - a relationship's interaction_count and nonce against its interaction PDAs
+//
+// Accounts are fetched with separate RPC calls, so a transaction landing
+// mid-snapshot can cause a one-off discrepancy; the armour-auditor binary
+// only reports those seen in consecutive rounds.
+use std::collections::HashMap;
+use std::fmt;
+
+use anchor_lang::{AccountDeserialize, Discriminator, ZeroCopy};
+use armour_sdk::agent_registry::{self, AgentData, MatchData};
+use armour_sdk::influence::{self, BurnRecord, MintConfig, MintRecord};
+use armour_sdk::relationship::{self, InteractionData, InteractionNonce, RelationshipData};
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+
+// Leading fields read from record accounts; the rest is never fetched.
+// MintRecord and BurnRecord: mint, recipient or owner, amount.
+const SUPPLY_RECORD_LEN: usize = 8 + 32 + 32 + 8;
+// InteractionData: relationship, index
+const INTERACTION_HEADER_LEN: usize = 8 + 32 + 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SupplyRecord {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InteractionRef {
+    pub address: Pubkey,
+    pub relationship: Pubkey,
+    pub index: u64,
+}
+
+#[derive(Default)]
+pub struct Snapshot {
+    pub slot: u64,  // Slot when the snapshot started
+    pub agents: Vec<(Pubkey, AgentData)>,
+    pub matches: Vec<(Pubkey, MatchData)>,
+    pub supplies: Vec<(Pubkey, u64)>,  // Supply of each mint with a MintConfig
+    pub mint_records: Vec<SupplyRecord>,
+    pub burn_records: Vec<SupplyRecord>,
+    pub relationships: Vec<(Pubkey, RelationshipData)>,
+    pub legacy_relationships: usize,  // Not yet migrated to the zero-copy layout; not audited
+    pub nonces: HashMap<Pubkey, u64>,  // Relationship -> next interaction index
+    pub interactions: Vec<InteractionRef>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Discrepancy {
+    // An agent's open match count (match_count less closed_match_count)
+    // differs from the MatchData accounts naming it
+    MatchCount { agent: Pubkey, match_count: u32, matches: Vec<Pubkey> },
+    // A MatchData account names an agent that does not exist
+    OrphanMatch { match_data: Pubkey, agent: Pubkey },
+    // A mint's supply differs from its records' minted minus burned amount,
+    // e.g. tokens minted or burned outside the influence program
+    Supply { mint: Pubkey, supply: u64, minted: u128, burned: u128 },
+    // More interaction PDAs than the relationship's interaction_count.
+    // Fewer is expected: compressed and Light interactions have no PDA.
+    InteractionCount { relationship: Pubkey, interaction_count: u32, interactions: Vec<Pubkey> },
+    // An interaction PDA at or past the relationship's next nonce index
+    InteractionIndex { relationship: Pubkey, interaction: Pubkey, index: u64, next_index: u64 },
+}
+
+impl Discrepancy {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Discrepancy::MatchCount { .. } => "match_count",
+            Discrepancy::OrphanMatch { .. } => "orphan_match",
+            Discrepancy::Supply { .. } => "supply",
+            Discrepancy::InteractionCount { .. } => "interaction_count",
+            Discrepancy::InteractionIndex { .. } => "interaction_index",
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let keys = |keys: &[Pubkey]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let details = match self {
+            Discrepancy::MatchCount { agent, match_count, matches } => json!({
+                "agent": agent.to_string(),
+                "match_count": match_count,
+                "matches": keys(matches),
+            }),
+            Discrepancy::OrphanMatch { match_data, agent } => json!({
+                "match_data": match_data.to_string(),
+                "agent": agent.to_string(),
+            }),
+            Discrepancy::Supply { mint, supply, minted, burned } => json!({
+                "mint": mint.to_string(),
+                "supply": supply.to_string(),
+                "minted": minted.to_string(),
+                "burned": burned.to_string(),
+            }),
+            Discrepancy::InteractionCount { relationship, interaction_count, interactions } => json!({
+                "relationship": relationship.to_string(),
+                "interaction_count": interaction_count,
+                "interactions": keys(interactions),
+            }),
+            Discrepancy::InteractionIndex { relationship, interaction, index, next_index } => json!({
+                "relationship": relationship.to_string(),
+                "interaction": interaction.to_string(),
+                "index": index,
+                "next_index": next_index,
+            }),
+        };
+        json!({ "kind": self.kind(), "details": details })
+    }
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::MatchCount { agent, match_count, matches } => write!(
+                f,
+                "agent {} has {} open matches but {} match accounts",
+                agent,
+                match_count,
+                matches.len()
+            ),
+            Discrepancy::OrphanMatch { match_data, agent } => {
+                write!(f, "match {} names missing agent {}", match_data, agent)
+            }
+            Discrepancy::Supply { mint, supply, minted, burned } => write!(
+                f,
+                "mint {} has supply {} but records mint {} and burn {}",
+                mint, supply, minted, burned
+            ),
+            Discrepancy::InteractionCount { relationship, interaction_count, interactions } => write!(
+                f,
+                "relationship {} has interaction_count {} but {} interaction accounts",
+                relationship,
+                interaction_count,
+                interactions.len()
+            ),
+            Discrepancy::InteractionIndex { relationship, interaction, index, next_index } => write!(
+                f,
+                "interaction {} of relationship {} has index {} at or past the nonce's {}",
+                interaction, relationship, index, next_index
+            ),
+        }
+    }
+}
+
+impl Snapshot {
+    // Every discrepancy, grouped by check and ordered by key within each
+    pub fn audit(&self) -> Vec<Discrepancy> {
+        let mut found = self.audit_matches();
+        found.extend(self.audit_supply());
+        found.extend(self.audit_interactions());
+        found
+    }
+
+    pub fn audit_matches(&self) -> Vec<Discrepancy> {
+        let mut by_agent: HashMap<Pubkey, Vec<Pubkey>> =
+            self.agents.iter().map(|(key, _)| (*key, Vec::new())).collect();
+        let mut found = Vec::new();
+        for (key, match_data) in &self.matches {
+            for agent in [match_data.agent_one, match_data.agent_two] {
+                match by_agent.get_mut(&agent) {
+                    Some(matches) => matches.push(*key),
+                    None => found.push(Discrepancy::OrphanMatch { match_data: *key, agent }),
+                }
+            }
+        }
+        for (key, agent) in &self.agents {
+            let mut matches = by_agent.remove(key).unwrap_or_default();
+            // match_count saturates rather than overflowing
+            let open = agent.match_count.saturating_sub(agent.closed_match_count);
+            if open != matches.len().min(u32::MAX as usize) as u32 {
+                matches.sort();
+                found.push(Discrepancy::MatchCount { agent: *key, match_count: open, matches });
+            }
+        }
+        found.sort();
+        found
+    }
+
+    pub fn audit_supply(&self) -> Vec<Discrepancy> {
+        let total = |records: &[SupplyRecord], mint: &Pubkey| -> u128 {
+            records.iter().filter(|record| record.mint == *mint).map(|record| record.amount as u128).sum()
+        };
+        let mut found: Vec<Discrepancy> = self
+            .supplies
+            .iter()
+            .filter_map(|(mint, supply)| {
+                let minted = total(&self.mint_records, mint);
+                let burned = total(&self.burn_records, mint);
+                (*supply as i128 != minted as i128 - burned as i128)
+                    .then_some(Discrepancy::Supply { mint: *mint, supply: *supply, minted, burned })
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    // Interactions of relationships that no longer exist (the duplicate of a
+    // merge) are not checked
+    pub fn audit_interactions(&self) -> Vec<Discrepancy> {
+        let mut by_relationship: HashMap<Pubkey, Vec<&InteractionRef>> = HashMap::new();
+        for interaction in &self.interactions {
+            by_relationship.entry(interaction.relationship).or_default().push(interaction);
+        }
+        let mut found = Vec::new();
+        for (key, relationship) in &self.relationships {
+            let Some(interactions) = by_relationship.get(key) else {
+                continue;
+            };
+            if interactions.len() > relationship.interaction_count as usize {
+                let mut interactions: Vec<Pubkey> = interactions.iter().map(|i| i.address).collect();
+                interactions.sort();
+                found.push(Discrepancy::InteractionCount {
+                    relationship: *key,
+                    interaction_count: relationship.interaction_count,
+                    interactions,
+                });
+            }
+            if let Some(next_index) = self.nonces.get(key) {
+                for interaction in interactions.iter().filter(|i| i.index >= *next_index) {
+                    found.push(Discrepancy::InteractionIndex {
+                        relationship: *key,
+                        interaction: interaction.address,
+                        index: interaction.index,
+                        next_index: *next_index,
+                    });
+                }
+            }
+        }
+        found.sort();
+        found
+    }
+}
+
+pub struct Auditor {
+    pub rpc: RpcClient,
+}
+
+impl Auditor {
+    pub fn new(rpc_url: String) -> Self {
+        Self { rpc: RpcClient::new(rpc_url) }
+    }
+
+    pub async fn snapshot(&self) -> Result<Snapshot, AuditError> {
+        let slot = self.rpc.get_slot().await?;
+
+        let agents = self.decode_all::<AgentData>(agent_registry::ID).await?;
+        let matches = self.decode_all::<MatchData>(agent_registry::ID).await?;
+
+        let mut supplies = Vec::new();
+        for (_, mint_config) in self.decode_all::<MintConfig>(influence::ID).await? {
+            let supply = self.rpc.get_token_supply(&mint_config.mint).await?;
+            let supply: u64 = supply
+                .amount
+                .parse()
+                .map_err(|_| AuditError::Decode(format!("supply of {}: {}", mint_config.mint, supply.amount)))?;
+            supplies.push((mint_config.mint, supply));
+        }
+        let mint_records = self.supply_records(MintRecord::DISCRIMINATOR).await?;
+        let burn_records = self.supply_records(BurnRecord::DISCRIMINATOR).await?;
+
+        let size = 8 + std::mem::size_of::<RelationshipData>();
+        let relationships = self
+            .program_accounts(relationship::ID, RelationshipData::DISCRIMINATOR, Some(size), None)
+            .await?
+            .into_iter()
+            .map(|(key, data)| Ok((key, read_zero_copy::<RelationshipData>(&key, &data)?)))
+            .collect::<Result<_, AuditError>>()?;
+        let legacy_relationships = self
+            .program_accounts(
+                relationship::ID,
+                RelationshipData::DISCRIMINATOR,
+                Some(relationship::LEGACY_RELATIONSHIP_SPACE),
+                Some(0),
+            )
+            .await?
+            .len();
+        let nonces = self
+            .decode_all::<InteractionNonce>(relationship::ID)
+            .await?
+            .into_iter()
+            .map(|(_, nonce)| (nonce.relationship, nonce.next_index))
+            .collect();
+        let interactions = self
+            .program_accounts(
+                relationship::ID,
+                InteractionData::DISCRIMINATOR,
+                None,
+                Some(INTERACTION_HEADER_LEN),
+            )
+            .await?
+            .into_iter()
+            .map(|(address, data)| {
+                Ok(InteractionRef {
+                    address,
+                    relationship: read_pubkey(&address, &data, 8)?,
+                    index: read_u64(&address, &data, 40)?,
+                })
+            })
+            .collect::<Result<_, AuditError>>()?;
+
+        Ok(Snapshot {
+            slot,
+            agents,
+            matches,
+            supplies,
+            mint_records,
+            burn_records,
+            relationships,
+            legacy_relationships,
+            nonces,
+            interactions,
+        })
+    }
+
+    async fn decode_all<T: AccountDeserialize + Discriminator>(
+        &self,
+        program_id: Pubkey,
+    ) -> Result<Vec<(Pubkey, T)>, AuditError> {
+        self.program_accounts(program_id, T::DISCRIMINATOR, None, None)
+            .await?
+            .into_iter()
+            .map(|(key, data)| {
+                let account = T::try_deserialize(&mut data.as_slice())
+                    .map_err(|err| AuditError::Decode(format!("{}: {}", key, err)))?;
+                Ok((key, account))
+            })
+            .collect()
+    }
+
+    async fn supply_records(&self, discriminator: [u8; 8]) -> Result<Vec<SupplyRecord>, AuditError> {
+        self.program_accounts(influence::ID, discriminator, None, Some(SUPPLY_RECORD_LEN))
+            .await?
+            .into_iter()
+            .map(|(address, data)| {
+                Ok(SupplyRecord {
+                    address,
+                    mint: read_pubkey(&address, &data, 8)?,
+                    amount: read_u64(&address, &data, 72)?,
+                })
+            })
+            .collect()
+    }
+
+    // Accounts of one type, optionally of an exact size, with only the first
+    // `length` bytes of their data when given
+    async fn program_accounts(
+        &self,
+        program_id: Pubkey,
+        discriminator: [u8; 8],
+        size: Option<usize>,
+        length: Option<usize>,
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, AuditError> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &discriminator))];
+        if let Some(size) = size {
+            filters.push(RpcFilterType::DataSize(size as u64));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: length.map(|length| UiDataSliceConfig { offset: 0, length }),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self.rpc.get_program_accounts_with_config(&program_id, config).await?;
+        Ok(accounts.into_iter().map(|(key, account)| (key, account.data)).collect())
+    }
+}
+
+// Errors
+#[derive(Debug)]
+pub enum AuditError {
+    Rpc(ClientError),
+    Decode(String),
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::Rpc(err) => write!(f, "RPC error: {}", err),
+            AuditError::Decode(err) => write!(f, "Account decoding failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+impl From<ClientError> for AuditError {
+    fn from(err: ClientError) -> Self {
+        AuditError::Rpc(err)
+    }
+}
+
+// Helper functions
+fn read_zero_copy<T: ZeroCopy>(address: &Pubkey, data: &[u8]) -> Result<T, AuditError> {
+    let size = std::mem::size_of::<T>();
+    data.get(8..8 + size)
+        .map(bytemuck::pod_read_unaligned)
+        .ok_or_else(|| AuditError::Decode(format!("{} is too short", address)))
+}
+
+fn read_pubkey(address: &Pubkey, data: &[u8], offset: usize) -> Result<Pubkey, AuditError> {
+    data.get(offset..offset + 32)
+        .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+        .ok_or_else(|| AuditError::Decode(format!("{} is too short", address)))
+}
+
+fn read_u64(address: &Pubkey, data: &[u8], offset: usize) -> Result<u64, AuditError> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| AuditError::Decode(format!("{} is too short", address)))
+}
@@ -0,0 +1,103 @@
+// armour-auditor --rpc <url> [--interval <secs>] [--once] [--json]
+//
+// Audits every `--interval` seconds (default 60). A discrepancy is reported
+// once it shows up in two consecutive rounds, and reported as resolved when
+// it disappears. With --once, audits a single time, prints everything found
+// and exits 1 if anything was.
+use std::collections::HashSet;
+use std::process::exit;
+use std::time::Duration;
+
+use armour_auditor::{Auditor, Discrepancy};
+use serde_json::json;
+
+const USAGE: &str = "usage: armour-auditor --rpc <url> [--interval <secs>] [--once] [--json]";
+
+struct Args {
+    rpc: String,
+    interval: Duration,
+    once: bool,
+    json: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut rpc = None;
+    let mut interval = Duration::from_secs(60);
+    let mut once = false;
+    let mut json = false;
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--rpc" => rpc = Some(value("--rpc")?),
+            "--interval" => {
+                interval = Duration::from_secs(value("--interval")?.parse().map_err(|_| "invalid --interval")?)
+            }
+            "--once" => once = true,
+            "--json" => json = true,
+            flag => return Err(format!("unknown argument {}", flag)),
+        }
+    }
+    Ok(Args { rpc: rpc.ok_or("missing --rpc")?, interval, once, json })
+}
+
+#[tokio::main]
+async fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        exit(2);
+    });
+    let auditor = Auditor::new(args.rpc.clone());
+
+    if args.once {
+        let snapshot = auditor.snapshot().await.unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        });
+        let found = snapshot.audit();
+        for discrepancy in &found {
+            report(&args, "found", snapshot.slot, discrepancy);
+        }
+        if snapshot.legacy_relationships > 0 {
+            eprintln!("skipped {} relationships on the legacy layout", snapshot.legacy_relationships);
+        }
+        exit(if found.is_empty() { 0 } else { 1 });
+    }
+
+    let mut previous: HashSet<Discrepancy> = HashSet::new();
+    let mut reported: HashSet<Discrepancy> = HashSet::new();
+    loop {
+        match auditor.snapshot().await {
+            Ok(snapshot) => {
+                let current: HashSet<Discrepancy> = snapshot.audit().into_iter().collect();
+                for discrepancy in current.intersection(&previous) {
+                    if reported.insert(discrepancy.clone()) {
+                        report(&args, "found", snapshot.slot, discrepancy);
+                    }
+                }
+                reported.retain(|discrepancy| {
+                    let open = current.contains(discrepancy);
+                    if !open {
+                        report(&args, "resolved", snapshot.slot, discrepancy);
+                    }
+                    open
+                });
+                previous = current;
+            }
+            // Transient RPC failures skip a round; the previous round still counts
+            Err(err) => eprintln!("audit failed: {}", err),
+        }
+        tokio::time::sleep(args.interval).await;
+    }
+}
+
+fn report(args: &Args, status: &str, slot: u64, discrepancy: &Discrepancy) {
+    if args.json {
+        let mut line = discrepancy.to_json();
+        line["status"] = json!(status);
+        line["slot"] = json!(slot);
+        println!("{}", line);
+    } else {
+        println!("[slot {}] {}: {}", slot, status, discrepancy);
+    }
+}
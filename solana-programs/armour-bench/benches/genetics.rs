@@ -0,0 +1,32 @@
+use armour_core::{PersonalityTraits, MAX_TRAIT_VALUE, TRAIT_COUNT};
+use breeding::{combine_traits, GeneticsParams};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn genetics() -> GeneticsParams {
+    GeneticsParams {
+        dominance_bps: [6_000; TRAIT_COUNT],
+        min_mutation_chance_bps: 500,
+        max_mutation_chance_bps: 2_000,
+        max_mutation: 10,
+        trait_caps: [MAX_TRAIT_VALUE; 8],
+    }
+}
+
+fn breeding_math(c: &mut Criterion) {
+    let parent_one = PersonalityTraits::from_values([80, 20, 65, 40, 10, 90, 55, 70]);
+    let parent_two = PersonalityTraits::from_values([30, 75, 50, 95, 60, 45, 85, 15]);
+    let params = genetics();
+    let seed = [7u8; 32];
+
+    c.bench_function("derive_entropy", |bench| {
+        bench.iter(|| armour_randomness::derive(black_box(&seed), &[b"breed".as_ref(), &[1u8, 2, 3]]))
+    });
+
+    c.bench_function("combine_traits", |bench| {
+        let entropy = armour_randomness::derive(&seed, &[b"breed".as_ref()]);
+        bench.iter(|| combine_traits(black_box(&parent_one), black_box(&parent_two), &params, 3, &entropy))
+    });
+}
+
+criterion_group!(benches, breeding_math);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+use armour_core::{calculate_compatibility_score, PersonalityTraits, TRAIT_COUNT};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn traits(seed: u8) -> PersonalityTraits {
+    let mut values = [0u8; TRAIT_COUNT];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = seed.wrapping_mul(31).wrapping_add(i as u8 * 17) % 101;
+    }
+    PersonalityTraits::from_values(values)
+}
+
+fn scoring(c: &mut Criterion) {
+    let (a, b) = (traits(3), traits(200));
+    c.bench_function("calculate_compatibility_score", |bench| {
+        bench.iter(|| calculate_compatibility_score(black_box(&a), black_box(&b)))
+    });
+
+    // A matchmaking pass: one agent against a pool
+    let pool: Vec<PersonalityTraits> = (0..=255).map(traits).collect();
+    c.bench_function("score_against_256", |bench| {
+        bench.iter(|| {
+            pool.iter()
+                .map(|candidate| calculate_compatibility_score(black_box(&a), candidate))
+                .max()
+        })
+    });
+}
+
+criterion_group!(benches, scoring);
+criterion_main!(benches);
@@ -0,0 +1,145 @@
+// Measures compute units for the main instructions against the programs'
+// BPF builds and exits non-zero when any exceeds its budget (see CU_BUDGETS).
+use armour_bench::{budget, within_budget};
+use armour_test_utils::{bpf_program_test, ArmourTest, LAMPORTS_PER_SOL};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+
+use agent_registry_cpi::PersonalityTraits;
+use relationship_cpi::{InteractionType, RelationshipType};
+
+struct Profile {
+    results: Vec<(&'static str, u64)>,
+}
+
+impl Profile {
+    async fn measure(
+        &mut self,
+        test: &mut ArmourTest,
+        name: &'static str,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) {
+        let units = test
+            .compute_units(&[instruction.clone()], signers)
+            .await
+            .unwrap_or_else(|err| panic!("{} failed: {:?}", name, err));
+        self.results.push((name, units));
+        // Apply it so later instructions see the state it creates
+        test.process(&[instruction], signers).await.expect("instruction failed after simulation");
+    }
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let failed = runtime.block_on(profile());
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+async fn profile() -> bool {
+    let mut test = ArmourTest::start_with(bpf_program_test()).await;
+    let mut profile = Profile { results: Vec::new() };
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+
+    let register = |owner: &Keypair, name: &str, traits: [u8; 8]| {
+        armour_sdk::register_agent(
+            owner.pubkey(),
+            name.to_string(),
+            format!("https://example.com/{}.json", name),
+            PersonalityTraits::from_values(traits),
+            test.merkle_tree,
+            test.collection_mint,
+            None,
+        )
+    };
+    let register_alice = register(&alice, "alice", [80, 20, 65, 40, 10, 90, 55, 70]);
+    let register_bob = register(&bob, "bob", [30, 75, 50, 95, 60, 45, 85, 15]);
+    profile.measure(&mut test, "register_agent", register_alice, &[&alice]).await;
+    test.process(&[register_bob], &[&bob]).await.expect("registration failed");
+
+    let alice_agent = agent_registry_cpi::pda::find_agent_address(&alice.pubkey(), "alice").0;
+    let bob_agent = agent_registry_cpi::pda::find_agent_address(&bob.pubkey(), "bob").0;
+    let (agent_one, agent_two) = armour_sdk::canonical_pair(alice_agent, bob_agent);
+
+    let compatibility = agent_registry_cpi::builders::calculate_compatibility(
+        agent_one,
+        agent_two,
+        agent_registry_cpi::CompatibilityAlgorithm::Heuristic,
+        false,
+    );
+    profile.measure(&mut test, "calculate_compatibility", compatibility, &[]).await;
+
+    let propose = agent_registry_cpi::builders::propose_match(alice.pubkey(), alice_agent, bob_agent);
+    profile.measure(&mut test, "propose_match", propose, &[&alice]).await;
+    let store = agent_registry_cpi::builders::store_compatibility(
+        alice.pubkey(),
+        agent_one,
+        agent_two,
+        agent_registry_cpi::CompatibilityAlgorithm::Heuristic,
+        false,
+    );
+    test.process(&[store], &[&alice]).await.expect("storing compatibility failed");
+    let accept = agent_registry_cpi::builders::accept_match(
+        bob.pubkey(),
+        alice.pubkey(),
+        alice_agent,
+        bob_agent,
+        0,
+        false,
+        [None, None],
+    );
+    profile.measure(&mut test, "accept_match", accept, &[&bob]).await;
+
+    let create = relationship_cpi::builders::create_relationship(
+        alice.pubkey(),
+        agent_one,
+        agent_two,
+        None,
+        RelationshipType::Friends,
+        50,
+    );
+    profile.measure(&mut test, "create_relationship", create, &[&alice]).await;
+
+    let relationship = relationship_cpi::pda::find_relationship_address(&agent_one, &agent_two).0;
+    let record = relationship_cpi::builders::record_interaction(
+        alice.pubkey(),
+        alice.pubkey(),
+        relationship,
+        agent_one,
+        agent_two,
+        0,
+        InteractionType::Conversation,
+        "hello".to_string(),
+        None,
+        None,
+    );
+    profile.measure(&mut test, "record_interaction", record, &[&alice]).await;
+
+    test.mint_influence(&alice.pubkey(), 1_000_000).await;
+    let timestamp = test.clock().await.unix_timestamp;
+    let transfer = influence_cpi::builders::transfer_tokens(
+        alice.pubkey(),
+        alice.pubkey(),
+        test.influence_mint,
+        bob.pubkey(),
+        1_000,
+        "tip".to_string(),
+        timestamp,
+        None,
+    );
+    profile.measure(&mut test, "transfer_tokens", transfer, &[&alice]).await;
+
+    let mut failed = false;
+    println!("{:<28} {:>10} {:>10}", "instruction", "units", "budget");
+    for (name, units) in &profile.results {
+        let budget = budget(name).unwrap_or(u64::MAX);
+        let ok = within_budget(budget, *units);
+        failed |= !ok;
+        println!("{:<28} {:>10} {:>10} {}", name, units, budget, if ok { "" } else { "OVER BUDGET" });
+    }
+    failed
+}
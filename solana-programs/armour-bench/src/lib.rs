@@ -0,0 +1,26 @@
+// Compute unit budgets enforced by `cu_profile`. A measurement more than
+// CU_TOLERANCE_BPS over its budget fails the run; raise a budget only
+// alongside the change that justifies it.
+pub const CU_TOLERANCE_BPS: u64 = 1_000;
+
+pub const CU_BUDGETS: &[(&str, u64)] = &[
+    ("register_agent", 40_000),
+    ("calculate_compatibility", 8_000),
+    ("propose_match", 20_000),
+    ("accept_match", 70_000),
+    ("create_relationship", 45_000),
+    ("record_interaction", 60_000),
+    ("transfer_tokens", 45_000),
+];
+
+pub fn budget(instruction: &str) -> Option<u64> {
+    CU_BUDGETS
+        .iter()
+        .find(|(name, _)| *name == instruction)
+        .map(|(_, budget)| *budget)
+}
+
+// Whether `units` is within the instruction's budget plus tolerance
+pub fn within_budget(budget: u64, units: u64) -> bool {
+    units <= budget + budget * CU_TOLERANCE_BPS / 10_000
+}
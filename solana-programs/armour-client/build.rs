@@ -0,0 +1,35 @@
+// Generate the clients from the programs' sources on every build, so they
+// always match the programs in this tree
+use std::path::{Path, PathBuf};
+
+use armour_codegen::idl::IdlTypeDefinition;
+use armour_codegen::{declared_id, generate, parse_program, parse_shared_types, CodegenError};
+
+const PROGRAMS: [&str; 3] = ["agent-registry", "influence", "relationship"];
+// Library crates whose types the programs use in instructions, accounts and events
+const SHARED: [&str; 2] = ["armour-core", "armour-light"];
+
+fn main() {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+
+    let mut shared = Vec::new();
+    for library in SHARED {
+        let crate_dir = format!("../{}", library);
+        println!("cargo:rerun-if-changed={}/src", crate_dir);
+        let types = parse_shared_types(Path::new(&crate_dir), &out_dir).unwrap_or_else(|err| panic!("{}: {}", library, err));
+        shared.extend(types);
+    }
+
+    for program in PROGRAMS {
+        let crate_dir = format!("../{}", program);
+        println!("cargo:rerun-if-changed={}/src", crate_dir);
+        client(Path::new(&crate_dir), &shared, &out_dir).unwrap_or_else(|err| panic!("{}: {}", program, err));
+    }
+}
+
+fn client(crate_dir: &Path, shared: &[IdlTypeDefinition], out_dir: &Path) -> Result<(), CodegenError> {
+    let idl = parse_program(crate_dir)?;
+    let client = generate(&idl, &declared_id(crate_dir)?, shared)?;
+    std::fs::write(out_dir.join(format!("{}.rs", idl.name)), client)?;
+    Ok(())
+}
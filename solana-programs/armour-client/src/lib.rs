@@ -0,0 +1,17 @@
+// Typed clients for the registry, influence and relationship programs,
+// generated from their IDLs at build time (see armour-codegen) so they change
+// with the programs. Each module has the program's `ID` and, as the program
+// defines them, `types`, `accounts` and `events` (structs with their
+// discriminators and a `decode` for raw data), `instructions` (an accounts
+// struct, args struct and builder per instruction) and `errors`.
+pub mod agent_registry {
+    include!(concat!(env!("OUT_DIR"), "/agent_registry.rs"));
+}
+
+pub mod influence {
+    include!(concat!(env!("OUT_DIR"), "/influence.rs"));
+}
+
+pub mod relationship {
+    include!(concat!(env!("OUT_DIR"), "/relationship.rs"));
+}
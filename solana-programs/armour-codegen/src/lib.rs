@@ -0,0 +1,510 @@
+// Typed Rust clients generated from the programs' Anchor IDLs: instruction
+// builders, account and event structs with their discriminators and
+// decoders, and each program's error codes. IDLs are parsed from the
+// programs' sources with the same parser `anchor build` uses, or loaded from
+// IDL JSON files, so a client regenerated at build time (see armour-client)
+// picks up new instructions, fields and errors on its own.
+//
+// Types a program borrows from a library crate (armour-core, armour-light)
+// don't appear in its IDL. Pass them as shared types, and the generator
+// includes the ones the program references.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use anchor_syn::codegen::program::common::sighash;
+use heck::SnakeCase;
+
+pub use anchor_syn::idl;
+
+use idl::{EnumFields, Idl, IdlAccount, IdlAccountItem, IdlType, IdlTypeDefinition, IdlTypeDefinitionTy};
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "box", "final", "try",
+];
+
+// Parse a program crate's sources into its IDL
+pub fn parse_program(crate_dir: &Path) -> Result<Idl, CodegenError> {
+    parse(&crate_dir.join("src/lib.rs"))
+}
+
+// The program id in a program crate's declare_id!
+pub fn declared_id(crate_dir: &Path) -> Result<String, CodegenError> {
+    let lib = crate_dir.join("src/lib.rs");
+    let source = fs::read_to_string(&lib)?;
+    source
+        .split("declare_id!(\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .map(str::to_string)
+        .ok_or(CodegenError::NoProgramId(lib))
+}
+
+// Types declared by a library crate with no program module. Shared types use
+// plain borsh derives, which the IDL parser skips, so this parses a copy
+// (written to `scratch_dir`) that spells the derives the Anchor way under an
+// empty program.
+pub fn parse_shared_types(crate_dir: &Path, scratch_dir: &Path) -> Result<Vec<IdlTypeDefinition>, CodegenError> {
+    let source = fs::read_to_string(crate_dir.join("src/lib.rs"))?;
+    let mut source: String = source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("pub mod"))
+        .map(|line| line.replace("BorshSerialize, BorshDeserialize", "AnchorSerialize, AnchorDeserialize") + "\n")
+        .collect();
+    let name = fs::canonicalize(crate_dir)?
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('-', "_"))
+        .unwrap_or_default();
+    source.push_str(&format!("#[program]\npub mod {} {{}}\n", name));
+
+    let copy = scratch_dir.join(format!("{}.rs", name));
+    fs::write(&copy, source)?;
+    Ok(parse(&copy)?.types)
+}
+
+// Load an IDL JSON file, as written by `anchor build`, along with the program
+// id in its metadata if it has one
+pub fn load_idl(path: &Path) -> Result<(Idl, Option<String>), CodegenError> {
+    let idl: Idl = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| CodegenError::Parse { path: path.to_path_buf(), error: err.to_string() })?;
+    let address = idl
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("address"))
+        .and_then(|address| address.as_str())
+        .map(str::to_string);
+    Ok((idl, address))
+}
+
+fn parse(path: &Path) -> Result<Idl, CodegenError> {
+    idl::file::parse(path, "0.1.0".to_string(), false, true, false)
+        .map_err(|err| CodegenError::Parse { path: path.to_path_buf(), error: err.to_string() })?
+        .ok_or_else(|| CodegenError::NoProgram(path.to_path_buf()))
+}
+
+// Generate the client for one program: the items of a module named after the
+// program, to be written to `<idl.name>.rs` or `include!`d in a `mod`
+pub fn generate(idl: &Idl, program_id: &str, shared: &[IdlTypeDefinition]) -> Result<String, CodegenError> {
+    Generator::new(idl, shared)?.generate(program_id)
+}
+
+struct Generator<'a> {
+    idl: &'a Idl,
+    types: Vec<&'a IdlTypeDefinition>,  // The IDL's types, then the shared types it references
+    modules: BTreeMap<&'a str, &'static str>,  // Module defining each type name
+}
+
+impl<'a> Generator<'a> {
+    fn new(idl: &'a Idl, shared: &'a [IdlTypeDefinition]) -> Result<Self, CodegenError> {
+        let mut modules = BTreeMap::new();
+        for account in &idl.accounts {
+            modules.insert(account.name.as_str(), "accounts");
+        }
+        for ty in &idl.types {
+            modules.insert(ty.name.as_str(), "types");
+        }
+        let mut types: Vec<&IdlTypeDefinition> = idl.types.iter().collect();
+
+        let mut pending: Vec<&IdlType> = idl.accounts.iter().chain(&idl.types).flat_map(field_types).collect();
+        for instruction in &idl.instructions {
+            pending.extend(instruction.args.iter().map(|arg| &arg.ty));
+        }
+        for event in idl.events.iter().flatten() {
+            pending.extend(event.fields.iter().map(|field| &field.ty));
+        }
+        while let Some(ty) = pending.pop() {
+            match ty {
+                IdlType::Defined(name) if !modules.contains_key(name.as_str()) => {
+                    let shared_type = shared.iter().find(|ty| ty.name == *name).ok_or_else(|| {
+                        CodegenError::UnknownType { program: idl.name.clone(), name: name.clone() }
+                    })?;
+                    modules.insert(shared_type.name.as_str(), "types");
+                    types.push(shared_type);
+                    pending.extend(field_types(shared_type));
+                }
+                IdlType::Option(inner) | IdlType::Vec(inner) | IdlType::Array(inner, _) => pending.push(inner),
+                _ => {}
+            }
+        }
+
+        Ok(Generator { idl, types, modules })
+    }
+
+    fn generate(&self, program_id: &str) -> Result<String, CodegenError> {
+        let mut out = Output::default();
+        out.line(0, &format!("// Generated by armour-codegen from the {} IDL. Do not edit.", self.idl.name));
+        out.line(0, "");
+        out.line(0, &format!("anchor_lang::declare_id!({:?});", program_id));
+
+        if !self.types.is_empty() {
+            out.line(0, "");
+            out.line(0, "pub mod types {");
+            out.line(1, "use anchor_lang::prelude::*;");
+            for ty in &self.types {
+                out.line(0, "");
+                self.type_definition(&mut out, ty)?;
+            }
+            out.line(0, "}");
+        }
+
+        if !self.idl.accounts.is_empty() {
+            let accounts = self
+                .idl
+                .accounts
+                .iter()
+                .map(|account| match &account.ty {
+                    IdlTypeDefinitionTy::Struct { fields } => {
+                        Ok((account.name.as_str(), fields.iter().map(|field| (field.name.as_str(), &field.ty)).collect()))
+                    }
+                    IdlTypeDefinitionTy::Enum { .. } => Err(CodegenError::UnsupportedType {
+                        program: self.idl.name.clone(),
+                        ty: format!("enum account {}", account.name),
+                    }),
+                })
+                .collect::<Result<Vec<_>, CodegenError>>()?;
+            // Zero-copy accounts decode the same way: their fields can't have
+            // padding between them, so their layout is the Borsh encoding
+            self.tagged_module(&mut out, "accounts", "account", "Account", &accounts)?;
+        }
+
+        if let Some(events) = self.idl.events.as_ref().filter(|events| !events.is_empty()) {
+            let events: Vec<(&str, Vec<(&str, &IdlType)>)> = events
+                .iter()
+                .map(|event| {
+                    (event.name.as_str(), event.fields.iter().map(|field| (field.name.as_str(), &field.ty)).collect())
+                })
+                .collect();
+            self.tagged_module(&mut out, "events", "event", "Event", &events)?;
+        }
+
+        if !self.idl.instructions.is_empty() {
+            self.instructions_module(&mut out)?;
+        }
+
+        if let Some(errors) = self.idl.errors.as_ref().filter(|errors| !errors.is_empty()) {
+            out.line(0, "");
+            out.line(0, "pub mod errors {");
+            out.line(1, "#[repr(u32)]");
+            out.line(1, "#[derive(Clone, Copy, Debug, PartialEq, Eq)]");
+            out.line(1, "pub enum ErrorCode {");
+            for error in errors {
+                out.line(2, &format!("{} = {},", error.name, error.code));
+            }
+            out.line(1, "}");
+            out.line(0, "");
+            out.line(1, "impl ErrorCode {");
+            out.line(2, "pub fn from_code(code: u32) -> Option<Self> {");
+            out.line(3, "Some(match code {");
+            for error in errors {
+                out.line(4, &format!("{} => Self::{},", error.code, error.name));
+            }
+            out.line(4, "_ => return None,");
+            out.line(3, "})");
+            out.line(2, "}");
+            out.line(0, "");
+            out.line(2, "pub fn message(&self) -> &'static str {");
+            out.line(3, "match self {");
+            for error in errors {
+                let message = error.msg.as_deref().unwrap_or(&error.name);
+                out.line(4, &format!("Self::{} => {:?},", error.name, message));
+            }
+            out.line(3, "}");
+            out.line(2, "}");
+            out.line(1, "}");
+            out.line(0, "}");
+        }
+
+        Ok(out.0)
+    }
+
+    fn type_definition(&self, out: &mut Output, ty: &IdlTypeDefinition) -> Result<(), CodegenError> {
+        match &ty.ty {
+            IdlTypeDefinitionTy::Struct { fields } => {
+                let fields: Vec<(&str, &IdlType)> = fields.iter().map(|field| (field.name.as_str(), &field.ty)).collect();
+                self.struct_definition(out, &ty.name, &fields)
+            }
+            IdlTypeDefinitionTy::Enum { variants } => {
+                out.line(1, "#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]");
+                out.line(1, &format!("pub enum {} {{", ty.name));
+                for variant in variants {
+                    match &variant.fields {
+                        None => out.line(2, &format!("{},", variant.name)),
+                        Some(EnumFields::Named(fields)) => {
+                            let fields = fields
+                                .iter()
+                                .map(|field| Ok(format!("{}: {}", field_name(&field.name), self.rust_type(&field.ty)?)))
+                                .collect::<Result<Vec<_>, CodegenError>>()?;
+                            out.line(2, &format!("{} {{ {} }},", variant.name, fields.join(", ")));
+                        }
+                        Some(EnumFields::Tuple(types)) => {
+                            let types =
+                                types.iter().map(|ty| self.rust_type(ty)).collect::<Result<Vec<_>, CodegenError>>()?;
+                            out.line(2, &format!("{}({}),", variant.name, types.join(", ")));
+                        }
+                    }
+                }
+                out.line(1, "}");
+                Ok(())
+            }
+        }
+    }
+
+    fn struct_definition(&self, out: &mut Output, name: &str, fields: &[(&str, &IdlType)]) -> Result<(), CodegenError> {
+        out.line(1, "#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]");
+        out.line(1, &format!("pub struct {} {{", name));
+        for (field, ty) in fields {
+            out.line(2, &format!("pub {}: {},", field_name(field), self.rust_type(ty)?));
+        }
+        out.line(1, "}");
+        Ok(())
+    }
+
+    // A module of structs that are stored or emitted behind an 8-byte
+    // discriminator (accounts, events), with an enum of all of them and a
+    // decoder that picks the struct by discriminator
+    fn tagged_module(
+        &self,
+        out: &mut Output,
+        module: &str,
+        namespace: &str,
+        enum_name: &str,
+        items: &[(&str, Vec<(&str, &IdlType)>)],
+    ) -> Result<(), CodegenError> {
+        out.line(0, "");
+        out.line(0, &format!("pub mod {} {{", module));
+        out.line(1, "use anchor_lang::prelude::*;");
+        for (name, fields) in items {
+            out.line(0, "");
+            self.struct_definition(out, name, fields)?;
+            out.line(0, "");
+            out.line(1, &format!("impl {} {{", name));
+            out.line(2, &format!("pub const DISCRIMINATOR: [u8; 8] = {:?};", sighash(namespace, name)));
+            out.line(1, "}");
+        }
+
+        out.line(0, "");
+        out.line(1, "#[allow(clippy::large_enum_variant)]");
+        out.line(1, "#[derive(Clone, Debug, PartialEq)]");
+        out.line(1, &format!("pub enum {} {{", enum_name));
+        for (name, _) in items {
+            out.line(2, &format!("{}({}),", name, name));
+        }
+        out.line(1, "}");
+        out.line(0, "");
+        out.line(1, &format!("// Decode {} data, discriminator first; None for anything else", namespace));
+        out.line(1, &format!("pub fn decode(data: &[u8]) -> Option<{}> {{", enum_name));
+        out.line(2, "let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;");
+        out.line(2, "let mut data = &data[8..];");
+        out.line(2, "Some(match discriminator {");
+        for (name, _) in items {
+            out.line(3, &format!("{}::DISCRIMINATOR => {}::{}(AnchorDeserialize::deserialize(&mut data).ok()?),", name, enum_name, name));
+        }
+        out.line(3, "_ => return None,");
+        out.line(2, "})");
+        out.line(1, "}");
+        out.line(0, "}");
+        Ok(())
+    }
+
+    // Per instruction: an accounts struct (nested account groups flattened,
+    // field names prefixed with the group's), an args struct with the
+    // instruction data encoding, and a builder
+    fn instructions_module(&self, out: &mut Output) -> Result<(), CodegenError> {
+        out.line(0, "");
+        out.line(0, "#[allow(clippy::too_many_arguments)]");
+        out.line(0, "pub mod instructions {");
+        out.line(1, "use anchor_lang::prelude::*;");
+        out.line(1, "use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};");
+
+        for instruction in &self.idl.instructions {
+            let type_name = upper_camel(&instruction.name);
+            let mut accounts = Vec::new();
+            flatten_accounts(&instruction.accounts, "", &mut accounts);
+            let args = instruction
+                .args
+                .iter()
+                .map(|arg| Ok((field_name(&arg.name), self.rust_type(&arg.ty)?)))
+                .collect::<Result<Vec<_>, CodegenError>>()?;
+
+            out.line(0, "");
+            out.line(1, "#[derive(Clone, Debug, PartialEq)]");
+            out.line(1, &format!("pub struct {}Accounts {{", type_name));
+            for (name, account) in &accounts {
+                let ty = if account.is_optional == Some(true) { "Option<Pubkey>" } else { "Pubkey" };
+                out.line(2, &format!("pub {}: {},", name, ty));
+            }
+            out.line(1, "}");
+
+            out.line(0, "");
+            out.line(1, "#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]");
+            out.line(1, &format!("pub struct {}Args {{", type_name));
+            for (name, ty) in &args {
+                out.line(2, &format!("pub {}: {},", name, ty));
+            }
+            out.line(1, "}");
+            out.line(0, "");
+            out.line(1, &format!("impl {}Args {{", type_name));
+            out.line(2, &format!("pub const DISCRIMINATOR: [u8; 8] = {:?};", sighash("global", &instruction.name.to_snake_case())));
+            out.line(0, "");
+            out.line(2, "// Instruction data: discriminator, then the Borsh-encoded args");
+            out.line(2, "pub fn data(&self) -> Vec<u8> {");
+            out.line(3, "let mut data = Self::DISCRIMINATOR.to_vec();");
+            out.line(3, "self.serialize(&mut data).unwrap();");
+            out.line(3, "data");
+            out.line(2, "}");
+            out.line(1, "}");
+
+            let params: Vec<String> = args.iter().map(|(name, ty)| format!(", {}: {}", name, ty)).collect();
+            let names: Vec<&str> = args.iter().map(|(name, _)| name.as_str()).collect();
+            out.line(0, "");
+            out.line(1, &format!(
+                "pub fn {}(accounts: &{}Accounts{}) -> Instruction {{",
+                instruction.name.to_snake_case(),
+                type_name,
+                params.concat()
+            ));
+            out.line(2, &format!("let args = {}Args {{ {} }};", type_name, names.join(", ")));
+            out.line(2, "Instruction {");
+            out.line(3, "program_id: super::ID,");
+            out.line(3, "accounts: vec![");
+            for (name, account) in &accounts {
+                let constructor = if account.is_mut { "new" } else { "new_readonly" };
+                if account.is_optional == Some(true) {
+                    // Anchor reads the program's own id as "not provided"
+                    out.line(4, &format!("match accounts.{} {{", name));
+                    out.line(5, &format!("Some(key) => AccountMeta::{}(key, {}),", constructor, account.is_signer));
+                    out.line(5, "None => AccountMeta::new_readonly(super::ID, false),");
+                    out.line(4, "},");
+                } else {
+                    out.line(4, &format!("AccountMeta::{}(accounts.{}, {}),", constructor, name, account.is_signer));
+                }
+            }
+            out.line(3, "],");
+            out.line(3, "data: args.data(),");
+            out.line(2, "}");
+            out.line(1, "}");
+        }
+
+        out.line(0, "}");
+        Ok(())
+    }
+
+    fn rust_type(&self, ty: &IdlType) -> Result<String, CodegenError> {
+        Ok(match ty {
+            IdlType::Bool => "bool".to_string(),
+            IdlType::U8 => "u8".to_string(),
+            IdlType::I8 => "i8".to_string(),
+            IdlType::U16 => "u16".to_string(),
+            IdlType::I16 => "i16".to_string(),
+            IdlType::U32 => "u32".to_string(),
+            IdlType::I32 => "i32".to_string(),
+            IdlType::F32 => "f32".to_string(),
+            IdlType::U64 => "u64".to_string(),
+            IdlType::I64 => "i64".to_string(),
+            IdlType::F64 => "f64".to_string(),
+            IdlType::U128 => "u128".to_string(),
+            IdlType::I128 => "i128".to_string(),
+            IdlType::Bytes => "Vec<u8>".to_string(),
+            IdlType::String => "String".to_string(),
+            IdlType::PublicKey => "Pubkey".to_string(),
+            IdlType::Defined(name) => format!("super::{}::{}", self.modules[name.as_str()], name),
+            IdlType::Option(inner) => format!("Option<{}>", self.rust_type(inner)?),
+            IdlType::Vec(inner) => format!("Vec<{}>", self.rust_type(inner)?),
+            IdlType::Array(inner, len) => format!("[{}; {}]", self.rust_type(inner)?, len),
+            IdlType::U256 | IdlType::I256 => {
+                return Err(CodegenError::UnsupportedType { program: self.idl.name.clone(), ty: format!("{:?}", ty) })
+            }
+        })
+    }
+}
+
+fn field_types(ty: &IdlTypeDefinition) -> Vec<&IdlType> {
+    match &ty.ty {
+        IdlTypeDefinitionTy::Struct { fields } => fields.iter().map(|field| &field.ty).collect(),
+        IdlTypeDefinitionTy::Enum { variants } => variants
+            .iter()
+            .flat_map(|variant| match &variant.fields {
+                None => Vec::new(),
+                Some(EnumFields::Named(fields)) => fields.iter().map(|field| &field.ty).collect(),
+                Some(EnumFields::Tuple(types)) => types.iter().collect(),
+            })
+            .collect(),
+    }
+}
+
+fn flatten_accounts<'b>(items: &'b [IdlAccountItem], prefix: &str, flat: &mut Vec<(String, &'b IdlAccount)>) {
+    for item in items {
+        match item {
+            IdlAccountItem::IdlAccount(account) => flat.push((format!("{}{}", prefix, account.name.to_snake_case()), account)),
+            IdlAccountItem::IdlAccounts(group) => {
+                let prefix = format!("{}{}_", prefix, group.name.to_snake_case());
+                flatten_accounts(&group.accounts, &prefix, flat);
+            }
+        }
+    }
+}
+
+fn field_name(name: &str) -> String {
+    let name = name.to_snake_case();
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+// IDL instruction names are camelCase
+fn upper_camel(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+#[derive(Default)]
+struct Output(String);
+
+impl Output {
+    fn line(&mut self, depth: usize, text: &str) {
+        if !text.is_empty() {
+            self.0.push_str(&"    ".repeat(depth));
+            self.0.push_str(text);
+        }
+        self.0.push('\n');
+    }
+}
+
+// Errors
+#[derive(Debug)]
+pub enum CodegenError {
+    Io(io::Error),
+    Parse { path: PathBuf, error: String },
+    NoProgram(PathBuf),
+    NoProgramId(PathBuf),
+    UnknownType { program: String, name: String },
+    UnsupportedType { program: String, ty: String },
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Io(err) => write!(f, "I/O error: {}", err),
+            CodegenError::Parse { path, error } => write!(f, "Failed to parse {}: {}", path.display(), error),
+            CodegenError::NoProgram(path) => write!(f, "{} has no #[program] module", path.display()),
+            CodegenError::NoProgramId(path) => write!(f, "{} has no declare_id!", path.display()),
+            CodegenError::UnknownType { program, name } => {
+                write!(f, "{} references type {}, which neither its IDL nor the shared types define", program, name)
+            }
+            CodegenError::UnsupportedType { program, ty } => write!(f, "{} uses unsupported type {}", program, ty),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+impl From<io::Error> for CodegenError {
+    fn from(err: io::Error) -> Self {
+        CodegenError::Io(err)
+    }
+}
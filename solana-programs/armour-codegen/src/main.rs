@@ -0,0 +1,86 @@
+// armour-codegen --out <dir> [--shared <crate dir>]... [--program-id <name>=<id>]...
+//     <program crate dir | IDL JSON file>...
+//
+// Writes `<program>.rs` into --out for each program, parsed from the crate's
+// sources or loaded from an `anchor build` IDL. Program ids come from the
+// crate's declare_id! or the IDL's metadata; --program-id overrides either.
+// --shared names library crates whose types the programs borrow.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::exit;
+
+use armour_codegen::{declared_id, generate, load_idl, parse_program, parse_shared_types, CodegenError};
+
+const USAGE: &str = "usage: armour-codegen --out <dir> [--shared <crate dir>]... [--program-id <name>=<id>]... \
+<program crate dir | IDL JSON file>...";
+
+struct Args {
+    out: PathBuf,
+    shared: Vec<PathBuf>,
+    program_ids: HashMap<String, String>,
+    programs: Vec<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut out = None;
+    let mut shared = Vec::new();
+    let mut program_ids = HashMap::new();
+    let mut programs = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--out" => out = Some(PathBuf::from(value("--out")?)),
+            "--shared" => shared.push(PathBuf::from(value("--shared")?)),
+            "--program-id" => {
+                let value = value("--program-id")?;
+                let (name, id) = value.split_once('=').ok_or("--program-id takes <name>=<id>")?;
+                program_ids.insert(name.replace('-', "_"), id.to_string());
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown argument {}", flag)),
+            program => programs.push(PathBuf::from(program)),
+        }
+    }
+    if programs.is_empty() {
+        return Err("no programs given".to_string());
+    }
+    Ok(Args { out: out.ok_or("missing --out")?, shared, program_ids, programs })
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        exit(2);
+    });
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<(), CodegenError> {
+    std::fs::create_dir_all(&args.out)?;
+    let mut shared = Vec::new();
+    for crate_dir in &args.shared {
+        shared.extend(parse_shared_types(crate_dir, &std::env::temp_dir())?);
+    }
+
+    for program in &args.programs {
+        let (idl, program_id) = if program.extension().is_some_and(|ext| ext == "json") {
+            load_idl(program)?
+        } else {
+            (parse_program(program)?, Some(declared_id(program)?))
+        };
+        let program_id = match args.program_ids.get(&idl.name).cloned().or(program_id) {
+            Some(program_id) => program_id,
+            None => {
+                eprintln!("{} has no program id; pass --program-id {}=<id>", program.display(), idl.name);
+                exit(2);
+            }
+        };
+        let path = args.out.join(format!("{}.rs", idl.name));
+        std::fs::write(&path, generate(&idl, &program_id, &shared)?)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,399 @@
+// Types and scoring shared by the registry, relationship and breeding
+// programs and by off-chain clients. Only borsh is required, and the crate
+// builds without std (disable the default `std` feature), so the exact
+// compatibility and health functions the programs run can be used anywhere,
+// and the `wasm` feature exports them to JavaScript (see wasm.rs). The borsh
+// derives are the ones behind AnchorSerialize/AnchorDeserialize, so these
+// types can be used directly in accounts, events and instruction arguments.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::fmt;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub const TRAIT_COUNT: usize = 8;
+pub const MAX_TRAIT_VALUE: u8 = 100;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct PersonalityTraits {
+    pub openness: u8,           // 0-100 scale
+    pub conscientiousness: u8,  // 0-100 scale
+    pub extraversion: u8,       // 0-100 scale
+    pub agreeableness: u8,      // 0-100 scale
+    pub neuroticism: u8,        // 0-100 scale
+    pub intelligence: u8,       // 0-100 scale
+    pub creativity: u8,         // 0-100 scale
+    pub humor: u8,              // 0-100 scale
+    // Additional traits could be added
+}
+
+impl PersonalityTraits {
+    // Traits in declaration order, the order used by trait arrays everywhere
+    pub fn values(&self) -> [u8; TRAIT_COUNT] {
+        [
+            self.openness,
+            self.conscientiousness,
+            self.extraversion,
+            self.agreeableness,
+            self.neuroticism,
+            self.intelligence,
+            self.creativity,
+            self.humor,
+        ]
+    }
+
+    pub fn from_values(values: [u8; TRAIT_COUNT]) -> Self {
+        Self {
+            openness: values[0],
+            conscientiousness: values[1],
+            extraversion: values[2],
+            agreeableness: values[3],
+            neuroticism: values[4],
+            intelligence: values[5],
+            creativity: values[6],
+            humor: values[7],
+        }
+    }
+
+    // Every trait within 0-MAX_TRAIT_VALUE. Programs check this wherever
+    // traits are written, mapping the error to their own InvalidTraitValue.
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if self.values().iter().all(|value| *value <= MAX_TRAIT_VALUE) {
+            Ok(())
+        } else {
+            Err(CoreError::InvalidTraitValue)
+        }
+    }
+}
+
+// Compatibility is a weighted average of per-trait terms, each 0-100:
+// similarity (100 - difference) for openness, conscientiousness and
+// extraversion, and difference for agreeableness and neuroticism, where
+// complementary values work better. It depends only on the absolute trait
+// differences, so it is symmetric, and it never decreases as similarity
+// traits converge or complementary traits diverge. Traits above
+// MAX_TRAIT_VALUE are clamped, so every input scores within 0-100.
+pub const SIMILARITY_WEIGHTS: [u16; 3] = [6, 6, 9];     // openness, conscientiousness, extraversion
+pub const COMPLEMENTARY_WEIGHTS: [u16; 2] = [2, 2];     // agreeableness, neuroticism
+
+pub fn calculate_compatibility_score(
+    traits_one: &PersonalityTraits,
+    traits_two: &PersonalityTraits,
+) -> u8 {
+    calculate_compatibility_score_with(&CompatibilityWeights::default(), traits_one, traits_two)
+}
+
+// How per-trait terms combine into a score
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    // Weighted mean of the terms
+    WeightedAverage,
+    // Weighted mean of the squared terms, scaled back to 0-100, so a few
+    // strong matches count for more than many middling ones
+    Quadratic,
+}
+
+// Per-trait weights, in PersonalityTraits::values order. A trait can carry
+// both a similarity and a complementary weight; traits with neither are
+// not scored.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CompatibilityWeights {
+    pub similarity: [u16; TRAIT_COUNT],
+    pub complementary: [u16; TRAIT_COUNT],
+    pub normalization: Normalization,
+}
+
+impl Default for CompatibilityWeights {
+    // The fixed weights of `calculate_compatibility_score`
+    fn default() -> Self {
+        let mut similarity = [0; TRAIT_COUNT];
+        similarity[..3].copy_from_slice(&SIMILARITY_WEIGHTS);
+        let mut complementary = [0; TRAIT_COUNT];
+        complementary[3..5].copy_from_slice(&COMPLEMENTARY_WEIGHTS);
+        Self {
+            similarity,
+            complementary,
+            normalization: Normalization::WeightedAverage,
+        }
+    }
+}
+
+impl CompatibilityWeights {
+    pub fn total_weight(&self) -> u64 {
+        self.similarity
+            .iter()
+            .chain(self.complementary.iter())
+            .map(|weight| *weight as u64)
+            .sum()
+    }
+
+    // At least one weight must be set for a score to be defined
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if self.total_weight() > 0 {
+            Ok(())
+        } else {
+            Err(CoreError::InvalidWeights)
+        }
+    }
+}
+
+// Score under configurable weights. Symmetric, within 0-100, and with the
+// same monotonicity as `calculate_compatibility_score` for each weighted
+// trait. Weights that are all zero score 0.
+pub fn calculate_compatibility_score_with(
+    weights: &CompatibilityWeights,
+    traits_one: &PersonalityTraits,
+    traits_two: &PersonalityTraits,
+) -> u8 {
+    let total_weight = weights.total_weight();
+    if total_weight == 0 {
+        return 0;
+    }
+
+    let max = MAX_TRAIT_VALUE as u64;
+    let normalize = |term: u64| match weights.normalization {
+        Normalization::WeightedAverage => term,
+        Normalization::Quadratic => term * term,
+    };
+    let mut weighted = 0u64;
+    for (index, (one, two)) in traits_one.values().into_iter().zip(traits_two.values()).enumerate() {
+        let diff = one.min(MAX_TRAIT_VALUE).abs_diff(two.min(MAX_TRAIT_VALUE)) as u64;
+        weighted += weights.similarity[index] as u64 * normalize(max - diff);
+        weighted += weights.complementary[index] as u64 * normalize(diff);
+    }
+
+    let score = match weights.normalization {
+        Normalization::WeightedAverage => weighted / total_weight,
+        Normalization::Quadratic => weighted / (total_weight * max),
+    };
+    score as u8
+}
+
+// Cosine similarity of the two trait vectors, scaled to 0-100 and rounded
+// down. Unlike the heuristic it has no complementary traits and ignores
+// weights. An all-zero vector has no direction, so it scores 100 against
+// another all-zero vector and 0 against anything else.
+pub fn calculate_cosine_compatibility(
+    traits_one: &PersonalityTraits,
+    traits_two: &PersonalityTraits,
+) -> u8 {
+    let one = traits_one.values().map(|value| value.min(MAX_TRAIT_VALUE) as u128);
+    let two = traits_two.values().map(|value| value.min(MAX_TRAIT_VALUE) as u128);
+    let dot: u128 = one.iter().zip(two.iter()).map(|(a, b)| a * b).sum();
+    let norm_one: u128 = one.iter().map(|a| a * a).sum();
+    let norm_two: u128 = two.iter().map(|b| b * b).sum();
+    match (norm_one, norm_two) {
+        (0, 0) => return 100,
+        (0, _) | (_, 0) => return 0,
+        _ => {}
+    }
+
+    // Largest score with score / 100 <= dot / sqrt(norm_one * norm_two)
+    let bound = 10_000 * dot * dot;
+    let norms = norm_one * norm_two;
+    (0..=100u128).rev().find(|score| score * score * norms <= bound).unwrap_or(0) as u8
+}
+
+// Compatibility algorithms, by the version recorded alongside each score
+// so historical scores stay interpretable
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompatibilityAlgorithm {
+    Heuristic,  // `calculate_compatibility_score_with` under the configured weights
+    Cosine,     // `calculate_cosine_compatibility`
+}
+
+impl CompatibilityAlgorithm {
+    pub fn version(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn score(
+        &self,
+        weights: &CompatibilityWeights,
+        traits_one: &PersonalityTraits,
+        traits_two: &PersonalityTraits,
+    ) -> u8 {
+        match self {
+            CompatibilityAlgorithm::Heuristic => {
+                calculate_compatibility_score_with(weights, traits_one, traits_two)
+            }
+            CompatibilityAlgorithm::Cosine => calculate_cosine_compatibility(traits_one, traits_two),
+        }
+    }
+}
+
+impl TryFrom<u8> for CompatibilityAlgorithm {
+    type Error = CoreError;
+
+    fn try_from(value: u8) -> Result<Self, CoreError> {
+        Ok(match value {
+            0 => CompatibilityAlgorithm::Heuristic,
+            1 => CompatibilityAlgorithm::Cosine,
+            _ => return Err(CoreError::InvalidEnumValue),
+        })
+    }
+}
+
+// Relationship health, 0-MAX_HEALTH. New relationships start at full health;
+// it decays with inactivity and moves with witnesses and dispute rulings.
+pub const MAX_HEALTH: u8 = 100;
+
+// Health after a signed adjustment, such as a dispute ruling's delta
+pub fn adjust_health(health: u8, delta: i8) -> u8 {
+    (health as i16 + delta as i16).clamp(0, MAX_HEALTH as i16) as u8
+}
+
+// Health after a bonus, such as a first witness attestation
+pub fn add_health_bonus(health: u8, bonus: u8) -> u8 {
+    health.saturating_add(bonus).min(MAX_HEALTH)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HealthDecay {
+    pub periods: i64,         // Full inactive periods applied
+    pub health: u8,           // Health after decay
+    pub last_decay_at: i64,   // End of the last applied period
+}
+
+// Decay for each full `inactivity_period` between `since` (the later of the
+// last interaction and last decay) and `now`, losing `decay_per_period` per
+// period. None if no full period has passed or the period is not positive.
+pub fn decay_health(
+    health: u8,
+    since: i64,
+    now: i64,
+    inactivity_period: i64,
+    decay_per_period: u8,
+) -> Option<HealthDecay> {
+    if inactivity_period <= 0 {
+        return None;
+    }
+    let periods = now.saturating_sub(since) / inactivity_period;
+    if periods <= 0 {
+        return None;
+    }
+    let decay = (periods as u64)
+        .saturating_mul(decay_per_period as u64)
+        .min(MAX_HEALTH as u64) as u8;
+    Some(HealthDecay {
+        periods,
+        health: health.saturating_sub(decay),
+        last_decay_at: since.saturating_add(periods.saturating_mul(inactivity_period)),
+    })
+}
+
+// A relationship's quality as sampled by reputation: the mean of its
+// compatibility score and health, 0-100
+pub fn relationship_quality(compatibility_score: u8, health: u8) -> u8 {
+    ((compatibility_score as u16 + health as u16) / 2) as u8
+}
+
+// Relationship enums. Accounts store them as their u8 variant index, which
+// is also their borsh encoding.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum RelationshipType {
+    Friends,
+    Dating,
+    Romantic,
+    Professional,
+    Mentorship,
+    Custom,
+}
+
+impl RelationshipType {
+    pub fn index(&self) -> usize {
+        self.clone() as usize
+    }
+
+    // Contract-like types that may run for a fixed term
+    pub fn is_term_based(&self) -> bool {
+        matches!(self, RelationshipType::Professional | RelationshipType::Mentorship)
+    }
+}
+
+impl TryFrom<u8> for RelationshipType {
+    type Error = CoreError;
+
+    fn try_from(value: u8) -> Result<Self, CoreError> {
+        Ok(match value {
+            0 => RelationshipType::Friends,
+            1 => RelationshipType::Dating,
+            2 => RelationshipType::Romantic,
+            3 => RelationshipType::Professional,
+            4 => RelationshipType::Mentorship,
+            5 => RelationshipType::Custom,
+            _ => return Err(CoreError::InvalidEnumValue),
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum RelationshipStatus {
+    Active,
+    Paused,
+    Ended,
+}
+
+impl TryFrom<u8> for RelationshipStatus {
+    type Error = CoreError;
+
+    fn try_from(value: u8) -> Result<Self, CoreError> {
+        Ok(match value {
+            0 => RelationshipStatus::Active,
+            1 => RelationshipStatus::Paused,
+            2 => RelationshipStatus::Ended,
+            _ => return Err(CoreError::InvalidEnumValue),
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Visibility {
+    Public,
+    Unlisted,  // Readable, but excluded from public listings by indexers
+    Private,   // Interaction events carry only hashes
+}
+
+impl TryFrom<u8> for Visibility {
+    type Error = CoreError;
+
+    fn try_from(value: u8) -> Result<Self, CoreError> {
+        Ok(match value {
+            0 => Visibility::Public,
+            1 => Visibility::Unlisted,
+            2 => Visibility::Private,
+            _ => return Err(CoreError::InvalidEnumValue),
+        })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum InteractionType {
+    Conversation,
+    Activity,
+    Gift,
+    DateEvent,
+    Milestone,
+    Custom,
+}
+
+// Errors from decoding and validating shared types; programs map them to
+// their own error codes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CoreError {
+    InvalidEnumValue,
+    InvalidTraitValue,
+    InvalidWeights,
+}
+
+impl fmt::Display for CoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoreError::InvalidEnumValue => f.write_str("Invalid enum value"),
+            CoreError::InvalidTraitValue => f.write_str("Trait value out of range"),
+            CoreError::InvalidWeights => f.write_str("Compatibility weights are all zero"),
+        }
+    }
+}
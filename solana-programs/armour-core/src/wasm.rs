@@ -0,0 +1,52 @@
+// JavaScript bindings for front-ends, built with
+// `wasm-pack build --features wasm`. Traits are passed as a Uint8Array in
+// PersonalityTraits::values order.
+use wasm_bindgen::prelude::*;
+
+use crate::{PersonalityTraits, TRAIT_COUNT};
+
+fn traits(values: &[u8]) -> Result<PersonalityTraits, JsError> {
+    let values: [u8; TRAIT_COUNT] = values
+        .try_into()
+        .map_err(|_| JsError::new("expected one value per personality trait"))?;
+    Ok(PersonalityTraits::from_values(values))
+}
+
+#[wasm_bindgen(js_name = compatibilityScore)]
+pub fn compatibility_score(traits_one: &[u8], traits_two: &[u8]) -> Result<u8, JsError> {
+    Ok(crate::calculate_compatibility_score(&traits(traits_one)?, &traits(traits_two)?))
+}
+
+#[wasm_bindgen(js_name = cosineCompatibility)]
+pub fn cosine_compatibility(traits_one: &[u8], traits_two: &[u8]) -> Result<u8, JsError> {
+    Ok(crate::calculate_cosine_compatibility(&traits(traits_one)?, &traits(traits_two)?))
+}
+
+#[wasm_bindgen(js_name = adjustHealth)]
+pub fn adjust_health(health: u8, delta: i8) -> u8 {
+    crate::adjust_health(health, delta)
+}
+
+#[wasm_bindgen(js_name = addHealthBonus)]
+pub fn add_health_bonus(health: u8, bonus: u8) -> u8 {
+    crate::add_health_bonus(health, bonus)
+}
+
+// Health after decay, unchanged if no full period has passed. Timestamps
+// are Unix seconds.
+#[wasm_bindgen(js_name = decayHealth)]
+pub fn decay_health(
+    health: u8,
+    since: i64,
+    now: i64,
+    inactivity_period: i64,
+    decay_per_period: u8,
+) -> u8 {
+    crate::decay_health(health, since, now, inactivity_period, decay_per_period)
+        .map_or(health, |decay| decay.health)
+}
+
+#[wasm_bindgen(js_name = relationshipQuality)]
+pub fn relationship_quality(compatibility_score: u8, health: u8) -> u8 {
+    crate::relationship_quality(compatibility_score, health)
+}
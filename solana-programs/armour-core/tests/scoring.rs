@@ -0,0 +1,210 @@
+use armour_core::{
+    calculate_compatibility_score, calculate_compatibility_score_with, calculate_cosine_compatibility,
+    CompatibilityAlgorithm, CompatibilityWeights, Normalization, PersonalityTraits, MAX_TRAIT_VALUE, TRAIT_COUNT,
+};
+use proptest::prelude::*;
+
+// Any u8 values, including out-of-range ones the programs may still hold
+fn any_traits() -> impl Strategy<Value = PersonalityTraits> {
+    any::<[u8; TRAIT_COUNT]>().prop_map(PersonalityTraits::from_values)
+}
+
+fn in_range_traits() -> impl Strategy<Value = PersonalityTraits> {
+    prop::array::uniform8(0..=MAX_TRAIT_VALUE).prop_map(PersonalityTraits::from_values)
+}
+
+fn any_weights() -> impl Strategy<Value = CompatibilityWeights> {
+    (any::<[u16; TRAIT_COUNT]>(), any::<[u16; TRAIT_COUNT]>(), any::<bool>()).prop_map(
+        |(similarity, complementary, quadratic)| CompatibilityWeights {
+            similarity,
+            complementary,
+            normalization: if quadratic { Normalization::Quadratic } else { Normalization::WeightedAverage },
+        },
+    )
+}
+
+// Trait indexes in declaration order
+const SIMILARITY_TRAITS: [usize; 3] = [0, 1, 2];     // openness, conscientiousness, extraversion
+const COMPLEMENTARY_TRAITS: [usize; 2] = [3, 4];     // agreeableness, neuroticism
+const UNSCORED_TRAITS: [usize; 3] = [5, 6, 7];       // intelligence, creativity, humor
+
+fn with_trait(traits: &PersonalityTraits, index: usize, value: u8) -> PersonalityTraits {
+    let mut values = traits.values();
+    values[index] = value;
+    PersonalityTraits::from_values(values)
+}
+
+proptest! {
+    #[test]
+    fn score_is_symmetric(a in any_traits(), b in any_traits()) {
+        prop_assert_eq!(calculate_compatibility_score(&a, &b), calculate_compatibility_score(&b, &a));
+    }
+
+    #[test]
+    fn score_is_bounded(a in any_traits(), b in any_traits()) {
+        prop_assert!(calculate_compatibility_score(&a, &b) <= 100);
+    }
+
+    #[test]
+    fn out_of_range_traits_score_as_max(a in any_traits(), b in any_traits()) {
+        let clamp = |traits: &PersonalityTraits| {
+            PersonalityTraits::from_values(traits.values().map(|value| value.min(MAX_TRAIT_VALUE)))
+        };
+        prop_assert_eq!(
+            calculate_compatibility_score(&a, &b),
+            calculate_compatibility_score(&clamp(&a), &clamp(&b))
+        );
+    }
+
+    // Moving a similarity trait of `b` towards `a` never lowers the score
+    #[test]
+    fn converging_similarity_traits_never_lowers_score(
+        a in in_range_traits(),
+        b in in_range_traits(),
+        index in prop::sample::select(SIMILARITY_TRAITS.to_vec()),
+        step in 0u8..=MAX_TRAIT_VALUE,
+    ) {
+        let (target, current) = (a.values()[index], b.values()[index]);
+        let closer = if current > target {
+            current - step.min(current - target)
+        } else {
+            current + step.min(target - current)
+        };
+        prop_assert!(
+            calculate_compatibility_score(&a, &with_trait(&b, index, closer))
+                >= calculate_compatibility_score(&a, &b)
+        );
+    }
+
+    // Moving a complementary trait of `b` away from `a` never lowers the score
+    #[test]
+    fn diverging_complementary_traits_never_lowers_score(
+        a in in_range_traits(),
+        b in in_range_traits(),
+        index in prop::sample::select(COMPLEMENTARY_TRAITS.to_vec()),
+        step in 0u8..=MAX_TRAIT_VALUE,
+    ) {
+        let (target, current) = (a.values()[index], b.values()[index]);
+        let further = if current >= target {
+            current.saturating_add(step).min(MAX_TRAIT_VALUE)
+        } else {
+            current.saturating_sub(step)
+        };
+        prop_assert!(
+            calculate_compatibility_score(&a, &with_trait(&b, index, further))
+                >= calculate_compatibility_score(&a, &b)
+        );
+    }
+
+    #[test]
+    fn unscored_traits_do_not_affect_score(
+        a in in_range_traits(),
+        b in in_range_traits(),
+        index in prop::sample::select(UNSCORED_TRAITS.to_vec()),
+        value in any::<u8>(),
+    ) {
+        prop_assert_eq!(
+            calculate_compatibility_score(&a, &with_trait(&b, index, value)),
+            calculate_compatibility_score(&a, &b)
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn weighted_score_is_symmetric_and_bounded(
+        weights in any_weights(),
+        a in any_traits(),
+        b in any_traits(),
+    ) {
+        let score = calculate_compatibility_score_with(&weights, &a, &b);
+        prop_assert_eq!(score, calculate_compatibility_score_with(&weights, &b, &a));
+        prop_assert!(score <= 100);
+    }
+
+    #[test]
+    fn default_weights_match_fixed_score(a in any_traits(), b in any_traits()) {
+        prop_assert_eq!(
+            calculate_compatibility_score_with(&CompatibilityWeights::default(), &a, &b),
+            calculate_compatibility_score(&a, &b)
+        );
+    }
+
+    // Squaring terms of at most 100 and scaling back never raises the score
+    #[test]
+    fn quadratic_never_exceeds_weighted_average(
+        weights in any_weights(),
+        a in any_traits(),
+        b in any_traits(),
+    ) {
+        let average = CompatibilityWeights { normalization: Normalization::WeightedAverage, ..weights.clone() };
+        let quadratic = CompatibilityWeights { normalization: Normalization::Quadratic, ..weights };
+        prop_assert!(
+            calculate_compatibility_score_with(&quadratic, &a, &b)
+                <= calculate_compatibility_score_with(&average, &a, &b)
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn cosine_score_is_symmetric_and_bounded(a in any_traits(), b in any_traits()) {
+        let score = calculate_cosine_compatibility(&a, &b);
+        prop_assert_eq!(score, calculate_cosine_compatibility(&b, &a));
+        prop_assert!(score <= 100);
+    }
+
+    // Scaling a vector keeps its direction
+    #[test]
+    fn cosine_ignores_magnitude(values in prop::array::uniform8(1..=MAX_TRAIT_VALUE / 2)) {
+        let a = PersonalityTraits::from_values(values);
+        let doubled = PersonalityTraits::from_values(values.map(|value| value * 2));
+        prop_assert_eq!(calculate_cosine_compatibility(&a, &doubled), 100);
+    }
+
+    #[test]
+    fn algorithm_versions_round_trip(version in any::<u8>()) {
+        if let Ok(algorithm) = CompatibilityAlgorithm::try_from(version) {
+            prop_assert_eq!(algorithm.version(), version);
+        }
+    }
+}
+
+#[test]
+fn cosine_extremes() {
+    let zero = PersonalityTraits::from_values([0; TRAIT_COUNT]);
+    let high = PersonalityTraits::from_values([MAX_TRAIT_VALUE; TRAIT_COUNT]);
+    let first = PersonalityTraits::from_values([MAX_TRAIT_VALUE, 0, 0, 0, 0, 0, 0, 0]);
+    let second = PersonalityTraits::from_values([0, MAX_TRAIT_VALUE, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(calculate_cosine_compatibility(&high, &high), 100);
+    // Orthogonal vectors
+    assert_eq!(calculate_cosine_compatibility(&first, &second), 0);
+    // One trait of eight: 1 / sqrt(8) = 0.353...
+    assert_eq!(calculate_cosine_compatibility(&first, &high), 35);
+    assert_eq!(calculate_cosine_compatibility(&zero, &zero), 100);
+    assert_eq!(calculate_cosine_compatibility(&zero, &high), 0);
+}
+
+#[test]
+fn zero_weights_are_invalid() {
+    let weights = CompatibilityWeights {
+        similarity: [0; TRAIT_COUNT],
+        complementary: [0; TRAIT_COUNT],
+        normalization: Normalization::WeightedAverage,
+    };
+    assert!(weights.validate().is_err());
+    assert!(CompatibilityWeights::default().validate().is_ok());
+}
+
+#[test]
+fn score_extremes() {
+    let low = PersonalityTraits::from_values([0; TRAIT_COUNT]);
+    let high = PersonalityTraits::from_values([MAX_TRAIT_VALUE; TRAIT_COUNT]);
+    // Identical agents: full similarity, no complement
+    assert_eq!(calculate_compatibility_score(&low, &low), 84);
+    // Opposites everywhere: full complement, no similarity
+    assert_eq!(calculate_compatibility_score(&low, &high), 16);
+    // Alike where it matters, opposite where complements help
+    let complement = PersonalityTraits::from_values([0, 0, 0, MAX_TRAIT_VALUE, MAX_TRAIT_VALUE, 0, 0, 0]);
+    assert_eq!(calculate_compatibility_score(&low, &complement), 100);
+}
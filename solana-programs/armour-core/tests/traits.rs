@@ -0,0 +1,36 @@
+use armour_core::{CoreError, PersonalityTraits, MAX_TRAIT_VALUE, TRAIT_COUNT};
+
+fn with_trait(index: usize, value: u8) -> PersonalityTraits {
+    let mut values = [50; TRAIT_COUNT];
+    values[index] = value;
+    PersonalityTraits::from_values(values)
+}
+
+#[test]
+fn bounds_are_valid() {
+    assert_eq!(PersonalityTraits::from_values([0; TRAIT_COUNT]).validate(), Ok(()));
+    assert_eq!(PersonalityTraits::from_values([MAX_TRAIT_VALUE; TRAIT_COUNT]).validate(), Ok(()));
+}
+
+#[test]
+fn each_trait_accepts_its_bounds() {
+    for index in 0..TRAIT_COUNT {
+        assert_eq!(with_trait(index, 0).validate(), Ok(()), "trait {}", index);
+        assert_eq!(with_trait(index, MAX_TRAIT_VALUE).validate(), Ok(()), "trait {}", index);
+    }
+}
+
+#[test]
+fn each_trait_rejects_values_above_max() {
+    for index in 0..TRAIT_COUNT {
+        for value in [MAX_TRAIT_VALUE + 1, u8::MAX] {
+            assert_eq!(
+                with_trait(index, value).validate(),
+                Err(CoreError::InvalidTraitValue),
+                "trait {} = {}",
+                index,
+                value
+            );
+        }
+    }
+}
@@ -0,0 +1,26 @@
+// Parse the programs' sources into Anchor IDLs, the same way `anchor build`
+// does, so the shipped event schemas always match the code they decode.
+use std::path::{Path, PathBuf};
+
+use armour_codegen::{parse_program, parse_shared_types};
+
+const PROGRAMS: [&str; 3] = ["agent-registry", "influence", "relationship"];
+
+fn main() {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+
+    for program in PROGRAMS {
+        let source = format!("../{}/src", program);
+        println!("cargo:rerun-if-changed={}", source);
+        let idl = parse_program(Path::new(&format!("../{}", program))).unwrap_or_else(|err| panic!("{}: {}", program, err));
+        let json = serde_json::to_string(&idl).unwrap();
+        std::fs::write(out_dir.join(format!("{}.json", program)), json).unwrap();
+    }
+
+    // Shared types are declared with plain borsh derives and armour-core has
+    // no program module; see parse_shared_types
+    let core_source = "../armour-core/src";
+    println!("cargo:rerun-if-changed={}", core_source);
+    let types = parse_shared_types(Path::new("../armour-core"), &out_dir).unwrap_or_else(|err| panic!("armour-core: {}", err));
+    std::fs::write(out_dir.join("armour-core.json"), serde_json::to_string(&types).unwrap()).unwrap();
+}
@@ -0,0 +1,139 @@
+// Borsh to JSON, driven by IDL types. Integers up to 64 bits become JSON
+// numbers and wider ones decimal strings; public keys are base58, `bytes`
+// base64 and fixed byte arrays (hashes, mostly) hex. Unit enum variants
+// become their name, and variants with fields `{ "Variant": fields }`.
+use base64::Engine;
+use serde_json::{Map, Number, Value};
+
+use crate::idl::{EnumFields, IdlEventField, IdlField, IdlType, IdlTypeDefinitionTy};
+use crate::SchemaRegistry;
+
+pub(crate) fn fields(
+    registry: &SchemaRegistry,
+    fields: &[IdlEventField],
+    data: &mut &[u8],
+) -> Result<Value, String> {
+    let mut object = Map::new();
+    for field in fields {
+        object.insert(field.name.clone(), value(registry, &field.ty, data)?);
+    }
+    Ok(Value::Object(object))
+}
+
+fn struct_fields(registry: &SchemaRegistry, fields: &[IdlField], data: &mut &[u8]) -> Result<Value, String> {
+    let mut object = Map::new();
+    for field in fields {
+        object.insert(field.name.clone(), value(registry, &field.ty, data)?);
+    }
+    Ok(Value::Object(object))
+}
+
+pub(crate) fn value(registry: &SchemaRegistry, ty: &IdlType, data: &mut &[u8]) -> Result<Value, String> {
+    Ok(match ty {
+        IdlType::Bool => match take::<1>(data)?[0] {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            other => return Err(format!("invalid bool {}", other)),
+        },
+        IdlType::U8 => u8::from_le_bytes(take(data)?).into(),
+        IdlType::I8 => i8::from_le_bytes(take(data)?).into(),
+        IdlType::U16 => u16::from_le_bytes(take(data)?).into(),
+        IdlType::I16 => i16::from_le_bytes(take(data)?).into(),
+        IdlType::U32 => u32::from_le_bytes(take(data)?).into(),
+        IdlType::I32 => i32::from_le_bytes(take(data)?).into(),
+        IdlType::U64 => u64::from_le_bytes(take(data)?).into(),
+        IdlType::I64 => i64::from_le_bytes(take(data)?).into(),
+        IdlType::U128 => Value::String(u128::from_le_bytes(take(data)?).to_string()),
+        IdlType::I128 => Value::String(i128::from_le_bytes(take(data)?).to_string()),
+        IdlType::F32 => float(f32::from_le_bytes(take(data)?) as f64),
+        IdlType::F64 => float(f64::from_le_bytes(take(data)?)),
+        IdlType::PublicKey => Value::String(bs58::encode(take::<32>(data)?).into_string()),
+        IdlType::String => {
+            let bytes = take_len(data)?;
+            Value::String(String::from_utf8(bytes.to_vec()).map_err(|err| err.to_string())?)
+        }
+        IdlType::Bytes => Value::String(base64::engine::general_purpose::STANDARD.encode(take_len(data)?)),
+        IdlType::Option(inner) => match take::<1>(data)?[0] {
+            0 => Value::Null,
+            1 => value(registry, inner, data)?,
+            other => return Err(format!("invalid option tag {}", other)),
+        },
+        IdlType::Vec(inner) => {
+            let len = u32::from_le_bytes(take(data)?);
+            let mut items = Vec::new();
+            for _ in 0..len {
+                items.push(value(registry, inner, data)?);
+            }
+            Value::Array(items)
+        }
+        IdlType::Array(inner, len) if **inner == IdlType::U8 => {
+            Value::String(crate::hex(take_slice(data, *len)?))
+        }
+        IdlType::Array(inner, len) => {
+            let mut items = Vec::with_capacity(*len);
+            for _ in 0..*len {
+                items.push(value(registry, inner, data)?);
+            }
+            Value::Array(items)
+        }
+        IdlType::Defined(name) => {
+            let definition = registry
+                .type_definition(name)
+                .ok_or_else(|| format!("unknown type {}", name))?;
+            match &definition.ty {
+                IdlTypeDefinitionTy::Struct { fields } => struct_fields(registry, fields, data)?,
+                IdlTypeDefinitionTy::Enum { variants } => {
+                    let index = take::<1>(data)?[0] as usize;
+                    let variant = variants
+                        .get(index)
+                        .ok_or_else(|| format!("invalid {} variant {}", name, index))?;
+                    match &variant.fields {
+                        None => Value::String(variant.name.clone()),
+                        Some(EnumFields::Named(fields)) => {
+                            tagged(&variant.name, struct_fields(registry, fields, data)?)
+                        }
+                        Some(EnumFields::Tuple(types)) => {
+                            let mut items = Vec::with_capacity(types.len());
+                            for ty in types {
+                                items.push(value(registry, ty, data)?);
+                            }
+                            tagged(&variant.name, Value::Array(items))
+                        }
+                    }
+                }
+            }
+        }
+        IdlType::U256 | IdlType::I256 => return Err("256-bit integers are not supported".to_string()),
+    })
+}
+
+fn take<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], String> {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(take_slice(data, N)?);
+    Ok(bytes)
+}
+
+fn take_slice<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if data.len() < len {
+        return Err("unexpected end of data".to_string());
+    }
+    let (bytes, rest) = data.split_at(len);
+    *data = rest;
+    Ok(bytes)
+}
+
+// A u32 length prefix and that many bytes
+fn take_len<'a>(data: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    let len = u32::from_le_bytes(take(data)?) as usize;
+    take_slice(data, len)
+}
+
+fn float(value: f64) -> Value {
+    Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+fn tagged(name: &str, value: Value) -> Value {
+    let mut object = Map::new();
+    object.insert(name.to_string(), value);
+    Value::Object(object)
+}
@@ -0,0 +1,307 @@
+// Schemas for every event the registry, influence and relationship programs
+// emit, and a decoder from raw event data (or any account the programs
+// define) to JSON, for services that consume events without linking the
+// programs' types. The schemas are the programs'
+// Anchor IDL events, generated from their sources at build time (build.rs),
+// with each event's discriminator and schema version.
+use std::collections::HashMap;
+use std::fmt;
+
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use serde_json::{json, Value};
+
+mod decode;
+mod raw;
+
+pub use anchor_syn::idl;
+pub use raw::{cpi_event, log_events, RawEvent};
+
+use idl::{IdlEventField, IdlTypeDefinition};
+
+const PROGRAM_IDLS: [(&str, &str); 3] = [
+    ("agent_registry", include_str!(concat!(env!("OUT_DIR"), "/agent-registry.json"))),
+    ("influence", include_str!(concat!(env!("OUT_DIR"), "/influence.json"))),
+    ("relationship", include_str!(concat!(env!("OUT_DIR"), "/relationship.json"))),
+];
+const CORE_TYPES: &str = include_str!(concat!(env!("OUT_DIR"), "/armour-core.json"));
+
+#[derive(Clone, Debug)]
+pub struct EventSchema {
+    pub program: &'static str,
+    pub program_id: Pubkey,
+    pub name: String,
+    pub discriminator: [u8; 8],
+    // Schema version the event leads with, for programs that version their
+    // events; the version this crate was built against
+    pub version: Option<u8>,
+    pub fields: Vec<IdlEventField>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedEvent {
+    pub program: &'static str,
+    pub program_id: Pubkey,
+    pub name: String,
+    pub version: Option<u8>,  // As emitted
+    pub data: Value,          // Fields by their IDL (camelCase) names
+}
+
+impl DecodedEvent {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "program": self.program,
+            "programId": self.program_id.to_string(),
+            "event": self.name,
+            "version": self.version,
+            "data": self.data,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AccountSchema {
+    pub program: &'static str,
+    pub program_id: Pubkey,
+    pub name: String,
+    pub discriminator: [u8; 8],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedAccount {
+    pub program: &'static str,
+    pub program_id: Pubkey,
+    pub name: String,
+    pub data: Value,  // Fields by their IDL (camelCase) names
+}
+
+impl DecodedAccount {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "program": self.program,
+            "programId": self.program_id.to_string(),
+            "account": self.name,
+            "data": self.data,
+        })
+    }
+}
+
+pub struct SchemaRegistry {
+    events: Vec<EventSchema>,
+    accounts: Vec<AccountSchema>,
+    types: HashMap<String, IdlTypeDefinition>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        let mut events = Vec::new();
+        let mut accounts = Vec::new();
+        let mut types = HashMap::new();
+        let core_types: Vec<IdlTypeDefinition> = serde_json::from_str(CORE_TYPES).expect("generated IDL");
+        types.extend(core_types.into_iter().map(|ty| (ty.name.clone(), ty)));
+
+        for (program, idl) in PROGRAM_IDLS {
+            let idl: idl::Idl = serde_json::from_str(idl).expect("generated IDL");
+            let (program_id, schema_version) = match program {
+                "agent_registry" => (agent_registry::ID, None),
+                "influence" => (influence::ID, None),
+                _ => (relationship::ID, Some(relationship::EVENT_SCHEMA_VERSION)),
+            };
+            for event in idl.events.into_iter().flatten() {
+                let versioned = event.fields.first().map_or(false, |field| field.name == "version");
+                events.push(EventSchema {
+                    program,
+                    program_id,
+                    discriminator: event_discriminator(&event.name),
+                    version: schema_version.filter(|_| versioned),
+                    name: event.name,
+                    fields: event.fields,
+                });
+            }
+            accounts.extend(idl.accounts.iter().map(|account| AccountSchema {
+                program,
+                program_id,
+                name: account.name.clone(),
+                discriminator: account_discriminator(&account.name),
+            }));
+            types.extend(idl.accounts.into_iter().chain(idl.types).map(|ty| (ty.name.clone(), ty)));
+        }
+        Self { events, accounts, types }
+    }
+
+    pub fn events(&self) -> &[EventSchema] {
+        &self.events
+    }
+
+    pub fn event(&self, program_id: &Pubkey, discriminator: &[u8]) -> Option<&EventSchema> {
+        self.events
+            .iter()
+            .find(|event| event.program_id == *program_id && event.discriminator[..] == *discriminator)
+    }
+
+    pub fn event_by_name(&self, name: &str) -> Option<&EventSchema> {
+        self.events.iter().find(|event| event.name == name)
+    }
+
+    pub fn accounts(&self) -> &[AccountSchema] {
+        &self.accounts
+    }
+
+    pub fn type_definition(&self, name: &str) -> Option<&IdlTypeDefinition> {
+        self.types.get(name)
+    }
+
+    // Decode discriminator + Borsh body. Ok(None) for events of programs or
+    // types this registry does not know about.
+    pub fn decode(&self, raw: &RawEvent) -> Result<Option<DecodedEvent>, EventError> {
+        if raw.data.len() < 8 {
+            return Ok(None);
+        }
+        let (discriminator, body) = raw.data.split_at(8);
+        let Some(schema) = self.event(&raw.program_id, discriminator) else {
+            return Ok(None);
+        };
+
+        // Anything newer than the version this crate was built against may
+        // have fields it cannot decode, so it is reported instead of misread
+        let version = match schema.version {
+            Some(supported) => match body.first() {
+                Some(version) if *version <= supported => Some(*version),
+                Some(version) => {
+                    return Err(EventError::UnsupportedSchema { event: schema.name.clone(), version: *version })
+                }
+                None => return Err(EventError::Decode(format!("{}: empty event", schema.name))),
+            },
+            None => None,
+        };
+
+        let data = decode::fields(self, &schema.fields, &mut &body[..])
+            .map_err(|err| EventError::Decode(format!("{}: {}", schema.name, err)))?;
+        Ok(Some(DecodedEvent {
+            program: schema.program,
+            program_id: schema.program_id,
+            name: schema.name.clone(),
+            version,
+            data,
+        }))
+    }
+
+    // Borsh data of any account or type the programs define, such as an
+    // account's body after its discriminator
+    pub fn decode_type(&self, name: &str, data: &[u8]) -> Result<Value, EventError> {
+        decode::value(self, &idl::IdlType::Defined(name.to_string()), &mut &data[..])
+            .map_err(|err| EventError::Decode(format!("{}: {}", name, err)))
+    }
+
+    // An account's data, identified by its owner and discriminator. Ok(None)
+    // for accounts of other programs or types this registry does not know.
+    pub fn decode_account(&self, owner: &Pubkey, data: &[u8]) -> Result<Option<DecodedAccount>, EventError> {
+        if data.len() < 8 {
+            return Ok(None);
+        }
+        let (discriminator, body) = data.split_at(8);
+        let Some(schema) = self
+            .accounts
+            .iter()
+            .find(|account| account.program_id == *owner && account.discriminator[..] == *discriminator)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(DecodedAccount {
+            program: schema.program,
+            program_id: schema.program_id,
+            name: schema.name.clone(),
+            data: self.decode_type(&schema.name, body)?,
+        }))
+    }
+
+    // Every known event in a transaction's logs
+    pub fn decode_logs(&self, logs: &[String]) -> Result<Vec<DecodedEvent>, EventError> {
+        let mut decoded = Vec::new();
+        for raw in log_events(logs)? {
+            decoded.extend(self.decode(&raw)?);
+        }
+        Ok(decoded)
+    }
+
+    // An emit_cpi! event from an inner instruction's program and data, None
+    // if the instruction is not a known event
+    pub fn decode_cpi_event(
+        &self,
+        program_id: &Pubkey,
+        instruction_data: &[u8],
+    ) -> Result<Option<DecodedEvent>, EventError> {
+        match cpi_event(program_id, instruction_data) {
+            Some(raw) => self.decode(&raw),
+            None => Ok(None),
+        }
+    }
+
+    // The registry as JSON, for consumers in other languages: every event
+    // with its program, discriminator (hex) and version, plus the type
+    // definitions its fields refer to
+    pub fn to_json(&self) -> Value {
+        let events: Vec<Value> = self
+            .events
+            .iter()
+            .map(|event| {
+                json!({
+                    "program": event.program,
+                    "programId": event.program_id.to_string(),
+                    "name": event.name,
+                    "discriminator": hex(&event.discriminator),
+                    "version": event.version,
+                    "fields": event.fields,
+                })
+            })
+            .collect();
+        let mut types: Vec<&IdlTypeDefinition> = self.types.values().collect();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        json!({ "events": events, "types": types })
+    }
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Anchor's event discriminator: the first 8 bytes of sha256("event:<Name>")
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(format!("event:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+// Anchor's account discriminator: the first 8 bytes of sha256("account:<Name>")
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(format!("account:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Errors
+
+#[derive(Debug)]
+pub enum EventError {
+    Decode(String),
+    UnsupportedSchema { event: String, version: u8 },
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::Decode(err) => write!(f, "Event decoding failed: {}", err),
+            EventError::UnsupportedSchema { event, version } => {
+                write!(f, "{} uses schema version {}, newer than this decoder supports", event, version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
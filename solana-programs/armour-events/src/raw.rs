@@ -0,0 +1,55 @@
+// Extraction of raw events from transactions. Registry and influence events
+// arrive as `Program data:` log lines (emit!); relationship events arrive as
+// self-CPI instruction data (emit_cpi!) prefixed with Anchor's event
+// instruction tag.
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use base64::Engine;
+
+use crate::EventError;
+
+// An event as emitted: the emitting program and discriminator + Borsh body
+#[derive(Clone, Debug)]
+pub struct RawEvent {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+// Events emitted with emit! in a transaction's logs, attributed to the program
+// on top of the invocation stack when the line was logged
+pub fn log_events(logs: &[String]) -> Result<Vec<RawEvent>, EventError> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if let Some(rest) = line.strip_prefix("Program data: ") {
+            let Some(program_id) = stack.last() else { continue };
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(rest.trim())
+                .map_err(|err| EventError::Decode(err.to_string()))?;
+            events.push(RawEvent { program_id: *program_id, data });
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut parts = rest.split_whitespace();
+            let (Some(id), Some(action)) = (parts.next(), parts.next()) else { continue };
+            match action {
+                "invoke" => {
+                    if let Ok(program_id) = id.parse() {
+                        stack.push(program_id);
+                    }
+                }
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(events)
+}
+
+// An emit_cpi! event: an instruction from a program to itself carrying the
+// event tag, discriminator and Borsh body
+pub fn cpi_event(program_id: &Pubkey, instruction_data: &[u8]) -> Option<RawEvent> {
+    instruction_data
+        .strip_prefix(&EVENT_IX_TAG_LE[..])
+        .map(|data| RawEvent { program_id: *program_id, data: data.to_vec() })
+}
@@ -0,0 +1,425 @@
+// Runs arbitrary sequences of registry, influence and relationship
+// instructions, signed by arbitrary actors and optionally with one account
+// swapped for another known account, and checks after every transaction:
+// - agent owners only change through transfer_ownership signed by the owner
+//   and the new owner
+// - influence supply moves only by minted and burned amounts, and equals the
+//   sum of all known token account balances
+// - agent, relationship and interaction nonce counters never decrease
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Mint;
+use arbitrary::{Arbitrary, Unstructured};
+use honggfuzz::fuzz;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+
+use agent_registry::{AgentData, PersonalityTraits};
+use armour_test_utils::{ArmourTest, LAMPORTS_PER_SOL};
+use relationship::{InteractionNonce, InteractionType, RelationshipStatus, RelationshipType};
+
+const ACTORS: usize = 4;
+const MAX_STEPS: usize = 24;
+
+#[derive(Arbitrary, Debug)]
+enum FuzzInstruction {
+    RegisterAgent { owner: u8, name: u8, traits: [u8; 8] },
+    UpdateAgentStatus { signer: u8, agent: u8, is_active: bool },
+    UpdatePersonalityTraits { signer: u8, agent: u8, traits: [u8; 8] },
+    TransferOwnership { signer: u8, agent: u8, new_owner: u8, new_owner_signs: bool },
+    CreateRelationship { signer: u8, agent_a: u8, agent_b: u8, relationship_type: u8 },
+    UpdateRelationshipStatus { signer_one: u8, signer_two: u8, relationship: u8, status: u8 },
+    RecordInteraction { signer: u8, relationship: u8, interaction_type: u8, index_skew: i8, data: String },
+    MintTokens { signer: u8, recipient: u8, amount: u64 },
+    TransferTokens { signer: u8, owner: u8, recipient: u8, amount: u64 },
+    BurnTokens { signer: u8, amount: u64 },
+    Warp { seconds: u16 },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Step {
+    instruction: FuzzInstruction,
+    // Replace account `.0` of the instruction with known account `.1`
+    mutation: Option<(u8, u8)>,
+}
+
+struct World {
+    test: ArmourTest,
+    actors: Vec<Keypair>,  // actors[0] is the mint authority / config admin
+    agents: Vec<Pubkey>,
+    relationships: Vec<Pubkey>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Snapshot {
+    agents: HashMap<Pubkey, (Pubkey, u32, u32)>,  // owner, match_count, interaction_count
+    relationships: HashMap<Pubkey, (u32, u64)>,   // interaction_count, nonce next_index
+    supply: u64,
+    balances: u64,
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    loop {
+        fuzz!(|data: &[u8]| {
+            let mut input = Unstructured::new(data);
+            let Ok(steps) = input.arbitrary_len::<Step>() else { return };
+            let steps: Vec<Step> = (0..steps.min(MAX_STEPS))
+                .filter_map(|_| Step::arbitrary(&mut input).ok())
+                .collect();
+            runtime.block_on(run(steps));
+        });
+    }
+}
+
+async fn run(steps: Vec<Step>) {
+    let mut test = ArmourTest::start().await;
+    let mut actors = vec![Keypair::from_bytes(&test.admin.to_bytes()).unwrap()];
+    for _ in 1..ACTORS {
+        actors.push(test.funded_keypair(100 * LAMPORTS_PER_SOL).await);
+    }
+    let mut world = World { test, actors, agents: Vec::new(), relationships: Vec::new() };
+
+    for step in steps {
+        let before = world.snapshot().await;
+        let Some((instruction, signers, expected)) = world.build(&step.instruction).await else {
+            continue;
+        };
+        let instruction = world.mutate(instruction, step.mutation);
+        let signer_refs: Vec<&Keypair> = signers.iter().map(|index| &world.actors[*index]).collect();
+        let signer_keys: Vec<Pubkey> = signer_refs.iter().map(|signer| signer.pubkey()).collect();
+        let result = world.test.process(&[instruction], &signer_refs).await;
+
+        let after = world.snapshot().await;
+        if result.is_err() {
+            assert_eq!(before, after, "failed transaction changed state: {:?}", step);
+            continue;
+        }
+        world.track(&step.instruction);
+        check_invariants(&before, &after, &expected, &signer_keys, &step);
+    }
+}
+
+// What a successful step is allowed to change
+#[derive(Default)]
+struct Expected {
+    transfer: Option<(Pubkey, Pubkey)>,  // agent, new owner
+    minted: u64,
+    burned: u64,
+}
+
+fn check_invariants(before: &Snapshot, after: &Snapshot, expected: &Expected, signers: &[Pubkey], step: &Step) {
+    for (agent, (owner, match_count, interaction_count)) in &after.agents {
+        let Some((previous_owner, previous_matches, previous_interactions)) = before.agents.get(agent) else {
+            continue;
+        };
+        if owner != previous_owner {
+            assert_eq!(expected.transfer, Some((*agent, *owner)), "unexpected owner change: {:?}", step);
+            assert!(signers.contains(previous_owner), "owner changed without the owner's signature: {:?}", step);
+            assert!(signers.contains(owner), "owner changed without the new owner's signature: {:?}", step);
+        }
+        assert!(match_count >= previous_matches, "agent match count decreased: {:?}", step);
+        assert!(interaction_count >= previous_interactions, "agent interaction count decreased: {:?}", step);
+    }
+    for (relationship, (interaction_count, next_index)) in &after.relationships {
+        if let Some((previous_count, previous_index)) = before.relationships.get(relationship) {
+            assert!(interaction_count >= previous_count, "interaction count decreased: {:?}", step);
+            assert!(next_index >= previous_index, "interaction nonce decreased: {:?}", step);
+        }
+    }
+    assert_eq!(
+        after.supply as i128,
+        before.supply as i128 + expected.minted as i128 - expected.burned as i128,
+        "supply changed by more than minted and burned amounts: {:?}",
+        step
+    );
+    assert_eq!(after.supply, after.balances, "supply does not match token balances: {:?}", step);
+}
+
+impl World {
+    fn actor(&self, index: u8) -> usize {
+        index as usize % self.actors.len()
+    }
+
+    fn agent(&self, index: u8) -> Option<Pubkey> {
+        (!self.agents.is_empty()).then(|| self.agents[index as usize % self.agents.len()])
+    }
+
+    fn relationship(&self, index: u8) -> Option<Pubkey> {
+        (!self.relationships.is_empty()).then(|| self.relationships[index as usize % self.relationships.len()])
+    }
+
+    fn token_account(&self, actor: usize) -> Pubkey {
+        get_associated_token_address(&self.actors[actor].pubkey(), &self.test.influence_mint)
+    }
+
+    // Every account an instruction could be pointed at by a mutation
+    fn known_accounts(&self) -> Vec<Pubkey> {
+        let mut accounts: Vec<Pubkey> = self.actors.iter().map(|actor| actor.pubkey()).collect();
+        accounts.extend((0..self.actors.len()).map(|actor| self.token_account(actor)));
+        accounts.extend(&self.agents);
+        accounts.extend(&self.relationships);
+        accounts.push(self.test.influence_mint);
+        accounts
+    }
+
+    fn mutate(&self, mut instruction: Instruction, mutation: Option<(u8, u8)>) -> Instruction {
+        if let Some((slot, replacement)) = mutation {
+            let known = self.known_accounts();
+            if !instruction.accounts.is_empty() {
+                let slot = slot as usize % instruction.accounts.len();
+                instruction.accounts[slot].pubkey = known[replacement as usize % known.len()];
+            }
+        }
+        instruction
+    }
+
+    // Record accounts created by a successful step
+    fn track(&mut self, instruction: &FuzzInstruction) {
+        match instruction {
+            FuzzInstruction::RegisterAgent { owner, name, .. } => {
+                let owner = self.actors[self.actor(*owner)].pubkey();
+                let agent = agent_registry_cpi::pda::find_agent_address(&owner, &agent_name(*name)).0;
+                if !self.agents.contains(&agent) {
+                    self.agents.push(agent);
+                }
+            }
+            FuzzInstruction::CreateRelationship { agent_a, agent_b, .. } => {
+                if let (Some(a), Some(b)) = (self.agent(*agent_a), self.agent(*agent_b)) {
+                    let (one, two) = if a < b { (a, b) } else { (b, a) };
+                    let relationship = relationship_cpi::pda::find_relationship_address(&one, &two).0;
+                    if !self.relationships.contains(&relationship) {
+                        self.relationships.push(relationship);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn build(&mut self, instruction: &FuzzInstruction) -> Option<(Instruction, Vec<usize>, Expected)> {
+        let mut expected = Expected::default();
+        let timestamp = self.test.clock().await.unix_timestamp;
+        let (built, signers) = match instruction {
+            FuzzInstruction::RegisterAgent { owner, name, traits } => {
+                let owner = self.actor(*owner);
+                let instruction = register_agent_instruction(
+                    self.actors[owner].pubkey(),
+                    agent_name(*name),
+                    traits,
+                    self.test.merkle_tree,
+                    self.test.collection_mint,
+                );
+                (instruction, vec![owner])
+            }
+            FuzzInstruction::UpdateAgentStatus { signer, agent, is_active } => {
+                let signer = self.actor(*signer);
+                let agent = self.agent(*agent)?;
+                let instruction = agent_registry_cpi::builders::update_agent_status(
+                    self.actors[signer].pubkey(),
+                    agent,
+                    None,
+                    *is_active,
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::UpdatePersonalityTraits { signer, agent, traits } => {
+                let signer = self.actor(*signer);
+                let agent = self.agent(*agent)?;
+                let instruction = agent_registry_cpi::builders::update_personality_traits(
+                    self.actors[signer].pubkey(),
+                    agent,
+                    PersonalityTraits::from_values(*traits),
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::TransferOwnership { signer, agent, new_owner, new_owner_signs } => {
+                let signer = self.actor(*signer);
+                let agent = self.agent(*agent)?;
+                let new_owner_index = self.actor(*new_owner);
+                let new_owner = self.actors[new_owner_index].pubkey();
+                expected.transfer = Some((agent, new_owner));
+                let mut instruction =
+                    agent_registry_cpi::builders::transfer_ownership(self.actors[signer].pubkey(), new_owner, agent);
+                instruction.accounts[1].is_signer = *new_owner_signs;
+                if *new_owner_signs {
+                    (instruction, vec![signer, new_owner_index])
+                } else {
+                    (instruction, vec![signer])
+                }
+            }
+            FuzzInstruction::CreateRelationship { signer, agent_a, agent_b, relationship_type } => {
+                let signer = self.actor(*signer);
+                let (a, b) = (self.agent(*agent_a)?, self.agent(*agent_b)?);
+                let (one, two) = if a < b { (a, b) } else { (b, a) };
+                let relationship_type = RelationshipType::try_from(relationship_type % 6).ok()?;
+                let instruction = relationship_cpi::builders::create_relationship(
+                    self.actors[signer].pubkey(),
+                    one,
+                    two,
+                    None,
+                    relationship_type,
+                    50,
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::UpdateRelationshipStatus { signer_one, signer_two, relationship, status } => {
+                let (one, two) = (self.actor(*signer_one), self.actor(*signer_two));
+                let relationship = self.relationship(*relationship)?;
+                let status = RelationshipStatus::try_from(status % 3).ok()?;
+                let instruction = relationship_cpi::builders::update_relationship_status(
+                    self.actors[one].pubkey(),
+                    self.actors[two].pubkey(),
+                    relationship,
+                    None,
+                    status,
+                );
+                (instruction, vec![one, two])
+            }
+            FuzzInstruction::RecordInteraction { signer, relationship, interaction_type, index_skew, data } => {
+                let signer = self.actor(*signer);
+                let relationship = self.relationship(*relationship)?;
+                let relationship_data = self.test.relationship(&relationship).await;
+                let nonce: InteractionNonce = self
+                    .test
+                    .account(&relationship_cpi::pda::find_interaction_nonce_address(&relationship).0)
+                    .await;
+                let interaction_type = match interaction_type % 5 {
+                    0 => InteractionType::Conversation,
+                    1 => InteractionType::Activity,
+                    2 => InteractionType::Gift,
+                    3 => InteractionType::DateEvent,
+                    _ => InteractionType::Milestone,
+                };
+                let instruction = relationship_cpi::builders::record_interaction(
+                    self.actors[signer].pubkey(),
+                    self.actors[signer].pubkey(),
+                    relationship,
+                    relationship_data.agent_one,
+                    relationship_data.agent_two,
+                    nonce.next_index.saturating_add_signed(*index_skew as i64),
+                    interaction_type,
+                    data.chars().take(64).collect(),
+                    None,
+                    None,
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::MintTokens { signer, recipient, amount } => {
+                let signer = self.actor(*signer);
+                let recipient = self.actors[self.actor(*recipient)].pubkey();
+                expected.minted = *amount;
+                let instruction = influence_cpi::builders::mint_tokens(
+                    self.actors[signer].pubkey(),
+                    self.test.influence_mint,
+                    recipient,
+                    *amount,
+                    String::new(),
+                    timestamp,
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::TransferTokens { signer, owner, recipient, amount } => {
+                let signer = self.actor(*signer);
+                let owner = self.actors[self.actor(*owner)].pubkey();
+                let recipient = self.actors[self.actor(*recipient)].pubkey();
+                let instruction = influence_cpi::builders::transfer_tokens(
+                    self.actors[signer].pubkey(),
+                    owner,
+                    self.test.influence_mint,
+                    recipient,
+                    *amount,
+                    String::new(),
+                    timestamp,
+                    None,
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::BurnTokens { signer, amount } => {
+                let signer = self.actor(*signer);
+                let owner = self.actors[signer].pubkey();
+                let mint = self.test.influence_mint;
+                expected.burned = *amount;
+                let instruction = influence_cpi::builders::build(
+                    influence_cpi::accounts::BurnTokens {
+                        owner,
+                        mint,
+                        owner_token_account: self.token_account(signer),
+                        burn_record: Pubkey::find_program_address(
+                            &[b"burn_record", mint.as_ref(), owner.as_ref(), &timestamp.to_le_bytes()],
+                            &influence::ID,
+                        )
+                        .0,
+                        token_program: anchor_spl::token::ID,
+                        system_program: solana_sdk::system_program::ID,
+                        guardian: influence_cpi::pda::find_guardian_address().0,
+                    },
+                    influence_cpi::instruction::BurnTokens { amount: *amount, reason: String::new() },
+                );
+                (instruction, vec![signer])
+            }
+            FuzzInstruction::Warp { seconds } => {
+                self.test.warp_forward(*seconds as i64).await;
+                return None;
+            }
+        };
+        Some((built, signers, expected))
+    }
+
+    async fn snapshot(&mut self) -> Snapshot {
+        let mint = self.test.influence_mint;
+        let mut snapshot = Snapshot {
+            agents: HashMap::new(),
+            relationships: HashMap::new(),
+            supply: self.test.account::<Mint>(&mint).await.supply,
+            balances: 0,
+        };
+        for agent in self.agents.clone() {
+            let data: AgentData = self.test.agent(&agent).await;
+            snapshot.agents.insert(agent, (data.owner, data.match_count, data.interaction_count));
+        }
+        for relationship in self.relationships.clone() {
+            let data = self.test.relationship(&relationship).await;
+            let nonce: InteractionNonce = self
+                .test
+                .account(&relationship_cpi::pda::find_interaction_nonce_address(&relationship).0)
+                .await;
+            snapshot.relationships.insert(relationship, (data.interaction_count, nonce.next_index));
+        }
+        for actor in 0..self.actors.len() {
+            let token_account = self.token_account(actor);
+            if self.test.account_exists(&token_account).await {
+                snapshot.balances += self.test.token_balance(&token_account).await;
+            }
+        }
+        snapshot
+    }
+}
+
+fn agent_name(index: u8) -> String {
+    format!("agent-{}", index % 8)
+}
+
+fn register_agent_instruction(
+    owner: Pubkey,
+    name: String,
+    traits: &[u8; 8],
+    merkle_tree: Pubkey,
+    collection_mint: Pubkey,
+) -> Instruction {
+    armour_sdk::register_agent(
+        owner,
+        name.clone(),
+        format!("https://example.com/{}.json", name),
+        PersonalityTraits::from_values(*traits),
+        merkle_tree,
+        collection_mint,
+        None,
+    )
+}
@@ -0,0 +1,283 @@
+// Geyser plugin that streams registry, relationship and influence account
+// changes to NATS as they are applied by the validator. Updates are decoded
+// into the messages below and published as JSON on
+// `<subject_prefix>.agents`, `.relationships` and `.mints`, so matchmaking
+// backends see agent and relationship state within the slot instead of
+// polling RPC.
+//
+// Validator config (passed with --geyser-plugin-config):
+//   {
+//     "libpath": "/path/to/libarmour_geyser.so",
+//     "nats_url": "nats://127.0.0.1:4222",
+//     "subject_prefix": "armour",
+//     "skip_startup": true
+//   }
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use serde::{Deserialize, Serialize};
+use solana_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, Result as PluginResult,
+};
+
+use agent_registry::AgentData;
+use influence::MintConfig;
+use relationship::RelationshipData;
+
+// Updates buffered between the validator thread and the publisher. When NATS
+// falls this far behind, updates are dropped rather than stalling replay.
+const QUEUE_CAPACITY: usize = 65_536;
+
+#[derive(Deserialize)]
+struct PluginConfig {
+    nats_url: String,
+    #[serde(default = "default_subject_prefix")]
+    subject_prefix: String,
+    #[serde(default = "default_skip_startup")]
+    skip_startup: bool,  // Skip the snapshot load at validator start
+}
+
+fn default_subject_prefix() -> String {
+    "armour".to_string()
+}
+
+fn default_skip_startup() -> bool {
+    true
+}
+
+// Messages
+#[derive(Serialize)]
+pub struct AccountUpdate<T> {
+    pub pubkey: String,
+    pub slot: u64,
+    pub write_version: u64,
+    pub is_startup: bool,
+    pub account: T,
+}
+
+#[derive(Serialize)]
+pub struct AgentMessage {
+    pub owner: String,
+    pub name: String,
+    pub metadata_uri: String,
+    pub is_active: bool,
+    pub is_frozen: bool,
+    pub personality_traits: [u8; 8],  // Declaration order, see PersonalityTraits::values
+    pub generation: u16,
+    pub match_count: u32,
+    pub interaction_count: u32,
+    pub last_active: i64,
+}
+
+#[derive(Serialize)]
+pub struct RelationshipMessage {
+    pub agent_one: String,
+    pub agent_two: String,
+    pub owner_one: String,
+    pub owner_two: String,
+    pub relationship_type: u8,  // RelationshipType variant index
+    pub status: u8,             // RelationshipStatus variant index
+    pub visibility: u8,         // Visibility variant index
+    pub compatibility_score: u8,
+    pub health: u8,
+    pub interaction_count: u32,
+    pub last_interaction: i64,
+    pub expires_at: i64,
+}
+
+#[derive(Serialize)]
+pub struct MintMessage {
+    pub mint: String,
+    pub authority: String,
+    pub symbol: String,
+    pub is_active: bool,
+}
+
+struct Outgoing {
+    subject: String,
+    payload: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct ArmourGeyserPlugin {
+    subject_prefix: String,
+    skip_startup: bool,
+    sender: Option<SyncSender<Outgoing>>,
+    publisher: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for ArmourGeyserPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ArmourGeyserPlugin")
+    }
+}
+
+impl GeyserPlugin for ArmourGeyserPlugin {
+    fn name(&self) -> &'static str {
+        "armour-geyser"
+    }
+
+    fn on_load(&mut self, config_file: &str) -> PluginResult<()> {
+        let contents = std::fs::read_to_string(config_file)?;
+        let config: PluginConfig = serde_json::from_str(&contents)
+            .map_err(|err| GeyserPluginError::ConfigFileReadError { msg: err.to_string() })?;
+
+        let connection = nats::connect(&config.nats_url)
+            .map_err(|err| GeyserPluginError::Custom(Box::new(err)))?;
+        let (sender, receiver) = mpsc::sync_channel::<Outgoing>(QUEUE_CAPACITY);
+        let publisher = std::thread::Builder::new()
+            .name("armour-geyser-nats".to_string())
+            .spawn(move || {
+                for message in receiver {
+                    if let Err(err) = connection.publish(&message.subject, &message.payload) {
+                        log::error!("armour-geyser: publish to {} failed: {}", message.subject, err);
+                    }
+                }
+                let _ = connection.flush();
+            })?;
+
+        self.subject_prefix = config.subject_prefix;
+        self.skip_startup = config.skip_startup;
+        self.sender = Some(sender);
+        self.publisher = Some(publisher);
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        // Dropping the sender ends the publisher loop once the queue drains
+        self.sender = None;
+        if let Some(publisher) = self.publisher.take() {
+            let _ = publisher.join();
+        }
+    }
+
+    fn update_account(
+        &self,
+        account: ReplicaAccountInfoVersions,
+        slot: u64,
+        is_startup: bool,
+    ) -> PluginResult<()> {
+        if is_startup && self.skip_startup {
+            return Ok(());
+        }
+        let (pubkey, owner, data, write_version) = match account {
+            ReplicaAccountInfoVersions::V0_0_1(info) => (info.pubkey, info.owner, info.data, info.write_version),
+            ReplicaAccountInfoVersions::V0_0_2(info) => (info.pubkey, info.owner, info.data, info.write_version),
+            ReplicaAccountInfoVersions::V0_0_3(info) => (info.pubkey, info.owner, info.data, info.write_version),
+        };
+        let (Ok(pubkey), Ok(owner)) = (Pubkey::try_from(pubkey), Pubkey::try_from(owner)) else {
+            return Ok(());
+        };
+
+        let Some((topic, payload)) = decode_account(&pubkey, &owner, data, slot, write_version, is_startup) else {
+            return Ok(());
+        };
+        let Some(sender) = &self.sender else { return Ok(()) };
+        let message = Outgoing {
+            subject: format!("{}.{}", self.subject_prefix, topic),
+            payload,
+        };
+        match sender.try_send(message) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(message)) => {
+                log::warn!("armour-geyser: queue full, dropping update on {}", message.subject);
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Ok(()),
+        }
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        false
+    }
+}
+
+// The subject suffix and JSON payload for an update to one of our accounts,
+// or None for accounts of other programs or types
+pub fn decode_account(
+    pubkey: &Pubkey,
+    owner: &Pubkey,
+    data: &[u8],
+    slot: u64,
+    write_version: u64,
+    is_startup: bool,
+) -> Option<(&'static str, Vec<u8>)> {
+    let discriminator = data.get(..8)?;
+    let encode = |topic, account: serde_json::Value| {
+        let update = AccountUpdate {
+            pubkey: pubkey.to_string(),
+            slot,
+            write_version,
+            is_startup,
+            account,
+        };
+        Some((topic, serde_json::to_vec(&update).ok()?))
+    };
+
+    if *owner == agent_registry::ID && discriminator == &AgentData::DISCRIMINATOR[..] {
+        let agent = AgentData::try_deserialize(&mut &data[..]).ok()?;
+        let message = AgentMessage {
+            owner: agent.owner.to_string(),
+            name: agent.name,
+            metadata_uri: agent.metadata_uri,
+            is_active: agent.is_active,
+            is_frozen: agent.is_frozen,
+            personality_traits: agent.personality_traits.values(),
+            generation: agent.generation,
+            match_count: agent.match_count,
+            interaction_count: agent.interaction_count,
+            last_active: agent.last_active,
+        };
+        return encode("agents", serde_json::to_value(message).ok()?);
+    }
+
+    if *owner == relationship::ID && discriminator == &RelationshipData::DISCRIMINATOR[..] {
+        // Zero-copy account: read the fixed layout after the discriminator
+        let body = data.get(8..8 + std::mem::size_of::<RelationshipData>())?;
+        let relationship: RelationshipData = bytemuck::try_pod_read_unaligned(body).ok()?;
+        let message = RelationshipMessage {
+            agent_one: relationship.agent_one.to_string(),
+            agent_two: relationship.agent_two.to_string(),
+            owner_one: relationship.owner_one.to_string(),
+            owner_two: relationship.owner_two.to_string(),
+            relationship_type: relationship.relationship_type,
+            status: relationship.status,
+            visibility: relationship.visibility,
+            compatibility_score: relationship.compatibility_score,
+            health: relationship.health,
+            interaction_count: relationship.interaction_count,
+            last_interaction: relationship.last_interaction,
+            expires_at: relationship.expires_at,
+        };
+        return encode("relationships", serde_json::to_value(message).ok()?);
+    }
+
+    if *owner == influence::ID && discriminator == &MintConfig::DISCRIMINATOR[..] {
+        let config = MintConfig::try_deserialize(&mut &data[..]).ok()?;
+        let message = MintMessage {
+            mint: config.mint.to_string(),
+            authority: config.authority.to_string(),
+            symbol: config.symbol,
+            is_active: config.is_active,
+        };
+        return encode("mints", serde_json::to_value(message).ok()?);
+    }
+
+    None
+}
+
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+/// # Safety
+///
+/// Called by the validator's plugin manager, which takes ownership of the
+/// returned plugin.
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    Box::into_raw(Box::new(ArmourGeyserPlugin::default()))
+}
@@ -0,0 +1,107 @@
+// Typed decoding of every event the registry, influence and relationship
+// programs emit. Raw events are extracted from transactions by armour-events.
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::IndexerError;
+
+macro_rules! armour_events {
+    ($($program:ident => [$($event:ident),* $(,)?]),* $(,)?) => {
+        pub enum ArmourEvent {
+            $($($event($program::$event),)*)*
+        }
+
+        impl ArmourEvent {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($(ArmourEvent::$event(_) => stringify!($event),)*)*
+                }
+            }
+
+            pub fn program_id(&self) -> Pubkey {
+                match self {
+                    $($(ArmourEvent::$event(_) => $program::ID,)*)*
+                }
+            }
+
+            // Decode discriminator + Borsh body. Ok(None) for events of
+            // programs or types this indexer does not know about.
+            pub fn decode(program_id: &Pubkey, data: &[u8]) -> Result<Option<Self>, IndexerError> {
+                if data.len() < 8 {
+                    return Ok(None);
+                }
+                let (discriminator, mut body) = data.split_at(8);
+                $(
+                    if *program_id == $program::ID {
+                        $(
+                            if discriminator == &$program::$event::DISCRIMINATOR[..] {
+                                check_schema_version(program_id, stringify!($event), body)?;
+                                let event = $program::$event::deserialize(&mut body)
+                                    .map_err(|err| IndexerError::Decode(format!("{}: {}", stringify!($event), err)))?;
+                                return Ok(Some(ArmourEvent::$event(event)));
+                            }
+                        )*
+                        return Ok(None);
+                    }
+                )*
+                Ok(None)
+            }
+        }
+    };
+}
+
+armour_events! {
+    agent_registry => [
+        AgentRegistered, AgentStatusUpdated, AgentPersonalityUpdated, CompatibilityCalculated,
+        AgentMatchRecorded, AgentOwnershipTransferred, ModeratorUpdated, AgentFrozenUpdated,
+        AgentLineageSet, AgentRecordUpdated, RegistryAdminUpdated, OwnershipTransferProposed,
+        OwnershipTransferCancelled, AgentClosed, AgentActivityRecorded, AgentDelegateUpdated,
+        CustomTraitUpdated, TraitWeightsUpdated, AgentXpGranted, AgentLeveledUp, LevelConfigUpdated,
+        CompatibilityConfigUpdated, AgentMatchProposed, AgentMatchDeclined, AgentMatchDissolved,
+        MatchCooldownUpdated, AgentQueued, AgentLeftQueue, QueuePairSkipped, AgentLayoutMigrated,
+        MatchLayoutMigrated,
+    ],
+    influence => [
+        MintInitialized, TokensMinted, TokensBurned, TokensTransferred, TransferRecordCompressed,
+        MintStatusUpdated, MintAuthorityUpdated,
+    ],
+    relationship => [
+        RelationshipCreated, InteractionRecorded, RelationshipStatusUpdated, RelationshipTypeChanged,
+        ConfigInitialized, ConfigUpdated, RewardsFunded, DateScheduled, DateConfirmed, DateCompleted,
+        NoShowReported, NoShowDisputed, NoShowSettled, JointWalletCreated, JointWalletDeposit,
+        WithdrawalProposed, WithdrawalApproved, WithdrawalExecuted, WithdrawalCancelled,
+        PrenupUpdated, RelationshipEnded, ArbiterUpdated, DisputeOpened, EvidenceSubmitted,
+        DisputeResolved, MatchmakerParamsUpdated, MatchmakerRegistered, RelationshipProposed,
+        RelationshipProposalAccepted, RelationshipProposalDeclined, MatchmakerCommissionPaid,
+        VisibilityChanged, InteractionHookAdded, InteractionHookRemoved, CompatibilityRefreshed,
+        RelationshipsMerged, GoalParamsUpdated, RateLimitsUpdated, GoalSet, GoalCompleted,
+        GoalExpired, TranscriptAnchored, InteractionWitnessed, HealthDecayed, AnniversaryReached,
+        AutomationRegistered, RelationshipLayoutMigrated, InteractionTreeCreated,
+        CompressedInteractionRecorded, InteractionVerified, LightInteractionRecorded,
+        LightInteractionVerified, CustomTypeAdded, CustomTypeRemoved, CustomLabelSet,
+        RelationshipChangeProposed, RelationshipChangeConfirmed, RelationshipChangeCancelled,
+        RelationshipTemplateUpdated, RelationshipOwnersSynced, InteractionTypeRegistered,
+        InteractionSchemaUpdated, InteractionTypeRemoved, RelationshipRenewed, RelationshipExpired,
+        ConfigAdminUpdated,
+    ],
+}
+
+impl std::fmt::Debug for ArmourEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Relationship events lead with the schema version they were emitted under.
+// Anything newer than the version this indexer was built against may have
+// fields it cannot decode, so it is reported instead of misread.
+fn check_schema_version(program_id: &Pubkey, event: &'static str, body: &[u8]) -> Result<(), IndexerError> {
+    if *program_id != relationship::ID {
+        return Ok(());
+    }
+    match body.first() {
+        Some(version) if *version <= relationship::EVENT_SCHEMA_VERSION => Ok(()),
+        Some(version) => Err(IndexerError::UnsupportedSchema { event, version: *version }),
+        None => Err(IndexerError::Decode(format!("{}: empty event", event))),
+    }
+}
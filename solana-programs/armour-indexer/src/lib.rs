@@ -0,0 +1,84 @@
+// Indexer for the registry, influence and relationship programs. `Indexer`
+// follows the programs over websocket, fetches each transaction that mentions
+// them, decodes its events into `ArmourEvent`s and hands them to an
+// `EventSink`. Events are delivered at confirmed commitment and later either
+// finalized or rolled back if their transaction did not survive a fork.
+use std::fmt;
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+mod events;
+mod sink;
+mod source;
+
+pub use armour_events::{cpi_event, log_events, RawEvent};
+pub use events::ArmourEvent;
+#[cfg(feature = "kafka")]
+pub use sink::KafkaSink;
+#[cfg(feature = "postgres")]
+pub use sink::PostgresSink;
+pub use sink::{EventSink, FileSink};
+pub use source::{GapTracker, Indexer, IndexerConfig};
+
+pub struct IndexedEvent {
+    pub signature: Signature,
+    pub slot: u64,
+    pub index: u32,  // Position among the transaction's events
+    pub raw: RawEvent,
+    pub event: ArmourEvent,
+}
+
+// Missed sequence numbers, reported to the sink so it can backfill
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Gap {
+    // Interaction nonce indexes skipped for a relationship
+    Interactions { relationship: Pubkey, expected: u64, found: u64 },
+    // Slots not covered while the subscription was down; backfilled from
+    // signature history before resubscribing
+    Slots { from: u64, to: u64 },
+}
+
+#[derive(Debug)]
+pub enum IndexerError {
+    Rpc(String),
+    Decode(String),
+    UnsupportedSchema { event: &'static str, version: u8 },
+    Sink(String),
+}
+
+impl fmt::Display for IndexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexerError::Rpc(err) => write!(f, "RPC error: {}", err),
+            IndexerError::Decode(err) => write!(f, "Event decoding failed: {}", err),
+            IndexerError::UnsupportedSchema { event, version } => {
+                write!(f, "{} uses schema version {}, newer than this indexer supports", event, version)
+            }
+            IndexerError::Sink(err) => write!(f, "Sink error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for IndexerError {}
+
+impl From<armour_events::EventError> for IndexerError {
+    fn from(err: armour_events::EventError) -> Self {
+        match err {
+            armour_events::EventError::Decode(err) => IndexerError::Decode(err),
+            armour_events::EventError::UnsupportedSchema { .. } => IndexerError::Decode(err.to_string()),
+        }
+    }
+}
+
+impl From<solana_client::client_error::ClientError> for IndexerError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        IndexerError::Rpc(err.to_string())
+    }
+}
+
+impl From<solana_client::nonblocking::pubsub_client::PubsubClientError> for IndexerError {
+    fn from(err: solana_client::nonblocking::pubsub_client::PubsubClientError) -> Self {
+        IndexerError::Rpc(err.to_string())
+    }
+}
@@ -0,0 +1,234 @@
+// Destinations for indexed events. Sinks receive events at confirmed
+// commitment; `finalize` and `rollback` tell them which ones became final
+// and which were dropped with a fork.
+use async_trait::async_trait;
+use base64::Engine;
+use solana_sdk::signature::Signature;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use crate::{Gap, IndexedEvent, IndexerError};
+
+#[async_trait]
+pub trait EventSink: Send {
+    async fn write(&mut self, events: &[IndexedEvent]) -> Result<(), IndexerError>;
+
+    // Everything at or below `slot` is final
+    async fn finalize(&mut self, _slot: u64) -> Result<(), IndexerError> {
+        Ok(())
+    }
+
+    // The transaction's events were written but it did not land on the finalized fork
+    async fn rollback(&mut self, signature: &Signature) -> Result<(), IndexerError>;
+
+    async fn gap(&mut self, _gap: &Gap) -> Result<(), IndexerError> {
+        Ok(())
+    }
+}
+
+// JSON lines, one record per event, rollback, finalization or gap. Event
+// bodies are the base64 Borsh data, decodable with `ArmourEvent::decode`.
+pub struct FileSink {
+    file: File,
+}
+
+impl FileSink {
+    pub async fn open(path: &str) -> Result<Self, IndexerError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(sink_error)?;
+        Ok(Self { file })
+    }
+
+    async fn append(&mut self, line: String) -> Result<(), IndexerError> {
+        self.file.write_all(line.as_bytes()).await.map_err(sink_error)?;
+        self.file.write_all(b"\n").await.map_err(sink_error)
+    }
+}
+
+#[async_trait]
+impl EventSink for FileSink {
+    async fn write(&mut self, events: &[IndexedEvent]) -> Result<(), IndexerError> {
+        for event in events {
+            let line = format!(
+                r#"{{"kind":"event","signature":"{}","slot":{},"index":{},"program":"{}","event":"{}","data":"{}"}}"#,
+                event.signature,
+                event.slot,
+                event.index,
+                event.raw.program_id,
+                event.event.name(),
+                base64::engine::general_purpose::STANDARD.encode(&event.raw.data),
+            );
+            self.append(line).await?;
+        }
+        self.file.flush().await.map_err(sink_error)
+    }
+
+    async fn finalize(&mut self, slot: u64) -> Result<(), IndexerError> {
+        self.append(format!(r#"{{"kind":"finalized","slot":{}}}"#, slot)).await
+    }
+
+    async fn rollback(&mut self, signature: &Signature) -> Result<(), IndexerError> {
+        self.append(format!(r#"{{"kind":"rollback","signature":"{}"}}"#, signature)).await
+    }
+
+    async fn gap(&mut self, gap: &Gap) -> Result<(), IndexerError> {
+        let line = match gap {
+            Gap::Interactions { relationship, expected, found } => format!(
+                r#"{{"kind":"gap","relationship":"{}","expected":{},"found":{}}}"#,
+                relationship, expected, found
+            ),
+            Gap::Slots { from, to } => format!(r#"{{"kind":"gap","from_slot":{},"to_slot":{}}}"#, from, to),
+        };
+        self.append(line).await
+    }
+}
+
+// Events in `armour_events`, keyed by (signature, event_index). Rows start
+// unfinalized and are deleted on rollback.
+#[cfg(feature = "postgres")]
+pub struct PostgresSink {
+    client: tokio_postgres::Client,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresSink {
+    pub async fn new(client: tokio_postgres::Client) -> Result<Self, IndexerError> {
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS armour_events (
+                    signature TEXT NOT NULL,
+                    event_index INTEGER NOT NULL,
+                    slot BIGINT NOT NULL,
+                    program TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    data BYTEA NOT NULL,
+                    finalized BOOLEAN NOT NULL DEFAULT FALSE,
+                    PRIMARY KEY (signature, event_index)
+                );
+                CREATE INDEX IF NOT EXISTS armour_events_slot ON armour_events (slot);
+                CREATE TABLE IF NOT EXISTS armour_gaps (
+                    detail TEXT NOT NULL,
+                    detected_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                );",
+            )
+            .await
+            .map_err(sink_error)?;
+        Ok(Self { client })
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl EventSink for PostgresSink {
+    async fn write(&mut self, events: &[IndexedEvent]) -> Result<(), IndexerError> {
+        let transaction = self.client.transaction().await.map_err(sink_error)?;
+        for event in events {
+            transaction
+                .execute(
+                    "INSERT INTO armour_events (signature, event_index, slot, program, name, data)
+                     VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT DO NOTHING",
+                    &[
+                        &event.signature.to_string(),
+                        &(event.index as i32),
+                        &(event.slot as i64),
+                        &event.raw.program_id.to_string(),
+                        &event.event.name(),
+                        &event.raw.data,
+                    ],
+                )
+                .await
+                .map_err(sink_error)?;
+        }
+        transaction.commit().await.map_err(sink_error)
+    }
+
+    async fn finalize(&mut self, slot: u64) -> Result<(), IndexerError> {
+        self.client
+            .execute(
+                "UPDATE armour_events SET finalized = TRUE WHERE slot <= $1 AND NOT finalized",
+                &[&(slot as i64)],
+            )
+            .await
+            .map_err(sink_error)?;
+        Ok(())
+    }
+
+    async fn rollback(&mut self, signature: &Signature) -> Result<(), IndexerError> {
+        self.client
+            .execute("DELETE FROM armour_events WHERE signature = $1", &[&signature.to_string()])
+            .await
+            .map_err(sink_error)?;
+        Ok(())
+    }
+
+    async fn gap(&mut self, gap: &Gap) -> Result<(), IndexerError> {
+        self.client
+            .execute("INSERT INTO armour_gaps (detail) VALUES ($1)", &[&format!("{:?}", gap)])
+            .await
+            .map_err(sink_error)?;
+        Ok(())
+    }
+}
+
+// Events on `topic`, keyed by signature with the Borsh data as payload and
+// the event name, slot and program as headers. Rollbacks are empty records
+// with a `rollback` kind header so consumers can retract by key.
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    producer: rdkafka::producer::FutureProducer,
+    topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    pub fn new(producer: rdkafka::producer::FutureProducer, topic: String) -> Self {
+        Self { producer, topic }
+    }
+
+    async fn send(&self, key: &str, payload: &[u8], headers: rdkafka::message::OwnedHeaders) -> Result<(), IndexerError> {
+        let record = rdkafka::producer::FutureRecord::to(&self.topic)
+            .key(key)
+            .payload(payload)
+            .headers(headers);
+        self.producer
+            .send(record, rdkafka::util::Timeout::Never)
+            .await
+            .map_err(|(err, _)| sink_error(err))?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl EventSink for KafkaSink {
+    async fn write(&mut self, events: &[IndexedEvent]) -> Result<(), IndexerError> {
+        use rdkafka::message::{Header, OwnedHeaders};
+
+        for event in events {
+            let slot = event.slot.to_string();
+            let program = event.raw.program_id.to_string();
+            let headers = OwnedHeaders::new()
+                .insert(Header { key: "kind", value: Some("event") })
+                .insert(Header { key: "event", value: Some(event.event.name()) })
+                .insert(Header { key: "slot", value: Some(&slot) })
+                .insert(Header { key: "program", value: Some(&program) });
+            self.send(&event.signature.to_string(), &event.raw.data, headers).await?;
+        }
+        Ok(())
+    }
+
+    async fn rollback(&mut self, signature: &Signature) -> Result<(), IndexerError> {
+        use rdkafka::message::{Header, OwnedHeaders};
+
+        let headers = OwnedHeaders::new().insert(Header { key: "kind", value: Some("rollback") });
+        self.send(&signature.to_string(), &[], headers).await
+    }
+}
+
+fn sink_error(err: impl std::fmt::Display) -> IndexerError {
+    IndexerError::Sink(err.to_string())
+}
@@ -0,0 +1,309 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use futures::stream::{select_all, StreamExt};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{
+    RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{UiInstruction, UiTransactionEncoding};
+
+use crate::{cpi_event, log_events, ArmourEvent, EventSink, Gap, IndexedEvent, IndexerError};
+
+const RECENT_SIGNATURES: usize = 4096;  // Dedup window for transactions mentioning several programs
+
+pub struct IndexerConfig {
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub programs: Vec<Pubkey>,
+    pub finality_check_interval: Duration,
+    pub reconnect_delay: Duration,
+}
+
+impl IndexerConfig {
+    pub fn new(rpc_url: String, ws_url: String) -> Self {
+        Self {
+            rpc_url,
+            ws_url,
+            programs: vec![agent_registry::ID, influence::ID, relationship::ID],
+            finality_check_interval: Duration::from_secs(10),
+            reconnect_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+pub struct Indexer {
+    config: IndexerConfig,
+    rpc: RpcClient,
+    // Newest processed signature per program, where backfill stops
+    cursors: HashMap<Pubkey, Signature>,
+    last_slot: u64,
+    // Confirmed but not yet finalized transactions by slot
+    pending: BTreeMap<u64, Vec<Signature>>,
+    recent: VecDeque<Signature>,
+    recent_set: HashSet<Signature>,
+    gaps: GapTracker,
+}
+
+impl Indexer {
+    pub fn new(config: IndexerConfig) -> Self {
+        let rpc = RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed());
+        Self {
+            config,
+            rpc,
+            cursors: HashMap::new(),
+            last_slot: 0,
+            pending: BTreeMap::new(),
+            recent: VecDeque::new(),
+            recent_set: HashSet::new(),
+            gaps: GapTracker::default(),
+        }
+    }
+
+    // Resume after the given per-program signatures instead of the current tip
+    pub fn with_cursors(mut self, cursors: HashMap<Pubkey, Signature>) -> Self {
+        self.cursors = cursors;
+        self
+    }
+
+    pub fn cursors(&self) -> &HashMap<Pubkey, Signature> {
+        &self.cursors
+    }
+
+    // Index until the sink fails. Dropped subscriptions are re-established
+    // after backfilling whatever was missed while disconnected.
+    pub async fn run<S: EventSink>(&mut self, sink: &mut S) -> Result<(), IndexerError> {
+        loop {
+            let disconnected_at = self.last_slot;
+            self.backfill(sink).await?;
+            if disconnected_at > 0 && self.last_slot > disconnected_at + 1 {
+                sink.gap(&Gap::Slots { from: disconnected_at + 1, to: self.last_slot }).await?;
+            }
+
+            if let Err(err) = self.follow(sink).await {
+                if matches!(err, IndexerError::Sink(_)) {
+                    return Err(err);
+                }
+            }
+            tokio::time::sleep(self.config.reconnect_delay).await;
+        }
+    }
+
+    async fn follow<S: EventSink>(&mut self, sink: &mut S) -> Result<(), IndexerError> {
+        let pubsub = PubsubClient::new(&self.config.ws_url).await?;
+        let mut subscriptions = Vec::new();
+        for program in &self.config.programs {
+            let (stream, _unsubscribe) = pubsub
+                .logs_subscribe(
+                    RpcTransactionLogsFilter::Mentions(vec![program.to_string()]),
+                    RpcTransactionLogsConfig { commitment: Some(CommitmentConfig::confirmed()) },
+                )
+                .await?;
+            subscriptions.push(stream);
+        }
+        let mut notifications = select_all(subscriptions);
+        let mut finality = tokio::time::interval(self.config.finality_check_interval);
+
+        loop {
+            tokio::select! {
+                notification = notifications.next() => {
+                    let Some(notification) = notification else { return Ok(()) };
+                    if notification.value.err.is_some() {
+                        continue;
+                    }
+                    let Ok(signature) = notification.value.signature.parse() else { continue };
+                    self.index_transaction(&signature, sink).await?;
+                }
+                _ = finality.tick() => self.settle(sink).await?,
+            }
+        }
+    }
+
+    // Walk each program's signature history back to its cursor and index the
+    // missed transactions oldest first
+    async fn backfill<S: EventSink>(&mut self, sink: &mut S) -> Result<(), IndexerError> {
+        for program in self.config.programs.clone() {
+            let Some(until) = self.cursors.get(&program).copied() else { continue };
+            let mut missed = Vec::new();
+            let mut before = None;
+            loop {
+                let page = self
+                    .rpc
+                    .get_signatures_for_address_with_config(
+                        &program,
+                        GetConfirmedSignaturesForAddress2Config {
+                            before,
+                            until: Some(until),
+                            limit: None,
+                            commitment: Some(CommitmentConfig::confirmed()),
+                        },
+                    )
+                    .await?;
+                let Some(last) = page.last() else { break };
+                before = last.signature.parse().ok();
+                missed.extend(
+                    page.into_iter()
+                        .filter(|status| status.err.is_none())
+                        .filter_map(|status| status.signature.parse::<Signature>().ok()),
+                );
+                if before.is_none() {
+                    break;
+                }
+            }
+            for signature in missed.into_iter().rev() {
+                self.index_transaction(&signature, sink).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn index_transaction<S: EventSink>(
+        &mut self,
+        signature: &Signature,
+        sink: &mut S,
+    ) -> Result<(), IndexerError> {
+        if !self.remember(*signature) {
+            return Ok(());
+        }
+        let transaction = self
+            .rpc
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+        let slot = transaction.slot;
+        let Some(meta) = transaction.transaction.meta else { return Ok(()) };
+        if meta.err.is_some() {
+            return Ok(());
+        }
+        let Some(decoded) = transaction.transaction.transaction.decode() else {
+            return Err(IndexerError::Decode(format!("transaction {}", signature)));
+        };
+
+        let mut account_keys = decoded.message.static_account_keys().to_vec();
+        if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+            for key in loaded.writable.iter().chain(loaded.readonly.iter()) {
+                account_keys.push(key.parse().map_err(|_| IndexerError::Decode(key.clone()))?);
+            }
+        }
+
+        let mut raw = match &meta.log_messages {
+            OptionSerializer::Some(logs) => log_events(logs)?,
+            _ => Vec::new(),
+        };
+        if let OptionSerializer::Some(inner) = &meta.inner_instructions {
+            for instruction in inner.iter().flat_map(|inner| inner.instructions.iter()) {
+                let UiInstruction::Compiled(compiled) = instruction else { continue };
+                let Some(program_id) = account_keys.get(compiled.program_id_index as usize) else {
+                    continue;
+                };
+                if !self.config.programs.contains(program_id) {
+                    continue;
+                }
+                let data = bs58::decode(&compiled.data)
+                    .into_vec()
+                    .map_err(|err| IndexerError::Decode(err.to_string()))?;
+                raw.extend(cpi_event(program_id, &data));
+            }
+        }
+
+        let mut events = Vec::new();
+        for raw in raw {
+            let Some(event) = ArmourEvent::decode(&raw.program_id, &raw.data)? else { continue };
+            if let Some(gap) = self.gaps.observe(&event) {
+                sink.gap(&gap).await?;
+            }
+            events.push(IndexedEvent {
+                signature: *signature,
+                slot,
+                index: events.len() as u32,
+                raw,
+                event,
+            });
+        }
+
+        if !events.is_empty() {
+            sink.write(&events).await?;
+            self.pending.entry(slot).or_default().push(*signature);
+        }
+        for program in &self.config.programs {
+            if account_keys.contains(program) {
+                self.cursors.insert(*program, *signature);
+            }
+        }
+        self.last_slot = self.last_slot.max(slot);
+        Ok(())
+    }
+
+    // Finalize pending transactions the cluster has rooted, and roll back the
+    // ones that were dropped with their fork
+    async fn settle<S: EventSink>(&mut self, sink: &mut S) -> Result<(), IndexerError> {
+        let finalized = self.rpc.get_slot_with_commitment(CommitmentConfig::finalized()).await?;
+        let settled: Vec<u64> = self.pending.range(..=finalized).map(|(slot, _)| *slot).collect();
+        if settled.is_empty() {
+            return Ok(());
+        }
+
+        for slot in &settled {
+            let signatures = self.pending.remove(slot).unwrap_or_default();
+            let statuses = self.rpc.get_signature_statuses_with_history(&signatures).await?.value;
+            for (signature, status) in signatures.iter().zip(statuses) {
+                let rooted = status.map_or(false, |status| status.slot == *slot && status.err.is_none());
+                if !rooted {
+                    sink.rollback(signature).await?;
+                }
+            }
+        }
+        sink.finalize(finalized).await
+    }
+
+    fn remember(&mut self, signature: Signature) -> bool {
+        if !self.recent_set.insert(signature) {
+            return false;
+        }
+        self.recent.push_back(signature);
+        if self.recent.len() > RECENT_SIGNATURES {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.recent_set.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+// Tracks each relationship's interaction nonce. The first index seen for a
+// relationship is taken as-is, since indexing may start mid-history.
+#[derive(Default)]
+pub struct GapTracker {
+    next_interaction: HashMap<Pubkey, u64>,
+}
+
+impl GapTracker {
+    pub fn observe(&mut self, event: &ArmourEvent) -> Option<Gap> {
+        let (relationship, index) = match event {
+            ArmourEvent::InteractionRecorded(event) => (event.relationship_id, event.interaction_index),
+            ArmourEvent::LightInteractionRecorded(event) => (event.relationship_id, event.interaction_index),
+            _ => return None,
+        };
+        let expected = self.next_interaction.insert(relationship, index.saturating_add(1));
+        match expected {
+            Some(expected) if index > expected => Some(Gap::Interactions {
+                relationship,
+                expected,
+                found: index,
+            }),
+            _ => None,
+        }
+    }
+}
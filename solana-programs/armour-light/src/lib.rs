@@ -0,0 +1,353 @@
+// ZK-compressed storage through Light Protocol, shared by every program that
+// writes high-volume records (token transfers, relationship interactions).
+// A compressed record lives as a leaf in one of Light's state trees instead
+// of a rent-paying PDA; only its hash is on-chain, and the full data goes to
+// the ledger through the noop program, where Light's indexer (Photon) picks
+// it up.
+//
+// Programs collect the Light system accounts in their own accounts struct
+// (Anchor can't nest one from another crate), hand them over as
+// `LightCpiAccounts`, and pass Light's state/address trees and queues as
+// remaining accounts, in the order the client packed them. Record addresses are derived from the same seeds the
+// equivalent PDA would use, so a record has one well-known address whichever
+// storage mode wrote it. Reads go through `verify_record`, which has the
+// Light system program check a validity proof for the record.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::keccak::hashv;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::Discriminator;
+
+// Light Protocol programs (mainnet)
+pub const LIGHT_SYSTEM_PROGRAM_ID: Pubkey = solana_program::pubkey!("SySTEM1eSU2p4BGQfQpimFEWWSC1XDFeun3Nqzz3rT7");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = solana_program::pubkey!("compr6CUsB5m2jS4Y3831ztGSTnDpnKJTKS95d64XVq");
+pub const LIGHT_NOOP_PROGRAM_ID: Pubkey = solana_program::pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// Seed of the PDA through which a program signs for its compressed accounts
+pub const CPI_AUTHORITY_SEED: &[u8] = b"cpi_authority";
+
+// Accounts every Light CPI needs, besides the trees and queues
+pub struct LightCpiAccounts<'info> {
+    pub fee_payer: AccountInfo<'info>,
+    pub cpi_authority: AccountInfo<'info>,  // Invoking program's `CPI_AUTHORITY_SEED` PDA
+    pub registered_program_pda: AccountInfo<'info>,
+    pub account_compression_authority: AccountInfo<'info>,
+    pub invoking_program: AccountInfo<'info>,
+    pub noop_program: AccountInfo<'info>,
+    pub account_compression_program: AccountInfo<'info>,
+    pub light_system_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+// Groth16 validity proof produced by Light's prover
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressedProof {
+    pub a: [u8; 32],
+    pub b: [u8; 64],
+    pub c: [u8; 32],
+}
+
+// Client-supplied proof and tree positions for writing a new record. Indexes
+// refer to the instruction's remaining accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct NewRecordArgs {
+    pub proof: CompressedProof,  // Non-inclusion of the new address
+    pub address_merkle_tree_index: u8,
+    pub address_queue_index: u8,
+    pub address_merkle_tree_root_index: u16,
+    pub output_tree_index: u8,  // State tree the record is appended to
+}
+
+// Client-supplied proof that a record is in a state tree, as returned by
+// the indexer for the record's address
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ExistingRecordArgs {
+    pub proof: CompressedProof,
+    pub address: [u8; 32],
+    pub merkle_context: PackedMerkleContext,
+    pub root_index: u16,
+    pub output_tree_index: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedMerkleContext {
+    pub merkle_tree_pubkey_index: u8,
+    pub nullifier_queue_pubkey_index: u8,
+    pub leaf_index: u32,
+    pub queue_index: Option<QueueIndex>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueIndex {
+    pub queue_id: u8,
+    pub index: u16,
+}
+
+// Light system program instruction data; field order is Light's wire format
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CompressedAccount {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub address: Option<[u8; 32]>,
+    pub data: Option<CompressedAccountData>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CompressedAccountData {
+    pub discriminator: [u8; 8],
+    pub data: Vec<u8>,
+    pub data_hash: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct NewAddressParamsPacked {
+    seed: [u8; 32],
+    address_queue_account_index: u8,
+    address_merkle_tree_account_index: u8,
+    address_merkle_tree_root_index: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct PackedCompressedAccountWithMerkleContext {
+    compressed_account: CompressedAccount,
+    merkle_context: PackedMerkleContext,
+    root_index: u16,
+    read_only: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct OutputCompressedAccountWithPackedContext {
+    compressed_account: CompressedAccount,
+    merkle_tree_index: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct CompressedCpiContext {
+    set_context: bool,
+    first_set_context: bool,
+    cpi_context_account_index: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct InstructionDataInvokeCpi {
+    proof: Option<CompressedProof>,
+    new_address_params: Vec<NewAddressParamsPacked>,
+    input_compressed_accounts_with_merkle_context: Vec<PackedCompressedAccountWithMerkleContext>,
+    output_compressed_accounts: Vec<OutputCompressedAccountWithPackedContext>,
+    relay_fee: Option<u64>,
+    compress_or_decompress_lamports: Option<u64>,
+    is_compress: bool,
+    signer_seeds: Vec<Vec<u8>>,
+    cpi_context: Option<CompressedCpiContext>,
+}
+
+// Address seed of a record: the program id plus the seeds its PDA would use
+pub fn address_seed(program_id: &Pubkey, seeds: &[&[u8]]) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = Vec::with_capacity(seeds.len() + 1);
+    parts.push(program_id.as_ref());
+    parts.extend_from_slice(seeds);
+    field_hash(&parts)
+}
+
+// Address of a record once created in `address_merkle_tree`
+pub fn derive_address(address_merkle_tree: &Pubkey, seed: &[u8; 32]) -> [u8; 32] {
+    field_hash(&[address_merkle_tree.as_ref(), seed])
+}
+
+// Hash of a record's serialized data, as committed to in the state tree
+pub fn data_hash(data: &[u8]) -> [u8; 32] {
+    field_hash(&[data])
+}
+
+// Write `record` as a new compressed account owned by `program_id`, at the
+// address derived from `seeds`. Returns the record's address.
+pub fn create_record<'info, T: AnchorSerialize + Discriminator>(
+    light: &LightCpiAccounts<'info>,
+    program_id: &Pubkey,
+    trees: &[AccountInfo<'info>],
+    args: &NewRecordArgs,
+    seeds: &[&[u8]],
+    record: &T,
+) -> Result<[u8; 32]> {
+    let address_merkle_tree = tree_at(trees, args.address_merkle_tree_index)?;
+    tree_at(trees, args.address_queue_index)?;
+    tree_at(trees, args.output_tree_index)?;
+
+    let seed = address_seed(program_id, seeds);
+    let address = derive_address(&address_merkle_tree.key(), &seed);
+    let inputs = InstructionDataInvokeCpi {
+        proof: Some(args.proof),
+        new_address_params: vec![NewAddressParamsPacked {
+            seed,
+            address_queue_account_index: args.address_queue_index,
+            address_merkle_tree_account_index: args.address_merkle_tree_index,
+            address_merkle_tree_root_index: args.address_merkle_tree_root_index,
+        }],
+        input_compressed_accounts_with_merkle_context: Vec::new(),
+        output_compressed_accounts: vec![OutputCompressedAccountWithPackedContext {
+            compressed_account: record_account(program_id, address, record)?,
+            merkle_tree_index: args.output_tree_index,
+        }],
+        relay_fee: None,
+        compress_or_decompress_lamports: None,
+        is_compress: false,
+        signer_seeds: Vec::new(),
+        cpi_context: None,
+    };
+    invoke_light(light, program_id, trees, &inputs)?;
+
+    Ok(address)
+}
+
+// Prove that `record` is stored at `args.address`. Light only proves
+// inclusion by consuming the account, so the record is written back
+// unchanged to `args.output_tree_index`; its address stays the same.
+pub fn verify_record<'info, T: AnchorSerialize + Discriminator>(
+    light: &LightCpiAccounts<'info>,
+    program_id: &Pubkey,
+    trees: &[AccountInfo<'info>],
+    args: &ExistingRecordArgs,
+    record: &T,
+) -> Result<()> {
+    tree_at(trees, args.merkle_context.merkle_tree_pubkey_index)?;
+    tree_at(trees, args.merkle_context.nullifier_queue_pubkey_index)?;
+    tree_at(trees, args.output_tree_index)?;
+
+    let compressed_account = record_account(program_id, args.address, record)?;
+    let inputs = InstructionDataInvokeCpi {
+        proof: Some(args.proof),
+        new_address_params: Vec::new(),
+        input_compressed_accounts_with_merkle_context: vec![PackedCompressedAccountWithMerkleContext {
+            compressed_account: compressed_account.clone(),
+            merkle_context: args.merkle_context,
+            root_index: args.root_index,
+            read_only: false,
+        }],
+        output_compressed_accounts: vec![OutputCompressedAccountWithPackedContext {
+            compressed_account,
+            merkle_tree_index: args.output_tree_index,
+        }],
+        relay_fee: None,
+        compress_or_decompress_lamports: None,
+        is_compress: false,
+        signer_seeds: Vec::new(),
+        cpi_context: None,
+    };
+    invoke_light(light, program_id, trees, &inputs)
+}
+
+#[error_code(offset = 9200)]
+pub enum LightError {
+    #[msg("CPI authority is not the invoking program's PDA")]
+    InvalidCpiAuthority,
+    #[msg("Invoking program does not match the calling program")]
+    InvalidInvokingProgram,
+    #[msg("Account is not the expected Light Protocol program")]
+    InvalidLightProgram,
+    #[msg("Tree index is out of range of the remaining accounts")]
+    MissingTreeAccount,
+}
+
+fn record_account<T: AnchorSerialize + Discriminator>(
+    program_id: &Pubkey,
+    address: [u8; 32],
+    record: &T,
+) -> Result<CompressedAccount> {
+    let data = record.try_to_vec()?;
+    Ok(CompressedAccount {
+        owner: *program_id,
+        lamports: 0,
+        address: Some(address),
+        data: Some(CompressedAccountData {
+            discriminator: T::discriminator(),
+            data_hash: data_hash(&data),
+            data,
+        }),
+    })
+}
+
+fn invoke_light<'info>(
+    light: &LightCpiAccounts<'info>,
+    program_id: &Pubkey,
+    trees: &[AccountInfo<'info>],
+    inputs: &InstructionDataInvokeCpi,
+) -> Result<()> {
+    require_keys_eq!(
+        light.invoking_program.key(),
+        *program_id,
+        LightError::InvalidInvokingProgram
+    );
+    require!(
+        light.noop_program.key() == LIGHT_NOOP_PROGRAM_ID
+            && light.account_compression_program.key() == ACCOUNT_COMPRESSION_PROGRAM_ID
+            && light.light_system_program.key() == LIGHT_SYSTEM_PROGRAM_ID,
+        LightError::InvalidLightProgram
+    );
+    let (cpi_authority, bump) = Pubkey::find_program_address(&[CPI_AUTHORITY_SEED], program_id);
+    require_keys_eq!(
+        light.cpi_authority.key(),
+        cpi_authority,
+        LightError::InvalidCpiAuthority
+    );
+
+    // Anchor instruction `invoke_cpi(inputs: Vec<u8>)`
+    let mut data = hash(b"global:invoke_cpi").to_bytes()[..8].to_vec();
+    inputs.try_to_vec()?.serialize(&mut data)?;
+
+    // Light's optional accounts (sol pool, decompression recipient, CPI
+    // context) are unused; Anchor reads the program's own id as None
+    let none = light.light_system_program.clone();
+    let mut account_infos = vec![
+        light.fee_payer.clone(),
+        light.cpi_authority.clone(),
+        light.registered_program_pda.clone(),
+        light.noop_program.clone(),
+        light.account_compression_authority.clone(),
+        light.account_compression_program.clone(),
+        light.invoking_program.clone(),
+        none.clone(),
+        none.clone(),
+        light.system_program.clone(),
+        none.clone(),
+    ];
+    let mut accounts: Vec<AccountMeta> = account_infos
+        .iter()
+        .enumerate()
+        .map(|(i, info)| match i {
+            0 => AccountMeta::new(info.key(), true),
+            1 => AccountMeta::new_readonly(info.key(), true),
+            _ => AccountMeta::new_readonly(info.key(), false),
+        })
+        .collect();
+    for tree in trees {
+        accounts.push(AccountMeta::new(tree.key(), false));
+        account_infos.push(tree.clone());
+    }
+    account_infos.push(none);
+
+    invoke_signed(
+        &Instruction {
+            program_id: LIGHT_SYSTEM_PROGRAM_ID,
+            accounts,
+            data,
+        },
+        &account_infos,
+        &[&[CPI_AUTHORITY_SEED, &[bump]]],
+    )?;
+
+    Ok(())
+}
+
+fn tree_at<'a, 'info>(trees: &'a [AccountInfo<'info>], index: u8) -> Result<&'a AccountInfo<'info>> {
+    trees
+        .get(index as usize)
+        .ok_or_else(|| error!(LightError::MissingTreeAccount))
+}
+
+// Keccak hash truncated to fit the BN254 scalar field used by Light's circuits
+fn field_hash(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hash = hashv(parts).to_bytes();
+    hash[0] = 0;
+    hash
+}
@@ -0,0 +1,52 @@
+// Progress of a migration run, saved after every batch so an interrupted
+// run picks up where it stopped. Accounts that were migrated no longer match
+// the old layout and drop out of the scan on their own; the checkpoint keeps
+// the history and the accounts whose migration failed, which are skipped
+// unless retried.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::MigrateError;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub migration: String,
+    pub migrated: Vec<String>,             // Account addresses, in migration order
+    pub failed: BTreeMap<String, String>,  // Account address -> last error
+    pub signatures: Vec<String>,
+}
+
+impl Checkpoint {
+    // The checkpoint at `path`, or a fresh one if there is none yet. A
+    // checkpoint left by a different migration is an error rather than
+    // silently mixed in.
+    pub fn load(path: &Path, migration: &str) -> Result<Self, MigrateError> {
+        if !path.exists() {
+            return Ok(Self { migration: migration.to_string(), ..Self::default() });
+        }
+        let json = std::fs::read_to_string(path).map_err(checkpoint_error)?;
+        let checkpoint: Self = serde_json::from_str(&json).map_err(checkpoint_error)?;
+        if checkpoint.migration != migration {
+            return Err(MigrateError::Checkpoint(format!(
+                "{} belongs to migration {}",
+                path.display(),
+                checkpoint.migration
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    // Write through a temporary file so a crash never leaves a torn checkpoint
+    pub fn save(&self, path: &Path) -> Result<(), MigrateError> {
+        let json = serde_json::to_string_pretty(self).map_err(checkpoint_error)?;
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, json).map_err(checkpoint_error)?;
+        std::fs::rename(&temporary, path).map_err(checkpoint_error)
+    }
+}
+
+fn checkpoint_error(err: impl std::fmt::Display) -> MigrateError {
+    MigrateError::Checkpoint(err.to_string())
+}
@@ -0,0 +1,408 @@
+// Rolls account layout migrations out across the registry, influence and
+// relationship programs. A `Migration` describes an account whose layout
+// changed: how to recognise accounts still on the old layout and the program
+// instruction that rewrites one. `Migrator` finds every affected account and
+// either previews the rewrite by simulation (`dry_run`) or sends the
+// instructions in batches (`run`), checkpointing progress to a file.
+use std::fmt;
+use std::path::PathBuf;
+
+use anchor_lang::Discriminator;
+use armour_events::SchemaRegistry;
+use armour_sdk::{ArmourClient, SdkError};
+use serde_json::Value;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::client_error::ClientError;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionAccountsConfig,
+    RpcSimulateTransactionConfig,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+mod checkpoint;
+
+pub use checkpoint::Checkpoint;
+
+// getMultipleAccounts limit
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+#[derive(Clone)]
+pub struct Migration {
+    pub name: &'static str,
+    pub program_id: Pubkey,
+    pub discriminator: [u8; 8],
+    pub old_space: usize,          // Accounts on the old layout have exactly this size
+    pub old_layout: &'static str,  // IDL type of the old layout
+    pub new_layout: &'static str,  // IDL account of the new layout
+    // The rewrite of one account, given its current (old layout) data
+    pub instruction: fn(payer: Pubkey, account: Pubkey, data: &[u8]) -> Instruction,
+}
+
+// Every migration the programs support. The influence program has not
+// changed an account layout. Matches read their first agent's owner, so
+// "agent-v2" runs before "match-v2".
+pub fn migrations() -> Vec<Migration> {
+    vec![
+        Migration {
+            name: "agent-v2",
+            program_id: agent_registry_cpi::ID,
+            discriminator: agent_registry_cpi::AgentData::DISCRIMINATOR,
+            old_space: agent_registry_cpi::LEGACY_AGENT_SPACE,
+            old_layout: "LegacyAgentData",
+            new_layout: "AgentData",
+            instruction: migrate_agent,
+        },
+        Migration {
+            name: "match-v2",
+            program_id: agent_registry_cpi::ID,
+            discriminator: agent_registry_cpi::MatchData::DISCRIMINATOR,
+            old_space: agent_registry_cpi::LEGACY_MATCH_SPACE,
+            old_layout: "LegacyMatchData",
+            new_layout: "MatchData",
+            instruction: migrate_match,
+        },
+        Migration {
+            name: "relationship-zero-copy",
+            program_id: relationship_cpi::ID,
+            discriminator: relationship_cpi::RelationshipData::DISCRIMINATOR,
+            old_space: relationship_cpi::LEGACY_RELATIONSHIP_SPACE,
+            old_layout: "LegacyRelationshipData",
+            new_layout: "RelationshipData",
+            instruction: migrate_relationship,
+        },
+    ]
+}
+
+// Pubkey at `offset` in an account's data
+fn pubkey_at(data: &[u8], offset: usize) -> Pubkey {
+    data.get(offset..offset + 32)
+        .and_then(|bytes| Pubkey::try_from(bytes).ok())
+        .unwrap_or_default()
+}
+
+fn migrate_agent(payer: Pubkey, account: Pubkey, _data: &[u8]) -> Instruction {
+    agent_registry_cpi::builders::migrate_agent(payer, account)
+}
+
+// The legacy layout starts with the first agent
+fn migrate_match(payer: Pubkey, account: Pubkey, data: &[u8]) -> Instruction {
+    agent_registry_cpi::builders::migrate_match(payer, account, pubkey_at(data, 8))
+}
+
+// The legacy layout starts with the agents, whose registry accounts the
+// migration reads the owners from
+fn migrate_relationship(payer: Pubkey, account: Pubkey, data: &[u8]) -> Instruction {
+    relationship_cpi::builders::migrate_relationship_layout(payer, account, pubkey_at(data, 8), pubkey_at(data, 40))
+}
+
+pub fn migration(name: &str) -> Option<Migration> {
+    migrations().into_iter().find(|migration| migration.name == name)
+}
+
+// One field whose decoded value differs between the layouts. None where the
+// field exists in only one of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountDiff {
+    pub address: Pubkey,
+    pub old_space: usize,
+    pub new_space: usize,
+    pub lamports_due: u64,  // Extra rent the payer covers
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub total: usize,     // Affected accounts found by this run's scan
+    pub migrated: usize,  // Migrated by this run
+    pub failed: usize,    // Failed in this run or skipped after failing in an earlier one
+}
+
+pub struct MigratorConfig {
+    pub batch_size: usize,  // Migrate instructions per transaction
+    pub checkpoint_path: PathBuf,
+    pub retry_failed: bool,  // Retry accounts an earlier run failed to migrate
+}
+
+impl Default for MigratorConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 8,
+            checkpoint_path: PathBuf::from("migration-checkpoint.json"),
+            retry_failed: false,
+        }
+    }
+}
+
+pub struct Migrator {
+    pub client: ArmourClient,  // Its payer pays fees and any extra rent
+    pub config: MigratorConfig,
+    schemas: SchemaRegistry,
+}
+
+impl Migrator {
+    pub fn new(client: ArmourClient, config: MigratorConfig) -> Self {
+        Self { client, config, schemas: SchemaRegistry::new() }
+    }
+
+    // Accounts still on the migration's old layout, in address order
+    pub async fn affected_accounts(&self, migration: &Migration) -> Result<Vec<Pubkey>, MigrateError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(migration.old_space as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &migration.discriminator)),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                // Only the addresses are needed
+                data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let mut accounts: Vec<Pubkey> = self
+            .client
+            .rpc
+            .get_program_accounts_with_config(&migration.program_id, config)
+            .await?
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect();
+        accounts.sort();
+        Ok(accounts)
+    }
+
+    // Simulate every batch and diff each account's decoded old layout against
+    // the layout the simulation left it in. Nothing is sent.
+    pub async fn dry_run(&self, migration: &Migration) -> Result<Vec<AccountDiff>, MigrateError> {
+        let accounts = self.affected_accounts(migration).await?;
+        let mut diffs = Vec::with_capacity(accounts.len());
+        for batch in accounts.chunks(self.batch_size()) {
+            let before = self.fetch_accounts(batch).await?;
+            let after = self.simulate(migration, batch, &before).await?;
+            for ((address, before), after) in batch.iter().zip(before).zip(after) {
+                let (Some(before), Some(after)) = (before, after) else {
+                    return Err(MigrateError::Decode(format!("{} vanished during the dry run", address)));
+                };
+                diffs.push(self.diff(migration, *address, &before, &after)?);
+            }
+        }
+        Ok(diffs)
+    }
+
+    // Migrate every affected account, saving the checkpoint after each batch.
+    // A failed batch is retried one account at a time so a single bad
+    // account does not hold back the rest.
+    pub async fn run(
+        &self,
+        migration: &Migration,
+        mut on_progress: impl FnMut(&Progress),
+    ) -> Result<Checkpoint, MigrateError> {
+        let path = &self.config.checkpoint_path;
+        let mut checkpoint = Checkpoint::load(path, migration.name)?;
+        let accounts: Vec<Pubkey> = self
+            .affected_accounts(migration)
+            .await?
+            .into_iter()
+            .filter(|address| self.config.retry_failed || !checkpoint.failed.contains_key(&address.to_string()))
+            .collect();
+        let mut progress = Progress {
+            total: accounts.len(),
+            failed: if self.config.retry_failed { 0 } else { checkpoint.failed.len() },
+            ..Progress::default()
+        };
+        on_progress(&progress);
+
+        for batch in accounts.chunks(self.batch_size()) {
+            match self.send(migration, batch).await {
+                Ok(signature) => {
+                    checkpoint.signatures.push(signature);
+                    for address in batch {
+                        record_success(&mut checkpoint, &mut progress, address);
+                    }
+                }
+                Err(_) if batch.len() > 1 => {
+                    for address in batch {
+                        match self.send(migration, std::slice::from_ref(address)).await {
+                            Ok(signature) => {
+                                checkpoint.signatures.push(signature);
+                                record_success(&mut checkpoint, &mut progress, address);
+                            }
+                            Err(err) => record_failure(&mut checkpoint, &mut progress, address, &err),
+                        }
+                    }
+                }
+                Err(err) => record_failure(&mut checkpoint, &mut progress, &batch[0], &err),
+            }
+            checkpoint.save(path)?;
+            on_progress(&progress);
+        }
+        Ok(checkpoint)
+    }
+
+    fn batch_size(&self) -> usize {
+        self.config.batch_size.max(1)
+    }
+
+    // Accounts that no longer exist are skipped
+    fn instructions(&self, migration: &Migration, batch: &[Pubkey], accounts: &[Option<Account>]) -> Vec<Instruction> {
+        let payer = self.client.payer.pubkey();
+        batch
+            .iter()
+            .zip(accounts)
+            .filter_map(|(address, account)| {
+                account.as_ref().map(|account| (migration.instruction)(payer, *address, &account.data))
+            })
+            .collect()
+    }
+
+    async fn send(&self, migration: &Migration, batch: &[Pubkey]) -> Result<String, MigrateError> {
+        let accounts = self.fetch_accounts(batch).await?;
+        let signature = self.client.send(&self.instructions(migration, batch, &accounts), &[]).await?;
+        Ok(signature.to_string())
+    }
+
+    // The batch's accounts as the simulated transaction left them
+    async fn simulate(
+        &self,
+        migration: &Migration,
+        batch: &[Pubkey],
+        accounts: &[Option<Account>],
+    ) -> Result<Vec<Option<Account>>, MigrateError> {
+        let transaction = self.client.build_transaction(&self.instructions(migration, batch, accounts), &[]).await?;
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: batch.iter().map(Pubkey::to_string).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self.client.rpc.simulate_transaction_with_config(&transaction, config).await?.value;
+        if let Some(err) = result.err {
+            return Err(MigrateError::Simulation { error: err.to_string(), logs: result.logs.unwrap_or_default() });
+        }
+        Ok(result
+            .accounts
+            .unwrap_or_default()
+            .into_iter()
+            .map(|account| account.and_then(|account| account.decode::<Account>()))
+            .collect())
+    }
+
+    async fn fetch_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>, MigrateError> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            accounts.extend(self.client.rpc.get_multiple_accounts(chunk).await?);
+        }
+        Ok(accounts)
+    }
+
+    fn diff(
+        &self,
+        migration: &Migration,
+        address: Pubkey,
+        before: &Account,
+        after: &Account,
+    ) -> Result<AccountDiff, MigrateError> {
+        let old = self.schemas.decode_type(migration.old_layout, body(&before.data))?;
+        let new = self.schemas.decode_type(migration.new_layout, body(&after.data))?;
+        Ok(AccountDiff {
+            address,
+            old_space: before.data.len(),
+            new_space: after.data.len(),
+            lamports_due: after.lamports.saturating_sub(before.lamports),
+            changes: field_changes(&old, &new),
+        })
+    }
+}
+
+// Account data after its discriminator
+fn body(data: &[u8]) -> &[u8] {
+    data.get(8..).unwrap_or_default()
+}
+
+fn record_success(checkpoint: &mut Checkpoint, progress: &mut Progress, address: &Pubkey) {
+    let address = address.to_string();
+    checkpoint.failed.remove(&address);
+    checkpoint.migrated.push(address);
+    progress.migrated += 1;
+}
+
+fn record_failure(checkpoint: &mut Checkpoint, progress: &mut Progress, address: &Pubkey, err: &MigrateError) {
+    checkpoint.failed.insert(address.to_string(), err.to_string());
+    progress.failed += 1;
+}
+
+// Fields of two decoded layouts whose values differ, old layout order first
+pub fn field_changes(old: &Value, new: &Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let mut changes: Vec<FieldChange> = old
+        .iter()
+        .filter(|(field, value)| new.get(*field) != Some(*value))
+        .map(|(field, value)| FieldChange {
+            field: field.clone(),
+            old: Some(value.clone()),
+            new: new.get(field).cloned(),
+        })
+        .collect();
+    changes.extend(new.iter().filter(|(field, _)| !old.contains_key(*field)).map(|(field, value)| {
+        FieldChange { field: field.clone(), old: None, new: Some(value.clone()) }
+    }));
+    changes
+}
+
+// Errors
+
+#[derive(Debug)]
+pub enum MigrateError {
+    Rpc(ClientError),
+    Sdk(SdkError),
+    Decode(String),
+    Simulation { error: String, logs: Vec<String> },
+    Checkpoint(String),
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::Rpc(err) => write!(f, "RPC error: {}", err),
+            MigrateError::Sdk(err) => write!(f, "{}", err),
+            MigrateError::Decode(err) => write!(f, "Decoding failed: {}", err),
+            MigrateError::Simulation { error, .. } => write!(f, "Simulation failed: {}", error),
+            MigrateError::Checkpoint(err) => write!(f, "Checkpoint error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+impl From<ClientError> for MigrateError {
+    fn from(err: ClientError) -> Self {
+        MigrateError::Rpc(err)
+    }
+}
+
+impl From<SdkError> for MigrateError {
+    fn from(err: SdkError) -> Self {
+        MigrateError::Sdk(err)
+    }
+}
+
+impl From<armour_events::EventError> for MigrateError {
+    fn from(err: armour_events::EventError) -> Self {
+        MigrateError::Decode(err.to_string())
+    }
+}
@@ -0,0 +1,113 @@
+// armour-migrate <migration> --rpc <url> --keypair <path> [--dry-run]
+//     [--batch-size <n>] [--checkpoint <path>] [--retry-failed]
+// armour-migrate --list
+use std::path::PathBuf;
+use std::process::exit;
+
+use armour_migrate::{migration, migrations, Migrator, MigratorConfig};
+use armour_sdk::ArmourClient;
+use solana_sdk::signature::read_keypair_file;
+
+const USAGE: &str = "usage: armour-migrate <migration> --rpc <url> --keypair <path> [--dry-run] \
+[--batch-size <n>] [--checkpoint <path>] [--retry-failed]\n       armour-migrate --list";
+
+struct Args {
+    migration: String,
+    rpc: String,
+    keypair: String,
+    dry_run: bool,
+    config: MigratorConfig,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut migration = None;
+    let mut rpc = None;
+    let mut keypair = None;
+    let mut dry_run = false;
+    let mut config = MigratorConfig::default();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--list" => {
+                for migration in migrations() {
+                    println!("{}  {} -> {}", migration.name, migration.old_layout, migration.new_layout);
+                }
+                exit(0);
+            }
+            "--rpc" => rpc = Some(value("--rpc")?),
+            "--keypair" => keypair = Some(value("--keypair")?),
+            "--dry-run" => dry_run = true,
+            "--retry-failed" => config.retry_failed = true,
+            "--batch-size" => {
+                config.batch_size = value("--batch-size")?.parse().map_err(|_| "invalid --batch-size")?
+            }
+            "--checkpoint" => config.checkpoint_path = PathBuf::from(value("--checkpoint")?),
+            flag if flag.starts_with("--") => return Err(format!("unknown flag {}", flag)),
+            name => migration = Some(name.to_string()),
+        }
+    }
+    Ok(Args {
+        migration: migration.ok_or("missing migration")?,
+        rpc: rpc.ok_or("missing --rpc")?,
+        keypair: keypair.ok_or("missing --keypair")?,
+        dry_run,
+        config,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        exit(2);
+    });
+    let Some(migration) = migration(&args.migration) else {
+        eprintln!("unknown migration {}; see --list", args.migration);
+        exit(2);
+    };
+    let payer = read_keypair_file(&args.keypair).unwrap_or_else(|err| {
+        eprintln!("cannot read {}: {}", args.keypair, err);
+        exit(2);
+    });
+    let migrator = Migrator::new(ArmourClient::new(args.rpc, payer), args.config);
+
+    if args.dry_run {
+        let diffs = migrator.dry_run(&migration).await.unwrap_or_else(|err| fail(err));
+        for diff in &diffs {
+            println!(
+                "{}: {} -> {} bytes, {} lamports rent",
+                diff.address, diff.old_space, diff.new_space, diff.lamports_due
+            );
+            for change in &diff.changes {
+                let show = |value: &Option<serde_json::Value>| value.as_ref().map_or("-".to_string(), |v| v.to_string());
+                println!("  {}: {} -> {}", change.field, show(&change.old), show(&change.new));
+            }
+        }
+        println!("{} accounts would be migrated", diffs.len());
+        return;
+    }
+
+    let checkpoint = migrator
+        .run(&migration, |progress| {
+            eprintln!("{}/{} migrated, {} failed", progress.migrated, progress.total, progress.failed);
+        })
+        .await
+        .unwrap_or_else(|err| fail(err));
+    for (address, err) in &checkpoint.failed {
+        eprintln!("failed {}: {}", address, err);
+    }
+    if !checkpoint.failed.is_empty() {
+        exit(1);
+    }
+}
+
+fn fail(err: armour_migrate::MigrateError) -> ! {
+    eprintln!("{}", err);
+    if let armour_migrate::MigrateError::Simulation { logs, .. } = &err {
+        for line in logs {
+            eprintln!("  {}", line);
+        }
+    }
+    exit(1);
+}
@@ -0,0 +1,151 @@
+// Pyth price account parsing shared by every program that prices in USD.
+// Feeds are read straight from the Pyth v2 price account layout, checked for
+// staleness and confidence, and converted with integer math only, so every
+// program quotes the same amount for the same USD price.
+use anchor_lang::prelude::*;
+
+// Pyth oracle program that owns the price accounts (mainnet)
+pub const PYTH_ORACLE_PROGRAM_ID: Pubkey = solana_program::pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+// Pyth SOL/USD price account (mainnet)
+pub const SOL_USD_FEED: Pubkey = solana_program::pubkey!("H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG");
+
+pub const LAMPORTS_DECIMALS: u8 = 9;
+const BPS_DENOMINATOR: u128 = 10_000;
+const CENTS_PER_DOLLAR: u128 = 100;
+
+// Offsets into the Pyth v2 price account
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_VERSION: u32 = 2;
+const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const MIN_PRICE_ACCOUNT_LEN: usize = 240;
+
+// A validated aggregate price: `price * 10^expo` USD per whole unit
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Price {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+// Read the aggregate price from a Pyth price account. Fails if the feed is
+// not trading, was last published more than `max_age` seconds ago, or its
+// confidence interval is wider than `max_conf_bps` of the price.
+pub fn load_price(feed: &AccountInfo, max_age: i64, max_conf_bps: u16) -> Result<Price> {
+    load_price_at(feed, Clock::get()?.unix_timestamp, max_age, max_conf_bps)
+}
+
+// `load_price` as of the unix time `now`
+pub fn load_price_at(feed: &AccountInfo, now: i64, max_age: i64, max_conf_bps: u16) -> Result<Price> {
+    require_keys_eq!(*feed.owner, PYTH_ORACLE_PROGRAM_ID, PriceFeedError::InvalidPriceFeed);
+    let data = feed.try_borrow_data()?;
+    require!(
+        data.len() >= MIN_PRICE_ACCOUNT_LEN
+            && read_u32(&data, 0) == PYTH_MAGIC
+            && read_u32(&data, 4) == PYTH_VERSION
+            && read_u32(&data, 8) == PYTH_PRICE_ACCOUNT_TYPE,
+        PriceFeedError::InvalidPriceFeed
+    );
+    require!(
+        read_u32(&data, AGG_STATUS_OFFSET) == PYTH_STATUS_TRADING,
+        PriceFeedError::PriceUnavailable
+    );
+
+    let price = Price {
+        price: read_u64(&data, AGG_PRICE_OFFSET) as i64,
+        conf: read_u64(&data, AGG_CONF_OFFSET),
+        expo: read_u32(&data, EXPO_OFFSET) as i32,
+        publish_time: read_u64(&data, TIMESTAMP_OFFSET) as i64,
+    };
+    require!(price.price > 0, PriceFeedError::PriceUnavailable);
+
+    require!(
+        now.saturating_sub(price.publish_time) <= max_age,
+        PriceFeedError::StalePrice
+    );
+    require!(
+        price.conf as u128 * BPS_DENOMINATOR <= price.price as u128 * max_conf_bps as u128,
+        PriceFeedError::PriceTooUncertain
+    );
+
+    Ok(price)
+}
+
+impl Price {
+    // Base units of a token with `decimals` worth `usd_cents`, rounded down
+    pub fn usd_cents_to_amount(&self, usd_cents: u64, decimals: u8) -> Result<u64> {
+        // amount = cents * 10^decimals / (100 * price * 10^expo)
+        let (numerator_exp, denominator_exp) = split_exponent(decimals as i32 - self.expo);
+        let numerator = (usd_cents as u128)
+            .checked_mul(pow10(numerator_exp)?)
+            .ok_or(PriceFeedError::ConversionOverflow)?;
+        let denominator = CENTS_PER_DOLLAR
+            .checked_mul(self.price as u128)
+            .and_then(|d| d.checked_mul(pow10(denominator_exp).ok()?))
+            .ok_or(PriceFeedError::ConversionOverflow)?;
+        u64::try_from(numerator / denominator).map_err(|_| error!(PriceFeedError::ConversionOverflow))
+    }
+
+    // USD cents worth `amount` base units of a token with `decimals`, rounded down
+    pub fn amount_to_usd_cents(&self, amount: u64, decimals: u8) -> Result<u64> {
+        // cents = amount * 100 * price * 10^expo / 10^decimals
+        let (numerator_exp, denominator_exp) = split_exponent(self.expo - decimals as i32);
+        let numerator = (amount as u128)
+            .checked_mul(CENTS_PER_DOLLAR * self.price as u128)
+            .and_then(|n| n.checked_mul(pow10(numerator_exp).ok()?))
+            .ok_or(PriceFeedError::ConversionOverflow)?;
+        let denominator = pow10(denominator_exp)?;
+        u64::try_from(numerator / denominator).map_err(|_| error!(PriceFeedError::ConversionOverflow))
+    }
+
+    pub fn usd_cents_to_lamports(&self, usd_cents: u64) -> Result<u64> {
+        self.usd_cents_to_amount(usd_cents, LAMPORTS_DECIMALS)
+    }
+
+    pub fn lamports_to_usd_cents(&self, lamports: u64) -> Result<u64> {
+        self.amount_to_usd_cents(lamports, LAMPORTS_DECIMALS)
+    }
+}
+
+#[error_code(offset = 9100)]
+pub enum PriceFeedError {
+    #[msg("Account is not a Pyth price feed")]
+    InvalidPriceFeed,
+    #[msg("Price feed is not trading")]
+    PriceUnavailable,
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Price feed confidence interval is too wide")]
+    PriceTooUncertain,
+    #[msg("Price conversion overflowed")]
+    ConversionOverflow,
+}
+
+// Split a signed power of ten into (multiply, divide) exponents
+fn split_exponent(exponent: i32) -> (u32, u32) {
+    if exponent >= 0 {
+        (exponent as u32, 0)
+    } else {
+        (0, exponent.unsigned_abs())
+    }
+}
+
+fn pow10(exponent: u32) -> Result<u128> {
+    10u128
+        .checked_pow(exponent)
+        .ok_or_else(|| error!(PriceFeedError::ConversionOverflow))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use armour_price_feed::{load_price_at, Price, PriceFeedError, PYTH_ORACLE_PROGRAM_ID};
+
+const NOW: i64 = 1_700_000_000;
+
+// $150 per SOL at each exponent a feed might publish with
+fn sol_at_150(expo: i32) -> Price {
+    let price = match expo {
+        1 => 15,
+        0 => 150,
+        _ => 150 * 10i64.pow(expo.unsigned_abs()),
+    };
+    Price {
+        price,
+        conf: 0,
+        expo,
+        publish_time: NOW,
+    }
+}
+
+fn error_code<T: std::fmt::Debug>(result: Result<T>) -> u32 {
+    match result.unwrap_err() {
+        Error::AnchorError(error) => error.error_code_number,
+        Error::ProgramError(error) => panic!("expected an anchor error, got {:?}", error),
+    }
+}
+
+// A Pyth v2 price account with the given aggregate
+fn price_account(price: i64, conf: u64, expo: i32, publish_time: i64, status: u32) -> Vec<u8> {
+    let mut data = vec![0; 240];
+    data[0..4].copy_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+    data[4..8].copy_from_slice(&2u32.to_le_bytes());
+    data[8..12].copy_from_slice(&3u32.to_le_bytes());
+    data[20..24].copy_from_slice(&expo.to_le_bytes());
+    data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[216..224].copy_from_slice(&conf.to_le_bytes());
+    data[224..228].copy_from_slice(&status.to_le_bytes());
+    data
+}
+
+fn load(mut data: Vec<u8>, owner: Pubkey, now: i64, max_age: i64, max_conf_bps: u16) -> Result<Price> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let feed = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    load_price_at(&feed, now, max_age, max_conf_bps)
+}
+
+#[test]
+fn conversions_agree_across_exponents() {
+    for expo in [1, 0, -2, -8] {
+        let price = sol_at_150(expo);
+        assert_eq!(price.usd_cents_to_lamports(15_000).unwrap(), 1_000_000_000, "expo {}", expo);
+        assert_eq!(price.lamports_to_usd_cents(1_000_000_000).unwrap(), 15_000, "expo {}", expo);
+        // A token without decimals
+        assert_eq!(price.usd_cents_to_amount(45_000, 0).unwrap(), 3, "expo {}", expo);
+        assert_eq!(price.amount_to_usd_cents(3, 0).unwrap(), 45_000, "expo {}", expo);
+    }
+}
+
+#[test]
+fn conversions_round_down() {
+    let price = sol_at_150(-8);
+    // $1 is 6_666_666.67 lamports
+    assert_eq!(price.usd_cents_to_lamports(100).unwrap(), 6_666_666);
+    // ...which is worth 99.9999 cents
+    assert_eq!(price.lamports_to_usd_cents(6_666_666).unwrap(), 99);
+    assert_eq!(price.usd_cents_to_amount(149, 0).unwrap(), 0);
+}
+
+#[test]
+fn conversions_fail_on_overflow() {
+    let overflow: u32 = PriceFeedError::ConversionOverflow.into();
+
+    // The result does not fit in a u64
+    let tiny = Price {
+        price: 1,
+        conf: 0,
+        expo: -8,
+        publish_time: NOW,
+    };
+    assert_eq!(error_code(tiny.usd_cents_to_lamports(u64::MAX)), overflow);
+    let huge = Price {
+        price: i64::MAX,
+        conf: 0,
+        expo: 0,
+        publish_time: NOW,
+    };
+    assert_eq!(error_code(huge.lamports_to_usd_cents(u64::MAX)), overflow);
+
+    // The power of ten does not fit in a u128
+    let extreme = Price {
+        price: 1,
+        conf: 0,
+        expo: -40,
+        publish_time: NOW,
+    };
+    assert_eq!(error_code(extreme.usd_cents_to_lamports(1)), overflow);
+    assert_eq!(error_code(sol_at_150(0).amount_to_usd_cents(1, 40)), overflow);
+}
+
+#[test]
+fn loads_a_fresh_confident_price() {
+    let data = price_account(15_000_000_000, 300_000_000, -8, NOW - 60, 1);
+    let price = load(data, PYTH_ORACLE_PROGRAM_ID, NOW, 60, 200).unwrap();
+    assert_eq!(
+        price,
+        Price {
+            price: 15_000_000_000,
+            conf: 300_000_000,
+            expo: -8,
+            publish_time: NOW - 60,
+        }
+    );
+    assert_eq!(price.usd_cents_to_lamports(15_000).unwrap(), 1_000_000_000);
+}
+
+#[test]
+fn rejects_stale_prices() {
+    let data = price_account(15_000_000_000, 0, -8, NOW - 61, 1);
+    let result = load(data, PYTH_ORACLE_PROGRAM_ID, NOW, 60, 200);
+    assert_eq!(error_code(result), PriceFeedError::StalePrice.into());
+}
+
+#[test]
+fn rejects_wide_confidence_intervals() {
+    // 2% plus one unit, against a 2% maximum
+    let data = price_account(15_000_000_000, 300_000_001, -8, NOW, 1);
+    let result = load(data, PYTH_ORACLE_PROGRAM_ID, NOW, 60, 200);
+    assert_eq!(error_code(result), PriceFeedError::PriceTooUncertain.into());
+}
+
+#[test]
+fn rejects_feeds_that_are_not_trading_or_not_pyth() {
+    let halted = price_account(15_000_000_000, 0, -8, NOW, 2);
+    let result = load(halted, PYTH_ORACLE_PROGRAM_ID, NOW, 60, 200);
+    assert_eq!(error_code(result), PriceFeedError::PriceUnavailable.into());
+
+    let negative = price_account(-1, 0, -8, NOW, 1);
+    let result = load(negative, PYTH_ORACLE_PROGRAM_ID, NOW, 60, 200);
+    assert_eq!(error_code(result), PriceFeedError::PriceUnavailable.into());
+
+    let spoofed = price_account(15_000_000_000, 0, -8, NOW, 1);
+    let result = load(spoofed, Pubkey::new_unique(), NOW, 60, 200);
+    assert_eq!(error_code(result), PriceFeedError::InvalidPriceFeed.into());
+}
@@ -0,0 +1,129 @@
+// Randomness shared by every program that rolls dice: breeding mutations,
+// crafting, raffle draws and matchmaking. Programs keep a `vrf_authority`
+// in their config (Pubkey::default() to fall back to slot hashes) and pass
+// `vrf_authority: Option<Signer>` plus the slot hashes sysvar in their
+// context; everything from there goes through `resolve_seed` and the
+// derivation helpers below so all randomness is auditable in one place.
+//
+// Two flows are supported:
+// - Inline: the VRF authority (a Switchboard VRF callback signer or our own
+//   VRF service) co-signs the instruction that consumes the randomness.
+// - Request/settle: the program stores a `RandomnessRequest` in one of its
+//   accounts, and the VRF authority settles it in a later callback
+//   instruction before the result is consumed.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+const BPS_DENOMINATOR: u16 = 10_000;
+
+// Pick the seed for a roll. With no VRF authority configured the most recent
+// slot hash is used; otherwise the configured authority must sign and supply
+// the randomness.
+pub fn resolve_seed(
+    configured_authority: Pubkey,
+    vrf_authority: Option<&Signer>,
+    randomness: Option<[u8; 32]>,
+    recent_slothashes: &AccountInfo,
+) -> Result<[u8; 32]> {
+    if configured_authority == Pubkey::default() {
+        return slot_hash_seed(recent_slothashes);
+    }
+    let vrf_authority = vrf_authority.ok_or(RandomnessError::MissingRandomness)?;
+    require!(
+        vrf_authority.key() == configured_authority,
+        RandomnessError::MissingRandomness
+    );
+    randomness.ok_or_else(|| error!(RandomnessError::MissingRandomness))
+}
+
+// Most recent slot hash: the sysvar is a u64 length followed by (slot, hash) entries
+pub fn slot_hash_seed(recent_slothashes: &AccountInfo) -> Result<[u8; 32]> {
+    require_keys_eq!(
+        recent_slothashes.key(),
+        anchor_lang::solana_program::sysvar::slot_hashes::ID,
+        RandomnessError::SlotHashesUnavailable
+    );
+    let data = recent_slothashes.try_borrow_data()?;
+    require!(data.len() >= 48, RandomnessError::SlotHashesUnavailable);
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&data[16..48]);
+    Ok(seed)
+}
+
+// Bind a seed to the context it is used in, so one seed never yields the
+// same roll for two different purposes
+pub fn derive(seed: &[u8; 32], context: &[&[u8]]) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = Vec::with_capacity(context.len() + 1);
+    parts.push(seed);
+    parts.extend_from_slice(context);
+    hashv(&parts).to_bytes()
+}
+
+// Roll in basis points, 0-9999
+pub fn roll_bps(entropy: &[u8; 32]) -> u16 {
+    u16::from_le_bytes([entropy[0], entropy[1]]) % BPS_DENOMINATOR
+}
+
+// Uniform-ish index below `count`, which must be non-zero
+pub fn pick_index(entropy: &[u8; 32], count: u64) -> u64 {
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&entropy[..8]);
+    u64::from_le_bytes(value) % count
+}
+
+// Pending randomness stored inside a program account for the request/settle
+// flow. The requester commits to `seed` up front; the VRF authority's
+// callback settles it, and the result mixes both so neither side controls it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct RandomnessRequest {
+    pub requester: Pubkey,
+    pub seed: [u8; 32],
+    pub requested_slot: u64,
+    pub settled: bool,
+    pub result: [u8; 32],  // Zeroed until settled
+}
+
+impl RandomnessRequest {
+    pub fn open(requester: Pubkey, seed: [u8; 32]) -> Result<Self> {
+        Ok(Self {
+            requester,
+            seed,
+            requested_slot: Clock::get()?.slot,
+            settled: false,
+            result: [0u8; 32],
+        })
+    }
+
+    // Settle from the VRF authority's callback. The callback must run in a
+    // later slot than the request so the randomness can't be known when the
+    // request is made.
+    pub fn settle(&mut self, randomness: [u8; 32]) -> Result<[u8; 32]> {
+        require!(!self.settled, RandomnessError::AlreadySettled);
+        require!(
+            Clock::get()?.slot > self.requested_slot,
+            RandomnessError::SettledTooEarly
+        );
+        self.result = derive(&self.seed, &[&randomness]);
+        self.settled = true;
+        Ok(self.result)
+    }
+
+    pub fn result(&self) -> Result<[u8; 32]> {
+        require!(self.settled, RandomnessError::NotSettled);
+        Ok(self.result)
+    }
+}
+
+#[error_code(offset = 9000)]
+pub enum RandomnessError {
+    #[msg("VRF authority signature and randomness are required")]
+    MissingRandomness,
+    #[msg("Slot hashes sysvar is empty")]
+    SlotHashesUnavailable,
+    #[msg("Randomness request is already settled")]
+    AlreadySettled,
+    #[msg("Randomness request must be settled in a later slot")]
+    SettledTooEarly,
+    #[msg("Randomness request is not settled yet")]
+    NotSettled,
+}
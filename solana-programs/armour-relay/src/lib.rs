@@ -0,0 +1,12 @@
+// Relay between the indexer and web front-ends. `BroadcastSink` receives
+// events from an `armour_indexer::Indexer`, decodes each once to JSON and
+// broadcasts it; every WebSocket connection (`/ws`) forwards the ones
+// matching its agent, owner and relationship filters. REST endpoints
+// (`/accounts`) return decoded account snapshots. Clients authenticate with
+// one of the relay's API tokens, so they need neither raw RPC subscriptions
+// nor Borsh decoding.
+mod server;
+mod sink;
+
+pub use server::{router, Filter, RelayState};
+pub use sink::{BroadcastSink, EventKeys, RelayMessage};
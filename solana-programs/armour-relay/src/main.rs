@@ -0,0 +1,93 @@
+// armour-relay --rpc <url> --ws <url> --tokens <path> [--listen <addr>] [--buffer <n>]
+//
+// --tokens is a file of API tokens, one per line. --listen defaults to
+// 0.0.0.0:8080; --buffer is how many messages a slow client may fall behind
+// before it is told it lagged (default 1024).
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::process::exit;
+use std::sync::Arc;
+
+use armour_events::SchemaRegistry;
+use armour_indexer::{Indexer, IndexerConfig};
+use armour_relay::{router, BroadcastSink, RelayState};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::sync::broadcast;
+
+const USAGE: &str = "usage: armour-relay --rpc <url> --ws <url> --tokens <path> [--listen <addr>] [--buffer <n>]";
+
+struct Args {
+    rpc: String,
+    ws: String,
+    tokens: String,
+    listen: SocketAddr,
+    buffer: usize,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut rpc = None;
+    let mut ws = None;
+    let mut tokens = None;
+    let mut listen = SocketAddr::from(([0, 0, 0, 0], 8080));
+    let mut buffer = 1024;
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--rpc" => rpc = Some(value("--rpc")?),
+            "--ws" => ws = Some(value("--ws")?),
+            "--tokens" => tokens = Some(value("--tokens")?),
+            "--listen" => listen = value("--listen")?.parse().map_err(|_| "invalid --listen")?,
+            "--buffer" => buffer = value("--buffer")?.parse().map_err(|_| "invalid --buffer")?,
+            flag => return Err(format!("unknown argument {}", flag)),
+        }
+    }
+    Ok(Args {
+        rpc: rpc.ok_or("missing --rpc")?,
+        ws: ws.ok_or("missing --ws")?,
+        tokens: tokens.ok_or("missing --tokens")?,
+        listen,
+        buffer,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        exit(2);
+    });
+    let tokens: HashSet<String> = std::fs::read_to_string(&args.tokens)
+        .unwrap_or_else(|err| {
+            eprintln!("{}: {}", args.tokens, err);
+            exit(1);
+        })
+        .lines()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    let (sender, _) = broadcast::channel(args.buffer);
+    let state = Arc::new(RelayState {
+        rpc: RpcClient::new_with_commitment(args.rpc.clone(), CommitmentConfig::confirmed()),
+        registry: SchemaRegistry::new(),
+        tokens,
+        events: sender.clone(),
+    });
+
+    let mut sink = BroadcastSink::new(sender);
+    let mut indexer = Indexer::new(IndexerConfig::new(args.rpc, args.ws));
+    tokio::spawn(async move {
+        if let Err(err) = indexer.run(&mut sink).await {
+            eprintln!("indexer stopped: {}", err);
+            exit(1);
+        }
+    });
+
+    if let Err(err) = axum::Server::bind(&args.listen).serve(router(state).into_make_service()).await {
+        eprintln!("{}", err);
+        exit(1);
+    }
+}
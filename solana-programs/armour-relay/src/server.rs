@@ -0,0 +1,222 @@
+// HTTP side of the relay:
+//
+//   GET /ws?agent=<key>,..&owner=<key>,..&relationship=<key>,..
+//       WebSocket stream of event messages matching any of the filters (all
+//       events with none), plus every finality and rollback message
+//   GET /accounts/<address>            decoded account snapshot
+//   GET /accounts?addresses=<a>,<b>,..  the same for up to 100, null for
+//                                       missing or unknown accounts
+//
+// Requests carry an API token as `Authorization: Bearer <token>` or, since
+// browsers can't set headers on WebSocket requests, `?token=<token>`.
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use armour_events::SchemaRegistry;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{EventKeys, RelayMessage};
+
+// getMultipleAccounts' limit
+const MAX_ADDRESSES: usize = 100;
+
+type HttpError = (StatusCode, String);
+
+pub struct RelayState {
+    pub rpc: RpcClient,
+    pub registry: SchemaRegistry,
+    pub tokens: HashSet<String>,
+    pub events: broadcast::Sender<Arc<RelayMessage>>,
+}
+
+pub fn router(state: Arc<RelayState>) -> Router {
+    Router::new()
+        .route("/ws", get(subscribe))
+        .route("/accounts", get(accounts))
+        .route("/accounts/:address", get(account))
+        .with_state(state)
+}
+
+#[derive(Deserialize)]
+struct StreamQuery {
+    token: Option<String>,
+    agent: Option<String>,
+    owner: Option<String>,
+    relationship: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AccountsQuery {
+    token: Option<String>,
+    addresses: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+// Keys a connection follows; an event matches if any of its keys of the
+// same kind is listed
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub agents: HashSet<Pubkey>,
+    pub owners: HashSet<Pubkey>,
+    pub relationships: HashSet<Pubkey>,
+}
+
+impl Filter {
+    pub fn matches(&self, keys: &EventKeys) -> bool {
+        if self.agents.is_empty() && self.owners.is_empty() && self.relationships.is_empty() {
+            return true;
+        }
+        keys.agents.iter().any(|key| self.agents.contains(key))
+            || keys.owners.iter().any(|key| self.owners.contains(key))
+            || keys.relationships.iter().any(|key| self.relationships.contains(key))
+    }
+}
+
+async fn subscribe(
+    State(state): State<Arc<RelayState>>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Err(err) = authorize(&state, &headers, query.token.as_deref()) {
+        return err.into_response();
+    }
+    let filter = match filter(&query) {
+        Ok(filter) => filter,
+        Err(err) => return err.into_response(),
+    };
+    let events = state.events.subscribe();
+    ws.on_upgrade(move |socket| stream(socket, filter, events))
+}
+
+fn filter(query: &StreamQuery) -> Result<Filter, HttpError> {
+    Ok(Filter {
+        agents: keys(query.agent.as_deref())?,
+        owners: keys(query.owner.as_deref())?,
+        relationships: keys(query.relationship.as_deref())?,
+    })
+}
+
+async fn stream(mut socket: WebSocket, filter: Filter, mut events: broadcast::Receiver<Arc<RelayMessage>>) {
+    loop {
+        tokio::select! {
+            message = events.recv() => {
+                let text = match message {
+                    Ok(message) => match &message.keys {
+                        Some(keys) if !filter.matches(keys) => continue,
+                        _ => message.text.clone(),
+                    },
+                    // The client fell behind the broadcast buffer. Tell it
+                    // how much it missed so it can resync over REST.
+                    Err(RecvError::Lagged(missed)) => json!({ "kind": "lagged", "missed": missed }).to_string(),
+                    Err(RecvError::Closed) => return,
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    return;
+                }
+            }
+            // The stream is one-way; client messages other than close are ignored
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn account(
+    State(state): State<Arc<RelayState>>,
+    Path(address): Path<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, HttpError> {
+    authorize(&state, &headers, query.token.as_deref())?;
+    let address = parse_key(&address)?;
+    let response = state
+        .rpc
+        .get_account_with_commitment(&address, state.rpc.commitment())
+        .await
+        .map_err(|err| (StatusCode::BAD_GATEWAY, err.to_string()))?;
+    let account = response.value.ok_or((StatusCode::NOT_FOUND, format!("{} does not exist", address)))?;
+    snapshot(&state.registry, &address, &account, response.context.slot)?
+        .map(Json)
+        .ok_or((StatusCode::UNPROCESSABLE_ENTITY, format!("{} is not an Armour account", address)))
+}
+
+async fn accounts(
+    State(state): State<Arc<RelayState>>,
+    Query(query): Query<AccountsQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, HttpError> {
+    authorize(&state, &headers, query.token.as_deref())?;
+    let addresses: Vec<Pubkey> = keys(query.addresses.as_deref())?;
+    if addresses.len() > MAX_ADDRESSES {
+        return Err((StatusCode::BAD_REQUEST, format!("at most {} addresses per request", MAX_ADDRESSES)));
+    }
+    let response = state
+        .rpc
+        .get_multiple_accounts_with_commitment(&addresses, state.rpc.commitment())
+        .await
+        .map_err(|err| (StatusCode::BAD_GATEWAY, err.to_string()))?;
+
+    let mut snapshots = Vec::with_capacity(addresses.len());
+    for (address, account) in addresses.iter().zip(response.value) {
+        let snapshot = match account {
+            Some(account) => snapshot(&state.registry, address, &account, response.context.slot)?,
+            None => None,
+        };
+        snapshots.push(snapshot.unwrap_or(Value::Null));
+    }
+    Ok(Json(Value::Array(snapshots)))
+}
+
+fn snapshot(registry: &SchemaRegistry, address: &Pubkey, account: &Account, slot: u64) -> Result<Option<Value>, HttpError> {
+    let decoded = registry
+        .decode_account(&account.owner, &account.data)
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    Ok(decoded.map(|decoded| {
+        let mut snapshot = decoded.to_json();
+        snapshot["address"] = json!(address.to_string());
+        snapshot["slot"] = json!(slot);
+        snapshot
+    }))
+}
+
+fn authorize(state: &RelayState, headers: &HeaderMap, query_token: Option<&str>) -> Result<(), HttpError> {
+    let header_token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match header_token.or(query_token) {
+        Some(token) if state.tokens.contains(token) => Ok(()),
+        _ => Err((StatusCode::UNAUTHORIZED, "missing or unknown API token".to_string())),
+    }
+}
+
+// A comma-separated list of public keys
+fn keys<C: FromIterator<Pubkey>>(list: Option<&str>) -> Result<C, HttpError> {
+    list.into_iter()
+        .flat_map(|list| list.split(','))
+        .filter(|key| !key.is_empty())
+        .map(parse_key)
+        .collect()
+}
+
+fn parse_key(key: &str) -> Result<Pubkey, HttpError> {
+    key.parse().map_err(|_| (StatusCode::BAD_REQUEST, format!("invalid public key {}", key)))
+}
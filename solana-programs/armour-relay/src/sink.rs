@@ -0,0 +1,93 @@
+// Indexer sink that turns each event into the JSON message clients receive,
+// tagged with the keys it concerns, and broadcasts it to every connection
+use std::sync::Arc;
+
+use armour_events::idl::IdlType;
+use armour_events::SchemaRegistry;
+use armour_indexer::{EventSink, IndexedEvent, IndexerError};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use tokio::sync::broadcast;
+
+#[derive(Debug)]
+pub struct RelayMessage {
+    pub text: String,             // JSON, as sent to clients
+    pub keys: Option<EventKeys>,  // None for messages every client gets (finality, rollbacks)
+}
+
+// An event's public key fields, sorted by what they name. Agents and
+// relationships are recognized by field name (`agentId`, `agentOne`,
+// `relationshipId`, ...); every other key is a wallet or account the event
+// involves, and counts as an owner.
+#[derive(Debug, Default)]
+pub struct EventKeys {
+    pub agents: Vec<Pubkey>,
+    pub owners: Vec<Pubkey>,
+    pub relationships: Vec<Pubkey>,
+}
+
+impl EventKeys {
+    fn new(registry: &SchemaRegistry, event: &IndexedEvent, data: &Value) -> Self {
+        let mut keys = EventKeys::default();
+        let Some(schema) = registry.event(&event.raw.program_id, &event.raw.data[..8]) else { return keys };
+        for field in schema.fields.iter().filter(|field| field.ty == IdlType::PublicKey) {
+            let Some(key) = data[&field.name].as_str().and_then(|key| key.parse().ok()) else { continue };
+            let name = field.name.to_lowercase();
+            if name.contains("agent") || name.starts_with("parent") {
+                keys.agents.push(key);
+            } else if name.contains("relationship") || name == "canonicalid" || name == "duplicateid" {
+                keys.relationships.push(key);
+            } else {
+                keys.owners.push(key);
+            }
+        }
+        keys
+    }
+}
+
+pub struct BroadcastSink {
+    registry: SchemaRegistry,
+    sender: broadcast::Sender<Arc<RelayMessage>>,
+}
+
+impl BroadcastSink {
+    pub fn new(sender: broadcast::Sender<Arc<RelayMessage>>) -> Self {
+        Self { registry: SchemaRegistry::new(), sender }
+    }
+
+    // Nobody listening is fine; clients come and go
+    fn broadcast(&self, text: String, keys: Option<EventKeys>) {
+        let _ = self.sender.send(Arc::new(RelayMessage { text, keys }));
+    }
+}
+
+#[async_trait]
+impl EventSink for BroadcastSink {
+    async fn write(&mut self, events: &[IndexedEvent]) -> Result<(), IndexerError> {
+        for event in events {
+            let Some(decoded) = self.registry.decode(&event.raw)? else { continue };
+            let keys = EventKeys::new(&self.registry, event, &decoded.data);
+            let text = json!({
+                "kind": "event",
+                "signature": event.signature.to_string(),
+                "slot": event.slot,
+                "index": event.index,
+                "event": decoded.to_json(),
+            });
+            self.broadcast(text.to_string(), Some(keys));
+        }
+        Ok(())
+    }
+
+    async fn finalize(&mut self, slot: u64) -> Result<(), IndexerError> {
+        self.broadcast(json!({ "kind": "finalized", "slot": slot }).to_string(), None);
+        Ok(())
+    }
+
+    async fn rollback(&mut self, signature: &Signature) -> Result<(), IndexerError> {
+        self.broadcast(json!({ "kind": "rollback", "signature": signature.to_string() }).to_string(), None);
+        Ok(())
+    }
+}
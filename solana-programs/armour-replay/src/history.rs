@@ -0,0 +1,99 @@
+// Reads the transactions to replay from an indexer `FileSink` log and fetches
+// them from an archival RPC node
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::ReplayError;
+
+pub struct HistoricalTransaction {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: i64,
+    pub transaction: VersionedTransaction,
+    pub account_keys: Vec<Pubkey>,  // Static keys, then lookup table writable and readonly keys
+    pub pre_balances: Vec<u64>,     // Lamports per account key before execution
+}
+
+// Signatures of the finalized transactions in an indexer log, oldest slot
+// first. Within a slot, transactions keep the order the indexer wrote them in,
+// which may differ from their order in the block. Rolled-back transactions
+// and those above the last finalized slot are left out.
+pub fn read_index_log(path: &Path) -> Result<Vec<(u64, Signature)>, ReplayError> {
+    let contents = fs::read_to_string(path).map_err(|err| ReplayError::History(err.to_string()))?;
+    let mut transactions = Vec::new();
+    let mut seen = HashSet::new();
+    let mut rolled_back = HashSet::new();
+    let mut finalized = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let record: Value = serde_json::from_str(line).map_err(|err| ReplayError::History(err.to_string()))?;
+        let signature = || -> Result<Signature, ReplayError> {
+            record["signature"]
+                .as_str()
+                .and_then(|signature| signature.parse().ok())
+                .ok_or_else(|| ReplayError::History(format!("bad signature in {}", line)))
+        };
+        match record["kind"].as_str() {
+            Some("event") => {
+                let signature = signature()?;
+                let slot = record["slot"].as_u64().unwrap_or_default();
+                if seen.insert(signature) {
+                    transactions.push((slot, signature));
+                }
+            }
+            Some("rollback") => {
+                rolled_back.insert(signature()?);
+            }
+            Some("finalized") => finalized = finalized.max(record["slot"].as_u64().unwrap_or_default()),
+            _ => {}
+        }
+    }
+    transactions.retain(|(slot, signature)| *slot <= finalized && !rolled_back.contains(signature));
+    transactions.sort_by_key(|(slot, _)| *slot);
+    Ok(transactions)
+}
+
+pub async fn fetch_transaction(
+    rpc: &RpcClient,
+    slot: u64,
+    signature: &Signature,
+) -> Result<HistoricalTransaction, ReplayError> {
+    let fetched = rpc
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::finalized()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+    let decode_error = || ReplayError::History(format!("cannot decode transaction {}", signature));
+    let meta = fetched.transaction.meta.ok_or_else(decode_error)?;
+    let transaction = fetched.transaction.transaction.decode().ok_or_else(decode_error)?;
+
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        for key in loaded.writable.iter().chain(loaded.readonly.iter()) {
+            account_keys.push(key.parse().map_err(|_| decode_error())?);
+        }
+    }
+    Ok(HistoricalTransaction {
+        signature: *signature,
+        slot,
+        block_time: fetched.block_time.unwrap_or_default(),
+        transaction,
+        account_keys,
+        pre_balances: meta.pre_balances,
+    })
+}
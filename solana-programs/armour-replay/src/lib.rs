@@ -0,0 +1,261 @@
+// Replays historical transactions against two local banks, one running the
+// deployed program builds and one running candidate builds (new
+// compatibility weights, decay rates, ...), and reports every transaction
+// whose outcome or resulting account state differs between them.
+//
+// Transactions come from an indexer `FileSink` log and are fetched from an
+// archival RPC node. Accounts a transaction touches that the banks have not
+// seen yet are copied from the cluster's current state, so a replay is most
+// faithful when the log starts at the programs' deployment; the report counts
+// how many program accounts had to be seeded that way. Lamport balances of
+// accounts outside the programs are reset to their historical values before
+// each transaction.
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use armour_events::SchemaRegistry;
+use armour_migrate::{field_changes, FieldChange};
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::account::{AccountSharedData, ReadableAccount};
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+mod history;
+mod sandbox;
+
+pub use history::{fetch_transaction, read_index_log, HistoricalTransaction};
+pub use sandbox::Sandbox;
+
+// getMultipleAccounts limit
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+// Programs a build directory can replace, by their build file name
+pub fn programs() -> Vec<(&'static str, Pubkey)> {
+    vec![
+        ("agent_registry", agent_registry_cpi::ID),
+        ("influence", influence_cpi::ID),
+        ("relationship", relationship_cpi::ID),
+        ("sessions", sessions::ID),
+        ("guardian", guardian::ID),
+        ("rate_limiter", rate_limiter::ID),
+        ("attestation", attestation::ID),
+        ("analytics", analytics::ID),
+    ]
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Divergence {
+    // Error messages, None where the transaction succeeded
+    Outcome { baseline: Option<String>, candidate: Option<String> },
+    // An account both banks hold but with different contents. Field changes
+    // are filled in for program accounts the schema registry can decode.
+    Account {
+        address: Pubkey,
+        lamports: (u64, u64),
+        data_len: (usize, usize),
+        changes: Vec<FieldChange>,
+    },
+    // An account only one bank holds after the transaction
+    Existence { address: Pubkey, in_baseline: bool },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionReport {
+    pub signature: Signature,
+    pub slot: u64,
+    pub divergences: Vec<Divergence>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    // Transactions that succeeded on the cluster but failed on the baseline
+    // builds, usually because the replay started mid-history
+    pub baseline_failures: Vec<(Signature, String)>,
+    pub seeded_program_accounts: usize,
+    pub divergent: Vec<TransactionReport>,
+}
+
+pub struct Replayer {
+    rpc: RpcClient,
+    baseline: Sandbox,
+    candidate: Sandbox,
+    schemas: SchemaRegistry,
+    programs: HashSet<Pubkey>,
+}
+
+impl Replayer {
+    pub fn new(rpc_url: String, baseline_dir: &Path, candidate_dir: &Path) -> Result<Self, ReplayError> {
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(rpc_url, CommitmentConfig::finalized()),
+            baseline: Sandbox::new(baseline_dir)?,
+            candidate: Sandbox::new(candidate_dir)?,
+            schemas: SchemaRegistry::new(),
+            programs: programs().into_iter().map(|(_, program_id)| program_id).collect(),
+        })
+    }
+
+    // Replay the log's transactions in order, up to `limit` of them
+    pub async fn replay(
+        &mut self,
+        index_log: &Path,
+        limit: Option<usize>,
+        mut on_transaction: impl FnMut(&HistoricalTransaction, Option<&TransactionReport>),
+    ) -> Result<ReplayReport, ReplayError> {
+        let mut report = ReplayReport::default();
+        let transactions = read_index_log(index_log)?;
+        for (slot, signature) in transactions.into_iter().take(limit.unwrap_or(usize::MAX)) {
+            let historical = fetch_transaction(&self.rpc, slot, &signature).await?;
+            report.seeded_program_accounts += self.seed_accounts(&historical).await?;
+            for (address, lamports) in historical.account_keys.iter().zip(&historical.pre_balances) {
+                if !self.is_program_account(address) {
+                    self.baseline.set_lamports(address, *lamports);
+                    self.candidate.set_lamports(address, *lamports);
+                }
+            }
+
+            let baseline = self.baseline.execute(&historical).err().map(|err| err.to_string());
+            let candidate = self.candidate.execute(&historical).err().map(|err| err.to_string());
+            if let Some(err) = &baseline {
+                report.baseline_failures.push((signature, err.clone()));
+            }
+            let mut divergences = Vec::new();
+            if baseline != candidate {
+                divergences.push(Divergence::Outcome { baseline, candidate });
+            }
+            for address in &historical.account_keys {
+                divergences.extend(self.compare(address)?);
+            }
+
+            report.replayed += 1;
+            let transaction_report = (!divergences.is_empty()).then(|| TransactionReport {
+                signature,
+                slot,
+                divergences,
+            });
+            on_transaction(&historical, transaction_report.as_ref());
+            report.divergent.extend(transaction_report);
+        }
+        Ok(report)
+    }
+
+    fn is_program_account(&self, address: &Pubkey) -> bool {
+        self.baseline
+            .account(address)
+            .map_or(false, |account| self.programs.contains(account.owner()) || account.executable())
+    }
+
+    // Copy accounts the banks have not seen from the cluster, with the
+    // program data of upgradeable programs. Returns how many belonged to the
+    // replayed programs.
+    async fn seed_accounts(&self, historical: &HistoricalTransaction) -> Result<usize, ReplayError> {
+        let mut missing: Vec<Pubkey> = historical
+            .account_keys
+            .iter()
+            .chain(
+                historical
+                    .transaction
+                    .message
+                    .address_table_lookups()
+                    .into_iter()
+                    .flatten()
+                    .map(|lookup| &lookup.account_key),
+            )
+            .filter(|address| self.baseline.account(address).is_none())
+            .copied()
+            .collect();
+        missing.sort();
+        missing.dedup();
+
+        let mut seeded_program_accounts = 0;
+        while !missing.is_empty() {
+            let mut program_data = Vec::new();
+            for chunk in missing.chunks(MAX_MULTIPLE_ACCOUNTS) {
+                let accounts = self.rpc.get_multiple_accounts(chunk).await?;
+                for (address, account) in chunk.iter().zip(accounts) {
+                    let Some(account) = account else { continue };
+                    if self.programs.contains(&account.owner) {
+                        seeded_program_accounts += 1;
+                    }
+                    if account.executable && account.owner == bpf_loader_upgradeable::id() {
+                        if let Ok(UpgradeableLoaderState::Program { programdata_address }) = account.deserialize_data() {
+                            program_data.push(programdata_address);
+                        }
+                    }
+                    let account = AccountSharedData::from(account);
+                    self.baseline.store_account(address, &account);
+                    self.candidate.store_account(address, &account);
+                }
+            }
+            missing = program_data
+                .into_iter()
+                .filter(|address| self.baseline.account(address).is_none())
+                .collect();
+        }
+        Ok(seeded_program_accounts)
+    }
+
+    fn compare(&self, address: &Pubkey) -> Result<Option<Divergence>, ReplayError> {
+        let (baseline, candidate) = match (self.baseline.account(address), self.candidate.account(address)) {
+            (None, None) => return Ok(None),
+            (Some(_), None) => return Ok(Some(Divergence::Existence { address: *address, in_baseline: true })),
+            (None, Some(_)) => return Ok(Some(Divergence::Existence { address: *address, in_baseline: false })),
+            (Some(baseline), Some(candidate)) => (baseline, candidate),
+        };
+        if baseline == candidate {
+            return Ok(None);
+        }
+        let changes = match (
+            self.schemas.decode_account(baseline.owner(), baseline.data())?,
+            self.schemas.decode_account(candidate.owner(), candidate.data())?,
+        ) {
+            (Some(old), Some(new)) => field_changes(&old.data, &new.data),
+            _ => Vec::new(),
+        };
+        Ok(Some(Divergence::Account {
+            address: *address,
+            lamports: (baseline.lamports(), candidate.lamports()),
+            data_len: (baseline.data().len(), candidate.data().len()),
+            changes,
+        }))
+    }
+}
+
+// Errors
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Rpc(ClientError),
+    History(String),
+    Build(String),
+    Decode(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Rpc(err) => write!(f, "RPC error: {}", err),
+            ReplayError::History(err) => write!(f, "History error: {}", err),
+            ReplayError::Build(err) => write!(f, "Program build error: {}", err),
+            ReplayError::Decode(err) => write!(f, "Decoding failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<ClientError> for ReplayError {
+    fn from(err: ClientError) -> Self {
+        ReplayError::Rpc(err)
+    }
+}
+
+impl From<armour_events::EventError> for ReplayError {
+    fn from(err: armour_events::EventError) -> Self {
+        ReplayError::Decode(err.to_string())
+    }
+}
@@ -0,0 +1,108 @@
+// armour-replay --rpc <url> --index <events.jsonl> --baseline <dir> --candidate <dir>
+//     [--limit <n>]
+//
+// Build directories hold `<program>.so` files, e.g. target/deploy of the
+// deployed commit and of the change under test. Exits 1 when any
+// transaction diverges.
+use std::path::PathBuf;
+use std::process::exit;
+
+use armour_replay::{Divergence, Replayer};
+
+const USAGE: &str = "usage: armour-replay --rpc <url> --index <events.jsonl> --baseline <dir> \
+--candidate <dir> [--limit <n>]";
+
+struct Args {
+    rpc: String,
+    index: PathBuf,
+    baseline: PathBuf,
+    candidate: PathBuf,
+    limit: Option<usize>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut rpc = None;
+    let mut index = None;
+    let mut baseline = None;
+    let mut candidate = None;
+    let mut limit = None;
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--rpc" => rpc = Some(value("--rpc")?),
+            "--index" => index = Some(PathBuf::from(value("--index")?)),
+            "--baseline" => baseline = Some(PathBuf::from(value("--baseline")?)),
+            "--candidate" => candidate = Some(PathBuf::from(value("--candidate")?)),
+            "--limit" => limit = Some(value("--limit")?.parse().map_err(|_| "invalid --limit")?),
+            other => return Err(format!("unknown argument {}", other)),
+        }
+    }
+    Ok(Args {
+        rpc: rpc.ok_or("missing --rpc")?,
+        index: index.ok_or("missing --index")?,
+        baseline: baseline.ok_or("missing --baseline")?,
+        candidate: candidate.ok_or("missing --candidate")?,
+        limit,
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+        exit(2);
+    });
+    let mut replayer = Replayer::new(args.rpc, &args.baseline, &args.candidate).unwrap_or_else(|err| fail(err));
+
+    let report = replayer
+        .replay(&args.index, args.limit, |historical, report| {
+            let Some(report) = report else { return };
+            println!("{} (slot {})", historical.signature, historical.slot);
+            for divergence in &report.divergences {
+                match divergence {
+                    Divergence::Outcome { baseline, candidate } => {
+                        let show = |result: &Option<String>| result.clone().unwrap_or("ok".to_string());
+                        println!("  outcome: {} -> {}", show(baseline), show(candidate));
+                    }
+                    Divergence::Account { address, lamports, data_len, changes } => {
+                        println!(
+                            "  {}: {} -> {} lamports, {} -> {} bytes",
+                            address, lamports.0, lamports.1, data_len.0, data_len.1
+                        );
+                        for change in changes {
+                            let show = |value: &Option<serde_json::Value>| {
+                                value.as_ref().map_or("-".to_string(), |v| v.to_string())
+                            };
+                            println!("    {}: {} -> {}", change.field, show(&change.old), show(&change.new));
+                        }
+                    }
+                    Divergence::Existence { address, in_baseline } => {
+                        let only = if *in_baseline { "baseline" } else { "candidate" };
+                        println!("  {}: exists only in the {}", address, only);
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|err| fail(err));
+
+    for (signature, err) in &report.baseline_failures {
+        eprintln!("baseline failed {}: {}", signature, err);
+    }
+    println!(
+        "{} transactions replayed, {} diverged, {} failed on the baseline, {} program accounts seeded from the cluster",
+        report.replayed,
+        report.divergent.len(),
+        report.baseline_failures.len(),
+        report.seeded_program_accounts
+    );
+    if !report.divergent.is_empty() {
+        exit(1);
+    }
+}
+
+fn fail(err: armour_replay::ReplayError) -> ! {
+    eprintln!("{}", err);
+    exit(1);
+}
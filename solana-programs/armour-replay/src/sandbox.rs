@@ -0,0 +1,83 @@
+// A local bank running one set of program builds. Signatures are not
+// verified and fees are zero, so historical transactions execute as sent
+// apart from their blockhash, which is swapped for the bank's.
+use std::fs;
+use std::path::Path;
+
+use solana_runtime::bank::Bank;
+use solana_runtime::genesis_utils::create_genesis_config;
+use solana_sdk::account::{Account, AccountSharedData, ReadableAccount, WritableAccount};
+use solana_sdk::bpf_loader;
+use solana_sdk::clock::Clock;
+use solana_sdk::fee_calculator::FeeRateGovernor;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::transaction::TransactionError;
+
+use crate::history::HistoricalTransaction;
+use crate::{programs, ReplayError};
+
+pub struct Sandbox {
+    bank: Bank,
+}
+
+impl Sandbox {
+    // Load `<name>.so` from `build_dir` for each of `programs()` that has
+    // one. Programs without a build run the cluster's version, fetched with
+    // the other accounts on first use.
+    pub fn new(build_dir: &Path) -> Result<Self, ReplayError> {
+        let mut genesis = create_genesis_config(0).genesis_config;
+        genesis.fee_rate_governor = FeeRateGovernor::new(0, 0);
+        let mut loaded = 0;
+        for (name, program_id) in programs() {
+            let path = build_dir.join(format!("{}.so", name));
+            let Ok(elf) = fs::read(&path) else { continue };
+            genesis.add_account(
+                program_id,
+                AccountSharedData::from(Account {
+                    lamports: Rent::default().minimum_balance(elf.len()),
+                    data: elf,
+                    owner: bpf_loader::id(),
+                    executable: true,
+                    rent_epoch: 0,
+                }),
+            );
+            loaded += 1;
+        }
+        if loaded == 0 {
+            return Err(ReplayError::Build(format!("no program builds in {}", build_dir.display())));
+        }
+        Ok(Self { bank: Bank::new_for_tests(&genesis) })
+    }
+
+    pub fn account(&self, address: &Pubkey) -> Option<AccountSharedData> {
+        self.bank.get_account(address)
+    }
+
+    pub fn store_account(&self, address: &Pubkey, account: &AccountSharedData) {
+        self.bank.store_account(address, account);
+    }
+
+    pub fn set_lamports(&self, address: &Pubkey, lamports: u64) {
+        if let Some(mut account) = self.bank.get_account(address) {
+            if account.lamports() != lamports {
+                account.set_lamports(lamports);
+                self.bank.store_account(address, &account);
+            }
+        }
+    }
+
+    // Execute at the transaction's historical block time
+    pub fn execute(&self, historical: &HistoricalTransaction) -> Result<(), TransactionError> {
+        let mut clock: Clock = self.bank.clock();
+        clock.unix_timestamp = historical.block_time;
+        self.bank.set_sysvar_for_tests(&clock);
+
+        let mut transaction = historical.transaction.clone();
+        transaction.message.set_recent_blockhash(self.bank.last_blockhash());
+        self.bank
+            .process_entry_transactions(vec![transaction])
+            .pop()
+            .unwrap_or(Err(TransactionError::SanitizeFailure))
+    }
+}
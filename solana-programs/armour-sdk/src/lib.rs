@@ -0,0 +1,440 @@
+// Async RPC client for the LOVE platform programs. Instruction builders and
+// PDA finders come from the *-cpi interface crates (re-exported below); this
+// crate adds account fetching, transaction construction with priority fees,
+// and flows that bundle several instructions into one transaction. Agent cNFT
+// metadata JSON is generated by the `metadata` module.
+use std::fmt;
+
+pub mod metadata;
+
+use anchor_lang::{AccountDeserialize, Discriminator, Id, ZeroCopy};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::ClientError;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+
+pub use agent_registry_cpi as agent_registry;
+pub use armour_core;
+pub use influence_cpi as influence;
+pub use referrals_cpi as referrals;
+pub use relationship_cpi as relationship;
+
+use agent_registry_cpi::{AgentData, Bubblegum, PersonalityTraits};
+use influence_cpi::MintConfig;
+use referrals_cpi::{Referral, ReferralEdge};
+use relationship_cpi::{
+    CustomInteraction, InteractionNonce, InteractionType, RelationshipData, RelationshipType,
+};
+
+// Compute budget prepended to every transaction the client sends
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PriorityFee {
+    pub micro_lamports_per_cu: u64,     // 0 sends without a priority fee
+    pub compute_unit_limit: Option<u32>, // None keeps the runtime default
+}
+
+pub struct ArmourClient {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub priority_fee: PriorityFee,
+}
+
+impl ArmourClient {
+    pub fn new(rpc_url: String, payer: Keypair) -> Self {
+        Self {
+            rpc: RpcClient::new(rpc_url),
+            payer,
+            priority_fee: PriorityFee::default(),
+        }
+    }
+
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    // Transactions
+
+    // Build a transaction paid and signed by the payer, with the compute
+    // budget instructions in front of `instructions`
+    pub async fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        extra_signers: &[&Keypair],
+    ) -> Result<Transaction, SdkError> {
+        let mut all = compute_budget_instructions(&self.priority_fee);
+        all.extend_from_slice(instructions);
+
+        let mut signers: Vec<&dyn Signer> = vec![&self.payer];
+        signers.extend(extra_signers.iter().map(|signer| *signer as &dyn Signer));
+
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        Ok(Transaction::new_signed_with_payer(
+            &all,
+            Some(&self.payer.pubkey()),
+            &signers,
+            blockhash,
+        ))
+    }
+
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        extra_signers: &[&Keypair],
+    ) -> Result<Signature, SdkError> {
+        let transaction = self.build_transaction(instructions, extra_signers).await?;
+        Ok(self.rpc.send_and_confirm_transaction(&transaction).await?)
+    }
+
+    // Accounts
+
+    pub async fn fetch_agent(&self, agent: &Pubkey) -> Result<AgentData, SdkError> {
+        self.fetch_account(agent).await
+    }
+
+    // Every agent registered by `owner`
+    pub async fn fetch_agents_by_owner(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<(Pubkey, AgentData)>, SdkError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &AgentData::discriminator())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, owner.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&agent_registry_cpi::ID, config)
+            .await?;
+        accounts
+            .into_iter()
+            .map(|(key, account)| {
+                let agent = AgentData::try_deserialize(&mut account.data.as_slice())?;
+                Ok((key, agent))
+            })
+            .collect()
+    }
+
+    pub async fn fetch_relationship(&self, relationship: &Pubkey) -> Result<RelationshipData, SdkError> {
+        self.fetch_zero_copy(relationship).await
+    }
+
+    pub async fn fetch_interaction_nonce(&self, relationship: &Pubkey) -> Result<InteractionNonce, SdkError> {
+        let nonce = relationship_cpi::pda::find_interaction_nonce_address(relationship).0;
+        self.fetch_account(&nonce).await
+    }
+
+    pub async fn fetch_mint_config(&self, mint: &Pubkey) -> Result<MintConfig, SdkError> {
+        let mint_config = influence_cpi::pda::find_mint_config_address(mint).0;
+        self.fetch_account(&mint_config).await
+    }
+
+    // Whoever referred `wallet`, if anyone did
+    pub async fn fetch_referrer(&self, wallet: &Pubkey) -> Result<Option<Pubkey>, SdkError> {
+        let referral = referrals_cpi::pda::find_referral_address(wallet).0;
+        let account = self
+            .rpc
+            .get_account_with_commitment(&referral, self.rpc.commitment())
+            .await?
+            .value;
+        account
+            .map(|account| -> Result<Pubkey, SdkError> {
+                Ok(Referral::try_deserialize(&mut account.data.as_slice())?.referrer)
+            })
+            .transpose()
+    }
+
+    pub async fn fetch_account<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T, SdkError> {
+        let data = self.rpc.get_account_data(address).await?;
+        Ok(T::try_deserialize(&mut data.as_slice())?)
+    }
+
+    // Zero-copy accounts are read in place by the programs and have no Borsh layout
+    pub async fn fetch_zero_copy<T: ZeroCopy>(&self, address: &Pubkey) -> Result<T, SdkError> {
+        let data = self.rpc.get_account_data(address).await?;
+        let body = data
+            .strip_prefix(&T::discriminator()[..])
+            .ok_or(SdkError::AccountMismatch(*address))?;
+        let size = std::mem::size_of::<T>();
+        if body.len() < size {
+            return Err(SdkError::AccountMismatch(*address));
+        }
+        Ok(bytemuck::pod_read_unaligned(&body[..size]))
+    }
+
+    // Flows
+
+    // Register an agent, minting its cNFT into `merkle_tree` and the
+    // registry's collection, `collection_mint`
+    pub async fn register_agent(
+        &self,
+        name: String,
+        metadata_uri: String,
+        personality_traits: PersonalityTraits,
+        merkle_tree: Pubkey,
+        collection_mint: Pubkey,
+    ) -> Result<(Pubkey, Signature), SdkError> {
+        let owner = self.payer.pubkey();
+        let agent = agent_registry_cpi::pda::find_agent_address(&owner, &name).0;
+        let referrer = self.fetch_referrer(&owner).await?;
+        let instruction = register_agent(
+            owner,
+            name,
+            metadata_uri,
+            personality_traits,
+            merkle_tree,
+            collection_mint,
+            referrer,
+        );
+        let signature = self.send(&[instruction], &[]).await?;
+        Ok((agent, signature))
+    }
+
+    // Register an agent and start a relationship with `partner` in the same
+    // transaction, scoring the pair with the registry's compatibility function
+    pub async fn register_agent_and_relate(
+        &self,
+        name: String,
+        metadata_uri: String,
+        personality_traits: PersonalityTraits,
+        merkle_tree: Pubkey,
+        collection_mint: Pubkey,
+        partner: Pubkey,
+        relationship_type: RelationshipType,
+    ) -> Result<(Pubkey, Pubkey, Signature), SdkError> {
+        let owner = self.payer.pubkey();
+        let agent = agent_registry_cpi::pda::find_agent_address(&owner, &name).0;
+        let partner_data = self.fetch_agent(&partner).await?;
+        let referrer = self.fetch_referrer(&owner).await?;
+        let score = armour_core::calculate_compatibility_score(
+            &personality_traits,
+            &partner_data.personality_traits,
+        );
+
+        let (agent_one, agent_two) = canonical_pair(agent, partner);
+        let relationship = relationship_cpi::pda::find_relationship_address(&agent_one, &agent_two).0;
+        let instructions = [
+            register_agent(
+                owner,
+                name,
+                metadata_uri,
+                personality_traits,
+                merkle_tree,
+                collection_mint,
+                referrer,
+            ),
+            relationship_cpi::builders::create_relationship(
+                owner,
+                agent_one,
+                agent_two,
+                None,
+                relationship_type,
+                score,
+            ),
+        ];
+        let signature = self.send(&instructions, &[]).await?;
+        Ok((agent, relationship, signature))
+    }
+
+    // Create a relationship between two existing agents and record its first
+    // interaction. The pair may be given in either order.
+    pub async fn start_relationship(
+        &self,
+        agent_a: Pubkey,
+        agent_b: Pubkey,
+        relationship_type: RelationshipType,
+        interaction_type: InteractionType,
+        interaction_data: String,
+    ) -> Result<(Pubkey, Signature), SdkError> {
+        let authority = self.payer.pubkey();
+        let (agent_one, agent_two) = canonical_pair(agent_a, agent_b);
+        let (one, two) = (self.fetch_agent(&agent_one).await?, self.fetch_agent(&agent_two).await?);
+        let score = armour_core::calculate_compatibility_score(&one.personality_traits, &two.personality_traits);
+
+        let relationship = relationship_cpi::pda::find_relationship_address(&agent_one, &agent_two).0;
+        let instructions = [
+            relationship_cpi::builders::create_relationship(
+                authority,
+                agent_one,
+                agent_two,
+                None,
+                relationship_type,
+                score,
+            ),
+            relationship_cpi::builders::record_interaction(
+                authority,
+                authority,
+                relationship,
+                agent_one,
+                agent_two,
+                0,
+                interaction_type,
+                interaction_data,
+                None,
+                None,
+            ),
+        ];
+        let signature = self.send(&instructions, &[]).await?;
+        Ok((relationship, signature))
+    }
+
+    // Record an interaction at the relationship's next nonce index
+    pub async fn record_interaction(
+        &self,
+        relationship: Pubkey,
+        interaction_type: InteractionType,
+        interaction_data: String,
+        custom_type: Option<CustomInteraction>,
+    ) -> Result<Signature, SdkError> {
+        let relationship_data = self.fetch_relationship(&relationship).await?;
+        let nonce = self.fetch_interaction_nonce(&relationship).await?;
+        let instruction = relationship_cpi::builders::record_interaction(
+            self.payer.pubkey(),
+            self.payer.pubkey(),
+            relationship,
+            relationship_data.agent_one,
+            relationship_data.agent_two,
+            nonce.next_index,
+            interaction_type,
+            interaction_data,
+            custom_type,
+            None,
+        );
+        self.send(&[instruction], &[]).await
+    }
+
+    // Tip influence tokens from the payer's associated token account. The
+    // transfer record is keyed by the cluster clock, so the current block
+    // time is used and the call should be retried if the second rolls over.
+    pub async fn tip(
+        &self,
+        mint: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+        reason: String,
+    ) -> Result<Signature, SdkError> {
+        let slot = self.rpc.get_slot().await?;
+        let timestamp = self.rpc.get_block_time(slot).await?;
+        let owner = self.payer.pubkey();
+        let instruction = influence_cpi::builders::transfer_tokens(
+            owner, owner, mint, recipient, amount, reason, timestamp, None,
+        );
+        self.send(&[instruction], &[]).await
+    }
+}
+
+// Errors
+#[derive(Debug)]
+pub enum SdkError {
+    Rpc(ClientError),
+    Deserialize(anchor_lang::error::Error),
+    AccountMismatch(Pubkey),  // Wrong discriminator or too short for the expected type
+}
+
+impl fmt::Display for SdkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdkError::Rpc(err) => write!(f, "RPC error: {}", err),
+            SdkError::Deserialize(err) => write!(f, "Account deserialization failed: {}", err),
+            SdkError::AccountMismatch(address) => write!(f, "Account {} is not of the expected type", address),
+        }
+    }
+}
+
+impl std::error::Error for SdkError {}
+
+impl From<ClientError> for SdkError {
+    fn from(err: ClientError) -> Self {
+        SdkError::Rpc(err)
+    }
+}
+
+impl From<anchor_lang::error::Error> for SdkError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        SdkError::Deserialize(err)
+    }
+}
+
+// Helper functions
+pub fn compute_budget_instructions(priority_fee: &PriorityFee) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    if let Some(limit) = priority_fee.compute_unit_limit {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+    if priority_fee.micro_lamports_per_cu > 0 {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+            priority_fee.micro_lamports_per_cu,
+        ));
+    }
+    instructions
+}
+
+// Relationships store each pair once, ordered by agent key
+pub fn canonical_pair(agent_a: Pubkey, agent_b: Pubkey) -> (Pubkey, Pubkey) {
+    if agent_a < agent_b {
+        (agent_a, agent_b)
+    } else {
+        (agent_b, agent_a)
+    }
+}
+
+// The registry mints each agent as a cNFT in its collection, so
+// registration carries the Bubblegum tree and collection accounts.
+// `referrer` is whoever referred the owner, to credit them for it.
+pub fn register_agent(
+    owner: Pubkey,
+    name: String,
+    metadata_uri: String,
+    personality_traits: PersonalityTraits,
+    merkle_tree: Pubkey,
+    collection_mint: Pubkey,
+    referrer: Option<Pubkey>,
+) -> Instruction {
+    let agent_data = agent_registry_cpi::pda::find_agent_address(&owner, &name).0;
+    let referral = referrer.map(|referrer| ReferralEdge { referee: owner, referrer }.addresses());
+    let tree_authority = Pubkey::find_program_address(&[merkle_tree.as_ref()], &Bubblegum::id()).0;
+    agent_registry_cpi::builders::build(
+        agent_registry_cpi::accounts::RegisterAgent {
+            owner,
+            agent_data,
+            registry_state: agent_registry_cpi::pda::find_registry_state_address().0,
+            tree_authority,
+            merkle_tree,
+            bubblegum_program: Bubblegum::id(),
+            compression_program: spl_account_compression::ID,
+            system_program: solana_sdk::system_program::ID,
+            guardian: agent_registry_cpi::pda::find_guardian_address().0,
+            rate_limiter_config: agent_registry_cpi::pda::find_rate_limiter_config_address().0,
+            wallet_quota: agent_registry_cpi::pda::find_wallet_quota_address(&owner).0,
+            rate_limiter_program: rate_limiter::ID,
+            credential: Some(agent_registry_cpi::pda::find_credential_address(&owner).0),
+            analytics_hook_authority: agent_registry_cpi::pda::find_analytics_hook_authority().0,
+            analytics_state: agent_registry_cpi::pda::find_analytics_state_address().0,
+            analytics_program: agent_registry_cpi::ANALYTICS_PROGRAM_ID,
+            referral_reporter: referral.map(|_| agent_registry_cpi::pda::find_referral_reporter_address().0),
+            referrals_config: referral.map(|_| referrals_cpi::pda::find_config_address().0),
+            referral: referral.map(|(referral, _)| referral),
+            referrer_rewards: referral.map(|(_, rewards)| rewards),
+            referrals_program: referral.map(|_| referrals_cpi::ID),
+            collection: agent_registry_cpi::builders::collection_accounts(collection_mint),
+        },
+        agent_registry_cpi::instruction::RegisterAgent {
+            metadata_uri,
+            name,
+            personality_traits,
+        },
+    )
+}
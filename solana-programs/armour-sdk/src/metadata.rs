@@ -0,0 +1,196 @@
+// Metaplex-standard JSON for agent cNFTs, the document behind an agent's
+// `metadata_uri`. Fields serialize in struct order and attributes in a fixed
+// order, so the same agent always produces the same bytes, and therefore the
+// same `metadata_hash`, whichever integrator generates it. Upload exactly
+// the output of `to_json`.
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::{hash, Hash};
+use solana_sdk::pubkey::Pubkey;
+
+use agent_registry_cpi::{AgentData, PersonalityTraits};
+
+// The symbol the registry mints agents with
+pub const AGENT_SYMBOL: &str = "AIAGENT";
+
+const TRAIT_NAMES: [&str; armour_core::TRAIT_COUNT] = [
+    "Openness",
+    "Conscientiousness",
+    "Extraversion",
+    "Agreeableness",
+    "Neuroticism",
+    "Intelligence",
+    "Creativity",
+    "Humor",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AgentMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub properties: Properties,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: AttributeValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Properties {
+    pub files: Vec<File>,
+    pub category: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct File {
+    pub uri: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+}
+
+// How an agent looks. Features become text attributes, in the given order,
+// after the personality traits.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Appearance {
+    pub image: String,
+    pub image_type: String,  // MIME type, e.g. "image/png"
+    pub animation_url: Option<String>,
+    pub features: Vec<(String, String)>,  // (trait_type, value)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lineage {
+    pub generation: u16,
+    pub parents: Option<(Pubkey, Pubkey)>,  // None for agents that were not bred
+}
+
+impl Lineage {
+    pub fn of(agent: &AgentData) -> Self {
+        let parents = (agent.generation > 0).then_some((agent.parent_one, agent.parent_two));
+        Self { generation: agent.generation, parents }
+    }
+}
+
+// Everything the metadata is generated from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgentMetadataArgs {
+    pub name: String,
+    pub description: String,
+    pub external_url: Option<String>,
+    pub personality_traits: PersonalityTraits,
+    pub appearance: Appearance,
+    pub level: u32,
+    pub lineage: Lineage,
+}
+
+impl AgentMetadataArgs {
+    // Args for an on-chain agent; the name, traits and lineage come from its account
+    pub fn from_agent(agent: &AgentData, description: String, appearance: Appearance, level: u32) -> Self {
+        Self {
+            name: agent.name.clone(),
+            description,
+            external_url: None,
+            personality_traits: agent.personality_traits.clone(),
+            appearance,
+            level,
+            lineage: Lineage::of(agent),
+        }
+    }
+}
+
+impl AgentMetadata {
+    // Attributes are the personality traits in PersonalityTraits::values
+    // order, then appearance features, level, generation and parents
+    pub fn new(args: &AgentMetadataArgs) -> Self {
+        let mut attributes: Vec<Attribute> = TRAIT_NAMES
+            .iter()
+            .zip(args.personality_traits.values())
+            .map(|(name, value)| Attribute::number(name, value as u64, None))
+            .collect();
+        attributes.extend(
+            args.appearance
+                .features
+                .iter()
+                .map(|(trait_type, value)| Attribute::text(trait_type, value.clone())),
+        );
+        attributes.push(Attribute::number("Level", args.level as u64, Some("number")));
+        attributes.push(Attribute::number("Generation", args.lineage.generation as u64, Some("number")));
+        if let Some((parent_one, parent_two)) = args.lineage.parents {
+            attributes.push(Attribute::text("Parent One", parent_one.to_string()));
+            attributes.push(Attribute::text("Parent Two", parent_two.to_string()));
+        }
+
+        Self {
+            name: args.name.clone(),
+            symbol: AGENT_SYMBOL.to_string(),
+            description: args.description.clone(),
+            image: args.appearance.image.clone(),
+            animation_url: args.appearance.animation_url.clone(),
+            external_url: args.external_url.clone(),
+            attributes,
+            properties: Properties {
+                files: vec![File {
+                    uri: args.appearance.image.clone(),
+                    mime_type: args.appearance.image_type.clone(),
+                }],
+                category: "image".to_string(),
+            },
+        }
+    }
+
+    // The canonical document: pretty-printed with two-space indents, no
+    // trailing newline
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("metadata always serializes")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    // The integrity hash to store as `metadata_hash`
+    pub fn hash(&self) -> Hash {
+        metadata_hash(self.to_json().as_bytes())
+    }
+}
+
+impl Attribute {
+    fn number(trait_type: &str, value: u64, display_type: Option<&str>) -> Self {
+        Self {
+            trait_type: trait_type.to_string(),
+            value: AttributeValue::Number(value),
+            display_type: display_type.map(str::to_string),
+        }
+    }
+
+    fn text(trait_type: &str, value: String) -> Self {
+        Self {
+            trait_type: trait_type.to_string(),
+            value: AttributeValue::Text(value),
+            display_type: None,
+        }
+    }
+}
+
+// sha256 of the document as served, for checking fetched metadata against
+// an agent's stored hash
+pub fn metadata_hash(json: &[u8]) -> Hash {
+    hash(json)
+}
@@ -0,0 +1,88 @@
+// Golden-file tests for agent metadata. The files under tests/golden are the
+// bytes every integrator must produce; regenerate them with
+// `UPDATE_GOLDEN=1 cargo test --test metadata` only for deliberate format changes.
+use std::path::PathBuf;
+
+use armour_sdk::agent_registry::PersonalityTraits;
+use armour_sdk::metadata::{metadata_hash, AgentMetadata, AgentMetadataArgs, Appearance, Lineage};
+use solana_sdk::pubkey::Pubkey;
+
+fn registered_agent() -> AgentMetadataArgs {
+    AgentMetadataArgs {
+        name: "Ada".to_string(),
+        description: "A curious, outgoing conversationalist.".to_string(),
+        external_url: None,
+        personality_traits: PersonalityTraits::from_values([80, 20, 65, 40, 10, 90, 55, 70]),
+        appearance: Appearance {
+            image: "https://arweave.net/ada.png".to_string(),
+            image_type: "image/png".to_string(),
+            animation_url: None,
+            features: vec![],
+        },
+        level: 1,
+        lineage: Lineage::default(),
+    }
+}
+
+fn bred_agent() -> AgentMetadataArgs {
+    AgentMetadataArgs {
+        name: "Byte \"Junior\"".to_string(),
+        description: "Offspring of Ada and Turing.".to_string(),
+        external_url: Some("https://love.example/agents/byte".to_string()),
+        personality_traits: PersonalityTraits::from_values([65, 45, 60, 70, 30, 85, 100, 0]),
+        appearance: Appearance {
+            image: "https://arweave.net/byte.webp".to_string(),
+            image_type: "image/webp".to_string(),
+            animation_url: Some("https://arweave.net/byte.mp4".to_string()),
+            features: vec![
+                ("Background".to_string(), "Nebula".to_string()),
+                ("Eyes".to_string(), "Amber".to_string()),
+            ],
+        },
+        level: 12,
+        lineage: Lineage {
+            generation: 2,
+            parents: Some((Pubkey::new_from_array([1; 32]), Pubkey::new_from_array([2; 32]))),
+        },
+    }
+}
+
+fn check_golden(case: &str, args: &AgentMetadataArgs) {
+    let metadata = AgentMetadata::new(args);
+    let json = metadata.to_json();
+    let hash = metadata.hash().to_string();
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let json_path = dir.join(format!("{}.json", case));
+    let hash_path = dir.join(format!("{}.hash", case));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&json_path, &json).unwrap();
+        std::fs::write(&hash_path, format!("{}\n", hash)).unwrap();
+        return;
+    }
+
+    let golden = std::fs::read_to_string(&json_path).unwrap();
+    assert_eq!(json, golden, "{} metadata differs from {}", case, json_path.display());
+    let golden_hash = std::fs::read_to_string(&hash_path).unwrap();
+    assert_eq!(hash, golden_hash.trim(), "{} hash differs from {}", case, hash_path.display());
+    assert_eq!(metadata_hash(golden.as_bytes()).to_string(), hash);
+    assert_eq!(AgentMetadata::from_json(&golden).unwrap(), metadata);
+}
+
+#[test]
+fn registered_agent_golden() {
+    check_golden("registered_agent", &registered_agent());
+}
+
+#[test]
+fn bred_agent_golden() {
+    check_golden("bred_agent", &bred_agent());
+}
+
+#[test]
+fn hash_changes_with_traits() {
+    let mut args = registered_agent();
+    let before = AgentMetadata::new(&args).hash();
+    args.personality_traits.humor += 1;
+    assert_ne!(AgentMetadata::new(&args).hash(), before);
+}
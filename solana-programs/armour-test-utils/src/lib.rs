@@ -0,0 +1,574 @@
+// solana-program-test fixtures covering the registry, influence,
+// relationship, sessions, guardian, rate limiter, attestation, analytics and
+// referrals programs. Bubblegum, account compression, noop and Token Metadata are
+// replaced by stubs that accept every instruction, so agents can be
+// registered without a real merkle tree or collection NFT; cNFT state is not
+// simulated.
+//
+//     let mut test = ArmourTest::start().await;
+//     let owner = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+//     let agent = test.create_agent(&owner, "alice", PersonalityTraits::default()).await;
+use anchor_lang::prelude::{AccountInfo, ProgramResult};
+use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::{AccountDeserialize, Id, InstructionData, ToAccountMetas, ZeroCopy};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::state::Mint;
+use anchor_spl::token::TokenAccount;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{AccountSharedData, WritableAccount};
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_option::COption;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::{system_program, sysvar};
+
+use agent_registry::{AgentData, Bubblegum, PersonalityTraits};
+use rate_limiter::{ActionLimit, RateLimitedAction};
+use relationship::{DateParams, InteractionType, RelationshipData, RelationshipType};
+
+pub use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+fn accept_all(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+// All programs, with stubs for the external cNFT programs
+pub fn program_test() -> ProgramTest {
+    program_test_with(false)
+}
+
+// Runs the programs from their built .so files (BPF_OUT_DIR or
+// target/deploy), so compute units are metered as on a cluster
+pub fn bpf_program_test() -> ProgramTest {
+    program_test_with(true)
+}
+
+fn program_test_with(prefer_bpf: bool) -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(prefer_bpf);
+    program_test.add_program("agent_registry", agent_registry::ID, processor!(agent_registry::entry));
+    program_test.add_program("influence", influence::ID, processor!(influence::entry));
+    program_test.add_program("relationship", relationship::ID, processor!(relationship::entry));
+    program_test.add_program("sessions", sessions::ID, processor!(sessions::entry));
+    program_test.add_program("guardian", guardian::ID, processor!(guardian::entry));
+    program_test.add_program("rate_limiter", rate_limiter::ID, processor!(rate_limiter::entry));
+    program_test.add_program("attestation", attestation::ID, processor!(attestation::entry));
+    program_test.add_program("analytics", analytics::ID, processor!(analytics::entry));
+    program_test.add_program("referrals", referrals::ID, processor!(referrals::entry));
+    // Stubs have no .so and always run natively
+    program_test.prefer_bpf(false);
+    program_test.add_program("mpl_bubblegum", Bubblegum::id(), processor!(accept_all));
+    program_test.add_program("spl_account_compression", spl_account_compression::ID, processor!(accept_all));
+    program_test.add_program("spl_noop", spl_account_compression::Noop::id(), processor!(accept_all));
+    program_test.add_program(
+        "mpl_token_metadata",
+        agent_registry::TOKEN_METADATA_PROGRAM_ID,
+        processor!(accept_all),
+    );
+    program_test
+}
+
+pub struct ArmourTest {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,  // Relationship config admin, influence mint authority, guardian and rate limiter authority, attester
+    pub influence_mint: Pubkey,
+    pub merkle_tree: Pubkey,  // Never created; the Bubblegum stub ignores it
+    pub collection_mint: Pubkey,  // Stand-in collection NFT, see `fake_collection`
+}
+
+impl ArmourTest {
+    // Start the validator and set up the guardian (unpaused), the rate
+    // limiter (no limits), attestation (admin attests), the registry config,
+    // state and agent collection (admin is admin and moderator), the influence
+    // mint, the relationship program's config, stats and hook registry, and
+    // analytics (daily epochs)
+    pub async fn start() -> Self {
+        Self::start_with(program_test()).await
+    }
+
+    pub async fn start_with(program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        let admin = clone_keypair(&context.payer);
+        let mut test = Self {
+            context,
+            admin,
+            influence_mint: Pubkey::default(),
+            merkle_tree: Pubkey::new_unique(),
+            collection_mint: Pubkey::new_unique(),
+        };
+        test.fake_collection();
+
+        let mint = Keypair::new();
+        test.influence_mint = mint.pubkey();
+        let admin = test.admin.pubkey();
+        let config = relationship_cpi::pda::find_config_address().0;
+        let setup = [
+            Instruction {
+                program_id: guardian::ID,
+                accounts: guardian::accounts::InitializeGuardian {
+                    authority: admin,
+                    guardian: relationship_cpi::pda::find_guardian_address().0,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: guardian::instruction::InitializeGuardian { pauser: admin }.data(),
+            },
+            Instruction {
+                program_id: rate_limiter::ID,
+                accounts: rate_limiter::accounts::InitializeRateLimiter {
+                    authority: admin,
+                    config: agent_registry_cpi::pda::find_rate_limiter_config_address().0,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: rate_limiter::instruction::InitializeRateLimiter {}.data(),
+            },
+            Instruction {
+                program_id: attestation::ID,
+                accounts: attestation::accounts::InitializeAttestation {
+                    authority: admin,
+                    config: Pubkey::find_program_address(&[attestation::ATTESTATION_CONFIG_SEED], &attestation::ID).0,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: attestation::instruction::InitializeAttestation { attester: admin }.data(),
+            },
+            agent_registry_cpi::builders::build(
+                agent_registry_cpi::accounts::InitializeRegistryConfig {
+                    admin,
+                    registry_config: agent_registry_cpi::pda::find_registry_config_address().0,
+                    system_program: system_program::ID,
+                },
+                agent_registry_cpi::instruction::InitializeRegistryConfig { moderator: admin },
+            ),
+            agent_registry_cpi::builders::initialize_registry_state(admin),
+            agent_registry_cpi::builders::initialize_compatibility_config(
+                admin,
+                agent_registry_cpi::CompatibilityWeights::default(),
+            ),
+            agent_registry_cpi::builders::initialize_collection(admin, test.collection_mint),
+            influence_cpi::builders::build(
+                influence_cpi::accounts::InitializeMint {
+                    authority: admin,
+                    mint: mint.pubkey(),
+                    mint_config: influence_cpi::pda::find_mint_config_address(&mint.pubkey()).0,
+                    token_program: anchor_spl::token::ID,
+                    system_program: system_program::ID,
+                    rent: sysvar::rent::ID,
+                },
+                influence_cpi::instruction::InitializeMint {
+                    name: "Influence".to_string(),
+                    symbol: "INF".to_string(),
+                    uri: String::new(),
+                },
+            ),
+            relationship_cpi::builders::build(
+                relationship_cpi::accounts::InitializeConfig {
+                    admin,
+                    config,
+                    influence_mint: mint.pubkey(),
+                    rewards_vault: Pubkey::find_program_address(&[b"rewards_vault"], &relationship::ID).0,
+                    token_program: anchor_spl::token::ID,
+                    system_program: system_program::ID,
+                    rent: sysvar::rent::ID,
+                    event_authority: relationship_cpi::pda::find_event_authority().0,
+                    program: relationship::ID,
+                },
+                relationship_cpi::instruction::InitializeConfig {
+                    oracle: admin,
+                    treasury: admin,
+                    date_params: DateParams::default(),
+                },
+            ),
+            relationship_cpi::builders::build(
+                relationship_cpi::accounts::InitializeStats {
+                    admin,
+                    config,
+                    stats: relationship_cpi::pda::find_stats_address().0,
+                    system_program: system_program::ID,
+                },
+                relationship_cpi::instruction::InitializeStats {},
+            ),
+            relationship_cpi::builders::build(
+                relationship_cpi::accounts::InitializeHookRegistry {
+                    admin,
+                    config,
+                    hook_registry: relationship_cpi::pda::find_hook_registry_address().0,
+                    system_program: system_program::ID,
+                },
+                relationship_cpi::instruction::InitializeHookRegistry {},
+            ),
+            Instruction {
+                program_id: analytics::ID,
+                accounts: analytics::accounts::InitializeAnalytics {
+                    authority: admin,
+                    state: agent_registry_cpi::pda::find_analytics_state_address().0,
+                    influence_mint: mint.pubkey(),
+                    relationship_stats: relationship_cpi::pda::find_stats_address().0,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: analytics::instruction::InitializeAnalytics { epoch_length: 86_400 }.data(),
+            },
+        ];
+        test.process(&setup, &[&mint]).await.expect("fixture setup failed");
+        test
+    }
+
+    // Send `instructions` paid by the payer, also signed by `signers`
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers).await?;
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    // Compute units the transaction would consume, measured by simulation.
+    // Only meaningful with `bpf_program_test`; native programs are not metered.
+    pub async fn compute_units(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<u64, BanksClientError> {
+        let transaction = self.transaction(instructions, signers).await?;
+        let simulation = self.context.banks_client.simulate_transaction(transaction).await?;
+        if let Some(Err(err)) = simulation.result {
+            return Err(err.into());
+        }
+        Ok(simulation.simulation_details.map_or(0, |details| details.units_consumed))
+    }
+
+    async fn transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Transaction, BanksClientError> {
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let mut all: Vec<&Keypair> = vec![&self.context.payer];
+        all.extend_from_slice(signers);
+        Ok(Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all,
+            blockhash,
+        ))
+    }
+
+    // Builders
+
+    pub async fn funded_keypair(&mut self, lamports: u64) -> Keypair {
+        let keypair = Keypair::new();
+        let transfer = system_instruction::transfer(&self.context.payer.pubkey(), &keypair.pubkey(), lamports);
+        self.process(&[transfer], &[]).await.expect("funding failed");
+        keypair
+    }
+
+    pub async fn create_agent(&mut self, owner: &Keypair, name: &str, traits: PersonalityTraits) -> Pubkey {
+        let instruction = armour_sdk::register_agent(
+            owner.pubkey(),
+            name.to_string(),
+            format!("https://example.com/{}.json", name),
+            traits,
+            self.merkle_tree,
+            self.collection_mint,
+            None,
+        );
+        self.process(&[instruction], &[owner]).await.expect("agent registration failed");
+        agent_registry_cpi::pda::find_agent_address(&owner.pubkey(), name).0
+    }
+
+    // Mint influence to `recipient`'s associated token account, returning it
+    pub async fn mint_influence(&mut self, recipient: &Pubkey, amount: u64) -> Pubkey {
+        let timestamp = self.clock().await.unix_timestamp;
+        let instruction = influence_cpi::builders::mint_tokens(
+            self.admin.pubkey(),
+            self.influence_mint,
+            *recipient,
+            amount,
+            "test".to_string(),
+            timestamp,
+        );
+        let admin = clone_keypair(&self.admin);
+        self.process(&[instruction], &[&admin]).await.expect("influence mint failed");
+        get_associated_token_address(recipient, &self.influence_mint)
+    }
+
+    // Create a relationship between two agents in either order, scored with
+    // the registry's compatibility function
+    pub async fn create_relationship(
+        &mut self,
+        authority: &Keypair,
+        agent_a: Pubkey,
+        agent_b: Pubkey,
+        relationship_type: RelationshipType,
+    ) -> Pubkey {
+        let (agent_one, agent_two) = armour_sdk::canonical_pair(agent_a, agent_b);
+        let one = self.agent(&agent_one).await;
+        let two = self.agent(&agent_two).await;
+        let score = agent_registry::calculate_compatibility_score(&one.personality_traits, &two.personality_traits);
+        let instruction = relationship_cpi::builders::create_relationship(
+            authority.pubkey(),
+            agent_one,
+            agent_two,
+            None,
+            relationship_type,
+            score,
+        );
+        self.process(&[instruction], &[authority]).await.expect("relationship creation failed");
+        relationship_cpi::pda::find_relationship_address(&agent_one, &agent_two).0
+    }
+
+    pub async fn record_interaction(
+        &mut self,
+        authority: &Keypair,
+        relationship: Pubkey,
+        interaction_type: InteractionType,
+        interaction_data: &str,
+    ) -> Result<(), BanksClientError> {
+        let relationship_data = self.relationship(&relationship).await;
+        let nonce: relationship::InteractionNonce = self
+            .account(&relationship_cpi::pda::find_interaction_nonce_address(&relationship).0)
+            .await;
+        let instruction = relationship_cpi::builders::record_interaction(
+            authority.pubkey(),
+            authority.pubkey(),
+            relationship,
+            relationship_data.agent_one,
+            relationship_data.agent_two,
+            nonce.next_index,
+            interaction_type,
+            interaction_data.to_string(),
+            None,
+            None,
+        );
+        self.process(&[instruction], &[authority]).await
+    }
+
+    // Pause or unpause `program`, or every program when None, as the guardian authority
+    pub async fn set_paused(&mut self, program: Option<Pubkey>, paused: bool) {
+        let instruction = Instruction {
+            program_id: guardian::ID,
+            accounts: guardian::accounts::SetPaused {
+                signer: self.admin.pubkey(),
+                guardian: relationship_cpi::pda::find_guardian_address().0,
+            }
+            .to_account_metas(None),
+            data: guardian::instruction::SetPaused { program, paused }.data(),
+        };
+        let admin = clone_keypair(&self.admin);
+        self.process(&[instruction], &[&admin]).await.expect("guardian update failed");
+    }
+
+    // Limit how often each wallet may perform `action`, as the rate limiter authority
+    pub async fn set_action_limit(&mut self, action: RateLimitedAction, window: i64, max_actions: u16) {
+        let instruction = Instruction {
+            program_id: rate_limiter::ID,
+            accounts: rate_limiter::accounts::UpdateRateLimiter {
+                authority: self.admin.pubkey(),
+                config: agent_registry_cpi::pda::find_rate_limiter_config_address().0,
+            }
+            .to_account_metas(None),
+            data: rate_limiter::instruction::SetActionLimit {
+                action,
+                limit: ActionLimit { window, max_actions },
+            }
+            .data(),
+        };
+        let admin = clone_keypair(&self.admin);
+        self.process(&[instruction], &[&admin]).await.expect("rate limit update failed");
+    }
+
+    // Attest `wallet` as the attester, until `expires_at` (0 for no expiry)
+    pub async fn issue_credential(&mut self, wallet: &Pubkey, expires_at: i64) {
+        let instruction = Instruction {
+            program_id: attestation::ID,
+            accounts: attestation::accounts::IssueCredential {
+                attester: self.admin.pubkey(),
+                config: Pubkey::find_program_address(&[attestation::ATTESTATION_CONFIG_SEED], &attestation::ID).0,
+                wallet: *wallet,
+                credential: agent_registry_cpi::pda::find_credential_address(wallet).0,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: attestation::instruction::IssueCredential { expires_at }.data(),
+        };
+        let admin = clone_keypair(&self.admin);
+        self.process(&[instruction], &[&admin]).await.expect("credential issue failed");
+    }
+
+    pub async fn revoke_credential(&mut self, wallet: &Pubkey) {
+        let instruction = Instruction {
+            program_id: attestation::ID,
+            accounts: attestation::accounts::RevokeCredential {
+                attester: self.admin.pubkey(),
+                config: Pubkey::find_program_address(&[attestation::ATTESTATION_CONFIG_SEED], &attestation::ID).0,
+                credential: agent_registry_cpi::pda::find_credential_address(wallet).0,
+            }
+            .to_account_metas(None),
+            data: attestation::instruction::RevokeCredential {}.data(),
+        };
+        let admin = clone_keypair(&self.admin);
+        self.process(&[instruction], &[&admin]).await.expect("credential revocation failed");
+    }
+
+    // Close the current analytics epoch; the clock must be past its end
+    pub async fn close_epoch(&mut self) -> Result<(), BanksClientError> {
+        let state = agent_registry_cpi::pda::find_analytics_state_address().0;
+        let epoch = self.account::<analytics::AnalyticsState>(&state).await.current_epoch;
+        let instruction = Instruction {
+            program_id: analytics::ID,
+            accounts: analytics::accounts::CloseEpoch {
+                payer: self.context.payer.pubkey(),
+                state,
+                epoch_metrics: Pubkey::find_program_address(
+                    &[analytics::EPOCH_METRICS_SEED, &epoch.to_le_bytes()],
+                    &analytics::ID,
+                )
+                .0,
+                influence_mint: self.influence_mint,
+                relationship_stats: relationship_cpi::pda::find_stats_address().0,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: analytics::instruction::CloseEpoch {}.data(),
+        };
+        self.process(&[instruction], &[]).await
+    }
+
+    // State
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("banks client error")
+            .unwrap_or_else(|| panic!("account {} not found", address));
+        T::try_deserialize(&mut account.data.as_slice()).expect("account deserialization failed")
+    }
+
+    pub async fn zero_copy_account<T: ZeroCopy>(&mut self, address: &Pubkey) -> T {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .expect("banks client error")
+            .unwrap_or_else(|| panic!("account {} not found", address));
+        assert_eq!(&account.data[..8], &T::discriminator()[..], "account {} has the wrong type", address);
+        anchor_lang::__private::bytemuck::pod_read_unaligned(&account.data[8..8 + std::mem::size_of::<T>()])
+    }
+
+    pub async fn agent(&mut self, agent: &Pubkey) -> AgentData {
+        self.account(agent).await
+    }
+
+    pub async fn relationship(&mut self, relationship: &Pubkey) -> RelationshipData {
+        self.zero_copy_account(relationship).await
+    }
+
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        self.account::<TokenAccount>(token_account).await.amount
+    }
+
+    pub async fn account_exists(&mut self, address: &Pubkey) -> bool {
+        self.context.banks_client.get_account(*address).await.expect("banks client error").is_some()
+    }
+
+    // Write the collection NFT accounts the registry checks in
+    // `initialize_collection`: a one-of-one mint, and metadata and edition
+    // owned by Token Metadata with the registry's collection authority as
+    // update authority
+    fn fake_collection(&mut self) {
+        let mut mint = vec![0u8; Mint::LEN];
+        Mint {
+            mint_authority: COption::None,
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }
+        .pack_into_slice(&mut mint);
+        let mut metadata = vec![4u8]; // Token Metadata's MetadataV1 key
+        metadata.extend_from_slice(agent_registry_cpi::pda::find_collection_authority().0.as_ref());
+        metadata.extend_from_slice(self.collection_mint.as_ref());
+        let accounts = [
+            (self.collection_mint, anchor_spl::token::ID, mint),
+            (
+                agent_registry_cpi::pda::find_collection_metadata_address(&self.collection_mint).0,
+                agent_registry::TOKEN_METADATA_PROGRAM_ID,
+                metadata,
+            ),
+            (
+                agent_registry_cpi::pda::find_collection_edition_address(&self.collection_mint).0,
+                agent_registry::TOKEN_METADATA_PROGRAM_ID,
+                vec![6u8], // MasterEditionV2 key
+            ),
+        ];
+        for (address, owner, data) in accounts {
+            let mut account = AccountSharedData::new(LAMPORTS_PER_SOL, data.len(), &owner);
+            account.set_data_from_slice(&data);
+            self.context.set_account(&address, &account);
+        }
+    }
+
+    // Clock
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar::<Clock>().await.expect("clock sysvar")
+    }
+
+    // Move the clock forward, e.g. past cooldowns or session expiry
+    pub async fn warp_forward(&mut self, seconds: i64) {
+        // Warp first: a new bank recomputes the clock, which would undo the shift
+        let slot = self.clock().await.slot + 1;
+        self.context.warp_to_slot(slot).expect("warp failed");
+        let mut clock = self.clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+}
+
+// Assertions
+
+// The custom error code a failed transaction ended with, if any
+pub fn custom_error_code(result: &Result<(), BanksClientError>) -> Option<u32> {
+    let err = match result {
+        Err(BanksClientError::TransactionError(err)) => err,
+        Err(BanksClientError::SimulationError { err, .. }) => err,
+        _ => return None,
+    };
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => Some(*code),
+        _ => None,
+    }
+}
+
+// Assert a transaction failed with a program's Anchor error, e.g.
+// `assert_anchor_error(&result, RelationshipError::Unauthorized)`
+pub fn assert_anchor_error(result: &Result<(), BanksClientError>, error: impl Into<u32>) {
+    let expected = error.into();
+    assert_eq!(
+        custom_error_code(result),
+        Some(expected),
+        "expected custom error {}, got {:?}",
+        expected,
+        result
+    );
+}
+
+pub fn assert_success(result: &Result<(), BanksClientError>) {
+    assert!(result.is_ok(), "transaction failed: {:?}", result);
+}
+
+// Helper functions
+fn clone_keypair(keypair: &Keypair) -> Keypair {
+    Keypair::from_bytes(&keypair.to_bytes()).expect("valid keypair")
+}
@@ -0,0 +1,520 @@
+use agent_registry::{
+    AgentData, AgentError, AgentRegistration, Bubblegum, LegacyAgentData, LegacyMatchData, LevelParams, MatchData,
+    MatchEndReason, MatchQueue, PairState, PersonalityTraits, QueuePreferences, DEFAULT_MATCH_COOLDOWN,
+    LEGACY_AGENT_SPACE, LEGACY_MATCH_SPACE, MATCH_CLOSE_GRACE_PERIOD, MAX_QUEUED_PER_OWNER, QUEUE_ENTRY_TTL,
+};
+use agent_registry_cpi::{builders, canonical_pair, pda, CompatibilityAlgorithm, ReferralEdge};
+use anchor_lang::{AnchorSerialize, Discriminator, Id};
+use referrals_cpi::{ReferrerRewards, ValueEvent};
+use armour_test_utils::{assert_anchor_error, assert_success, ArmourTest, LAMPORTS_PER_SOL};
+use solana_program_test::BanksClientError;
+use solana_sdk::account::{AccountSharedData, WritableAccount};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+
+struct Pair {
+    alice: Keypair,
+    bob: Keypair,
+    agent_one: Pubkey,  // Alice's agent, which proposes
+    agent_two: Pubkey,
+}
+
+async fn pair(test: &mut ArmourTest) -> Pair {
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let agent_one = test.create_agent(&alice, "alice", PersonalityTraits::default()).await;
+    let agent_two = test.create_agent(&bob, "bob", PersonalityTraits::default()).await;
+    Pair { alice, bob, agent_one, agent_two }
+}
+
+// Propose, score and accept the pair's next match
+async fn match_pair(test: &mut ArmourTest, pair: &Pair, match_nonce: u64) {
+    match_pair_with_referrals(test, pair, match_nonce, [None, None]).await;
+}
+
+async fn match_pair_with_referrals(
+    test: &mut ArmourTest,
+    pair: &Pair,
+    match_nonce: u64,
+    referrals: [Option<ReferralEdge>; 2],
+) {
+    let (lower, higher) = canonical_pair(pair.agent_one, pair.agent_two);
+    let payer = test.context.payer.pubkey();
+    let propose = builders::propose_match(pair.alice.pubkey(), pair.agent_one, pair.agent_two);
+    let store = builders::store_compatibility(payer, lower, higher, CompatibilityAlgorithm::Heuristic, false);
+    let result = test.process(&[propose, store], &[&pair.alice]).await;
+    assert_success(&result);
+    let accept = builders::accept_match(
+        pair.bob.pubkey(),
+        pair.alice.pubkey(),
+        pair.agent_one,
+        pair.agent_two,
+        match_nonce,
+        false,
+        referrals,
+    );
+    let result = test.process(&[accept], &[&pair.bob]).await;
+    assert_success(&result);
+}
+
+#[tokio::test]
+async fn close_match_keeps_match_count() {
+    let mut test = ArmourTest::start().await;
+    let pair = pair(&mut test).await;
+    match_pair(&mut test, &pair, 0).await;
+
+    let dissolve =
+        builders::dissolve_match(pair.alice.pubkey(), pair.agent_one, pair.agent_two, 0, MatchEndReason::Mutual);
+    let result = test.process(&[dissolve], &[&pair.alice]).await;
+    assert_success(&result);
+    let match_data = pda::find_match_address(&pair.agent_one, &pair.agent_two, 0).0;
+    let rent_payer = test.account::<MatchData>(&match_data).await.rent_payer;
+
+    let close = builders::close_match(pair.agent_one, pair.agent_two, 0, rent_payer);
+    let result = test.process(&[close.clone()], &[]).await;
+    assert_anchor_error(&result, AgentError::MatchGracePeriod);
+
+    test.warp_forward(MATCH_CLOSE_GRACE_PERIOD).await;
+    let result = test.process(&[close], &[]).await;
+    assert_success(&result);
+    assert!(!test.account_exists(&match_data).await);
+    for agent in [pair.agent_one, pair.agent_two] {
+        let agent = test.agent(&agent).await;
+        assert_eq!(agent.match_count, 1);
+        assert_eq!(agent.closed_match_count, 1);
+    }
+}
+
+// Level config with the fixture admin as XP authority
+async fn level_config(test: &mut ArmourTest) {
+    let admin = test.admin.pubkey();
+    let params = LevelParams {
+        xp_authority: admin,
+        match_xp: 10,
+        interaction_xp: 1,
+        thresholds: vec![100, 1_000],
+    };
+    let result = test.process(&[builders::initialize_level_config(admin, params)], &[]).await;
+    assert_success(&result);
+}
+
+#[tokio::test]
+async fn only_the_xp_authority_grants_xp() {
+    let mut test = ArmourTest::start().await;
+    level_config(&mut test).await;
+    let pair = pair(&mut test).await;
+    let delegate = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let set_delegate = builders::set_delegate(pair.alice.pubkey(), pair.agent_one, delegate.pubkey());
+    let result = test.process(&[set_delegate], &[&pair.alice]).await;
+    assert_success(&result);
+
+    for signer in [&pair.alice, &delegate] {
+        let grant = builders::grant_xp(signer.pubkey(), pair.agent_one, 1_000);
+        let result = test.process(&[grant], &[signer]).await;
+        assert_anchor_error(&result, AgentError::Unauthorized);
+    }
+
+    let grant = builders::grant_xp(test.admin.pubkey(), pair.agent_one, 100);
+    let result = test.process(&[grant], &[]).await;
+    assert_success(&result);
+    let agent = test.agent(&pair.agent_one).await;
+    assert_eq!((agent.xp, agent.level), (100, 1));
+}
+
+#[tokio::test]
+async fn recorded_activity_earns_no_xp() {
+    let mut test = ArmourTest::start().await;
+    level_config(&mut test).await;
+    let pair = pair(&mut test).await;
+
+    let activity = builders::record_agent_activity(pair.alice.pubkey(), pair.agent_one, u32::MAX);
+    let result = test.process(&[activity], &[&pair.alice]).await;
+    assert_success(&result);
+    let agent = test.agent(&pair.agent_one).await;
+    assert_eq!(agent.interaction_count, u32::MAX);
+    assert_eq!((agent.xp, agent.level), (0, 0));
+}
+
+fn register_batch(test: &ArmourTest, owner: &Pubkey, names: &[&str]) -> Instruction {
+    let mut instruction = builders::build(
+        agent_registry_cpi::accounts::RegisterAgentsBatch {
+            owner: *owner,
+            registry_state: pda::find_registry_state_address().0,
+            tree_authority: Pubkey::find_program_address(&[test.merkle_tree.as_ref()], &Bubblegum::id()).0,
+            merkle_tree: test.merkle_tree,
+            bubblegum_program: Bubblegum::id(),
+            compression_program: spl_account_compression::ID,
+            system_program: solana_sdk::system_program::ID,
+            guardian: pda::find_guardian_address().0,
+            rate_limiter_config: pda::find_rate_limiter_config_address().0,
+            wallet_quota: pda::find_wallet_quota_address(owner).0,
+            rate_limiter_program: rate_limiter::ID,
+            credential: Some(pda::find_credential_address(owner).0),
+            analytics_hook_authority: pda::find_analytics_hook_authority().0,
+            analytics_state: pda::find_analytics_state_address().0,
+            analytics_program: agent_registry::ANALYTICS_PROGRAM_ID,
+            collection: builders::collection_accounts(test.collection_mint),
+        },
+        agent_registry_cpi::instruction::RegisterAgentsBatch {
+            registrations: names
+                .iter()
+                .map(|name| AgentRegistration {
+                    name: name.to_string(),
+                    metadata_uri: format!("https://example.com/{}.json", name),
+                    personality_traits: PersonalityTraits::default(),
+                })
+                .collect(),
+        },
+    );
+    instruction.accounts.extend(
+        names
+            .iter()
+            .map(|name| AccountMeta::new(pda::find_agent_address(owner, name).0, false)),
+    );
+    instruction
+}
+
+#[tokio::test]
+async fn batch_registration_survives_prefunded_addresses() {
+    let mut test = ArmourTest::start().await;
+    let owner = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let griefer = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let carol = pda::find_agent_address(&owner.pubkey(), "carol").0;
+    let transfer = system_instruction::transfer(&griefer.pubkey(), &carol, 1_000_000);
+    let result = test.process(&[transfer], &[&griefer]).await;
+    assert_success(&result);
+
+    let register = register_batch(&test, &owner.pubkey(), &["carol", "dave"]);
+    let result = test.process(&[register], &[&owner]).await;
+    assert_success(&result);
+    assert_eq!(test.agent(&carol).await.name, "carol");
+
+    // An existing agent is still rejected
+    let register = register_batch(&test, &owner.pubkey(), &["carol"]);
+    let result = test.process(&[register], &[&owner]).await;
+    assert_anchor_error(&result, AgentError::AgentAlreadyExists);
+}
+
+async fn enter_queue(test: &mut ArmourTest, pair: &Pair) {
+    let preferences = QueuePreferences { min_score: 0, min_level: 0, max_level: u16::MAX };
+    let enter_one = builders::enter_queue(pair.alice.pubkey(), pair.agent_one, preferences.clone());
+    let enter_two = builders::enter_queue(pair.bob.pubkey(), pair.agent_two, preferences);
+    let result = test.process(&[enter_one, enter_two], &[&pair.alice, &pair.bob]).await;
+    assert_success(&result);
+}
+
+async fn queued(test: &mut ArmourTest) -> Vec<Pubkey> {
+    let queue: MatchQueue = test.account(&pda::find_match_queue_address().0).await;
+    queue.entries.iter().map(|entry| entry.agent).collect()
+}
+
+// Process the queue, trying only the pair
+async fn crank(test: &mut ArmourTest, pair: &Pair, match_nonce: u64) -> Result<(), BanksClientError> {
+    let payer = test.context.payer.pubkey();
+    let queued = queued(test).await;
+    let process = builders::process_queue(
+        payer,
+        &queued,
+        &[(pair.agent_one, pair.agent_two)],
+        match_nonce,
+        false,
+        false,
+    );
+    test.process(&[process], &[]).await
+}
+
+async fn start_with_queue() -> (ArmourTest, Pair) {
+    let mut test = ArmourTest::start().await;
+    let admin = test.admin.pubkey();
+    let result = test.process(&[builders::initialize_match_queue(admin)], &[]).await;
+    assert_success(&result);
+    let pair = pair(&mut test).await;
+    (test, pair)
+}
+
+#[tokio::test]
+async fn queue_skips_pairs_in_cooldown() {
+    let (mut test, pair) = start_with_queue().await;
+    enter_queue(&mut test, &pair).await;
+    assert_success(&crank(&mut test, &pair, 0).await);
+    assert!(queued(&mut test).await.is_empty());
+    let dissolve =
+        builders::dissolve_match(pair.alice.pubkey(), pair.agent_one, pair.agent_two, 0, MatchEndReason::Mutual);
+    let result = test.process(&[dissolve], &[&pair.alice]).await;
+    assert_success(&result);
+
+    // The pair is in its cooldown: the crank skips it and both stay queued
+    enter_queue(&mut test, &pair).await;
+    assert_success(&crank(&mut test, &pair, 1).await);
+    assert_eq!(queued(&mut test).await, vec![pair.agent_one, pair.agent_two]);
+    assert!(!test.account_exists(&pda::find_match_address(&pair.agent_one, &pair.agent_two, 1).0).await);
+
+    test.warp_forward(DEFAULT_MATCH_COOLDOWN).await;
+    assert_success(&crank(&mut test, &pair, 1).await);
+    assert!(queued(&mut test).await.is_empty());
+    let pair_state: PairState = test.account(&pda::find_pair_state_address(&pair.agent_one, &pair.agent_two).0).await;
+    assert_eq!(pair_state.match_nonce, 2);
+    for agent in [pair.agent_one, pair.agent_two] {
+        assert_eq!(test.agent(&agent).await.match_count, 2);
+    }
+}
+
+#[tokio::test]
+async fn queue_matches_prefunded_addresses() {
+    let (mut test, pair) = start_with_queue().await;
+    enter_queue(&mut test, &pair).await;
+    let griefer = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let pair_state = pda::find_pair_state_address(&pair.agent_one, &pair.agent_two).0;
+    let match_data = pda::find_match_address(&pair.agent_one, &pair.agent_two, 0).0;
+    let transfers = [
+        system_instruction::transfer(&griefer.pubkey(), &pair_state, 1_000_000),
+        system_instruction::transfer(&griefer.pubkey(), &match_data, 1_000_000),
+    ];
+    let result = test.process(&transfers, &[&griefer]).await;
+    assert_success(&result);
+
+    assert_success(&crank(&mut test, &pair, 0).await);
+    assert!(queued(&mut test).await.is_empty());
+    let match_data: MatchData = test.account(&match_data).await;
+    assert!(match_data.is_active);
+    assert_eq!(match_data.rent_payer, test.context.payer.pubkey());
+}
+
+#[tokio::test]
+async fn queue_caps_agents_per_owner() {
+    let (mut test, pair) = start_with_queue().await;
+    let preferences = QueuePreferences { min_score: 0, min_level: 0, max_level: u16::MAX };
+    let mut agents = vec![pair.agent_one];
+    for index in 1..=MAX_QUEUED_PER_OWNER {
+        let name = format!("alice-{index}");
+        agents.push(test.create_agent(&pair.alice, &name, PersonalityTraits::default()).await);
+    }
+    let enter: Vec<_> = agents[..MAX_QUEUED_PER_OWNER]
+        .iter()
+        .map(|agent| builders::enter_queue(pair.alice.pubkey(), *agent, preferences.clone()))
+        .collect();
+    let result = test.process(&enter, &[&pair.alice]).await;
+    assert_success(&result);
+
+    let enter = builders::enter_queue(pair.alice.pubkey(), agents[MAX_QUEUED_PER_OWNER], preferences);
+    let result = test.process(&[enter], &[&pair.alice]).await;
+    assert_anchor_error(&result, AgentError::OwnerQueueLimit);
+}
+
+#[tokio::test]
+async fn queue_evicts_expired_and_inactive_entries() {
+    let (mut test, pair) = start_with_queue().await;
+    enter_queue(&mut test, &pair).await;
+    let deactivate = builders::update_agent_status(pair.bob.pubkey(), pair.agent_two, None, false);
+    let result = test.process(&[deactivate], &[&pair.bob]).await;
+    assert_success(&result);
+
+    // Bob's agent is inactive: the crank drops it without matching
+    assert_success(&crank(&mut test, &pair, 0).await);
+    assert_eq!(queued(&mut test).await, vec![pair.agent_one]);
+    assert!(!test.account_exists(&pda::find_match_address(&pair.agent_one, &pair.agent_two, 0).0).await);
+
+    // Alice's entry expires and is evicted by the next entrant
+    test.warp_forward(QUEUE_ENTRY_TTL).await;
+    let carol = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let agent = test.create_agent(&carol, "carol", PersonalityTraits::default()).await;
+    let preferences = QueuePreferences { min_score: 0, min_level: 0, max_level: u16::MAX };
+    let result = test.process(&[builders::enter_queue(carol.pubkey(), agent, preferences)], &[&carol]).await;
+    assert_success(&result);
+    assert_eq!(queued(&mut test).await, vec![agent]);
+}
+
+#[tokio::test]
+async fn accept_match_rejects_deactivated_agents() {
+    let mut test = ArmourTest::start().await;
+    let pair = pair(&mut test).await;
+    let (lower, higher) = canonical_pair(pair.agent_one, pair.agent_two);
+    let payer = test.context.payer.pubkey();
+    let propose = builders::propose_match(pair.alice.pubkey(), pair.agent_one, pair.agent_two);
+    let store = builders::store_compatibility(payer, lower, higher, CompatibilityAlgorithm::Heuristic, false);
+    let deactivate = builders::update_agent_status(pair.alice.pubkey(), pair.agent_one, None, false);
+    let result = test.process(&[propose, store, deactivate], &[&pair.alice]).await;
+    assert_success(&result);
+
+    let accept = builders::accept_match(
+        pair.bob.pubkey(),
+        pair.alice.pubkey(),
+        pair.agent_one,
+        pair.agent_two,
+        0,
+        false,
+        [None, None],
+    );
+    let result = test.process(&[accept], &[&pair.bob]).await;
+    assert_anchor_error(&result, AgentError::AgentInactive);
+}
+
+#[tokio::test]
+async fn transfer_needs_the_new_owner_signature() {
+    let mut test = ArmourTest::start().await;
+    let pair = pair(&mut test).await;
+
+    let mut transfer = builders::transfer_ownership(pair.alice.pubkey(), pair.bob.pubkey(), pair.agent_one);
+    transfer.accounts[1].is_signer = false;
+    let result = test.process(&[transfer], &[&pair.alice]).await;
+    assert_anchor_error(&result, AgentError::NewOwnerMustSign);
+
+    let transfer = builders::transfer_ownership(pair.alice.pubkey(), pair.bob.pubkey(), pair.agent_one);
+    let result = test.process(&[transfer], &[&pair.alice, &pair.bob]).await;
+    assert_success(&result);
+    let agent = test.agent(&pair.agent_one).await;
+    assert_eq!((agent.owner, agent.transfer_nonce), (pair.bob.pubkey(), 1));
+}
+
+#[tokio::test]
+async fn pending_transfer_is_stale_after_a_round_trip() {
+    let mut test = ArmourTest::start().await;
+    let pair = pair(&mut test).await;
+    let carol = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let propose = builders::propose_ownership_transfer(pair.alice.pubkey(), pair.agent_one, carol.pubkey());
+    let result = test.process(&[propose], &[&pair.alice]).await;
+    assert_success(&result);
+
+    // The agent goes to Bob and comes back, as through a listing and delisting
+    let there = builders::transfer_ownership(pair.alice.pubkey(), pair.bob.pubkey(), pair.agent_one);
+    let back = builders::transfer_ownership(pair.bob.pubkey(), pair.alice.pubkey(), pair.agent_one);
+    let result = test.process(&[there, back], &[&pair.alice, &pair.bob]).await;
+    assert_success(&result);
+    assert_eq!(test.agent(&pair.agent_one).await.owner, pair.alice.pubkey());
+
+    let accept = builders::accept_ownership(carol.pubkey(), pair.alice.pubkey(), pair.agent_one);
+    let result = test.process(&[accept], &[&carol]).await;
+    assert_anchor_error(&result, AgentError::StaleTransfer);
+}
+
+// Write an account as the original registry stored it
+fn write_legacy(test: &mut ArmourTest, address: Pubkey, discriminator: [u8; 8], legacy: impl AnchorSerialize, space: usize) {
+    let mut data = discriminator.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    let mut account = AccountSharedData::new(LAMPORTS_PER_SOL / 100, data.len(), &agent_registry::ID);
+    account.set_data_from_slice(&data);
+    test.context.set_account(&address, &account);
+}
+
+#[tokio::test]
+async fn migrates_original_agents_and_matches() {
+    let mut test = ArmourTest::start().await;
+    let pair = pair(&mut test).await;
+    let carol = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let agent = pda::find_agent_address(&carol.pubkey(), "carol").0;
+    let traits = PersonalityTraits { openness: 80, ..PersonalityTraits::default() };
+    let legacy_agent = LegacyAgentData {
+        owner: carol.pubkey(),
+        name: "carol".to_string(),
+        metadata_uri: "https://example.com/carol.json".to_string(),
+        is_active: true,
+        personality_traits: traits.clone(),
+        creation_date: 1_650_000_000,
+        match_count: 3,
+        interaction_count: 12,
+        last_active: 1_660_000_000,
+    };
+    write_legacy(&mut test, agent, AgentData::DISCRIMINATOR, legacy_agent, LEGACY_AGENT_SPACE);
+    let match_address = Pubkey::find_program_address(&[b"match", agent.as_ref(), pair.agent_two.as_ref()], &agent_registry::ID).0;
+    let legacy_match = LegacyMatchData {
+        agent_one: agent,
+        agent_two: pair.agent_two,
+        compatibility_score: 64,
+        match_date: 1_655_000_000,
+        is_active: false,
+    };
+    write_legacy(&mut test, match_address, MatchData::DISCRIMINATOR, legacy_match, LEGACY_MATCH_SPACE);
+
+    let payer = test.context.payer.pubkey();
+    let result = test
+        .process(
+            &[builders::migrate_agent(payer, agent), builders::migrate_match(payer, match_address, agent)],
+            &[],
+        )
+        .await;
+    assert_success(&result);
+
+    let migrated = test.agent(&agent).await;
+    assert_eq!((migrated.owner, migrated.name.as_str()), (carol.pubkey(), "carol"));
+    assert_eq!((migrated.match_count, migrated.interaction_count), (3, 12));
+    assert_eq!(migrated.personality_traits, traits);
+    // The two agents registered by the test come first
+    assert_eq!(migrated.agent_index, 2);
+    assert!(!migrated.is_frozen && migrated.custom_traits.is_empty());
+
+    let migrated: MatchData = test.account(&match_address).await;
+    assert_eq!(migrated.rent_payer, carol.pubkey());
+    assert_eq!(migrated.ended_at, 1_655_000_000);
+    assert_eq!(migrated.compatibility_score, 64);
+
+    // Migrated accounts are no longer on the original layout
+    let result = test.process(&[builders::migrate_agent(payer, agent)], &[]).await;
+    assert_anchor_error(&result, AgentError::NotLegacyAccount);
+}
+
+// Referrals paying `reward` for registrations and first matches, with the
+// registry allowed to report them
+async fn start_with_referrals(reward: u64) -> ArmourTest {
+    let mut test = ArmourTest::start().await;
+    let admin = test.admin.pubkey();
+    let setup = [
+        referrals_cpi::builders::initialize_referrals(admin, test.influence_mint),
+        referrals_cpi::builders::set_commission_rule(admin, ValueEvent::Registration, reward),
+        referrals_cpi::builders::set_commission_rule(admin, ValueEvent::FirstMatch, reward),
+        referrals_cpi::builders::add_reporter(admin, pda::find_referral_reporter_address().0),
+    ];
+    test.process(&setup, &[]).await.expect("referrals setup failed");
+    test
+}
+
+async fn claimable(test: &mut ArmourTest, referrer: &Pubkey) -> u64 {
+    let rewards: ReferrerRewards = test
+        .account(&referrals_cpi::pda::find_referrer_rewards_address(referrer).0)
+        .await;
+    rewards.claimable
+}
+
+#[tokio::test]
+async fn registration_and_first_match_credit_the_referrer() {
+    let mut test = start_with_referrals(100).await;
+    let referrer = Pubkey::new_unique();
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let refer = referrals_cpi::builders::register_referral(alice.pubkey(), referrer);
+    let result = test.process(&[refer], &[&alice]).await;
+    assert_success(&result);
+
+    // Bob was never referred, so passing the hook for him credits nothing;
+    // Alice's second registration is not her first
+    for (owner, name) in [(&alice, "alice"), (&bob, "bob"), (&alice, "alice-2")] {
+        let register = armour_sdk::register_agent(
+            owner.pubkey(),
+            name.to_string(),
+            format!("https://example.com/{}.json", name),
+            PersonalityTraits::default(),
+            test.merkle_tree,
+            test.collection_mint,
+            Some(referrer),
+        );
+        let result = test.process(&[register], &[owner]).await;
+        assert_success(&result);
+    }
+    assert_eq!(claimable(&mut test, &referrer).await, 100);
+
+    let pair = Pair {
+        agent_one: pda::find_agent_address(&alice.pubkey(), "alice").0,
+        agent_two: pda::find_agent_address(&bob.pubkey(), "bob").0,
+        alice,
+        bob,
+    };
+    let alice_referral = ReferralEdge {
+        referee: pair.alice.pubkey(),
+        referrer,
+    };
+    match_pair_with_referrals(&mut test, &pair, 0, [Some(alice_referral), None]).await;
+    assert_eq!(claimable(&mut test, &referrer).await, 200);
+}
@@ -0,0 +1,223 @@
+use agent_registry::PersonalityTraits;
+use anchor_lang::{AnchorSerialize, Discriminator, InstructionData, ToAccountMetas};
+use armour_test_utils::{assert_success, ArmourTest, LAMPORTS_PER_SOL};
+use relationship::{LegacyRelationshipData, LEGACY_RELATIONSHIP_SPACE};
+use relationship_cpi::{
+    builders, pda, InteractionType, RelationshipData, RelationshipStatus, RelationshipType, Visibility,
+};
+use solana_sdk::account::{AccountSharedData, WritableAccount};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+
+// Write a relationship as the original program stored it, at its PDA
+fn write_legacy_relationship(test: &mut ArmourTest, legacy: &LegacyRelationshipData) -> Pubkey {
+    let address = pda::find_relationship_address(&legacy.agent_one, &legacy.agent_two).0;
+    let mut data = RelationshipData::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(LEGACY_RELATIONSHIP_SPACE, 0);
+    let mut account = AccountSharedData::new(LAMPORTS_PER_SOL / 100, data.len(), &relationship::ID);
+    account.set_data_from_slice(&data);
+    test.context.set_account(&address, &account);
+    address
+}
+
+fn legacy(agent_one: Pubkey, agent_two: Pubkey) -> LegacyRelationshipData {
+    LegacyRelationshipData {
+        agent_one,
+        agent_two,
+        relationship_type: RelationshipType::Dating,
+        compatibility_score: 72,
+        creation_date: 1_650_000_000,
+        last_interaction: 1_660_000_000,
+        interaction_count: 41,
+        status: RelationshipStatus::Paused,
+    }
+}
+
+#[tokio::test]
+async fn migrates_the_original_layout() {
+    let mut test = ArmourTest::start().await;
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let agent_a = test.create_agent(&alice, "alice", PersonalityTraits::default()).await;
+    let agent_b = test.create_agent(&bob, "bob", PersonalityTraits::default()).await;
+    let legacy = legacy(agent_a, agent_b);
+    let address = write_legacy_relationship(&mut test, &legacy);
+
+    let payer = test.context.payer.pubkey();
+    let migrate = builders::migrate_relationship_layout(payer, address, agent_a, agent_b);
+    let result = test.process(&[migrate], &[]).await;
+    assert_success(&result);
+
+    let migrated = test.relationship(&address).await;
+    assert_eq!((migrated.agent_one, migrated.agent_two), (agent_a, agent_b));
+    assert_eq!((migrated.owner_one, migrated.owner_two), (alice.pubkey(), bob.pubkey()));
+    assert_eq!(migrated.relationship_type().unwrap(), legacy.relationship_type);
+    assert_eq!(migrated.compatibility_score, legacy.compatibility_score);
+    assert_eq!(migrated.creation_date, legacy.creation_date);
+    assert_eq!(migrated.last_interaction, legacy.last_interaction);
+    assert_eq!(migrated.last_decay_at, legacy.last_interaction);
+    assert_eq!(migrated.interaction_count, legacy.interaction_count);
+    assert_eq!(migrated.status().unwrap(), legacy.status);
+    assert_eq!(migrated.visibility().unwrap(), Visibility::Public);
+    assert_eq!(migrated.matchmaker, Pubkey::default());
+    assert_eq!(migrated.layout_version, 1);
+}
+
+#[tokio::test]
+async fn migration_checks_the_agents() {
+    let mut test = ArmourTest::start().await;
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let agent_a = test.create_agent(&alice, "alice", PersonalityTraits::default()).await;
+    let agent_b = test.create_agent(&bob, "bob", PersonalityTraits::default()).await;
+    let address = write_legacy_relationship(&mut test, &legacy(agent_a, agent_b));
+
+    let payer = test.context.payer.pubkey();
+    let migrate = builders::migrate_relationship_layout(payer, address, agent_b, agent_a);
+    let result = test.process(&[migrate], &[]).await;
+    armour_test_utils::assert_anchor_error(&result, relationship::RelationshipError::AgentMismatch);
+}
+
+#[tokio::test]
+async fn merges_a_migrated_duplicate() {
+    let mut test = ArmourTest::start().await;
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let agent_a = test.create_agent(&alice, "alice", PersonalityTraits::default()).await;
+    let agent_b = test.create_agent(&bob, "bob", PersonalityTraits::default()).await;
+    let canonical = test.create_relationship(&alice, agent_a, agent_b, RelationshipType::Dating).await;
+    let (lower, higher) = armour_sdk::canonical_pair(agent_a, agent_b);
+    // Stored in reverse order, as before pairs were canonical
+    let duplicate = write_legacy_relationship(&mut test, &legacy(higher, lower));
+
+    let payer = test.context.payer.pubkey();
+    let migrate = builders::migrate_relationship_layout(payer, duplicate, higher, lower);
+    let merge = builders::build(
+        relationship_cpi::accounts::MergeRelationships {
+            authority: test.admin.pubkey(),
+            co_signer: None,
+            config: pda::find_config_address().0,
+            canonical,
+            duplicate,
+            stats: pda::find_stats_address().0,
+            guardian: pda::find_guardian_address().0,
+            event_authority: pda::find_event_authority().0,
+            program: relationship::ID,
+        },
+        relationship_cpi::instruction::MergeRelationships {},
+    );
+    let result = test.process(&[migrate, merge], &[]).await;
+    assert_success(&result);
+
+    assert!(!test.account_exists(&duplicate).await);
+    let merged = test.relationship(&canonical).await;
+    assert_eq!(merged.interaction_count, 41);
+    assert_eq!(merged.creation_date, 1_650_000_000);
+}
+
+async fn start_with_relationship() -> (ArmourTest, Keypair, Keypair, Pubkey) {
+    let mut test = ArmourTest::start().await;
+    let alice = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let bob = test.funded_keypair(10 * LAMPORTS_PER_SOL).await;
+    let agent_a = test.create_agent(&alice, "alice", PersonalityTraits::default()).await;
+    let agent_b = test.create_agent(&bob, "bob", PersonalityTraits::default()).await;
+    let relationship = test.create_relationship(&alice, agent_a, agent_b, RelationshipType::Dating).await;
+    (test, alice, bob, relationship)
+}
+
+fn record(relationship_data: &RelationshipData, signer: Pubkey, owner: Pubkey, session: Option<Pubkey>) -> Instruction {
+    let relationship = pda::find_relationship_address(&relationship_data.agent_one, &relationship_data.agent_two).0;
+    builders::record_interaction(
+        signer,
+        owner,
+        relationship,
+        relationship_data.agent_one,
+        relationship_data.agent_two,
+        0,
+        InteractionType::Conversation,
+        "hello".to_string(),
+        None,
+        session,
+    )
+}
+
+#[tokio::test]
+async fn only_owners_record_interactions() {
+    let (mut test, alice, _bob, relationship) = start_with_relationship().await;
+    let stranger = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let data = test.relationship(&relationship).await;
+
+    let result = test.process(&[record(&data, stranger.pubkey(), stranger.pubkey(), None)], &[&stranger]).await;
+    armour_test_utils::assert_anchor_error(&result, relationship::RelationshipError::Unauthorized);
+
+    let result = test.process(&[record(&data, alice.pubkey(), alice.pubkey(), None)], &[&alice]).await;
+    assert_success(&result);
+    assert_eq!(test.relationship(&relationship).await.interaction_count, 1);
+}
+
+#[tokio::test]
+async fn session_records_under_the_owner() {
+    let (mut test, alice, _bob, relationship) = start_with_relationship().await;
+    let session_signer = test.funded_keypair(LAMPORTS_PER_SOL).await;
+    let session = Pubkey::find_program_address(
+        &[b"session", alice.pubkey().as_ref(), session_signer.pubkey().as_ref()],
+        &sessions::ID,
+    )
+    .0;
+    let create_session = Instruction {
+        program_id: sessions::ID,
+        accounts: sessions::accounts::CreateSession {
+            owner: alice.pubkey(),
+            session,
+            tip_token_account: None,
+            token_program: None,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: sessions::instruction::CreateSession {
+            session_signer: session_signer.pubkey(),
+            scopes: sessions::SCOPE_RECORD_INTERACTIONS,
+            tip_limit: 0,
+            duration: 60 * 60,
+        }
+        .data(),
+    };
+    let result = test.process(&[create_session], &[&alice]).await;
+    assert_success(&result);
+    let data = test.relationship(&relationship).await;
+
+    let record = record(&data, session_signer.pubkey(), alice.pubkey(), Some(session));
+    let result = test.process(&[record], &[&session_signer]).await;
+    assert_success(&result);
+    assert_eq!(test.relationship(&relationship).await.interaction_count, 1);
+    // Rate limits were counted against Alice, not the session key
+    for (owner_account, session_account) in [
+        (
+            pda::find_recorder_rate_address(&alice.pubkey()).0,
+            pda::find_recorder_rate_address(&session_signer.pubkey()).0,
+        ),
+        (
+            pda::find_wallet_quota_address(&alice.pubkey()).0,
+            pda::find_wallet_quota_address(&session_signer.pubkey()).0,
+        ),
+    ] {
+        assert!(test.account_exists(&owner_account).await);
+        assert!(!test.account_exists(&session_account).await);
+    }
+}
+
+#[tokio::test]
+async fn status_changes_repeat_without_interactions() {
+    let (mut test, alice, bob, relationship) = start_with_relationship().await;
+    let data = test.relationship(&relationship).await;
+    for status in [RelationshipStatus::Paused, RelationshipStatus::Active] {
+        let update = builders::update_relationship_status(data.owner_one, data.owner_two, relationship, None, status.clone());
+        let result = test.process(&[update], &[&alice, &bob]).await;
+        assert_success(&result);
+        assert_eq!(test.relationship(&relationship).await.status().unwrap(), status);
+    }
+    assert_eq!(test.relationship(&relationship).await.interaction_count, 0);
+}
@@ -0,0 +1,264 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Attestat1onPr0graMxXxXxXxXxXxXxXxXxXxXxXxX");
+
+pub const ATTESTATION_CONFIG_SEED: &[u8] = b"attestation";
+pub const CREDENTIAL_SEED: &[u8] = b"credential";
+
+#[program]
+pub mod attestation {
+    use super::*;
+
+    // Create the config naming the attester, the key (typically a KYC
+    // provider's) that issues and revokes credentials
+    pub fn initialize_attestation(
+        ctx: Context<InitializeAttestation>,
+        attester: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.attester = attester;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit!(AttesterUpdated {
+            previous_attester: Pubkey::default(),
+            attester,
+        });
+
+        Ok(())
+    }
+
+    // Replace the attester. Credentials it already issued stay valid until
+    // they expire or are revoked.
+    pub fn set_attester(
+        ctx: Context<UpdateAttestation>,
+        attester: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_attester = config.attester;
+        config.attester = attester;
+
+        emit!(AttesterUpdated {
+            previous_attester,
+            attester,
+        });
+
+        Ok(())
+    }
+
+    // Hand the config to another key, such as the admin multisig authority
+    pub fn set_attestation_authority(
+        ctx: Context<UpdateAttestation>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+        config.authority = authority;
+
+        emit!(AttestationAuthorityUpdated {
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Attest `wallet`, valid until `expires_at` (0 for no expiry).
+    // Reissuing a revoked or expired credential reinstates it.
+    pub fn issue_credential(
+        ctx: Context<IssueCredential>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at == 0 || expires_at > now, AttestationError::InvalidExpiry);
+
+        let credential = &mut ctx.accounts.credential;
+        credential.wallet = ctx.accounts.wallet.key();
+        credential.issuer = ctx.accounts.attester.key();
+        credential.issued_at = now;
+        credential.expires_at = expires_at;
+        credential.revoked = false;
+        credential.bump = *ctx.bumps.get("credential").unwrap();
+
+        emit!(CredentialIssued {
+            wallet: credential.wallet,
+            issuer: credential.issuer,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    // Revoke a wallet's credential. The account is kept so the revocation
+    // stays on record.
+    pub fn revoke_credential(ctx: Context<RevokeCredential>) -> Result<()> {
+        let credential = &mut ctx.accounts.credential;
+        require!(!credential.revoked, AttestationError::CredentialRevoked);
+        credential.revoked = true;
+
+        emit!(CredentialRevoked {
+            wallet: credential.wallet,
+            attester: ctx.accounts.attester.key(),
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct AttestationConfig {
+    pub authority: Pubkey,  // Manages the config
+    pub attester: Pubkey,   // Issues and revokes credentials
+    pub bump: u8,
+}
+
+// One wallet's attestation; its PDA is seeded by the wallet, so a wallet
+// holds at most one
+#[account]
+pub struct Credential {
+    pub wallet: Pubkey,
+    pub issuer: Pubkey,    // Attester at issuance
+    pub issued_at: i64,
+    pub expires_at: i64,   // 0 for no expiry
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+impl Credential {
+    pub fn is_valid(&self, now: i64) -> bool {
+        !self.revoked && (self.expires_at == 0 || now < self.expires_at)
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeAttestation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<AttestationConfig>(),
+        seeds = [ATTESTATION_CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, AttestationConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAttestation<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ATTESTATION_CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority @ AttestationError::Unauthorized,
+    )]
+    pub config: Account<'info, AttestationConfig>,
+}
+
+#[derive(Accounts)]
+pub struct IssueCredential<'info> {
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    #[account(
+        seeds = [ATTESTATION_CONFIG_SEED],
+        bump = config.bump,
+        has_one = attester @ AttestationError::Unauthorized,
+    )]
+    pub config: Account<'info, AttestationConfig>,
+
+    /// CHECK: the wallet being attested; any key
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = attester,
+        space = 8 + std::mem::size_of::<Credential>(),
+        seeds = [CREDENTIAL_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub credential: Account<'info, Credential>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeCredential<'info> {
+    pub attester: Signer<'info>,
+
+    #[account(
+        seeds = [ATTESTATION_CONFIG_SEED],
+        bump = config.bump,
+        has_one = attester @ AttestationError::Unauthorized,
+    )]
+    pub config: Account<'info, AttestationConfig>,
+
+    #[account(
+        mut,
+        seeds = [CREDENTIAL_SEED, credential.wallet.as_ref()],
+        bump = credential.bump,
+    )]
+    pub credential: Account<'info, Credential>,
+}
+
+// Events
+#[event]
+pub struct CredentialIssued {
+    pub wallet: Pubkey,
+    pub issuer: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct CredentialRevoked {
+    pub wallet: Pubkey,
+    pub attester: Pubkey,
+}
+
+#[event]
+pub struct AttesterUpdated {
+    pub previous_attester: Pubkey,
+    pub attester: Pubkey,
+}
+
+#[event]
+pub struct AttestationAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum AttestationError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Credential expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("The credential has been revoked")]
+    CredentialRevoked,
+    #[msg("This action requires a valid attestation credential")]
+    CredentialRequired,
+}
+
+// Helper functions
+// Require `credential` to be a valid credential for `wallet`. Gated builds
+// of other programs call this with the optional credential account their
+// instructions take.
+pub fn require_credential(credential: Option<&AccountInfo>, wallet: &Pubkey) -> Result<()> {
+    let credential = credential.ok_or(AttestationError::CredentialRequired)?;
+    require_keys_eq!(*credential.owner, ID, AttestationError::CredentialRequired);
+    let data = Credential::try_deserialize(&mut &credential.try_borrow_data()?[..])?;
+    require_keys_eq!(data.wallet, *wallet, AttestationError::CredentialRequired);
+    require!(
+        data.is_valid(Clock::get()?.unix_timestamp),
+        AttestationError::CredentialRequired
+    );
+    Ok(())
+}
@@ -0,0 +1,801 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use agent_registry::program::AgentRegistry;
+use agent_registry::{AgentData, Bubblegum};
+use spl_account_compression::{program::SplAccountCompression, Noop};
+
+declare_id!("Auct10nPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_FEE_BPS: u16 = 1_000;  // 10%
+
+#[program]
+pub mod auction {
+    use super::*;
+
+    // Create the auction house config
+    pub fn initialize_auction_house(
+        ctx: Context<InitializeAuctionHouse>,
+        fee_bps: u16,
+        treasury: Pubkey,
+        anti_snipe_window: i64,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, AuctionError::InvalidFee);
+        require!(anti_snipe_window >= 0, AuctionError::InvalidSchedule);
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.treasury = treasury;
+        config.fee_bps = fee_bps;
+        config.anti_snipe_window = anti_snipe_window;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit!(AuctionHouseUpdated {
+            fee_bps,
+            treasury,
+            anti_snipe_window,
+        });
+
+        Ok(())
+    }
+
+    // Update the fee, treasury and anti-snipe window (admin)
+    pub fn update_auction_house(
+        ctx: Context<UpdateAuctionHouse>,
+        fee_bps: u16,
+        treasury: Pubkey,
+        anti_snipe_window: i64,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, AuctionError::InvalidFee);
+        require!(anti_snipe_window >= 0, AuctionError::InvalidSchedule);
+
+        let config = &mut ctx.accounts.config;
+        config.fee_bps = fee_bps;
+        config.treasury = treasury;
+        config.anti_snipe_window = anti_snipe_window;
+
+        emit!(AuctionHouseUpdated {
+            fee_bps,
+            treasury,
+            anti_snipe_window,
+        });
+
+        Ok(())
+    }
+
+    // Put an agent up for auction. The agent's cNFT and registry ownership are
+    // escrowed by the auction PDA until settlement or cancellation. Bubblegum
+    // proof accounts are passed as remaining accounts.
+    pub fn create_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateAuction<'info>>,
+        format: AuctionFormat,
+        start_time: i64,
+        end_time: i64,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        require!(
+            end_time > start_time && end_time > Clock::get()?.unix_timestamp,
+            AuctionError::InvalidSchedule
+        );
+        match &format {
+            AuctionFormat::English { reserve_price, min_increment } => require!(
+                *reserve_price > 0 && *min_increment > 0,
+                AuctionError::InvalidPrice
+            ),
+            AuctionFormat::Dutch { start_price, end_price } => require!(
+                *end_price > 0 && start_price > end_price,
+                AuctionError::InvalidPrice
+            ),
+        }
+        require!(!ctx.accounts.agent_data.is_frozen, AuctionError::AgentFrozen);
+
+        let auction_key = ctx.accounts.auction.key();
+        let agent_key = ctx.accounts.agent_data.key();
+        let auction_bump = *ctx.bumps.get("auction").unwrap();
+        let auction = &mut ctx.accounts.auction;
+        auction.seller = ctx.accounts.seller.key();
+        auction.agent = agent_key;
+        auction.merkle_tree = ctx.accounts.cnft_accounts.merkle_tree.key();
+        auction.format = format.clone();
+        auction.start_time = start_time;
+        auction.end_time = end_time;
+        auction.highest_bid = 0;
+        auction.highest_bidder = Pubkey::default();
+        auction.bid_count = 0;
+        auction.bump = auction_bump;
+        let auction_seeds: &[&[u8]] = &[b"auction", agent_key.as_ref(), &[auction_bump]];
+
+        // Move the cNFT and registry ownership into escrow
+        ctx.accounts.cnft_accounts.transfer(
+            ctx.accounts.seller.to_account_info(),
+            auction.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+            &cnft,
+            &[],
+        )?;
+        agent_registry::cpi::transfer_ownership(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            agent_registry::cpi::accounts::TransferOwnership {
+                owner: ctx.accounts.seller.to_account_info(),
+                new_owner: auction.to_account_info(),
+                agent_data: ctx.accounts.agent_data.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                guardian: ctx.accounts.guardian.to_account_info(),
+            },
+            &[auction_seeds],
+        ))?;
+
+        emit!(AuctionCreated {
+            auction_id: auction_key,
+            agent: auction.agent,
+            seller: auction.seller,
+            format,
+            start_time,
+            end_time,
+        });
+
+        Ok(())
+    }
+
+    // Bid on an English auction. The bid is escrowed in the auction PDA and
+    // the previous highest bidder is refunded in the same instruction. Bids
+    // close to the end extend the auction by the anti-snipe window.
+    pub fn place_bid(ctx: Context<PlaceBid>, amount: u64) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let auction = &mut ctx.accounts.auction;
+        let bidder = ctx.accounts.bidder.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        let AuctionFormat::English { reserve_price, min_increment } = auction.format else {
+            return err!(AuctionError::WrongFormat);
+        };
+        require!(
+            now >= auction.start_time && now < auction.end_time,
+            AuctionError::AuctionNotLive
+        );
+        require!(bidder != auction.seller, AuctionError::Unauthorized);
+        let minimum_bid = if auction.bid_count == 0 {
+            reserve_price
+        } else {
+            auction
+                .highest_bid
+                .checked_add(min_increment)
+                .ok_or(AuctionError::ArithmeticOverflow)?
+        };
+        require!(amount >= minimum_bid, AuctionError::BidTooLow);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.bidder.to_account_info(),
+                    to: auction.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // Refund the bidder being outbid
+        if auction.bid_count > 0 {
+            let previous_bidder = ctx
+                .accounts
+                .previous_bidder
+                .as_ref()
+                .ok_or(AuctionError::MissingPreviousBidder)?;
+            require!(
+                previous_bidder.key() == auction.highest_bidder,
+                AuctionError::MissingPreviousBidder
+            );
+            pay_from_auction(auction, &previous_bidder.to_account_info(), auction.highest_bid)?;
+        }
+
+        auction.highest_bid = amount;
+        auction.highest_bidder = bidder;
+        auction.bid_count = auction.bid_count.checked_add(1).unwrap_or(u32::MAX);
+        if auction.end_time.saturating_sub(now) < config.anti_snipe_window {
+            auction.end_time = now.saturating_add(config.anti_snipe_window);
+        }
+
+        emit!(BidPlaced {
+            auction_id: auction.key(),
+            bidder,
+            amount,
+            end_time: auction.end_time,
+        });
+
+        Ok(())
+    }
+
+    // Settle an English auction after it ends. Permissionless: the winner
+    // receives the agent and the seller the winning bid minus the fee; with
+    // no bids the agent returns to the seller.
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleAuction<'info>>,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let auction = &ctx.accounts.auction;
+
+        require!(
+            matches!(auction.format, AuctionFormat::English { .. }),
+            AuctionError::WrongFormat
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= auction.end_time,
+            AuctionError::AuctionNotEnded
+        );
+
+        let (recipient, fee) = if auction.bid_count > 0 {
+            let winner = ctx
+                .accounts
+                .winner
+                .as_ref()
+                .ok_or(AuctionError::MissingWinner)?;
+            require!(winner.key() == auction.highest_bidder, AuctionError::MissingWinner);
+
+            let fee = auction_fee(auction.highest_bid, config.fee_bps)?;
+            pay_from_auction(auction, &ctx.accounts.treasury.to_account_info(), fee)?;
+            pay_from_auction(
+                auction,
+                &ctx.accounts.seller.to_account_info(),
+                auction.highest_bid - fee,
+            )?;
+            (winner.to_account_info(), fee)
+        } else {
+            (ctx.accounts.seller.to_account_info(), 0)
+        };
+
+        release_agent(
+            auction,
+            &ctx.accounts.agent_data,
+            recipient.clone(),
+            &ctx.accounts.cnft_accounts,
+            &ctx.accounts.registry_program,
+            &ctx.accounts.guardian,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            &cnft,
+        )?;
+
+        emit!(AuctionSettled {
+            auction_id: auction.key(),
+            agent: auction.agent,
+            seller: auction.seller,
+            winner: (auction.bid_count > 0).then_some(recipient.key()),
+            price: auction.highest_bid,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    // Buy from a Dutch auction at the current descending price, paying the
+    // seller and treasury directly. `max_price` bounds the price the buyer
+    // accepts.
+    pub fn buy_dutch<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyDutch<'info>>,
+        max_price: u64,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let auction = &ctx.accounts.auction;
+        let buyer = ctx.accounts.buyer.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            now >= auction.start_time && now < auction.end_time,
+            AuctionError::AuctionNotLive
+        );
+        require!(buyer != auction.seller, AuctionError::Unauthorized);
+        let price = auction.current_price(now)?;
+        require!(price <= max_price, AuctionError::PriceAboveMaximum);
+
+        let fee = auction_fee(price, config.fee_bps)?;
+        for (to, amount) in [
+            (ctx.accounts.seller.to_account_info(), price - fee),
+            (ctx.accounts.treasury.to_account_info(), fee),
+        ] {
+            if amount > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to,
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        release_agent(
+            auction,
+            &ctx.accounts.agent_data,
+            ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.cnft_accounts,
+            &ctx.accounts.registry_program,
+            &ctx.accounts.guardian,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            &cnft,
+        )?;
+
+        emit!(AuctionSettled {
+            auction_id: auction.key(),
+            agent: auction.agent,
+            seller: auction.seller,
+            winner: Some(buyer),
+            price,
+            fee,
+        });
+
+        Ok(())
+    }
+
+    // Cancel an auction and return the agent to the seller. English auctions
+    // can only be cancelled before the first bid; unsold Dutch auctions at
+    // any time.
+    pub fn cancel_auction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelAuction<'info>>,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        require!(auction.bid_count == 0, AuctionError::AuctionHasBids);
+
+        release_agent(
+            auction,
+            &ctx.accounts.agent_data,
+            ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.cnft_accounts,
+            &ctx.accounts.registry_program,
+            &ctx.accounts.guardian,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            &cnft,
+        )?;
+
+        emit!(AuctionCancelled {
+            auction_id: auction.key(),
+            agent: auction.agent,
+            seller: auction.seller,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct AuctionHouse {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,  // Receives the fee on every sale
+    pub fee_bps: u16,
+    pub anti_snipe_window: i64,  // Bids this close to the end push the end back by as much
+    pub bump: u8,
+}
+
+// An agent up for auction; the auction PDA holds the agent and, for English
+// auctions, the highest bid until settlement
+#[account]
+pub struct Auction {
+    pub seller: Pubkey,
+    pub agent: Pubkey,
+    pub merkle_tree: Pubkey,  // Tree holding the agent's cNFT
+    pub format: AuctionFormat,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub highest_bid: u64,
+    pub highest_bidder: Pubkey,
+    pub bid_count: u32,
+    pub bump: u8,
+}
+
+impl Auction {
+    // Dutch price falls linearly from start_price at start_time to
+    // end_price at end_time
+    pub fn current_price(&self, now: i64) -> Result<u64> {
+        let AuctionFormat::Dutch { start_price, end_price } = self.format else {
+            return err!(AuctionError::WrongFormat);
+        };
+        let duration = self.end_time.saturating_sub(self.start_time) as u128;
+        let elapsed = now.clamp(self.start_time, self.end_time).saturating_sub(self.start_time) as u128;
+        let drop = ((start_price - end_price) as u128)
+            .checked_mul(elapsed)
+            .ok_or(AuctionError::ArithmeticOverflow)?
+            / duration;
+        Ok(start_price - drop as u64)
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeAuctionHouse<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<AuctionHouse>(),
+        seeds = [b"auction_house"],
+        bump
+    )]
+    pub config: Account<'info, AuctionHouse>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAuctionHouse<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction_house"],
+        bump = config.bump,
+        has_one = admin @ AuctionError::Unauthorized,
+    )]
+    pub config: Account<'info, AuctionHouse>,
+}
+
+// Bubblegum accounts needed to move an agent's cNFT; mirrors
+// marketplace::CnftAccounts
+#[derive(Accounts)]
+pub struct CnftAccounts<'info> {
+    /// CHECK: Bubblegum tree config; validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAuction<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == seller.key() @ AuctionError::Unauthorized,
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + std::mem::size_of::<Auction>(),
+        seeds = [b"auction", agent_data.key().as_ref()],
+        bump
+    )]
+    pub auction: Account<'info, Auction>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(seeds = [b"auction_house"], bump = config.bump)]
+    pub config: Account<'info, AuctionHouse>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", auction.agent.as_ref()],
+        bump = auction.bump,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// CHECK: refunded the outbid amount; checked against the auction's highest bidder
+    #[account(mut)]
+    pub previous_bidder: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    #[account(seeds = [b"auction_house"], bump = config.bump)]
+    pub config: Account<'info, AuctionHouse>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", agent_data.key().as_ref()],
+        bump = auction.bump,
+        has_one = seller,
+        constraint = auction.merkle_tree == cnft_accounts.merkle_tree.key() @ AuctionError::TreeMismatch,
+        close = seller,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// CHECK: receives the proceeds and the auction's rent; validated by `has_one`
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// CHECK: receives the agent; checked against the auction's highest bidder
+    pub winner: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    /// CHECK: receives the fee
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyDutch<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"auction_house"], bump = config.bump)]
+    pub config: Account<'info, AuctionHouse>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", agent_data.key().as_ref()],
+        bump = auction.bump,
+        has_one = seller,
+        constraint = auction.merkle_tree == cnft_accounts.merkle_tree.key() @ AuctionError::TreeMismatch,
+        close = seller,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    /// CHECK: receives the proceeds and the auction's rent; validated by `has_one`
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    /// CHECK: receives the fee
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", agent_data.key().as_ref()],
+        bump = auction.bump,
+        has_one = seller @ AuctionError::Unauthorized,
+        constraint = auction.merkle_tree == cnft_accounts.merkle_tree.key() @ AuctionError::TreeMismatch,
+        close = seller,
+    )]
+    pub auction: Account<'info, Auction>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum AuctionFormat {
+    English { reserve_price: u64, min_increment: u64 },  // Lamports
+    Dutch { start_price: u64, end_price: u64 },          // Lamports
+}
+
+// Current Bubblegum leaf of the agent's cNFT, as reported by the DAS API
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CnftArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+// Events
+#[event]
+pub struct AuctionHouseUpdated {
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub anti_snipe_window: i64,
+}
+
+#[event]
+pub struct AuctionCreated {
+    pub auction_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub format: AuctionFormat,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub auction_id: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub end_time: i64,  // After any anti-snipe extension
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub auction_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub winner: Option<Pubkey>,  // None when an English auction ended without bids
+    pub price: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct AuctionCancelled {
+    pub auction_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum AuctionError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Fee exceeds the maximum")]
+    InvalidFee,
+    #[msg("Invalid auction schedule")]
+    InvalidSchedule,
+    #[msg("Invalid auction prices")]
+    InvalidPrice,
+    #[msg("Agent has been frozen by the registry moderator")]
+    AgentFrozen,
+    #[msg("Instruction does not apply to this auction format")]
+    WrongFormat,
+    #[msg("Auction is not live")]
+    AuctionNotLive,
+    #[msg("Auction has not ended")]
+    AuctionNotEnded,
+    #[msg("Bid is below the reserve price or minimum increment")]
+    BidTooLow,
+    #[msg("Previous highest bidder account is required for the refund")]
+    MissingPreviousBidder,
+    #[msg("Winning bidder account is required to settle")]
+    MissingWinner,
+    #[msg("Current price is above the buyer's maximum")]
+    PriceAboveMaximum,
+    #[msg("Auction already has bids")]
+    AuctionHasBids,
+    #[msg("Merkle tree does not hold this agent's cNFT")]
+    TreeMismatch,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+impl<'info> CnftAccounts<'info> {
+    // Transfer the cNFT leaf, optionally signing as a PDA leaf owner
+    pub fn transfer(
+        &self,
+        leaf_owner: AccountInfo<'info>,
+        new_leaf_owner: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        proof: &[AccountInfo<'info>],
+        cnft: &CnftArgs,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_accounts = mpl_bubblegum::accounts::Transfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: leaf_owner.clone(),
+            leaf_delegate: leaf_owner,
+            new_leaf_owner,
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.bubblegum_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(proof.to_vec());
+        mpl_bubblegum::cpi::transfer(
+            cpi_ctx,
+            cnft.root,
+            cnft.data_hash,
+            cnft.creator_hash,
+            cnft.nonce,
+            cnft.index,
+        )
+    }
+}
+
+pub fn auction_fee(price: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (price as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(AuctionError::ArithmeticOverflow)?
+        / BPS_DENOMINATOR as u128;
+    Ok(fee as u64)
+}
+
+// Pay escrowed bid lamports out of the auction PDA
+fn pay_from_auction(auction: &Account<Auction>, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let from = auction.to_account_info();
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(AuctionError::ArithmeticOverflow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(AuctionError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Hand an escrowed agent's cNFT and registry ownership to `recipient`,
+// signing as the auction PDA
+#[allow(clippy::too_many_arguments)]
+fn release_agent<'info>(
+    auction: &Account<'info, Auction>,
+    agent_data: &Account<'info, AgentData>,
+    recipient: AccountInfo<'info>,
+    cnft_accounts: &CnftAccounts<'info>,
+    registry_program: &Program<'info, AgentRegistry>,
+    guardian: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    proof: &[AccountInfo<'info>],
+    cnft: &CnftArgs,
+) -> Result<()> {
+    let auction_seeds: &[&[u8]] = &[b"auction", auction.agent.as_ref(), &[auction.bump]];
+
+    cnft_accounts.transfer(
+        auction.to_account_info(),
+        recipient.clone(),
+        system_program.to_account_info(),
+        proof,
+        cnft,
+        &[auction_seeds],
+    )?;
+    agent_registry::cpi::transfer_ownership(
+        CpiContext::new_with_signer(
+            registry_program.to_account_info(),
+            agent_registry::cpi::accounts::TransferOwnership {
+                owner: auction.to_account_info(),
+                new_owner: recipient,
+                agent_data: agent_data.to_account_info(),
+                system_program: system_program.to_account_info(),
+                guardian: guardian.to_account_info(),
+            },
+            &[auction_seeds],
+        ),
+    )
+}
@@ -0,0 +1,479 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use clockwork_sdk::state::{Thread, Trigger};
+use clockwork_sdk::ThreadProgram;
+
+declare_id!("Aut0mat10nPr0graMxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_CRANK_ACCOUNTS: usize = 24;
+const MAX_CRANK_DATA_LEN: usize = 256;
+const MAX_CRON_LEN: usize = 32;
+
+// Protocol-wide Clockwork threads for recurring cranks (relationship decay,
+// emissions, subscription renewals, auction settlement). Every thread is
+// owned by the automation authority PDA; governance creates, funds, pauses
+// and deletes them.
+#[program]
+pub mod automation {
+    use super::*;
+
+    pub fn initialize_automation(ctx: Context<InitializeAutomation>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.crank_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Create a thread running `instruction` on `schedule`, funded with
+    // `fee_lamports` from the governance authority (governance)
+    pub fn create_crank(
+        ctx: Context<CreateCrank>,
+        kind: CrankKind,
+        instruction: CrankInstruction,
+        schedule: CrankSchedule,
+        fee_lamports: u64,
+    ) -> Result<()> {
+        instruction.validate()?;
+        schedule.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        let id = config.crank_count;
+        let thread_id = crank_thread_id(id);
+        let authority_key = ctx.accounts.automation_authority.key();
+        let thread = Thread::pubkey(authority_key, thread_id.clone());
+        require_keys_eq!(
+            ctx.accounts.thread.key(),
+            thread,
+            AutomationError::InvalidThread
+        );
+
+        let authority_seeds: &[&[u8]] = &[
+            b"automation_authority",
+            &[*ctx.bumps.get("automation_authority").unwrap()],
+        ];
+        clockwork_sdk::cpi::thread_create(
+            CpiContext::new_with_signer(
+                ctx.accounts.clockwork_program.to_account_info(),
+                clockwork_sdk::cpi::ThreadCreate {
+                    authority: ctx.accounts.automation_authority.to_account_info(),
+                    payer: ctx.accounts.governance_authority.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    thread: ctx.accounts.thread.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            fee_lamports,
+            thread_id,
+            vec![instruction.to_instruction().into()],
+            schedule.trigger(),
+        )?;
+
+        config.crank_count = config.crank_count.checked_add(1).unwrap_or(u64::MAX);
+
+        let crank = &mut ctx.accounts.crank;
+        crank.id = id;
+        crank.kind = kind.clone();
+        crank.thread = thread;
+        crank.target_program = instruction.program_id;
+        crank.paused = false;
+        crank.funded = fee_lamports;
+        crank.created_at = Clock::get()?.unix_timestamp;
+        crank.bump = *ctx.bumps.get("crank").unwrap();
+
+        emit!(CrankCreated {
+            id,
+            kind,
+            thread,
+            target_program: instruction.program_id,
+        });
+
+        Ok(())
+    }
+
+    // Top up a crank's thread so it keeps paying for its executions (governance)
+    pub fn fund_crank(ctx: Context<FundCrank>, amount: u64) -> Result<()> {
+        require!(amount > 0, AutomationError::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.governance_authority.to_account_info(),
+                    to: ctx.accounts.thread.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let crank = &mut ctx.accounts.crank;
+        crank.funded = crank.funded.checked_add(amount).unwrap_or(u64::MAX);
+
+        emit!(CrankFunded {
+            id: crank.id,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Pause or resume cranks (governance). Remaining accounts are
+    // (crank, thread) pairs, so every crank in the suite can be stopped in
+    // one proposal.
+    pub fn set_cranks_paused<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetCranksPaused<'info>>,
+        paused: bool,
+    ) -> Result<()> {
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 2 == 0,
+            AutomationError::InvalidCrankAccounts
+        );
+        let authority_seeds: &[&[u8]] = &[
+            b"automation_authority",
+            &[*ctx.bumps.get("automation_authority").unwrap()],
+        ];
+
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let mut crank: Account<CrankRecord> = Account::try_from(&pair[0])?;
+            require_keys_eq!(pair[1].key(), crank.thread, AutomationError::InvalidThread);
+            if crank.paused == paused {
+                continue;
+            }
+
+            let program = ctx.accounts.clockwork_program.to_account_info();
+            let authority = ctx.accounts.automation_authority.to_account_info();
+            if paused {
+                clockwork_sdk::cpi::thread_pause(CpiContext::new_with_signer(
+                    program,
+                    clockwork_sdk::cpi::ThreadPause {
+                        authority,
+                        thread: pair[1].clone(),
+                    },
+                    &[authority_seeds],
+                ))?;
+            } else {
+                clockwork_sdk::cpi::thread_resume(CpiContext::new_with_signer(
+                    program,
+                    clockwork_sdk::cpi::ThreadResume {
+                        authority,
+                        thread: pair[1].clone(),
+                    },
+                    &[authority_seeds],
+                ))?;
+            }
+            crank.paused = paused;
+            crank.exit(&crate::ID)?;
+
+            emit!(CrankPauseSet {
+                id: crank.id,
+                paused,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Delete a crank's thread; its remaining balance and the record's rent
+    // return to the governance authority (governance)
+    pub fn delete_crank(ctx: Context<DeleteCrank>) -> Result<()> {
+        let authority_seeds: &[&[u8]] = &[
+            b"automation_authority",
+            &[*ctx.bumps.get("automation_authority").unwrap()],
+        ];
+        clockwork_sdk::cpi::thread_delete(CpiContext::new_with_signer(
+            ctx.accounts.clockwork_program.to_account_info(),
+            clockwork_sdk::cpi::ThreadDelete {
+                authority: ctx.accounts.automation_authority.to_account_info(),
+                close_to: ctx.accounts.governance_authority.to_account_info(),
+                thread: ctx.accounts.thread.to_account_info(),
+            },
+            &[authority_seeds],
+        ))?;
+
+        emit!(CrankDeleted {
+            id: ctx.accounts.crank.id,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct AutomationConfig {
+    pub crank_count: u64,  // Seeds the next crank's thread id
+    pub bump: u8,
+}
+
+// A protocol crank and the Clockwork thread that runs it
+#[account]
+pub struct CrankRecord {
+    pub id: u64,
+    pub kind: CrankKind,
+    pub thread: Pubkey,
+    pub target_program: Pubkey,
+    pub paused: bool,
+    pub funded: u64,  // Lamports given to the thread, including at creation
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeAutomation<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = governance_authority,
+        space = 8 + std::mem::size_of::<AutomationConfig>(),
+        seeds = [b"automation_config"],
+        bump
+    )]
+    pub config: Account<'info, AutomationConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCrank<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"automation_config"], bump = config.bump)]
+    pub config: Account<'info, AutomationConfig>,
+
+    #[account(
+        init,
+        payer = governance_authority,
+        space = 8 + std::mem::size_of::<CrankRecord>(),
+        seeds = [b"crank", config.crank_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub crank: Account<'info, CrankRecord>,
+
+    /// CHECK: PDA that owns every crank thread; holds no data
+    #[account(seeds = [b"automation_authority"], bump)]
+    pub automation_authority: UncheckedAccount<'info>,
+
+    /// CHECK: created by the thread program; address checked in the handler
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+
+    pub clockwork_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundCrank<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"crank", crank.id.to_le_bytes().as_ref()],
+        bump = crank.bump,
+        has_one = thread,
+    )]
+    pub crank: Account<'info, CrankRecord>,
+
+    /// CHECK: the crank's thread; validated by `has_one`
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCranksPaused<'info> {
+    #[account(
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    /// CHECK: PDA that owns every crank thread; holds no data
+    #[account(seeds = [b"automation_authority"], bump)]
+    pub automation_authority: UncheckedAccount<'info>,
+
+    pub clockwork_program: Program<'info, ThreadProgram>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteCrank<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"crank", crank.id.to_le_bytes().as_ref()],
+        bump = crank.bump,
+        has_one = thread,
+        close = governance_authority,
+    )]
+    pub crank: Account<'info, CrankRecord>,
+
+    /// CHECK: PDA that owns every crank thread; holds no data
+    #[account(seeds = [b"automation_authority"], bump)]
+    pub automation_authority: UncheckedAccount<'info>,
+
+    /// CHECK: the crank's thread; validated by `has_one`
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+
+    pub clockwork_program: Program<'info, ThreadProgram>,
+}
+
+// Data structures
+// What a crank is for; informational, the instruction defines what it does
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum CrankKind {
+    RelationshipDecay,
+    Emissions,
+    SubscriptionRenewal,
+    AuctionSettlement,
+    Other,
+}
+
+// Instruction a crank's thread executes. Accounts flagged as signers can
+// only be the thread itself, which Clockwork signs for.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CrankInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<CrankAccount>,
+    pub data: Vec<u8>,
+}
+
+impl CrankInstruction {
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.accounts.len() <= MAX_CRANK_ACCOUNTS && self.data.len() <= MAX_CRANK_DATA_LEN,
+            AutomationError::InstructionTooLarge
+        );
+        Ok(())
+    }
+
+    fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CrankAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum CrankSchedule {
+    Cron { schedule: String },  // Clockwork cron, with seconds
+    At { unix_ts: i64 },        // Once, e.g. an auction's end time
+}
+
+impl CrankSchedule {
+    fn validate(&self) -> Result<()> {
+        if let CrankSchedule::Cron { schedule } = self {
+            require!(
+                !schedule.is_empty() && schedule.len() <= MAX_CRON_LEN,
+                AutomationError::InvalidSchedule
+            );
+        }
+        Ok(())
+    }
+
+    fn trigger(&self) -> Trigger {
+        match self {
+            CrankSchedule::Cron { schedule } => Trigger::Cron {
+                schedule: schedule.clone(),
+                skippable: true,
+            },
+            CrankSchedule::At { unix_ts } => Trigger::Timestamp { unix_ts: *unix_ts },
+        }
+    }
+}
+
+// Events
+#[event]
+pub struct CrankCreated {
+    pub id: u64,
+    pub kind: CrankKind,
+    pub thread: Pubkey,
+    pub target_program: Pubkey,
+}
+
+#[event]
+pub struct CrankFunded {
+    pub id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CrankPauseSet {
+    pub id: u64,
+    pub paused: bool,
+}
+
+#[event]
+pub struct CrankDeleted {
+    pub id: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum AutomationError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Thread account does not match the crank")]
+    InvalidThread,
+    #[msg("Crank instruction has too many accounts or too much data")]
+    InstructionTooLarge,
+    #[msg("Invalid cron schedule")]
+    InvalidSchedule,
+    #[msg("Remaining accounts must be (crank, thread) pairs")]
+    InvalidCrankAccounts,
+}
+
+// Helper functions
+fn crank_thread_id(id: u64) -> Vec<u8> {
+    let mut thread_id = b"crank".to_vec();
+    thread_id.extend_from_slice(&id.to_le_bytes());
+    thread_id
+}
@@ -0,0 +1,828 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use relationship::program::Relationship;
+use relationship::{Dispute, DisputeRuling, DisputeStatus, RelationshipData, RelationshipStatus};
+
+declare_id!("B0ndsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+
+#[program]
+pub mod bonds {
+    use super::*;
+
+    // Create the bonds config and its bonus vault. Forfeited bonds go to
+    // `treasury`; completed bonds earn `bonus_bps` from the bonus vault.
+    pub fn initialize_bonds(ctx: Context<InitializeBonds>, bonus_bps: u16) -> Result<()> {
+        require!(bonus_bps <= BPS_DENOMINATOR, BondError::InvalidBonus);
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.bonus_vault = ctx.accounts.bonus_vault.key();
+        config.treasury = ctx.accounts.treasury.key();
+        config.bonus_bps = bonus_bps;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Change the completion bonus (admin)
+    pub fn set_bonus_bps(ctx: Context<UpdateBondsConfig>, bonus_bps: u16) -> Result<()> {
+        require!(bonus_bps <= BPS_DENOMINATOR, BondError::InvalidBonus);
+        ctx.accounts.config.bonus_bps = bonus_bps;
+
+        Ok(())
+    }
+
+    // Top up the bonus vault (anyone)
+    pub fn fund_bonus_vault(ctx: Context<FundBonusVault>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.funder_token_account.to_account_info(),
+                    to: ctx.accounts.bonus_vault.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    // Propose a bond for a relationship and post this owner's stake. The
+    // bond activates once the other owner posts the same amount.
+    pub fn create_bond(ctx: Context<CreateBond>, amount: u64, term_ends_at: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(amount > 0, BondError::InvalidAmount);
+        require!(term_ends_at > now, BondError::InvalidTerm);
+
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        require!(
+            relationship_data.status()? != RelationshipStatus::Ended,
+            BondError::RelationshipEnded
+        );
+        let owner = ctx.accounts.owner.key();
+        let posted_by_one = if owner == relationship_data.owner_one {
+            true
+        } else if owner == relationship_data.owner_two {
+            false
+        } else {
+            return err!(BondError::Unauthorized);
+        };
+
+        let bond = &mut ctx.accounts.bond;
+        bond.relationship = ctx.accounts.relationship_data.key();
+        bond.owner_one = relationship_data.owner_one;
+        bond.owner_two = relationship_data.owner_two;
+        bond.vault = ctx.accounts.bond_vault.key();
+        bond.amount = amount;
+        bond.posted_one = posted_by_one;
+        bond.posted_two = !posted_by_one;
+        bond.status = BondStatus::Pending;
+        bond.term_ends_at = term_ends_at;
+        bond.activated_at = 0;
+        bond.bump = *ctx.bumps.get("bond").unwrap();
+
+        post_stake(
+            amount,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(BondCreated {
+            bond: bond.key(),
+            relationship: bond.relationship,
+            amount,
+            term_ends_at,
+        });
+
+        Ok(())
+    }
+
+    // Post the other owner's stake, activating the bond
+    pub fn post_bond(ctx: Context<PostBond>) -> Result<()> {
+        let bond = &mut ctx.accounts.bond;
+        require!(bond.status == BondStatus::Pending, BondError::InvalidStatus);
+        let owner = ctx.accounts.owner.key();
+        if owner == bond.owner_one && !bond.posted_one {
+            bond.posted_one = true;
+        } else if owner == bond.owner_two && !bond.posted_two {
+            bond.posted_two = true;
+        } else {
+            return err!(BondError::Unauthorized);
+        }
+
+        post_stake(
+            bond.amount,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+        )?;
+        bond.status = BondStatus::Active;
+        bond.activated_at = Clock::get()?.unix_timestamp;
+
+        emit!(BondActivated {
+            bond: bond.key(),
+            relationship: bond.relationship,
+            term_ends_at: bond.term_ends_at,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw a bond the other owner never matched; the stake is refunded
+    pub fn cancel_bond(ctx: Context<CancelBond>) -> Result<()> {
+        let bond = &ctx.accounts.bond;
+        require!(bond.status == BondStatus::Pending, BondError::InvalidStatus);
+        let owner = ctx.accounts.owner.key();
+        require!(
+            (owner == bond.owner_one && bond.posted_one) || (owner == bond.owner_two && bond.posted_two),
+            BondError::Unauthorized
+        );
+
+        release_stake(
+            bond,
+            bond.amount,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(BondSettled {
+            bond: bond.key(),
+            relationship: bond.relationship,
+            outcome: BondOutcome::Cancelled,
+            owner_one_payout: if bond.posted_one { bond.amount } else { 0 },
+            owner_two_payout: if bond.posted_two { bond.amount } else { 0 },
+        });
+
+        Ok(())
+    }
+
+    // End a bonded relationship through the relationship program. Ending an
+    // active bond before its term forfeits the caller's stake to the other
+    // owner, who also gets their own stake back; after the term both stakes
+    // are returned with the completion bonus.
+    pub fn end_bonded_relationship(ctx: Context<EndBondedRelationship>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let bond = &ctx.accounts.bond;
+        require!(bond.status == BondStatus::Active, BondError::InvalidStatus);
+        require!(
+            owner == bond.owner_one || owner == bond.owner_two,
+            BondError::Unauthorized
+        );
+
+        relationship::cpi::end_relationship(CpiContext::new(
+            ctx.accounts.relationship_program.to_account_info(),
+            relationship::cpi::accounts::EndRelationship {
+                owner: ctx.accounts.owner.to_account_info(),
+                relationship_data: ctx.accounts.relationship_data.to_account_info(),
+                template: ctx.accounts.template.as_ref().map(|a| a.to_account_info()),
+                joint_wallet: ctx.accounts.joint_wallet.as_ref().map(|a| a.to_account_info()),
+                vault: ctx.accounts.joint_wallet_vault.as_ref().map(|a| a.to_account_info()),
+                owner_one_token_account: Some(ctx.accounts.owner_one_token_account.to_account_info()),
+                owner_two_token_account: Some(ctx.accounts.owner_two_token_account.to_account_info()),
+                token_program: Some(ctx.accounts.token_program.to_account_info()),
+                stats: ctx.accounts.relationship_stats.to_account_info(),
+                guardian: ctx.accounts.guardian.to_account_info(),
+                event_authority: ctx.accounts.relationship_event_authority.to_account_info(),
+                program: ctx.accounts.relationship_program.to_account_info(),
+            },
+        ))?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let accounts = &ctx.accounts;
+        let (outcome, owner_one_payout, owner_two_payout) = if now < bond.term_ends_at {
+            let payout = bond.amount.checked_mul(2).ok_or(BondError::ArithmeticOverflow)?;
+            let (outcome, owner_one_payout, owner_two_payout) = if owner == bond.owner_one {
+                (BondOutcome::BrokenByOwnerOne, 0, payout)
+            } else {
+                (BondOutcome::BrokenByOwnerTwo, payout, 0)
+            };
+            pay_out_bond(
+                bond,
+                owner_one_payout,
+                owner_two_payout,
+                &accounts.bond_vault,
+                &accounts.owner_one_token_account,
+                &accounts.owner_two_token_account,
+                &accounts.token_program,
+            )?;
+            (outcome, owner_one_payout, owner_two_payout)
+        } else {
+            let bonus = complete_bond(
+                &accounts.config,
+                bond,
+                &accounts.bond_vault,
+                &accounts.bonus_vault,
+                &accounts.owner_one_token_account,
+                &accounts.owner_two_token_account,
+                &accounts.token_program,
+            )?;
+            (BondOutcome::Completed, bond.amount + bonus, bond.amount + bonus)
+        };
+
+        let bond = &mut ctx.accounts.bond;
+        bond.status = BondStatus::Settled;
+
+        emit!(BondSettled {
+            bond: bond.key(),
+            relationship: bond.relationship,
+            outcome,
+            owner_one_payout,
+            owner_two_payout,
+        });
+
+        Ok(())
+    }
+
+    // Settle an active bond (permissionless). Once the term has passed with
+    // the relationship intact, both stakes are returned with the completion
+    // bonus. A relationship ended before the term without going through
+    // `end_bonded_relationship` can't attribute the breakup, so both stakes
+    // are forfeited to the treasury.
+    pub fn settle_bond(ctx: Context<SettleBond>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let bond = &ctx.accounts.bond;
+        require!(bond.status == BondStatus::Active, BondError::InvalidStatus);
+        let ended = ctx.accounts.relationship_data.load()?.status()? == RelationshipStatus::Ended;
+        require!(ended || now >= bond.term_ends_at, BondError::TermNotEnded);
+
+        let accounts = &ctx.accounts;
+        let (outcome, owner_one_payout, owner_two_payout) = if now >= bond.term_ends_at {
+            let bonus = complete_bond(
+                &accounts.config,
+                bond,
+                &accounts.bond_vault,
+                &accounts.bonus_vault,
+                &accounts.owner_one_token_account,
+                &accounts.owner_two_token_account,
+                &accounts.token_program,
+            )?;
+            (BondOutcome::Completed, bond.amount + bonus, bond.amount + bonus)
+        } else {
+            release_stake(
+                bond,
+                accounts.bond_vault.amount,
+                &accounts.bond_vault,
+                &accounts.treasury,
+                &accounts.token_program,
+            )?;
+            (BondOutcome::Forfeited, 0, 0)
+        };
+
+        let bond = &mut ctx.accounts.bond;
+        bond.status = BondStatus::Settled;
+
+        emit!(BondSettled {
+            bond: bond.key(),
+            relationship: bond.relationship,
+            outcome,
+            owner_one_payout,
+            owner_two_payout,
+        });
+
+        Ok(())
+    }
+
+    // Slash an active bond after the relationship program's arbiter rules
+    // against one owner in a dispute opened while the bond was active
+    // (permissionless). The at-fault owner's stake goes to the other owner,
+    // who also gets their own stake back.
+    pub fn slash_for_misconduct(ctx: Context<SlashForMisconduct>) -> Result<()> {
+        let bond = &ctx.accounts.bond;
+        let dispute = &ctx.accounts.dispute;
+        require!(bond.status == BondStatus::Active, BondError::InvalidStatus);
+        require!(
+            dispute.relationship == bond.relationship
+                && dispute.status == DisputeStatus::Resolved
+                && dispute.opened_at >= bond.activated_at,
+            BondError::InvalidDispute
+        );
+        let at_fault = match dispute.ruling {
+            DisputeRuling::OpenerFavored => dispute.respondent,
+            DisputeRuling::RespondentFavored => dispute.opener,
+            _ => return err!(BondError::InvalidDispute),
+        };
+
+        let payout = bond.amount.checked_mul(2).ok_or(BondError::ArithmeticOverflow)?;
+        let (outcome, owner_one_payout, owner_two_payout) = if at_fault == bond.owner_one {
+            (BondOutcome::SlashedOwnerOne, 0, payout)
+        } else {
+            (BondOutcome::SlashedOwnerTwo, payout, 0)
+        };
+        pay_out_bond(
+            bond,
+            owner_one_payout,
+            owner_two_payout,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.owner_one_token_account,
+            &ctx.accounts.owner_two_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        let bond = &mut ctx.accounts.bond;
+        bond.status = BondStatus::Settled;
+
+        emit!(BondSettled {
+            bond: bond.key(),
+            relationship: bond.relationship,
+            outcome,
+            owner_one_payout,
+            owner_two_payout,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct BondsConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub bonus_vault: Pubkey,
+    pub treasury: Pubkey,  // Receives bonds forfeited by unattributed breakups
+    pub bonus_bps: u16,    // Completion bonus per owner, in bps of the stake
+    pub bump: u8,
+}
+
+#[account]
+pub struct Bond {
+    pub relationship: Pubkey,
+    pub owner_one: Pubkey,
+    pub owner_two: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,  // Stake per owner
+    pub posted_one: bool,
+    pub posted_two: bool,
+    pub status: BondStatus,
+    pub term_ends_at: i64,
+    pub activated_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeBonds<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<BondsConfig>(),
+        seeds = [b"bonds_config"],
+        bump
+    )]
+    pub config: Account<'info, BondsConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"bonus_vault"],
+        bump,
+        token::mint = influence_mint,
+        token::authority = config,
+    )]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(constraint = treasury.mint == influence_mint.key())]
+    pub treasury: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBondsConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"bonds_config"],
+        bump = config.bump,
+        has_one = admin @ BondError::Unauthorized,
+    )]
+    pub config: Account<'info, BondsConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FundBonusVault<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(seeds = [b"bonds_config"], bump = config.bump)]
+    pub config: Account<'info, BondsConfig>,
+
+    #[account(mut, address = config.bonus_vault)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = funder_token_account.owner == funder.key() @ BondError::Unauthorized,
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBond<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"bonds_config"], bump = config.bump)]
+    pub config: Account<'info, BondsConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Bond>(),
+        seeds = [b"bond", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub bond: Account<'info, Bond>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"bond_vault", bond.key().as_ref()],
+        bump,
+        token::mint = influence_mint,
+        token::authority = bond,
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ BondError::Unauthorized,
+        constraint = owner_token_account.mint == config.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PostBond<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub bond: Account<'info, Bond>,
+
+    #[account(mut, address = bond.vault)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ BondError::Unauthorized,
+        constraint = owner_token_account.mint == bond_vault.mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBond<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, close = owner)]
+    pub bond: Account<'info, Bond>,
+
+    #[account(mut, address = bond.vault)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ BondError::Unauthorized,
+        constraint = owner_token_account.mint == bond_vault.mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EndBondedRelationship<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"bonds_config"], bump = config.bump)]
+    pub config: Account<'info, BondsConfig>,
+
+    #[account(mut)]
+    pub bond: Account<'info, Bond>,
+
+    #[account(mut, address = bond.vault)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.bonus_vault)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_one_token_account.owner == bond.owner_one @ BondError::Unauthorized,
+        constraint = owner_one_token_account.mint == config.influence_mint,
+    )]
+    pub owner_one_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_two_token_account.owner == bond.owner_two @ BondError::Unauthorized,
+        constraint = owner_two_token_account.mint == config.influence_mint,
+    )]
+    pub owner_two_token_account: Account<'info, TokenAccount>,
+
+    // Relationship program accounts for `end_relationship`
+    /// CHECK: validated by the relationship program
+    #[account(mut, address = bond.relationship)]
+    pub relationship_data: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub template: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub joint_wallet: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub joint_wallet_vault: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub relationship_stats: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub relationship_event_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub guardian: UncheckedAccount<'info>,
+
+    pub relationship_program: Program<'info, Relationship>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBond<'info> {
+    #[account(seeds = [b"bonds_config"], bump = config.bump)]
+    pub config: Account<'info, BondsConfig>,
+
+    #[account(mut)]
+    pub bond: Account<'info, Bond>,
+
+    #[account(address = bond.relationship)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(mut, address = bond.vault)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.bonus_vault)]
+    pub bonus_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.treasury)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_one_token_account.owner == bond.owner_one @ BondError::Unauthorized,
+        constraint = owner_one_token_account.mint == config.influence_mint,
+    )]
+    pub owner_one_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_two_token_account.owner == bond.owner_two @ BondError::Unauthorized,
+        constraint = owner_two_token_account.mint == config.influence_mint,
+    )]
+    pub owner_two_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SlashForMisconduct<'info> {
+    #[account(seeds = [b"bonds_config"], bump = config.bump)]
+    pub config: Account<'info, BondsConfig>,
+
+    #[account(mut)]
+    pub bond: Account<'info, Bond>,
+
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(mut, address = bond.vault)]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_one_token_account.owner == bond.owner_one @ BondError::Unauthorized,
+        constraint = owner_one_token_account.mint == config.influence_mint,
+    )]
+    pub owner_one_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_two_token_account.owner == bond.owner_two @ BondError::Unauthorized,
+        constraint = owner_two_token_account.mint == config.influence_mint,
+    )]
+    pub owner_two_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum BondStatus {
+    Pending,  // Waiting for the second owner's stake
+    Active,
+    Settled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum BondOutcome {
+    Cancelled,
+    Completed,
+    BrokenByOwnerOne,
+    BrokenByOwnerTwo,
+    SlashedOwnerOne,
+    SlashedOwnerTwo,
+    Forfeited,
+}
+
+// Events
+#[event]
+pub struct BondCreated {
+    pub bond: Pubkey,
+    pub relationship: Pubkey,
+    pub amount: u64,
+    pub term_ends_at: i64,
+}
+
+#[event]
+pub struct BondActivated {
+    pub bond: Pubkey,
+    pub relationship: Pubkey,
+    pub term_ends_at: i64,
+}
+
+#[event]
+pub struct BondSettled {
+    pub bond: Pubkey,
+    pub relationship: Pubkey,
+    pub outcome: BondOutcome,
+    pub owner_one_payout: u64,
+    pub owner_two_payout: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum BondError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Bonus cannot exceed 100%")]
+    InvalidBonus,
+    #[msg("Bond amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Bond term must end in the future")]
+    InvalidTerm,
+    #[msg("Relationship has already ended")]
+    RelationshipEnded,
+    #[msg("Bond is not in the required status")]
+    InvalidStatus,
+    #[msg("Bond term has not ended and the relationship is intact")]
+    TermNotEnded,
+    #[msg("Dispute does not rule against an owner of this bond")]
+    InvalidDispute,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+fn post_stake<'info>(
+    amount: u64,
+    owner_token_account: &Account<'info, TokenAccount>,
+    bond_vault: &Account<'info, TokenAccount>,
+    owner: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: owner_token_account.to_account_info(),
+                to: bond_vault.to_account_info(),
+                authority: owner.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+// Transfer out of the bond vault, signing as the bond PDA
+fn release_stake<'info>(
+    bond: &Account<'info, Bond>,
+    amount: u64,
+    bond_vault: &Account<'info, TokenAccount>,
+    recipient: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let bond_seeds: &[&[u8]] = &[b"bond", bond.relationship.as_ref(), &[bond.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: bond_vault.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: bond.to_account_info(),
+            },
+            &[bond_seeds],
+        ),
+        amount,
+    )
+}
+
+fn pay_out_bond<'info>(
+    bond: &Account<'info, Bond>,
+    owner_one_amount: u64,
+    owner_two_amount: u64,
+    bond_vault: &Account<'info, TokenAccount>,
+    owner_one_token_account: &Account<'info, TokenAccount>,
+    owner_two_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    release_stake(bond, owner_one_amount, bond_vault, owner_one_token_account, token_program)?;
+    release_stake(bond, owner_two_amount, bond_vault, owner_two_token_account, token_program)
+}
+
+// Return both stakes plus the completion bonus, returning the bonus paid to
+// each owner. The bonus is capped at half the bonus vault's balance.
+fn complete_bond<'info>(
+    config: &Account<'info, BondsConfig>,
+    bond: &Account<'info, Bond>,
+    bond_vault: &Account<'info, TokenAccount>,
+    bonus_vault: &Account<'info, TokenAccount>,
+    owner_one_token_account: &Account<'info, TokenAccount>,
+    owner_two_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<u64> {
+    let bonus = ((bond.amount as u128)
+        .checked_mul(config.bonus_bps as u128)
+        .ok_or(BondError::ArithmeticOverflow)?
+        / BPS_DENOMINATOR as u128) as u64;
+    let bonus = bonus.min(bonus_vault.amount / 2);
+
+    pay_bonus(config, bonus, bonus_vault, owner_one_token_account, token_program)?;
+    pay_bonus(config, bonus, bonus_vault, owner_two_token_account, token_program)?;
+    pay_out_bond(
+        bond,
+        bond.amount,
+        bond.amount,
+        bond_vault,
+        owner_one_token_account,
+        owner_two_token_account,
+        token_program,
+    )?;
+
+    Ok(bonus)
+}
+
+fn pay_bonus<'info>(
+    config: &Account<'info, BondsConfig>,
+    amount: u64,
+    bonus_vault: &Account<'info, TokenAccount>,
+    recipient: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let config_seeds: &[&[u8]] = &[b"bonds_config", &[config.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: bonus_vault.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: config.to_account_info(),
+            },
+            &[config_seeds],
+        ),
+        amount,
+    )
+}
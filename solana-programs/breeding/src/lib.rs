@@ -0,0 +1,614 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use agent_registry::program::AgentRegistry;
+use agent_registry::{AgentData, Bubblegum};
+use armour_core::{PersonalityTraits, MAX_TRAIT_VALUE, TRAIT_COUNT};
+use spl_account_compression::program::SplAccountCompression;
+
+declare_id!("Breed1ngPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const GENETICS_GENERATIONS: usize = 8;
+
+#[program]
+pub mod breeding {
+    use super::*;
+
+    // Create the breeding config
+    pub fn initialize_breeding(
+        ctx: Context<InitializeBreeding>,
+        breeding_fee: u64,
+        breeding_cooldown: i64,
+        max_offspring: u16,
+        vrf_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            breeding_cooldown >= 0 && max_offspring > 0,
+            BreedingError::InvalidConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.breeding_fee = breeding_fee;
+        config.breeding_cooldown = breeding_cooldown;
+        config.max_offspring = max_offspring;
+        config.vrf_authority = vrf_authority;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit!(BreedingConfigUpdated {
+            breeding_fee,
+            breeding_cooldown,
+            max_offspring,
+            vrf_authority,
+        });
+
+        Ok(())
+    }
+
+    // Update the fee, cooldown and offspring cap (admin)
+    pub fn update_breeding_config(
+        ctx: Context<UpdateBreedingConfig>,
+        breeding_fee: u64,
+        breeding_cooldown: i64,
+        max_offspring: u16,
+        vrf_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            breeding_cooldown >= 0 && max_offspring > 0,
+            BreedingError::InvalidConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.breeding_fee = breeding_fee;
+        config.breeding_cooldown = breeding_cooldown;
+        config.max_offspring = max_offspring;
+        config.vrf_authority = vrf_authority;
+
+        emit!(BreedingConfigUpdated {
+            breeding_fee,
+            breeding_cooldown,
+            max_offspring,
+            vrf_authority,
+        });
+
+        Ok(())
+    }
+
+    // Create the genetics config that governs trait inheritance (admin)
+    pub fn initialize_genetics(
+        ctx: Context<InitializeGenetics>,
+        params: GeneticsParams,
+    ) -> Result<()> {
+        validate_genetics_params(&params)?;
+
+        let genetics = &mut ctx.accounts.genetics;
+        genetics.params = params.clone();
+        genetics.bump = *ctx.bumps.get("genetics").unwrap();
+
+        emit!(GeneticsConfigUpdated { params });
+
+        Ok(())
+    }
+
+    // Retune dominance weights, mutation ranges and trait caps (admin)
+    pub fn update_genetics(ctx: Context<UpdateGenetics>, params: GeneticsParams) -> Result<()> {
+        validate_genetics_params(&params)?;
+
+        ctx.accounts.genetics.params = params.clone();
+
+        emit!(GeneticsConfigUpdated { params });
+
+        Ok(())
+    }
+
+    // Breed two agents into a new child agent. Both parents' owners must sign;
+    // owner_one pays the Influence fee (burned) and receives the child. Each
+    // parent must be out of cooldown and under the offspring cap; traits follow
+    // the genetics config. When the config names a VRF authority, it must sign
+    // and supply the randomness; otherwise recent slot hashes are used.
+    pub fn breed(
+        ctx: Context<Breed>,
+        name: String,
+        metadata_uri: String,
+        randomness: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let parent_one = &ctx.accounts.parent_one;
+        let parent_two = &ctx.accounts.parent_two;
+
+        require!(parent_one.key() != parent_two.key(), BreedingError::SameParent);
+        require!(
+            parent_one.is_active && parent_two.is_active,
+            BreedingError::ParentInactive
+        );
+        require!(
+            !parent_one.is_frozen && !parent_two.is_frozen,
+            BreedingError::ParentFrozen
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        check_breeding_record(&ctx.accounts.record_one, config, now)?;
+        check_breeding_record(&ctx.accounts.record_two, config, now)?;
+
+        let seed = armour_randomness::resolve_seed(
+            config.vrf_authority,
+            ctx.accounts.vrf_authority.as_ref(),
+            randomness,
+            &ctx.accounts.recent_slothashes,
+        )?;
+        let entropy = armour_randomness::derive(
+            &seed,
+            &[
+                parent_one.key().as_ref(),
+                parent_two.key().as_ref(),
+                name.as_bytes(),
+            ],
+        );
+        let generation = parent_one
+            .generation
+            .max(parent_two.generation)
+            .checked_add(1)
+            .ok_or(BreedingError::ArithmeticOverflow)?;
+        let child_traits = combine_traits(
+            &parent_one.personality_traits,
+            &parent_two.personality_traits,
+            &ctx.accounts.genetics.params,
+            generation,
+            &entropy,
+        );
+
+        // Burn the breeding fee
+        if config.breeding_fee > 0 {
+            token::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.influence_mint.to_account_info(),
+                        from: ctx.accounts.fee_token_account.to_account_info(),
+                        authority: ctx.accounts.owner_one.to_account_info(),
+                    },
+                ),
+                config.breeding_fee,
+            )?;
+        }
+
+        // Register the child, then record its lineage as the breeding authority
+        agent_registry::cpi::register_agent(
+            CpiContext::new(
+                ctx.accounts.registry_program.to_account_info(),
+                agent_registry::cpi::accounts::RegisterAgent {
+                    owner: ctx.accounts.owner_one.to_account_info(),
+                    agent_data: ctx.accounts.child.to_account_info(),
+                    registry_state: ctx.accounts.registry_state.to_account_info(),
+                    tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+                    compression_program: ctx.accounts.compression_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    guardian: ctx.accounts.guardian.to_account_info(),
+                    rate_limiter_config: ctx.accounts.rate_limiter_config.to_account_info(),
+                    wallet_quota: ctx.accounts.wallet_quota.to_account_info(),
+                    rate_limiter_program: ctx.accounts.rate_limiter_program.to_account_info(),
+                    credential: ctx.accounts.credential.as_ref().map(|credential| credential.to_account_info()),
+                    analytics_hook_authority: ctx.accounts.analytics_hook_authority.to_account_info(),
+                    analytics_state: ctx.accounts.analytics_state.to_account_info(),
+                    analytics_program: ctx.accounts.analytics_program.to_account_info(),
+                    referral_reporter: None,
+                    referrals_config: None,
+                    referral: None,
+                    referrer_rewards: None,
+                    referrals_program: None,
+                    collection: agent_registry::cpi::accounts::AgentCollection {
+                        collection_config: ctx.accounts.collection_config.to_account_info(),
+                        collection_authority: ctx.accounts.collection_authority.to_account_info(),
+                        collection_mint: ctx.accounts.collection_mint.to_account_info(),
+                        collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+                        collection_edition: ctx.accounts.collection_edition.to_account_info(),
+                        bubblegum_signer: ctx.accounts.bubblegum_signer.to_account_info(),
+                        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                    },
+                },
+            ),
+            metadata_uri,
+            name,
+            child_traits.clone(),
+        )?;
+        let authority_seeds: &[&[u8]] = &[
+            b"breeding_authority",
+            &[*ctx.bumps.get("breeding_authority").unwrap()],
+        ];
+        agent_registry::cpi::set_lineage(
+            CpiContext::new_with_signer(
+                ctx.accounts.registry_program.to_account_info(),
+                agent_registry::cpi::accounts::SetLineage {
+                    breeding_authority: ctx.accounts.breeding_authority.to_account_info(),
+                    agent_data: ctx.accounts.child.to_account_info(),
+                    guardian: ctx.accounts.guardian.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            parent_one.key(),
+            parent_two.key(),
+            generation,
+        )?;
+
+        let parent_one_key = parent_one.key();
+        let parent_two_key = parent_two.key();
+        record_offspring(
+            &mut ctx.accounts.record_one,
+            parent_one_key,
+            *ctx.bumps.get("record_one").unwrap(),
+            now,
+        );
+        record_offspring(
+            &mut ctx.accounts.record_two,
+            parent_two_key,
+            *ctx.bumps.get("record_two").unwrap(),
+            now,
+        );
+
+        emit!(AgentBred {
+            child: ctx.accounts.child.key(),
+            parent_one: parent_one_key,
+            parent_two: parent_two_key,
+            owner: ctx.accounts.owner_one.key(),
+            generation,
+            traits: child_traits,
+            fee_burned: ctx.accounts.config.breeding_fee,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct BreedingConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub breeding_fee: u64,         // Influence burned per child
+    pub breeding_cooldown: i64,    // Seconds a parent must wait between breedings
+    pub max_offspring: u16,        // Lifetime cap on children per agent
+    pub vrf_authority: Pubkey,     // Pubkey::default() to use slot hashes instead of VRF
+    pub bump: u8,
+}
+
+#[account]
+pub struct GeneticsConfig {
+    pub params: GeneticsParams,
+    pub bump: u8,
+}
+
+// Per-parent breeding history, created on an agent's first breeding
+#[account]
+pub struct BreedingRecord {
+    pub agent: Pubkey,
+    pub offspring_count: u16,
+    pub last_bred_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeBreeding<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<BreedingConfig>(),
+        seeds = [b"breeding_config"],
+        bump
+    )]
+    pub config: Account<'info, BreedingConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBreedingConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"breeding_config"],
+        bump = config.bump,
+        has_one = admin @ BreedingError::Unauthorized,
+    )]
+    pub config: Account<'info, BreedingConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGenetics<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"breeding_config"],
+        bump = config.bump,
+        has_one = admin @ BreedingError::Unauthorized,
+    )]
+    pub config: Account<'info, BreedingConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<GeneticsConfig>(),
+        seeds = [b"genetics_config"],
+        bump
+    )]
+    pub genetics: Account<'info, GeneticsConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGenetics<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"breeding_config"],
+        bump = config.bump,
+        has_one = admin @ BreedingError::Unauthorized,
+    )]
+    pub config: Account<'info, BreedingConfig>,
+
+    #[account(mut, seeds = [b"genetics_config"], bump = genetics.bump)]
+    pub genetics: Account<'info, GeneticsConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Breed<'info> {
+    #[account(mut)]
+    pub owner_one: Signer<'info>,
+    pub owner_two: Signer<'info>,
+
+    #[account(seeds = [b"breeding_config"], bump = config.bump)]
+    pub config: Account<'info, BreedingConfig>,
+
+    #[account(seeds = [b"genetics_config"], bump = genetics.bump)]
+    pub genetics: Account<'info, GeneticsConfig>,
+
+    #[account(constraint = parent_one.owner == owner_one.key() @ BreedingError::Unauthorized)]
+    pub parent_one: Account<'info, AgentData>,
+
+    #[account(constraint = parent_two.owner == owner_two.key() @ BreedingError::Unauthorized)]
+    pub parent_two: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = owner_one,
+        space = 8 + std::mem::size_of::<BreedingRecord>(),
+        seeds = [b"breeding_record", parent_one.key().as_ref()],
+        bump
+    )]
+    pub record_one: Account<'info, BreedingRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = owner_one,
+        space = 8 + std::mem::size_of::<BreedingRecord>(),
+        seeds = [b"breeding_record", parent_two.key().as_ref()],
+        bump
+    )]
+    pub record_two: Account<'info, BreedingRecord>,
+
+    /// CHECK: created by the registry's `register_agent`
+    #[account(mut)]
+    pub child: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs lineage updates in the registry
+    #[account(seeds = [b"breeding_authority"], bump)]
+    pub breeding_authority: UncheckedAccount<'info>,
+
+    // Required when the config names a VRF authority
+    pub vrf_authority: Option<Signer<'info>>,
+
+    /// CHECK: slot hashes sysvar, read for fallback randomness
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+
+    #[account(mut, address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = fee_token_account.owner == owner_one.key() @ BreedingError::Unauthorized,
+        constraint = fee_token_account.mint == config.influence_mint,
+    )]
+    pub fee_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub registry_state: UncheckedAccount<'info>,
+
+    // Accounts needed by the registry to mint the child's cNFT
+    /// CHECK: validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub rate_limiter_program: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program, which requires it in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the registry program
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub analytics_program: UncheckedAccount<'info>,
+
+    // The registry's agent collection, which the cNFT is minted into
+    /// CHECK: validated by the registry program
+    pub collection_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub collection_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub collection_mint: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub collection_edition: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub bubblegum_signer: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct GeneticsParams {
+    pub dominance_bps: [u16; TRAIT_COUNT],      // Per trait, chance of the higher parent value
+    pub min_mutation_chance_bps: u16,           // Mutation chance is drawn from min..=max
+    pub max_mutation_chance_bps: u16,
+    pub max_mutation: u8,                       // Largest change a mutation makes to a trait
+    pub trait_caps: [u8; GENETICS_GENERATIONS], // Trait ceiling by generation; last covers later
+}
+
+// Events
+#[event]
+pub struct BreedingConfigUpdated {
+    pub breeding_fee: u64,
+    pub breeding_cooldown: i64,
+    pub max_offspring: u16,
+    pub vrf_authority: Pubkey,
+}
+
+#[event]
+pub struct GeneticsConfigUpdated {
+    pub params: GeneticsParams,
+}
+
+#[event]
+pub struct AgentBred {
+    pub child: Pubkey,
+    pub parent_one: Pubkey,
+    pub parent_two: Pubkey,
+    pub owner: Pubkey,
+    pub generation: u16,
+    pub traits: PersonalityTraits,
+    pub fee_burned: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum BreedingError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Invalid breeding config")]
+    InvalidConfig,
+    #[msg("An agent cannot breed with itself")]
+    SameParent,
+    #[msg("Both parents must be active")]
+    ParentInactive,
+    #[msg("Parent has been frozen by the registry moderator")]
+    ParentFrozen,
+    #[msg("Invalid genetics parameters")]
+    InvalidGenetics,
+    #[msg("Parent is still in its breeding cooldown")]
+    BreedingCooldown,
+    #[msg("Parent has reached the maximum number of offspring")]
+    OffspringLimitReached,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+fn validate_genetics_params(params: &GeneticsParams) -> Result<()> {
+    require!(
+        params.dominance_bps.iter().all(|bps| *bps <= BPS_DENOMINATOR)
+            && params.min_mutation_chance_bps <= params.max_mutation_chance_bps
+            && params.max_mutation_chance_bps <= BPS_DENOMINATOR
+            && params.max_mutation <= MAX_TRAIT_VALUE
+            && params.trait_caps.iter().all(|cap| *cap <= MAX_TRAIT_VALUE),
+        BreedingError::InvalidGenetics
+    );
+    Ok(())
+}
+
+fn check_breeding_record(record: &BreedingRecord, config: &BreedingConfig, now: i64) -> Result<()> {
+    require!(
+        record.offspring_count < config.max_offspring,
+        BreedingError::OffspringLimitReached
+    );
+    if record.offspring_count > 0 {
+        require!(
+            now.saturating_sub(record.last_bred_at) >= config.breeding_cooldown,
+            BreedingError::BreedingCooldown
+        );
+    }
+    Ok(())
+}
+
+fn record_offspring(record: &mut BreedingRecord, agent: Pubkey, bump: u8, now: i64) {
+    record.agent = agent;
+    record.bump = bump;
+    record.offspring_count = record.offspring_count.checked_add(1).unwrap_or(u16::MAX);
+    record.last_bred_at = now;
+}
+
+// Bytes 0..24 drive the traits, three per trait: which parent (weighted
+// towards the higher value by dominance_bps), whether it mutates, and by how
+// much (up to ±max_mutation). Byte 24 draws this breeding's mutation chance
+// from the configured range. The result is capped for the child's generation.
+pub fn combine_traits(
+    parent_one: &PersonalityTraits,
+    parent_two: &PersonalityTraits,
+    genetics: &GeneticsParams,
+    generation: u16,
+    entropy: &[u8; 32],
+) -> PersonalityTraits {
+    let one = parent_one.values();
+    let two = parent_two.values();
+    let mutation_span = genetics.max_mutation_chance_bps - genetics.min_mutation_chance_bps;
+    let mutation_chance = genetics.min_mutation_chance_bps as u32
+        + (entropy[24] as u32 * (mutation_span as u32 + 1)) / 256;
+    let cap_index = (generation.max(1) as usize - 1).min(GENETICS_GENERATIONS - 1);
+    let cap = genetics.trait_caps[cap_index] as i16;
+
+    let mut child = [0u8; TRAIT_COUNT];
+    for i in 0..TRAIT_COUNT {
+        let roll = &entropy[i * 3..i * 3 + 3];
+        let (dominant, recessive) = (one[i].max(two[i]), one[i].min(two[i]));
+        let inherit = (roll[0] as u32 * BPS_DENOMINATOR as u32) / 256;
+        let mut value = if inherit < genetics.dominance_bps[i] as u32 {
+            dominant
+        } else {
+            recessive
+        } as i16;
+
+        let chance = (roll[1] as u32 * BPS_DENOMINATOR as u32) / 256;
+        if chance < mutation_chance && genetics.max_mutation > 0 {
+            let span = 2 * genetics.max_mutation as i16 + 1;
+            value += (roll[2] as i16 % span) - genetics.max_mutation as i16;
+        }
+        child[i] = value.clamp(0, cap) as u8;
+    }
+
+    PersonalityTraits::from_values(child)
+}
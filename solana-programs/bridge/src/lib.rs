@@ -0,0 +1,667 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program;
+use agent_registry::program::AgentRegistry;
+use agent_registry::{AgentData, Bubblegum, PersonalityTraits};
+use spl_account_compression::{program::SplAccountCompression, Noop};
+
+declare_id!("Br1dgePr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+// Wormhole core bridge (mainnet)
+pub const WORMHOLE_PROGRAM_ID: Pubkey = solana_program::pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+
+const WORMHOLE_POST_MESSAGE: u8 = 1;
+const WORMHOLE_CONSISTENCY_FINALIZED: u8 = 1;
+const WORMHOLE_FEE_OFFSET: usize = 16;  // BridgeData: guardian set index, last lamports, expiration time, fee
+// PostedVAA account: "vaa" magic, then header fields up to the emitter and payload
+const POSTED_VAA_SEQUENCE_OFFSET: usize = 49;
+const POSTED_VAA_EMITTER_CHAIN_OFFSET: usize = 57;
+const POSTED_VAA_EMITTER_ADDRESS_OFFSET: usize = 59;
+const POSTED_VAA_PAYLOAD_OFFSET: usize = 91;
+
+#[program]
+pub mod bridge {
+    use super::*;
+
+    pub fn initialize_bridge(ctx: Context<InitializeBridge>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.message_count = 0;
+        config.locked_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Register (or replace) the contract on another chain that agents can be
+    // bridged to and returned from (admin)
+    pub fn register_foreign_emitter(
+        ctx: Context<RegisterForeignEmitter>,
+        chain: u16,
+        address: [u8; 32],
+    ) -> Result<()> {
+        let emitter = &mut ctx.accounts.foreign_emitter;
+        emitter.chain = chain;
+        emitter.address = address;
+        emitter.bump = *ctx.bumps.get("foreign_emitter").unwrap();
+
+        emit!(ForeignEmitterRegistered { chain, address });
+
+        Ok(())
+    }
+
+    // Lock an agent for bridging. Its cNFT and registry ownership move into
+    // the lock PDA, so nothing can change on Solana while it is away, and a
+    // Wormhole message carries its canonical state to `recipient` on
+    // `target_chain`. Bubblegum proof accounts are passed as remaining
+    // accounts.
+    pub fn lock_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, LockAgent<'info>>,
+        target_chain: u16,
+        recipient: [u8; 32],
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let agent_data = &ctx.accounts.agent_data;
+        require!(!agent_data.is_frozen, BridgeError::AgentFrozen);
+        let agent = agent_data.key();
+        let owner = ctx.accounts.owner.key();
+        let payload = BridgeMessage::Lock(AgentState {
+            agent,
+            owner,
+            recipient,
+            name: agent_data.name.clone(),
+            metadata_uri: agent_data.metadata_uri.clone(),
+            personality_traits: agent_data.personality_traits.clone(),
+            creation_date: agent_data.creation_date,
+            generation: agent_data.generation,
+            parent_one: agent_data.parent_one,
+            parent_two: agent_data.parent_two,
+            match_count: agent_data.match_count,
+            interaction_count: agent_data.interaction_count,
+            wins: agent_data.wins,
+            losses: agent_data.losses,
+        })
+        .try_to_vec()?;
+
+        let lock_bump = *ctx.bumps.get("agent_lock").unwrap();
+        let lock_seeds: &[&[u8]] = &[b"agent_lock", agent.as_ref(), &[lock_bump]];
+        ctx.accounts.cnft_accounts.transfer(
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.agent_lock.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+            &cnft,
+            &[],
+        )?;
+        agent_registry::cpi::transfer_ownership(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            agent_registry::cpi::accounts::TransferOwnership {
+                owner: ctx.accounts.owner.to_account_info(),
+                new_owner: ctx.accounts.agent_lock.to_account_info(),
+                agent_data: ctx.accounts.agent_data.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                guardian: ctx.accounts.guardian.to_account_info(),
+            },
+            &[lock_seeds],
+        ))?;
+
+        let sequence = post_wormhole_message(&ctx, payload)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let lock = &mut ctx.accounts.agent_lock;
+        lock.agent = agent;
+        lock.owner = owner;
+        lock.merkle_tree = ctx.accounts.cnft_accounts.merkle_tree.key();
+        lock.target_chain = target_chain;
+        lock.recipient = recipient;
+        lock.sequence = sequence;
+        lock.locked_at = now;
+        lock.bump = lock_bump;
+
+        let config = &mut ctx.accounts.config;
+        config.message_count = config.message_count.checked_add(1).unwrap_or(u64::MAX);
+        config.locked_count = config.locked_count.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(AgentLocked {
+            agent,
+            owner,
+            target_chain,
+            recipient,
+            sequence,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Unlock a returning agent from a verified Wormhole message sent by the
+    // registered contract on its chain. The agent and its cNFT go to the
+    // Solana recipient named in the message; each message redeems once.
+    pub fn redeem_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, RedeemAgent<'info>>,
+        emitter_chain: u16,
+        sequence: u64,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let vaa = read_posted_vaa(&ctx.accounts.posted_vaa)?;
+        let foreign_emitter = &ctx.accounts.foreign_emitter;
+        require!(
+            vaa.emitter_chain == emitter_chain
+                && vaa.sequence == sequence
+                && vaa.emitter_chain == foreign_emitter.chain
+                && vaa.emitter_address == foreign_emitter.address,
+            BridgeError::UnknownEmitter
+        );
+        let BridgeMessage::Return { agent, recipient } =
+            BridgeMessage::try_from_slice(&vaa.payload).map_err(|_| BridgeError::InvalidPayload)?
+        else {
+            return err!(BridgeError::InvalidPayload);
+        };
+        let lock = &ctx.accounts.agent_lock;
+        require_keys_eq!(agent, lock.agent, BridgeError::InvalidPayload);
+        require_keys_eq!(
+            recipient,
+            ctx.accounts.recipient.key(),
+            BridgeError::InvalidPayload
+        );
+
+        let lock_seeds: &[&[u8]] = &[b"agent_lock", lock.agent.as_ref(), &[lock.bump]];
+        ctx.accounts.cnft_accounts.transfer(
+            lock.to_account_info(),
+            ctx.accounts.recipient.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+            &cnft,
+            &[lock_seeds],
+        )?;
+        agent_registry::cpi::transfer_ownership(
+            CpiContext::new_with_signer(
+                ctx.accounts.registry_program.to_account_info(),
+                agent_registry::cpi::accounts::TransferOwnership {
+                    owner: lock.to_account_info(),
+                    new_owner: ctx.accounts.recipient.to_account_info(),
+                    agent_data: ctx.accounts.agent_data.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    guardian: ctx.accounts.guardian.to_account_info(),
+                },
+                &[lock_seeds],
+            ),
+        )?;
+
+        let received = &mut ctx.accounts.received;
+        received.emitter_chain = emitter_chain;
+        received.sequence = sequence;
+        received.agent = agent;
+        received.bump = *ctx.bumps.get("received").unwrap();
+
+        let config = &mut ctx.accounts.config;
+        config.locked_count = config.locked_count.saturating_sub(1);
+
+        emit!(AgentRedeemed {
+            agent,
+            recipient,
+            emitter_chain,
+            sequence,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct BridgeConfig {
+    pub admin: Pubkey,
+    pub message_count: u64,  // Seeds the next Wormhole message account
+    pub locked_count: u64,   // Agents currently bridged out
+    pub bump: u8,
+}
+
+// Contract on another chain allowed to return agents
+#[account]
+pub struct ForeignEmitter {
+    pub chain: u16,  // Wormhole chain id
+    pub address: [u8; 32],
+    pub bump: u8,
+}
+
+// A bridged-out agent; the PDA holds its cNFT and registry ownership
+#[account]
+pub struct AgentLock {
+    pub agent: Pubkey,
+    pub owner: Pubkey,  // Owner when locked; receives the lock's rent on return
+    pub merkle_tree: Pubkey,
+    pub target_chain: u16,
+    pub recipient: [u8; 32],  // Address on the target chain
+    pub sequence: u64,        // Wormhole sequence of the lock message
+    pub locked_at: i64,
+    pub bump: u8,
+}
+
+// Marks a return message as redeemed
+#[account]
+pub struct ReceivedMessage {
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    pub agent: Pubkey,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeBridge<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<BridgeConfig>(),
+        seeds = [b"bridge_config"],
+        bump
+    )]
+    pub config: Account<'info, BridgeConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain: u16)]
+pub struct RegisterForeignEmitter<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"bridge_config"],
+        bump = config.bump,
+        has_one = admin @ BridgeError::Unauthorized,
+    )]
+    pub config: Account<'info, BridgeConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + std::mem::size_of::<ForeignEmitter>(),
+        seeds = [b"foreign_emitter", chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub foreign_emitter: Account<'info, ForeignEmitter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Bubblegum accounts needed to move an agent's cNFT; mirrors
+// marketplace::CnftAccounts
+#[derive(Accounts)]
+pub struct CnftAccounts<'info> {
+    /// CHECK: Bubblegum tree config; validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_chain: u16)]
+pub struct LockAgent<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, seeds = [b"bridge_config"], bump = config.bump)]
+    pub config: Box<Account<'info, BridgeConfig>>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == owner.key() @ BridgeError::Unauthorized,
+    )]
+    pub agent_data: Box<Account<'info, AgentData>>,
+
+    // Only chains with a registered contract can receive agents
+    #[account(seeds = [b"foreign_emitter", target_chain.to_le_bytes().as_ref()], bump = foreign_emitter.bump)]
+    pub foreign_emitter: Box<Account<'info, ForeignEmitter>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<AgentLock>(),
+        seeds = [b"agent_lock", agent_data.key().as_ref()],
+        bump
+    )]
+    pub agent_lock: Box<Account<'info, AgentLock>>,
+
+    /// CHECK: PDA that emits this program's Wormhole messages
+    #[account(seeds = [b"emitter"], bump)]
+    pub emitter: UncheckedAccount<'info>,
+
+    /// CHECK: Wormhole message account, created by the core bridge
+    #[account(mut, seeds = [b"message", config.message_count.to_le_bytes().as_ref()], bump)]
+    pub wormhole_message: UncheckedAccount<'info>,
+
+    /// CHECK: Wormhole core bridge config
+    #[account(mut, seeds = [b"Bridge"], bump, seeds::program = WORMHOLE_PROGRAM_ID)]
+    pub wormhole_bridge: UncheckedAccount<'info>,
+
+    /// CHECK: Wormhole sequence tracker for the emitter
+    #[account(
+        mut,
+        seeds = [b"Sequence", emitter.key().as_ref()],
+        bump,
+        seeds::program = WORMHOLE_PROGRAM_ID
+    )]
+    pub wormhole_sequence: UncheckedAccount<'info>,
+
+    /// CHECK: Wormhole fee collector
+    #[account(mut, seeds = [b"fee_collector"], bump, seeds::program = WORMHOLE_PROGRAM_ID)]
+    pub wormhole_fee_collector: UncheckedAccount<'info>,
+
+    /// CHECK: Wormhole core bridge program
+    #[account(address = WORMHOLE_PROGRAM_ID, executable)]
+    pub wormhole_program: UncheckedAccount<'info>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(emitter_chain: u16, sequence: u64)]
+pub struct RedeemAgent<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"bridge_config"], bump = config.bump)]
+    pub config: Box<Account<'info, BridgeConfig>>,
+
+    /// CHECK: Wormhole PostedVAA account, checked in `read_posted_vaa`
+    pub posted_vaa: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"foreign_emitter", emitter_chain.to_le_bytes().as_ref()], bump = foreign_emitter.bump)]
+    pub foreign_emitter: Box<Account<'info, ForeignEmitter>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<ReceivedMessage>(),
+        seeds = [
+            b"received",
+            emitter_chain.to_le_bytes().as_ref(),
+            sequence.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub received: Box<Account<'info, ReceivedMessage>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent_lock", agent_lock.agent.as_ref()],
+        bump = agent_lock.bump,
+        constraint = agent_lock.merkle_tree == cnft_accounts.merkle_tree.key() @ BridgeError::TreeMismatch,
+        close = lock_owner,
+    )]
+    pub agent_lock: Box<Account<'info, AgentLock>>,
+
+    /// CHECK: receives the lock's rent
+    #[account(mut, address = agent_lock.owner)]
+    pub lock_owner: UncheckedAccount<'info>,
+
+    /// CHECK: the Solana owner named in the return message
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(mut, address = agent_lock.agent)]
+    pub agent_data: Box<Account<'info, AgentData>>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+// Current Bubblegum leaf of the agent's cNFT, as reported by the DAS API
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CnftArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+// Borsh-encoded Wormhole payloads exchanged with foreign contracts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum BridgeMessage {
+    Lock(AgentState),
+    Return { agent: Pubkey, recipient: Pubkey },
+}
+
+// Canonical agent state carried to the target chain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AgentState {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub recipient: [u8; 32],
+    pub name: String,
+    pub metadata_uri: String,
+    pub personality_traits: PersonalityTraits,
+    pub creation_date: i64,
+    pub generation: u16,
+    pub parent_one: Pubkey,
+    pub parent_two: Pubkey,
+    pub match_count: u32,
+    pub interaction_count: u32,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+// Fields of a verified Wormhole message this program relies on
+pub struct PostedVaa {
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+// Events
+#[event]
+pub struct ForeignEmitterRegistered {
+    pub chain: u16,
+    pub address: [u8; 32],
+}
+
+#[event]
+pub struct AgentLocked {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub target_chain: u16,
+    pub recipient: [u8; 32],
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AgentRedeemed {
+    pub agent: Pubkey,
+    pub recipient: Pubkey,
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+
+// Custom errors
+#[error_code]
+pub enum BridgeError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Agent has been frozen by the registry moderator")]
+    AgentFrozen,
+    #[msg("Merkle tree does not hold this agent's cNFT")]
+    TreeMismatch,
+    #[msg("Account is not a verified Wormhole message")]
+    InvalidVaa,
+    #[msg("Message was not sent by the registered contract")]
+    UnknownEmitter,
+    #[msg("Message payload does not match this agent")]
+    InvalidPayload,
+}
+
+// Helper functions
+impl<'info> CnftAccounts<'info> {
+    // Transfer the cNFT leaf, optionally signing as a PDA leaf owner
+    pub fn transfer(
+        &self,
+        leaf_owner: AccountInfo<'info>,
+        new_leaf_owner: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        proof: &[AccountInfo<'info>],
+        cnft: &CnftArgs,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_accounts = mpl_bubblegum::accounts::Transfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: leaf_owner.clone(),
+            leaf_delegate: leaf_owner,
+            new_leaf_owner,
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.bubblegum_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(proof.to_vec());
+        mpl_bubblegum::cpi::transfer(
+            cpi_ctx,
+            cnft.root,
+            cnft.data_hash,
+            cnft.creator_hash,
+            cnft.nonce,
+            cnft.index,
+        )
+    }
+}
+
+// Pay the core bridge fee and post `payload` from this program's emitter.
+// Returns the sequence number Wormhole assigns to the message.
+fn post_wormhole_message<'info>(
+    ctx: &Context<'_, '_, '_, 'info, LockAgent<'info>>,
+    payload: Vec<u8>,
+) -> Result<u64> {
+    let accounts = &ctx.accounts;
+    let fee = {
+        let data = accounts.wormhole_bridge.try_borrow_data()?;
+        require!(
+            data.len() >= WORMHOLE_FEE_OFFSET + 8,
+            BridgeError::InvalidVaa
+        );
+        u64::from_le_bytes(data[WORMHOLE_FEE_OFFSET..WORMHOLE_FEE_OFFSET + 8].try_into().unwrap())
+    };
+    if fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: accounts.owner.to_account_info(),
+                    to: accounts.wormhole_fee_collector.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+    let sequence = {
+        let data = accounts.wormhole_sequence.try_borrow_data()?;
+        if data.len() >= 8 {
+            u64::from_le_bytes(data[..8].try_into().unwrap())
+        } else {
+            0
+        }
+    };
+
+    let mut data = vec![WORMHOLE_POST_MESSAGE];
+    0u32.serialize(&mut data)?;  // Nonce; unused
+    payload.serialize(&mut data)?;
+    WORMHOLE_CONSISTENCY_FINALIZED.serialize(&mut data)?;
+    let instruction = Instruction {
+        program_id: WORMHOLE_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(accounts.wormhole_bridge.key(), false),
+            AccountMeta::new(accounts.wormhole_message.key(), true),
+            AccountMeta::new_readonly(accounts.emitter.key(), true),
+            AccountMeta::new(accounts.wormhole_sequence.key(), false),
+            AccountMeta::new(accounts.owner.key(), true),
+            AccountMeta::new(accounts.wormhole_fee_collector.key(), false),
+            AccountMeta::new_readonly(accounts.clock.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.rent.key(), false),
+        ],
+        data,
+    };
+    let message_count = accounts.config.message_count.to_le_bytes();
+    invoke_signed(
+        &instruction,
+        &[
+            accounts.wormhole_bridge.to_account_info(),
+            accounts.wormhole_message.to_account_info(),
+            accounts.emitter.to_account_info(),
+            accounts.wormhole_sequence.to_account_info(),
+            accounts.owner.to_account_info(),
+            accounts.wormhole_fee_collector.to_account_info(),
+            accounts.clock.to_account_info(),
+            accounts.system_program.to_account_info(),
+            accounts.rent.to_account_info(),
+            accounts.wormhole_program.to_account_info(),
+        ],
+        &[
+            &[b"emitter", &[*ctx.bumps.get("emitter").unwrap()]],
+            &[
+                b"message",
+                message_count.as_ref(),
+                &[*ctx.bumps.get("wormhole_message").unwrap()],
+            ],
+        ],
+    )?;
+
+    Ok(sequence)
+}
+
+// Read a PostedVAA account written by the core bridge once the guardians'
+// signatures were verified
+fn read_posted_vaa(posted_vaa: &AccountInfo) -> Result<PostedVaa> {
+    require_keys_eq!(*posted_vaa.owner, WORMHOLE_PROGRAM_ID, BridgeError::InvalidVaa);
+    let data = posted_vaa.try_borrow_data()?;
+    require!(
+        data.len() >= POSTED_VAA_PAYLOAD_OFFSET + 4 && &data[..3] == b"vaa",
+        BridgeError::InvalidVaa
+    );
+    let read_u64 = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+    let mut emitter_address = [0u8; 32];
+    emitter_address.copy_from_slice(
+        &data[POSTED_VAA_EMITTER_ADDRESS_OFFSET..POSTED_VAA_EMITTER_ADDRESS_OFFSET + 32],
+    );
+    let payload = Vec::<u8>::deserialize(&mut &data[POSTED_VAA_PAYLOAD_OFFSET..])
+        .map_err(|_| BridgeError::InvalidVaa)?;
+
+    Ok(PostedVaa {
+        sequence: read_u64(POSTED_VAA_SEQUENCE_OFFSET),
+        emitter_chain: u16::from_le_bytes([
+            data[POSTED_VAA_EMITTER_CHAIN_OFFSET],
+            data[POSTED_VAA_EMITTER_CHAIN_OFFSET + 1],
+        ]),
+        emitter_address,
+        payload,
+    })
+}
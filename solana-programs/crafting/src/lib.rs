@@ -0,0 +1,375 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use agent_registry::AgentData;
+use items::program::Items;
+use items::{Inventory, ItemBalance, ItemType};
+
+declare_id!("Craft1ngPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_RECIPE_INPUTS: usize = 4;
+
+#[program]
+pub mod crafting {
+    use super::*;
+
+    // Create the crafting config. Recipes are administered by governance:
+    // every admin instruction must be signed by the governance authority PDA
+    // through an executed proposal, and that PDA pays for new accounts.
+    pub fn initialize_crafting(
+        ctx: Context<InitializeCrafting>,
+        vrf_authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.vrf_authority = vrf_authority;
+        config.recipe_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Change the VRF authority (governance)
+    pub fn set_vrf_authority(ctx: Context<UpdateCraftingConfig>, vrf_authority: Pubkey) -> Result<()> {
+        ctx.accounts.config.vrf_authority = vrf_authority;
+
+        emit!(VrfAuthorityUpdated { vrf_authority });
+
+        Ok(())
+    }
+
+    // Add a recipe (governance). Inputs and output must be fungible items.
+    pub fn create_recipe(
+        ctx: Context<CreateRecipe>,
+        recipe_id: u32,
+        inputs: Vec<ItemBalance>,
+        output_item_id: u16,
+        output_quantity: u32,
+        success_bps: u16,
+    ) -> Result<()> {
+        require!(
+            !inputs.is_empty()
+                && inputs.len() <= MAX_RECIPE_INPUTS
+                && inputs.iter().all(|input| input.amount > 0)
+                && output_quantity > 0
+                && success_bps > 0
+                && success_bps <= BPS_DENOMINATOR,
+            CraftingError::InvalidRecipe
+        );
+        // Each input item may appear only once
+        for (i, input) in inputs.iter().enumerate() {
+            require!(
+                !inputs[..i].iter().any(|other| other.item_id == input.item_id),
+                CraftingError::InvalidRecipe
+            );
+        }
+
+        let recipe = &mut ctx.accounts.recipe;
+        recipe.recipe_id = recipe_id;
+        recipe.inputs = inputs.clone();
+        recipe.output_item_id = output_item_id;
+        recipe.output_quantity = output_quantity;
+        recipe.success_bps = success_bps;
+        recipe.active = true;
+        recipe.attempts = 0;
+        recipe.successes = 0;
+        recipe.bump = *ctx.bumps.get("recipe").unwrap();
+
+        let config = &mut ctx.accounts.config;
+        config.recipe_count = config.recipe_count.checked_add(1).unwrap_or(u32::MAX);
+
+        emit!(RecipeCreated {
+            recipe_id,
+            inputs,
+            output_item_id,
+            output_quantity,
+            success_bps,
+        });
+
+        Ok(())
+    }
+
+    // Change a recipe's success chance or retire it (governance)
+    pub fn update_recipe(ctx: Context<UpdateRecipe>, success_bps: u16, active: bool) -> Result<()> {
+        require!(
+            success_bps > 0 && success_bps <= BPS_DENOMINATOR,
+            CraftingError::InvalidRecipe
+        );
+
+        let recipe = &mut ctx.accounts.recipe;
+        recipe.success_bps = success_bps;
+        recipe.active = active;
+
+        emit!(RecipeUpdated {
+            recipe_id: recipe.recipe_id,
+            success_bps,
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Craft a recipe for an agent. The inputs are always burned from the
+    // agent's inventory; the output is granted only if the roll succeeds.
+    // When the config names a VRF authority, it must sign and supply the
+    // randomness; otherwise recent slot hashes are used.
+    pub fn craft(ctx: Context<Craft>, randomness: Option<[u8; 32]>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let recipe = &ctx.accounts.recipe;
+        require!(recipe.active, CraftingError::RecipeInactive);
+
+        let seed = armour_randomness::resolve_seed(
+            config.vrf_authority,
+            ctx.accounts.vrf_authority.as_ref(),
+            randomness,
+            &ctx.accounts.recent_slothashes,
+        )?;
+        let agent = ctx.accounts.agent_data.key();
+        let entropy = armour_randomness::derive(
+            &seed,
+            &[
+                agent.as_ref(),
+                recipe.key().as_ref(),
+                &recipe.attempts.to_le_bytes(),
+            ],
+        );
+        let success = armour_randomness::roll_bps(&entropy) < recipe.success_bps;
+
+        let authority_seeds: &[&[u8]] = &[
+            b"crafting_authority",
+            &[*ctx.bumps.get("crafting_authority").unwrap()],
+        ];
+        items::cpi::burn_items(
+            CpiContext::new_with_signer(
+                ctx.accounts.items_program.to_account_info(),
+                items::cpi::accounts::BurnItems {
+                    crafting_authority: ctx.accounts.crafting_authority.to_account_info(),
+                    inventory: ctx.accounts.inventory.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            recipe.inputs.clone(),
+        )?;
+
+        if success {
+            items::cpi::grant_items(
+                CpiContext::new_with_signer(
+                    ctx.accounts.items_program.to_account_info(),
+                    items::cpi::accounts::GrantItems {
+                        crafting_authority: ctx.accounts.crafting_authority.to_account_info(),
+                        payer: ctx.accounts.owner.to_account_info(),
+                        item_type: ctx.accounts.output_item_type.to_account_info(),
+                        agent_data: ctx.accounts.agent_data.to_account_info(),
+                        inventory: ctx.accounts.inventory.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                    },
+                    &[authority_seeds],
+                ),
+                recipe.output_quantity,
+            )?;
+        }
+
+        let recipe = &mut ctx.accounts.recipe;
+        recipe.attempts = recipe.attempts.checked_add(1).unwrap_or(u64::MAX);
+        if success {
+            recipe.successes = recipe.successes.checked_add(1).unwrap_or(u64::MAX);
+        }
+
+        emit!(ItemCrafted {
+            agent,
+            recipe_id: recipe.recipe_id,
+            success,
+            output_item_id: recipe.output_item_id,
+            output_quantity: if success { recipe.output_quantity } else { 0 },
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct CraftingConfig {
+    pub vrf_authority: Pubkey,  // Pubkey::default() to use slot hashes instead of VRF
+    pub recipe_count: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Recipe {
+    pub recipe_id: u32,
+    pub inputs: Vec<ItemBalance>,  // Fungible items burned per attempt
+    pub output_item_id: u16,
+    pub output_quantity: u32,
+    pub success_bps: u16,          // Chance the attempt yields the output
+    pub active: bool,
+    pub attempts: u64,
+    pub successes: u64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeCrafting<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = governance_authority,
+        space = 8 + std::mem::size_of::<CraftingConfig>(),
+        seeds = [b"crafting_config"],
+        bump
+    )]
+    pub config: Account<'info, CraftingConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCraftingConfig<'info> {
+    #[account(
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"crafting_config"], bump = config.bump)]
+    pub config: Account<'info, CraftingConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipe_id: u32)]
+pub struct CreateRecipe<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"crafting_config"], bump = config.bump)]
+    pub config: Account<'info, CraftingConfig>,
+
+    #[account(
+        init,
+        payer = governance_authority,
+        space = 8 + std::mem::size_of::<Recipe>()
+            + MAX_RECIPE_INPUTS * std::mem::size_of::<ItemBalance>(),
+        seeds = [b"recipe", recipe_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRecipe<'info> {
+    #[account(
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub recipe: Account<'info, Recipe>,
+}
+
+#[derive(Accounts)]
+pub struct Craft<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"crafting_config"], bump = config.bump)]
+    pub config: Account<'info, CraftingConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"recipe", recipe.recipe_id.to_le_bytes().as_ref()],
+        bump = recipe.bump,
+    )]
+    pub recipe: Account<'info, Recipe>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ CraftingError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory", agent_data.key().as_ref()],
+        bump = inventory.bump,
+        seeds::program = items::ID
+    )]
+    pub inventory: Account<'info, Inventory>,
+
+    #[account(
+        mut,
+        constraint = output_item_type.item_id == recipe.output_item_id @ CraftingError::InvalidOutputItem,
+    )]
+    pub output_item_type: Account<'info, ItemType>,
+
+    /// CHECK: PDA that signs inventory updates in the items program
+    #[account(seeds = [b"crafting_authority"], bump)]
+    pub crafting_authority: UncheckedAccount<'info>,
+
+    // Required when the config names a VRF authority
+    pub vrf_authority: Option<Signer<'info>>,
+
+    /// CHECK: slot hashes sysvar, read for fallback randomness
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+
+    pub items_program: Program<'info, Items>,
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct VrfAuthorityUpdated {
+    pub vrf_authority: Pubkey,
+}
+
+#[event]
+pub struct RecipeCreated {
+    pub recipe_id: u32,
+    pub inputs: Vec<ItemBalance>,
+    pub output_item_id: u16,
+    pub output_quantity: u32,
+    pub success_bps: u16,
+}
+
+#[event]
+pub struct RecipeUpdated {
+    pub recipe_id: u32,
+    pub success_bps: u16,
+    pub active: bool,
+}
+
+#[event]
+pub struct ItemCrafted {
+    pub agent: Pubkey,
+    pub recipe_id: u32,
+    pub success: bool,
+    pub output_item_id: u16,
+    pub output_quantity: u32,  // 0 when the roll failed
+}
+
+// Custom errors
+#[error_code]
+pub enum CraftingError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Recipe inputs, output or success chance are invalid")]
+    InvalidRecipe,
+    #[msg("Recipe is not active")]
+    RecipeInactive,
+    #[msg("Output item does not match the recipe")]
+    InvalidOutputItem,
+}
@@ -0,0 +1,177 @@
+// Interface for programs and clients that hold funds or agents through the
+// escrow program: the program id and account layouts, PDA finders, and
+// instruction builders. The `cpi` feature adds the program's Anchor CPI
+// module. A program that settles escrows itself names its own id in a
+// `ReleaseCondition::CpiCaller` and signs with `pda::find_escrow_authority`.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::get_associated_token_address;
+
+pub use escrow::{
+    accounts, escrow_authority, id, instruction, program, CnftArgs, Escrow, EscrowAsset,
+    EscrowError, ReleaseCondition, ESCROW_AUTHORITY_SEED, ESCROW_SEED, ESCROW_VAULT_SEED, ID,
+};
+
+#[cfg(feature = "cpi")]
+pub use escrow::cpi;
+
+pub mod pda {
+    use super::*;
+
+    pub fn find_escrow_address(depositor: &Pubkey, reference: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ESCROW_SEED, depositor.as_ref(), reference.as_ref()], &ID)
+    }
+
+    pub fn find_vault_address(escrow: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ESCROW_VAULT_SEED, escrow.as_ref()], &ID)
+    }
+
+    // The signer `program` uses to settle escrows whose condition names it
+    pub fn find_escrow_authority(program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ESCROW_AUTHORITY_SEED], program)
+    }
+}
+
+pub mod builders {
+    use super::*;
+
+    pub fn deposit_sol(
+        depositor: Pubkey,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        release_condition: ReleaseCondition,
+        refund_condition: ReleaseCondition,
+    ) -> Instruction {
+        build(
+            accounts::DepositSol {
+                depositor,
+                escrow: pda::find_escrow_address(&depositor, &reference).0,
+                system_program: system_program::ID,
+            },
+            instruction::DepositSol {
+                reference,
+                beneficiary,
+                amount,
+                release_condition,
+                refund_condition,
+            },
+        )
+    }
+
+    // Deposit from the depositor's associated token account
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_tokens(
+        depositor: Pubkey,
+        mint: Pubkey,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        release_condition: ReleaseCondition,
+        refund_condition: ReleaseCondition,
+    ) -> Instruction {
+        let escrow = pda::find_escrow_address(&depositor, &reference).0;
+        build(
+            accounts::DepositTokens {
+                depositor,
+                escrow,
+                vault: pda::find_vault_address(&escrow).0,
+                mint,
+                depositor_token_account: get_associated_token_address(&depositor, &mint),
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::DepositTokens {
+                reference,
+                beneficiary,
+                amount,
+                release_condition,
+                refund_condition,
+            },
+        )
+    }
+
+    // Release (`refund` false) or refund SOL. `oracle_worker` is the
+    // worker account when the condition is an oracle attestation.
+    pub fn settle_sol(
+        authority: Pubkey,
+        depositor: Pubkey,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        refund: bool,
+        oracle_worker: Option<Pubkey>,
+    ) -> Instruction {
+        let accounts = accounts::SettleSol {
+            authority,
+            escrow: pda::find_escrow_address(&depositor, &reference).0,
+            depositor,
+            beneficiary,
+            oracle_worker,
+        };
+        if refund {
+            build(accounts, instruction::RefundSol { amount })
+        } else {
+            build(accounts, instruction::ReleaseSol { amount })
+        }
+    }
+
+    // Release or refund tokens between the parties' associated token accounts
+    #[allow(clippy::too_many_arguments)]
+    pub fn settle_tokens(
+        authority: Pubkey,
+        depositor: Pubkey,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        refund: bool,
+        oracle_worker: Option<Pubkey>,
+    ) -> Instruction {
+        let escrow = pda::find_escrow_address(&depositor, &reference).0;
+        let accounts = accounts::SettleTokens {
+            authority,
+            escrow,
+            vault: pda::find_vault_address(&escrow).0,
+            depositor,
+            depositor_token_account: get_associated_token_address(&depositor, &mint),
+            beneficiary_token_account: get_associated_token_address(&beneficiary, &mint),
+            oracle_worker,
+            token_program: anchor_spl::token::ID,
+        };
+        if refund {
+            build(accounts, instruction::RefundTokens { amount })
+        } else {
+            build(accounts, instruction::ReleaseTokens { amount })
+        }
+    }
+
+    // Redirect an escrow to a new beneficiary; `authority` is the escrow
+    // authority of the program its condition names
+    pub fn set_beneficiary(
+        authority: Pubkey,
+        depositor: Pubkey,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+    ) -> Instruction {
+        build(
+            accounts::SetBeneficiary {
+                authority,
+                escrow: pda::find_escrow_address(&depositor, &reference).0,
+            },
+            instruction::SetBeneficiary { beneficiary },
+        )
+    }
+
+    // Any instruction from its Anchor account and argument structs
+    pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+}
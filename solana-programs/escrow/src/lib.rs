@@ -0,0 +1,740 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use oracle::Worker;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+
+declare_id!("EscrowPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const ESCROW_VAULT_SEED: &[u8] = b"escrow_vault";
+// Programs named in a `CpiCaller` condition sign with their PDA at this seed
+pub const ESCROW_AUTHORITY_SEED: &[u8] = b"escrow_authority";
+
+#[program]
+pub mod escrow {
+    use super::*;
+
+    // Escrow `amount` lamports for `beneficiary`. `reference` distinguishes a
+    // depositor's escrows, e.g. the offer or date the deposit backs.
+    pub fn deposit_sol(
+        ctx: Context<DepositSol>,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        release_condition: ReleaseCondition,
+        refund_condition: ReleaseCondition,
+    ) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.open(
+            ctx.accounts.depositor.key(),
+            beneficiary,
+            reference,
+            EscrowAsset::Sol,
+            amount,
+            release_condition,
+            refund_condition,
+            *ctx.bumps.get("escrow").unwrap(),
+        )?;
+
+        Ok(())
+    }
+
+    // Escrow `amount` SPL tokens for `beneficiary` in a vault owned by the escrow PDA
+    pub fn deposit_tokens(
+        ctx: Context<DepositTokens>,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        release_condition: ReleaseCondition,
+        refund_condition: ReleaseCondition,
+    ) -> Result<()> {
+        require!(amount > 0, EscrowError::InvalidAmount);
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.open(
+            ctx.accounts.depositor.key(),
+            beneficiary,
+            reference,
+            EscrowAsset::Token { mint: ctx.accounts.mint.key() },
+            amount,
+            release_condition,
+            refund_condition,
+            *ctx.bumps.get("escrow").unwrap(),
+        )?;
+
+        Ok(())
+    }
+
+    // Take custody of a cNFT for `beneficiary`: the leaf is transferred to the
+    // escrow PDA until released or refunded. Bubblegum proof accounts are
+    // passed as remaining accounts.
+    pub fn deposit_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositCnft<'info>>,
+        reference: Pubkey,
+        beneficiary: Pubkey,
+        release_condition: ReleaseCondition,
+        refund_condition: ReleaseCondition,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        ctx.accounts.cnft_accounts.transfer(
+            ctx.accounts.depositor.to_account_info(),
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+            &cnft,
+            &[],
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.open(
+            ctx.accounts.depositor.key(),
+            beneficiary,
+            reference,
+            EscrowAsset::Cnft { merkle_tree: ctx.accounts.cnft_accounts.merkle_tree.key() },
+            1,
+            release_condition,
+            refund_condition,
+            *ctx.bumps.get("escrow").unwrap(),
+        )?;
+
+        Ok(())
+    }
+
+    // Pay `amount` lamports to the beneficiary once the release condition is
+    // met. The escrow closes to the depositor when emptied.
+    pub fn release_sol(ctx: Context<SettleSol>, amount: u64) -> Result<()> {
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        settle_sol(ctx, amount, Settlement::Release, beneficiary)
+    }
+
+    // Return `amount` lamports to the depositor once the refund condition is met
+    pub fn refund_sol(ctx: Context<SettleSol>, amount: u64) -> Result<()> {
+        let depositor = ctx.accounts.depositor.to_account_info();
+        settle_sol(ctx, amount, Settlement::Refund, depositor)
+    }
+
+    // Pay `amount` tokens to the beneficiary once the release condition is
+    // met. The escrow and its vault close to the depositor when emptied.
+    pub fn release_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleTokens<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let beneficiary_token_account = ctx.accounts.beneficiary_token_account.to_account_info();
+        settle_tokens(ctx, amount, Settlement::Release, beneficiary_token_account)
+    }
+
+    // Return `amount` tokens to the depositor once the refund condition is met
+    pub fn refund_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleTokens<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let depositor_token_account = ctx.accounts.depositor_token_account.to_account_info();
+        settle_tokens(ctx, amount, Settlement::Refund, depositor_token_account)
+    }
+
+    // Transfer the escrowed cNFT to the beneficiary once the release condition is met
+    pub fn release_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleCnft<'info>>,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        settle_cnft(ctx, cnft, Settlement::Release, beneficiary)
+    }
+
+    // Return the escrowed cNFT to the depositor once the refund condition is met
+    pub fn refund_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleCnft<'info>>,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let depositor = ctx.accounts.depositor.to_account_info();
+        settle_cnft(ctx, cnft, Settlement::Refund, depositor)
+    }
+
+    // Name a new beneficiary (the program named in a `CpiCaller` release
+    // condition). A program that settles an escrow itself can pick the
+    // payee at settlement, e.g. a listing's buyer.
+    pub fn set_beneficiary(ctx: Context<SetBeneficiary>, beneficiary: Pubkey) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        require!(
+            matches!(escrow.release_condition, ReleaseCondition::CpiCaller { .. }),
+            EscrowError::Unauthorized
+        );
+        require_condition(escrow, Settlement::Release, &ctx.accounts.authority.key(), None)?;
+        require!(beneficiary != escrow.depositor, EscrowError::InvalidBeneficiary);
+        escrow.beneficiary = beneficiary;
+
+        emit!(EscrowBeneficiaryUpdated {
+            escrow: escrow.key(),
+            beneficiary,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+// One deposit. SOL is held in the escrow account itself, tokens in its vault
+// and cNFTs as a leaf owned by the escrow PDA.
+#[account]
+pub struct Escrow {
+    pub depositor: Pubkey,  // Receives refunds and the rent when the escrow closes
+    pub beneficiary: Pubkey,
+    pub reference: Pubkey,
+    pub asset: EscrowAsset,
+    pub amount: u64,        // Still held; 1 for a cNFT
+    pub release_condition: ReleaseCondition,
+    pub refund_condition: ReleaseCondition,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Escrow {
+    #[allow(clippy::too_many_arguments)]
+    fn open(
+        &mut self,
+        depositor: Pubkey,
+        beneficiary: Pubkey,
+        reference: Pubkey,
+        asset: EscrowAsset,
+        amount: u64,
+        release_condition: ReleaseCondition,
+        refund_condition: ReleaseCondition,
+        bump: u8,
+    ) -> Result<()> {
+        require!(beneficiary != depositor, EscrowError::InvalidBeneficiary);
+        self.depositor = depositor;
+        self.beneficiary = beneficiary;
+        self.reference = reference;
+        self.asset = asset.clone();
+        self.amount = amount;
+        self.release_condition = release_condition.clone();
+        self.refund_condition = refund_condition.clone();
+        self.created_at = Clock::get()?.unix_timestamp;
+        self.bump = bump;
+
+        emit!(EscrowCreated {
+            depositor,
+            beneficiary,
+            reference,
+            asset,
+            amount,
+            release_condition,
+            refund_condition,
+        });
+
+        Ok(())
+    }
+
+    fn condition(&self, settlement: Settlement) -> &ReleaseCondition {
+        match settlement {
+            Settlement::Release => &self.release_condition,
+            Settlement::Refund => &self.refund_condition,
+        }
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct DepositSol<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + std::mem::size_of::<Escrow>(),
+        seeds = [ESCROW_SEED, depositor.key().as_ref(), reference.as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct DepositTokens<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + std::mem::size_of::<Escrow>(),
+        seeds = [ESCROW_SEED, depositor.key().as_ref(), reference.as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        init,
+        payer = depositor,
+        seeds = [ESCROW_VAULT_SEED, escrow.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = depositor_token_account.mint == mint.key())]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(reference: Pubkey)]
+pub struct DepositCnft<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + std::mem::size_of::<Escrow>(),
+        seeds = [ESCROW_SEED, depositor.key().as_ref(), reference.as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleSol<'info> {
+    // Whoever satisfies the condition: the counterparty, the oracle worker's
+    // signing key, the calling program's escrow authority PDA, or anyone
+    // after a timeout
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.depositor.as_ref(), escrow.reference.as_ref()],
+        bump = escrow.bump,
+        has_one = depositor,
+        has_one = beneficiary,
+        constraint = escrow.asset == EscrowAsset::Sol @ EscrowError::AssetMismatch,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: the escrow's depositor, checked by has_one
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: the escrow's beneficiary, checked by has_one
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    // Required when the condition being met is an oracle attestation
+    pub oracle_worker: Option<Account<'info, Worker>>,
+}
+
+#[derive(Accounts)]
+pub struct SettleTokens<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.depositor.as_ref(), escrow.reference.as_ref()],
+        bump = escrow.bump,
+        has_one = depositor,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(mut, seeds = [ESCROW_VAULT_SEED, escrow.key().as_ref()], bump)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// CHECK: the escrow's depositor, checked by has_one
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.owner == escrow.depositor @ EscrowError::Unauthorized,
+        constraint = depositor_token_account.mint == vault.mint @ EscrowError::AssetMismatch,
+    )]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = beneficiary_token_account.owner == escrow.beneficiary @ EscrowError::Unauthorized,
+        constraint = beneficiary_token_account.mint == vault.mint @ EscrowError::AssetMismatch,
+    )]
+    pub beneficiary_token_account: Account<'info, TokenAccount>,
+
+    pub oracle_worker: Option<Account<'info, Worker>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleCnft<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.depositor.as_ref(), escrow.reference.as_ref()],
+        bump = escrow.bump,
+        has_one = depositor,
+        has_one = beneficiary,
+        constraint = escrow.asset == EscrowAsset::Cnft { merkle_tree: cnft_accounts.merkle_tree.key() }
+            @ EscrowError::AssetMismatch,
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: the escrow's depositor, checked by has_one
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: the escrow's beneficiary, checked by has_one
+    pub beneficiary: UncheckedAccount<'info>,
+
+    pub oracle_worker: Option<Account<'info, Worker>>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetBeneficiary<'info> {
+    // The escrow authority PDA of the program named in the release condition
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.depositor.as_ref(), escrow.reference.as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, Escrow>,
+}
+
+#[derive(Accounts)]
+pub struct CnftAccounts<'info> {
+    /// CHECK: Bubblegum tree config; validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum EscrowAsset {
+    Sol,
+    Token { mint: Pubkey },
+    Cnft { merkle_tree: Pubkey },
+}
+
+// Who may release (or refund) an escrow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ReleaseCondition {
+    // This key signs
+    Counterparty { signer: Pubkey },
+    // Anyone, at or after this unix time
+    Timeout { after: i64 },
+    // The signing key of an active oracle worker signs
+    Oracle { signing_key: Pubkey },
+    // This program signs through CPI with its ESCROW_AUTHORITY_SEED PDA
+    CpiCaller { program: Pubkey },
+    // Disabled
+    Never,
+}
+
+impl ReleaseCondition {
+    pub fn is_met(&self, authority: &Pubkey, oracle_worker: Option<&Worker>, now: i64) -> bool {
+        match self {
+            ReleaseCondition::Counterparty { signer } => authority == signer,
+            ReleaseCondition::Timeout { after } => now >= *after,
+            ReleaseCondition::Oracle { signing_key } => {
+                authority == signing_key
+                    && oracle_worker.map_or(false, |worker| worker.signing_key == *signing_key && worker.active)
+            }
+            ReleaseCondition::CpiCaller { program } => *authority == escrow_authority(program),
+            ReleaseCondition::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Settlement {
+    Release,
+    Refund,
+}
+
+// Current Bubblegum leaf of the escrowed cNFT, as reported by the DAS API
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CnftArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+// Program representing Metaplex Bubblegum
+#[derive(Clone)]
+pub struct Bubblegum;
+
+impl anchor_lang::Id for Bubblegum {
+    fn id() -> Pubkey {
+        mpl_bubblegum::id()
+    }
+}
+
+// Events
+#[event]
+pub struct EscrowCreated {
+    pub depositor: Pubkey,
+    pub beneficiary: Pubkey,
+    pub reference: Pubkey,
+    pub asset: EscrowAsset,
+    pub amount: u64,
+    pub release_condition: ReleaseCondition,
+    pub refund_condition: ReleaseCondition,
+}
+
+#[event]
+pub struct EscrowReleased {
+    pub escrow: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct EscrowBeneficiaryUpdated {
+    pub escrow: Pubkey,
+    pub beneficiary: Pubkey,
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub escrow: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum EscrowError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Amount must be positive and no more than the escrowed balance")]
+    InvalidAmount,
+    #[msg("The depositor cannot be the beneficiary")]
+    InvalidBeneficiary,
+    #[msg("The escrow holds a different asset")]
+    AssetMismatch,
+    #[msg("The escrow's release or refund condition has not been met")]
+    ConditionNotMet,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// The PDA a program signs with to satisfy a `CpiCaller` condition naming it
+pub fn escrow_authority(program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ESCROW_AUTHORITY_SEED], program).0
+}
+
+fn require_condition(
+    escrow: &Escrow,
+    settlement: Settlement,
+    authority: &Pubkey,
+    oracle_worker: Option<&Account<Worker>>,
+) -> Result<()> {
+    require!(
+        escrow
+            .condition(settlement)
+            .is_met(authority, oracle_worker.map(|worker| &**worker), Clock::get()?.unix_timestamp),
+        EscrowError::ConditionNotMet
+    );
+    Ok(())
+}
+
+fn emit_settlement(escrow: &Account<Escrow>, settlement: Settlement, amount: u64) {
+    match settlement {
+        Settlement::Release => emit!(EscrowReleased {
+            escrow: escrow.key(),
+            beneficiary: escrow.beneficiary,
+            amount,
+            remaining: escrow.amount,
+        }),
+        Settlement::Refund => emit!(EscrowRefunded {
+            escrow: escrow.key(),
+            depositor: escrow.depositor,
+            amount,
+            remaining: escrow.amount,
+        }),
+    }
+}
+
+fn settle_sol(
+    ctx: Context<SettleSol>,
+    amount: u64,
+    settlement: Settlement,
+    recipient: AccountInfo,
+) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow;
+    require_condition(escrow, settlement, &ctx.accounts.authority.key(), ctx.accounts.oracle_worker.as_ref())?;
+    require!(amount > 0 && amount <= escrow.amount, EscrowError::InvalidAmount);
+
+    let from = escrow.to_account_info();
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(amount)
+        .ok_or(EscrowError::ArithmeticOverflow)?;
+    escrow.amount -= amount;
+
+    emit_settlement(escrow, settlement, amount);
+    if escrow.amount == 0 {
+        escrow.close(ctx.accounts.depositor.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+fn settle_tokens<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleTokens<'info>>,
+    amount: u64,
+    settlement: Settlement,
+    recipient: AccountInfo<'info>,
+) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow;
+    require_condition(escrow, settlement, &ctx.accounts.authority.key(), ctx.accounts.oracle_worker.as_ref())?;
+    require!(amount > 0 && amount <= escrow.amount, EscrowError::InvalidAmount);
+
+    let (depositor, reference) = (escrow.depositor, escrow.reference);
+    let escrow_seeds: &[&[u8]] = &[ESCROW_SEED, depositor.as_ref(), reference.as_ref(), &[escrow.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: recipient,
+                authority: escrow.to_account_info(),
+            },
+            &[escrow_seeds],
+        ),
+        amount,
+    )?;
+    escrow.amount -= amount;
+
+    emit_settlement(escrow, settlement, amount);
+    if escrow.amount == 0 {
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vault.to_account_info(),
+                destination: ctx.accounts.depositor.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            &[escrow_seeds],
+        ))?;
+        escrow.close(ctx.accounts.depositor.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+fn settle_cnft<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleCnft<'info>>,
+    cnft: CnftArgs,
+    settlement: Settlement,
+    recipient: AccountInfo<'info>,
+) -> Result<()> {
+    let escrow = &mut ctx.accounts.escrow;
+    require_condition(escrow, settlement, &ctx.accounts.authority.key(), ctx.accounts.oracle_worker.as_ref())?;
+
+    let escrow_seeds: &[&[u8]] = &[ESCROW_SEED, escrow.depositor.as_ref(), escrow.reference.as_ref(), &[escrow.bump]];
+    ctx.accounts.cnft_accounts.transfer(
+        escrow.to_account_info(),
+        recipient,
+        ctx.accounts.system_program.to_account_info(),
+        ctx.remaining_accounts,
+        &cnft,
+        &[escrow_seeds],
+    )?;
+    escrow.amount = 0;
+
+    emit_settlement(escrow, settlement, 1);
+    escrow.close(ctx.accounts.depositor.to_account_info())
+}
+
+impl<'info> CnftAccounts<'info> {
+    // Transfer the cNFT leaf, optionally signing as a PDA leaf owner
+    pub fn transfer(
+        &self,
+        leaf_owner: AccountInfo<'info>,
+        new_leaf_owner: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        proof: &[AccountInfo<'info>],
+        cnft: &CnftArgs,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_accounts = mpl_bubblegum::accounts::Transfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: leaf_owner.clone(),
+            leaf_delegate: leaf_owner,
+            new_leaf_owner,
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.bubblegum_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(proof.to_vec());
+        mpl_bubblegum::cpi::transfer(
+            cpi_ctx,
+            cnft.root,
+            cnft.data_hash,
+            cnft.creator_hash,
+            cnft.nonce,
+            cnft.index,
+        )
+    }
+}
@@ -0,0 +1,739 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use agent_registry::AgentData;
+use relationship::program::Relationship;
+use relationship::{HookRegistry, InteractionNonce, InteractionType, INTERACTION_RECORDER_SEED};
+
+declare_id!("EventsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_TITLE_LEN: usize = 64;
+const ATTENDANCE_ACCOUNTS: usize = 8;  // Fixed accounts per relationship in record_attendance
+
+#[program]
+pub mod events {
+    use super::*;
+
+    // Create an event and its Influence ticket vault. A ticket price of zero
+    // makes the event free; the oracle may attest check-ins alongside the host.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_event(
+        ctx: Context<CreateEvent>,
+        event_id: u64,
+        title: String,
+        starts_at: i64,
+        ends_at: i64,
+        capacity: u16,
+        ticket_price: u64,
+        oracle: Pubkey,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(starts_at > now && ends_at > starts_at, EventsError::InvalidSchedule);
+        require!(capacity >= 2, EventsError::InvalidCapacity);
+        require!(
+            !title.is_empty() && title.len() <= MAX_TITLE_LEN,
+            EventsError::InvalidTitle
+        );
+
+        let event = &mut ctx.accounts.event;
+        event.host = ctx.accounts.host.key();
+        event.oracle = oracle;
+        event.event_id = event_id;
+        event.title = title;
+        event.influence_mint = ctx.accounts.influence_mint.key();
+        event.ticket_vault = ctx.accounts.ticket_vault.key();
+        event.status = EventStatus::Scheduled;
+        event.starts_at = starts_at;
+        event.ends_at = ends_at;
+        event.capacity = capacity;
+        event.rsvp_count = 0;
+        event.checked_in_count = 0;
+        event.ticket_price = ticket_price;
+        event.interactions_recorded = 0;
+        event.bump = *ctx.bumps.get("event").unwrap();
+
+        emit!(EventCreated {
+            event: event.key(),
+            host: event.host,
+            starts_at,
+            ends_at,
+            capacity,
+            ticket_price,
+        });
+
+        Ok(())
+    }
+
+    // RSVP an agent before the event starts, escrowing its ticket in the vault
+    pub fn rsvp(ctx: Context<CreateRsvp>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &mut ctx.accounts.event;
+        require!(event.status == EventStatus::Scheduled, EventsError::InvalidStatus);
+        require!(now < event.starts_at, EventsError::EventStarted);
+        require!(event.rsvp_count < event.capacity, EventsError::EventFull);
+
+        if event.ticket_price > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.owner_token_account.to_account_info(),
+                        to: ctx.accounts.ticket_vault.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                event.ticket_price,
+            )?;
+        }
+        event.rsvp_count += 1;
+
+        let rsvp = &mut ctx.accounts.rsvp_record;
+        rsvp.event = event.key();
+        rsvp.agent = ctx.accounts.agent_data.key();
+        rsvp.owner = ctx.accounts.owner.key();
+        rsvp.ticket_paid = event.ticket_price;
+        rsvp.checked_in = false;
+        rsvp.settled = false;
+        rsvp.bump = *ctx.bumps.get("rsvp_record").unwrap();
+
+        emit!(RsvpCreated {
+            event: event.key(),
+            agent: rsvp.agent,
+            owner: rsvp.owner,
+            ticket_paid: rsvp.ticket_paid,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw an RSVP before the event starts or after it is cancelled; the
+    // ticket is refunded
+    pub fn cancel_rsvp(ctx: Context<CancelRsvp>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &mut ctx.accounts.event;
+        require!(
+            event.status == EventStatus::Cancelled || now < event.starts_at,
+            EventsError::EventStarted
+        );
+
+        let rsvp = &ctx.accounts.rsvp_record;
+        require!(!rsvp.settled, EventsError::AlreadySettled);
+        refund_ticket(
+            event,
+            rsvp.ticket_paid,
+            &ctx.accounts.ticket_vault,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.token_program,
+        )?;
+        event.rsvp_count = event.rsvp_count.saturating_sub(1);
+
+        emit!(RsvpCancelled {
+            event: event.key(),
+            agent: rsvp.agent,
+            refunded: rsvp.ticket_paid,
+        });
+
+        Ok(())
+    }
+
+    // Attest that an RSVP'd agent attended (host or oracle)
+    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &mut ctx.accounts.event;
+        require!(event.status == EventStatus::Scheduled, EventsError::InvalidStatus);
+        require!(
+            now >= event.starts_at && now <= event.ends_at,
+            EventsError::OutsideEventWindow
+        );
+
+        let rsvp = &mut ctx.accounts.rsvp_record;
+        require!(!rsvp.checked_in, EventsError::AlreadyCheckedIn);
+        rsvp.checked_in = true;
+        event.checked_in_count += 1;
+
+        emit!(AttendeeCheckedIn {
+            event: event.key(),
+            agent: rsvp.agent,
+            attester: ctx.accounts.attester.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Record a group Activity interaction for each relationship whose agents
+    // both checked in (host or oracle). Remaining accounts hold, per
+    // relationship: relationship, agent one, agent two, interaction nonce,
+    // interaction PDA, agent one's RSVP, agent two's RSVP, attendance PDA,
+    // followed by the accounts the relationship program's hooks require.
+    pub fn record_attendance<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordAttendance<'info>>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &ctx.accounts.event;
+        require!(event.status == EventStatus::Scheduled, EventsError::InvalidStatus);
+        require!(now >= event.starts_at, EventsError::OutsideEventWindow);
+
+        let hook_accounts = ctx
+            .accounts
+            .hook_registry
+            .hooks
+            .iter()
+            .map(|hook| 1 + hook.extra_accounts as usize)
+            .sum::<usize>();
+        let chunk_len = ATTENDANCE_ACCOUNTS + hook_accounts;
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % chunk_len == 0,
+            EventsError::AttendanceAccountsMismatch
+        );
+
+        let event_key = event.key();
+        let recorder_seeds: &[&[u8]] = &[
+            INTERACTION_RECORDER_SEED,
+            &[*ctx.bumps.get("event_recorder").unwrap()],
+        ];
+        let interaction_data = format!("event:{}", event_key);
+        let mut recorded = 0u32;
+        for chunk in ctx.remaining_accounts.chunks(chunk_len) {
+            let relationship_data = &chunk[0];
+            let interaction_nonce: Account<InteractionNonce> = Account::try_from(&chunk[3])?;
+            let rsvp_one: Account<RsvpRecord> = Account::try_from(&chunk[5])?;
+            let rsvp_two: Account<RsvpRecord> = Account::try_from(&chunk[6])?;
+            require!(
+                rsvp_one.event == event_key
+                    && rsvp_two.event == event_key
+                    && rsvp_one.agent == chunk[1].key()
+                    && rsvp_two.agent == chunk[2].key(),
+                EventsError::AttendanceAccountsMismatch
+            );
+            require!(
+                rsvp_one.checked_in && rsvp_two.checked_in,
+                EventsError::NotCheckedIn
+            );
+
+            create_attendance_record(
+                &ctx.accounts.attester,
+                &chunk[7],
+                &ctx.accounts.system_program,
+                event_key,
+                relationship_data.key(),
+                chunk[4].key(),
+            )?;
+
+            relationship::cpi::record_interaction(
+                CpiContext::new_with_signer(
+                    ctx.accounts.relationship_program.to_account_info(),
+                    relationship::cpi::accounts::RecordInteraction {
+                        authority: ctx.accounts.attester.to_account_info(),
+                        session: None,
+                        event_recorder: Some(ctx.accounts.event_recorder.to_account_info()),
+                        relationship_data: relationship_data.clone(),
+                        agent_one: chunk[1].clone(),
+                        agent_two: chunk[2].clone(),
+                        interaction_nonce: chunk[3].clone(),
+                        interaction_data: chunk[4].clone(),
+                        hook_registry: ctx.accounts.hook_registry.to_account_info(),
+                        hook_authority: ctx.accounts.hook_authority.to_account_info(),
+                        interaction_type_registry: None,
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        config: ctx.accounts.relationship_config.to_account_info(),
+                        recorder_rate: ctx.accounts.recorder_rate.to_account_info(),
+                        matchmaker_stats: None,
+                        matchmaker_token_account: None,
+                        rewards_vault: None,
+                        token_program: None,
+                        stats: ctx.accounts.relationship_stats.to_account_info(),
+                        guardian: ctx.accounts.guardian.to_account_info(),
+                        rate_limiter_config: ctx.accounts.rate_limiter_config.to_account_info(),
+                        wallet_quota: ctx.accounts.wallet_quota.to_account_info(),
+                        rate_limiter_program: ctx.accounts.rate_limiter_program.to_account_info(),
+                        event_authority: ctx.accounts.relationship_event_authority.to_account_info(),
+                        program: ctx.accounts.relationship_program.to_account_info(),
+                    },
+                    &[recorder_seeds],
+                )
+                .with_remaining_accounts(chunk[ATTENDANCE_ACCOUNTS..].to_vec()),
+                interaction_nonce.next_index,
+                InteractionType::Activity,
+                interaction_data.clone(),
+                None,
+            )?;
+            recorded += 1;
+        }
+
+        let event = &mut ctx.accounts.event;
+        event.interactions_recorded = event.interactions_recorded.checked_add(recorded).unwrap_or(u32::MAX);
+
+        emit!(AttendanceRecorded {
+            event: event_key,
+            relationships: recorded,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Cancel an event before it starts (host). Attendees reclaim their
+    // tickets through `cancel_rsvp` or `settle_rsvp`.
+    pub fn cancel_event(ctx: Context<ManageEvent>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &mut ctx.accounts.event;
+        require!(event.status == EventStatus::Scheduled, EventsError::InvalidStatus);
+        require!(now < event.starts_at, EventsError::EventStarted);
+
+        event.status = EventStatus::Cancelled;
+
+        emit!(EventCancelled {
+            event: event.key(),
+        });
+
+        Ok(())
+    }
+
+    // Release one escrowed ticket once the event has ended (permissionless).
+    // Attended tickets are paid to the host; no-shows and tickets for
+    // cancelled events are refunded to the agent's owner.
+    pub fn settle_rsvp(ctx: Context<SettleRsvp>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let event = &ctx.accounts.event;
+        let rsvp = &mut ctx.accounts.rsvp_record;
+        require!(!rsvp.settled, EventsError::AlreadySettled);
+        require!(
+            event.status == EventStatus::Cancelled || now > event.ends_at,
+            EventsError::EventNotEnded
+        );
+
+        let to_host = event.status == EventStatus::Scheduled && rsvp.checked_in;
+        let recipient = if to_host {
+            &ctx.accounts.host_token_account
+        } else {
+            &ctx.accounts.owner_token_account
+        };
+        refund_ticket(
+            event,
+            rsvp.ticket_paid,
+            &ctx.accounts.ticket_vault,
+            recipient,
+            &ctx.accounts.token_program,
+        )?;
+        rsvp.settled = true;
+
+        emit!(RsvpSettled {
+            event: event.key(),
+            agent: rsvp.agent,
+            amount: rsvp.ticket_paid,
+            paid_to_host: to_host,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct EventData {
+    pub host: Pubkey,
+    pub oracle: Pubkey,  // Also allowed to check attendees in; Pubkey::default() for none
+    pub event_id: u64,
+    pub title: String,
+    pub influence_mint: Pubkey,
+    pub ticket_vault: Pubkey,
+    pub status: EventStatus,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub capacity: u16,
+    pub rsvp_count: u16,
+    pub checked_in_count: u16,
+    pub ticket_price: u64,
+    pub interactions_recorded: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct RsvpRecord {
+    pub event: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,  // Paid the ticket and receives any refund
+    pub ticket_paid: u64,
+    pub checked_in: bool,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+// Marks a relationship's attendance as recorded so it is only counted once per event
+#[account]
+pub struct AttendanceRecord {
+    pub event: Pubkey,
+    pub relationship: Pubkey,
+    pub interaction: Pubkey,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+#[instruction(event_id: u64)]
+pub struct CreateEvent<'info> {
+    #[account(mut)]
+    pub host: Signer<'info>,
+
+    #[account(
+        init,
+        payer = host,
+        space = 8 + std::mem::size_of::<EventData>() + MAX_TITLE_LEN,
+        seeds = [b"event", host.key().as_ref(), event_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub event: Account<'info, EventData>,
+
+    #[account(
+        init,
+        payer = host,
+        seeds = [b"ticket_vault", event.key().as_ref()],
+        bump,
+        token::mint = influence_mint,
+        token::authority = event,
+    )]
+    pub ticket_vault: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRsvp<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, EventData>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ EventsError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<RsvpRecord>(),
+        seeds = [b"rsvp", event.key().as_ref(), agent_data.key().as_ref()],
+        bump
+    )]
+    pub rsvp_record: Account<'info, RsvpRecord>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ EventsError::Unauthorized,
+        constraint = owner_token_account.mint == event.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = event.ticket_vault)]
+    pub ticket_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRsvp<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, EventData>,
+
+    #[account(
+        mut,
+        has_one = event,
+        has_one = owner @ EventsError::Unauthorized,
+        close = owner,
+    )]
+    pub rsvp_record: Account<'info, RsvpRecord>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ EventsError::Unauthorized,
+        constraint = owner_token_account.mint == event.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = event.ticket_vault)]
+    pub ticket_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    #[account(
+        constraint = attester.key() == event.host
+            || (event.oracle != Pubkey::default() && attester.key() == event.oracle)
+            @ EventsError::Unauthorized
+    )]
+    pub attester: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, EventData>,
+
+    #[account(mut, has_one = event)]
+    pub rsvp_record: Account<'info, RsvpRecord>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAttendance<'info> {
+    // Pays for the attendance and interaction accounts and records the
+    // interactions in the relationship program
+    #[account(
+        mut,
+        constraint = attester.key() == event.host
+            || (event.oracle != Pubkey::default() && attester.key() == event.oracle)
+            @ EventsError::Unauthorized
+    )]
+    pub attester: Signer<'info>,
+
+    #[account(mut)]
+    pub event: Account<'info, EventData>,
+
+    /// CHECK: PDA signing the interactions for the checked-in agents' owners
+    #[account(seeds = [INTERACTION_RECORDER_SEED], bump)]
+    pub event_recorder: UncheckedAccount<'info>,
+
+    // Relationship program accounts shared by every interaction in the batch
+    pub hook_registry: Account<'info, HookRegistry>,
+    /// CHECK: validated by the relationship program
+    pub hook_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub relationship_config: UncheckedAccount<'info>,
+    /// CHECK: the attester's rate limit account, validated by the relationship program
+    #[account(mut)]
+    pub recorder_rate: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub relationship_stats: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub relationship_event_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub rate_limiter_program: UncheckedAccount<'info>,
+
+    pub relationship_program: Program<'info, Relationship>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageEvent<'info> {
+    pub host: Signer<'info>,
+
+    #[account(mut, has_one = host @ EventsError::Unauthorized)]
+    pub event: Account<'info, EventData>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRsvp<'info> {
+    pub event: Account<'info, EventData>,
+
+    #[account(mut, has_one = event)]
+    pub rsvp_record: Account<'info, RsvpRecord>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == rsvp_record.owner @ EventsError::Unauthorized,
+        constraint = owner_token_account.mint == event.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = host_token_account.owner == event.host @ EventsError::Unauthorized,
+        constraint = host_token_account.mint == event.influence_mint,
+    )]
+    pub host_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = event.ticket_vault)]
+    pub ticket_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum EventStatus {
+    Scheduled,
+    Cancelled,
+}
+
+// Events
+#[event]
+pub struct EventCreated {
+    pub event: Pubkey,
+    pub host: Pubkey,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub capacity: u16,
+    pub ticket_price: u64,
+}
+
+#[event]
+pub struct EventCancelled {
+    pub event: Pubkey,
+}
+
+#[event]
+pub struct RsvpCreated {
+    pub event: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub ticket_paid: u64,
+}
+
+#[event]
+pub struct RsvpCancelled {
+    pub event: Pubkey,
+    pub agent: Pubkey,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct AttendeeCheckedIn {
+    pub event: Pubkey,
+    pub agent: Pubkey,
+    pub attester: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttendanceRecorded {
+    pub event: Pubkey,
+    pub relationships: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RsvpSettled {
+    pub event: Pubkey,
+    pub agent: Pubkey,
+    pub amount: u64,
+    pub paid_to_host: bool,
+}
+
+// Custom errors
+#[error_code]
+pub enum EventsError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Event must start in the future and end after it starts")]
+    InvalidSchedule,
+    #[msg("Event must admit at least two agents")]
+    InvalidCapacity,
+    #[msg("Event title is invalid")]
+    InvalidTitle,
+    #[msg("Event is not in the required status")]
+    InvalidStatus,
+    #[msg("Event has already started")]
+    EventStarted,
+    #[msg("Event has not ended yet")]
+    EventNotEnded,
+    #[msg("Event is full")]
+    EventFull,
+    #[msg("Action is only allowed while the event is running")]
+    OutsideEventWindow,
+    #[msg("Agent has already checked in")]
+    AlreadyCheckedIn,
+    #[msg("Both agents must have checked in")]
+    NotCheckedIn,
+    #[msg("Ticket has already been settled")]
+    AlreadySettled,
+    #[msg("Attendance accounts do not match the expected layout")]
+    AttendanceAccountsMismatch,
+    #[msg("Attendance for this relationship has already been recorded")]
+    AttendanceAlreadyRecorded,
+}
+
+// Helper functions
+// Transfer an escrowed ticket out of the vault, signing as the event PDA
+fn refund_ticket<'info>(
+    event: &Account<'info, EventData>,
+    amount: u64,
+    ticket_vault: &Account<'info, TokenAccount>,
+    recipient: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let id_bytes = event.event_id.to_le_bytes();
+    let event_seeds: &[&[u8]] = &[
+        b"event",
+        event.host.as_ref(),
+        id_bytes.as_ref(),
+        &[event.bump],
+    ];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: ticket_vault.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: event.to_account_info(),
+            },
+            &[event_seeds],
+        ),
+        amount,
+    )
+}
+
+// Create the attendance PDA for a relationship, failing if it already exists
+fn create_attendance_record<'info>(
+    payer: &Signer<'info>,
+    attendance: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    event: Pubkey,
+    relationship: Pubkey,
+    interaction: Pubkey,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"attendance", event.as_ref(), relationship.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(attendance.key(), expected, EventsError::AttendanceAccountsMismatch);
+    require!(attendance.lamports() == 0, EventsError::AttendanceAlreadyRecorded);
+
+    let space = 8 + std::mem::size_of::<AttendanceRecord>();
+    let attendance_seeds: &[&[u8]] = &[
+        b"attendance",
+        event.as_ref(),
+        relationship.as_ref(),
+        &[bump],
+    ];
+    create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            CreateAccount {
+                from: payer.to_account_info(),
+                to: attendance.clone(),
+            },
+            &[attendance_seeds],
+        ),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        &crate::ID,
+    )?;
+
+    let record = AttendanceRecord {
+        event,
+        relationship,
+        interaction,
+        bump,
+    };
+    let mut data = attendance.try_borrow_mut_data()?;
+    record.try_serialize(&mut &mut data[..])
+}
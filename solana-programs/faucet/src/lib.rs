@@ -0,0 +1,415 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use agent_registry::program::AgentRegistry;
+use agent_registry::Bubblegum;
+use armour_core::{PersonalityTraits, MAX_TRAIT_VALUE, TRAIT_COUNT};
+use spl_account_compression::program::SplAccountCompression;
+
+declare_id!("Fauc3tPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const FAUCET_SEED: &[u8] = b"faucet";
+const FAUCET_VAULT_SEED: &[u8] = b"faucet_vault";
+const FAUCET_CLAIM_SEED: &[u8] = b"faucet_claim";
+
+// Bootstraps test environments: one `drip` sends the wallet test Influence
+// from the faucet vault and registers an agent with random traits for it
+// through the registry. Drips only work in builds with the `devnet` feature,
+// so the program is inert if a default build is ever deployed.
+#[program]
+pub mod faucet {
+    use super::*;
+
+    // Create the faucet config and its Influence vault
+    pub fn initialize_faucet(ctx: Context<InitializeFaucet>, drip_amount: u64, cooldown: i64) -> Result<()> {
+        require!(cooldown >= 0, FaucetError::InvalidConfig);
+
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.vault = ctx.accounts.vault.key();
+        config.drip_amount = drip_amount;
+        config.cooldown = cooldown;
+        config.total_drips = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit!(FaucetParamsUpdated { drip_amount, cooldown });
+
+        Ok(())
+    }
+
+    // Top up the vault (anyone)
+    pub fn fund_faucet(ctx: Context<FundFaucet>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.funder_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    // Change the drip amount and per-wallet cooldown (authority)
+    pub fn set_faucet_params(ctx: Context<UpdateFaucet>, drip_amount: u64, cooldown: i64) -> Result<()> {
+        require!(cooldown >= 0, FaucetError::InvalidConfig);
+
+        let config = &mut ctx.accounts.config;
+        config.drip_amount = drip_amount;
+        config.cooldown = cooldown;
+
+        emit!(FaucetParamsUpdated { drip_amount, cooldown });
+
+        Ok(())
+    }
+
+    // Hand the faucet authority to another key (authority)
+    pub fn set_faucet_authority(ctx: Context<UpdateFaucet>, new_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+        config.authority = new_authority;
+
+        emit!(FaucetAuthorityUpdated {
+            previous_authority,
+            authority: new_authority,
+        });
+
+        Ok(())
+    }
+
+    // Send the wallet its drip of test Influence and register a test agent
+    // it owns, named after its drip count, with traits rolled from the
+    // latest slot hash. A wallet can drip once per cooldown. The registry
+    // still applies its own checks (guardian pause, rate limits), and the
+    // profile hook accounts are forwarded as remaining accounts.
+    pub fn drip<'info>(
+        ctx: Context<'_, '_, '_, 'info, Drip<'info>>,
+        metadata_uri: String,
+    ) -> Result<()> {
+        require!(cfg!(feature = "devnet"), FaucetError::DevnetOnly);
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.config;
+        let claim = &mut ctx.accounts.claim;
+        let wallet = ctx.accounts.wallet.key();
+        if claim.drips > 0 {
+            require!(
+                now >= claim.last_drip_at.saturating_add(config.cooldown),
+                FaucetError::CooldownActive
+            );
+        }
+        claim.wallet = wallet;
+        claim.last_drip_at = now;
+        claim.drips = claim.drips.checked_add(1).ok_or(FaucetError::ArithmeticOverflow)?;
+        claim.bump = *ctx.bumps.get("claim").unwrap();
+        config.total_drips = config.total_drips.saturating_add(1);
+
+        // Test Influence from the vault
+        let amount = config.drip_amount;
+        if amount > 0 {
+            require!(ctx.accounts.vault.amount >= amount, FaucetError::FaucetEmpty);
+            let config_seeds: &[&[u8]] = &[FAUCET_SEED, &[config.bump]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.wallet_token_account.to_account_info(),
+                        authority: config.to_account_info(),
+                    },
+                    &[config_seeds],
+                ),
+                amount,
+            )?;
+        }
+
+        // A test agent with random traits
+        let name = format!("test-agent-{}", claim.drips);
+        let seed = armour_randomness::slot_hash_seed(&ctx.accounts.recent_slothashes)?;
+        let entropy = armour_randomness::derive(
+            &seed,
+            &[b"faucet", wallet.as_ref(), &claim.drips.to_le_bytes()],
+        );
+        let personality_traits = random_traits(&entropy);
+        agent_registry::cpi::register_agent(
+            CpiContext::new(
+                ctx.accounts.registry_program.to_account_info(),
+                agent_registry::cpi::accounts::RegisterAgent {
+                    owner: ctx.accounts.wallet.to_account_info(),
+                    agent_data: ctx.accounts.agent_data.to_account_info(),
+                    registry_state: ctx.accounts.registry_state.to_account_info(),
+                    tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+                    compression_program: ctx.accounts.compression_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    guardian: ctx.accounts.guardian.to_account_info(),
+                    rate_limiter_config: ctx.accounts.rate_limiter_config.to_account_info(),
+                    wallet_quota: ctx.accounts.wallet_quota.to_account_info(),
+                    rate_limiter_program: ctx.accounts.rate_limiter_program.to_account_info(),
+                    credential: ctx.accounts.credential.as_ref().map(|credential| credential.to_account_info()),
+                    analytics_hook_authority: ctx.accounts.analytics_hook_authority.to_account_info(),
+                    analytics_state: ctx.accounts.analytics_state.to_account_info(),
+                    analytics_program: ctx.accounts.analytics_program.to_account_info(),
+                    referral_reporter: None,
+                    referrals_config: None,
+                    referral: None,
+                    referrer_rewards: None,
+                    referrals_program: None,
+                    collection: agent_registry::cpi::accounts::AgentCollection {
+                        collection_config: ctx.accounts.collection_config.to_account_info(),
+                        collection_authority: ctx.accounts.collection_authority.to_account_info(),
+                        collection_mint: ctx.accounts.collection_mint.to_account_info(),
+                        collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+                        collection_edition: ctx.accounts.collection_edition.to_account_info(),
+                        bubblegum_signer: ctx.accounts.bubblegum_signer.to_account_info(),
+                        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                    },
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            metadata_uri,
+            name.clone(),
+            personality_traits.clone(),
+        )?;
+
+        emit!(FaucetDripped {
+            wallet,
+            amount,
+            agent: ctx.accounts.agent_data.key(),
+            name,
+            personality_traits,
+            drips: claim.drips,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct FaucetConfig {
+    pub authority: Pubkey,       // Sets drip parameters
+    pub influence_mint: Pubkey,
+    pub vault: Pubkey,           // Influence handed out by drips, owned by this config
+    pub drip_amount: u64,        // Influence per drip
+    pub cooldown: i64,           // Seconds between drips for one wallet
+    pub total_drips: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct FaucetClaim {
+    pub wallet: Pubkey,
+    pub last_drip_at: i64,
+    pub drips: u64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeFaucet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<FaucetConfig>(),
+        seeds = [FAUCET_SEED],
+        bump
+    )]
+    pub config: Account<'info, FaucetConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [FAUCET_VAULT_SEED],
+        bump,
+        token::mint = influence_mint,
+        token::authority = config,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct FundFaucet<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(seeds = [FAUCET_SEED], bump = config.bump)]
+    pub config: Account<'info, FaucetConfig>,
+
+    #[account(mut)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFaucet<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [FAUCET_SEED],
+        bump = config.bump,
+        constraint = config.authority == authority.key() @ FaucetError::Unauthorized
+    )]
+    pub config: Account<'info, FaucetConfig>,
+}
+
+#[derive(Accounts)]
+pub struct Drip<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(mut, seeds = [FAUCET_SEED], bump = config.bump)]
+    pub config: Account<'info, FaucetConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = 8 + std::mem::size_of::<FaucetClaim>(),
+        seeds = [FAUCET_CLAIM_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, FaucetClaim>,
+
+    #[account(mut, address = config.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = wallet_token_account.owner == wallet.key() @ FaucetError::Unauthorized,
+        constraint = wallet_token_account.mint == config.influence_mint,
+    )]
+    pub wallet_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: slot hashes sysvar, read for the trait roll
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+
+    /// CHECK: created by the registry's `register_agent`
+    #[account(mut)]
+    pub agent_data: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub registry_state: UncheckedAccount<'info>,
+
+    // Accounts needed by the registry to mint the agent's cNFT
+    /// CHECK: validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub rate_limiter_program: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program, which requires it in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated by the registry program
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub analytics_program: UncheckedAccount<'info>,
+
+    // The registry's agent collection, which the cNFT is minted into
+    /// CHECK: validated by the registry program
+    pub collection_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub collection_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub collection_mint: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub collection_edition: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub bubblegum_signer: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: validated by the registry program
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct FaucetParamsUpdated {
+    pub drip_amount: u64,
+    pub cooldown: i64,
+}
+
+#[event]
+pub struct FaucetAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct FaucetDripped {
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub agent: Pubkey,
+    pub name: String,
+    pub personality_traits: PersonalityTraits,
+    pub drips: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum FaucetError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("The faucet only dispenses in devnet builds")]
+    DevnetOnly,
+    #[msg("Invalid faucet configuration")]
+    InvalidConfig,
+    #[msg("This wallet must wait for its cooldown before dripping again")]
+    CooldownActive,
+    #[msg("The faucet vault does not hold enough Influence")]
+    FaucetEmpty,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// One byte of entropy per trait, spread over 0..=MAX_TRAIT_VALUE
+fn random_traits(entropy: &[u8; 32]) -> PersonalityTraits {
+    let mut values = [0u8; TRAIT_COUNT];
+    for (value, byte) in values.iter_mut().zip(entropy.iter()) {
+        *value = byte % (MAX_TRAIT_VALUE + 1);
+    }
+    PersonalityTraits::from_values(values)
+}
@@ -0,0 +1,884 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("G0vernancePr0graMxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_DESCRIPTION_URI_LEN: usize = 200;
+const MAX_PROPOSAL_INSTRUCTIONS: usize = 4;
+const MAX_INSTRUCTION_ACCOUNTS: usize = 12;
+const MAX_INSTRUCTION_DATA_LEN: usize = 256;
+
+#[program]
+pub mod governance {
+    use super::*;
+
+    // Create the DAO and the vault that holds staked Influence and proposal
+    // deposits. Other programs hand their admin roles to the governance
+    // authority PDA, which only signs through executed proposals.
+    pub fn initialize_governance(
+        ctx: Context<InitializeGovernance>,
+        params: GovernanceParams,
+    ) -> Result<()> {
+        validate_governance_params(&params)?;
+
+        let governance = &mut ctx.accounts.governance;
+        governance.influence_mint = ctx.accounts.influence_mint.key();
+        governance.vault = ctx.accounts.vault.key();
+        governance.params = params.clone();
+        governance.proposal_count = 0;
+        governance.bump = *ctx.bumps.get("governance").unwrap();
+        governance.authority_bump = *ctx.bumps.get("governance_authority").unwrap();
+
+        emit!(GovernanceParamsUpdated { params });
+
+        Ok(())
+    }
+
+    // Change voting rules; only callable by an executed proposal
+    pub fn update_governance_params(
+        ctx: Context<UpdateGovernanceParams>,
+        params: GovernanceParams,
+    ) -> Result<()> {
+        validate_governance_params(&params)?;
+
+        ctx.accounts.governance.params = params.clone();
+
+        emit!(GovernanceParamsUpdated { params });
+
+        Ok(())
+    }
+
+    // Stake Influence for voting power
+    pub fn deposit_votes(ctx: Context<DepositVotes>, amount: u64) -> Result<()> {
+        require!(amount > 0, GovernanceError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.voter_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let voter_record = &mut ctx.accounts.voter_record;
+        voter_record.voter = ctx.accounts.voter.key();
+        voter_record.deposited = voter_record
+            .deposited
+            .checked_add(amount)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        voter_record.last_deposit_at = Clock::get()?.unix_timestamp;
+        voter_record.bump = *ctx.bumps.get("voter_record").unwrap();
+
+        emit!(VotesDeposited {
+            voter: voter_record.voter,
+            amount,
+            deposited: voter_record.deposited,
+        });
+
+        Ok(())
+    }
+
+    // Unstake Influence once every proposal the voter voted on has closed
+    pub fn withdraw_votes(ctx: Context<WithdrawVotes>, amount: u64) -> Result<()> {
+        let voter_record = &mut ctx.accounts.voter_record;
+        require!(
+            Clock::get()?.unix_timestamp >= voter_record.locked_until,
+            GovernanceError::VotesLocked
+        );
+        require!(
+            amount > 0 && amount <= voter_record.deposited,
+            GovernanceError::InvalidAmount
+        );
+
+        let governance_seeds: &[&[u8]] = &[b"governance", &[ctx.accounts.governance.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.voter_token_account.to_account_info(),
+                    authority: ctx.accounts.governance.to_account_info(),
+                },
+                &[governance_seeds],
+            ),
+            amount,
+        )?;
+        voter_record.deposited -= amount;
+
+        emit!(VotesWithdrawn {
+            voter: voter_record.voter,
+            amount,
+            deposited: voter_record.deposited,
+        });
+
+        Ok(())
+    }
+
+    // Open a proposal, paying the Influence deposit. Voting power is
+    // snapshotted at creation: only stake deposited before now counts.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        description_uri: String,
+        instructions: Vec<ProposalInstruction>,
+    ) -> Result<()> {
+        require!(
+            description_uri.len() <= MAX_DESCRIPTION_URI_LEN,
+            GovernanceError::DescriptionTooLong
+        );
+        validate_proposal_instructions(&instructions, &ctx.accounts.governance_authority.key())?;
+
+        let governance = &mut ctx.accounts.governance;
+        let params = &governance.params;
+        if params.proposal_deposit > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.proposer_token_account.to_account_info(),
+                        to: ctx.accounts.vault.to_account_info(),
+                        authority: ctx.accounts.proposer.to_account_info(),
+                    },
+                ),
+                params.proposal_deposit,
+            )?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.proposal_id = governance.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.description_uri = description_uri;
+        proposal.deposit = params.proposal_deposit;
+        proposal.snapshot_at = now;
+        proposal.voting_ends_at = now
+            .checked_add(params.voting_period)
+            .ok_or(GovernanceError::ArithmeticOverflow)?;
+        proposal.quorum_votes = params.quorum_votes;
+        proposal.approval_threshold_bps = params.approval_threshold_bps;
+        proposal.timelock_delay = params.timelock_delay;
+        proposal.yes_votes = 0;
+        proposal.no_votes = 0;
+        proposal.state = ProposalState::Voting;
+        proposal.executable_at = 0;
+        proposal.instructions = instructions;
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+        governance.proposal_count = governance.proposal_count.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            proposal_id: proposal.proposal_id,
+            proposer: proposal.proposer,
+            voting_ends_at: proposal.voting_ends_at,
+        });
+
+        Ok(())
+    }
+
+    // Vote with all stake held at the proposal's snapshot. Stake stays
+    // locked until voting on this proposal ends.
+    pub fn cast_vote(ctx: Context<CastVote>, approve: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let voter_record = &mut ctx.accounts.voter_record;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            proposal.state == ProposalState::Voting && now < proposal.voting_ends_at,
+            GovernanceError::VotingClosed
+        );
+        require!(
+            voter_record.last_deposit_at < proposal.snapshot_at,
+            GovernanceError::StakeAfterSnapshot
+        );
+        let weight = voter_record.deposited;
+        require!(weight > 0, GovernanceError::NoVotingPower);
+
+        if approve {
+            proposal.yes_votes = proposal.yes_votes.saturating_add(weight);
+        } else {
+            proposal.no_votes = proposal.no_votes.saturating_add(weight);
+        }
+        voter_record.locked_until = voter_record.locked_until.max(proposal.voting_ends_at);
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.proposal = proposal.key();
+        vote_record.voter = voter_record.voter;
+        vote_record.approve = approve;
+        vote_record.weight = weight;
+        vote_record.bump = *ctx.bumps.get("vote_record").unwrap();
+
+        emit!(VoteCast {
+            proposal: proposal.key(),
+            voter: vote_record.voter,
+            approve,
+            weight,
+        });
+
+        Ok(())
+    }
+
+    // Tally a proposal once voting ends (permissionless). The deposit is
+    // returned if quorum was reached and forfeited to the DAO otherwise.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(proposal.state == ProposalState::Voting, GovernanceError::InvalidProposalState);
+        require!(now >= proposal.voting_ends_at, GovernanceError::VotingInProgress);
+
+        let total_votes = proposal.yes_votes.saturating_add(proposal.no_votes);
+        let quorum_reached = total_votes >= proposal.quorum_votes;
+        let approved = quorum_reached
+            && (proposal.yes_votes as u128) * (BPS_DENOMINATOR as u128)
+                > (total_votes as u128) * (proposal.approval_threshold_bps as u128);
+
+        if approved {
+            proposal.state = ProposalState::Succeeded;
+            proposal.executable_at = now.saturating_add(proposal.timelock_delay);
+        } else {
+            proposal.state = ProposalState::Defeated;
+        }
+
+        if quorum_reached && proposal.deposit > 0 {
+            let governance_seeds: &[&[u8]] = &[b"governance", &[ctx.accounts.governance.bump]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.proposer_token_account.to_account_info(),
+                        authority: ctx.accounts.governance.to_account_info(),
+                    },
+                    &[governance_seeds],
+                ),
+                proposal.deposit,
+            )?;
+        }
+
+        emit!(ProposalFinalized {
+            proposal: proposal.key(),
+            state: proposal.state.clone(),
+            yes_votes: proposal.yes_votes,
+            no_votes: proposal.no_votes,
+            executable_at: proposal.executable_at,
+            deposit_returned: quorum_reached,
+        });
+
+        Ok(())
+    }
+
+    // Execute a passed proposal after its timelock (permissionless). Every
+    // account its instructions reference, including the target programs,
+    // must be passed as remaining accounts; the governance authority PDA
+    // signs each instruction.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.state == ProposalState::Succeeded, GovernanceError::InvalidProposalState);
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.executable_at,
+            GovernanceError::TimelockActive
+        );
+
+        let authority_seeds: &[&[u8]] = &[
+            b"governance_authority",
+            &[ctx.accounts.governance.authority_bump],
+        ];
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.governance_authority.to_account_info());
+        for proposal_instruction in proposal.instructions.iter() {
+            let instruction = Instruction {
+                program_id: proposal_instruction.program_id,
+                accounts: proposal_instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| AccountMeta {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: proposal_instruction.data.clone(),
+            };
+            invoke_signed(&instruction, &account_infos, &[authority_seeds])?;
+        }
+        proposal.state = ProposalState::Executed;
+
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            executor: ctx.accounts.executor.key(),
+        });
+
+        Ok(())
+    }
+
+    // Upgrade a program from a buffer; both must have the governance
+    // authority PDA as their upgrade authority. Only callable by an executed
+    // proposal, so upgrades wait out the vote and timelock.
+    pub fn upgrade_program(ctx: Context<UpgradeProgram>) -> Result<()> {
+        let authority_seeds: &[&[u8]] = &[
+            b"governance_authority",
+            &[ctx.accounts.governance.authority_bump],
+        ];
+        invoke_signed(
+            &bpf_loader_upgradeable::upgrade(
+                &ctx.accounts.program.key(),
+                &ctx.accounts.buffer.key(),
+                &ctx.accounts.governance_authority.key(),
+                &ctx.accounts.spill.key(),
+            ),
+            &[
+                ctx.accounts.program_data.to_account_info(),
+                ctx.accounts.program.to_account_info(),
+                ctx.accounts.buffer.to_account_info(),
+                ctx.accounts.spill.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
+                ctx.accounts.clock.to_account_info(),
+                ctx.accounts.governance_authority.to_account_info(),
+                ctx.accounts.bpf_loader_upgradeable.to_account_info(),
+            ],
+            &[authority_seeds],
+        )?;
+
+        emit!(ProgramUpgraded {
+            program: ctx.accounts.program.key(),
+            buffer: ctx.accounts.buffer.key(),
+        });
+
+        Ok(())
+    }
+
+    // Hand a governed program's upgrade authority elsewhere, or make it
+    // immutable with None. Only callable by an executed proposal.
+    pub fn set_program_upgrade_authority(
+        ctx: Context<SetProgramUpgradeAuthority>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let authority_seeds: &[&[u8]] = &[
+            b"governance_authority",
+            &[ctx.accounts.governance.authority_bump],
+        ];
+        let mut account_infos = vec![
+            ctx.accounts.program_data.to_account_info(),
+            ctx.accounts.governance_authority.to_account_info(),
+            ctx.accounts.bpf_loader_upgradeable.to_account_info(),
+        ];
+        if let Some(new_authority_account) = &ctx.accounts.new_authority {
+            require!(
+                Some(new_authority_account.key()) == new_authority,
+                GovernanceError::UpgradeAuthorityMismatch
+            );
+            account_infos.push(new_authority_account.to_account_info());
+        } else {
+            require!(new_authority.is_none(), GovernanceError::UpgradeAuthorityMismatch);
+        }
+        invoke_signed(
+            &bpf_loader_upgradeable::set_upgrade_authority(
+                &ctx.accounts.program.key(),
+                &ctx.accounts.governance_authority.key(),
+                new_authority.as_ref(),
+            ),
+            &account_infos,
+            &[authority_seeds],
+        )?;
+
+        emit!(ProgramUpgradeAuthorityChanged {
+            program: ctx.accounts.program.key(),
+            new_authority,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct Governance {
+    pub influence_mint: Pubkey,
+    pub vault: Pubkey,  // Holds staked Influence and proposal deposits
+    pub params: GovernanceParams,
+    pub proposal_count: u64,
+    pub bump: u8,
+    pub authority_bump: u8,
+}
+
+#[account]
+pub struct VoterRecord {
+    pub voter: Pubkey,
+    pub deposited: u64,
+    pub last_deposit_at: i64,  // Stake only counts on proposals created after this
+    pub locked_until: i64,     // Latest voting end among proposals voted on
+    pub bump: u8,
+}
+
+#[account]
+pub struct Proposal {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub description_uri: String,
+    pub deposit: u64,
+    pub snapshot_at: i64,
+    pub voting_ends_at: i64,
+    // Voting rules in force when the proposal was created
+    pub quorum_votes: u64,
+    pub approval_threshold_bps: u16,
+    pub timelock_delay: i64,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub state: ProposalState,
+    pub executable_at: i64,
+    pub instructions: Vec<ProposalInstruction>,
+    pub bump: u8,
+}
+
+// One per voter per proposal; its existence prevents double voting
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeGovernance<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Governance>(),
+        seeds = [b"governance"],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: PDA that signs executed proposals
+    #[account(seeds = [b"governance_authority"], bump)]
+    pub governance_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"governance_vault"],
+        bump,
+        token::mint = influence_mint,
+        token::authority = governance,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGovernanceParams<'info> {
+    #[account(seeds = [b"governance_authority"], bump = governance.authority_bump)]
+    pub governance_authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+}
+
+#[derive(Accounts)]
+pub struct DepositVotes<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = 8 + std::mem::size_of::<VoterRecord>(),
+        seeds = [b"voter", voter.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ GovernanceError::Unauthorized,
+        constraint = voter_token_account.mint == governance.influence_mint,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = governance.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVotes<'info> {
+    pub voter: Signer<'info>,
+
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [b"voter", voter.key().as_ref()],
+        bump = voter_record.bump,
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(
+        mut,
+        constraint = voter_token_account.owner == voter.key() @ GovernanceError::Unauthorized,
+        constraint = voter_token_account.mint == governance.influence_mint,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = governance.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(mut, seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: PDA that signs executed proposals
+    #[account(seeds = [b"governance_authority"], bump = governance.authority_bump)]
+    pub governance_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + std::mem::size_of::<Proposal>()
+            + MAX_DESCRIPTION_URI_LEN
+            + MAX_PROPOSAL_INSTRUCTIONS
+                * (32 + 4 + MAX_INSTRUCTION_ACCOUNTS * 34 + 4 + MAX_INSTRUCTION_DATA_LEN),
+        seeds = [b"proposal", governance.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        constraint = proposer_token_account.owner == proposer.key() @ GovernanceError::Unauthorized,
+        constraint = proposer_token_account.mint == governance.influence_mint,
+    )]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = governance.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"voter", voter.key().as_ref()],
+        bump = voter_record.bump,
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + std::mem::size_of::<VoteRecord>(),
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        constraint = proposer_token_account.owner == proposal.proposer @ GovernanceError::Unauthorized,
+        constraint = proposer_token_account.mint == governance.influence_mint,
+    )]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = governance.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    pub executor: Signer<'info>,
+
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: PDA that signs the proposal's instructions
+    #[account(seeds = [b"governance_authority"], bump = governance.authority_bump)]
+    pub governance_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct UpgradeProgram<'info> {
+    #[account(seeds = [b"governance_authority"], bump = governance.authority_bump)]
+    pub governance_authority: Signer<'info>,
+
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: validated by the upgradeable loader
+    #[account(mut, executable)]
+    pub program: UncheckedAccount<'info>,
+
+    /// CHECK: the program's data account, derived from its address
+    #[account(
+        mut,
+        seeds = [program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: UncheckedAccount<'info>,
+
+    /// CHECK: buffer holding the new program; validated by the upgradeable loader
+    #[account(mut)]
+    pub buffer: UncheckedAccount<'info>,
+
+    /// CHECK: receives the buffer's lamports
+    #[account(mut)]
+    pub spill: UncheckedAccount<'info>,
+
+    /// CHECK: upgradeable loader program
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable: UncheckedAccount<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramUpgradeAuthority<'info> {
+    #[account(seeds = [b"governance_authority"], bump = governance.authority_bump)]
+    pub governance_authority: Signer<'info>,
+
+    #[account(seeds = [b"governance"], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: validated by the upgradeable loader
+    #[account(executable)]
+    pub program: UncheckedAccount<'info>,
+
+    /// CHECK: the program's data account, derived from its address
+    #[account(
+        mut,
+        seeds = [program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: UncheckedAccount<'info>,
+
+    /// CHECK: must match `new_authority`; omitted to make the program immutable
+    pub new_authority: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: upgradeable loader program
+    #[account(address = bpf_loader_upgradeable::ID)]
+    pub bpf_loader_upgradeable: UncheckedAccount<'info>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct GovernanceParams {
+    pub proposal_deposit: u64,        // Influence escrowed by the proposer
+    pub voting_period: i64,           // Seconds
+    pub quorum_votes: u64,            // Minimum staked Influence that must vote
+    pub approval_threshold_bps: u16,  // Yes share of votes cast that must be exceeded
+    pub timelock_delay: i64,          // Seconds between passing and execution
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,  // Only the governance authority may sign
+    pub is_writable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ProposalState {
+    Voting,
+    Succeeded,
+    Defeated,
+    Executed,
+}
+
+// Events
+#[event]
+pub struct GovernanceParamsUpdated {
+    pub params: GovernanceParams,
+}
+
+#[event]
+pub struct VotesDeposited {
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub deposited: u64,
+}
+
+#[event]
+pub struct VotesWithdrawn {
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub deposited: u64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub proposal: Pubkey,
+    pub state: ProposalState,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub executable_at: i64,
+    pub deposit_returned: bool,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct ProgramUpgraded {
+    pub program: Pubkey,
+    pub buffer: Pubkey,
+}
+
+#[event]
+pub struct ProgramUpgradeAuthorityChanged {
+    pub program: Pubkey,
+    pub new_authority: Option<Pubkey>,
+}
+
+// Custom errors
+#[error_code]
+pub enum GovernanceError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Invalid governance parameters")]
+    InvalidParams,
+    #[msg("Invalid token amount")]
+    InvalidAmount,
+    #[msg("Staked votes are locked until voting ends on every proposal voted on")]
+    VotesLocked,
+    #[msg("Description URI is too long")]
+    DescriptionTooLong,
+    #[msg("Proposal instructions exceed size limits or require other signers")]
+    InvalidInstructions,
+    #[msg("Voting on this proposal is closed")]
+    VotingClosed,
+    #[msg("Voting on this proposal has not ended")]
+    VotingInProgress,
+    #[msg("Stake was deposited after the proposal's snapshot")]
+    StakeAfterSnapshot,
+    #[msg("No staked Influence to vote with")]
+    NoVotingPower,
+    #[msg("Proposal is not in the right state for this action")]
+    InvalidProposalState,
+    #[msg("Proposal timelock has not elapsed")]
+    TimelockActive,
+    #[msg("New upgrade authority account does not match the requested authority")]
+    UpgradeAuthorityMismatch,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+fn validate_governance_params(params: &GovernanceParams) -> Result<()> {
+    require!(
+        params.voting_period > 0
+            && params.timelock_delay >= 0
+            && params.quorum_votes > 0
+            && params.approval_threshold_bps < BPS_DENOMINATOR,
+        GovernanceError::InvalidParams
+    );
+    Ok(())
+}
+
+fn validate_proposal_instructions(
+    instructions: &[ProposalInstruction],
+    governance_authority: &Pubkey,
+) -> Result<()> {
+    require!(
+        !instructions.is_empty() && instructions.len() <= MAX_PROPOSAL_INSTRUCTIONS,
+        GovernanceError::InvalidInstructions
+    );
+    for instruction in instructions {
+        require!(
+            instruction.accounts.len() <= MAX_INSTRUCTION_ACCOUNTS
+                && instruction.data.len() <= MAX_INSTRUCTION_DATA_LEN
+                && instruction
+                    .accounts
+                    .iter()
+                    .all(|meta| !meta.is_signer || meta.pubkey == *governance_authority),
+            GovernanceError::InvalidInstructions
+        );
+    }
+    Ok(())
+}
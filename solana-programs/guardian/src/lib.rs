@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Guard1anPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+pub const GUARDIAN_SEED: &[u8] = b"guardian";
+const MAX_PAUSED_PROGRAMS: usize = 16;
+
+#[program]
+pub mod guardian {
+    use super::*;
+
+    // Create the guardian account. There is exactly one (its PDA has fixed
+    // seeds), so programs trust any Guardian account owned by this program.
+    pub fn initialize_guardian(
+        ctx: Context<InitializeGuardian>,
+        pauser: Pubkey,
+    ) -> Result<()> {
+        let guardian = &mut ctx.accounts.guardian;
+        guardian.authority = ctx.accounts.authority.key();
+        guardian.pauser = pauser;
+        guardian.global_paused = false;
+        guardian.paused_programs = Vec::new();
+        guardian.bump = *ctx.bumps.get("guardian").unwrap();
+
+        emit!(PauserUpdated {
+            previous_pauser: Pubkey::default(),
+            pauser,
+        });
+
+        Ok(())
+    }
+
+    // Pause or unpause one program, or the whole suite when `program` is
+    // None. The pauser (a hot key for incident response) can only pause;
+    // unpausing needs the authority.
+    pub fn set_paused(
+        ctx: Context<SetPaused>,
+        program: Option<Pubkey>,
+        paused: bool,
+    ) -> Result<()> {
+        let guardian = &mut ctx.accounts.guardian;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == guardian.authority || (paused && signer == guardian.pauser),
+            GuardianError::Unauthorized
+        );
+
+        match program {
+            None => guardian.global_paused = paused,
+            Some(program) => {
+                let index = guardian.paused_programs.iter().position(|p| *p == program);
+                match (index, paused) {
+                    (None, true) => {
+                        require!(
+                            guardian.paused_programs.len() < MAX_PAUSED_PROGRAMS,
+                            GuardianError::TooManyPausedPrograms
+                        );
+                        guardian.paused_programs.push(program);
+                    }
+                    (Some(index), false) => {
+                        guardian.paused_programs.swap_remove(index);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        emit!(PauseUpdated {
+            program,
+            paused,
+            signer,
+        });
+
+        Ok(())
+    }
+
+    // Replace the pauser key
+    pub fn set_pauser(
+        ctx: Context<UpdateGuardian>,
+        pauser: Pubkey,
+    ) -> Result<()> {
+        let guardian = &mut ctx.accounts.guardian;
+        let previous_pauser = guardian.pauser;
+        guardian.pauser = pauser;
+
+        emit!(PauserUpdated {
+            previous_pauser,
+            pauser,
+        });
+
+        Ok(())
+    }
+
+    // Hand the guardian authority to another key, such as the admin
+    // multisig's authority PDA
+    pub fn set_guardian_authority(
+        ctx: Context<UpdateGuardian>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let guardian = &mut ctx.accounts.guardian;
+        let previous_authority = guardian.authority;
+        guardian.authority = authority;
+
+        emit!(GuardianAuthorityUpdated {
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct Guardian {
+    pub authority: Pubkey,             // Pauses, unpauses and manages the guardian
+    pub pauser: Pubkey,                // May only pause
+    pub global_paused: bool,           // Halts every program that checks the guardian
+    pub paused_programs: Vec<Pubkey>,  // Individually paused program ids
+    pub bump: u8,
+}
+
+impl Guardian {
+    // Whether `program_id` must reject state-changing instructions. Programs
+    // check this in an account constraint on the passed guardian account.
+    pub fn is_paused(&self, program_id: &Pubkey) -> bool {
+        self.global_paused || self.paused_programs.contains(program_id)
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeGuardian<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<Guardian>() + MAX_PAUSED_PROGRAMS * 32,
+        seeds = [GUARDIAN_SEED],
+        bump
+    )]
+    pub guardian: Account<'info, Guardian>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GUARDIAN_SEED],
+        bump = guardian.bump,
+    )]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGuardian<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GUARDIAN_SEED],
+        bump = guardian.bump,
+        has_one = authority @ GuardianError::Unauthorized,
+    )]
+    pub guardian: Account<'info, Guardian>,
+}
+
+// Events
+#[event]
+pub struct PauseUpdated {
+    pub program: Option<Pubkey>,  // None for the global flag
+    pub paused: bool,
+    pub signer: Pubkey,
+}
+
+#[event]
+pub struct PauserUpdated {
+    pub previous_pauser: Pubkey,
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct GuardianAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum GuardianError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Too many programs are individually paused")]
+    TooManyPausedPrograms,
+}
@@ -0,0 +1,746 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use agent_registry::AgentData;
+
+declare_id!("Gu1ldsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_GUILD_NAME_LEN: usize = 32;
+const MAX_OFFICERS: usize = 5;
+
+#[program]
+pub mod guilds {
+    use super::*;
+
+    // Found a guild and its Influence treasury. The leader is its first officer.
+    pub fn create_guild(
+        ctx: Context<CreateGuild>,
+        name: String,
+        rules: MembershipRules,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_GUILD_NAME_LEN,
+            GuildError::InvalidName
+        );
+        require!(rules.max_members > 0, GuildError::InvalidRules);
+
+        let guild = &mut ctx.accounts.guild;
+        guild.leader = ctx.accounts.leader.key();
+        guild.name = name.clone();
+        guild.rules = rules;
+        guild.influence_mint = ctx.accounts.influence_mint.key();
+        guild.treasury = ctx.accounts.treasury.key();
+        guild.officers = [Pubkey::default(); MAX_OFFICERS];
+        guild.officers[0] = guild.leader;
+        guild.officer_count = 1;
+        guild.spend_threshold = 1;
+        guild.officers_version = 0;
+        guild.member_count = 0;
+        guild.spend_count = 0;
+        guild.stats = GuildStats::default();
+        guild.created_at = Clock::get()?.unix_timestamp;
+        guild.bump = *ctx.bumps.get("guild").unwrap();
+
+        emit!(GuildCreated {
+            guild: guild.key(),
+            leader: guild.leader,
+            name,
+        });
+
+        Ok(())
+    }
+
+    // Replace the membership rules (leader)
+    pub fn update_membership_rules(
+        ctx: Context<LeaderOnly>,
+        rules: MembershipRules,
+    ) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        require!(
+            rules.max_members > 0 && rules.max_members >= guild.member_count,
+            GuildError::InvalidRules
+        );
+        guild.rules = rules.clone();
+
+        emit!(MembershipRulesUpdated {
+            guild: guild.key(),
+            rules,
+        });
+
+        Ok(())
+    }
+
+    // Replace the officer set and the number of officer approvals needed to
+    // spend from the treasury (leader). Open spend proposals are invalidated.
+    pub fn set_officers(
+        ctx: Context<LeaderOnly>,
+        officers: Vec<Pubkey>,
+        spend_threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !officers.is_empty()
+                && officers.len() <= MAX_OFFICERS
+                && spend_threshold > 0
+                && spend_threshold as usize <= officers.len(),
+            GuildError::InvalidOfficers
+        );
+        for (i, officer) in officers.iter().enumerate() {
+            require!(!officers[..i].contains(officer), GuildError::InvalidOfficers);
+        }
+
+        let guild = &mut ctx.accounts.guild;
+        guild.officers = [Pubkey::default(); MAX_OFFICERS];
+        guild.officers[..officers.len()].copy_from_slice(&officers);
+        guild.officer_count = officers.len() as u8;
+        guild.spend_threshold = spend_threshold;
+        guild.officers_version = guild.officers_version.wrapping_add(1);
+
+        emit!(OfficersUpdated {
+            guild: guild.key(),
+            officers,
+            spend_threshold,
+        });
+
+        Ok(())
+    }
+
+    // Ask to join with an agent; the owner's signature is their consent.
+    // Open guilds admit immediately, others wait for an officer's approval.
+    pub fn join_guild(ctx: Context<JoinGuild>) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        let agent_data = &ctx.accounts.agent_data;
+
+        require!(
+            agent_data.is_active && !agent_data.is_frozen,
+            GuildError::AgentNotEligible
+        );
+        require!(
+            agent_data.match_count >= guild.rules.min_match_count,
+            GuildError::RequirementsNotMet
+        );
+
+        let membership = &mut ctx.accounts.membership;
+        membership.guild = guild.key();
+        membership.agent = agent_data.key();
+        membership.owner = ctx.accounts.owner.key();
+        membership.approved = false;
+        membership.joined_at = 0;
+        membership.synced_match_count = agent_data.match_count;
+        membership.synced_interaction_count = agent_data.interaction_count;
+        membership.synced_wins = agent_data.wins;
+        membership.bump = *ctx.bumps.get("membership").unwrap();
+
+        if guild.rules.open {
+            admit_member(guild, membership)?;
+        }
+
+        emit!(MembershipRequested {
+            guild: guild.key(),
+            agent: membership.agent,
+            owner: membership.owner,
+            approved: membership.approved,
+        });
+
+        Ok(())
+    }
+
+    // Admit a pending member (officer)
+    pub fn approve_member(ctx: Context<OfficerMembership>) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        let membership = &mut ctx.accounts.membership;
+        require!(!membership.approved, GuildError::AlreadyMember);
+
+        admit_member(guild, membership)?;
+
+        emit!(MemberApproved {
+            guild: guild.key(),
+            agent: membership.agent,
+            officer: ctx.accounts.officer.key(),
+        });
+
+        Ok(())
+    }
+
+    // Leave the guild, or withdraw a pending request; the membership rent
+    // returns to the owner
+    pub fn leave_guild(ctx: Context<LeaveGuild>) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        let membership = &ctx.accounts.membership;
+        if membership.approved {
+            guild.member_count = guild.member_count.saturating_sub(1);
+        }
+
+        emit!(MemberLeft {
+            guild: guild.key(),
+            agent: membership.agent,
+            removed_by: Pubkey::default(),
+        });
+
+        Ok(())
+    }
+
+    // Remove a member or reject a pending request (officer)
+    pub fn remove_member(ctx: Context<RemoveMember>) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        let membership = &ctx.accounts.membership;
+        if membership.approved {
+            guild.member_count = guild.member_count.saturating_sub(1);
+        }
+
+        emit!(MemberLeft {
+            guild: guild.key(),
+            agent: membership.agent,
+            removed_by: ctx.accounts.officer.key(),
+        });
+
+        Ok(())
+    }
+
+    // Add a member's activity since its last sync to the guild's totals
+    // (permissionless)
+    pub fn sync_member_stats(ctx: Context<SyncMemberStats>) -> Result<()> {
+        let guild = &mut ctx.accounts.guild;
+        let membership = &mut ctx.accounts.membership;
+        let agent_data = &ctx.accounts.agent_data;
+        require!(membership.approved, GuildError::NotMember);
+
+        let matches = agent_data.match_count.saturating_sub(membership.synced_match_count);
+        let interactions = agent_data
+            .interaction_count
+            .saturating_sub(membership.synced_interaction_count);
+        let wins = agent_data.wins.saturating_sub(membership.synced_wins);
+
+        guild.stats.total_matches = guild.stats.total_matches.saturating_add(matches as u64);
+        guild.stats.total_interactions = guild
+            .stats
+            .total_interactions
+            .saturating_add(interactions as u64);
+        guild.stats.total_wins = guild.stats.total_wins.saturating_add(wins as u64);
+        guild.stats.last_synced_at = Clock::get()?.unix_timestamp;
+
+        membership.synced_match_count = agent_data.match_count;
+        membership.synced_interaction_count = agent_data.interaction_count;
+        membership.synced_wins = agent_data.wins;
+
+        emit!(GuildStatsUpdated {
+            guild: guild.key(),
+            agent: membership.agent,
+            stats: guild.stats.clone(),
+        });
+
+        Ok(())
+    }
+
+    // Fund the guild treasury (anyone)
+    pub fn deposit_to_treasury(ctx: Context<DepositToTreasury>, amount: u64) -> Result<()> {
+        require!(amount > 0, GuildError::InvalidAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(TreasuryDeposit {
+            guild: ctx.accounts.guild.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Propose a treasury payment (officer). The proposer's approval counts.
+    pub fn propose_spend(
+        ctx: Context<ProposeSpend>,
+        amount: u64,
+        recipient: Pubkey,
+        memo_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, GuildError::InvalidAmount);
+        let guild = &mut ctx.accounts.guild;
+        let officer_index = officer_index(guild, &ctx.accounts.officer.key())?;
+
+        let spend = &mut ctx.accounts.spend;
+        spend.guild = guild.key();
+        spend.spend_id = guild.spend_count;
+        spend.proposer = ctx.accounts.officer.key();
+        spend.recipient = recipient;
+        spend.amount = amount;
+        spend.memo_hash = memo_hash;
+        spend.officers_version = guild.officers_version;
+        spend.approvals = 1 << officer_index;
+        spend.approval_count = 1;
+        spend.executed = false;
+        spend.bump = *ctx.bumps.get("spend").unwrap();
+
+        guild.spend_count = guild.spend_count.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(SpendProposed {
+            guild: guild.key(),
+            spend: spend.key(),
+            proposer: spend.proposer,
+            recipient,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Approve a treasury payment (officer)
+    pub fn approve_spend(ctx: Context<ApproveSpend>) -> Result<()> {
+        let guild = &ctx.accounts.guild;
+        let spend = &mut ctx.accounts.spend;
+        require!(!spend.executed, GuildError::SpendAlreadyExecuted);
+        require!(
+            spend.officers_version == guild.officers_version,
+            GuildError::StaleSpendProposal
+        );
+
+        let officer_bit = 1u8 << officer_index(guild, &ctx.accounts.officer.key())?;
+        require!(spend.approvals & officer_bit == 0, GuildError::AlreadyApproved);
+        spend.approvals |= officer_bit;
+        spend.approval_count += 1;
+
+        emit!(SpendApproved {
+            spend: spend.key(),
+            officer: ctx.accounts.officer.key(),
+            approval_count: spend.approval_count,
+        });
+
+        Ok(())
+    }
+
+    // Pay out an approved spend from the treasury (permissionless)
+    pub fn execute_spend(ctx: Context<ExecuteSpend>) -> Result<()> {
+        let guild = &ctx.accounts.guild;
+        let spend = &mut ctx.accounts.spend;
+        require!(!spend.executed, GuildError::SpendAlreadyExecuted);
+        require!(
+            spend.officers_version == guild.officers_version,
+            GuildError::StaleSpendProposal
+        );
+        require!(
+            spend.approval_count >= guild.spend_threshold,
+            GuildError::NotEnoughApprovals
+        );
+
+        let guild_seeds: &[&[u8]] = &[b"guild", guild.name.as_bytes(), &[guild.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: guild.to_account_info(),
+                },
+                &[guild_seeds],
+            ),
+            spend.amount,
+        )?;
+        spend.executed = true;
+
+        emit!(SpendExecuted {
+            guild: guild.key(),
+            spend: spend.key(),
+            recipient: spend.recipient,
+            amount: spend.amount,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct Guild {
+    pub leader: Pubkey,
+    pub name: String,
+    pub rules: MembershipRules,
+    pub influence_mint: Pubkey,
+    pub treasury: Pubkey,  // Influence token account owned by the guild PDA
+    pub officers: [Pubkey; MAX_OFFICERS],
+    pub officer_count: u8,
+    pub spend_threshold: u8,   // Officer approvals needed to spend
+    pub officers_version: u8,  // Bumped on officer changes to invalidate open spends
+    pub member_count: u16,
+    pub spend_count: u64,
+    pub stats: GuildStats,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Membership {
+    pub guild: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub approved: bool,
+    pub joined_at: i64,  // 0 while pending
+    // Agent counters already counted towards the guild's stats
+    pub synced_match_count: u32,
+    pub synced_interaction_count: u32,
+    pub synced_wins: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct SpendProposal {
+    pub guild: Pubkey,
+    pub spend_id: u64,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,  // Influence token account to pay
+    pub amount: u64,
+    pub memo_hash: [u8; 32],  // Hash of the off-chain description of the spend
+    pub officers_version: u8,
+    pub approvals: u8,  // Bitmask over the guild's officer slots
+    pub approval_count: u8,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateGuild<'info> {
+    #[account(mut)]
+    pub leader: Signer<'info>,
+
+    #[account(
+        init,
+        payer = leader,
+        space = 8 + std::mem::size_of::<Guild>() + MAX_GUILD_NAME_LEN,
+        seeds = [b"guild", name.as_bytes()],
+        bump
+    )]
+    pub guild: Account<'info, Guild>,
+
+    #[account(
+        init,
+        payer = leader,
+        seeds = [b"guild_treasury", guild.key().as_ref()],
+        bump,
+        token::mint = influence_mint,
+        token::authority = guild,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct LeaderOnly<'info> {
+    pub leader: Signer<'info>,
+
+    #[account(mut, has_one = leader @ GuildError::Unauthorized)]
+    pub guild: Account<'info, Guild>,
+}
+
+#[derive(Accounts)]
+pub struct JoinGuild<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub guild: Account<'info, Guild>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ GuildError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Membership>(),
+        seeds = [b"membership", guild.key().as_ref(), agent_data.key().as_ref()],
+        bump
+    )]
+    pub membership: Account<'info, Membership>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OfficerMembership<'info> {
+    pub officer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_officer(&guild, &officer.key()) @ GuildError::Unauthorized,
+    )]
+    pub guild: Account<'info, Guild>,
+
+    #[account(mut, has_one = guild)]
+    pub membership: Account<'info, Membership>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveGuild<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub guild: Account<'info, Guild>,
+
+    #[account(
+        mut,
+        has_one = guild,
+        has_one = owner @ GuildError::Unauthorized,
+        close = owner,
+    )]
+    pub membership: Account<'info, Membership>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    pub officer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = is_officer(&guild, &officer.key()) @ GuildError::Unauthorized,
+    )]
+    pub guild: Account<'info, Guild>,
+
+    #[account(mut, has_one = guild, has_one = owner, close = owner)]
+    pub membership: Account<'info, Membership>,
+
+    /// CHECK: receives the membership rent; validated by `has_one`
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncMemberStats<'info> {
+    #[account(mut)]
+    pub guild: Account<'info, Guild>,
+
+    #[account(mut, has_one = guild)]
+    pub membership: Account<'info, Membership>,
+
+    #[account(address = membership.agent)]
+    pub agent_data: Account<'info, AgentData>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToTreasury<'info> {
+    pub depositor: Signer<'info>,
+
+    pub guild: Account<'info, Guild>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.owner == depositor.key() @ GuildError::Unauthorized,
+        constraint = depositor_token_account.mint == guild.influence_mint,
+    )]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = guild.treasury)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeSpend<'info> {
+    #[account(mut)]
+    pub officer: Signer<'info>,
+
+    #[account(mut)]
+    pub guild: Account<'info, Guild>,
+
+    #[account(
+        init,
+        payer = officer,
+        space = 8 + std::mem::size_of::<SpendProposal>(),
+        seeds = [b"spend", guild.key().as_ref(), guild.spend_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub spend: Account<'info, SpendProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveSpend<'info> {
+    pub officer: Signer<'info>,
+
+    pub guild: Account<'info, Guild>,
+
+    #[account(mut, has_one = guild)]
+    pub spend: Account<'info, SpendProposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSpend<'info> {
+    pub guild: Account<'info, Guild>,
+
+    #[account(mut, has_one = guild)]
+    pub spend: Account<'info, SpendProposal>,
+
+    #[account(mut, address = guild.treasury)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    #[account(mut, address = spend.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MembershipRules {
+    pub open: bool,  // Admit without officer approval
+    pub max_members: u16,
+    pub min_match_count: u32,  // Registry matches an agent needs to join
+}
+
+// Member activity aggregated for inter-guild competitions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct GuildStats {
+    pub total_matches: u64,
+    pub total_interactions: u64,
+    pub total_wins: u64,
+    pub last_synced_at: i64,
+}
+
+// Events
+#[event]
+pub struct GuildCreated {
+    pub guild: Pubkey,
+    pub leader: Pubkey,
+    pub name: String,
+}
+
+#[event]
+pub struct MembershipRulesUpdated {
+    pub guild: Pubkey,
+    pub rules: MembershipRules,
+}
+
+#[event]
+pub struct OfficersUpdated {
+    pub guild: Pubkey,
+    pub officers: Vec<Pubkey>,
+    pub spend_threshold: u8,
+}
+
+#[event]
+pub struct MembershipRequested {
+    pub guild: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub approved: bool,
+}
+
+#[event]
+pub struct MemberApproved {
+    pub guild: Pubkey,
+    pub agent: Pubkey,
+    pub officer: Pubkey,
+}
+
+#[event]
+pub struct MemberLeft {
+    pub guild: Pubkey,
+    pub agent: Pubkey,
+    pub removed_by: Pubkey,  // Pubkey::default() when the owner left
+}
+
+#[event]
+pub struct GuildStatsUpdated {
+    pub guild: Pubkey,
+    pub agent: Pubkey,
+    pub stats: GuildStats,
+}
+
+#[event]
+pub struct TreasuryDeposit {
+    pub guild: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SpendProposed {
+    pub guild: Pubkey,
+    pub spend: Pubkey,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SpendApproved {
+    pub spend: Pubkey,
+    pub officer: Pubkey,
+    pub approval_count: u8,
+}
+
+#[event]
+pub struct SpendExecuted {
+    pub guild: Pubkey,
+    pub spend: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum GuildError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Guild name must be 1-32 bytes")]
+    InvalidName,
+    #[msg("Invalid membership rules")]
+    InvalidRules,
+    #[msg("Officers must be unique, at most five, and cover the spend threshold")]
+    InvalidOfficers,
+    #[msg("Agent must be active and not frozen to join")]
+    AgentNotEligible,
+    #[msg("Agent does not meet the guild's membership requirements")]
+    RequirementsNotMet,
+    #[msg("Guild is full")]
+    GuildFull,
+    #[msg("Agent is already a member")]
+    AlreadyMember,
+    #[msg("Agent is not a member")]
+    NotMember,
+    #[msg("Invalid token amount")]
+    InvalidAmount,
+    #[msg("Officer has already approved this spend")]
+    AlreadyApproved,
+    #[msg("Spend has already been executed")]
+    SpendAlreadyExecuted,
+    #[msg("Officers changed since this spend was proposed")]
+    StaleSpendProposal,
+    #[msg("Spend does not have enough officer approvals")]
+    NotEnoughApprovals,
+}
+
+// Helper functions
+fn is_officer(guild: &Guild, key: &Pubkey) -> bool {
+    guild.officers[..guild.officer_count as usize].contains(key)
+}
+
+fn officer_index(guild: &Guild, key: &Pubkey) -> Result<usize> {
+    guild.officers[..guild.officer_count as usize]
+        .iter()
+        .position(|officer| officer == key)
+        .ok_or_else(|| error!(GuildError::Unauthorized))
+}
+
+fn admit_member(guild: &mut Guild, membership: &mut Membership) -> Result<()> {
+    require!(guild.member_count < guild.rules.max_members, GuildError::GuildFull);
+    guild.member_count += 1;
+    membership.approved = true;
+    membership.joined_at = Clock::get()?.unix_timestamp;
+    Ok(())
+}
@@ -0,0 +1,172 @@
+// Interface for programs and clients that integrate with the influence
+// token: the program id and account layouts, PDA finders, and instruction
+// builders. The `cpi` feature adds the program's Anchor CPI module.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+
+pub use influence::{
+    accounts, id, instruction, program, BurnRecord, InfluenceError, MintConfig, MintRecord,
+    TransferRecord, ANALYTICS_HOOK_AUTHORITY_SEED, ANALYTICS_PROGRAM_ID, ID,
+};
+
+#[cfg(feature = "cpi")]
+pub use influence::cpi;
+
+pub mod pda {
+    use super::*;
+
+    pub fn find_mint_config_address(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"mint_config", mint.as_ref()], &ID)
+    }
+
+    // Records are keyed by the clock at execution, so `timestamp` must be the
+    // unix time the transaction is expected to land at
+    pub fn find_mint_record_address(mint: &Pubkey, recipient: &Pubkey, timestamp: i64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"mint_record", mint.as_ref(), recipient.as_ref(), &timestamp.to_le_bytes()],
+            &ID,
+        )
+    }
+
+    // `sender` is the owner of the sending token account, not a session signer
+    pub fn find_transfer_record_address(
+        mint: &Pubkey,
+        sender: &Pubkey,
+        recipient: &Pubkey,
+        timestamp: i64,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"transfer_record",
+                mint.as_ref(),
+                sender.as_ref(),
+                recipient.as_ref(),
+                &timestamp.to_le_bytes(),
+            ],
+            &ID,
+        )
+    }
+
+    // The guardian whose pause flags gate state-changing instructions
+    pub fn find_guardian_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[guardian::GUARDIAN_SEED], &guardian::ID)
+    }
+
+    // The rate limiter config and a wallet's quota, for instructions that
+    // consume rate-limited actions
+    pub fn find_rate_limiter_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[rate_limiter::RATE_LIMITER_CONFIG_SEED], &rate_limiter::ID)
+    }
+
+    pub fn find_wallet_quota_address(wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[rate_limiter::WALLET_QUOTA_SEED, wallet.as_ref()], &rate_limiter::ID)
+    }
+
+    // A wallet's attestation credential, which gated builds require for large transfers
+    pub fn find_credential_address(wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[attestation::CREDENTIAL_SEED, wallet.as_ref()], &attestation::ID)
+    }
+
+    // The analytics hook signer and the analytics state it updates
+    pub fn find_analytics_hook_authority() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ANALYTICS_HOOK_AUTHORITY_SEED], &ID)
+    }
+
+    pub fn find_analytics_state_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"analytics"], &ANALYTICS_PROGRAM_ID)
+    }
+}
+
+pub mod builders {
+    use super::*;
+
+    pub fn mint_tokens(
+        authority: Pubkey,
+        mint: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+        reason: String,
+        timestamp: i64,
+    ) -> Instruction {
+        build(
+            accounts::MintTokens {
+                authority,
+                mint_config: pda::find_mint_config_address(&mint).0,
+                mint,
+                recipient,
+                recipient_token_account: get_associated_token_address(&recipient, &mint),
+                mint_record: pda::find_mint_record_address(&mint, &recipient, timestamp).0,
+                token_program: anchor_spl::token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+                guardian: pda::find_guardian_address().0,
+                rate_limiter_config: pda::find_rate_limiter_config_address().0,
+                wallet_quota: pda::find_wallet_quota_address(&authority).0,
+                rate_limiter_program: rate_limiter::ID,
+                analytics_hook_authority: pda::find_analytics_hook_authority().0,
+                analytics_state: pda::find_analytics_state_address().0,
+                analytics_program: ANALYTICS_PROGRAM_ID,
+            },
+            instruction::MintTokens { amount, reason },
+        )
+    }
+
+    // Transfer from `owner`'s associated token account. `signer` is the owner,
+    // or the session signer when tipping under `session`.
+    pub fn transfer_tokens(
+        signer: Pubkey,
+        owner: Pubkey,
+        mint: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+        reason: String,
+        timestamp: i64,
+        session: Option<Pubkey>,
+    ) -> Instruction {
+        build(
+            accounts::TransferTokens {
+                sender: signer,
+                mint,
+                recipient,
+                sender_token_account: get_associated_token_address(&owner, &mint),
+                recipient_token_account: get_associated_token_address(&recipient, &mint),
+                transfer_record: pda::find_transfer_record_address(&mint, &owner, &recipient, timestamp).0,
+                session,
+                sessions_program: session.map(|_| sessions::ID),
+                credential: Some(pda::find_credential_address(&owner).0),
+                analytics_hook_authority: pda::find_analytics_hook_authority().0,
+                analytics_state: pda::find_analytics_state_address().0,
+                analytics_program: ANALYTICS_PROGRAM_ID,
+                token_program: anchor_spl::token::ID,
+                associated_token_program: associated_token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+                guardian: pda::find_guardian_address().0,
+            },
+            instruction::TransferTokens { amount, reason },
+        )
+    }
+
+    pub fn update_mint_status(authority: Pubkey, mint: Pubkey, is_active: bool) -> Instruction {
+        build(
+            accounts::UpdateMint {
+                authority,
+                mint_config: pda::find_mint_config_address(&mint).0,
+            },
+            instruction::UpdateMintStatus { is_active },
+        )
+    }
+
+    // Any instruction from its Anchor account and argument structs
+    pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+}
@@ -1,11 +1,27 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
 use anchor_spl::{
     token::{self, Mint, Token, TokenAccount, Transfer},
     associated_token::AssociatedToken,
 };
+use armour_light::{LightCpiAccounts, NewRecordArgs};
+use sessions::program::Sessions;
+use sessions::SessionKey;
+use guardian::Guardian;
+use rate_limiter::{program::RateLimiter, RateLimitedAction};
 
 declare_id!("1nf1uenceT0kenPr0graMxXxXxXxXxXxXxXxXxXxX");
 
+// Analytics program, notified of mints, burns and transfers for its epoch metrics
+pub const ANALYTICS_PROGRAM_ID: Pubkey = solana_program::pubkey!("Ana1yt1csPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+pub const ANALYTICS_HOOK_AUTHORITY_SEED: &[u8] = b"analytics_hook_authority";
+
+// Transfers of at least this much (10,000 INF) need a credential in gated builds
+#[cfg(feature = "gated")]
+const LARGE_TRANSFER_THRESHOLD: u64 = 10_000_000_000;
+
 #[program]
 pub mod influence {
     use super::*;
@@ -41,6 +57,16 @@ pub mod influence {
         amount: u64,
         reason: String,
     ) -> Result<()> {
+        rate_limiter::consume(
+            &ctx.accounts.rate_limiter_program.to_account_info(),
+            &ctx.accounts.rate_limiter_config,
+            &ctx.accounts.wallet_quota,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            RateLimitedAction::Mint,
+            1,
+        )?;
+
         // Ensure mint is active
         require!(
             ctx.accounts.mint_config.is_active,
@@ -77,6 +103,15 @@ pub mod influence {
             reason: mint_record.reason.clone(),
         });
         
+        notify_analytics_hook(
+            &ctx.accounts.analytics_program,
+            &ctx.accounts.analytics_hook_authority,
+            *ctx.bumps.get("analytics_hook_authority").unwrap(),
+            &ctx.accounts.analytics_state,
+            "on_tokens_minted",
+            amount,
+        )?;
+        
         Ok(())
     }
     
@@ -116,10 +151,21 @@ pub mod influence {
             reason: burn_record.reason.clone(),
         });
         
+        notify_analytics_hook(
+            &ctx.accounts.analytics_program,
+            &ctx.accounts.analytics_hook_authority,
+            *ctx.bumps.get("analytics_hook_authority").unwrap(),
+            &ctx.accounts.analytics_state,
+            "on_tokens_burned",
+            amount,
+        )?;
+        
         Ok(())
     }
     
-    // Transfer influence tokens between users
+    // Transfer influence tokens between users. A session key with the tip
+    // scope may sign in place of the sender; the tokens then move through the
+    // sessions program, which enforces the session's tip limit.
     pub fn transfer_tokens(
         ctx: Context<TransferTokens>,
         amount: u64,
@@ -128,6 +174,95 @@ pub mod influence {
         // Ensure amount is valid
         require!(amount > 0, InfluenceError::InvalidAmount);
         
+        let sender = ctx.accounts.sender_token_account.owner;
+        #[cfg(feature = "gated")]
+        if amount >= LARGE_TRANSFER_THRESHOLD {
+            attestation::require_credential(ctx.accounts.credential.as_deref(), &sender)?;
+        }
+        if let Some(session) = &ctx.accounts.session {
+            require_keys_eq!(session.owner, sender, InfluenceError::Unauthorized);
+            let sessions_program = ctx
+                .accounts
+                .sessions_program
+                .as_ref()
+                .ok_or(InfluenceError::MissingSessionsProgram)?;
+            
+            // Transfer tokens from sender to recipient as the session's delegate
+            sessions::cpi::spend_tip(
+                CpiContext::new(
+                    sessions_program.to_account_info(),
+                    sessions::cpi::accounts::SpendTip {
+                        session_signer: ctx.accounts.sender.to_account_info(),
+                        session: session.to_account_info(),
+                        owner_token_account: ctx.accounts.sender_token_account.to_account_info(),
+                        recipient_token_account: ctx.accounts.recipient_token_account.to_account_info(),
+                        token_program: ctx.accounts.token_program.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        } else {
+            require_keys_eq!(sender, ctx.accounts.sender.key(), InfluenceError::Unauthorized);
+            
+            // Transfer tokens from sender to recipient
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.sender_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.sender.to_account_info(),
+            };
+            
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            
+            token::transfer(cpi_ctx, amount)?;
+        }
+        
+        // Record the transfer transaction
+        let transfer_record = &mut ctx.accounts.transfer_record;
+        transfer_record.mint = ctx.accounts.mint.key();
+        transfer_record.sender = sender;
+        transfer_record.recipient = ctx.accounts.recipient.key();
+        transfer_record.amount = amount;
+        transfer_record.timestamp = Clock::get()?.unix_timestamp;
+        transfer_record.reason = reason;
+        
+        emit!(TokensTransferred {
+            mint: transfer_record.mint,
+            sender: transfer_record.sender,
+            recipient: transfer_record.recipient,
+            amount,
+            reason: transfer_record.reason.clone(),
+        });
+        
+        notify_analytics_hook(
+            &ctx.accounts.analytics_program,
+            &ctx.accounts.analytics_hook_authority,
+            *ctx.bumps.get("analytics_hook_authority").unwrap(),
+            &ctx.accounts.analytics_state,
+            "on_tokens_transferred",
+            amount,
+        )?;
+        
+        Ok(())
+    }
+    
+    // Transfer influence tokens, storing the transfer record as a Light
+    // compressed account rather than a PDA so frequent senders don't pay
+    // rent per transfer. Light's trees and queues are passed as remaining
+    // accounts.
+    pub fn transfer_tokens_light<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferTokensLight<'info>>,
+        amount: u64,
+        reason: String,
+        light_args: NewRecordArgs,
+    ) -> Result<()> {
+        // Ensure amount is valid
+        require!(amount > 0, InfluenceError::InvalidAmount);
+        #[cfg(feature = "gated")]
+        if amount >= LARGE_TRANSFER_THRESHOLD {
+            attestation::require_credential(ctx.accounts.credential.as_deref(), &ctx.accounts.sender.key())?;
+        }
+        
         // Transfer tokens from sender to recipient
         let cpi_accounts = Transfer {
             from: ctx.accounts.sender_token_account.to_account_info(),
@@ -140,14 +275,29 @@ pub mod influence {
         
         token::transfer(cpi_ctx, amount)?;
         
-        // Record the transfer transaction
-        let transfer_record = &mut ctx.accounts.transfer_record;
-        transfer_record.mint = ctx.accounts.mint.key();
-        transfer_record.sender = ctx.accounts.sender.key();
-        transfer_record.recipient = ctx.accounts.recipient.key();
-        transfer_record.amount = amount;
-        transfer_record.timestamp = Clock::get()?.unix_timestamp;
-        transfer_record.reason = reason;
+        // Record the transfer transaction under the address its PDA would have
+        let transfer_record = TransferRecord {
+            mint: ctx.accounts.mint.key(),
+            sender: ctx.accounts.sender.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+            reason,
+        };
+        let address = armour_light::create_record(
+            &ctx.accounts.light.cpi_accounts(ctx.accounts.sender.to_account_info()),
+            ctx.program_id,
+            ctx.remaining_accounts,
+            &light_args,
+            &[
+                b"transfer_record",
+                transfer_record.mint.as_ref(),
+                transfer_record.sender.as_ref(),
+                transfer_record.recipient.as_ref(),
+                &transfer_record.timestamp.to_le_bytes(),
+            ],
+            &transfer_record,
+        )?;
         
         emit!(TokensTransferred {
             mint: transfer_record.mint,
@@ -156,6 +306,20 @@ pub mod influence {
             amount,
             reason: transfer_record.reason.clone(),
         });
+        emit!(TransferRecordCompressed {
+            mint: transfer_record.mint,
+            sender: transfer_record.sender,
+            address,
+        });
+        
+        notify_analytics_hook(
+            &ctx.accounts.analytics_program,
+            &ctx.accounts.analytics_hook_authority,
+            *ctx.bumps.get("analytics_hook_authority").unwrap(),
+            &ctx.accounts.analytics_state,
+            "on_tokens_transferred",
+            amount,
+        )?;
         
         Ok(())
     }
@@ -182,6 +346,25 @@ pub mod influence {
         
         Ok(())
     }
+
+    // Hand the mint config's authority to another key, such as the admin
+    // multisig's authority PDA
+    pub fn set_mint_authority(
+        ctx: Context<UpdateMint>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let mint_config = &mut ctx.accounts.mint_config;
+        let previous_authority = mint_config.authority;
+        mint_config.authority = authority;
+
+        emit!(MintAuthorityUpdated {
+            mint: mint_config.mint,
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
 }
 
 // Account structures
@@ -294,6 +477,30 @@ pub struct MintTokens<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    
+    #[account(constraint = !guardian.is_paused(&ID) @ InfluenceError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// CHECK: validated by the rate limiter program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the rate limiter program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+    
+    pub rate_limiter_program: Program<'info, RateLimiter>,
+    
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+    
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -327,10 +534,26 @@ pub struct BurnTokens<'info> {
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    
+    #[account(constraint = !guardian.is_paused(&ID) @ InfluenceError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+    
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
 pub struct TransferTokens<'info> {
+    // The sender, or a session key holder tipping for them
     #[account(mut)]
     pub sender: Signer<'info>,
     
@@ -338,10 +561,10 @@ pub struct TransferTokens<'info> {
     
     pub recipient: AccountInfo<'info>,
     
+    // Owned by the sender, or by the session's owner when tipping with a session key
     #[account(
         mut,
         constraint = sender_token_account.mint == mint.key(),
-        constraint = sender_token_account.owner == sender.key(),
     )]
     pub sender_token_account: Account<'info, TokenAccount>,
     
@@ -360,7 +583,7 @@ pub struct TransferTokens<'info> {
         seeds = [
             b"transfer_record", 
             mint.key().as_ref(), 
-            sender.key().as_ref(),
+            sender_token_account.owner.as_ref(),
             recipient.key().as_ref(),
             &Clock::get()?.unix_timestamp.to_le_bytes()
         ],
@@ -368,10 +591,115 @@ pub struct TransferTokens<'info> {
     )]
     pub transfer_record: Account<'info, TransferRecord>,
     
+    // Required when a session key signs
+    pub session: Option<Account<'info, SessionKey>>,
+    pub sessions_program: Option<Program<'info, Sessions>>,
+    
+    /// CHECK: the sending owner's attestation credential, required only for
+    /// large transfers in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+    
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    
+    #[account(constraint = !guardian.is_paused(&ID) @ InfluenceError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+    
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferTokensLight<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == mint.key(),
+        constraint = sender_token_account.owner == sender.key(),
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    
+    pub light: LightAccounts<'info>,
+    
+    /// CHECK: the sender's attestation credential, required only for large
+    /// transfers in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    
+    #[account(constraint = !guardian.is_paused(&ID) @ InfluenceError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// CHECK: PDA that signs analytics hook CPIs
+    #[account(seeds = [ANALYTICS_HOOK_AUTHORITY_SEED], bump)]
+    pub analytics_hook_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the analytics program
+    #[account(mut)]
+    pub analytics_state: UncheckedAccount<'info>,
+    
+    /// CHECK: the analytics program
+    #[account(address = ANALYTICS_PROGRAM_ID, executable)]
+    pub analytics_program: UncheckedAccount<'info>,
+}
+
+// Light Protocol system accounts for compressed records; see armour_light
+#[derive(Accounts)]
+pub struct LightAccounts<'info> {
+    /// CHECK: PDA signing for this program's compressed accounts
+    #[account(seeds = [armour_light::CPI_AUTHORITY_SEED], bump)]
+    pub cpi_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the Light system program
+    pub registered_program_pda: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the Light system program
+    pub account_compression_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: this program
+    #[account(address = crate::ID)]
+    pub invoking_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Light's log wrapper
+    #[account(address = armour_light::LIGHT_NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Light account compression program
+    #[account(address = armour_light::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub account_compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Light system program
+    #[account(address = armour_light::LIGHT_SYSTEM_PROGRAM_ID, executable)]
+    pub light_system_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -420,12 +748,27 @@ pub struct TokensTransferred {
     pub reason: String,
 }
 
+// Address of a transfer record stored as a Light compressed account
+#[event]
+pub struct TransferRecordCompressed {
+    pub mint: Pubkey,
+    pub sender: Pubkey,
+    pub address: [u8; 32],
+}
+
 #[event]
 pub struct MintStatusUpdated {
     pub mint: Pubkey,
     pub is_active: bool,
 }
 
+#[event]
+pub struct MintAuthorityUpdated {
+    pub mint: Pubkey,
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
 // Custom errors
 #[error_code]
 pub enum InfluenceError {
@@ -435,4 +778,53 @@ pub enum InfluenceError {
     InvalidAmount,
     #[msg("Mint is not active")]
     MintInactive,
+    #[msg("Sessions program is required when signing with a session key")]
+    MissingSessionsProgram,
+    #[msg("The influence program is paused by the guardian")]
+    ProgramPaused,
+}
+
+// Helper functions
+impl<'info> LightAccounts<'info> {
+    pub fn cpi_accounts(&self, fee_payer: AccountInfo<'info>) -> LightCpiAccounts<'info> {
+        LightCpiAccounts {
+            fee_payer,
+            cpi_authority: self.cpi_authority.to_account_info(),
+            registered_program_pda: self.registered_program_pda.to_account_info(),
+            account_compression_authority: self.account_compression_authority.to_account_info(),
+            invoking_program: self.invoking_program.to_account_info(),
+            noop_program: self.noop_program.to_account_info(),
+            account_compression_program: self.account_compression_program.to_account_info(),
+            light_system_program: self.light_system_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        }
+    }
+}
+
+// Report a mint, burn or transfer of `amount` to the analytics program's
+// `instruction_name` hook
+fn notify_analytics_hook<'info>(
+    program: &AccountInfo<'info>,
+    hook_authority: &AccountInfo<'info>,
+    hook_authority_bump: u8,
+    analytics_state: &AccountInfo<'info>,
+    instruction_name: &str,
+    amount: u64,
+) -> Result<()> {
+    let mut data = hash(format!("global:{}", instruction_name).as_bytes()).to_bytes()[..8].to_vec();
+    amount.serialize(&mut data)?;
+    invoke_signed(
+        &Instruction {
+            program_id: ANALYTICS_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(hook_authority.key(), true),
+                AccountMeta::new(analytics_state.key(), false),
+            ],
+            data,
+        },
+        &[hook_authority.clone(), analytics_state.clone(), program.clone()],
+        &[&[ANALYTICS_HOOK_AUTHORITY_SEED, &[hook_authority_bump]]],
+    )?;
+
+    Ok(())
 }
@@ -0,0 +1,836 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use agent_registry::AgentData;
+use relationship::program::Relationship;
+use relationship::{HookRegistry, InteractionNonce, InteractionType, RelationshipData};
+
+declare_id!("ItemsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+// Crafting program, whose crafting authority PDA burns recipe inputs and grants outputs
+pub const CRAFTING_PROGRAM_ID: Pubkey = solana_program::pubkey!("Craft1ngPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+// Raffle program, whose raffle authority PDA awards item prizes
+pub const RAFFLE_PROGRAM_ID: Pubkey = solana_program::pubkey!("Raff1ePr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_NAME_LEN: usize = 32;
+const MAX_URI_LEN: usize = 200;
+const MAX_INVENTORY_SLOTS: usize = 16;  // Distinct fungible item types per agent
+
+#[program]
+pub mod items {
+    use super::*;
+
+    // Create the items config (admin)
+    pub fn initialize_items(ctx: Context<InitializeItems>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.item_type_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Define a purchasable item (admin). Fungible items are held as balances
+    // in the agent's inventory; non-fungible items are minted as individual
+    // serial-numbered instances. A max supply of zero means unlimited.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_item_type(
+        ctx: Context<CreateItemType>,
+        item_id: u16,
+        category: ItemCategory,
+        name: String,
+        uri: String,
+        fungible: bool,
+        price: u64,
+        max_supply: u32,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_NAME_LEN && uri.len() <= MAX_URI_LEN,
+            ItemsError::InvalidMetadata
+        );
+
+        let item_type = &mut ctx.accounts.item_type;
+        item_type.item_id = item_id;
+        item_type.category = category.clone();
+        item_type.name = name.clone();
+        item_type.uri = uri;
+        item_type.fungible = fungible;
+        item_type.price = price;
+        item_type.max_supply = max_supply;
+        item_type.minted = 0;
+        item_type.active = true;
+        item_type.bump = *ctx.bumps.get("item_type").unwrap();
+
+        let config = &mut ctx.accounts.config;
+        config.item_type_count = config.item_type_count.checked_add(1).unwrap_or(u16::MAX);
+
+        emit!(ItemTypeCreated {
+            item_id,
+            category,
+            name,
+            fungible,
+            price,
+            max_supply,
+        });
+
+        Ok(())
+    }
+
+    // Update an item's price or retire it from sale (admin). Items already
+    // held can still be consumed.
+    pub fn update_item_type(
+        ctx: Context<UpdateItemType>,
+        price: u64,
+        active: bool,
+    ) -> Result<()> {
+        let item_type = &mut ctx.accounts.item_type;
+        item_type.price = price;
+        item_type.active = active;
+
+        emit!(ItemTypeUpdated {
+            item_id: item_type.item_id,
+            price,
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Buy fungible items for an agent, burning the Influence price
+    pub fn purchase_item(ctx: Context<PurchaseItem>, quantity: u32) -> Result<()> {
+        require!(quantity > 0, ItemsError::InvalidQuantity);
+        let item_type = &mut ctx.accounts.item_type;
+        require!(item_type.fungible, ItemsError::NotFungible);
+
+        let cost = reserve_supply(item_type, quantity)?;
+        burn_price(
+            cost,
+            &ctx.accounts.influence_mint,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+        )?;
+
+        let agent = ctx.accounts.agent_data.key();
+        let inventory = &mut ctx.accounts.inventory;
+        inventory.agent = agent;
+        inventory.bump = *ctx.bumps.get("inventory").unwrap();
+        add_to_inventory(inventory, item_type.item_id, quantity)?;
+
+        emit!(ItemPurchased {
+            agent,
+            item_id: item_type.item_id,
+            quantity,
+            serial: None,
+            cost,
+        });
+
+        Ok(())
+    }
+
+    // Buy one non-fungible item for an agent, minting the next serial
+    pub fn purchase_unique_item(ctx: Context<PurchaseUniqueItem>) -> Result<()> {
+        let item_type = &mut ctx.accounts.item_type;
+        require!(!item_type.fungible, ItemsError::NotUnique);
+
+        let serial = item_type.minted;
+        let cost = reserve_supply(item_type, 1)?;
+        burn_price(
+            cost,
+            &ctx.accounts.influence_mint,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+        )?;
+
+        let agent = ctx.accounts.agent_data.key();
+        let instance = &mut ctx.accounts.item_instance;
+        instance.item_id = item_type.item_id;
+        instance.serial = serial;
+        instance.agent = agent;
+        instance.bump = *ctx.bumps.get("item_instance").unwrap();
+
+        let inventory = &mut ctx.accounts.inventory;
+        inventory.agent = agent;
+        inventory.unique_items = inventory.unique_items.checked_add(1).unwrap_or(u16::MAX);
+        inventory.bump = *ctx.bumps.get("inventory").unwrap();
+
+        emit!(ItemPurchased {
+            agent,
+            item_id: item_type.item_id,
+            quantity: 1,
+            serial: Some(serial),
+            cost,
+        });
+
+        Ok(())
+    }
+
+    // Give one item to the other agent in a relationship. The item is taken
+    // from the giver's inventory (or the non-fungible instance is closed) and
+    // a Gift interaction is recorded in the relationship program. Remaining
+    // accounts are forwarded as the relationship program's hook accounts.
+    pub fn consume_item<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConsumeItem<'info>>,
+    ) -> Result<()> {
+        let giver = ctx.accounts.agent_data.key();
+        let recipient = {
+            let relationship_data = ctx.accounts.relationship_data.load()?;
+            if relationship_data.agent_one == giver {
+                relationship_data.agent_two
+            } else if relationship_data.agent_two == giver {
+                relationship_data.agent_one
+            } else {
+                return err!(ItemsError::NotInRelationship);
+            }
+        };
+
+        let item_type = &ctx.accounts.item_type;
+        let inventory = &mut ctx.accounts.inventory;
+        let serial = if item_type.fungible {
+            remove_from_inventory(inventory, item_type.item_id, 1)?;
+            None
+        } else {
+            let Some(instance) = &ctx.accounts.item_instance else {
+                return err!(ItemsError::MissingItemInstance);
+            };
+            require!(
+                instance.item_id == item_type.item_id && instance.agent == giver,
+                ItemsError::InsufficientItems
+            );
+            inventory.unique_items = inventory.unique_items.saturating_sub(1);
+            instance.close(ctx.accounts.owner.to_account_info())?;
+            Some(instance.serial)
+        };
+
+        let interaction_data = match serial {
+            Some(serial) => format!("item:{}:{}#{}", item_type.item_id, item_type.name, serial),
+            None => format!("item:{}:{}", item_type.item_id, item_type.name),
+        };
+        relationship::cpi::record_interaction(
+            CpiContext::new(
+                ctx.accounts.relationship_program.to_account_info(),
+                relationship::cpi::accounts::RecordInteraction {
+                    authority: ctx.accounts.owner.to_account_info(),
+                    session: None,
+                    event_recorder: None,
+                    relationship_data: ctx.accounts.relationship_data.to_account_info(),
+                    agent_one: ctx.accounts.agent_one.to_account_info(),
+                    agent_two: ctx.accounts.agent_two.to_account_info(),
+                    interaction_nonce: ctx.accounts.interaction_nonce.to_account_info(),
+                    interaction_data: ctx.accounts.interaction_data.to_account_info(),
+                    hook_registry: ctx.accounts.hook_registry.to_account_info(),
+                    hook_authority: ctx.accounts.hook_authority.to_account_info(),
+                    interaction_type_registry: None,
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    config: ctx.accounts.relationship_config.to_account_info(),
+                    recorder_rate: ctx.accounts.recorder_rate.to_account_info(),
+                    matchmaker_stats: None,
+                    matchmaker_token_account: None,
+                    rewards_vault: None,
+                    token_program: None,
+                    stats: ctx.accounts.relationship_stats.to_account_info(),
+                    guardian: ctx.accounts.guardian.to_account_info(),
+                    rate_limiter_config: ctx.accounts.rate_limiter_config.to_account_info(),
+                    wallet_quota: ctx.accounts.wallet_quota.to_account_info(),
+                    rate_limiter_program: ctx.accounts.rate_limiter_program.to_account_info(),
+                    event_authority: ctx.accounts.relationship_event_authority.to_account_info(),
+                    program: ctx.accounts.relationship_program.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            ctx.accounts.interaction_nonce.next_index,
+            InteractionType::Gift,
+            interaction_data,
+            None,
+        )?;
+
+        emit!(ItemConsumed {
+            giver,
+            recipient,
+            relationship: ctx.accounts.relationship_data.key(),
+            item_id: item_type.item_id,
+            serial,
+        });
+
+        Ok(())
+    }
+    // Remove fungible items from an agent's inventory (crafting authority),
+    // used to consume recipe inputs
+    pub fn burn_items(ctx: Context<BurnItems>, items: Vec<ItemBalance>) -> Result<()> {
+        let inventory = &mut ctx.accounts.inventory;
+        for item in items.iter() {
+            remove_from_inventory(inventory, item.item_id, item.amount)?;
+        }
+
+        emit!(ItemsBurned {
+            agent: inventory.agent,
+            items,
+        });
+
+        Ok(())
+    }
+
+    // Add fungible items to an agent's inventory (crafting authority), used
+    // to deliver recipe outputs. Granted items count against the item's
+    // supply but are not charged.
+    pub fn grant_items(ctx: Context<GrantItems>, quantity: u32) -> Result<()> {
+        let inventory = &mut ctx.accounts.inventory;
+        inventory.agent = ctx.accounts.agent_data.key();
+        inventory.bump = *ctx.bumps.get("inventory").unwrap();
+        deliver_items(&mut ctx.accounts.item_type, inventory, quantity)
+    }
+
+    // Add fungible items to an agent's inventory (raffle authority), used to
+    // deliver item prizes. Like crafted items, awards count against supply.
+    pub fn award_items(ctx: Context<AwardItems>, quantity: u32) -> Result<()> {
+        let inventory = &mut ctx.accounts.inventory;
+        inventory.agent = ctx.accounts.agent_data.key();
+        inventory.bump = *ctx.bumps.get("inventory").unwrap();
+        deliver_items(&mut ctx.accounts.item_type, inventory, quantity)
+    }
+}
+
+// Account structures
+#[account]
+pub struct ItemsConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub item_type_count: u16,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ItemType {
+    pub item_id: u16,
+    pub category: ItemCategory,
+    pub name: String,
+    pub uri: String,
+    pub fungible: bool,
+    pub price: u64,        // Influence burned per item
+    pub max_supply: u32,   // 0 for unlimited
+    pub minted: u32,
+    pub active: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Inventory {
+    pub agent: Pubkey,
+    pub items: Vec<ItemBalance>,  // Fungible balances, at most MAX_INVENTORY_SLOTS
+    pub unique_items: u16,        // Non-fungible instances held
+    pub bump: u8,
+}
+
+#[account]
+pub struct ItemInstance {
+    pub item_id: u16,
+    pub serial: u32,
+    pub agent: Pubkey,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeItems<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<ItemsConfig>(),
+        seeds = [b"items_config"],
+        bump
+    )]
+    pub config: Account<'info, ItemsConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(item_id: u16)]
+pub struct CreateItemType<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"items_config"],
+        bump = config.bump,
+        has_one = admin @ ItemsError::Unauthorized,
+    )]
+    pub config: Account<'info, ItemsConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<ItemType>() + MAX_NAME_LEN + MAX_URI_LEN,
+        seeds = [b"item_type", item_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub item_type: Account<'info, ItemType>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateItemType<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"items_config"],
+        bump = config.bump,
+        has_one = admin @ ItemsError::Unauthorized,
+    )]
+    pub config: Account<'info, ItemsConfig>,
+
+    #[account(mut)]
+    pub item_type: Account<'info, ItemType>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseItem<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"items_config"], bump = config.bump)]
+    pub config: Account<'info, ItemsConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"item_type", item_type.item_id.to_le_bytes().as_ref()],
+        bump = item_type.bump,
+    )]
+    pub item_type: Account<'info, ItemType>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ ItemsError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Inventory>()
+            + MAX_INVENTORY_SLOTS * std::mem::size_of::<ItemBalance>(),
+        seeds = [b"inventory", agent_data.key().as_ref()],
+        bump
+    )]
+    pub inventory: Account<'info, Inventory>,
+
+    #[account(mut, address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ ItemsError::Unauthorized,
+        constraint = owner_token_account.mint == config.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseUniqueItem<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"items_config"], bump = config.bump)]
+    pub config: Account<'info, ItemsConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"item_type", item_type.item_id.to_le_bytes().as_ref()],
+        bump = item_type.bump,
+    )]
+    pub item_type: Account<'info, ItemType>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ ItemsError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Inventory>()
+            + MAX_INVENTORY_SLOTS * std::mem::size_of::<ItemBalance>(),
+        seeds = [b"inventory", agent_data.key().as_ref()],
+        bump
+    )]
+    pub inventory: Account<'info, Inventory>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<ItemInstance>(),
+        seeds = [
+            b"item_instance",
+            item_type.key().as_ref(),
+            item_type.minted.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub item_instance: Account<'info, ItemInstance>,
+
+    #[account(mut, address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ ItemsError::Unauthorized,
+        constraint = owner_token_account.mint == config.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeItem<'info> {
+    // Signs as the interaction's recorder and pays for the interaction account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [b"item_type", item_type.item_id.to_le_bytes().as_ref()],
+        bump = item_type.bump,
+    )]
+    pub item_type: Account<'info, ItemType>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ ItemsError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory", agent_data.key().as_ref()],
+        bump = inventory.bump,
+    )]
+    pub inventory: Account<'info, Inventory>,
+
+    // Required for non-fungible items
+    #[account(mut)]
+    pub item_instance: Option<Account<'info, ItemInstance>>,
+
+    // Relationship program accounts for the Gift interaction
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+    /// CHECK: validated by the relationship program
+    pub agent_one: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub agent_two: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub interaction_nonce: Account<'info, InteractionNonce>,
+    /// CHECK: initialized by the relationship program
+    #[account(mut)]
+    pub interaction_data: UncheckedAccount<'info>,
+    pub hook_registry: Account<'info, HookRegistry>,
+    /// CHECK: validated by the relationship program
+    pub hook_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub relationship_config: UncheckedAccount<'info>,
+    /// CHECK: the owner's rate limit account, validated by the relationship program
+    #[account(mut)]
+    pub recorder_rate: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub relationship_stats: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub relationship_event_authority: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+    /// CHECK: validated by the relationship program
+    pub rate_limiter_program: UncheckedAccount<'info>,
+
+    pub relationship_program: Program<'info, Relationship>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnItems<'info> {
+    #[account(
+        seeds = [b"crafting_authority"],
+        bump,
+        seeds::program = CRAFTING_PROGRAM_ID
+    )]
+    pub crafting_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"inventory", inventory.agent.as_ref()],
+        bump = inventory.bump,
+    )]
+    pub inventory: Account<'info, Inventory>,
+}
+
+#[derive(Accounts)]
+pub struct GrantItems<'info> {
+    #[account(
+        seeds = [b"crafting_authority"],
+        bump,
+        seeds::program = CRAFTING_PROGRAM_ID
+    )]
+    pub crafting_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"item_type", item_type.item_id.to_le_bytes().as_ref()],
+        bump = item_type.bump,
+    )]
+    pub item_type: Account<'info, ItemType>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Inventory>()
+            + MAX_INVENTORY_SLOTS * std::mem::size_of::<ItemBalance>(),
+        seeds = [b"inventory", agent_data.key().as_ref()],
+        bump
+    )]
+    pub inventory: Account<'info, Inventory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AwardItems<'info> {
+    #[account(
+        seeds = [b"raffle_authority"],
+        bump,
+        seeds::program = RAFFLE_PROGRAM_ID
+    )]
+    pub raffle_authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"item_type", item_type.item_id.to_le_bytes().as_ref()],
+        bump = item_type.bump,
+    )]
+    pub item_type: Account<'info, ItemType>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Inventory>()
+            + MAX_INVENTORY_SLOTS * std::mem::size_of::<ItemBalance>(),
+        seeds = [b"inventory", agent_data.key().as_ref()],
+        bump
+    )]
+    pub inventory: Account<'info, Inventory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ItemCategory {
+    Flowers,
+    Jewelry,
+    ExperienceVoucher,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ItemBalance {
+    pub item_id: u16,
+    pub amount: u32,
+}
+
+// Events
+#[event]
+pub struct ItemTypeCreated {
+    pub item_id: u16,
+    pub category: ItemCategory,
+    pub name: String,
+    pub fungible: bool,
+    pub price: u64,
+    pub max_supply: u32,
+}
+
+#[event]
+pub struct ItemTypeUpdated {
+    pub item_id: u16,
+    pub price: u64,
+    pub active: bool,
+}
+
+#[event]
+pub struct ItemPurchased {
+    pub agent: Pubkey,
+    pub item_id: u16,
+    pub quantity: u32,
+    pub serial: Option<u32>,  // Set for non-fungible items
+    pub cost: u64,
+}
+
+#[event]
+pub struct ItemConsumed {
+    pub giver: Pubkey,
+    pub recipient: Pubkey,
+    pub relationship: Pubkey,
+    pub item_id: u16,
+    pub serial: Option<u32>,
+}
+
+#[event]
+pub struct ItemsBurned {
+    pub agent: Pubkey,
+    pub items: Vec<ItemBalance>,
+}
+
+#[event]
+pub struct ItemsGranted {
+    pub agent: Pubkey,
+    pub item_id: u16,
+    pub quantity: u32,
+}
+
+// Custom errors
+#[error_code]
+pub enum ItemsError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Item name or URI is invalid")]
+    InvalidMetadata,
+    #[msg("Quantity must be greater than zero")]
+    InvalidQuantity,
+    #[msg("Item is not for sale")]
+    ItemInactive,
+    #[msg("Item supply is exhausted")]
+    SupplyExhausted,
+    #[msg("Item is non-fungible; use purchase_unique_item")]
+    NotFungible,
+    #[msg("Item is fungible; use purchase_item")]
+    NotUnique,
+    #[msg("Inventory has no free slots")]
+    InventoryFull,
+    #[msg("Agent does not hold this item")]
+    InsufficientItems,
+    #[msg("Non-fungible items require their item instance account")]
+    MissingItemInstance,
+    #[msg("Agent is not part of this relationship")]
+    NotInRelationship,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// Check the item can be sold and count the units against its supply,
+// returning the total Influence price
+fn reserve_supply(item_type: &mut ItemType, quantity: u32) -> Result<u64> {
+    require!(item_type.active, ItemsError::ItemInactive);
+    count_supply(item_type, quantity)?;
+
+    item_type
+        .price
+        .checked_mul(quantity as u64)
+        .ok_or(error!(ItemsError::ArithmeticOverflow))
+}
+
+fn count_supply(item_type: &mut ItemType, quantity: u32) -> Result<()> {
+    let minted = item_type
+        .minted
+        .checked_add(quantity)
+        .ok_or(ItemsError::ArithmeticOverflow)?;
+    require!(
+        item_type.max_supply == 0 || minted <= item_type.max_supply,
+        ItemsError::SupplyExhausted
+    );
+    item_type.minted = minted;
+
+    Ok(())
+}
+
+// Grant fungible items outside of a purchase, counting them against supply
+fn deliver_items(item_type: &mut ItemType, inventory: &mut Inventory, quantity: u32) -> Result<()> {
+    require!(quantity > 0, ItemsError::InvalidQuantity);
+    require!(item_type.fungible, ItemsError::NotFungible);
+    count_supply(item_type, quantity)?;
+    add_to_inventory(inventory, item_type.item_id, quantity)?;
+
+    emit!(ItemsGranted {
+        agent: inventory.agent,
+        item_id: item_type.item_id,
+        quantity,
+    });
+
+    Ok(())
+}
+
+fn add_to_inventory(inventory: &mut Inventory, item_id: u16, quantity: u32) -> Result<()> {
+    match inventory.items.iter_mut().find(|balance| balance.item_id == item_id) {
+        Some(balance) => {
+            balance.amount = balance
+                .amount
+                .checked_add(quantity)
+                .ok_or(ItemsError::ArithmeticOverflow)?;
+        }
+        None => {
+            require!(
+                inventory.items.len() < MAX_INVENTORY_SLOTS,
+                ItemsError::InventoryFull
+            );
+            inventory.items.push(ItemBalance {
+                item_id,
+                amount: quantity,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Empty balances are dropped to free the slot
+fn remove_from_inventory(inventory: &mut Inventory, item_id: u16, quantity: u32) -> Result<()> {
+    let position = inventory
+        .items
+        .iter()
+        .position(|balance| balance.item_id == item_id && balance.amount >= quantity)
+        .ok_or(ItemsError::InsufficientItems)?;
+    inventory.items[position].amount -= quantity;
+    if inventory.items[position].amount == 0 {
+        inventory.items.swap_remove(position);
+    }
+
+    Ok(())
+}
+
+fn burn_price<'info>(
+    amount: u64,
+    influence_mint: &Account<'info, Mint>,
+    owner_token_account: &Account<'info, TokenAccount>,
+    owner: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    token::burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: influence_mint.to_account_info(),
+                from: owner_token_account.to_account_info(),
+                authority: owner.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
@@ -0,0 +1,701 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use agent_registry_cpi::AgentData;
+use governance::VoterRecord;
+use relationship_cpi::RelationshipData;
+use reputation::ReputationProfile;
+
+declare_id!("LeaderB0ardPr0graMxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const TOP_N: usize = 100;
+const MAX_PRIZE_PLACES: usize = 10;
+const INFLUENCE_UNIT: u64 = 1_000_000;  // One whole token at the mint's 6 decimals
+
+#[program]
+pub mod leaderboard {
+    use super::*;
+
+    // Create the leaderboard config with the composite score weights
+    pub fn initialize_leaderboard(
+        ctx: Context<InitializeLeaderboard>,
+        weights: ScoreWeights,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.weights = weights;
+        config.season_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Replace the score weights. Scores pick them up on their next refresh,
+    // so change them between seasons.
+    pub fn set_score_weights(
+        ctx: Context<UpdateLeaderboard>,
+        weights: ScoreWeights,
+    ) -> Result<()> {
+        ctx.accounts.config.weights = weights.clone();
+
+        emit!(ScoreWeightsUpdated { weights });
+
+        Ok(())
+    }
+
+    // Hand the config to another key, such as the governance authority
+    pub fn set_leaderboard_authority(
+        ctx: Context<UpdateLeaderboard>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+        config.authority = authority;
+
+        emit!(LeaderboardAuthorityUpdated {
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Open a season with its top-N board and Influence prize vault. Prize
+    // shares for the top places are given in basis points of the pool.
+    pub fn create_season(
+        ctx: Context<CreateSeason>,
+        starts_at: i64,
+        ends_at: i64,
+        prize_bps: [u16; MAX_PRIZE_PLACES],
+    ) -> Result<()> {
+        require!(
+            ends_at > starts_at && ends_at > Clock::get()?.unix_timestamp,
+            LeaderboardError::InvalidSchedule
+        );
+        require!(
+            prize_bps.iter().map(|bps| *bps as u32).sum::<u32>() == BPS_DENOMINATOR as u32
+                && prize_bps.windows(2).all(|pair| pair[0] >= pair[1]),
+            LeaderboardError::InvalidPrizeShares
+        );
+
+        let config = &mut ctx.accounts.config;
+        let season = &mut ctx.accounts.season;
+        season.season_id = config.season_count;
+        season.starts_at = starts_at;
+        season.ends_at = ends_at;
+        season.board = ctx.accounts.board.key();
+        season.prize_vault = ctx.accounts.prize_vault.key();
+        season.prize_bps = prize_bps;
+        season.prize_pool = 0;
+        season.finalized = false;
+        season.bump = *ctx.bumps.get("season").unwrap();
+        config.season_count = config.season_count.saturating_add(1);
+
+        let mut board = ctx.accounts.board.load_init()?;
+        board.season = season.key();
+
+        emit!(SeasonCreated {
+            season: season.key(),
+            season_id: season.season_id,
+            starts_at,
+            ends_at,
+        });
+
+        Ok(())
+    }
+
+    // Add to a season's prize pool (anyone)
+    pub fn fund_season(ctx: Context<FundSeason>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.season.finalized, LeaderboardError::SeasonFinalized);
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.funder_token_account.to_account_info(),
+                    to: ctx.accounts.prize_vault.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    // Enter an agent in a season (owner). Match, milestone and earnings
+    // components count from here; `relationship`, when given, is the
+    // relationship whose milestones and health the agent is scored on.
+    pub fn join_season(ctx: Context<JoinSeason>) -> Result<()> {
+        let season = &ctx.accounts.season;
+        require!(
+            Clock::get()?.unix_timestamp < season.ends_at,
+            LeaderboardError::SeasonNotActive
+        );
+        let agent = ctx.accounts.agent_data.key();
+
+        let entry = &mut ctx.accounts.entry;
+        entry.season = season.key();
+        entry.agent = agent;
+        entry.owner_token_account = ctx.accounts.owner_token_account.key();
+        entry.baseline_matches = ctx.accounts.agent_data.match_count;
+        entry.baseline_balance = ctx.accounts.owner_token_account.amount;
+        entry.relationship = Pubkey::default();
+        entry.baseline_milestones = 0;
+        if let Some(relationship) = &ctx.accounts.relationship {
+            let relationship_data = relationship.load()?;
+            require!(
+                relationship_data.agent_one == agent || relationship_data.agent_two == agent,
+                LeaderboardError::RelationshipMismatch
+            );
+            entry.relationship = relationship.key();
+            entry.baseline_milestones = relationship_data.milestone_count;
+        }
+        entry.score = 0;
+        entry.claimed = false;
+        entry.bump = *ctx.bumps.get("entry").unwrap();
+
+        emit!(SeasonJoined {
+            season: entry.season,
+            agent,
+            relationship: entry.relationship,
+        });
+
+        Ok(())
+    }
+
+    // Recompute an entry's composite score and place it on the board
+    // (permissionless, during the season). The reputation profile and voter
+    // record are pinned to their PDAs and count as zero when they don't exist.
+    pub fn refresh_score(ctx: Context<RefreshScore>) -> Result<()> {
+        let season = &ctx.accounts.season;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= season.starts_at && now < season.ends_at,
+            LeaderboardError::SeasonNotActive
+        );
+
+        let entry = &mut ctx.accounts.entry;
+        let (milestones, health) = match &ctx.accounts.relationship {
+            Some(relationship) => {
+                require_keys_eq!(relationship.key(), entry.relationship, LeaderboardError::RelationshipMismatch);
+                let relationship_data = relationship.load()?;
+                (
+                    relationship_data.milestone_count.saturating_sub(entry.baseline_milestones),
+                    relationship_data.health,
+                )
+            }
+            None => {
+                require_keys_eq!(entry.relationship, Pubkey::default(), LeaderboardError::RelationshipMismatch);
+                (0, 0)
+            }
+        };
+        let inputs = ScoreInputs {
+            matches: ctx.accounts.agent_data.match_count.saturating_sub(entry.baseline_matches),
+            reputation: read_account::<ReputationProfile>(&ctx.accounts.reputation_profile, &reputation::ID)?
+                .map_or(0, |profile| profile.score),
+            milestones,
+            health,
+            earned: ctx.accounts.owner_token_account.amount.saturating_sub(entry.baseline_balance),
+            staked: read_account::<VoterRecord>(&ctx.accounts.voter_record, &governance::ID)?
+                .map_or(0, |record| record.deposited),
+        };
+        let score = ctx.accounts.config.weights.score(&inputs);
+        entry.score = score;
+        ctx.accounts.board.load_mut()?.record(entry.agent, score);
+
+        emit!(ScoreRefreshed {
+            season: season.key(),
+            agent: entry.agent,
+            score,
+        });
+
+        Ok(())
+    }
+
+    // Rank the board and fix the prize pool once the season is over (permissionless)
+    pub fn finalize_season(ctx: Context<FinalizeSeason>) -> Result<()> {
+        let season = &mut ctx.accounts.season;
+        require!(!season.finalized, LeaderboardError::SeasonFinalized);
+        require!(
+            Clock::get()?.unix_timestamp >= season.ends_at,
+            LeaderboardError::SeasonNotOver
+        );
+
+        let mut board = ctx.accounts.board.load_mut()?;
+        let len = board.len as usize;
+        board.entries[..len].sort_unstable_by(|a, b| b.score.cmp(&a.score));
+        season.prize_pool = ctx.accounts.prize_vault.amount;
+        season.finalized = true;
+
+        emit!(SeasonFinalized {
+            season: season.key(),
+            ranked: board.len,
+            prize_pool: season.prize_pool,
+        });
+
+        Ok(())
+    }
+
+    // Pay a ranked agent's prize to its current owner. Places the board
+    // didn't fill are dropped and the remaining shares scaled up, so the
+    // whole pool is paid out.
+    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+        let season = &ctx.accounts.season;
+        require!(season.finalized, LeaderboardError::SeasonNotFinalized);
+        let entry = &mut ctx.accounts.entry;
+        require!(!entry.claimed, LeaderboardError::AlreadyClaimed);
+
+        let board = ctx.accounts.board.load()?;
+        let len = board.len as usize;
+        let place = board.entries[..len]
+            .iter()
+            .position(|ranked| ranked.agent == entry.agent)
+            .filter(|place| *place < MAX_PRIZE_PLACES)
+            .ok_or(LeaderboardError::NoPrize)?;
+        drop(board);
+        let filled_bps: u16 = season.prize_bps[..len.min(MAX_PRIZE_PLACES)].iter().sum();
+        let amount = prize_share(season.prize_pool, season.prize_bps[place], filled_bps)?;
+        require!(amount > 0, LeaderboardError::NoPrize);
+
+        let id_bytes = season.season_id.to_le_bytes();
+        let season_seeds: &[&[u8]] = &[b"season", &id_bytes, &[season.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.prize_vault.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: season.to_account_info(),
+                },
+                &[season_seeds],
+            ),
+            amount,
+        )?;
+        entry.claimed = true;
+
+        emit!(PrizeClaimed {
+            season: season.key(),
+            agent: entry.agent,
+            owner: ctx.accounts.owner.key(),
+            place: place as u8,
+            amount,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct LeaderboardConfig {
+    pub authority: Pubkey,       // Creates seasons and sets weights
+    pub influence_mint: Pubkey,  // Earnings, stakes and prizes are in this mint
+    pub weights: ScoreWeights,
+    pub season_count: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Season {
+    pub season_id: u64,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub board: Pubkey,
+    pub prize_vault: Pubkey,
+    pub prize_bps: [u16; MAX_PRIZE_PLACES],  // Share of the pool by place
+    pub prize_pool: u64,                     // Vault balance at finalization
+    pub finalized: bool,
+    pub bump: u8,
+}
+
+// The season's top agents by score. Unordered until the season is
+// finalized, then sorted best first.
+#[account(zero_copy)]
+pub struct SeasonBoard {
+    pub season: Pubkey,
+    pub entries: [BoardEntry; TOP_N],
+    pub len: u32,
+    pub _padding: [u8; 4],
+}
+
+impl SeasonBoard {
+    // Update the agent's score if it is on the board, otherwise take the
+    // lowest place if the board is full and the score beats it
+    fn record(&mut self, agent: Pubkey, score: u64) {
+        let len = self.len as usize;
+        if let Some(ranked) = self.entries[..len].iter_mut().find(|ranked| ranked.agent == agent) {
+            ranked.score = score;
+        } else if len < TOP_N {
+            self.entries[len] = BoardEntry { agent, score };
+            self.len += 1;
+        } else if let Some(lowest) = self.entries.iter_mut().min_by_key(|ranked| ranked.score) {
+            if score > lowest.score {
+                *lowest = BoardEntry { agent, score };
+            }
+        }
+    }
+}
+
+// An agent's enrollment in a season, with the counters its season
+// components are measured from
+#[account]
+pub struct SeasonEntry {
+    pub season: Pubkey,
+    pub agent: Pubkey,
+    pub relationship: Pubkey,         // Scored relationship, Pubkey::default() for none
+    pub owner_token_account: Pubkey,  // Earnings are measured on this account
+    pub baseline_matches: u32,
+    pub baseline_milestones: u32,
+    pub baseline_balance: u64,
+    pub score: u64,                   // As of the last refresh
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeLeaderboard<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<LeaderboardConfig>(),
+        seeds = [b"leaderboard_config"],
+        bump
+    )]
+    pub config: Account<'info, LeaderboardConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLeaderboard<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"leaderboard_config"],
+        bump = config.bump,
+        has_one = authority @ LeaderboardError::Unauthorized,
+    )]
+    pub config: Account<'info, LeaderboardConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSeason<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"leaderboard_config"],
+        bump = config.bump,
+        has_one = authority @ LeaderboardError::Unauthorized,
+        has_one = influence_mint,
+    )]
+    pub config: Account<'info, LeaderboardConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<Season>(),
+        seeds = [b"season", config.season_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<SeasonBoard>(),
+        seeds = [b"season_board", season.key().as_ref()],
+        bump
+    )]
+    pub board: AccountLoader<'info, SeasonBoard>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"prize_vault", season.key().as_ref()],
+        bump,
+        token::mint = influence_mint,
+        token::authority = season,
+    )]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct FundSeason<'info> {
+    pub funder: Signer<'info>,
+
+    pub season: Account<'info, Season>,
+
+    #[account(mut)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = season.prize_vault)]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct JoinSeason<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"leaderboard_config"], bump = config.bump)]
+    pub config: Account<'info, LeaderboardConfig>,
+
+    pub season: Account<'info, Season>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ LeaderboardError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<SeasonEntry>(),
+        seeds = [b"season_entry", season.key().as_ref(), agent_data.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, SeasonEntry>,
+
+    #[account(
+        constraint = owner_token_account.owner == owner.key() @ LeaderboardError::Unauthorized,
+        constraint = owner_token_account.mint == config.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub relationship: Option<AccountLoader<'info, RelationshipData>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshScore<'info> {
+    #[account(seeds = [b"leaderboard_config"], bump = config.bump)]
+    pub config: Account<'info, LeaderboardConfig>,
+
+    pub season: Account<'info, Season>,
+
+    #[account(mut, address = season.board)]
+    pub board: AccountLoader<'info, SeasonBoard>,
+
+    #[account(
+        mut,
+        seeds = [b"season_entry", season.key().as_ref(), agent_data.key().as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, SeasonEntry>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(address = entry.owner_token_account)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: the agent's ReputationProfile PDA, read if it exists
+    #[account(seeds = [b"reputation", agent_data.key().as_ref()], bump, seeds::program = reputation::ID)]
+    pub reputation_profile: UncheckedAccount<'info>,
+
+    /// CHECK: the owner's governance VoterRecord PDA, read if it exists
+    #[account(seeds = [b"voter", agent_data.owner.as_ref()], bump, seeds::program = governance::ID)]
+    pub voter_record: UncheckedAccount<'info>,
+
+    // Required when the entry has a scored relationship
+    pub relationship: Option<AccountLoader<'info, RelationshipData>>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSeason<'info> {
+    #[account(mut)]
+    pub season: Account<'info, Season>,
+
+    #[account(mut, address = season.board)]
+    pub board: AccountLoader<'info, SeasonBoard>,
+
+    #[account(address = season.prize_vault)]
+    pub prize_vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    pub owner: Signer<'info>,
+
+    pub season: Account<'info, Season>,
+
+    #[account(address = season.board)]
+    pub board: AccountLoader<'info, SeasonBoard>,
+
+    #[account(
+        mut,
+        seeds = [b"season_entry", season.key().as_ref(), agent_data.key().as_ref()],
+        bump = entry.bump,
+    )]
+    pub entry: Account<'info, SeasonEntry>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ LeaderboardError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(mut, constraint = owner_token_account.owner == owner.key() @ LeaderboardError::Unauthorized)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = season.prize_vault)]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+#[zero_copy]
+pub struct BoardEntry {
+    pub agent: Pubkey,
+    pub score: u64,
+}
+
+// Points per unit of each component
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct ScoreWeights {
+    pub per_match: u32,             // Registry matches this season
+    pub per_reputation_point: u32,  // Reputation score, 0-100
+    pub per_milestone: u32,         // Scored relationship's milestones this season
+    pub per_health_point: u32,      // Scored relationship's health, 0-100
+    pub per_influence_earned: u32,  // Whole tokens gained this season
+    pub per_influence_staked: u32,  // Whole tokens staked in governance
+}
+
+impl ScoreWeights {
+    pub fn score(&self, inputs: &ScoreInputs) -> u64 {
+        let components = [
+            (inputs.matches as u64, self.per_match),
+            ((inputs.reputation / 100) as u64, self.per_reputation_point),
+            (inputs.milestones as u64, self.per_milestone),
+            (inputs.health as u64, self.per_health_point),
+            (inputs.earned / INFLUENCE_UNIT, self.per_influence_earned),
+            (inputs.staked / INFLUENCE_UNIT, self.per_influence_staked),
+        ];
+        components
+            .iter()
+            .fold(0u64, |score, (units, weight)| score.saturating_add(units.saturating_mul(*weight as u64)))
+    }
+}
+
+// Raw component values for one agent
+#[derive(Clone, Default, Debug)]
+pub struct ScoreInputs {
+    pub matches: u32,
+    pub reputation: u16,  // Basis points
+    pub milestones: u32,
+    pub health: u8,
+    pub earned: u64,
+    pub staked: u64,
+}
+
+// Events
+#[event]
+pub struct SeasonCreated {
+    pub season: Pubkey,
+    pub season_id: u64,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+#[event]
+pub struct SeasonJoined {
+    pub season: Pubkey,
+    pub agent: Pubkey,
+    pub relationship: Pubkey,
+}
+
+#[event]
+pub struct ScoreRefreshed {
+    pub season: Pubkey,
+    pub agent: Pubkey,
+    pub score: u64,
+}
+
+#[event]
+pub struct SeasonFinalized {
+    pub season: Pubkey,
+    pub ranked: u32,
+    pub prize_pool: u64,
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub season: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub place: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ScoreWeightsUpdated {
+    pub weights: ScoreWeights,
+}
+
+#[event]
+pub struct LeaderboardAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum LeaderboardError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Season must end after it starts, in the future")]
+    InvalidSchedule,
+    #[msg("Prize shares must sum to 10000 bps and not increase by place")]
+    InvalidPrizeShares,
+    #[msg("The season is not running")]
+    SeasonNotActive,
+    #[msg("The season has not ended yet")]
+    SeasonNotOver,
+    #[msg("The season has already been finalized")]
+    SeasonFinalized,
+    #[msg("The season has not been finalized")]
+    SeasonNotFinalized,
+    #[msg("The relationship does not match the agent's season entry")]
+    RelationshipMismatch,
+    #[msg("The agent did not place in the prizes")]
+    NoPrize,
+    #[msg("Prize has already been claimed")]
+    AlreadyClaimed,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// Deserialize an account owned by `program`, or None if it doesn't exist yet
+fn read_account<T: AccountDeserialize>(account: &AccountInfo, program: &Pubkey) -> Result<Option<T>> {
+    if account.owner != program || account.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(T::try_deserialize(&mut &account.try_borrow_data()?[..])?))
+}
+
+// `share_bps` of the pool, scaled up when unfilled places leave the paid
+// shares summing to `filled_bps` < 10000
+pub fn prize_share(prize_pool: u64, share_bps: u16, filled_bps: u16) -> Result<u64> {
+    if filled_bps == 0 {
+        return Ok(0);
+    }
+    let share = (prize_pool as u128)
+        .checked_mul(share_bps as u128)
+        .ok_or(LeaderboardError::ArithmeticOverflow)?
+        / filled_bps as u128;
+    Ok(share as u64)
+}
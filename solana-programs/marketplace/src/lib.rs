@@ -0,0 +1,1196 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use agent_registry_cpi::program::AgentRegistry;
+use agent_registry_cpi::{AgentData, Bubblegum};
+use escrow_cpi::program::Escrow as EscrowProgram;
+use escrow_cpi::{Escrow, ReleaseCondition, ESCROW_AUTHORITY_SEED};
+use referrals_cpi::{program::Referrals, Reporter, ValueEvent, REFERRAL_REPORTER_SEED};
+use spl_account_compression::{program::SplAccountCompression, Noop};
+
+pub use escrow_cpi::CnftArgs;
+
+declare_id!("MarketP1acePr0graMxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_FEE_BPS: u16 = 1_000;  // 10%
+const MAX_PRICE_AGE: i64 = 60;  // Seconds a SOL/USD price stays usable
+const MAX_PRICE_CONF_BPS: u16 = 200;  // Widest confidence interval accepted, 2%
+
+#[program]
+pub mod marketplace {
+    use super::*;
+
+    // Create the marketplace config with its fee and treasury accounts
+    pub fn initialize_marketplace(
+        ctx: Context<InitializeMarketplace>,
+        fee_bps: u16,
+        treasury: Pubkey,
+        treasury_token_account: Pubkey,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, MarketplaceError::InvalidFee);
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.treasury = treasury;
+        config.treasury_token_account = treasury_token_account;
+        config.fee_bps = fee_bps;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit!(MarketplaceConfigUpdated {
+            fee_bps,
+            treasury,
+            treasury_token_account,
+        });
+
+        Ok(())
+    }
+
+    // Update the marketplace fee and treasury accounts (admin)
+    pub fn update_marketplace_config(
+        ctx: Context<UpdateMarketplaceConfig>,
+        fee_bps: u16,
+        treasury: Pubkey,
+        treasury_token_account: Pubkey,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, MarketplaceError::InvalidFee);
+
+        let config = &mut ctx.accounts.config;
+        config.fee_bps = fee_bps;
+        config.treasury = treasury;
+        config.treasury_token_account = treasury_token_account;
+
+        emit!(MarketplaceConfigUpdated {
+            fee_bps,
+            treasury,
+            treasury_token_account,
+        });
+
+        Ok(())
+    }
+
+    // List an agent for sale. The agent's cNFT is deposited with the escrow
+    // program, which only this program can settle, and its registry
+    // ownership is held by the listing PDA until the agent is sold or
+    // delisted. Bubblegum proof accounts are passed as remaining accounts.
+    pub fn list_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, ListAgent<'info>>,
+        price: u64,
+        currency: Currency,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        require!(price > 0, MarketplaceError::InvalidPrice);
+        require!(!ctx.accounts.agent_data.is_frozen, MarketplaceError::AgentFrozen);
+
+        let listing_key = ctx.accounts.listing.key();
+        let agent_key = ctx.accounts.agent_data.key();
+        let listing_bump = *ctx.bumps.get("listing").unwrap();
+        let listing = &mut ctx.accounts.listing;
+        listing.seller = ctx.accounts.seller.key();
+        listing.agent = agent_key;
+        listing.merkle_tree = ctx.accounts.cnft_accounts.merkle_tree.key();
+        listing.price = price;
+        listing.currency = currency.clone();
+        listing.created_at = Clock::get()?.unix_timestamp;
+        listing.bump = listing_bump;
+        let listing_seeds: &[&[u8]] = &[b"listing", agent_key.as_ref(), &[listing_bump]];
+
+        // Move the cNFT and registry ownership into escrow. The buyer is not
+        // known yet, so the listing stands in as beneficiary until the sale.
+        let cnft_accounts = &ctx.accounts.cnft_accounts;
+        escrow_cpi::cpi::deposit_cnft(
+            CpiContext::new(
+                ctx.accounts.escrow_program.to_account_info(),
+                escrow_cpi::cpi::accounts::DepositCnft {
+                    depositor: ctx.accounts.seller.to_account_info(),
+                    escrow: ctx.accounts.escrow.to_account_info(),
+                    cnft_accounts: cnft_accounts.to_escrow_accounts(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            agent_key,
+            listing_key,
+            ReleaseCondition::CpiCaller { program: crate::ID },
+            ReleaseCondition::CpiCaller { program: crate::ID },
+            cnft,
+        )?;
+        agent_registry_cpi::cpi::transfer_ownership(CpiContext::new_with_signer(
+            ctx.accounts.registry_program.to_account_info(),
+            agent_registry_cpi::cpi::accounts::TransferOwnership {
+                owner: ctx.accounts.seller.to_account_info(),
+                new_owner: listing.to_account_info(),
+                agent_data: ctx.accounts.agent_data.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                guardian: ctx.accounts.guardian.to_account_info(),
+            },
+            &[listing_seeds],
+        ))?;
+
+        emit!(AgentListed {
+            listing_id: listing_key,
+            agent: listing.agent,
+            seller: listing.seller,
+            price,
+            currency,
+        });
+
+        Ok(())
+    }
+
+    // Change the asking price of an active listing (seller)
+    pub fn update_listing_price(ctx: Context<UpdateListing>, price: u64) -> Result<()> {
+        require!(price > 0, MarketplaceError::InvalidPrice);
+
+        let listing = &mut ctx.accounts.listing;
+        let previous_price = listing.price;
+        listing.price = price;
+
+        emit!(ListingPriceUpdated {
+            listing_id: listing.key(),
+            previous_price,
+            price,
+        });
+
+        Ok(())
+    }
+
+    // Buy a listed agent. The price is paid to the seller minus the marketplace
+    // fee, which goes to the treasury; the agent is released to the buyer.
+    // `max_price` guards against the seller raising the price in flight; for
+    // USD-priced listings it caps the lamports paid at the current SOL/USD price.
+    pub fn buy_agent<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyAgent<'info>>,
+        max_price: u64,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let listing = &ctx.accounts.listing;
+        let buyer = ctx.accounts.buyer.key();
+        require!(buyer != listing.seller, MarketplaceError::CannotBuyOwnListing);
+        #[cfg(feature = "gated")]
+        attestation::require_credential(ctx.accounts.credential.as_deref(), &buyer)?;
+
+        let price = match listing.currency {
+            Currency::UsdInSol => {
+                let sol_usd_feed = ctx
+                    .accounts
+                    .sol_usd_feed
+                    .as_ref()
+                    .ok_or(MarketplaceError::MissingPriceFeed)?;
+                armour_price_feed::load_price(sol_usd_feed, MAX_PRICE_AGE, MAX_PRICE_CONF_BPS)?
+                    .usd_cents_to_lamports(listing.price)?
+            }
+            _ => listing.price,
+        };
+        require!(price > 0 && price <= max_price, MarketplaceError::PriceChanged);
+
+        let fee = marketplace_fee(price, config.fee_bps)?;
+        let seller_proceeds = price - fee;
+        match listing.currency {
+            Currency::Sol | Currency::UsdInSol => {
+                for (to, amount) in [
+                    (ctx.accounts.seller.to_account_info(), seller_proceeds),
+                    (ctx.accounts.treasury.to_account_info(), fee),
+                ] {
+                    if amount > 0 {
+                        system_program::transfer(
+                            CpiContext::new(
+                                ctx.accounts.system_program.to_account_info(),
+                                system_program::Transfer {
+                                    from: ctx.accounts.buyer.to_account_info(),
+                                    to,
+                                },
+                            ),
+                            amount,
+                        )?;
+                    }
+                }
+            }
+            Currency::Influence => {
+                let (Some(buyer_token_account), Some(seller_token_account), Some(treasury_token_account), Some(token_program)) = (
+                    &ctx.accounts.buyer_token_account,
+                    &ctx.accounts.seller_token_account,
+                    &ctx.accounts.treasury_token_account,
+                    &ctx.accounts.token_program,
+                ) else {
+                    return err!(MarketplaceError::MissingTokenAccounts);
+                };
+                require!(
+                    seller_token_account.owner == listing.seller
+                        && seller_token_account.mint == config.influence_mint
+                        && treasury_token_account.key() == config.treasury_token_account,
+                    MarketplaceError::MissingTokenAccounts
+                );
+                for (to, amount) in [
+                    (seller_token_account, seller_proceeds),
+                    (treasury_token_account, fee),
+                ] {
+                    if amount > 0 {
+                        token::transfer(
+                            CpiContext::new(
+                                token_program.to_account_info(),
+                                Transfer {
+                                    from: buyer_token_account.to_account_info(),
+                                    to: to.to_account_info(),
+                                    authority: ctx.accounts.buyer.to_account_info(),
+                                },
+                            ),
+                            amount,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        release_agent(
+            listing,
+            &ctx.accounts.agent_data,
+            ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.listing_escrow,
+            ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.cnft_accounts,
+            &ctx.accounts.registry_program,
+            &ctx.accounts.guardian,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            cnft,
+        )?;
+        if let (Some(reporter), Some(config), Some(referral), Some(referrer_rewards), Some(referrals_program)) = (
+            &ctx.accounts.referral_reporter,
+            &ctx.accounts.referrals_config,
+            &ctx.accounts.referral,
+            &ctx.accounts.referrer_rewards,
+            &ctx.accounts.referrals_program,
+        ) {
+            referrals_cpi::report_value_event(
+                &crate::ID,
+                &Reporter {
+                    reporter,
+                    config,
+                    referrals_program,
+                },
+                &buyer,
+                referral,
+                referrer_rewards,
+                ValueEvent::FirstPurchase,
+            )?;
+        }
+
+        emit!(AgentSold {
+            listing_id: listing.key(),
+            agent: listing.agent,
+            seller: listing.seller,
+            buyer,
+            price,
+            currency: listing.currency.clone(),
+            fee,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw a listing and return the agent to the seller
+    pub fn cancel_listing<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelListing<'info>>,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+
+        release_agent(
+            listing,
+            &ctx.accounts.agent_data,
+            ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.listing_escrow,
+            ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.cnft_accounts,
+            &ctx.accounts.registry_program,
+            &ctx.accounts.guardian,
+            &ctx.accounts.system_program,
+            ctx.remaining_accounts,
+            cnft,
+        )?;
+
+        emit!(ListingCancelled {
+            listing_id: listing.key(),
+            agent: listing.agent,
+            seller: listing.seller,
+        });
+
+        Ok(())
+    }
+
+    // Offer to buy any registered agent, listed or not. The offer amount is
+    // escrowed (in the offer PDA for SOL, in its vault for Influence) until
+    // the owner accepts or the buyer withdraws after expiry.
+    pub fn make_offer(
+        ctx: Context<MakeOffer>,
+        amount: u64,
+        currency: Currency,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(amount > 0, MarketplaceError::InvalidPrice);
+        require!(currency != Currency::UsdInSol, MarketplaceError::UnsupportedCurrency);
+        require!(
+            expires_at > Clock::get()?.unix_timestamp,
+            MarketplaceError::InvalidExpiry
+        );
+        let buyer = ctx.accounts.buyer.key();
+        require!(buyer != ctx.accounts.agent_data.owner, MarketplaceError::CannotBuyOwnListing);
+        #[cfg(feature = "gated")]
+        attestation::require_credential(ctx.accounts.credential.as_deref(), &buyer)?;
+
+        match currency {
+            Currency::Sol | Currency::UsdInSol => {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to: ctx.accounts.offer.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+            Currency::Influence => {
+                let (Some(buyer_token_account), Some(offer_vault), Some(token_program)) = (
+                    &ctx.accounts.buyer_token_account,
+                    &ctx.accounts.offer_vault,
+                    &ctx.accounts.token_program,
+                ) else {
+                    return err!(MarketplaceError::MissingTokenAccounts);
+                };
+                token::transfer(
+                    CpiContext::new(
+                        token_program.to_account_info(),
+                        Transfer {
+                            from: buyer_token_account.to_account_info(),
+                            to: offer_vault.to_account_info(),
+                            authority: ctx.accounts.buyer.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        let offer = &mut ctx.accounts.offer;
+        offer.buyer = buyer;
+        offer.agent = ctx.accounts.agent_data.key();
+        offer.amount = amount;
+        offer.currency = currency.clone();
+        offer.expires_at = expires_at;
+        offer.bump = *ctx.bumps.get("offer").unwrap();
+
+        emit!(OfferMade {
+            offer_id: offer.key(),
+            agent: offer.agent,
+            buyer,
+            amount,
+            currency,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    // Accept an offer (agent owner). The escrowed amount is paid to the owner
+    // minus the marketplace fee and the agent is transferred to the buyer.
+    pub fn accept_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptOffer<'info>>,
+        cnft: CnftArgs,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let offer = &ctx.accounts.offer;
+        require!(
+            Clock::get()?.unix_timestamp <= offer.expires_at,
+            MarketplaceError::OfferExpired
+        );
+
+        let fee = marketplace_fee(offer.amount, config.fee_bps)?;
+        let owner_proceeds = offer.amount - fee;
+        let offer_seeds: &[&[u8]] = &[b"offer", offer.agent.as_ref(), offer.buyer.as_ref(), &[offer.bump]];
+        match offer.currency {
+            Currency::Sol | Currency::UsdInSol => {
+                pay_from_offer(offer, &ctx.accounts.owner.to_account_info(), owner_proceeds)?;
+                pay_from_offer(offer, &ctx.accounts.treasury.to_account_info(), fee)?;
+            }
+            Currency::Influence => {
+                let (Some(offer_vault), Some(owner_token_account), Some(treasury_token_account), Some(token_program)) = (
+                    &ctx.accounts.offer_vault,
+                    &ctx.accounts.owner_token_account,
+                    &ctx.accounts.treasury_token_account,
+                    &ctx.accounts.token_program,
+                ) else {
+                    return err!(MarketplaceError::MissingTokenAccounts);
+                };
+                require!(
+                    owner_token_account.mint == config.influence_mint
+                        && treasury_token_account.key() == config.treasury_token_account,
+                    MarketplaceError::MissingTokenAccounts
+                );
+                for (to, amount) in [
+                    (owner_token_account, owner_proceeds),
+                    (treasury_token_account, fee),
+                ] {
+                    if amount > 0 {
+                        token::transfer(
+                            CpiContext::new_with_signer(
+                                token_program.to_account_info(),
+                                Transfer {
+                                    from: offer_vault.to_account_info(),
+                                    to: to.to_account_info(),
+                                    authority: offer.to_account_info(),
+                                },
+                                &[offer_seeds],
+                            ),
+                            amount,
+                        )?;
+                    }
+                }
+                close_offer_vault(offer, offer_vault, &ctx.accounts.buyer, token_program)?;
+            }
+        }
+
+        // Transfer the cNFT and registry ownership to the buyer
+        ctx.accounts.cnft_accounts.transfer(
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+            &cnft,
+            &[],
+        )?;
+        // The buyer doesn't sign, so the agent passes through the offer
+        // account, which signs to receive it and then releases it
+        for (owner, new_owner) in [
+            (ctx.accounts.owner.to_account_info(), offer.to_account_info()),
+            (offer.to_account_info(), ctx.accounts.buyer.to_account_info()),
+        ] {
+            agent_registry_cpi::cpi::transfer_ownership(CpiContext::new_with_signer(
+                ctx.accounts.registry_program.to_account_info(),
+                agent_registry_cpi::cpi::accounts::TransferOwnership {
+                    owner,
+                    new_owner,
+                    agent_data: ctx.accounts.agent_data.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    guardian: ctx.accounts.guardian.to_account_info(),
+                },
+                &[offer_seeds],
+            ))?;
+        }
+
+        emit!(OfferAccepted {
+            offer_id: offer.key(),
+            agent: offer.agent,
+            seller: ctx.accounts.owner.key(),
+            buyer: offer.buyer,
+            amount: offer.amount,
+            currency: offer.currency.clone(),
+            fee,
+        });
+
+        Ok(())
+    }
+
+    // Reclaim an expired offer's escrow (buyer)
+    pub fn withdraw_offer(ctx: Context<WithdrawOffer>) -> Result<()> {
+        let offer = &ctx.accounts.offer;
+        require!(
+            Clock::get()?.unix_timestamp > offer.expires_at,
+            MarketplaceError::OfferNotExpired
+        );
+
+        // SOL offers are refunded when the offer account is closed
+        if offer.currency == Currency::Influence {
+            let (Some(offer_vault), Some(buyer_token_account), Some(token_program)) = (
+                &ctx.accounts.offer_vault,
+                &ctx.accounts.buyer_token_account,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(MarketplaceError::MissingTokenAccounts);
+            };
+            let offer_seeds: &[&[u8]] = &[b"offer", offer.agent.as_ref(), offer.buyer.as_ref(), &[offer.bump]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: offer_vault.to_account_info(),
+                        to: buyer_token_account.to_account_info(),
+                        authority: offer.to_account_info(),
+                    },
+                    &[offer_seeds],
+                ),
+                offer_vault.amount,
+            )?;
+            close_offer_vault(offer, offer_vault, &ctx.accounts.buyer, token_program)?;
+        }
+
+        emit!(OfferWithdrawn {
+            offer_id: offer.key(),
+            agent: offer.agent,
+            buyer: offer.buyer,
+            amount: offer.amount,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct MarketplaceConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub treasury: Pubkey,                // Receives fees on SOL sales
+    pub treasury_token_account: Pubkey,  // Receives fees on Influence sales
+    pub fee_bps: u16,
+    pub bump: u8,
+}
+
+// An agent for sale; the listing PDA holds the agent's registry ownership and
+// the escrow program its cNFT until it is sold or delisted
+#[account]
+pub struct Listing {
+    pub seller: Pubkey,
+    pub agent: Pubkey,
+    pub merkle_tree: Pubkey,  // Tree holding the agent's cNFT
+    pub price: u64,
+    pub currency: Currency,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// A standing bid for an agent; the offer PDA escrows the amount
+#[account]
+pub struct Offer {
+    pub buyer: Pubkey,
+    pub agent: Pubkey,
+    pub amount: u64,
+    pub currency: Currency,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeMarketplace<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<MarketplaceConfig>(),
+        seeds = [b"marketplace_config"],
+        bump
+    )]
+    pub config: Account<'info, MarketplaceConfig>,
+
+    /// CHECK: only its address is recorded
+    pub influence_mint: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketplaceConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"marketplace_config"],
+        bump = config.bump,
+        has_one = admin @ MarketplaceError::Unauthorized,
+    )]
+    pub config: Account<'info, MarketplaceConfig>,
+}
+
+// Bubblegum accounts needed to move an agent's cNFT
+#[derive(Accounts)]
+pub struct CnftAccounts<'info> {
+    /// CHECK: Bubblegum tree config; validated by Bubblegum
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by Bubblegum and the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+}
+
+#[derive(Accounts)]
+pub struct ListAgent<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == seller.key() @ MarketplaceError::Unauthorized,
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + std::mem::size_of::<Listing>(),
+        seeds = [b"listing", agent_data.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// CHECK: the escrow holding the cNFT, created by the escrow program at
+    /// its PDA for the seller and agent
+    #[account(mut)]
+    pub escrow: UncheckedAccount<'info>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub escrow_program: Program<'info, EscrowProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+// The escrow holding a listed agent's cNFT and this program's authority over it
+#[derive(Accounts)]
+pub struct ListingEscrow<'info> {
+    #[account(mut)]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: PDA this program signs escrow settlements with
+    #[account(seeds = [ESCROW_AUTHORITY_SEED], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListing<'info> {
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", listing.agent.as_ref()],
+        bump = listing.bump,
+        has_one = seller @ MarketplaceError::Unauthorized,
+    )]
+    pub listing: Account<'info, Listing>,
+}
+
+#[derive(Accounts)]
+pub struct BuyAgent<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"marketplace_config"], bump = config.bump)]
+    pub config: Account<'info, MarketplaceConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", agent_data.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller,
+        constraint = listing.merkle_tree == cnft_accounts.merkle_tree.key() @ MarketplaceError::TreeMismatch,
+        close = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    /// CHECK: receives the proceeds and the listing's rent; validated by `has_one`
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    /// CHECK: receives fees on SOL sales
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    // Influence accounts, required only for Influence listings
+    #[account(mut, constraint = buyer_token_account.owner == buyer.key() @ MarketplaceError::Unauthorized)]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub seller_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// CHECK: Pyth SOL/USD price account, required only for USD-priced listings
+    #[account(address = armour_price_feed::SOL_USD_FEED)]
+    pub sol_usd_feed: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the buyer's attestation credential, required only in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+
+    // Referral hook accounts, required only to credit the buyer's referrer
+    // for the purchase; `referral` is the buyer's, which need not exist
+    /// CHECK: PDA that signs referral reports
+    #[account(seeds = [REFERRAL_REPORTER_SEED], bump)]
+    pub referral_reporter: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    pub referrals_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the buyer before reporting
+    #[account(mut)]
+    pub referral: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the referrals program
+    #[account(mut)]
+    pub referrer_rewards: Option<UncheckedAccount<'info>>,
+
+    pub referrals_program: Option<Program<'info, Referrals>>,
+
+    #[account(
+        constraint = listing_escrow.escrow.depositor == listing.seller
+            && listing_escrow.escrow.reference == listing.agent @ MarketplaceError::EscrowMismatch,
+    )]
+    pub listing_escrow: ListingEscrow<'info>,
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"listing", agent_data.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ MarketplaceError::Unauthorized,
+        constraint = listing.merkle_tree == cnft_accounts.merkle_tree.key() @ MarketplaceError::TreeMismatch,
+        close = seller,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(mut)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        constraint = listing_escrow.escrow.depositor == listing.seller
+            && listing_escrow.escrow.reference == listing.agent @ MarketplaceError::EscrowMismatch,
+    )]
+    pub listing_escrow: ListingEscrow<'info>,
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MakeOffer<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"marketplace_config"], bump = config.bump)]
+    pub config: Account<'info, MarketplaceConfig>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + std::mem::size_of::<Offer>(),
+        seeds = [b"offer", agent_data.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    // Influence accounts, required only for Influence offers
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ MarketplaceError::Unauthorized,
+    )]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"offer_vault", offer.key().as_ref()],
+        bump,
+        token::mint = influence_mint,
+        token::authority = offer,
+    )]
+    pub offer_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = config.influence_mint)]
+    pub influence_mint: Option<Account<'info, Mint>>,
+
+    /// CHECK: the buyer's attestation credential, required only in gated builds
+    pub credential: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"marketplace_config"], bump = config.bump)]
+    pub config: Account<'info, MarketplaceConfig>,
+
+    #[account(
+        mut,
+        constraint = agent_data.owner == owner.key() @ MarketplaceError::Unauthorized,
+    )]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", agent_data.key().as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+        has_one = buyer,
+        close = buyer,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// CHECK: receives the agent and the offer's rent; validated by `has_one`
+    #[account(mut)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: receives fees on SOL offers
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    // Influence accounts, required only for Influence offers
+    #[account(mut, seeds = [b"offer_vault", offer.key().as_ref()], bump)]
+    pub offer_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub cnft_accounts: CnftAccounts<'info>,
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawOffer<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", offer.agent.as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+        has_one = buyer @ MarketplaceError::Unauthorized,
+        close = buyer,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    // Influence accounts, required only for Influence offers
+    #[account(mut, seeds = [b"offer_vault", offer.key().as_ref()], bump)]
+    pub offer_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ MarketplaceError::Unauthorized,
+    )]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum Currency {
+    Sol,
+    Influence,
+    UsdInSol,  // Priced in USD cents, paid in SOL at the Pyth SOL/USD price; listings only
+}
+
+// Events
+#[event]
+pub struct MarketplaceConfigUpdated {
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub treasury_token_account: Pubkey,
+}
+
+#[event]
+pub struct AgentListed {
+    pub listing_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub currency: Currency,
+}
+
+#[event]
+pub struct ListingPriceUpdated {
+    pub listing_id: Pubkey,
+    pub previous_price: u64,
+    pub price: u64,
+}
+
+#[event]
+pub struct AgentSold {
+    pub listing_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,  // Amount paid; lamports for UsdInSol listings
+    pub currency: Currency,
+    pub fee: u64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub listing_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+}
+
+#[event]
+pub struct OfferMade {
+    pub offer_id: Pubkey,
+    pub agent: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub currency: Currency,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct OfferAccepted {
+    pub offer_id: Pubkey,
+    pub agent: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub currency: Currency,
+    pub fee: u64,
+}
+
+#[event]
+pub struct OfferWithdrawn {
+    pub offer_id: Pubkey,
+    pub agent: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum MarketplaceError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Fee exceeds the maximum")]
+    InvalidFee,
+    #[msg("Price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Agent has been frozen by the registry moderator")]
+    AgentFrozen,
+    #[msg("Sellers cannot buy their own listing")]
+    CannotBuyOwnListing,
+    #[msg("Listing price is above the buyer's maximum")]
+    PriceChanged,
+    #[msg("Influence token accounts are required for this listing")]
+    MissingTokenAccounts,
+    #[msg("Merkle tree does not hold this agent's cNFT")]
+    TreeMismatch,
+    #[msg("Offer expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Offer can be withdrawn only after it expires")]
+    OfferNotExpired,
+    #[msg("The SOL/USD price feed is required for this listing")]
+    MissingPriceFeed,
+    #[msg("Offers cannot be made in this currency")]
+    UnsupportedCurrency,
+    #[msg("The escrow does not hold this listing's agent")]
+    EscrowMismatch,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+impl<'info> CnftAccounts<'info> {
+    // Transfer the cNFT leaf, optionally signing as a PDA leaf owner
+    pub fn transfer(
+        &self,
+        leaf_owner: AccountInfo<'info>,
+        new_leaf_owner: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        proof: &[AccountInfo<'info>],
+        cnft: &CnftArgs,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let cpi_accounts = mpl_bubblegum::accounts::Transfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: leaf_owner.clone(),
+            leaf_delegate: leaf_owner,
+            new_leaf_owner,
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.bubblegum_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(proof.to_vec());
+        mpl_bubblegum::cpi::transfer(
+            cpi_ctx,
+            cnft.root,
+            cnft.data_hash,
+            cnft.creator_hash,
+            cnft.nonce,
+            cnft.index,
+        )
+    }
+
+    // The same accounts for a CPI into the escrow program
+    fn to_escrow_accounts(&self) -> escrow_cpi::cpi::accounts::CnftAccounts<'info> {
+        escrow_cpi::cpi::accounts::CnftAccounts {
+            tree_authority: self.tree_authority.to_account_info(),
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            bubblegum_program: self.bubblegum_program.to_account_info(),
+        }
+    }
+}
+
+pub fn marketplace_fee(price: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (price as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(MarketplaceError::ArithmeticOverflow)?
+        / BPS_DENOMINATOR as u128;
+    Ok(fee as u64)
+}
+
+// Pay escrowed SOL out of the offer PDA
+fn pay_from_offer(offer: &Account<Offer>, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let from = offer.to_account_info();
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(MarketplaceError::ArithmeticOverflow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(MarketplaceError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Close an emptied offer vault, returning its rent to the buyer
+fn close_offer_vault<'info>(
+    offer: &Account<'info, Offer>,
+    offer_vault: &Account<'info, TokenAccount>,
+    buyer: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let offer_seeds: &[&[u8]] = &[b"offer", offer.agent.as_ref(), offer.buyer.as_ref(), &[offer.bump]];
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: offer_vault.to_account_info(),
+            destination: buyer.clone(),
+            authority: offer.to_account_info(),
+        },
+        &[offer_seeds],
+    ))
+}
+
+// Hand an escrowed agent's cNFT and registry ownership to `recipient`: the
+// seller on a refund, else a buyer, whom the escrow is pointed at first
+#[allow(clippy::too_many_arguments)]
+fn release_agent<'info>(
+    listing: &Account<'info, Listing>,
+    agent_data: &Account<'info, AgentData>,
+    recipient: AccountInfo<'info>,
+    listing_escrow: &ListingEscrow<'info>,
+    seller: AccountInfo<'info>,
+    cnft_accounts: &CnftAccounts<'info>,
+    registry_program: &Program<'info, AgentRegistry>,
+    guardian: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    proof: &[AccountInfo<'info>],
+    cnft: CnftArgs,
+) -> Result<()> {
+    let listing_seeds: &[&[u8]] = &[b"listing", listing.agent.as_ref(), &[listing.bump]];
+    let (_, authority_bump) = Pubkey::find_program_address(&[ESCROW_AUTHORITY_SEED], &crate::ID);
+    let authority_seeds: &[&[u8]] = &[ESCROW_AUTHORITY_SEED, &[authority_bump]];
+    let signer_seeds: &[&[&[u8]]] = &[authority_seeds];
+    let escrow_program = listing_escrow.escrow_program.to_account_info();
+    let authority = listing_escrow.escrow_authority.to_account_info();
+
+    let refund = recipient.key() == listing.seller;
+    if !refund {
+        escrow_cpi::cpi::set_beneficiary(
+            CpiContext::new_with_signer(
+                escrow_program.clone(),
+                escrow_cpi::cpi::accounts::SetBeneficiary {
+                    authority: authority.clone(),
+                    escrow: listing_escrow.escrow.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            recipient.key(),
+        )?;
+    }
+    let settle_ctx = CpiContext::new_with_signer(
+        escrow_program,
+        escrow_cpi::cpi::accounts::SettleCnft {
+            authority,
+            escrow: listing_escrow.escrow.to_account_info(),
+            depositor: seller,
+            beneficiary: recipient.clone(),
+            oracle_worker: None,
+            cnft_accounts: cnft_accounts.to_escrow_accounts(),
+            system_program: system_program.to_account_info(),
+        },
+        signer_seeds,
+    )
+    .with_remaining_accounts(proof.to_vec());
+    if refund {
+        escrow_cpi::cpi::refund_cnft(settle_ctx, cnft)?;
+    } else {
+        escrow_cpi::cpi::release_cnft(settle_ctx, cnft)?;
+    }
+
+    agent_registry_cpi::cpi::transfer_ownership(
+        CpiContext::new_with_signer(
+            registry_program.to_account_info(),
+            agent_registry_cpi::cpi::accounts::TransferOwnership {
+                owner: listing.to_account_info(),
+                new_owner: recipient,
+                agent_data: agent_data.to_account_info(),
+                system_program: system_program.to_account_info(),
+                guardian: guardian.to_account_info(),
+            },
+            &[listing_seeds],
+        ),
+    )
+}
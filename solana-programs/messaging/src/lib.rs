@@ -0,0 +1,261 @@
+use anchor_lang::prelude::*;
+use agent_registry::AgentData;
+use social_graph::EdgeKind;
+
+declare_id!("Messag1ngPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const MAX_CID_LEN: usize = 64;
+
+#[program]
+pub mod messaging {
+    use super::*;
+
+    // Post an envelope into the recipient agent's inbox. The payload itself
+    // is encrypted off-chain to the recipient; the envelope only carries its
+    // CID, the sender's ephemeral key and a hash of the ciphertext.
+    pub fn send_message(
+        ctx: Context<SendMessage>,
+        payload_cid: String,
+        ephemeral_pubkey: [u8; 32],
+        payload_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(payload_cid.len() <= MAX_CID_LEN, MessagingError::CidTooLong);
+        let sender_agent = ctx.accounts.sender_agent.key();
+        let recipient_agent = ctx.accounts.recipient_agent.key();
+        require_keys_neq!(sender_agent, recipient_agent, MessagingError::SelfMessage);
+        require!(
+            ctx.accounts.recipient_block.data_is_empty(),
+            MessagingError::Blocked
+        );
+
+        let inbox = &mut ctx.accounts.inbox;
+        if inbox.agent == Pubkey::default() {
+            inbox.agent = recipient_agent;
+            inbox.bump = *ctx.bumps.get("inbox").unwrap();
+        }
+        let index = inbox.message_count;
+        inbox.message_count = inbox
+            .message_count
+            .checked_add(1)
+            .ok_or(MessagingError::ArithmeticOverflow)?;
+        inbox.unread_count = inbox.unread_count.checked_add(1).unwrap_or(u32::MAX);
+
+        let now = Clock::get()?.unix_timestamp;
+        let envelope = &mut ctx.accounts.envelope;
+        envelope.sender_agent = sender_agent;
+        envelope.recipient_agent = recipient_agent;
+        envelope.index = index;
+        envelope.payload_cid = payload_cid;
+        envelope.ephemeral_pubkey = ephemeral_pubkey;
+        envelope.payload_hash = payload_hash;
+        envelope.payer = ctx.accounts.sender.key();
+        envelope.sent_at = now;
+        envelope.read_at = 0;
+        envelope.bump = *ctx.bumps.get("envelope").unwrap();
+
+        emit!(MessageSent {
+            sender_agent,
+            recipient_agent,
+            envelope: envelope.key(),
+            index,
+            payload_hash,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Post a read receipt for an envelope (recipient agent's owner)
+    pub fn mark_read(ctx: Context<MarkRead>) -> Result<()> {
+        let envelope = &mut ctx.accounts.envelope;
+        require!(envelope.read_at == 0, MessagingError::AlreadyRead);
+
+        let now = Clock::get()?.unix_timestamp;
+        envelope.read_at = now;
+        let inbox = &mut ctx.accounts.inbox;
+        inbox.unread_count = inbox.unread_count.saturating_sub(1);
+
+        emit!(MessageRead {
+            sender_agent: envelope.sender_agent,
+            recipient_agent: envelope.recipient_agent,
+            envelope: envelope.key(),
+            index: envelope.index,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Delete an envelope from the inbox (recipient agent's owner). Rent goes
+    // back to the wallet that sent it.
+    pub fn delete_message(ctx: Context<DeleteMessage>) -> Result<()> {
+        let envelope = &ctx.accounts.envelope;
+        if envelope.read_at == 0 {
+            let inbox = &mut ctx.accounts.inbox;
+            inbox.unread_count = inbox.unread_count.saturating_sub(1);
+        }
+
+        emit!(MessageDeleted {
+            recipient_agent: envelope.recipient_agent,
+            envelope: envelope.key(),
+            index: envelope.index,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+// Per-recipient index; envelopes are addressed by their position in it
+#[account]
+pub struct Inbox {
+    pub agent: Pubkey,
+    pub message_count: u64,  // Next envelope index
+    pub unread_count: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Envelope {
+    pub sender_agent: Pubkey,
+    pub recipient_agent: Pubkey,
+    pub index: u64,
+    pub payload_cid: String,         // Encrypted payload stored off-chain
+    pub ephemeral_pubkey: [u8; 32],  // Sender's x25519 key for this message
+    pub payload_hash: [u8; 32],      // Hash of the ciphertext
+    pub payer: Pubkey,               // Refunded when the envelope is deleted
+    pub sent_at: i64,
+    pub read_at: i64,  // 0 until the recipient posts a read receipt
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct SendMessage<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(constraint = sender_agent.owner == sender.key() @ MessagingError::Unauthorized)]
+    pub sender_agent: Account<'info, AgentData>,
+
+    pub recipient_agent: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + std::mem::size_of::<Inbox>(),
+        seeds = [b"inbox", recipient_agent.key().as_ref()],
+        bump
+    )]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + std::mem::size_of::<Envelope>() + MAX_CID_LEN,
+        seeds = [
+            b"envelope",
+            recipient_agent.key().as_ref(),
+            inbox.message_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub envelope: Account<'info, Envelope>,
+
+    /// CHECK: the recipient's social graph block edge towards the sender, only checked for being empty
+    #[account(
+        seeds = [
+            b"edge",
+            recipient_agent.key().as_ref(),
+            sender_agent.key().as_ref(),
+            &[EdgeKind::Block as u8]
+        ],
+        bump,
+        seeds::program = social_graph::ID
+    )]
+    pub recipient_block: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarkRead<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        address = envelope.recipient_agent,
+        constraint = recipient_agent.owner == owner.key() @ MessagingError::Unauthorized
+    )]
+    pub recipient_agent: Account<'info, AgentData>,
+
+    #[account(mut, seeds = [b"inbox", envelope.recipient_agent.as_ref()], bump = inbox.bump)]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(mut)]
+    pub envelope: Account<'info, Envelope>,
+}
+
+#[derive(Accounts)]
+pub struct DeleteMessage<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        address = envelope.recipient_agent,
+        constraint = recipient_agent.owner == owner.key() @ MessagingError::Unauthorized
+    )]
+    pub recipient_agent: Account<'info, AgentData>,
+
+    #[account(mut, seeds = [b"inbox", envelope.recipient_agent.as_ref()], bump = inbox.bump)]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(mut, close = payer)]
+    pub envelope: Account<'info, Envelope>,
+
+    /// CHECK: receives the envelope's rent
+    #[account(mut, address = envelope.payer)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+// Events
+#[event]
+pub struct MessageSent {
+    pub sender_agent: Pubkey,
+    pub recipient_agent: Pubkey,
+    pub envelope: Pubkey,
+    pub index: u64,
+    pub payload_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageRead {
+    pub sender_agent: Pubkey,
+    pub recipient_agent: Pubkey,
+    pub envelope: Pubkey,
+    pub index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageDeleted {
+    pub recipient_agent: Pubkey,
+    pub envelope: Pubkey,
+    pub index: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum MessagingError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Payload CID is too long")]
+    CidTooLong,
+    #[msg("An agent cannot message itself")]
+    SelfMessage,
+    #[msg("The recipient has blocked this agent")]
+    Blocked,
+    #[msg("Message has already been read")]
+    AlreadyRead,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
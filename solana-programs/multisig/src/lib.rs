@@ -0,0 +1,469 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+declare_id!("Mu1tis1gPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_MEMBERS: usize = 10;  // Approvals are a bitmask over member indexes
+const MAX_PROPOSAL_INSTRUCTIONS: usize = 4;
+const MAX_INSTRUCTION_ACCOUNTS: usize = 12;
+const MAX_INSTRUCTION_DATA_LEN: usize = 256;
+pub const MULTISIG_AUTHORITY_SEED: &[u8] = b"multisig_authority";
+
+#[program]
+pub mod multisig {
+    use super::*;
+
+    // Create a multisig. Its authority PDA is what the registry, influence
+    // and relationship programs hand their admin roles to (set_registry_admin,
+    // set_mint_authority, set_config_admin); it only signs through proposals
+    // approved by `threshold` members.
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_members(&members, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.create_key = ctx.accounts.create_key.key();
+        multisig.members = members.clone();
+        multisig.threshold = threshold;
+        multisig.members_version = 0;
+        multisig.proposal_count = 0;
+        multisig.bump = *ctx.bumps.get("multisig").unwrap();
+        multisig.authority_bump = *ctx.bumps.get("multisig_authority").unwrap();
+
+        emit!(MultisigCreated {
+            multisig: multisig.key(),
+            authority: ctx.accounts.multisig_authority.key(),
+            members,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    // Propose instructions for the authority PDA to sign. Counts as the
+    // proposer's approval.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        instructions: Vec<ProposalInstruction>,
+    ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let member_index = member_index(multisig, &ctx.accounts.proposer.key())?;
+        validate_proposal_instructions(&instructions, &ctx.accounts.multisig_authority.key())?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.multisig = multisig.key();
+        proposal.proposal_id = multisig.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.members_version = multisig.members_version;
+        proposal.approvals = 1 << member_index;
+        proposal.state = ProposalState::Active;
+        proposal.created_at = Clock::get()?.unix_timestamp;
+        proposal.instructions = instructions;
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+        multisig.proposal_count = multisig.proposal_count.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(ProposalCreated {
+            multisig: multisig.key(),
+            proposal: proposal.key(),
+            proposal_id: proposal.proposal_id,
+            proposer: proposal.proposer,
+        });
+
+        Ok(())
+    }
+
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        require_active(multisig, proposal)?;
+
+        let bit = 1 << member_index(multisig, &ctx.accounts.member.key())?;
+        require!(proposal.approvals & bit == 0, MultisigError::AlreadyApproved);
+        proposal.approvals |= bit;
+
+        emit!(ProposalApproved {
+            proposal: proposal.key(),
+            member: ctx.accounts.member.key(),
+            approvals: proposal.approvals.count_ones() as u8,
+            threshold: multisig.threshold,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw a proposal that has not been executed. Only its proposer.
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.state == ProposalState::Active, MultisigError::InvalidProposalState);
+        proposal.state = ProposalState::Cancelled;
+
+        emit!(ProposalCancelled {
+            proposal: proposal.key(),
+        });
+
+        Ok(())
+    }
+
+    // Execute a proposal approved by at least `threshold` members
+    // (permissionless). Every account its instructions reference, including
+    // the target programs, must be passed as remaining accounts; the
+    // authority PDA signs each instruction.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+    ) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let proposal = &mut ctx.accounts.proposal;
+        require_active(multisig, proposal)?;
+        require!(
+            proposal.approvals.count_ones() >= multisig.threshold as u32,
+            MultisigError::ThresholdNotMet
+        );
+
+        let multisig_key = multisig.key();
+        let authority_seeds: &[&[u8]] = &[
+            MULTISIG_AUTHORITY_SEED,
+            multisig_key.as_ref(),
+            &[multisig.authority_bump],
+        ];
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.multisig_authority.to_account_info());
+        for proposal_instruction in proposal.instructions.iter() {
+            let instruction = Instruction {
+                program_id: proposal_instruction.program_id,
+                accounts: proposal_instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| AccountMeta {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: proposal_instruction.data.clone(),
+            };
+            invoke_signed(&instruction, &account_infos, &[authority_seeds])?;
+        }
+        proposal.state = ProposalState::Executed;
+
+        emit!(ProposalExecuted {
+            proposal: proposal.key(),
+            executor: ctx.accounts.executor.key(),
+        });
+
+        Ok(())
+    }
+
+    // Replace the members and threshold. Only the multisig's own authority
+    // can sign, so changes go through an executed proposal. Proposals still
+    // open under the old members can no longer be approved or executed.
+    pub fn set_members(
+        ctx: Context<SetMembers>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_members(&members, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.members = members.clone();
+        multisig.threshold = threshold;
+        multisig.members_version = multisig.members_version.checked_add(1).unwrap_or(u32::MAX);
+
+        emit!(MembersUpdated {
+            multisig: multisig.key(),
+            members,
+            threshold,
+            members_version: multisig.members_version,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct Multisig {
+    pub create_key: Pubkey,  // Seed that makes the multisig's address unique
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub members_version: u32,  // Bumped on every membership change
+    pub proposal_count: u64,
+    pub bump: u8,
+    pub authority_bump: u8,
+}
+
+#[account]
+pub struct MultisigProposal {
+    pub multisig: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub members_version: u32,  // Membership the approvals refer to
+    pub approvals: u16,        // Bit i set when members[i] approved
+    pub state: ProposalState,
+    pub created_at: i64,
+    pub instructions: Vec<ProposalInstruction>,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub create_key: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Multisig>() + MAX_MEMBERS * 32,
+        seeds = [b"multisig", create_key.key().as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: PDA that signs executed proposals
+    #[account(seeds = [MULTISIG_AUTHORITY_SEED, multisig.key().as_ref()], bump)]
+    pub multisig_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: PDA that signs executed proposals
+    #[account(
+        seeds = [MULTISIG_AUTHORITY_SEED, multisig.key().as_ref()],
+        bump = multisig.authority_bump,
+    )]
+    pub multisig_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + std::mem::size_of::<MultisigProposal>()
+            + MAX_PROPOSAL_INSTRUCTIONS
+                * (32 + 4 + MAX_INSTRUCTION_ACCOUNTS * 34 + 4 + MAX_INSTRUCTION_DATA_LEN),
+        seeds = [b"proposal", multisig.key().as_ref(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, MultisigProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(mut, has_one = multisig)]
+    pub proposal: Account<'info, MultisigProposal>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    pub proposer: Signer<'info>,
+
+    #[account(mut, has_one = proposer @ MultisigError::Unauthorized)]
+    pub proposal: Account<'info, MultisigProposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    pub executor: Signer<'info>,
+
+    #[account(
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: PDA that signs the proposal's instructions
+    #[account(
+        seeds = [MULTISIG_AUTHORITY_SEED, multisig.key().as_ref()],
+        bump = multisig.authority_bump,
+    )]
+    pub multisig_authority: UncheckedAccount<'info>,
+
+    #[account(mut, has_one = multisig)]
+    pub proposal: Account<'info, MultisigProposal>,
+}
+
+#[derive(Accounts)]
+pub struct SetMembers<'info> {
+    #[account(
+        seeds = [MULTISIG_AUTHORITY_SEED, multisig.key().as_ref()],
+        bump = multisig.authority_bump,
+    )]
+    pub multisig_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,  // Only the multisig authority may sign
+    pub is_writable: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ProposalState {
+    Active,
+    Executed,
+    Cancelled,
+}
+
+// Events
+#[event]
+pub struct MultisigCreated {
+    pub multisig: Pubkey,
+    pub authority: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub multisig: Pubkey,
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub member: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub proposal: Pubkey,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct MembersUpdated {
+    pub multisig: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub members_version: u32,
+}
+
+// Custom errors
+#[error_code]
+pub enum MultisigError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Members must be unique, and between one and the maximum in number")]
+    InvalidMembers,
+    #[msg("Threshold must be between one and the number of members")]
+    InvalidThreshold,
+    #[msg("Proposal instructions exceed size limits or require other signers")]
+    InvalidInstructions,
+    #[msg("Signer is not a member of this multisig")]
+    NotAMember,
+    #[msg("Proposal is not in the right state for this action")]
+    InvalidProposalState,
+    #[msg("Members changed after the proposal was created")]
+    ProposalStale,
+    #[msg("Member has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal does not have enough approvals")]
+    ThresholdNotMet,
+}
+
+// Helper functions
+fn validate_members(members: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !members.is_empty()
+            && members.len() <= MAX_MEMBERS
+            && members.iter().enumerate().all(|(i, member)| !members[..i].contains(member)),
+        MultisigError::InvalidMembers
+    );
+    require!(
+        threshold > 0 && threshold as usize <= members.len(),
+        MultisigError::InvalidThreshold
+    );
+    Ok(())
+}
+
+fn member_index(multisig: &Multisig, key: &Pubkey) -> Result<usize> {
+    multisig
+        .members
+        .iter()
+        .position(|member| member == key)
+        .ok_or_else(|| error!(MultisigError::NotAMember))
+}
+
+fn require_active(multisig: &Multisig, proposal: &MultisigProposal) -> Result<()> {
+    require!(proposal.state == ProposalState::Active, MultisigError::InvalidProposalState);
+    require!(
+        proposal.members_version == multisig.members_version,
+        MultisigError::ProposalStale
+    );
+    Ok(())
+}
+
+fn validate_proposal_instructions(
+    instructions: &[ProposalInstruction],
+    multisig_authority: &Pubkey,
+) -> Result<()> {
+    require!(
+        !instructions.is_empty() && instructions.len() <= MAX_PROPOSAL_INSTRUCTIONS,
+        MultisigError::InvalidInstructions
+    );
+    for instruction in instructions {
+        require!(
+            instruction.accounts.len() <= MAX_INSTRUCTION_ACCOUNTS
+                && instruction.data.len() <= MAX_INSTRUCTION_DATA_LEN
+                && instruction
+                    .accounts
+                    .iter()
+                    .all(|meta| !meta.is_signer || meta.pubkey == *multisig_authority),
+            MultisigError::InvalidInstructions
+        );
+    }
+    Ok(())
+}
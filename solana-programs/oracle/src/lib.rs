@@ -0,0 +1,436 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked,
+};
+
+declare_id!("0rac1ePr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+// Layout of the Ed25519 program's instruction data for a single signature
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+
+#[program]
+pub mod oracle {
+    use super::*;
+
+    pub fn initialize_oracle(ctx: Context<InitializeOracle>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.worker_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Register an off-chain AI worker by the key it signs results with
+    // (admin). `operator` is the wallet running it, for attribution only.
+    pub fn register_worker(
+        ctx: Context<RegisterWorker>,
+        signing_key: Pubkey,
+        operator: Pubkey,
+    ) -> Result<()> {
+        let worker = &mut ctx.accounts.worker;
+        worker.signing_key = signing_key;
+        worker.operator = operator;
+        worker.active = true;
+        worker.results_submitted = 0;
+        worker.registered_at = Clock::get()?.unix_timestamp;
+        worker.bump = *ctx.bumps.get("worker").unwrap();
+
+        let config = &mut ctx.accounts.config;
+        config.worker_count = config.worker_count.checked_add(1).unwrap_or(u32::MAX);
+
+        emit!(WorkerUpdated {
+            signing_key,
+            operator,
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    // Enable or disable a worker (admin). Results it already submitted stay
+    // readable.
+    pub fn set_worker_active(ctx: Context<SetWorkerActive>, active: bool) -> Result<()> {
+        let worker = &mut ctx.accounts.worker;
+        worker.active = active;
+
+        emit!(WorkerUpdated {
+            signing_key: worker.signing_key,
+            operator: worker.operator,
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Store a worker-signed result. The instruction right before this one
+    // must be an Ed25519 program instruction verifying the worker's
+    // signature over `signed_result_message`. Anyone can relay it; the
+    // result PDA is keyed by worker and nonce, so each signed result can be
+    // stored once.
+    pub fn submit_result(
+        ctx: Context<SubmitResult>,
+        nonce: u64,
+        expires_at: i64,
+        payload: ResultPayload,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= expires_at, OracleError::ResultExpired);
+        payload.validate()?;
+
+        let signing_key = ctx.accounts.worker.signing_key;
+        let message = signed_result_message(nonce, expires_at, &payload)?;
+        verify_ed25519_signature(&ctx.accounts.instructions, &signing_key, &message)?;
+
+        let result = &mut ctx.accounts.result;
+        result.worker = signing_key;
+        result.nonce = nonce;
+        result.payload = payload.clone();
+        result.submitted_at = now;
+        result.expires_at = expires_at;
+        result.bump = *ctx.bumps.get("result").unwrap();
+
+        let worker = &mut ctx.accounts.worker;
+        worker.results_submitted = worker.results_submitted.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(ResultSubmitted {
+            result: result.key(),
+            worker: signing_key,
+            nonce,
+            kind: payload.kind(),
+            subject: payload.subject(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Fail unless the result is of `kind`, concerns `subject` and was stored
+    // within `max_age` seconds (0 accepts any age). The registry and
+    // relationship programs CPI into this before acting on a result;
+    // programs that already load the account can use `OracleResult::matches`
+    // instead.
+    pub fn verify_result(
+        ctx: Context<VerifyResult>,
+        subject: Pubkey,
+        kind: ResultKind,
+        max_age: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.result.matches(subject, kind, max_age, now),
+            OracleError::ResultMismatch
+        );
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct OracleConfig {
+    pub admin: Pubkey,
+    pub worker_count: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Worker {
+    pub signing_key: Pubkey,
+    pub operator: Pubkey,
+    pub active: bool,
+    pub results_submitted: u64,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct OracleResult {
+    pub worker: Pubkey,  // Signing key of the worker that produced it
+    pub nonce: u64,
+    pub payload: ResultPayload,
+    pub submitted_at: i64,
+    pub expires_at: i64,  // Deadline the worker set for submitting the result
+    pub bump: u8,
+}
+
+impl OracleResult {
+    pub fn matches(&self, subject: Pubkey, kind: ResultKind, max_age: i64, now: i64) -> bool {
+        self.payload.subject() == subject
+            && self.payload.kind() == kind
+            && (max_age == 0 || now - self.submitted_at <= max_age)
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeOracle<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<OracleConfig>(),
+        seeds = [b"oracle_config"],
+        bump
+    )]
+    pub config: Account<'info, OracleConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(signing_key: Pubkey)]
+pub struct RegisterWorker<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"oracle_config"],
+        bump = config.bump,
+        has_one = admin @ OracleError::Unauthorized,
+    )]
+    pub config: Account<'info, OracleConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Worker>(),
+        seeds = [b"worker", signing_key.as_ref()],
+        bump
+    )]
+    pub worker: Account<'info, Worker>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetWorkerActive<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"oracle_config"],
+        bump = config.bump,
+        has_one = admin @ OracleError::Unauthorized,
+    )]
+    pub config: Account<'info, OracleConfig>,
+
+    #[account(mut, seeds = [b"worker", worker.signing_key.as_ref()], bump = worker.bump)]
+    pub worker: Account<'info, Worker>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct SubmitResult<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"worker", worker.signing_key.as_ref()],
+        bump = worker.bump,
+        constraint = worker.active @ OracleError::WorkerInactive,
+    )]
+    pub worker: Account<'info, Worker>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<OracleResult>(),
+        seeds = [b"result", worker.signing_key.as_ref(), nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub result: Account<'info, OracleResult>,
+
+    /// CHECK: the instructions sysvar, read to find the Ed25519 verification
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyResult<'info> {
+    #[account(
+        seeds = [b"result", result.worker.as_ref(), result.nonce.to_le_bytes().as_ref()],
+        bump = result.bump,
+    )]
+    pub result: Account<'info, OracleResult>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResultKind {
+    DateOutcome,
+    ConversationSummary,
+    Sentiment,
+    TraitDelta,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ResultPayload {
+    DateOutcome {
+        relationship: Pubkey,
+        date_index: u32,
+        attended: bool,
+        rating: u8,  // 0-100
+    },
+    ConversationSummary {
+        relationship: Pubkey,
+        summary_hash: [u8; 32],  // Summary text is kept off-chain
+    },
+    Sentiment {
+        agent: Pubkey,
+        score: i8,  // -100 to 100
+    },
+    TraitDelta {
+        agent: Pubkey,
+        deltas: [i8; 8],  // Same order as PersonalityTraits
+    },
+}
+
+impl ResultPayload {
+    pub fn kind(&self) -> ResultKind {
+        match self {
+            ResultPayload::DateOutcome { .. } => ResultKind::DateOutcome,
+            ResultPayload::ConversationSummary { .. } => ResultKind::ConversationSummary,
+            ResultPayload::Sentiment { .. } => ResultKind::Sentiment,
+            ResultPayload::TraitDelta { .. } => ResultKind::TraitDelta,
+        }
+    }
+
+    // Relationship or agent the result is about
+    pub fn subject(&self) -> Pubkey {
+        match self {
+            ResultPayload::DateOutcome { relationship, .. }
+            | ResultPayload::ConversationSummary { relationship, .. } => *relationship,
+            ResultPayload::Sentiment { agent, .. } | ResultPayload::TraitDelta { agent, .. } => {
+                *agent
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ResultPayload::DateOutcome { rating, .. } => {
+                require!(*rating <= 100, OracleError::InvalidPayload)
+            }
+            ResultPayload::Sentiment { score, .. } => {
+                require!((-100..=100).contains(score), OracleError::InvalidPayload)
+            }
+            ResultPayload::TraitDelta { deltas, .. } => {
+                require!(
+                    deltas.iter().all(|d| (-100..=100).contains(d)),
+                    OracleError::InvalidPayload
+                )
+            }
+            ResultPayload::ConversationSummary { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+// Events
+#[event]
+pub struct WorkerUpdated {
+    pub signing_key: Pubkey,
+    pub operator: Pubkey,
+    pub active: bool,
+}
+
+#[event]
+pub struct ResultSubmitted {
+    pub result: Pubkey,
+    pub worker: Pubkey,
+    pub nonce: u64,
+    pub kind: ResultKind,
+    pub subject: Pubkey,
+    pub timestamp: i64,
+}
+
+// Custom errors
+#[error_code]
+pub enum OracleError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Worker is not active")]
+    WorkerInactive,
+    #[msg("Signed result has expired")]
+    ResultExpired,
+    #[msg("Result payload is out of range")]
+    InvalidPayload,
+    #[msg("Missing or invalid Ed25519 signature verification")]
+    InvalidSignature,
+    #[msg("Result does not match the expected subject, kind or age")]
+    ResultMismatch,
+}
+
+// Helper functions
+// Bytes a worker signs: this program's id followed by the borsh-encoded
+// nonce, expiry and payload, so signatures can't be replayed elsewhere
+pub fn signed_result_message(
+    nonce: u64,
+    expires_at: i64,
+    payload: &ResultPayload,
+) -> Result<Vec<u8>> {
+    let mut message = crate::ID.to_bytes().to_vec();
+    nonce.serialize(&mut message)?;
+    expires_at.serialize(&mut message)?;
+    payload.serialize(&mut message)?;
+    Ok(message)
+}
+
+// Check that the previous instruction is an Ed25519 program instruction
+// verifying one signature by `signer` over exactly `message`, with the key,
+// signature and message all inline in that instruction
+fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)?;
+    require!(current_index > 0, OracleError::InvalidSignature);
+    let ix = load_instruction_at_checked(current_index as usize - 1, instructions)?;
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        OracleError::InvalidSignature
+    );
+
+    let data = &ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        OracleError::InvalidSignature
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_ix_index = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix_index = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_len = read_u16(offsets + 10) as usize;
+    let message_ix_index = read_u16(offsets + 12);
+    require!(
+        signature_ix_index == u16::MAX
+            && pubkey_ix_index == u16::MAX
+            && message_ix_index == u16::MAX,
+        OracleError::InvalidSignature
+    );
+
+    let signed_key = data
+        .get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN)
+        .ok_or(OracleError::InvalidSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(OracleError::InvalidSignature)?;
+    require!(
+        signed_key == signer.as_ref() && signed_message == message,
+        OracleError::InvalidSignature
+    );
+
+    Ok(())
+}
@@ -0,0 +1,431 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_instruction_at_checked,
+};
+use anchor_lang::system_program;
+
+declare_id!("PaymasterPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+// Compute budget instructions are always allowed alongside sponsored ones
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = solana_program::pubkey!("ComputeBudget111111111111111111111111111111");
+
+const MAX_ALLOWED_INSTRUCTIONS: usize = 16;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+#[program]
+pub mod paymaster {
+    use super::*;
+
+    // Create a sponsor. Its relayer is the fee payer of sponsored
+    // transactions and is reimbursed from the sponsor's deposit.
+    pub fn create_sponsor(
+        ctx: Context<CreateSponsor>,
+        relayer: Pubkey,
+        policy: SponsorPolicy,
+    ) -> Result<()> {
+        policy.validate()?;
+
+        let sponsor = &mut ctx.accounts.sponsor;
+        sponsor.authority = ctx.accounts.authority.key();
+        sponsor.relayer = relayer;
+        sponsor.policy = policy;
+        sponsor.total_sponsored = 0;
+        sponsor.is_active = true;
+        sponsor.bump = *ctx.bumps.get("sponsor").unwrap();
+
+        emit!(SponsorCreated {
+            sponsor: sponsor.key(),
+            authority: sponsor.authority,
+            relayer,
+        });
+
+        Ok(())
+    }
+
+    // Update a sponsor's relayer, policy and status (sponsor authority)
+    pub fn update_sponsor(
+        ctx: Context<UpdateSponsor>,
+        relayer: Pubkey,
+        policy: SponsorPolicy,
+        is_active: bool,
+    ) -> Result<()> {
+        policy.validate()?;
+
+        let sponsor = &mut ctx.accounts.sponsor;
+        sponsor.relayer = relayer;
+        sponsor.policy = policy;
+        sponsor.is_active = is_active;
+
+        emit!(SponsorUpdated {
+            sponsor: sponsor.key(),
+            relayer,
+            is_active,
+        });
+
+        Ok(())
+    }
+
+    // Top up a sponsor's deposit; anyone may fund a sponsor
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, PaymasterError::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.sponsor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(SponsorFunded {
+            sponsor: ctx.accounts.sponsor.key(),
+            funder: ctx.accounts.funder.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw unspent deposit (sponsor authority)
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        require!(
+            amount <= available_deposit(&ctx.accounts.sponsor)?,
+            PaymasterError::InsufficientDeposit
+        );
+        pay_from_sponsor(
+            &ctx.accounts.sponsor,
+            &ctx.accounts.authority.to_account_info(),
+            amount,
+        )?;
+
+        emit!(SponsorWithdrawn {
+            sponsor: ctx.accounts.sponsor.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Sponsor the current transaction for `user`. The relayer pays the
+    // transaction fee and is reimbursed `fee_lamports`; `rent_lamports` is
+    // sent to the user up front so the sponsored instructions can pay rent
+    // from the user's wallet. Every other instruction in the transaction must
+    // be in the sponsor's allowed set, and the total counts against the
+    // user's daily budget.
+    pub fn sponsor_transaction(
+        ctx: Context<SponsorTransaction>,
+        rent_lamports: u64,
+        fee_lamports: u64,
+    ) -> Result<()> {
+        let sponsor = &ctx.accounts.sponsor;
+        require!(sponsor.is_active, PaymasterError::SponsorInactive);
+        require!(
+            fee_lamports <= sponsor.policy.max_fee_per_tx,
+            PaymasterError::FeeTooHigh
+        );
+        require!(
+            rent_lamports <= sponsor.policy.max_rent_per_tx,
+            PaymasterError::RentTooHigh
+        );
+        check_transaction_instructions(&ctx.accounts.instructions, &sponsor.policy)?;
+
+        let total = rent_lamports
+            .checked_add(fee_lamports)
+            .ok_or(PaymasterError::ArithmeticOverflow)?;
+        let now = Clock::get()?.unix_timestamp;
+        let day = now / SECONDS_PER_DAY;
+        let user_budget = &mut ctx.accounts.user_budget;
+        if user_budget.day != day {
+            user_budget.day = day;
+            user_budget.spent_today = 0;
+        }
+        let spent_today = user_budget
+            .spent_today
+            .checked_add(total)
+            .ok_or(PaymasterError::ArithmeticOverflow)?;
+        require!(
+            spent_today <= sponsor.policy.daily_user_budget,
+            PaymasterError::DailyBudgetExceeded
+        );
+        require!(
+            total <= available_deposit(sponsor)?,
+            PaymasterError::InsufficientDeposit
+        );
+        user_budget.sponsor = sponsor.key();
+        user_budget.user = ctx.accounts.user.key();
+        user_budget.spent_today = spent_today;
+        user_budget.bump = *ctx.bumps.get("user_budget").unwrap();
+
+        pay_from_sponsor(sponsor, &ctx.accounts.user.to_account_info(), rent_lamports)?;
+        pay_from_sponsor(sponsor, &ctx.accounts.relayer.to_account_info(), fee_lamports)?;
+
+        let sponsor = &mut ctx.accounts.sponsor;
+        sponsor.total_sponsored = sponsor.total_sponsored.checked_add(total).unwrap_or(u64::MAX);
+
+        emit!(TransactionSponsored {
+            sponsor: sponsor.key(),
+            user: ctx.accounts.user.key(),
+            rent_lamports,
+            fee_lamports,
+            spent_today,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+// A sponsor's deposit is held as lamports in this account, above its rent
+#[account]
+pub struct Sponsor {
+    pub authority: Pubkey,
+    pub relayer: Pubkey,  // Fee payer of sponsored transactions
+    pub policy: SponsorPolicy,
+    pub total_sponsored: u64,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+// Sponsored lamports per user, reset each UTC day
+#[account]
+pub struct UserBudget {
+    pub sponsor: Pubkey,
+    pub user: Pubkey,
+    pub day: i64,  // Days since the Unix epoch
+    pub spent_today: u64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct CreateSponsor<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<Sponsor>() + MAX_ALLOWED_INSTRUCTIONS * std::mem::size_of::<AllowedInstruction>(),
+        seeds = [b"sponsor", authority.key().as_ref()],
+        bump
+    )]
+    pub sponsor: Account<'info, Sponsor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSponsor<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor", authority.key().as_ref()],
+        bump = sponsor.bump,
+        has_one = authority @ PaymasterError::Unauthorized,
+    )]
+    pub sponsor: Account<'info, Sponsor>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(mut, seeds = [b"sponsor", sponsor.authority.as_ref()], bump = sponsor.bump)]
+    pub sponsor: Account<'info, Sponsor>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor", authority.key().as_ref()],
+        bump = sponsor.bump,
+        has_one = authority @ PaymasterError::Unauthorized,
+    )]
+    pub sponsor: Account<'info, Sponsor>,
+}
+
+#[derive(Accounts)]
+pub struct SponsorTransaction<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    // The sponsored user; signs so budgets can't be drawn in someone else's name
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"sponsor", sponsor.authority.as_ref()],
+        bump = sponsor.bump,
+        has_one = relayer @ PaymasterError::Unauthorized,
+    )]
+    pub sponsor: Account<'info, Sponsor>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + std::mem::size_of::<UserBudget>(),
+        seeds = [b"user_budget", sponsor.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_budget: Account<'info, UserBudget>,
+
+    /// CHECK: the instructions sysvar, read to check the transaction's instructions
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SponsorPolicy {
+    pub daily_user_budget: u64,  // Lamports per user per day
+    pub max_fee_per_tx: u64,
+    pub max_rent_per_tx: u64,
+    pub allowed: Vec<AllowedInstruction>,
+}
+
+impl SponsorPolicy {
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.allowed.len() <= MAX_ALLOWED_INSTRUCTIONS,
+            PaymasterError::TooManyAllowedInstructions
+        );
+        Ok(())
+    }
+
+    fn allows(&self, program_id: &Pubkey, data: &[u8]) -> bool {
+        data.len() >= 8
+            && self.allowed.iter().any(|allowed| {
+                &allowed.program_id == program_id && allowed.discriminator == data[..8]
+            })
+    }
+}
+
+// An Anchor instruction the sponsor pays for, by program and discriminator
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllowedInstruction {
+    pub program_id: Pubkey,
+    pub discriminator: [u8; 8],
+}
+
+// Events
+#[event]
+pub struct SponsorCreated {
+    pub sponsor: Pubkey,
+    pub authority: Pubkey,
+    pub relayer: Pubkey,
+}
+
+#[event]
+pub struct SponsorUpdated {
+    pub sponsor: Pubkey,
+    pub relayer: Pubkey,
+    pub is_active: bool,
+}
+
+#[event]
+pub struct SponsorFunded {
+    pub sponsor: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SponsorWithdrawn {
+    pub sponsor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TransactionSponsored {
+    pub sponsor: Pubkey,
+    pub user: Pubkey,
+    pub rent_lamports: u64,
+    pub fee_lamports: u64,
+    pub spent_today: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum PaymasterError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Sponsor is not active")]
+    SponsorInactive,
+    #[msg("Too many allowed instructions")]
+    TooManyAllowedInstructions,
+    #[msg("Transaction contains an instruction the sponsor does not pay for")]
+    InstructionNotAllowed,
+    #[msg("Only one sponsorship per transaction")]
+    DuplicateSponsorship,
+    #[msg("Fee reimbursement exceeds the sponsor's limit")]
+    FeeTooHigh,
+    #[msg("Rent top-up exceeds the sponsor's limit")]
+    RentTooHigh,
+    #[msg("User's daily sponsorship budget is exhausted")]
+    DailyBudgetExceeded,
+    #[msg("Sponsor deposit is too low")]
+    InsufficientDeposit,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// Every instruction must be this program's (exactly once), a compute budget
+// instruction, or allowed by the policy
+fn check_transaction_instructions(instructions: &AccountInfo, policy: &SponsorPolicy) -> Result<()> {
+    let mut sponsorships = 0;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == crate::ID {
+            sponsorships += 1;
+        } else if ix.program_id != COMPUTE_BUDGET_PROGRAM_ID {
+            require!(
+                policy.allows(&ix.program_id, &ix.data),
+                PaymasterError::InstructionNotAllowed
+            );
+        }
+        index += 1;
+    }
+    require!(sponsorships == 1, PaymasterError::DuplicateSponsorship);
+    Ok(())
+}
+
+// Deposit above the sponsor account's rent-exempt minimum
+fn available_deposit(sponsor: &Account<Sponsor>) -> Result<u64> {
+    let info = sponsor.to_account_info();
+    let rent = Rent::get()?.minimum_balance(info.data_len());
+    Ok(info.lamports().saturating_sub(rent))
+}
+
+// Pay SOL out of the sponsor PDA
+fn pay_from_sponsor(sponsor: &Account<Sponsor>, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let from = sponsor.to_account_info();
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(PaymasterError::ArithmeticOverflow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(PaymasterError::ArithmeticOverflow)?;
+    Ok(())
+}
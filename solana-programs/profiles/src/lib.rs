@@ -0,0 +1,446 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use agent_registry::{AgentData, PROFILE_HOOK_AUTHORITY_SEED};
+
+declare_id!("Pr0f1lesPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_AVATAR_CID_LEN: usize = 64;
+const MAX_SNS_DOMAIN_LEN: usize = 64;
+
+// Solana Name Service program and the `.sol` top-level domain it registers under
+pub const SNS_PROGRAM_ID: Pubkey = solana_program::pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+pub const SOL_TLD_AUTHORITY: Pubkey = solana_program::pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JJU");
+const SNS_HASH_PREFIX: &str = "SPL Name Service";
+
+#[program]
+pub mod profiles {
+    use super::*;
+
+    // Create the signer's profile. Agents the wallet already owns can be
+    // passed as remaining accounts to seed the linked agent count; the
+    // registry keeps it in sync from then on.
+    pub fn create_profile(
+        ctx: Context<CreateProfile>,
+        display_name_hash: [u8; 32],
+        avatar_cid: String,
+        settings: u64,
+    ) -> Result<()> {
+        require!(
+            avatar_cid.len() <= MAX_AVATAR_CID_LEN,
+            ProfileError::AvatarCidTooLong
+        );
+
+        let wallet = ctx.accounts.wallet.key();
+        let mut linked = Vec::with_capacity(ctx.remaining_accounts.len());
+        for agent_info in ctx.remaining_accounts {
+            let agent_data: Account<AgentData> = Account::try_from(agent_info)?;
+            require_keys_eq!(agent_data.owner, wallet, ProfileError::Unauthorized);
+            require!(
+                !linked.contains(&agent_info.key()),
+                ProfileError::DuplicateAgent
+            );
+            linked.push(agent_info.key());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let profile = &mut ctx.accounts.profile;
+        profile.wallet = wallet;
+        profile.display_name_hash = display_name_hash;
+        profile.avatar_cid = avatar_cid;
+        profile.settings = settings;
+        profile.linked_agent_count = linked.len() as u32;
+        profile.sns_domain_hash = [0; 32];
+        profile.sns_name_account = Pubkey::default();
+        profile.created_at = now;
+        profile.updated_at = now;
+        profile.bump = *ctx.bumps.get("profile").unwrap();
+
+        emit!(ProfileCreated {
+            wallet,
+            display_name_hash,
+            linked_agent_count: profile.linked_agent_count,
+        });
+
+        Ok(())
+    }
+
+    // Update the signer's profile fields
+    pub fn update_profile(
+        ctx: Context<UpdateProfile>,
+        display_name_hash: [u8; 32],
+        avatar_cid: String,
+        settings: u64,
+    ) -> Result<()> {
+        require!(
+            avatar_cid.len() <= MAX_AVATAR_CID_LEN,
+            ProfileError::AvatarCidTooLong
+        );
+
+        let profile = &mut ctx.accounts.profile;
+        profile.display_name_hash = display_name_hash;
+        profile.avatar_cid = avatar_cid;
+        profile.settings = settings;
+        profile.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(ProfileUpdated {
+            wallet: profile.wallet,
+            display_name_hash,
+            settings,
+        });
+
+        Ok(())
+    }
+
+    // Close the signer's profile and reclaim its rent
+    pub fn close_profile(_ctx: Context<CloseProfile>) -> Result<()> {
+        Ok(())
+    }
+
+    // Link a `.sol` domain to the signer's profile. `domain` is the name
+    // without the `.sol` suffix; the name account is derived from it and
+    // must be owned by the signer in the name service.
+    pub fn link_sns_domain(ctx: Context<LinkSnsDomain>, domain: String) -> Result<()> {
+        require!(
+            !domain.is_empty() && domain.len() <= MAX_SNS_DOMAIN_LEN,
+            ProfileError::InvalidSnsDomain
+        );
+        let wallet = ctx.accounts.wallet.key();
+        let domain_hash = sns_hashed_name(&domain);
+        let (name_account, _) = sns_name_account(&domain_hash);
+        require_keys_eq!(
+            ctx.accounts.name_account.key(),
+            name_account,
+            ProfileError::InvalidSnsDomain
+        );
+        require!(
+            sns_domain_owner(&ctx.accounts.name_account)? == Some(wallet),
+            ProfileError::SnsDomainNotOwned
+        );
+
+        let profile = &mut ctx.accounts.profile;
+        profile.sns_domain_hash = domain_hash;
+        profile.sns_name_account = name_account;
+        profile.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(SnsDomainLinked {
+            wallet,
+            domain: format!("{}.sol", domain),
+            domain_hash,
+            name_account,
+        });
+
+        Ok(())
+    }
+
+    // Remove the linked domain from the signer's profile
+    pub fn unlink_sns_domain(ctx: Context<UnlinkSnsDomain>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        require!(
+            profile.sns_name_account != Pubkey::default(),
+            ProfileError::NoSnsDomain
+        );
+        clear_sns_domain(profile)
+    }
+
+    // Remove a linked domain its wallet no longer owns. Anyone can call this
+    // so profiles don't keep displaying domains that were sold or expired.
+    pub fn clear_stale_sns_domain(ctx: Context<ClearStaleSnsDomain>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
+        require!(
+            sns_domain_owner(&ctx.accounts.name_account)? != Some(profile.wallet),
+            ProfileError::SnsDomainStillOwned
+        );
+        clear_sns_domain(profile)
+    }
+
+    // Registration hook invoked by the registry's `register_agent`
+    pub fn on_agent_registered(ctx: Context<OnAgentRegistered>, owner: Pubkey) -> Result<()> {
+        adjust_linked_agents(&ctx.accounts.profile, owner, true)
+    }
+
+    // Transfer hook invoked by the registry's `transfer_ownership`
+    pub fn on_agent_transferred(
+        ctx: Context<OnAgentTransferred>,
+        previous_owner: Pubkey,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        adjust_linked_agents(&ctx.accounts.previous_profile, previous_owner, false)?;
+        adjust_linked_agents(&ctx.accounts.new_profile, new_owner, true)
+    }
+
+    // Close hook invoked by the registry's `close_agent`
+    pub fn on_agent_closed(ctx: Context<OnAgentClosed>, owner: Pubkey) -> Result<()> {
+        adjust_linked_agents(&ctx.accounts.profile, owner, false)
+    }
+}
+
+// Account structures
+// Human identity for a wallet, separate from the agents it owns
+#[account]
+pub struct UserProfile {
+    pub wallet: Pubkey,
+    pub display_name_hash: [u8; 32],  // Display name is kept off-chain
+    pub avatar_cid: String,
+    pub settings: u64,  // Front-end preference bits
+    pub linked_agent_count: u32,
+    pub sns_domain_hash: [u8; 32],  // Hashed name of the linked .sol domain, zeroed when none
+    pub sns_name_account: Pubkey,   // Pubkey::default() when no domain is linked
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct CreateProfile<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + std::mem::size_of::<UserProfile>() + MAX_AVATAR_CID_LEN,
+        seeds = [b"user_profile", wallet.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    pub wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, UserProfile>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProfile<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        close = wallet,
+        seeds = [b"user_profile", wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, UserProfile>,
+}
+
+#[derive(Accounts)]
+pub struct LinkSnsDomain<'info> {
+    pub wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    /// CHECK: the domain's name registry account; derivation and owner are checked in the handler
+    pub name_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkSnsDomain<'info> {
+    pub wallet: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", wallet.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, UserProfile>,
+}
+
+#[derive(Accounts)]
+pub struct ClearStaleSnsDomain<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_profile", profile.wallet.as_ref()],
+        bump = profile.bump,
+        constraint = profile.sns_name_account != Pubkey::default() @ ProfileError::NoSnsDomain,
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    /// CHECK: the linked name registry account, read in `sns_domain_owner`
+    #[account(address = profile.sns_name_account)]
+    pub name_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OnAgentRegistered<'info> {
+    #[account(
+        seeds = [PROFILE_HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = agent_registry::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: the owner's UserProfile, if any; checked in `adjust_linked_agents`
+    #[account(mut)]
+    pub profile: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OnAgentTransferred<'info> {
+    #[account(
+        seeds = [PROFILE_HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = agent_registry::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: the previous owner's UserProfile, if any; checked in `adjust_linked_agents`
+    #[account(mut)]
+    pub previous_profile: UncheckedAccount<'info>,
+    /// CHECK: the new owner's UserProfile, if any; checked in `adjust_linked_agents`
+    #[account(mut)]
+    pub new_profile: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OnAgentClosed<'info> {
+    #[account(
+        seeds = [PROFILE_HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = agent_registry::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: the owner's UserProfile, if any; checked in `adjust_linked_agents`
+    #[account(mut)]
+    pub profile: UncheckedAccount<'info>,
+}
+
+// Events
+#[event]
+pub struct ProfileCreated {
+    pub wallet: Pubkey,
+    pub display_name_hash: [u8; 32],
+    pub linked_agent_count: u32,
+}
+
+#[event]
+pub struct ProfileUpdated {
+    pub wallet: Pubkey,
+    pub display_name_hash: [u8; 32],
+    pub settings: u64,
+}
+
+#[event]
+pub struct LinkedAgentsChanged {
+    pub wallet: Pubkey,
+    pub linked_agent_count: u32,
+}
+
+#[event]
+pub struct SnsDomainLinked {
+    pub wallet: Pubkey,
+    pub domain: String,
+    pub domain_hash: [u8; 32],
+    pub name_account: Pubkey,
+}
+
+#[event]
+pub struct SnsDomainUnlinked {
+    pub wallet: Pubkey,
+    pub domain_hash: [u8; 32],
+}
+
+// Custom errors
+#[error_code]
+pub enum ProfileError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Avatar CID is too long")]
+    AvatarCidTooLong,
+    #[msg("Agent is listed more than once")]
+    DuplicateAgent,
+    #[msg("Profile does not belong to this wallet")]
+    ProfileMismatch,
+    #[msg("Domain name is invalid or does not match the name account")]
+    InvalidSnsDomain,
+    #[msg("Wallet does not own this .sol domain")]
+    SnsDomainNotOwned,
+    #[msg("Wallet still owns this .sol domain")]
+    SnsDomainStillOwned,
+    #[msg("No .sol domain is linked")]
+    NoSnsDomain,
+}
+
+// Helper functions
+// Count an agent in or out of a wallet's profile. Wallets without a profile
+// are skipped so the registry hooks never block agent operations.
+fn adjust_linked_agents(profile_info: &AccountInfo, wallet: Pubkey, linked: bool) -> Result<()> {
+    if profile_info.owner != &crate::ID || profile_info.data_is_empty() {
+        return Ok(());
+    }
+    let mut profile: Account<UserProfile> = Account::try_from(profile_info)?;
+    require_keys_eq!(profile.wallet, wallet, ProfileError::ProfileMismatch);
+
+    profile.linked_agent_count = if linked {
+        profile.linked_agent_count.checked_add(1).unwrap_or(u32::MAX)
+    } else {
+        profile.linked_agent_count.saturating_sub(1)
+    };
+
+    emit!(LinkedAgentsChanged {
+        wallet,
+        linked_agent_count: profile.linked_agent_count,
+    });
+
+    profile.exit(&crate::ID)
+}
+
+// Hash a `.sol` name the way the name service derives its accounts
+fn sns_hashed_name(domain: &str) -> [u8; 32] {
+    hashv(&[SNS_HASH_PREFIX.as_bytes(), domain.as_bytes()]).to_bytes()
+}
+
+fn sns_name_account(hashed_name: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            hashed_name.as_ref(),
+            Pubkey::default().as_ref(),  // No name class
+            SOL_TLD_AUTHORITY.as_ref(),
+        ],
+        &SNS_PROGRAM_ID,
+    )
+}
+
+// Owner recorded in a `.sol` name registry header, or None when the account
+// isn't a live `.sol` name. The header is parent (32), owner (32), class (32).
+fn sns_domain_owner(name_account: &AccountInfo) -> Result<Option<Pubkey>> {
+    if name_account.owner != &SNS_PROGRAM_ID || name_account.data_len() < 96 {
+        return Ok(None);
+    }
+    let data = name_account.try_borrow_data()?;
+    let parent = Pubkey::try_from(&data[0..32]).unwrap();
+    if parent != SOL_TLD_AUTHORITY {
+        return Ok(None);
+    }
+    Ok(Some(Pubkey::try_from(&data[32..64]).unwrap()))
+}
+
+fn clear_sns_domain(profile: &mut Account<UserProfile>) -> Result<()> {
+    let domain_hash = profile.sns_domain_hash;
+    profile.sns_domain_hash = [0; 32];
+    profile.sns_name_account = Pubkey::default();
+    profile.updated_at = Clock::get()?.unix_timestamp;
+
+    emit!(SnsDomainUnlinked {
+        wallet: profile.wallet,
+        domain_hash,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,524 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
+use agent_registry_cpi::{AgentData, QUEST_HOOK_AUTHORITY_SEED};
+use relationship_cpi::{InteractionHookPayload, HOOK_AUTHORITY_SEED};
+
+declare_id!("Qu3stsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+#[program]
+pub mod quests {
+    use super::*;
+
+    // Create the quests config. Rewards are minted by the reward authority
+    // PDA, which must be the Influence mint's mint authority.
+    pub fn initialize_quests(ctx: Context<InitializeQuests>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.quest_count = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+        config.reward_authority_bump = *ctx.bumps.get("reward_authority").unwrap();
+
+        Ok(())
+    }
+
+    // Publish a quest (admin)
+    pub fn publish_quest(
+        ctx: Context<PublishQuest>,
+        objective: QuestObjective,
+        target_count: u32,
+        reward: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        require!(target_count > 0 && reward > 0, QuestError::InvalidQuest);
+        require!(deadline > Clock::get()?.unix_timestamp, QuestError::InvalidQuest);
+
+        let config = &mut ctx.accounts.config;
+        let quest = &mut ctx.accounts.quest;
+        quest.quest_id = config.quest_count;
+        quest.objective = objective.clone();
+        quest.target_count = target_count;
+        quest.reward = reward;
+        quest.deadline = deadline;
+        quest.active = true;
+        quest.completions = 0;
+        quest.bump = *ctx.bumps.get("quest").unwrap();
+
+        config.quest_count = config.quest_count.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(QuestPublished {
+            quest: quest.key(),
+            quest_id: quest.quest_id,
+            objective,
+            target_count,
+            reward,
+            deadline,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw or reopen a quest (admin). Quests already started can still
+    // be completed and claimed.
+    pub fn set_quest_active(ctx: Context<UpdateQuest>, active: bool) -> Result<()> {
+        let quest = &mut ctx.accounts.quest;
+        quest.active = active;
+
+        emit!(QuestStatusChanged {
+            quest: quest.key(),
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Start a quest for an agent. An agent works on one quest at a time.
+    pub fn start_quest(ctx: Context<StartQuest>) -> Result<()> {
+        let quest = &ctx.accounts.quest;
+        let now = Clock::get()?.unix_timestamp;
+        require!(quest.active, QuestError::QuestInactive);
+        require!(now < quest.deadline, QuestError::QuestExpired);
+
+        let progress = &mut ctx.accounts.progress;
+        progress.owner = ctx.accounts.owner.key();
+        progress.agent = ctx.accounts.agent_data.key();
+        progress.quest = quest.key();
+        progress.objective = quest.objective.clone();
+        progress.target_count = quest.target_count;
+        progress.deadline = quest.deadline;
+        progress.count = 0;
+        progress.started_at = now;
+        progress.completed = false;
+        progress.bump = *ctx.bumps.get("progress").unwrap();
+
+        emit!(QuestStarted {
+            quest: progress.quest,
+            agent: progress.agent,
+            owner: progress.owner,
+        });
+
+        Ok(())
+    }
+
+    // Drop the agent's current quest, returning the progress account's rent
+    pub fn abandon_quest(ctx: Context<AbandonQuest>) -> Result<()> {
+        emit!(QuestAbandoned {
+            quest: ctx.accounts.progress.quest,
+            agent: ctx.accounts.progress.agent,
+        });
+
+        Ok(())
+    }
+
+    // Interaction hook registered with the relationship program. Extra hook
+    // accounts are the quest progress PDAs of agent_one and agent_two, which
+    // may be uninitialized when an agent has no active quest.
+    pub fn on_interaction(
+        ctx: Context<OnInteraction>,
+        payload: InteractionHookPayload,
+    ) -> Result<()> {
+        let event = QuestEvent::Interaction(payload.interaction_type as u8);
+        advance_progress(&ctx.accounts.progress_one, payload.agent_one, &event, payload.timestamp)?;
+        advance_progress(&ctx.accounts.progress_two, payload.agent_two, &event, payload.timestamp)?;
+
+        Ok(())
+    }
+
+    // Match hook invoked by the registry's `accept_match`
+    pub fn on_match(ctx: Context<OnMatch>, agent_one: Pubkey, agent_two: Pubkey) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        advance_progress(&ctx.accounts.progress_one, agent_one, &QuestEvent::Match, now)?;
+        advance_progress(&ctx.accounts.progress_two, agent_two, &QuestEvent::Match, now)?;
+
+        Ok(())
+    }
+
+    // Mint the reward for a completed quest and close the progress account.
+    // Each agent can claim a quest once.
+    pub fn claim_quest_reward(ctx: Context<ClaimQuestReward>) -> Result<()> {
+        let progress = &ctx.accounts.progress;
+        let quest = &mut ctx.accounts.quest;
+        require!(progress.completed, QuestError::QuestNotCompleted);
+
+        let reward_seeds: &[&[u8]] = &[
+            b"reward_authority",
+            &[ctx.accounts.config.reward_authority_bump],
+        ];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.influence_mint.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_authority.to_account_info(),
+                },
+                &[reward_seeds],
+            ),
+            quest.reward,
+        )?;
+        quest.completions = quest.completions.checked_add(1).unwrap_or(u32::MAX);
+
+        let completion = &mut ctx.accounts.completion;
+        completion.quest = quest.key();
+        completion.agent = progress.agent;
+        completion.claimed_at = Clock::get()?.unix_timestamp;
+        completion.bump = *ctx.bumps.get("completion").unwrap();
+
+        emit!(QuestRewardClaimed {
+            quest: quest.key(),
+            agent: progress.agent,
+            owner: progress.owner,
+            reward: quest.reward,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct QuestsConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub quest_count: u64,
+    pub bump: u8,
+    pub reward_authority_bump: u8,
+}
+
+#[account]
+pub struct Quest {
+    pub quest_id: u64,
+    pub objective: QuestObjective,
+    pub target_count: u32,
+    pub reward: u64,  // Influence minted on completion
+    pub deadline: i64,
+    pub active: bool,
+    pub completions: u32,
+    pub bump: u8,
+}
+
+// An agent's progress on its current quest
+#[account]
+pub struct QuestProgress {
+    pub owner: Pubkey,
+    pub agent: Pubkey,
+    pub quest: Pubkey,
+    pub objective: QuestObjective,  // Copied from the quest so hooks need not load it
+    pub target_count: u32,
+    pub deadline: i64,
+    pub count: u32,
+    pub started_at: i64,
+    pub completed: bool,
+    pub bump: u8,
+}
+
+// Marks a claimed quest so the same agent cannot claim it twice
+#[account]
+pub struct QuestCompletion {
+    pub quest: Pubkey,
+    pub agent: Pubkey,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeQuests<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<QuestsConfig>(),
+        seeds = [b"quests_config"],
+        bump
+    )]
+    pub config: Account<'info, QuestsConfig>,
+
+    /// CHECK: PDA that mints quest rewards
+    #[account(seeds = [b"reward_authority"], bump)]
+    pub reward_authority: UncheckedAccount<'info>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PublishQuest<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"quests_config"],
+        bump = config.bump,
+        has_one = admin @ QuestError::Unauthorized,
+    )]
+    pub config: Account<'info, QuestsConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Quest>(),
+        seeds = [b"quest", config.quest_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub quest: Account<'info, Quest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateQuest<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"quests_config"],
+        bump = config.bump,
+        has_one = admin @ QuestError::Unauthorized,
+    )]
+    pub config: Account<'info, QuestsConfig>,
+
+    #[account(mut)]
+    pub quest: Account<'info, Quest>,
+}
+
+#[derive(Accounts)]
+pub struct StartQuest<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub quest: Account<'info, Quest>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ QuestError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<QuestProgress>(),
+        seeds = [b"quest_progress", agent_data.key().as_ref()],
+        bump
+    )]
+    pub progress: Account<'info, QuestProgress>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AbandonQuest<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ QuestError::Unauthorized,
+        close = owner,
+    )]
+    pub progress: Account<'info, QuestProgress>,
+}
+
+#[derive(Accounts)]
+pub struct OnInteraction<'info> {
+    #[account(
+        seeds = [HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = relationship_cpi::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: described by the payload
+    pub relationship: UncheckedAccount<'info>,
+    /// CHECK: described by the payload
+    pub interaction: UncheckedAccount<'info>,
+
+    /// CHECK: agent_one's QuestProgress, if any; checked in `advance_progress`
+    #[account(mut)]
+    pub progress_one: UncheckedAccount<'info>,
+    /// CHECK: agent_two's QuestProgress, if any; checked in `advance_progress`
+    #[account(mut)]
+    pub progress_two: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OnMatch<'info> {
+    #[account(
+        seeds = [QUEST_HOOK_AUTHORITY_SEED],
+        bump,
+        seeds::program = agent_registry_cpi::ID
+    )]
+    pub hook_authority: Signer<'info>,
+
+    /// CHECK: agent_one's QuestProgress, if any; checked in `advance_progress`
+    #[account(mut)]
+    pub progress_one: UncheckedAccount<'info>,
+    /// CHECK: agent_two's QuestProgress, if any; checked in `advance_progress`
+    #[account(mut)]
+    pub progress_two: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimQuestReward<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"quests_config"], bump = config.bump)]
+    pub config: Account<'info, QuestsConfig>,
+
+    #[account(mut, address = progress.quest)]
+    pub quest: Account<'info, Quest>,
+
+    #[account(
+        mut,
+        has_one = owner @ QuestError::Unauthorized,
+        close = owner,
+    )]
+    pub progress: Account<'info, QuestProgress>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<QuestCompletion>(),
+        seeds = [b"quest_completion", quest.key().as_ref(), progress.agent.as_ref()],
+        bump
+    )]
+    pub completion: Account<'info, QuestCompletion>,
+
+    #[account(mut, address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ QuestError::Unauthorized,
+        constraint = owner_token_account.mint == config.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that mints quest rewards
+    #[account(seeds = [b"reward_authority"], bump = config.reward_authority_bump)]
+    pub reward_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum QuestObjective {
+    AnyInteraction,
+    Interaction { interaction_type: u8 },  // Borsh variant index of relationship::InteractionType
+    Match,
+}
+
+// Activity reported by a hook
+pub enum QuestEvent {
+    Interaction(u8),
+    Match,
+}
+
+impl QuestObjective {
+    pub fn matches(&self, event: &QuestEvent) -> bool {
+        match (self, event) {
+            (QuestObjective::AnyInteraction, QuestEvent::Interaction(_)) => true,
+            (QuestObjective::Interaction { interaction_type }, QuestEvent::Interaction(kind)) => {
+                interaction_type == kind
+            }
+            (QuestObjective::Match, QuestEvent::Match) => true,
+            _ => false,
+        }
+    }
+}
+
+// Events
+#[event]
+pub struct QuestPublished {
+    pub quest: Pubkey,
+    pub quest_id: u64,
+    pub objective: QuestObjective,
+    pub target_count: u32,
+    pub reward: u64,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct QuestStatusChanged {
+    pub quest: Pubkey,
+    pub active: bool,
+}
+
+#[event]
+pub struct QuestStarted {
+    pub quest: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct QuestAbandoned {
+    pub quest: Pubkey,
+    pub agent: Pubkey,
+}
+
+#[event]
+pub struct QuestCompleted {
+    pub quest: Pubkey,
+    pub agent: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QuestRewardClaimed {
+    pub quest: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub reward: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum QuestError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Quest needs a target, a reward and a future deadline")]
+    InvalidQuest,
+    #[msg("Quest is not active")]
+    QuestInactive,
+    #[msg("Quest deadline has passed")]
+    QuestExpired,
+    #[msg("Quest progress account does not belong to the agent")]
+    ProgressMismatch,
+    #[msg("Quest has not been completed")]
+    QuestNotCompleted,
+}
+
+// Helper functions
+// Count an event towards an agent's active quest. Accounts this program does
+// not own are agents without a quest and are skipped.
+fn advance_progress(
+    progress_info: &AccountInfo,
+    agent: Pubkey,
+    event: &QuestEvent,
+    now: i64,
+) -> Result<()> {
+    if progress_info.owner != &crate::ID || progress_info.data_is_empty() {
+        return Ok(());
+    }
+    let mut progress: Account<QuestProgress> = Account::try_from(progress_info)?;
+    require!(progress.agent == agent, QuestError::ProgressMismatch);
+    if progress.completed || now > progress.deadline || !progress.objective.matches(event) {
+        return Ok(());
+    }
+
+    progress.count = progress.count.checked_add(1).unwrap_or(u32::MAX);
+    if progress.count >= progress.target_count {
+        progress.completed = true;
+        emit!(QuestCompleted {
+            quest: progress.quest,
+            agent,
+            timestamp: now,
+        });
+    }
+    progress.exit(&crate::ID)
+}
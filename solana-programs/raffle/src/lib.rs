@@ -0,0 +1,844 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+use agent_registry::AgentData;
+use items::program::Items;
+use items::ItemType;
+
+declare_id!("Raff1ePr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const MAX_PRIZES: usize = 5;
+
+#[program]
+pub mod raffle {
+    use super::*;
+
+    // Create the raffle config. When a VRF authority is set it must sign
+    // every draw with its randomness; otherwise recent slot hashes are used.
+    pub fn initialize_raffle(
+        ctx: Context<InitializeRaffle>,
+        ticket_price: u64,
+        epoch_duration: i64,
+        claim_window: i64,
+        vrf_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ticket_price > 0 && epoch_duration > 0 && claim_window > 0,
+            RaffleError::InvalidConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.ticket_price = ticket_price;
+        config.epoch_duration = epoch_duration;
+        config.claim_window = claim_window;
+        config.vrf_authority = vrf_authority;
+        config.current_epoch = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Update ticket pricing, timing and the VRF authority (admin). Changes
+    // apply to draws opened afterwards.
+    pub fn update_raffle_config(
+        ctx: Context<UpdateRaffleConfig>,
+        ticket_price: u64,
+        epoch_duration: i64,
+        claim_window: i64,
+        vrf_authority: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ticket_price > 0 && epoch_duration > 0 && claim_window > 0,
+            RaffleError::InvalidConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.ticket_price = ticket_price;
+        config.epoch_duration = epoch_duration;
+        config.claim_window = claim_window;
+        config.vrf_authority = vrf_authority;
+
+        Ok(())
+    }
+
+    // Open the next epoch's draw with its prize list (admin). Token prizes
+    // must be funded with `fund_prize` before the draw.
+    pub fn open_draw(ctx: Context<OpenDraw>, prizes: Vec<PrizeKind>) -> Result<()> {
+        require!(
+            !prizes.is_empty() && prizes.len() <= MAX_PRIZES,
+            RaffleError::InvalidPrizes
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &mut ctx.accounts.config;
+        let draw = &mut ctx.accounts.draw;
+        draw.epoch = config.current_epoch;
+        draw.status = DrawStatus::Open;
+        draw.ticket_price = config.ticket_price;
+        draw.opens_at = now;
+        draw.closes_at = now
+            .checked_add(config.epoch_duration)
+            .ok_or(RaffleError::ArithmeticOverflow)?;
+        draw.claim_deadline = 0;
+        draw.ticket_count = 0;
+        draw.batch_count = 0;
+        draw.randomness = [0u8; 32];
+        draw.prizes = prizes
+            .iter()
+            .map(|kind| Prize {
+                funded: !matches!(kind, PrizeKind::Tokens { .. }),
+                kind: kind.clone(),
+                winning_ticket: 0,
+                status: PrizeStatus::Pending,
+            })
+            .collect();
+        draw.bump = *ctx.bumps.get("draw").unwrap();
+        config.current_epoch = config.current_epoch.checked_add(1).unwrap_or(u64::MAX);
+
+        emit!(DrawOpened {
+            epoch: draw.epoch,
+            closes_at: draw.closes_at,
+            ticket_price: draw.ticket_price,
+            prizes,
+        });
+
+        Ok(())
+    }
+
+    // Escrow a token prize in its vault (admin)
+    pub fn fund_prize(ctx: Context<FundPrize>, prize_index: u8) -> Result<()> {
+        let draw = &mut ctx.accounts.draw;
+        require!(draw.status == DrawStatus::Open, RaffleError::InvalidStatus);
+        let prize = draw
+            .prizes
+            .get_mut(prize_index as usize)
+            .ok_or(RaffleError::InvalidPrizeIndex)?;
+        let PrizeKind::Tokens { mint, amount } = prize.kind else {
+            return err!(RaffleError::WrongPrizeKind);
+        };
+        require!(!prize.funded, RaffleError::PrizeAlreadyFunded);
+        require!(ctx.accounts.prize_mint.key() == mint, RaffleError::WrongPrizeKind);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin_token_account.to_account_info(),
+                    to: ctx.accounts.prize_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        prize.funded = true;
+
+        emit!(PrizeFunded {
+            epoch: draw.epoch,
+            prize_index,
+            mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Buy tickets in an open draw, burning their Influence price. Each
+    // purchase is a batch of consecutive ticket numbers.
+    pub fn buy_tickets(ctx: Context<BuyTickets>, count: u32) -> Result<()> {
+        require!(count > 0, RaffleError::InvalidTicketCount);
+        let now = Clock::get()?.unix_timestamp;
+        let draw = &mut ctx.accounts.draw;
+        require!(
+            draw.status == DrawStatus::Open && now < draw.closes_at,
+            RaffleError::DrawClosed
+        );
+
+        let cost = draw
+            .ticket_price
+            .checked_mul(count as u64)
+            .ok_or(RaffleError::ArithmeticOverflow)?;
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.influence_mint.to_account_info(),
+                    from: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            cost,
+        )?;
+
+        let batch = &mut ctx.accounts.ticket_batch;
+        batch.draw = draw.key();
+        batch.owner = ctx.accounts.buyer.key();
+        batch.first_ticket = draw.ticket_count;
+        batch.count = count;
+        batch.bump = *ctx.bumps.get("ticket_batch").unwrap();
+
+        draw.ticket_count = draw
+            .ticket_count
+            .checked_add(count as u64)
+            .ok_or(RaffleError::ArithmeticOverflow)?;
+        draw.batch_count = draw.batch_count.checked_add(1).ok_or(RaffleError::ArithmeticOverflow)?;
+
+        emit!(TicketsPurchased {
+            epoch: draw.epoch,
+            buyer: batch.owner,
+            first_ticket: batch.first_ticket,
+            count,
+            cost,
+        });
+
+        Ok(())
+    }
+
+    // Draw the winning ticket for every prize once the epoch has closed
+    // (permissionless, but the VRF authority must sign when one is set).
+    // Winners have until the claim deadline to claim; a draw without
+    // tickets leaves every prize to expire immediately.
+    pub fn draw_winners(ctx: Context<DrawWinners>, randomness: Option<[u8; 32]>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let draw = &mut ctx.accounts.draw;
+        require!(draw.status == DrawStatus::Open, RaffleError::InvalidStatus);
+        require!(now >= draw.closes_at, RaffleError::DrawStillOpen);
+        require!(
+            draw.prizes.iter().all(|prize| prize.funded),
+            RaffleError::PrizeNotFunded
+        );
+
+        let seed = armour_randomness::resolve_seed(
+            config.vrf_authority,
+            ctx.accounts.vrf_authority.as_ref(),
+            randomness,
+            &ctx.accounts.recent_slothashes,
+        )?;
+        draw.randomness = armour_randomness::derive(&seed, &[&draw.epoch.to_le_bytes()]);
+
+        if draw.ticket_count == 0 {
+            draw.claim_deadline = now;
+        } else {
+            draw.claim_deadline = now
+                .checked_add(config.claim_window)
+                .ok_or(RaffleError::ArithmeticOverflow)?;
+            let draw_randomness = draw.randomness;
+            let ticket_count = draw.ticket_count;
+            for (index, prize) in draw.prizes.iter_mut().enumerate() {
+                let roll = armour_randomness::derive(&draw_randomness, &[&[index as u8]]);
+                prize.winning_ticket = armour_randomness::pick_index(&roll, ticket_count);
+                prize.status = PrizeStatus::Unclaimed;
+            }
+        }
+        draw.status = DrawStatus::Drawn;
+
+        emit!(WinnersDrawn {
+            epoch: draw.epoch,
+            ticket_count: draw.ticket_count,
+            winning_tickets: draw.prizes.iter().map(|prize| prize.winning_ticket).collect(),
+            claim_deadline: draw.claim_deadline,
+        });
+
+        Ok(())
+    }
+
+    // Claim a token prize with the ticket batch holding the winning ticket
+    pub fn claim_token_prize(ctx: Context<ClaimTokenPrize>, prize_index: u8) -> Result<()> {
+        let draw = &mut ctx.accounts.draw;
+        let kind = take_prize(draw, &ctx.accounts.ticket_batch, prize_index)?;
+        let PrizeKind::Tokens { mint, amount } = kind else {
+            return err!(RaffleError::WrongPrizeKind);
+        };
+        require!(ctx.accounts.prize_vault.mint == mint, RaffleError::WrongPrizeKind);
+
+        release_prize_tokens(
+            draw,
+            amount,
+            &ctx.accounts.prize_vault,
+            &ctx.accounts.winner_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(PrizeClaimed {
+            epoch: draw.epoch,
+            prize_index,
+            winner: ctx.accounts.winner.key(),
+            kind,
+        });
+
+        Ok(())
+    }
+
+    // Claim an item prize into one of the winner's agents' inventories
+    pub fn claim_item_prize(ctx: Context<ClaimItemPrize>, prize_index: u8) -> Result<()> {
+        let draw = &mut ctx.accounts.draw;
+        let kind = take_prize(draw, &ctx.accounts.ticket_batch, prize_index)?;
+        let PrizeKind::Item { item_id, quantity } = kind else {
+            return err!(RaffleError::WrongPrizeKind);
+        };
+        require!(ctx.accounts.item_type.item_id == item_id, RaffleError::WrongPrizeKind);
+
+        let authority_seeds: &[&[u8]] = &[
+            b"raffle_authority",
+            &[*ctx.bumps.get("raffle_authority").unwrap()],
+        ];
+        items::cpi::award_items(
+            CpiContext::new_with_signer(
+                ctx.accounts.items_program.to_account_info(),
+                items::cpi::accounts::AwardItems {
+                    raffle_authority: ctx.accounts.raffle_authority.to_account_info(),
+                    payer: ctx.accounts.winner.to_account_info(),
+                    item_type: ctx.accounts.item_type.to_account_info(),
+                    agent_data: ctx.accounts.agent_data.to_account_info(),
+                    inventory: ctx.accounts.inventory.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            quantity,
+        )?;
+
+        emit!(PrizeClaimed {
+            epoch: draw.epoch,
+            prize_index,
+            winner: ctx.accounts.winner.key(),
+            kind,
+        });
+
+        Ok(())
+    }
+
+    // Claim a premium agent slot, credited to the winner's slot voucher
+    pub fn claim_slot_prize(ctx: Context<ClaimSlotPrize>, prize_index: u8) -> Result<()> {
+        let draw = &mut ctx.accounts.draw;
+        let kind = take_prize(draw, &ctx.accounts.ticket_batch, prize_index)?;
+        require!(kind == PrizeKind::PremiumAgentSlot, RaffleError::WrongPrizeKind);
+
+        let voucher = &mut ctx.accounts.slot_voucher;
+        voucher.owner = ctx.accounts.winner.key();
+        voucher.slots = voucher.slots.checked_add(1).unwrap_or(u16::MAX);
+        voucher.bump = *ctx.bumps.get("slot_voucher").unwrap();
+
+        emit!(PrizeClaimed {
+            epoch: draw.epoch,
+            prize_index,
+            winner: voucher.owner,
+            kind,
+        });
+
+        Ok(())
+    }
+
+    // Expire a prize left unclaimed past the claim deadline (admin). Token
+    // prizes are returned to the admin; the vault accounts are only needed
+    // for them.
+    pub fn expire_prize(ctx: Context<ExpirePrize>, prize_index: u8) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let draw = &mut ctx.accounts.draw;
+        require!(draw.status == DrawStatus::Drawn, RaffleError::InvalidStatus);
+        require!(now > draw.claim_deadline || draw.ticket_count == 0, RaffleError::ClaimWindowOpen);
+
+        let prize = draw
+            .prizes
+            .get_mut(prize_index as usize)
+            .ok_or(RaffleError::InvalidPrizeIndex)?;
+        require!(
+            prize.status != PrizeStatus::Claimed && prize.status != PrizeStatus::Expired,
+            RaffleError::PrizeAlreadyClaimed
+        );
+        prize.status = PrizeStatus::Expired;
+        let kind = prize.kind.clone();
+
+        if let PrizeKind::Tokens { mint, amount } = kind {
+            let (Some(prize_vault), Some(admin_token_account), Some(token_program)) = (
+                &ctx.accounts.prize_vault,
+                &ctx.accounts.admin_token_account,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(RaffleError::MissingTokenAccounts);
+            };
+            require!(prize_vault.mint == mint, RaffleError::WrongPrizeKind);
+            release_prize_tokens(draw, amount, prize_vault, admin_token_account, token_program)?;
+        }
+
+        emit!(PrizeExpired {
+            epoch: draw.epoch,
+            prize_index,
+            kind,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct RaffleConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub ticket_price: u64,    // Influence burned per ticket
+    pub epoch_duration: i64,  // Seconds a draw accepts tickets
+    pub claim_window: i64,    // Seconds winners have to claim after the draw
+    pub vrf_authority: Pubkey,  // Pubkey::default() to use slot hashes instead of VRF
+    pub current_epoch: u64,   // Epoch of the next draw to open
+    pub bump: u8,
+}
+
+#[account]
+pub struct Draw {
+    pub epoch: u64,
+    pub status: DrawStatus,
+    pub ticket_price: u64,
+    pub opens_at: i64,
+    pub closes_at: i64,
+    pub claim_deadline: i64,
+    pub ticket_count: u64,
+    pub batch_count: u32,
+    pub randomness: [u8; 32],
+    pub prizes: Vec<Prize>,
+    pub bump: u8,
+}
+
+#[account]
+pub struct TicketBatch {
+    pub draw: Pubkey,
+    pub owner: Pubkey,
+    pub first_ticket: u64,  // Holds tickets first_ticket..first_ticket + count
+    pub count: u32,
+    pub bump: u8,
+}
+
+// Premium agent slots won in raffles, redeemable by the registry
+#[account]
+pub struct SlotVoucher {
+    pub owner: Pubkey,
+    pub slots: u16,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeRaffle<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<RaffleConfig>(),
+        seeds = [b"raffle_config"],
+        bump
+    )]
+    pub config: Account<'info, RaffleConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRaffleConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"raffle_config"],
+        bump = config.bump,
+        has_one = admin @ RaffleError::Unauthorized,
+    )]
+    pub config: Account<'info, RaffleConfig>,
+}
+
+#[derive(Accounts)]
+pub struct OpenDraw<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"raffle_config"],
+        bump = config.bump,
+        has_one = admin @ RaffleError::Unauthorized,
+    )]
+    pub config: Account<'info, RaffleConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Draw>() + MAX_PRIZES * std::mem::size_of::<Prize>(),
+        seeds = [b"draw", config.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub draw: Account<'info, Draw>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(prize_index: u8)]
+pub struct FundPrize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"raffle_config"],
+        bump = config.bump,
+        has_one = admin @ RaffleError::Unauthorized,
+    )]
+    pub config: Account<'info, RaffleConfig>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"prize_vault", draw.key().as_ref(), &[prize_index]],
+        bump,
+        token::mint = prize_mint,
+        token::authority = draw,
+    )]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    pub prize_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = admin_token_account.owner == admin.key() @ RaffleError::Unauthorized,
+        constraint = admin_token_account.mint == prize_mint.key(),
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTickets<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"raffle_config"], bump = config.bump)]
+    pub config: Account<'info, RaffleConfig>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + std::mem::size_of::<TicketBatch>(),
+        seeds = [b"tickets", draw.key().as_ref(), draw.batch_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub ticket_batch: Account<'info, TicketBatch>,
+
+    #[account(mut, address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == buyer.key() @ RaffleError::Unauthorized,
+        constraint = buyer_token_account.mint == config.influence_mint,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DrawWinners<'info> {
+    #[account(seeds = [b"raffle_config"], bump = config.bump)]
+    pub config: Account<'info, RaffleConfig>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    // Required when the config names a VRF authority
+    pub vrf_authority: Option<Signer<'info>>,
+
+    /// CHECK: slot hashes sysvar, read for fallback randomness
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(prize_index: u8)]
+pub struct ClaimTokenPrize<'info> {
+    pub winner: Signer<'info>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    #[account(has_one = draw, constraint = ticket_batch.owner == winner.key() @ RaffleError::Unauthorized)]
+    pub ticket_batch: Account<'info, TicketBatch>,
+
+    #[account(
+        mut,
+        seeds = [b"prize_vault", draw.key().as_ref(), &[prize_index]],
+        bump,
+    )]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = winner_token_account.owner == winner.key() @ RaffleError::Unauthorized,
+        constraint = winner_token_account.mint == prize_vault.mint,
+    )]
+    pub winner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimItemPrize<'info> {
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    #[account(has_one = draw, constraint = ticket_batch.owner == winner.key() @ RaffleError::Unauthorized)]
+    pub ticket_batch: Account<'info, TicketBatch>,
+
+    #[account(constraint = agent_data.owner == winner.key() @ RaffleError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(mut)]
+    pub item_type: Account<'info, ItemType>,
+
+    /// CHECK: the agent's inventory, created if needed by the items program
+    #[account(mut)]
+    pub inventory: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs item awards in the items program
+    #[account(seeds = [b"raffle_authority"], bump)]
+    pub raffle_authority: UncheckedAccount<'info>,
+
+    pub items_program: Program<'info, Items>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSlotPrize<'info> {
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    #[account(has_one = draw, constraint = ticket_batch.owner == winner.key() @ RaffleError::Unauthorized)]
+    pub ticket_batch: Account<'info, TicketBatch>,
+
+    #[account(
+        init_if_needed,
+        payer = winner,
+        space = 8 + std::mem::size_of::<SlotVoucher>(),
+        seeds = [b"slot_voucher", winner.key().as_ref()],
+        bump
+    )]
+    pub slot_voucher: Account<'info, SlotVoucher>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(prize_index: u8)]
+pub struct ExpirePrize<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"raffle_config"],
+        bump = config.bump,
+        has_one = admin @ RaffleError::Unauthorized,
+    )]
+    pub config: Account<'info, RaffleConfig>,
+
+    #[account(mut)]
+    pub draw: Account<'info, Draw>,
+
+    // Required for token prizes
+    #[account(
+        mut,
+        seeds = [b"prize_vault", draw.key().as_ref(), &[prize_index]],
+        bump,
+    )]
+    pub prize_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = admin_token_account.owner == admin.key() @ RaffleError::Unauthorized,
+    )]
+    pub admin_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum PrizeKind {
+    Tokens { mint: Pubkey, amount: u64 },  // Escrowed in the prize's vault
+    Item { item_id: u16, quantity: u32 },  // Awarded through the items program
+    PremiumAgentSlot,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum PrizeStatus {
+    Pending,    // Not drawn yet
+    Unclaimed,
+    Claimed,
+    Expired,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Prize {
+    pub kind: PrizeKind,
+    pub funded: bool,
+    pub winning_ticket: u64,
+    pub status: PrizeStatus,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum DrawStatus {
+    Open,
+    Drawn,
+}
+
+// Events
+#[event]
+pub struct DrawOpened {
+    pub epoch: u64,
+    pub closes_at: i64,
+    pub ticket_price: u64,
+    pub prizes: Vec<PrizeKind>,
+}
+
+#[event]
+pub struct PrizeFunded {
+    pub epoch: u64,
+    pub prize_index: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TicketsPurchased {
+    pub epoch: u64,
+    pub buyer: Pubkey,
+    pub first_ticket: u64,
+    pub count: u32,
+    pub cost: u64,
+}
+
+#[event]
+pub struct WinnersDrawn {
+    pub epoch: u64,
+    pub ticket_count: u64,
+    pub winning_tickets: Vec<u64>,
+    pub claim_deadline: i64,
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub epoch: u64,
+    pub prize_index: u8,
+    pub winner: Pubkey,
+    pub kind: PrizeKind,
+}
+
+#[event]
+pub struct PrizeExpired {
+    pub epoch: u64,
+    pub prize_index: u8,
+    pub kind: PrizeKind,
+}
+
+// Custom errors
+#[error_code]
+pub enum RaffleError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Ticket price, epoch duration and claim window must be positive")]
+    InvalidConfig,
+    #[msg("A draw needs between one and five prizes")]
+    InvalidPrizes,
+    #[msg("Prize index is out of range")]
+    InvalidPrizeIndex,
+    #[msg("Prize is not of the expected kind")]
+    WrongPrizeKind,
+    #[msg("Prize has already been funded")]
+    PrizeAlreadyFunded,
+    #[msg("Every token prize must be funded before the draw")]
+    PrizeNotFunded,
+    #[msg("Ticket count must be greater than zero")]
+    InvalidTicketCount,
+    #[msg("Draw is not in the required status")]
+    InvalidStatus,
+    #[msg("Draw is no longer selling tickets")]
+    DrawClosed,
+    #[msg("Draw is still selling tickets")]
+    DrawStillOpen,
+    #[msg("Ticket batch does not hold the winning ticket")]
+    NotWinningTicket,
+    #[msg("Prize has already been claimed or expired")]
+    PrizeAlreadyClaimed,
+    #[msg("Claim window has closed")]
+    ClaimWindowClosed,
+    #[msg("Claim window is still open")]
+    ClaimWindowOpen,
+    #[msg("Token accounts are required for token prizes")]
+    MissingTokenAccounts,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// Check the batch holds the prize's winning ticket within the claim window
+// and mark the prize claimed, returning its kind
+fn take_prize(draw: &mut Draw, batch: &TicketBatch, prize_index: u8) -> Result<PrizeKind> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(draw.status == DrawStatus::Drawn, RaffleError::InvalidStatus);
+    require!(now <= draw.claim_deadline, RaffleError::ClaimWindowClosed);
+
+    let prize = draw
+        .prizes
+        .get_mut(prize_index as usize)
+        .ok_or(RaffleError::InvalidPrizeIndex)?;
+    require!(prize.status == PrizeStatus::Unclaimed, RaffleError::PrizeAlreadyClaimed);
+    require!(
+        prize.winning_ticket >= batch.first_ticket
+            && prize.winning_ticket < batch.first_ticket + batch.count as u64,
+        RaffleError::NotWinningTicket
+    );
+    prize.status = PrizeStatus::Claimed;
+
+    Ok(prize.kind.clone())
+}
+
+// Transfer escrowed prize tokens out of a vault, signing as the draw PDA
+fn release_prize_tokens<'info>(
+    draw: &Account<'info, Draw>,
+    amount: u64,
+    prize_vault: &Account<'info, TokenAccount>,
+    recipient: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let epoch_bytes = draw.epoch.to_le_bytes();
+    let draw_seeds: &[&[u8]] = &[b"draw", epoch_bytes.as_ref(), &[draw.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: prize_vault.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: draw.to_account_info(),
+            },
+            &[draw_seeds],
+        ),
+        amount,
+    )
+}
@@ -0,0 +1,325 @@
+use anchor_lang::prelude::*;
+use sessions::SessionKey;
+
+declare_id!("RateL1m1terPr0graMxXxXxXxXxXxXxXxXxXxXxXxX");
+
+pub const RATE_LIMITER_CONFIG_SEED: &[u8] = b"rate_limiter";
+pub const WALLET_QUOTA_SEED: &[u8] = b"quota";
+const ACTION_COUNT: usize = 4;
+
+#[program]
+pub mod rate_limiter {
+    use super::*;
+
+    // Create the config with every limit disabled
+    pub fn initialize_rate_limiter(ctx: Context<InitializeRateLimiter>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.limits = [ActionLimit::default(); ACTION_COUNT];
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Set how many times a wallet may perform `action` per sliding window
+    // (authority). A zero window or maximum disables the limit.
+    pub fn set_action_limit(
+        ctx: Context<UpdateRateLimiter>,
+        action: RateLimitedAction,
+        limit: ActionLimit,
+    ) -> Result<()> {
+        require!(limit.window >= 0, RateLimiterError::InvalidLimit);
+        ctx.accounts.config.limits[action as usize] = limit;
+
+        emit!(ActionLimitUpdated {
+            action,
+            window: limit.window,
+            max_actions: limit.max_actions,
+        });
+
+        Ok(())
+    }
+
+    // Hand the limits to another key, such as the governance or admin
+    // multisig authority
+    pub fn set_rate_limiter_authority(
+        ctx: Context<UpdateRateLimiter>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+        config.authority = authority;
+
+        emit!(RateLimiterAuthorityUpdated {
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Count `count` actions against the signing wallet, failing if that
+    // exceeds its limit. Other programs CPI this before mutating state; the
+    // wallet has to sign, so nobody can spend another wallet's quota.
+    pub fn consume_quota(
+        ctx: Context<ConsumeQuota>,
+        action: RateLimitedAction,
+        count: u16,
+    ) -> Result<()> {
+        let limit = ctx.accounts.config.limits[action as usize];
+        let quota = &mut ctx.accounts.wallet_quota;
+        quota.wallet = ctx.accounts.wallet.key();
+        quota.bump = *ctx.bumps.get("wallet_quota").unwrap();
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            quota.windows[action as usize].consume(limit, count, now),
+            RateLimiterError::QuotaExceeded
+        );
+
+        Ok(())
+    }
+
+    // Count `count` actions against the owner of `session`, signed by its
+    // session key. Programs accepting session keys CPI this instead of
+    // `consume_quota`, so a session spends its owner's quota rather than a
+    // fresh one of its own.
+    pub fn consume_session_quota(
+        ctx: Context<ConsumeSessionQuota>,
+        action: RateLimitedAction,
+        count: u16,
+    ) -> Result<()> {
+        // The calling program checks the scope it needs
+        ctx.accounts.session.authorize(&ctx.accounts.session_signer.key(), 0)?;
+
+        let limit = ctx.accounts.config.limits[action as usize];
+        let quota = &mut ctx.accounts.wallet_quota;
+        quota.wallet = ctx.accounts.session.owner;
+        quota.bump = *ctx.bumps.get("wallet_quota").unwrap();
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            quota.windows[action as usize].consume(limit, count, now),
+            RateLimiterError::QuotaExceeded
+        );
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct RateLimiterConfig {
+    pub authority: Pubkey,
+    pub limits: [ActionLimit; ACTION_COUNT],  // Indexed by RateLimitedAction
+    pub bump: u8,
+}
+
+// One wallet's usage across every rate-limited action
+#[account]
+pub struct WalletQuota {
+    pub wallet: Pubkey,
+    pub windows: [ActionWindow; ACTION_COUNT],  // Indexed by RateLimitedAction
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeRateLimiter<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RateLimiterConfig>(),
+        seeds = [RATE_LIMITER_CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, RateLimiterConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRateLimiter<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [RATE_LIMITER_CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority @ RateLimiterError::Unauthorized,
+    )]
+    pub config: Account<'info, RateLimiterConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeQuota<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    #[account(seeds = [RATE_LIMITER_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, RateLimiterConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = 8 + std::mem::size_of::<WalletQuota>(),
+        seeds = [WALLET_QUOTA_SEED, wallet.key().as_ref()],
+        bump
+    )]
+    pub wallet_quota: Account<'info, WalletQuota>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConsumeSessionQuota<'info> {
+    #[account(mut)]
+    pub session_signer: Signer<'info>,
+
+    pub session: Account<'info, SessionKey>,
+
+    #[account(seeds = [RATE_LIMITER_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, RateLimiterConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = session_signer,
+        space = 8 + std::mem::size_of::<WalletQuota>(),
+        seeds = [WALLET_QUOTA_SEED, session.owner.as_ref()],
+        bump
+    )]
+    pub wallet_quota: Account<'info, WalletQuota>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RateLimitedAction {
+    Registration,  // Agent registrations
+    Match,         // Recorded agent matches
+    Interaction,   // Relationship interactions
+    Mint,          // Influence token mints
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct ActionLimit {
+    pub window: i64,       // Seconds, e.g. 86400 for daily limits
+    pub max_actions: u16,  // Per wallet per window
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct ActionWindow {
+    pub window_start: i64,
+    pub count: u16,
+    pub previous: u16,  // Count of the window before, for the sliding estimate
+}
+
+impl ActionWindow {
+    // Sliding-window counter: the previous window's count is weighted by how
+    // much of it still overlaps the trailing window. Returns false when
+    // `count` more actions would exceed the limit.
+    fn consume(&mut self, limit: ActionLimit, count: u16, now: i64) -> bool {
+        if limit.window <= 0 || limit.max_actions == 0 {
+            return true;
+        }
+        let elapsed = now.saturating_sub(self.window_start) / limit.window;
+        if elapsed > 0 {
+            self.previous = if elapsed == 1 { self.count } else { 0 };
+            self.count = 0;
+            self.window_start = self.window_start.saturating_add(elapsed.saturating_mul(limit.window));
+        }
+        let remaining = limit.window.saturating_sub(now.saturating_sub(self.window_start));
+        let estimate = (self.previous as i64) * remaining / limit.window + self.count as i64;
+        if estimate + count as i64 > limit.max_actions as i64 {
+            return false;
+        }
+        self.count = self.count.saturating_add(count);
+        true
+    }
+}
+
+// Events
+#[event]
+pub struct ActionLimitUpdated {
+    pub action: RateLimitedAction,
+    pub window: i64,
+    pub max_actions: u16,
+}
+
+#[event]
+pub struct RateLimiterAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum RateLimiterError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Rate limit windows cannot be negative")]
+    InvalidLimit,
+    #[msg("The wallet has used up its quota for this action; try again later")]
+    QuotaExceeded,
+}
+
+// Helper functions
+// Consume `count` of the wallet's quota for `action` through the rate
+// limiter, with the accounts the calling instruction received for it
+#[cfg(feature = "cpi")]
+pub fn consume<'info>(
+    rate_limiter_program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    wallet_quota: &AccountInfo<'info>,
+    wallet: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    action: RateLimitedAction,
+    count: u16,
+) -> Result<()> {
+    cpi::consume_quota(
+        CpiContext::new(
+            rate_limiter_program.clone(),
+            cpi::accounts::ConsumeQuota {
+                wallet: wallet.clone(),
+                config: config.clone(),
+                wallet_quota: wallet_quota.clone(),
+                system_program: system_program.clone(),
+            },
+        ),
+        action,
+        count,
+    )
+}
+
+// `consume` for a session key signing on behalf of the session's owner
+#[cfg(feature = "cpi")]
+#[allow(clippy::too_many_arguments)]
+pub fn consume_for_session<'info>(
+    rate_limiter_program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    wallet_quota: &AccountInfo<'info>,
+    session_signer: &AccountInfo<'info>,
+    session: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    action: RateLimitedAction,
+    count: u16,
+) -> Result<()> {
+    cpi::consume_session_quota(
+        CpiContext::new(
+            rate_limiter_program.clone(),
+            cpi::accounts::ConsumeSessionQuota {
+                session_signer: session_signer.clone(),
+                session: session.clone(),
+                config: config.clone(),
+                wallet_quota: wallet_quota.clone(),
+                system_program: system_program.clone(),
+            },
+        ),
+        action,
+        count,
+    )
+}
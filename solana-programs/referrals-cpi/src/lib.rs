@@ -0,0 +1,182 @@
+// Interface for programs and clients that report referral value events: the
+// program id and account layouts, PDA finders, and instruction builders. The
+// `cpi` feature adds the program's Anchor CPI module and `report_value_event`.
+// A reporting program has its `pda::find_reporter_address` added with
+// `add_reporter` and signs its reports with it.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::get_associated_token_address;
+
+pub use referrals::{
+    accounts, id, instruction, program, Referral, ReferralError, ReferralsConfig, ReferrerRewards,
+    ValueEvent, ID, REFERRAL_REPORTER_SEED,
+};
+
+#[cfg(feature = "cpi")]
+pub use referrals::cpi;
+
+// A referee and the wallet that referred them, as read from their `Referral`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReferralEdge {
+    pub referee: Pubkey,
+    pub referrer: Pubkey,
+}
+
+impl ReferralEdge {
+    // The referee's `Referral` and the referrer's `ReferrerRewards`, which a
+    // reporting instruction takes for the referee
+    pub fn addresses(&self) -> (Pubkey, Pubkey) {
+        (
+            pda::find_referral_address(&self.referee).0,
+            pda::find_referrer_rewards_address(&self.referrer).0,
+        )
+    }
+}
+
+pub mod pda {
+    use super::*;
+
+    pub fn find_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"referrals_config"], &ID)
+    }
+
+    pub fn find_reward_vault_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"referral_vault"], &ID)
+    }
+
+    pub fn find_referral_address(referee: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"referral", referee.as_ref()], &ID)
+    }
+
+    pub fn find_referrer_rewards_address(referrer: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"referrer_rewards", referrer.as_ref()], &ID)
+    }
+
+    // The signer `program` reports value events with
+    pub fn find_reporter_address(program: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[REFERRAL_REPORTER_SEED], program)
+    }
+}
+
+pub mod builders {
+    use super::*;
+
+    pub fn initialize_referrals(admin: Pubkey, influence_mint: Pubkey) -> Instruction {
+        build(
+            accounts::InitializeReferrals {
+                admin,
+                config: pda::find_config_address().0,
+                reward_vault: pda::find_reward_vault_address().0,
+                influence_mint,
+                token_program: anchor_spl::token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            instruction::InitializeReferrals {},
+        )
+    }
+
+    pub fn set_commission_rule(admin: Pubkey, event: ValueEvent, reward: u64) -> Instruction {
+        build(
+            accounts::UpdateReferralsConfig {
+                admin,
+                config: pda::find_config_address().0,
+            },
+            instruction::SetCommissionRule { event, reward },
+        )
+    }
+
+    // `reporter` is usually a program's `pda::find_reporter_address`
+    pub fn add_reporter(admin: Pubkey, reporter: Pubkey) -> Instruction {
+        build(
+            accounts::UpdateReferralsConfig {
+                admin,
+                config: pda::find_config_address().0,
+            },
+            instruction::AddReporter { reporter },
+        )
+    }
+
+    // Signed by the referee
+    pub fn register_referral(referee: Pubkey, referrer: Pubkey) -> Instruction {
+        build(
+            accounts::RegisterReferral {
+                referee,
+                referral: pda::find_referral_address(&referee).0,
+                referrer_rewards: pda::find_referrer_rewards_address(&referrer).0,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterReferral { referrer },
+        )
+    }
+
+    // Pays out to the referrer's associated token account
+    pub fn claim_rewards(referrer: Pubkey, influence_mint: Pubkey) -> Instruction {
+        build(
+            accounts::ClaimRewards {
+                referrer,
+                config: pda::find_config_address().0,
+                referrer_rewards: pda::find_referrer_rewards_address(&referrer).0,
+                reward_vault: pda::find_reward_vault_address().0,
+                referrer_token_account: get_associated_token_address(&referrer, &influence_mint),
+                token_program: anchor_spl::token::ID,
+            },
+            instruction::ClaimRewards {},
+        )
+    }
+
+    // Any instruction from its Anchor account and argument structs
+    pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+}
+
+// The accounts a reporting program passes to the referrals program on
+// behalf of every referee in an instruction
+#[cfg(feature = "cpi")]
+pub struct Reporter<'a, 'info> {
+    pub reporter: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub referrals_program: &'a AccountInfo<'info>,
+}
+
+// Report `event` for `referee`, signing as `program`'s reporter PDA. A
+// wallet nobody referred has no `Referral`, so an uninitialized `referral`
+// is skipped rather than failing the reporting program's instruction.
+#[cfg(feature = "cpi")]
+pub fn report_value_event<'info>(
+    program: &Pubkey,
+    reporter: &Reporter<'_, 'info>,
+    referee: &Pubkey,
+    referral: &AccountInfo<'info>,
+    referrer_rewards: &AccountInfo<'info>,
+    event: ValueEvent,
+) -> Result<()> {
+    if referral.owner != &ID || referral.data_is_empty() {
+        return Ok(());
+    }
+    let edge = Account::<Referral>::try_from(referral)?;
+    require_keys_eq!(edge.referee, *referee, ReferralError::RefereeMismatch);
+
+    let bump = pda::find_reporter_address(program).1;
+    let signer_seeds: &[&[&[u8]]] = &[&[REFERRAL_REPORTER_SEED, &[bump]]];
+    cpi::record_value_event(
+        CpiContext::new_with_signer(
+            reporter.referrals_program.clone(),
+            cpi::accounts::RecordValueEvent {
+                reporter: reporter.reporter.clone(),
+                config: reporter.config.clone(),
+                referral: referral.clone(),
+                referrer_rewards: referrer_rewards.clone(),
+            },
+            signer_seeds,
+        ),
+        event,
+    )
+}
@@ -0,0 +1,400 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("Referra1sPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+// Seed of the PDA a reporting program signs value event reports with
+pub const REFERRAL_REPORTER_SEED: &[u8] = b"referral_reporter";
+
+const MAX_REPORTERS: usize = 8;
+const VALUE_EVENT_COUNT: usize = 3;
+
+#[program]
+pub mod referrals {
+    use super::*;
+
+    // Create the referrals config and its reward vault. Rewards are paid
+    // from the vault, which anyone can fund with a plain token transfer.
+    pub fn initialize_referrals(ctx: Context<InitializeReferrals>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.reward_vault = ctx.accounts.reward_vault.key();
+        config.rewards = [0; VALUE_EVENT_COUNT];
+        config.reporters = Vec::new();
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Set the referrer's reward for a value event; zero disables it (admin)
+    pub fn set_commission_rule(
+        ctx: Context<UpdateReferralsConfig>,
+        event: ValueEvent,
+        reward: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.rewards[event.index()] = reward;
+
+        emit!(CommissionRuleUpdated { event, reward });
+
+        Ok(())
+    }
+
+    // Allow a signer to report value events (admin). Programs register a
+    // PDA of their own and sign reports with it through CPI.
+    pub fn add_reporter(ctx: Context<UpdateReferralsConfig>, reporter: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            !config.reporters.contains(&reporter),
+            ReferralError::ReporterAlreadyAdded
+        );
+        require!(
+            config.reporters.len() < MAX_REPORTERS,
+            ReferralError::TooManyReporters
+        );
+        config.reporters.push(reporter);
+
+        emit!(ReporterUpdated {
+            reporter,
+            allowed: true,
+        });
+
+        Ok(())
+    }
+
+    // Revoke a reporter (admin)
+    pub fn remove_reporter(ctx: Context<UpdateReferralsConfig>, reporter: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let index = config
+            .reporters
+            .iter()
+            .position(|r| *r == reporter)
+            .ok_or(ReferralError::ReporterNotFound)?;
+        config.reporters.remove(index);
+
+        emit!(ReporterUpdated {
+            reporter,
+            allowed: false,
+        });
+
+        Ok(())
+    }
+
+    // Record who referred the signer. Each wallet can be referred once.
+    pub fn register_referral(ctx: Context<RegisterReferral>, referrer: Pubkey) -> Result<()> {
+        let referee = ctx.accounts.referee.key();
+        require!(referrer != referee, ReferralError::SelfReferral);
+
+        let now = Clock::get()?.unix_timestamp;
+        let edge = &mut ctx.accounts.referral;
+        edge.referee = referee;
+        edge.referrer = referrer;
+        edge.created_at = now;
+        edge.rewarded_events = 0;
+        edge.bump = *ctx.bumps.get("referral").unwrap();
+
+        let balance = &mut ctx.accounts.referrer_rewards;
+        balance.referrer = referrer;
+        balance.referral_count = balance.referral_count.checked_add(1).unwrap_or(u32::MAX);
+        balance.bump = *ctx.bumps.get("referrer_rewards").unwrap();
+
+        emit!(ReferralRegistered {
+            referrer,
+            referee,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Report a value event for a referred wallet (allowed reporters only).
+    // The referrer is credited the event's reward the first time each event
+    // is reported; repeats are ignored so callers don't need to track them.
+    pub fn record_value_event(ctx: Context<RecordValueEvent>, event: ValueEvent) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            config.reporters.contains(&ctx.accounts.reporter.key()),
+            ReferralError::Unauthorized
+        );
+
+        let edge = &mut ctx.accounts.referral;
+        let event_bit = 1u8 << event.index();
+        if edge.rewarded_events & event_bit != 0 {
+            return Ok(());
+        }
+        edge.rewarded_events |= event_bit;
+
+        let reward = config.rewards[event.index()];
+        let balance = &mut ctx.accounts.referrer_rewards;
+        balance.claimable = balance
+            .claimable
+            .checked_add(reward)
+            .ok_or(ReferralError::ArithmeticOverflow)?;
+        balance.total_earned = balance
+            .total_earned
+            .checked_add(reward)
+            .ok_or(ReferralError::ArithmeticOverflow)?;
+
+        emit!(ValueEventRecorded {
+            referrer: edge.referrer,
+            referee: edge.referee,
+            event,
+            reward,
+            reporter: ctx.accounts.reporter.key(),
+        });
+
+        Ok(())
+    }
+
+    // Withdraw the referrer's accrued rewards, up to the vault's balance
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let balance = &mut ctx.accounts.referrer_rewards;
+        let amount = balance.claimable.min(ctx.accounts.reward_vault.amount);
+        require!(amount > 0, ReferralError::NothingToClaim);
+
+        let config_seeds: &[&[u8]] = &[b"referrals_config", &[ctx.accounts.config.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.referrer_token_account.to_account_info(),
+                    authority: ctx.accounts.config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            amount,
+        )?;
+        balance.claimable -= amount;
+
+        emit!(RewardsClaimed {
+            referrer: balance.referrer,
+            amount,
+            remaining: balance.claimable,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct ReferralsConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub rewards: [u64; VALUE_EVENT_COUNT],  // Referrer reward per ValueEvent
+    pub reporters: Vec<Pubkey>,             // Signers allowed to report value events
+    pub bump: u8,
+}
+
+// Referrer -> referee edge, keyed by the referee
+#[account]
+pub struct Referral {
+    pub referee: Pubkey,
+    pub referrer: Pubkey,
+    pub created_at: i64,
+    pub rewarded_events: u8,  // Bit per ValueEvent already rewarded
+    pub bump: u8,
+}
+
+#[account]
+pub struct ReferrerRewards {
+    pub referrer: Pubkey,
+    pub claimable: u64,
+    pub total_earned: u64,
+    pub referral_count: u32,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeReferrals<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<ReferralsConfig>() + MAX_REPORTERS * 32,
+        seeds = [b"referrals_config"],
+        bump
+    )]
+    pub config: Account<'info, ReferralsConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"referral_vault"],
+        bump,
+        token::mint = influence_mint,
+        token::authority = config,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReferralsConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"referrals_config"],
+        bump = config.bump,
+        has_one = admin @ ReferralError::Unauthorized,
+    )]
+    pub config: Account<'info, ReferralsConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(referrer: Pubkey)]
+pub struct RegisterReferral<'info> {
+    #[account(mut)]
+    pub referee: Signer<'info>,
+
+    #[account(
+        init,
+        payer = referee,
+        space = 8 + std::mem::size_of::<Referral>(),
+        seeds = [b"referral", referee.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(
+        init_if_needed,
+        payer = referee,
+        space = 8 + std::mem::size_of::<ReferrerRewards>(),
+        seeds = [b"referrer_rewards", referrer.as_ref()],
+        bump
+    )]
+    pub referrer_rewards: Account<'info, ReferrerRewards>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordValueEvent<'info> {
+    pub reporter: Signer<'info>,
+
+    #[account(seeds = [b"referrals_config"], bump = config.bump)]
+    pub config: Account<'info, ReferralsConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"referral", referral.referee.as_ref()],
+        bump = referral.bump,
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer_rewards", referral.referrer.as_ref()],
+        bump = referrer_rewards.bump,
+    )]
+    pub referrer_rewards: Account<'info, ReferrerRewards>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub referrer: Signer<'info>,
+
+    #[account(seeds = [b"referrals_config"], bump = config.bump)]
+    pub config: Account<'info, ReferralsConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer_rewards", referrer.key().as_ref()],
+        bump = referrer_rewards.bump,
+    )]
+    pub referrer_rewards: Account<'info, ReferrerRewards>,
+
+    #[account(mut, address = config.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = referrer_token_account.owner == referrer.key() @ ReferralError::Unauthorized,
+        constraint = referrer_token_account.mint == config.influence_mint,
+    )]
+    pub referrer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ValueEvent {
+    Registration,   // Referee registered their first agent
+    FirstPurchase,  // Referee's first marketplace purchase
+    FirstMatch,     // Referee's agent was matched for the first time
+}
+
+impl ValueEvent {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// Events
+#[event]
+pub struct CommissionRuleUpdated {
+    pub event: ValueEvent,
+    pub reward: u64,
+}
+
+#[event]
+pub struct ReporterUpdated {
+    pub reporter: Pubkey,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct ReferralRegistered {
+    pub referrer: Pubkey,
+    pub referee: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValueEventRecorded {
+    pub referrer: Pubkey,
+    pub referee: Pubkey,
+    pub event: ValueEvent,
+    pub reward: u64,
+    pub reporter: Pubkey,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum ReferralError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("A wallet cannot refer itself")]
+    SelfReferral,
+    #[msg("Reporter is already allowed")]
+    ReporterAlreadyAdded,
+    #[msg("Reporter list is full")]
+    TooManyReporters,
+    #[msg("Reporter not found")]
+    ReporterNotFound,
+    #[msg("No rewards to claim")]
+    NothingToClaim,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Referral is not the referee's")]
+    RefereeMismatch,
+}
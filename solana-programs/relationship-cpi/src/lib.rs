@@ -0,0 +1,220 @@
+// Interface for programs and clients that integrate with the relationship
+// program: the program id, account layouts and hook payload, PDA finders, and
+// instruction builders. The `cpi` feature adds the program's Anchor CPI module.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData};
+
+pub use relationship::{
+    accounts, id, instruction, program, CustomInteraction, HookRegistry, InteractionData,
+    InteractionHookPayload, InteractionNonce, InteractionType, RelationshipConfig,
+    RelationshipData, RelationshipError, RelationshipStats, RelationshipStatus,
+    RelationshipTemplate, RelationshipType, Visibility, EVENT_SCHEMA_VERSION,
+    HOOK_AUTHORITY_SEED, ID, LEGACY_RELATIONSHIP_SPACE,
+};
+
+#[cfg(feature = "cpi")]
+pub use relationship::cpi;
+
+pub mod pda {
+    use super::*;
+
+    pub fn find_relationship_address(agent_one: &Pubkey, agent_two: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"relationship", agent_one.as_ref(), agent_two.as_ref()], &ID)
+    }
+
+    pub fn find_interaction_nonce_address(relationship: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"interaction_nonce", relationship.as_ref()], &ID)
+    }
+
+    pub fn find_interaction_address(relationship: &Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"interaction", relationship.as_ref(), &index.to_le_bytes()], &ID)
+    }
+
+    pub fn find_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"config"], &ID)
+    }
+
+    pub fn find_stats_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"stats"], &ID)
+    }
+
+    pub fn find_hook_registry_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"hooks"], &ID)
+    }
+
+    // Signs hook CPIs; hook programs check it with `seeds::program = relationship_cpi::ID`
+    pub fn find_hook_authority() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[HOOK_AUTHORITY_SEED], &ID)
+    }
+
+    pub fn find_interaction_type_registry_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"interaction_types"], &ID)
+    }
+
+    pub fn find_recorder_rate_address(recorder: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"recorder_rate", recorder.as_ref()], &ID)
+    }
+
+    pub fn find_event_authority() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"__event_authority"], &ID)
+    }
+
+    // The guardian whose pause flags gate state-changing instructions
+    pub fn find_guardian_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[guardian::GUARDIAN_SEED], &guardian::ID)
+    }
+
+    // The rate limiter config and a wallet's quota, for instructions that
+    // consume rate-limited actions
+    pub fn find_rate_limiter_config_address() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[rate_limiter::RATE_LIMITER_CONFIG_SEED], &rate_limiter::ID)
+    }
+
+    pub fn find_wallet_quota_address(wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[rate_limiter::WALLET_QUOTA_SEED, wallet.as_ref()], &rate_limiter::ID)
+    }
+}
+
+pub mod builders {
+    use super::*;
+
+    pub fn create_relationship(
+        authority: Pubkey,
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+        template: Option<Pubkey>,
+        relationship_type: RelationshipType,
+        compatibility_score: u8,
+    ) -> Instruction {
+        let relationship_data = pda::find_relationship_address(&agent_one, &agent_two).0;
+        build(
+            accounts::CreateRelationship {
+                authority,
+                agent_one,
+                agent_two,
+                template,
+                relationship_data,
+                interaction_nonce: pda::find_interaction_nonce_address(&relationship_data).0,
+                system_program: system_program::ID,
+                stats: pda::find_stats_address().0,
+                guardian: pda::find_guardian_address().0,
+                event_authority: pda::find_event_authority().0,
+                program: ID,
+            },
+            instruction::CreateRelationship { relationship_type, compatibility_score },
+        )
+    }
+
+    pub fn update_relationship_status(
+        owner_one: Pubkey,
+        owner_two: Pubkey,
+        relationship_data: Pubkey,
+        template: Option<Pubkey>,
+        status: RelationshipStatus,
+    ) -> Instruction {
+        build(
+            accounts::UpdateRelationship {
+                owner_one,
+                owner_two,
+                relationship_data,
+                template,
+                stats: pda::find_stats_address().0,
+                guardian: pda::find_guardian_address().0,
+                event_authority: pda::find_event_authority().0,
+                program: ID,
+            },
+            instruction::UpdateRelationshipStatus { status },
+        )
+    }
+
+    // Records without matchmaker commission. Matchmade relationships need the
+    // commission accounts, so build those from `accounts::RecordInteraction`
+    // directly. Registered hooks' accounts are appended by the caller.
+    // `authority` is `owner`, an owner of the relationship, or the session
+    // signer when recording under `session`.
+    pub fn record_interaction(
+        authority: Pubkey,
+        owner: Pubkey,
+        relationship_data: Pubkey,
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+        interaction_index: u64,
+        interaction_type: InteractionType,
+        interaction_data: String,
+        custom_type: Option<CustomInteraction>,
+        session: Option<Pubkey>,
+    ) -> Instruction {
+        let interaction_type_registry = custom_type
+            .as_ref()
+            .map(|_| pda::find_interaction_type_registry_address().0);
+        build(
+            accounts::RecordInteraction {
+                authority,
+                session,
+                event_recorder: None,
+                relationship_data,
+                agent_one,
+                agent_two,
+                interaction_nonce: pda::find_interaction_nonce_address(&relationship_data).0,
+                interaction_data: pda::find_interaction_address(&relationship_data, interaction_index).0,
+                hook_registry: pda::find_hook_registry_address().0,
+                hook_authority: pda::find_hook_authority().0,
+                interaction_type_registry,
+                system_program: system_program::ID,
+                config: pda::find_config_address().0,
+                recorder_rate: pda::find_recorder_rate_address(&owner).0,
+                matchmaker_stats: None,
+                matchmaker_token_account: None,
+                rewards_vault: None,
+                token_program: None,
+                stats: pda::find_stats_address().0,
+                guardian: pda::find_guardian_address().0,
+                rate_limiter_config: pda::find_rate_limiter_config_address().0,
+                wallet_quota: pda::find_wallet_quota_address(&owner).0,
+                rate_limiter_program: rate_limiter::ID,
+                event_authority: pda::find_event_authority().0,
+                program: ID,
+            },
+            instruction::RecordInteraction {
+                interaction_index,
+                interaction_type,
+                interaction_data,
+                custom_type,
+            },
+        )
+    }
+
+    // Rewrite a legacy Borsh-layout relationship into the zero-copy layout;
+    // `payer` covers the extra rent. The agents are the legacy account's, in
+    // its order.
+    pub fn migrate_relationship_layout(
+        payer: Pubkey,
+        relationship_data: Pubkey,
+        agent_one: Pubkey,
+        agent_two: Pubkey,
+    ) -> Instruction {
+        build(
+            accounts::MigrateRelationshipLayout {
+                payer,
+                relationship_data,
+                agent_one,
+                agent_two,
+                system_program: system_program::ID,
+                guardian: pda::find_guardian_address().0,
+                event_authority: pda::find_event_authority().0,
+                program: ID,
+            },
+            instruction::MigrateRelationshipLayout {},
+        )
+    }
+
+    // Any instruction from its Anchor account and argument structs
+    pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+}
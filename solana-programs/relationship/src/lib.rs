@@ -1,8 +1,54 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_lang::{Discriminator, InstructionData};
+use anchor_lang::system_program;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use agent_registry::AgentData;
+use armour_core::{add_health_bonus, adjust_health, calculate_compatibility_score, decay_health, MAX_HEALTH};
+use clockwork_sdk::state::{Thread, Trigger};
+use clockwork_sdk::ThreadProgram;
+use escrow_cpi::program::Escrow as EscrowProgram;
+use escrow_cpi::{Escrow, ReleaseCondition, ESCROW_AUTHORITY_SEED};
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use armour_light::{ExistingRecordArgs, LightCpiAccounts, NewRecordArgs};
+use sessions::{SessionKey, SCOPE_RECORD_INTERACTIONS};
+use guardian::Guardian;
+use rate_limiter::{program::RateLimiter, RateLimitedAction};
+
+pub use armour_core::{InteractionType, RelationshipStatus, RelationshipType, Visibility};
 
 declare_id!("Re1ati0nsh1pPr0graMxXxXxXxXxXxXxXxXxXxXxX");
 
+const BPS_DENOMINATOR: u16 = 10_000;
+const RELATIONSHIP_LAYOUT_VERSION: u8 = 1;
+// Accounts created before the zero-copy layout were allocated with this size
+pub const LEGACY_RELATIONSHIP_SPACE: usize = 8 + std::mem::size_of::<LegacyRelationshipData>();
+const RELATIONSHIP_TYPE_COUNT: usize = 6;
+const MAX_INTERACTION_HOOKS: usize = 8;
+const MAX_SCORE_HISTORY: usize = 16;
+const MAX_CUSTOM_TYPES: usize = 32;
+const MAX_CUSTOM_LABEL_LEN: usize = 32;
+const MAX_INTERACTION_TYPES: usize = 32;
+const MAX_INTERACTION_TYPE_NAME_LEN: usize = 32;
+const WITNESS_WINDOW: i64 = 7 * 24 * 60 * 60;  // Witnesses must attest within a week
+const WITNESS_HEALTH_BONUS: u8 = 2;
+pub const WITNESSED_INTERACTION_WEIGHT: u64 = 2;
+const INACTIVITY_PERIOD: i64 = 30 * 24 * 60 * 60;
+const INACTIVITY_DECAY: u8 = 5;  // Health lost per inactive period
+const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+const DAILY_SCHEDULE: &str = "0 0 0 * * * *";  // Clockwork cron, with seconds
+const CHANGE_PROPOSAL_WINDOW: i64 = 3 * 24 * 60 * 60;  // Time the other owner has to confirm a change
+pub const HOOK_AUTHORITY_SEED: &[u8] = b"hook_authority";
+// The events program signs with this PDA to record attendance interactions
+// for agents that checked in, on behalf of their owners
+pub const EVENTS_PROGRAM_ID: Pubkey = solana_program::pubkey!("EventsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+pub const INTERACTION_RECORDER_SEED: &[u8] = b"interaction_recorder";
+
+// Bumped whenever an event's fields change; indexers dispatch on it
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+
 #[program]
 pub mod relationship {
     use super::*;
@@ -13,20 +59,33 @@ pub mod relationship {
         relationship_type: RelationshipType,
         compatibility_score: u8,
     ) -> Result<()> {
-        let relationship_data = &mut ctx.accounts.relationship_data;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_init()?;
         
         // Set relationship data
-        relationship_data.agent_one = ctx.accounts.agent_one.key();
-        relationship_data.agent_two = ctx.accounts.agent_two.key();
-        relationship_data.relationship_type = relationship_type;
-        relationship_data.compatibility_score = compatibility_score;
-        relationship_data.creation_date = Clock::get()?.unix_timestamp;
-        relationship_data.last_interaction = Clock::get()?.unix_timestamp;
-        relationship_data.interaction_count = 0;
-        relationship_data.status = RelationshipStatus::Active;
-        
-        emit!(RelationshipCreated {
-            relationship_id: relationship_data.key(),
+        initialize_relationship(
+            &mut relationship_data,
+            &ctx.accounts.agent_one,
+            &ctx.accounts.agent_two,
+            relationship_type.clone(),
+            compatibility_score,
+        )?;
+        if let Some(template) = &ctx.accounts.template {
+            apply_template(&mut relationship_data, template)?;
+        }
+        let interaction_nonce = &mut ctx.accounts.interaction_nonce;
+        interaction_nonce.relationship = relationship_key;
+        interaction_nonce.next_index = 0;
+        interaction_nonce.bump = *ctx.bumps.get("interaction_nonce").unwrap();
+        ctx.accounts.stats.load_mut()?.on_created(
+            &relationship_type,
+            compatibility_score,
+            relationship_data.creation_date,
+        );
+        
+        emit_cpi!(RelationshipCreated {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
             agent_one: relationship_data.agent_one,
             agent_two: relationship_data.agent_two,
             relationship_type,
@@ -35,210 +94,6578 @@ pub mod relationship {
         Ok(())
     }
     
-    // Record an interaction between two agents in a relationship
-    pub fn record_interaction(
-        ctx: Context<UpdateRelationship>,
+    // Record an interaction between two agents in a relationship, signed by
+    // one of its owners, a session key holder acting for one, or anyone with
+    // the events program's recorder PDA
+    pub fn record_interaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordInteraction<'info>>,
+        interaction_index: u64,
         interaction_type: InteractionType,
         interaction_data: String,
+        custom_type: Option<CustomInteraction>,
     ) -> Result<()> {
-        let relationship_data = &mut ctx.accounts.relationship_data;
+        let authority = sessions::acting_owner(
+            &ctx.accounts.session,
+            &ctx.accounts.authority.key(),
+            SCOPE_RECORD_INTERACTIONS,
+        )?;
+        // Quotas are the owner's, whichever key signs
+        match &ctx.accounts.session {
+            Some(session) => rate_limiter::consume_for_session(
+                &ctx.accounts.rate_limiter_program.to_account_info(),
+                &ctx.accounts.rate_limiter_config,
+                &ctx.accounts.wallet_quota,
+                &ctx.accounts.authority.to_account_info(),
+                &session.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                RateLimitedAction::Interaction,
+                1,
+            )?,
+            None => rate_limiter::consume(
+                &ctx.accounts.rate_limiter_program.to_account_info(),
+                &ctx.accounts.rate_limiter_config,
+                &ctx.accounts.wallet_quota,
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                RateLimitedAction::Interaction,
+                1,
+            )?,
+        }
+
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        require!(
+            authority == relationship_data.owner_one
+                || authority == relationship_data.owner_two
+                || ctx.accounts.event_recorder.is_some(),
+            RelationshipError::Unauthorized
+        );
+        let custom_type_id = validate_custom_interaction(
+            &interaction_type,
+            &custom_type,
+            &ctx.accounts.interaction_type_registry,
+        )?;
         
         // Ensure relationship is active
         require!(
-            relationship_data.status == RelationshipStatus::Active,
+            relationship_data.status()? == RelationshipStatus::Active,
             RelationshipError::InactiveRelationship
         );
+        require_not_frozen(&ctx.accounts.agent_one, &ctx.accounts.agent_two)?;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let rate_limits = &ctx.accounts.config.rate_limits;
+        let recorder_rate = &mut ctx.accounts.recorder_rate;
+        recorder_rate.recorder = authority;
+        recorder_rate.bump = *ctx.bumps.get("recorder_rate").unwrap();
+        enforce_rate_limits(rate_limits, &mut relationship_data, recorder_rate, now)?;
+        
+        // The interaction PDA is derived from the nonce; a stale index means
+        // another interaction was recorded first and the client must re-derive
+        let interaction_nonce = &mut ctx.accounts.interaction_nonce;
+        require!(
+            interaction_index == interaction_nonce.next_index,
+            RelationshipError::StaleInteractionIndex
+        );
+        interaction_nonce.next_index = interaction_nonce.next_index.checked_add(1).unwrap_or(u64::MAX);
         
         // Update relationship data
-        relationship_data.last_interaction = Clock::get()?.unix_timestamp;
+        relationship_data.last_interaction = now;
         relationship_data.interaction_count = relationship_data.interaction_count.checked_add(1).unwrap_or(u32::MAX);
+        if interaction_type == InteractionType::Milestone {
+            relationship_data.milestone_count = relationship_data.milestone_count.checked_add(1).unwrap_or(u32::MAX);
+        }
+        ctx.accounts.stats.load_mut()?.on_interaction(relationship_data.last_interaction);
+        
+        // Private relationships only expose a hash of the interaction data in events
+        let interaction_data_hash = hash(interaction_data.as_bytes()).to_bytes();
+        let event_data = if relationship_data.visibility()? == Visibility::Private {
+            None
+        } else {
+            Some(interaction_data.clone())
+        };
         
         // Create interaction record
         let interaction = &mut ctx.accounts.interaction_data;
-        interaction.relationship = relationship_data.key();
-        interaction.interaction_type = interaction_type;
+        interaction.relationship = relationship_key;
+        interaction.index = interaction_index;
+        interaction.interaction_type = interaction_type.clone();
         interaction.interaction_data = interaction_data;
-        interaction.timestamp = Clock::get()?.unix_timestamp;
+        interaction.timestamp = now;
+        interaction.witness_count = 0;
+        interaction.custom_type_id = custom_type_id;
+        
+        let hook_payload = InteractionHookPayload {
+            relationship: relationship_key,
+            agent_one: relationship_data.agent_one,
+            agent_two: relationship_data.agent_two,
+            interaction: interaction.key(),
+            interaction_index,
+            interaction_type: interaction_type.clone(),
+            timestamp: interaction.timestamp,
+        };
         
-        emit!(InteractionRecorded {
+        emit_cpi!(InteractionRecorded {
+            version: EVENT_SCHEMA_VERSION,
             interaction_id: interaction.key(),
-            relationship_id: relationship_data.key(),
+            relationship_id: relationship_key,
+            interaction_index,
             interaction_type,
+            interaction_data: event_data,
+            interaction_data_hash,
         });
         
+        // Pay the matchmaker's commission once the relationship reaches the configured level
+        if let (Some(matchmaker_stats), Some(matchmaker_token_account), Some(rewards_vault), Some(token_program)) = (
+            &mut ctx.accounts.matchmaker_stats,
+            &ctx.accounts.matchmaker_token_account,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.token_program,
+        ) {
+            let amount = pay_matchmaker_commission(
+                &ctx.accounts.config,
+                &mut relationship_data,
+                matchmaker_stats,
+                rewards_vault,
+                matchmaker_token_account,
+                token_program,
+            )?;
+            if amount > 0 {
+                emit_cpi!(MatchmakerCommissionPaid {
+                    version: EVENT_SCHEMA_VERSION,
+                    relationship_id: relationship_key,
+                    matchmaker: relationship_data.matchmaker,
+                    amount,
+                });
+            }
+        }
+        // Release the borrow before the relationship account is passed to hooks
+        drop(relationship_data);
+        
+        // Notify registered hook programs
+        dispatch_interaction_hooks(
+            &ctx.accounts.hook_registry,
+            &ctx.accounts.hook_authority,
+            *ctx.bumps.get("hook_authority").unwrap(),
+            &ctx.accounts.relationship_data.to_account_info(),
+            &ctx.accounts.interaction_data.to_account_info(),
+            ctx.remaining_accounts,
+            &hook_payload,
+        )?;
+        
         Ok(())
     }
     
-    // Update relationship status (active, paused, ended)
+    // Update relationship status (active, paused, ended). Both owners must
+    // sign; see `propose_relationship_change` for approval across transactions.
     pub fn update_relationship_status(
         ctx: Context<UpdateRelationship>,
         status: RelationshipStatus,
     ) -> Result<()> {
-        let relationship_data = &mut ctx.accounts.relationship_data;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let template = relationship_template(&relationship_data, &ctx.accounts.template)?;
         
-        // Update status
-        relationship_data.status = status;
+        apply_status_change(
+            &mut relationship_data,
+            template,
+            &mut *ctx.accounts.stats.load_mut()?,
+            &status,
+            Clock::get()?.unix_timestamp,
+        )?;
         
-        emit!(RelationshipStatusUpdated {
-            relationship_id: relationship_data.key(),
+        emit_cpi!(RelationshipStatusUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
             status,
         });
         
         Ok(())
     }
     
-    // Change relationship type (friends, dating, etc.)
+    // Change relationship type (friends, dating, etc.). Both owners must sign.
     pub fn change_relationship_type(
         ctx: Context<UpdateRelationship>,
         relationship_type: RelationshipType,
     ) -> Result<()> {
-        let relationship_data = &mut ctx.accounts.relationship_data;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let template = relationship_template(&relationship_data, &ctx.accounts.template)?;
+        
+        apply_type_change(
+            &mut relationship_data,
+            template,
+            &mut *ctx.accounts.stats.load_mut()?,
+            &relationship_type,
+            Clock::get()?.unix_timestamp,
+        )?;
         
+        emit_cpi!(RelationshipTypeChanged {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            relationship_type,
+        });
+        
+        Ok(())
+    }
+
+    // Initialize the relationship program config and its Influence rewards vault
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        oracle: Pubkey,
+        treasury: Pubkey,
+        date_params: DateParams,
+    ) -> Result<()> {
+        require!(
+            date_params.no_show_present_share_bps <= BPS_DENOMINATOR,
+            RelationshipError::InvalidBasisPoints
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.rewards_vault = ctx.accounts.rewards_vault.key();
+        config.oracle = oracle;
+        config.treasury = treasury;
+        config.date_params = date_params;
+        config.arbiter = ctx.accounts.admin.key();
+        config.goal_params = GoalParams::default();
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        emit_cpi!(ConfigInitialized {
+            version: EVENT_SCHEMA_VERSION,
+            admin: config.admin,
+            influence_mint: config.influence_mint,
+            oracle,
+        });
+
+        Ok(())
+    }
+
+    // Update the oracle, treasury and date staking parameters
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        oracle: Pubkey,
+        treasury: Pubkey,
+        date_params: DateParams,
+    ) -> Result<()> {
+        require!(
+            date_params.no_show_present_share_bps <= BPS_DENOMINATOR,
+            RelationshipError::InvalidBasisPoints
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.oracle = oracle;
+        config.treasury = treasury;
+        config.date_params = date_params.clone();
+
+        emit_cpi!(ConfigUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            oracle,
+            treasury,
+            date_params,
+        });
+
+        Ok(())
+    }
+
+    // Deposit Influence into the rewards vault used to pay date bonuses
+    pub fn fund_rewards(
+        ctx: Context<FundRewards>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, RelationshipError::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.funder_token_account.to_account_info(),
+            to: ctx.accounts.rewards_vault.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit_cpi!(RewardsFunded {
+            version: EVENT_SCHEMA_VERSION,
+            funder: ctx.accounts.funder.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Schedule a date where both owners escrow an equal Influence stake with
+    // the escrow program. Each stake names the other owner as beneficiary
+    // and only this program can settle it.
+    pub fn schedule_date(
+        ctx: Context<ScheduleDate>,
+        scheduled_at: i64,
+        stake_amount: u64,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+
         // Ensure relationship is active
         require!(
-            relationship_data.status == RelationshipStatus::Active,
+            relationship_data.status()? == RelationshipStatus::Active,
             RelationshipError::InactiveRelationship
         );
-        
-        // Update relationship type
-        relationship_data.relationship_type = relationship_type;
-        
-        emit!(RelationshipTypeChanged {
-            relationship_id: relationship_data.key(),
-            relationship_type,
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(scheduled_at > now, RelationshipError::InvalidDateTime);
+        require!(
+            stake_amount > 0 && stake_amount <= ctx.accounts.config.date_params.max_date_stake,
+            RelationshipError::InvalidAmount
+        );
+
+        // Escrow both stakes
+        let date_key = ctx.accounts.date_data.key();
+        for (depositor, escrow, vault, depositor_token_account, beneficiary) in [
+            (
+                &ctx.accounts.owner_one,
+                &ctx.accounts.escrow_one,
+                &ctx.accounts.vault_one,
+                &ctx.accounts.owner_one_token_account,
+                ctx.accounts.owner_two.key(),
+            ),
+            (
+                &ctx.accounts.owner_two,
+                &ctx.accounts.escrow_two,
+                &ctx.accounts.vault_two,
+                &ctx.accounts.owner_two_token_account,
+                ctx.accounts.owner_one.key(),
+            ),
+        ] {
+            escrow_cpi::cpi::deposit_tokens(
+                CpiContext::new(
+                    ctx.accounts.escrow_program.to_account_info(),
+                    escrow_cpi::cpi::accounts::DepositTokens {
+                        depositor: depositor.to_account_info(),
+                        escrow: escrow.to_account_info(),
+                        vault: vault.to_account_info(),
+                        mint: ctx.accounts.influence_mint.to_account_info(),
+                        depositor_token_account: depositor_token_account.to_account_info(),
+                        token_program: ctx.accounts.token_program.to_account_info(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        rent: ctx.accounts.rent.to_account_info(),
+                    },
+                ),
+                date_key,
+                beneficiary,
+                stake_amount,
+                ReleaseCondition::CpiCaller { program: crate::ID },
+                ReleaseCondition::CpiCaller { program: crate::ID },
+            )?;
+        }
+
+        let date_data = &mut ctx.accounts.date_data;
+        date_data.relationship = relationship_key;
+        date_data.date_index = relationship_data.date_count;
+        date_data.scheduled_at = scheduled_at;
+        date_data.stake_amount = stake_amount;
+        date_data.confirmed_one = false;
+        date_data.confirmed_two = false;
+        date_data.status = DateStatus::Scheduled;
+        date_data.created_at = now;
+        date_data.absent_owner = Pubkey::default();
+        date_data.no_show_reported_at = 0;
+        date_data.bump = *ctx.bumps.get("date_data").unwrap();
+
+        // The count seeds the next date's address, so it must not saturate
+        relationship_data.date_count = relationship_data
+            .date_count
+            .checked_add(1)
+            .ok_or(RelationshipError::ArithmeticOverflow)?;
+
+        emit_cpi!(DateScheduled {
+            version: EVENT_SCHEMA_VERSION,
+            date_id: date_data.key(),
+            relationship_id: relationship_key,
+            date_index: date_data.date_index,
+            scheduled_at,
+            stake_amount,
         });
-        
+
         Ok(())
     }
-}
 
-// Account structures
-#[account]
-pub struct RelationshipData {
-    pub agent_one: Pubkey,
-    pub agent_two: Pubkey,
-    pub relationship_type: RelationshipType,
-    pub compatibility_score: u8,
-    pub creation_date: i64,
-    pub last_interaction: i64,
-    pub interaction_count: u32,
-    pub status: RelationshipStatus,
-}
+    // Confirm a date took place. Both owners confirming, or the oracle attesting,
+    // returns the escrowed stakes plus a bonus from the rewards vault.
+    pub fn confirm_date_completed(ctx: Context<ConfirmDate>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let date_data = &mut ctx.accounts.date_data;
+        let authority = ctx.accounts.authority.key();
 
-#[account]
-pub struct InteractionData {
-    pub relationship: Pubkey,
-    pub interaction_type: InteractionType,
-    pub interaction_data: String,  // Could be a reference to off-chain data
-    pub timestamp: i64,
-}
+        require!(
+            date_data.status == DateStatus::Scheduled,
+            RelationshipError::DateNotScheduled
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= date_data.scheduled_at,
+            RelationshipError::DateNotStarted
+        );
 
-// Context structs for instructions
-#[derive(Accounts)]
-pub struct CreateRelationship<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub agent_one: AccountInfo<'info>,
-    pub agent_two: AccountInfo<'info>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<RelationshipData>(),
-        seeds = [b"relationship", agent_one.key().as_ref(), agent_two.key().as_ref()],
-        bump
-    )]
-    pub relationship_data: Account<'info, RelationshipData>,
-    
-    pub system_program: Program<'info, System>,
-}
+        let attested = authority == config.oracle;
+        if !attested {
+            if authority == relationship_data.owner_one {
+                date_data.confirmed_one = true;
+            } else if authority == relationship_data.owner_two {
+                date_data.confirmed_two = true;
+            } else {
+                return err!(RelationshipError::Unauthorized);
+            }
+        }
 
-#[derive(Accounts)]
-pub struct UpdateRelationship<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    #[account(mut)]
-    pub relationship_data: Account<'info, RelationshipData>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<InteractionData>() + 200, // Extra space for interaction data
-        seeds = [
-            b"interaction", 
-            relationship_data.key().as_ref(), 
-            &relationship_data.interaction_count.to_le_bytes()
-        ],
-        bump
-    )]
-    pub interaction_data: Account<'info, InteractionData>,
-    
-    pub system_program: Program<'info, System>,
-}
+        emit_cpi!(DateConfirmed {
+            version: EVENT_SCHEMA_VERSION,
+            date_id: date_data.key(),
+            confirmed_by: authority,
+        });
 
-// Data structures
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
-pub enum RelationshipType {
-    Friends,
-    Dating,
-    Romantic,
-    Professional,
-    Mentorship,
-    Custom,
-}
+        if !attested && !(date_data.confirmed_one && date_data.confirmed_two) {
+            return Ok(());
+        }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
-pub enum RelationshipStatus {
-    Active,
-    Paused,
-    Ended,
-}
+        // Return both stakes from escrow
+        let stakes = ctx.accounts.stakes.settlement(
+            &ctx.accounts.token_program,
+            &ctx.accounts.owner_one_token_account,
+            &ctx.accounts.owner_two_token_account,
+        );
+        stakes.refund(&relationship_data.owner_one, date_data.stake_amount)?;
+        stakes.refund(&relationship_data.owner_two, date_data.stake_amount)?;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
-pub enum InteractionType {
-    Conversation,
-    Activity,
-    Gift,
-    DateEvent,
-    Milestone,
-    Custom,
-}
+        // Pay the completion bonus, limited to what the vault holds
+        let bonus = match relationship_template(&relationship_data, &ctx.accounts.template)? {
+            Some(template) => template.params.rewards.date_completion_bonus,
+            None => config.date_params.date_completion_bonus,
+        }
+        .min(ctx.accounts.rewards_vault.amount / 2);
+        if bonus > 0 {
+            let config_seeds: &[&[u8]] = &[b"config", &[config.bump]];
+            for to in [
+                &ctx.accounts.owner_one_token_account,
+                &ctx.accounts.owner_two_token_account,
+            ] {
+                pda_transfer(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.rewards_vault,
+                    to,
+                    config.to_account_info(),
+                    config_seeds,
+                    bonus,
+                )?;
+            }
+        }
 
-// Events
-#[event]
-pub struct RelationshipCreated {
-    pub relationship_id: Pubkey,
-    pub agent_one: Pubkey,
-    pub agent_two: Pubkey,
-    pub relationship_type: RelationshipType,
-}
+        date_data.status = DateStatus::Completed;
 
-#[event]
-pub struct InteractionRecorded {
-    pub interaction_id: Pubkey,
-    pub relationship_id: Pubkey,
-    pub interaction_type: InteractionType,
-}
+        emit_cpi!(DateCompleted {
+            version: EVENT_SCHEMA_VERSION,
+            date_id: date_data.key(),
+            relationship_id: relationship_key,
+            stake_amount: date_data.stake_amount,
+            bonus,
+            oracle_attested: attested,
+        });
 
-#[event]
-pub struct RelationshipStatusUpdated {
-    pub relationship_id: Pubkey,
-    pub status: RelationshipStatus,
-}
+        Ok(())
+    }
 
-#[event]
-pub struct RelationshipTypeChanged {
-    pub relationship_id: Pubkey,
-    pub relationship_type: RelationshipType,
-}
+    // Report that one side did not show up to a date. The oracle may attest for
+    // either side; an owner may only report the other side when they confirmed
+    // the date themselves and the other side never did.
+    pub fn report_no_show(
+        ctx: Context<ReportNoShow>,
+        absent_owner: Pubkey,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let date_data = &mut ctx.accounts.date_data;
+        let reporter = ctx.accounts.reporter.key();
 
-// Custom errors
-#[error_code]
-pub enum RelationshipError {
-    #[msg("Relationship is not active")]
-    InactiveRelationship,
-    #[msg("You are not authorized to perform this action")]
-    Unauthorized,
+        require!(
+            date_data.status == DateStatus::Scheduled,
+            RelationshipError::DateNotScheduled
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= date_data
+                .scheduled_at
+                .saturating_add(config.date_params.no_show_grace_period),
+            RelationshipError::GracePeriodActive
+        );
+
+        let absent_is_one = if absent_owner == relationship_data.owner_one {
+            true
+        } else if absent_owner == relationship_data.owner_two {
+            false
+        } else {
+            return err!(RelationshipError::InvalidAbsentParty);
+        };
+
+        if reporter != config.oracle {
+            let (reporter_is_one, reporter_confirmed, absent_confirmed) = if absent_is_one {
+                (false, date_data.confirmed_two, date_data.confirmed_one)
+            } else {
+                (true, date_data.confirmed_one, date_data.confirmed_two)
+            };
+            let expected_reporter = if reporter_is_one {
+                relationship_data.owner_one
+            } else {
+                relationship_data.owner_two
+            };
+            require!(reporter == expected_reporter, RelationshipError::Unauthorized);
+            require!(
+                reporter_confirmed && !absent_confirmed,
+                RelationshipError::NoShowNotProvable
+            );
+        }
+
+        date_data.status = DateStatus::NoShowReported;
+        date_data.absent_owner = absent_owner;
+        date_data.no_show_reported_at = now;
+
+        emit_cpi!(NoShowReported {
+            version: EVENT_SCHEMA_VERSION,
+            date_id: date_data.key(),
+            relationship_id: relationship_key,
+            absent_owner,
+            reported_by: reporter,
+            dispute_deadline: now.saturating_add(config.date_params.dispute_window),
+        });
+
+        Ok(())
+    }
+
+    // Dispute a no-show report during the dispute window. Disputed dates can
+    // only be settled by the oracle.
+    pub fn dispute_no_show(ctx: Context<DisputeNoShow>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let date_data = &mut ctx.accounts.date_data;
+
+        require!(
+            date_data.status == DateStatus::NoShowReported,
+            RelationshipError::NoShowNotReported
+        );
+        require!(
+            ctx.accounts.absent_owner.key() == date_data.absent_owner,
+            RelationshipError::Unauthorized
+        );
+        require!(
+            Clock::get()?.unix_timestamp
+                < date_data
+                    .no_show_reported_at
+                    .saturating_add(config.date_params.dispute_window),
+            RelationshipError::DisputeWindowClosed
+        );
+
+        date_data.status = DateStatus::NoShowDisputed;
+
+        emit_cpi!(NoShowDisputed {
+            version: EVENT_SCHEMA_VERSION,
+            date_id: date_data.key(),
+            disputed_by: date_data.absent_owner,
+        });
+
+        Ok(())
+    }
+
+    // Settle a no-show. Undisputed reports can be settled by anyone once the
+    // dispute window has passed; disputed reports need the oracle, who either
+    // upholds the report or refunds both stakes.
+    pub fn settle_no_show(
+        ctx: Context<SettleNoShow>,
+        uphold: bool,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let date_data = &mut ctx.accounts.date_data;
+        let is_oracle = ctx.accounts.authority.key() == config.oracle;
+
+        match date_data.status {
+            DateStatus::NoShowReported => {
+                require!(
+                    Clock::get()?.unix_timestamp
+                        >= date_data
+                            .no_show_reported_at
+                            .saturating_add(config.date_params.dispute_window),
+                    RelationshipError::DisputeWindowOpen
+                );
+                require!(uphold || is_oracle, RelationshipError::Unauthorized);
+            }
+            DateStatus::NoShowDisputed => {
+                require!(is_oracle, RelationshipError::Unauthorized);
+            }
+            _ => return err!(RelationshipError::NoShowNotReported),
+        }
+
+        let stake = date_data.stake_amount;
+        let stakes = ctx.accounts.stakes.settlement(
+            &ctx.accounts.token_program,
+            &ctx.accounts.owner_one_token_account,
+            &ctx.accounts.owner_two_token_account,
+        );
+        let absent = date_data.absent_owner;
+        let (present, present_account) = if absent == relationship_data.owner_one {
+            (relationship_data.owner_two, &ctx.accounts.owner_two_token_account)
+        } else {
+            (relationship_data.owner_one, &ctx.accounts.owner_one_token_account)
+        };
+
+        // The present side always gets its own stake back
+        stakes.refund(&present, stake)?;
+        let mut present_payout = stake;
+        let mut treasury_payout = 0;
+        if uphold {
+            // The present side also gets its share of the forfeited stake
+            let present_share = ((stake as u128)
+                .checked_mul(config.date_params.no_show_present_share_bps as u128)
+                .ok_or(RelationshipError::ArithmeticOverflow)?
+                / BPS_DENOMINATOR as u128) as u64;
+            present_payout = stake
+                .checked_add(present_share)
+                .ok_or(RelationshipError::ArithmeticOverflow)?;
+            treasury_payout = stake - present_share;
+            stakes.pay(&absent, present_account, present_share)?;
+            stakes.pay(&absent, &ctx.accounts.treasury, treasury_payout)?;
+        } else {
+            stakes.refund(&absent, stake)?;
+        }
+
+        date_data.status = if uphold {
+            DateStatus::NoShowSettled
+        } else {
+            DateStatus::Cancelled
+        };
+
+        emit_cpi!(NoShowSettled {
+            version: EVENT_SCHEMA_VERSION,
+            date_id: date_data.key(),
+            relationship_id: relationship_key,
+            absent_owner: date_data.absent_owner,
+            upheld: uphold,
+            present_payout,
+            treasury_payout,
+        });
+
+        Ok(())
+    }
+
+    // Open a shared Influence wallet for a relationship
+    pub fn create_joint_wallet(ctx: Context<CreateJointWallet>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == relationship_data.owner_one || owner == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+
+        let joint_wallet = &mut ctx.accounts.joint_wallet;
+        joint_wallet.relationship = relationship_key;
+        joint_wallet.vault = ctx.accounts.vault.key();
+        joint_wallet.proposal_count = 0;
+        joint_wallet.total_deposited = 0;
+        joint_wallet.total_withdrawn = 0;
+        joint_wallet.bump = *ctx.bumps.get("joint_wallet").unwrap();
+
+        emit_cpi!(JointWalletCreated {
+            version: EVENT_SCHEMA_VERSION,
+            joint_wallet: joint_wallet.key(),
+            relationship_id: relationship_key,
+            vault: joint_wallet.vault,
+        });
+
+        Ok(())
+    }
+
+    // Deposit Influence into the joint wallet (either owner)
+    pub fn deposit_joint_wallet(
+        ctx: Context<DepositJointWallet>,
+        amount: u64,
+    ) -> Result<()> {
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == relationship_data.owner_one || owner == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(amount > 0, RelationshipError::InvalidAmount);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+
+        let joint_wallet = &mut ctx.accounts.joint_wallet;
+        joint_wallet.total_deposited = joint_wallet.total_deposited.saturating_add(amount);
+
+        emit_cpi!(JointWalletDeposit {
+            version: EVENT_SCHEMA_VERSION,
+            joint_wallet: joint_wallet.key(),
+            depositor: owner,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Propose a withdrawal from the joint wallet. The proposer's approval is
+    // recorded immediately; the other owner must approve before funds move.
+    pub fn propose_withdrawal(
+        ctx: Context<ProposeWithdrawal>,
+        amount: u64,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let proposer = ctx.accounts.proposer.key();
+        require!(amount > 0, RelationshipError::InvalidAmount);
+
+        let joint_wallet = &mut ctx.accounts.joint_wallet;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.joint_wallet = joint_wallet.key();
+        proposal.proposal_index = joint_wallet.proposal_count;
+        proposal.proposer = proposer;
+        proposal.recipient = recipient;
+        proposal.amount = amount;
+        if proposer == relationship_data.owner_one {
+            proposal.approved_one = true;
+            proposal.approved_two = false;
+        } else if proposer == relationship_data.owner_two {
+            proposal.approved_one = false;
+            proposal.approved_two = true;
+        } else {
+            return err!(RelationshipError::Unauthorized);
+        }
+        proposal.executed = false;
+        proposal.created_at = Clock::get()?.unix_timestamp;
+
+        // The count seeds the next proposal's address, so it must not saturate
+        joint_wallet.proposal_count = joint_wallet
+            .proposal_count
+            .checked_add(1)
+            .ok_or(RelationshipError::ArithmeticOverflow)?;
+
+        emit_cpi!(WithdrawalProposed {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            joint_wallet: joint_wallet.key(),
+            proposer,
+            recipient,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Approve a pending withdrawal. Once both owners have approved, the
+    // funds are released to the recipient token account.
+    pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let approver = ctx.accounts.approver.key();
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(!proposal.executed, RelationshipError::ProposalAlreadyExecuted);
+        require!(
+            Clock::get()?.unix_timestamp >= relationship_data.prenup_lockup_until,
+            RelationshipError::JointWalletLocked
+        );
+
+        if approver == relationship_data.owner_one {
+            proposal.approved_one = true;
+        } else if approver == relationship_data.owner_two {
+            proposal.approved_two = true;
+        } else {
+            return err!(RelationshipError::Unauthorized);
+        }
+
+        emit_cpi!(WithdrawalApproved {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            approver,
+        });
+
+        if !(proposal.approved_one && proposal.approved_two) {
+            return Ok(());
+        }
+
+        let joint_wallet = &mut ctx.accounts.joint_wallet;
+        let wallet_seeds: &[&[u8]] = &[
+            b"joint_wallet",
+            relationship_key.as_ref(),
+            &[joint_wallet.bump],
+        ];
+        pda_transfer(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.recipient_token_account,
+            joint_wallet.to_account_info(),
+            wallet_seeds,
+            proposal.amount,
+        )?;
+
+        proposal.executed = true;
+        joint_wallet.total_withdrawn = joint_wallet.total_withdrawn.saturating_add(proposal.amount);
+
+        emit_cpi!(WithdrawalExecuted {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            joint_wallet: joint_wallet.key(),
+            recipient: proposal.recipient,
+            amount: proposal.amount,
+        });
+
+        Ok(())
+    }
+
+    // Cancel a pending withdrawal and reclaim its rent (either owner)
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == relationship_data.owner_one || owner == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(
+            !ctx.accounts.proposal.executed,
+            RelationshipError::ProposalAlreadyExecuted
+        );
+
+        emit_cpi!(WithdrawalCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: ctx.accounts.proposal.key(),
+            cancelled_by: owner,
+        });
+
+        Ok(())
+    }
+
+    // Set the prenup rules for splitting the joint wallet. Both owners must sign.
+    pub fn set_prenup(
+        ctx: Context<MutualConsent>,
+        prenup: PrenupRules,
+    ) -> Result<()> {
+        require!(
+            prenup.owner_one_share_bps <= BPS_DENOMINATOR,
+            RelationshipError::InvalidBasisPoints
+        );
+
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        require!(
+            relationship_data.status()? != RelationshipStatus::Ended,
+            RelationshipError::InactiveRelationship
+        );
+
+        relationship_data.set_prenup(&prenup);
+
+        emit_cpi!(PrenupUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            owner_one_share_bps: prenup.owner_one_share_bps,
+            lockup_until: prenup.lockup_until,
+        });
+
+        Ok(())
+    }
+
+    // End a relationship (either owner). If the pair has a joint wallet, its
+    // balance is split between the owners according to the prenup.
+    pub fn end_relationship(ctx: Context<EndRelationship>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == relationship_data.owner_one || owner == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(
+            relationship_data.status()? != RelationshipStatus::Ended,
+            RelationshipError::InactiveRelationship
+        );
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(template) = relationship_template(&relationship_data, &ctx.accounts.template)? {
+            require!(
+                now >= relationship_data
+                    .creation_date
+                    .saturating_add(template.params.transitions.min_duration),
+                RelationshipError::MinimumDurationNotMet
+            );
+        }
+
+        let mut owner_one_payout = 0;
+        let mut owner_two_payout = 0;
+        if let Some(joint_wallet) = &mut ctx.accounts.joint_wallet {
+            let (Some(vault), Some(owner_one_token_account), Some(owner_two_token_account), Some(token_program)) = (
+                &ctx.accounts.vault,
+                &ctx.accounts.owner_one_token_account,
+                &ctx.accounts.owner_two_token_account,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(RelationshipError::MissingJointWalletAccounts);
+            };
+            require!(vault.key() == joint_wallet.vault, RelationshipError::MissingJointWalletAccounts);
+            require!(
+                owner_one_token_account.owner == relationship_data.owner_one
+                    && owner_two_token_account.owner == relationship_data.owner_two,
+                RelationshipError::Unauthorized
+            );
+
+            let balance = vault.amount;
+            owner_one_payout = ((balance as u128)
+                .checked_mul(relationship_data.prenup_owner_one_share_bps as u128)
+                .ok_or(RelationshipError::ArithmeticOverflow)?
+                / BPS_DENOMINATOR as u128) as u64;
+            owner_two_payout = balance - owner_one_payout;
+
+            let wallet_seeds: &[&[u8]] = &[
+                b"joint_wallet",
+                relationship_key.as_ref(),
+                &[joint_wallet.bump],
+            ];
+            for (to, amount) in [
+                (owner_one_token_account, owner_one_payout),
+                (owner_two_token_account, owner_two_payout),
+            ] {
+                if amount > 0 {
+                    pda_transfer(
+                        token_program,
+                        vault,
+                        to,
+                        joint_wallet.to_account_info(),
+                        wallet_seeds,
+                        amount,
+                    )?;
+                }
+            }
+            joint_wallet.total_withdrawn = joint_wallet.total_withdrawn.saturating_add(balance);
+        }
+
+        ctx.accounts.stats.load_mut()?.on_status_changed(
+            &relationship_data.relationship_type()?,
+            &relationship_data.status()?,
+            &RelationshipStatus::Ended,
+            now,
+        );
+        relationship_data.status = RelationshipStatus::Ended as u8;
+
+        emit_cpi!(RelationshipEnded {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            ended_by: owner,
+            owner_one_payout,
+            owner_two_payout,
+        });
+
+        Ok(())
+    }
+
+    // Hand the config admin role to another key, such as the admin
+    // multisig's authority PDA
+    pub fn set_config_admin(
+        ctx: Context<UpdateConfig>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_admin = config.admin;
+        config.admin = admin;
+
+        emit_cpi!(ConfigAdminUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            previous_admin,
+            new_admin: admin,
+        });
+
+        Ok(())
+    }
+
+    // Set the arbiter who resolves disputes
+    pub fn set_arbiter(
+        ctx: Context<UpdateConfig>,
+        arbiter: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_arbiter = config.arbiter;
+        config.arbiter = arbiter;
+
+        emit_cpi!(ArbiterUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            previous_arbiter,
+            new_arbiter: arbiter,
+        });
+
+        Ok(())
+    }
+
+    // Open a dispute against the other owner, either on the relationship as a
+    // whole or on a specific interaction (e.g. a milestone)
+    pub fn open_dispute(
+        ctx: Context<OpenDispute>,
+        target: DisputeTarget,
+        reason_code: u8,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let opener = ctx.accounts.opener.key();
+
+        let respondent = if opener == relationship_data.owner_one {
+            relationship_data.owner_two
+        } else if opener == relationship_data.owner_two {
+            relationship_data.owner_one
+        } else {
+            return err!(RelationshipError::Unauthorized);
+        };
+
+        let now = Clock::get()?.unix_timestamp;
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.relationship = relationship_key;
+        dispute.dispute_index = relationship_data.dispute_count;
+        dispute.target = target.clone();
+        dispute.opener = opener;
+        dispute.respondent = respondent;
+        dispute.reason_code = reason_code;
+        dispute.evidence_count = 1;
+        dispute.last_evidence_hash = evidence_hash;
+        dispute.status = DisputeStatus::Open;
+        dispute.ruling = DisputeRuling::Pending;
+        dispute.opened_at = now;
+        dispute.resolved_at = 0;
+
+        // The count seeds the next dispute's address, so it must not saturate
+        relationship_data.dispute_count = relationship_data
+            .dispute_count
+            .checked_add(1)
+            .ok_or(RelationshipError::ArithmeticOverflow)?;
+
+        emit_cpi!(DisputeOpened {
+            version: EVENT_SCHEMA_VERSION,
+            dispute_id: dispute.key(),
+            relationship_id: relationship_key,
+            target,
+            opener,
+            respondent,
+            reason_code,
+            evidence_hash,
+        });
+
+        Ok(())
+    }
+
+    // Attach an evidence hash to an open dispute (either party)
+    pub fn submit_evidence(
+        ctx: Context<SubmitEvidence>,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let dispute = &mut ctx.accounts.dispute;
+        let submitter = ctx.accounts.submitter.key();
+
+        require!(
+            dispute.status == DisputeStatus::Open,
+            RelationshipError::DisputeNotOpen
+        );
+        require!(
+            submitter == dispute.opener || submitter == dispute.respondent,
+            RelationshipError::Unauthorized
+        );
+
+        dispute.evidence_count = dispute.evidence_count.checked_add(1).unwrap_or(u16::MAX);
+        dispute.last_evidence_hash = evidence_hash;
+
+        emit_cpi!(EvidenceSubmitted {
+            version: EVENT_SCHEMA_VERSION,
+            dispute_id: dispute.key(),
+            submitter,
+            evidence_hash,
+            evidence_index: dispute.evidence_count - 1,
+        });
+
+        Ok(())
+    }
+
+    // Resolve a dispute (arbiter only). The resolution may adjust relationship
+    // health, slash the at-fault side's stake in a scheduled date, and force a
+    // status change.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        resolution: DisputeResolution,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let dispute = &mut ctx.accounts.dispute;
+
+        require!(
+            dispute.status == DisputeStatus::Open,
+            RelationshipError::DisputeNotOpen
+        );
+        require!(
+            resolution.ruling != DisputeRuling::Pending,
+            RelationshipError::InvalidDisputeResolution
+        );
+
+        // Adjust health
+        let health = adjust_health(relationship_data.health, resolution.health_delta);
+        relationship_data.health = health;
+
+        // Slash the at-fault side's date stake to the treasury
+        let mut slashed_amount = 0;
+        if resolution.slash_date_stake {
+            let at_fault = match resolution.ruling {
+                DisputeRuling::OpenerFavored => dispute.respondent,
+                DisputeRuling::RespondentFavored => dispute.opener,
+                _ => return err!(RelationshipError::InvalidDisputeResolution),
+            };
+            let (Some(date_data), Some(escrow_authority), Some(escrow_one), Some(vault_one), Some(owner_one), Some(escrow_two), Some(vault_two), Some(owner_two), Some(escrow_program), Some(treasury), Some(owner_one_token_account), Some(owner_two_token_account), Some(token_program)) = (
+                &mut ctx.accounts.date_data,
+                &ctx.accounts.escrow_authority,
+                &ctx.accounts.escrow_one,
+                &ctx.accounts.vault_one,
+                &ctx.accounts.owner_one,
+                &ctx.accounts.escrow_two,
+                &ctx.accounts.vault_two,
+                &ctx.accounts.owner_two,
+                &ctx.accounts.escrow_program,
+                &ctx.accounts.treasury,
+                &ctx.accounts.owner_one_token_account,
+                &ctx.accounts.owner_two_token_account,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(RelationshipError::MissingDateAccounts);
+            };
+            require!(
+                date_data.relationship == relationship_key,
+                RelationshipError::DateMismatch
+            );
+            require!(
+                date_data.status == DateStatus::Scheduled,
+                RelationshipError::DateNotScheduled
+            );
+            require!(
+                treasury.key() == ctx.accounts.config.treasury,
+                RelationshipError::Unauthorized
+            );
+            require!(
+                owner_one_token_account.owner == relationship_data.owner_one
+                    && owner_two_token_account.owner == relationship_data.owner_two,
+                RelationshipError::Unauthorized
+            );
+            require!(
+                is_stake(escrow_one, &date_data.key(), &relationship_data.owner_one)
+                    && is_stake(escrow_two, &date_data.key(), &relationship_data.owner_two),
+                RelationshipError::DateMismatch
+            );
+
+            let stakes = StakeSettlement {
+                escrow_authority,
+                escrow_program,
+                token_program,
+                one: Stake {
+                    escrow: escrow_one,
+                    vault: vault_one,
+                    owner: owner_one,
+                    token_account: owner_one_token_account,
+                },
+                two: Stake {
+                    escrow: escrow_two,
+                    vault: vault_two,
+                    owner: owner_two,
+                    token_account: owner_two_token_account,
+                },
+            };
+            let innocent = if at_fault == relationship_data.owner_one {
+                relationship_data.owner_two
+            } else {
+                relationship_data.owner_one
+            };
+            slashed_amount = date_data.stake_amount;
+            stakes.refund(&innocent, date_data.stake_amount)?;
+            stakes.pay(&at_fault, treasury, slashed_amount)?;
+            date_data.status = DateStatus::Slashed;
+            date_data.absent_owner = at_fault;
+        }
+
+        // Force a status change
+        if let Some(status) = resolution.new_status.clone() {
+            ctx.accounts.stats.load_mut()?.on_status_changed(
+                &relationship_data.relationship_type()?,
+                &relationship_data.status()?,
+                &status,
+                Clock::get()?.unix_timestamp,
+            );
+            relationship_data.status = status as u8;
+        }
+
+        dispute.status = DisputeStatus::Resolved;
+        dispute.ruling = resolution.ruling.clone();
+        dispute.resolved_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(DisputeResolved {
+            version: EVENT_SCHEMA_VERSION,
+            dispute_id: dispute.key(),
+            relationship_id: relationship_key,
+            arbiter: ctx.accounts.arbiter.key(),
+            ruling: resolution.ruling,
+            health,
+            slashed_amount,
+            new_status: resolution.new_status,
+        });
+
+        Ok(())
+    }
+
+    // Set the matchmaker commission and the interaction count at which it is paid
+    pub fn set_matchmaker_params(
+        ctx: Context<UpdateConfig>,
+        matchmaker_params: MatchmakerParams,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.matchmaker_params = matchmaker_params.clone();
+
+        emit_cpi!(MatchmakerParamsUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            commission_amount: matchmaker_params.commission_amount,
+            commission_interaction_threshold: matchmaker_params.commission_interaction_threshold,
+        });
+
+        Ok(())
+    }
+
+    // Register the signer as a matchmaker
+    pub fn register_matchmaker(ctx: Context<RegisterMatchmaker>) -> Result<()> {
+        let matchmaker_stats = &mut ctx.accounts.matchmaker_stats;
+        matchmaker_stats.authority = ctx.accounts.authority.key();
+        matchmaker_stats.proposals_created = 0;
+        matchmaker_stats.relationships_formed = 0;
+        matchmaker_stats.commissions_paid = 0;
+        matchmaker_stats.total_commission = 0;
+        matchmaker_stats.registered_at = Clock::get()?.unix_timestamp;
+        matchmaker_stats.bump = *ctx.bumps.get("matchmaker_stats").unwrap();
+
+        emit_cpi!(MatchmakerRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            matchmaker: matchmaker_stats.authority,
+        });
+
+        Ok(())
+    }
+
+    // Propose a relationship between two agents as a registered matchmaker
+    pub fn propose_relationship(
+        ctx: Context<ProposeRelationship>,
+        relationship_type: RelationshipType,
+        compatibility_score: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_one.key() < ctx.accounts.agent_two.key(),
+            RelationshipError::NonCanonicalPair
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.matchmaker = ctx.accounts.matchmaker.key();
+        proposal.agent_one = ctx.accounts.agent_one.key();
+        proposal.agent_two = ctx.accounts.agent_two.key();
+        proposal.relationship_type = relationship_type.clone();
+        proposal.compatibility_score = compatibility_score;
+        proposal.created_at = Clock::get()?.unix_timestamp;
+
+        let matchmaker_stats = &mut ctx.accounts.matchmaker_stats;
+        matchmaker_stats.proposals_created = matchmaker_stats.proposals_created.checked_add(1).unwrap_or(u32::MAX);
+
+        emit_cpi!(RelationshipProposed {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            matchmaker: proposal.matchmaker,
+            agent_one: proposal.agent_one,
+            agent_two: proposal.agent_two,
+            relationship_type,
+        });
+
+        Ok(())
+    }
+
+    // Accept a matchmaker's proposal. Both agent owners must sign; the
+    // relationship records the matchmaker for commission purposes.
+    pub fn accept_relationship_proposal(ctx: Context<AcceptRelationshipProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_init()?;
+
+        initialize_relationship(
+            &mut relationship_data,
+            &ctx.accounts.agent_one,
+            &ctx.accounts.agent_two,
+            proposal.relationship_type.clone(),
+            proposal.compatibility_score,
+        )?;
+        if let Some(template) = &ctx.accounts.template {
+            apply_template(&mut relationship_data, template)?;
+        }
+        relationship_data.matchmaker = proposal.matchmaker;
+        let interaction_nonce = &mut ctx.accounts.interaction_nonce;
+        interaction_nonce.relationship = relationship_key;
+        interaction_nonce.next_index = 0;
+        interaction_nonce.bump = *ctx.bumps.get("interaction_nonce").unwrap();
+        ctx.accounts.stats.load_mut()?.on_created(
+            &proposal.relationship_type,
+            proposal.compatibility_score,
+            relationship_data.creation_date,
+        );
+
+        let matchmaker_stats = &mut ctx.accounts.matchmaker_stats;
+        matchmaker_stats.relationships_formed = matchmaker_stats.relationships_formed.checked_add(1).unwrap_or(u32::MAX);
+
+        emit_cpi!(RelationshipCreated {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            agent_one: relationship_data.agent_one,
+            agent_two: relationship_data.agent_two,
+            relationship_type: proposal.relationship_type.clone(),
+        });
+
+        emit_cpi!(RelationshipProposalAccepted {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            relationship_id: relationship_key,
+            matchmaker: proposal.matchmaker,
+        });
+
+        Ok(())
+    }
+
+    // Decline a matchmaker's proposal (either agent owner)
+    pub fn decline_relationship_proposal(ctx: Context<DeclineRelationshipProposal>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == ctx.accounts.agent_one.owner || owner == ctx.accounts.agent_two.owner,
+            RelationshipError::Unauthorized
+        );
+
+        emit_cpi!(RelationshipProposalDeclined {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: ctx.accounts.proposal.key(),
+            declined_by: owner,
+        });
+
+        Ok(())
+    }
+
+    // Pay an outstanding matchmaker commission (permissionless). Covers
+    // interactions recorded without the optional commission accounts.
+    pub fn claim_matchmaker_commission(ctx: Context<ClaimMatchmakerCommission>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let amount = pay_matchmaker_commission(
+            &ctx.accounts.config,
+            &mut relationship_data,
+            &mut ctx.accounts.matchmaker_stats,
+            &ctx.accounts.rewards_vault,
+            &ctx.accounts.matchmaker_token_account,
+            &ctx.accounts.token_program,
+        )?;
+        require!(amount > 0, RelationshipError::CommissionNotDue);
+
+        emit_cpi!(MatchmakerCommissionPaid {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            matchmaker: relationship_data.matchmaker,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // Set the relationship's visibility. Both owners must sign.
+    pub fn set_visibility(
+        ctx: Context<MutualConsent>,
+        visibility: Visibility,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        relationship_data.visibility = visibility as u8;
+
+        emit_cpi!(VisibilityChanged {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            visibility,
+        });
+
+        Ok(())
+    }
+
+    // Initialize the global relationship statistics account
+    pub fn initialize_stats(ctx: Context<InitializeStats>) -> Result<()> {
+        let mut stats = ctx.accounts.stats.load_init()?;
+        stats.last_updated = Clock::get()?.unix_timestamp;
+        stats.bump = *ctx.bumps.get("stats").unwrap();
+
+        Ok(())
+    }
+
+    // Create the interaction nonce for a relationship created before nonces
+    // existed, continuing from its current interaction count (permissionless)
+    pub fn initialize_interaction_nonce(ctx: Context<InitializeInteractionNonce>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let interaction_nonce = &mut ctx.accounts.interaction_nonce;
+        interaction_nonce.relationship = relationship_key;
+        interaction_nonce.next_index = relationship_data.interaction_count as u64;
+        interaction_nonce.bump = *ctx.bumps.get("interaction_nonce").unwrap();
+
+        Ok(())
+    }
+
+    // Create the global interaction hook registry
+    pub fn initialize_hook_registry(ctx: Context<InitializeHookRegistry>) -> Result<()> {
+        let hook_registry = &mut ctx.accounts.hook_registry;
+        hook_registry.hooks = Vec::new();
+        hook_registry.bump = *ctx.bumps.get("hook_registry").unwrap();
+
+        Ok(())
+    }
+
+    // Register a program to be notified of every recorded interaction. The
+    // program receives `extra_accounts` accounts from the caller's remaining
+    // accounts in addition to the standard hook accounts.
+    pub fn add_interaction_hook(
+        ctx: Context<ManageHooks>,
+        program_id: Pubkey,
+        extra_accounts: u8,
+    ) -> Result<()> {
+        let hook_registry = &mut ctx.accounts.hook_registry;
+        require!(
+            hook_registry.hooks.len() < MAX_INTERACTION_HOOKS,
+            RelationshipError::TooManyHooks
+        );
+        require!(
+            !hook_registry.hooks.iter().any(|hook| hook.program_id == program_id),
+            RelationshipError::HookAlreadyRegistered
+        );
+        require!(program_id != crate::ID, RelationshipError::InvalidHookProgram);
+
+        hook_registry.hooks.push(InteractionHook {
+            program_id,
+            extra_accounts,
+        });
+
+        emit_cpi!(InteractionHookAdded {
+            version: EVENT_SCHEMA_VERSION,
+            program_id,
+            extra_accounts,
+        });
+
+        Ok(())
+    }
+
+    // Unregister an interaction hook program
+    pub fn remove_interaction_hook(
+        ctx: Context<ManageHooks>,
+        program_id: Pubkey,
+    ) -> Result<()> {
+        let hook_registry = &mut ctx.accounts.hook_registry;
+        let position = hook_registry
+            .hooks
+            .iter()
+            .position(|hook| hook.program_id == program_id)
+            .ok_or(RelationshipError::HookNotRegistered)?;
+        hook_registry.hooks.remove(position);
+
+        emit_cpi!(InteractionHookRemoved {
+            version: EVENT_SCHEMA_VERSION,
+            program_id,
+        });
+
+        Ok(())
+    }
+
+    // Recompute the compatibility score from both agents' current traits
+    // (permissionless) and append the change to the score history
+    pub fn refresh_compatibility(ctx: Context<RefreshCompatibility>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let new_score = calculate_compatibility_score(
+            &ctx.accounts.agent_one.personality_traits,
+            &ctx.accounts.agent_two.personality_traits,
+        );
+        let previous_score = relationship_data.compatibility_score;
+        let now = Clock::get()?.unix_timestamp;
+
+        let history = &mut ctx.accounts.compatibility_history;
+        if history.relationship == Pubkey::default() {
+            history.relationship = relationship_key;
+        }
+        if history.entries.len() >= MAX_SCORE_HISTORY {
+            history.entries.remove(0);
+        }
+        history.entries.push(ScoreChange {
+            previous_score,
+            new_score,
+            timestamp: now,
+        });
+        history.total_changes = history.total_changes.checked_add(1).unwrap_or(u32::MAX);
+
+        relationship_data.compatibility_score = new_score;
+
+        emit_cpi!(CompatibilityRefreshed {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            previous_score,
+            new_score,
+        });
+
+        Ok(())
+    }
+
+    // Merge a duplicate relationship (the same pair stored in reverse order,
+    // created before pairs were canonically ordered) into the canonical
+    // account and close the duplicate. Requires both owners or the admin.
+    // Accounts still on the legacy layout must go through
+    // `migrate_relationship_layout` first, which can be in the same
+    // transaction.
+    pub fn merge_relationships(ctx: Context<MergeRelationships>) -> Result<()> {
+        let canonical_key = ctx.accounts.canonical.key();
+        let duplicate_key = ctx.accounts.duplicate.key();
+        let mut canonical = ctx.accounts.canonical.load_mut()?;
+        let duplicate = ctx.accounts.duplicate.load()?;
+        let authority = ctx.accounts.authority.key();
+
+        require!(
+            canonical.agent_one < canonical.agent_two
+                && duplicate.agent_one == canonical.agent_two
+                && duplicate.agent_two == canonical.agent_one,
+            RelationshipError::NotDuplicatePair
+        );
+
+        if authority != ctx.accounts.config.admin {
+            let co_signer = ctx
+                .accounts
+                .co_signer
+                .as_ref()
+                .map(|signer| signer.key())
+                .ok_or(RelationshipError::Unauthorized)?;
+            let owners = [canonical.owner_one, canonical.owner_two];
+            require!(
+                owners.contains(&authority) && owners.contains(&co_signer) && authority != co_signer,
+                RelationshipError::Unauthorized
+            );
+        }
+
+        canonical.interaction_count = canonical.interaction_count.saturating_add(duplicate.interaction_count);
+        canonical.milestone_count = canonical.milestone_count.saturating_add(duplicate.milestone_count);
+        canonical.creation_date = canonical.creation_date.min(duplicate.creation_date);
+        canonical.last_interaction = canonical.last_interaction.max(duplicate.last_interaction);
+
+        // The duplicate no longer counts towards protocol stats
+        let now = Clock::get()?.unix_timestamp;
+        let mut stats = ctx.accounts.stats.load_mut()?;
+        stats.on_status_changed(
+            &duplicate.relationship_type()?,
+            &duplicate.status()?,
+            &RelationshipStatus::Ended,
+            now,
+        );
+        stats.total_relationships = stats.total_relationships.saturating_sub(1);
+        stats.compatibility_sum = stats
+            .compatibility_sum
+            .saturating_sub(duplicate.compatibility_score as u64);
+
+        emit_cpi!(RelationshipsMerged {
+            version: EVENT_SCHEMA_VERSION,
+            canonical_id: canonical_key,
+            duplicate_id: duplicate_key,
+            merged_by: authority,
+            interaction_count: canonical.interaction_count,
+            creation_date: canonical.creation_date,
+        });
+
+        Ok(())
+    }
+
+    // Set the Influence bonus paid to each owner when a reward-eligible goal
+    // is completed, and the smallest target that may earn it
+    pub fn set_goal_params(ctx: Context<UpdateConfig>, goal_params: GoalParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.goal_params = goal_params.clone();
+
+        emit_cpi!(GoalParamsUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            completion_bonus: goal_params.completion_bonus,
+            min_reward_target: goal_params.min_reward_target,
+        });
+
+        Ok(())
+    }
+
+    // Set interaction rate limits (admin). A zero window or maximum disables
+    // the corresponding limit.
+    pub fn set_rate_limits(ctx: Context<UpdateConfig>, rate_limits: RateLimitParams) -> Result<()> {
+        require!(rate_limits.window >= 0, RelationshipError::InvalidRateLimits);
+        let config = &mut ctx.accounts.config;
+        config.rate_limits = rate_limits.clone();
+
+        emit_cpi!(RateLimitsUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            window: rate_limits.window,
+            max_per_relationship: rate_limits.max_per_relationship,
+            max_per_recorder: rate_limits.max_per_recorder,
+        });
+
+        Ok(())
+    }
+
+    // Set a goal for the relationship; both owners must sign. The target is
+    // measured from the counter's value when the goal is set.
+    pub fn set_goal(
+        ctx: Context<SetGoal>,
+        description_hash: [u8; 32],
+        metric: GoalMetric,
+        target: u64,
+        deadline: i64,
+        reward_eligible: bool,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            relationship_data.status()? != RelationshipStatus::Ended,
+            RelationshipError::InactiveRelationship
+        );
+        require!(target > 0, RelationshipError::InvalidAmount);
+        require!(deadline > now, RelationshipError::InvalidDateTime);
+        if reward_eligible {
+            require!(
+                target >= ctx.accounts.config.goal_params.min_reward_target,
+                RelationshipError::GoalTargetTooLow
+            );
+        }
+
+        let goal = &mut ctx.accounts.goal;
+        goal.relationship = relationship_key;
+        goal.goal_index = relationship_data.goal_count;
+        goal.description_hash = description_hash;
+        goal.metric = metric.clone();
+        goal.start_value = metric.current_value(&relationship_data);
+        goal.target = target;
+        goal.deadline = deadline;
+        goal.reward_eligible = reward_eligible;
+        goal.status = GoalStatus::Active;
+        goal.created_at = now;
+        goal.completed_at = 0;
+        goal.bump = *ctx.bumps.get("goal").unwrap();
+
+        // The count seeds the next goal's address, so it must not saturate
+        relationship_data.goal_count = relationship_data
+            .goal_count
+            .checked_add(1)
+            .ok_or(RelationshipError::ArithmeticOverflow)?;
+
+        emit_cpi!(GoalSet {
+            version: EVENT_SCHEMA_VERSION,
+            goal_id: goal.key(),
+            relationship_id: relationship_key,
+            metric,
+            target,
+            deadline,
+            reward_eligible,
+        });
+
+        Ok(())
+    }
+
+    // Complete a goal once its counter has reached the target before the
+    // deadline. Permissionless, so anyone may crank it; a goal checked after
+    // its deadline is marked expired.
+    pub fn complete_goal(ctx: Context<CompleteGoal>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let goal = &mut ctx.accounts.goal;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(goal.status == GoalStatus::Active, RelationshipError::GoalNotActive);
+
+        let progress = goal
+            .metric
+            .current_value(&relationship_data)
+            .saturating_sub(goal.start_value);
+        // Counters keep no history, so a goal checked after its deadline
+        // cannot be shown to have been reached in time
+        if now > goal.deadline {
+            goal.status = GoalStatus::Expired;
+
+            emit_cpi!(GoalExpired {
+                version: EVENT_SCHEMA_VERSION,
+                goal_id: goal.key(),
+                relationship_id: relationship_key,
+                progress,
+            });
+
+            return Ok(());
+        }
+        require!(progress >= goal.target, RelationshipError::GoalNotReached);
+
+        // Pay the completion bonus, limited to what the vault holds
+        let completion_bonus = match relationship_template(&relationship_data, &ctx.accounts.template)? {
+            Some(template) => template.params.rewards.goal_completion_bonus,
+            None => config.goal_params.completion_bonus,
+        };
+        let mut bonus = 0;
+        if goal.reward_eligible && completion_bonus > 0 {
+            let (Some(rewards_vault), Some(owner_one_token_account), Some(owner_two_token_account), Some(token_program)) = (
+                &ctx.accounts.rewards_vault,
+                &ctx.accounts.owner_one_token_account,
+                &ctx.accounts.owner_two_token_account,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(RelationshipError::MissingRewardAccounts);
+            };
+            require!(
+                rewards_vault.key() == config.rewards_vault,
+                RelationshipError::MissingRewardAccounts
+            );
+            require!(
+                owner_one_token_account.owner == relationship_data.owner_one
+                    && owner_two_token_account.owner == relationship_data.owner_two
+                    && owner_one_token_account.mint == config.influence_mint
+                    && owner_two_token_account.mint == config.influence_mint,
+                RelationshipError::Unauthorized
+            );
+
+            bonus = completion_bonus.min(rewards_vault.amount / 2);
+            if bonus > 0 {
+                let config_seeds: &[&[u8]] = &[b"config", &[config.bump]];
+                for to in [owner_one_token_account, owner_two_token_account] {
+                    pda_transfer(
+                        token_program,
+                        rewards_vault,
+                        to,
+                        config.to_account_info(),
+                        config_seeds,
+                        bonus,
+                    )?;
+                }
+            }
+        }
+
+        goal.status = GoalStatus::Completed;
+        goal.completed_at = now;
+
+        emit_cpi!(GoalCompleted {
+            version: EVENT_SCHEMA_VERSION,
+            goal_id: goal.key(),
+            relationship_id: relationship_key,
+            bonus,
+        });
+
+        Ok(())
+    }
+
+    // Anchor the hash of an off-chain (encrypted) conversation chunk. Each
+    // segment is chained to the previous head, so any rewritten or dropped
+    // chunk breaks every later hash. Content is never stored on-chain.
+    pub fn anchor_transcript(
+        ctx: Context<AnchorTranscript>,
+        segment_index: u64,
+        chunk_hash: [u8; 32],
+        byte_length: u32,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let recorder = ctx.accounts.recorder.key();
+
+        require!(
+            recorder == relationship_data.owner_one || recorder == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(byte_length > 0, RelationshipError::InvalidAmount);
+
+        let transcript = &mut ctx.accounts.transcript;
+        if transcript.relationship == Pubkey::default() {
+            transcript.relationship = relationship_key;
+            transcript.bump = *ctx.bumps.get("transcript").unwrap();
+        }
+        require!(
+            segment_index == transcript.segment_count,
+            RelationshipError::StaleTranscriptSegment
+        );
+
+        let previous_hash = transcript.head_hash;
+        let segment_hash = transcript_segment_hash(&previous_hash, segment_index, &chunk_hash, byte_length);
+        let now = Clock::get()?.unix_timestamp;
+
+        let segment = &mut ctx.accounts.segment;
+        segment.transcript = transcript.key();
+        segment.segment_index = segment_index;
+        segment.chunk_hash = chunk_hash;
+        segment.byte_length = byte_length;
+        segment.previous_hash = previous_hash;
+        segment.segment_hash = segment_hash;
+        segment.recorder = recorder;
+        segment.timestamp = now;
+
+        transcript.head_hash = segment_hash;
+        transcript.segment_count = transcript.segment_count.saturating_add(1);
+        transcript.total_bytes = transcript.total_bytes.saturating_add(byte_length as u64);
+        transcript.last_anchored = now;
+
+        emit_cpi!(TranscriptAnchored {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            segment_index,
+            chunk_hash,
+            byte_length,
+            segment_hash,
+            recorder,
+        });
+
+        Ok(())
+    }
+
+    // Attest, as the owner of a third registered agent, to having witnessed
+    // an interaction (e.g. a group event). The first witness of an interaction
+    // raises the relationship's health and counts it as witnessed.
+    pub fn witness_interaction(ctx: Context<WitnessInteraction>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let interaction = &mut ctx.accounts.interaction_data;
+        let witness_agent = ctx.accounts.witness_agent.key();
+        let witness_owner = ctx.accounts.witness_owner.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            relationship_data.status()? == RelationshipStatus::Active,
+            RelationshipError::InactiveRelationship
+        );
+        require!(
+            witness_agent != relationship_data.agent_one
+                && witness_agent != relationship_data.agent_two
+                && witness_owner != relationship_data.owner_one
+                && witness_owner != relationship_data.owner_two,
+            RelationshipError::InvalidWitness
+        );
+        require!(
+            now <= interaction.timestamp.saturating_add(WITNESS_WINDOW),
+            RelationshipError::WitnessWindowClosed
+        );
+
+        let witness = &mut ctx.accounts.witness;
+        witness.interaction = interaction.key();
+        witness.witness_agent = witness_agent;
+        witness.witness_owner = witness_owner;
+        witness.timestamp = now;
+
+        if interaction.witness_count == 0 {
+            relationship_data.witnessed_count = relationship_data.witnessed_count.checked_add(1).unwrap_or(u32::MAX);
+            relationship_data.health = add_health_bonus(relationship_data.health, WITNESS_HEALTH_BONUS);
+        }
+        interaction.witness_count = interaction.witness_count.checked_add(1).unwrap_or(u16::MAX);
+
+        emit_cpi!(InteractionWitnessed {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            interaction_id: interaction.key(),
+            witness_agent,
+            witness_count: interaction.witness_count,
+            health: relationship_data.health,
+        });
+
+        Ok(())
+    }
+
+    // Decay health for each full inactive period since the last interaction
+    // or decay. Permissionless; normally run by a Clockwork thread.
+    pub fn decay_inactivity(ctx: Context<CrankRelationship>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        if relationship_data.status()? != RelationshipStatus::Active {
+            return Ok(());
+        }
+        let decay_params = match relationship_template(&relationship_data, &ctx.accounts.template)? {
+            Some(template) => template.params.decay.clone(),
+            None => DecayParams::default(),
+        };
+        let since = relationship_data.last_interaction.max(relationship_data.last_decay_at);
+        let decay = match decay_health(
+            relationship_data.health,
+            since,
+            now,
+            decay_params.inactivity_period,
+            decay_params.decay_per_period,
+        ) {
+            Some(decay) => decay,
+            None => return Ok(()),
+        };
+        relationship_data.health = decay.health;
+        relationship_data.last_decay_at = decay.last_decay_at;
+
+        emit_cpi!(HealthDecayed {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            periods: decay.periods as u32,
+            health: relationship_data.health,
+        });
+
+        Ok(())
+    }
+
+    // Post any anniversary reached since the last one. Permissionless;
+    // normally run by a Clockwork thread.
+    pub fn post_anniversary(ctx: Context<CrankRelationship>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        if relationship_data.status()? == RelationshipStatus::Ended {
+            return Ok(());
+        }
+        let years = (now.saturating_sub(relationship_data.creation_date) / SECONDS_PER_YEAR)
+            .clamp(0, u16::MAX as i64) as u16;
+        if years <= relationship_data.anniversaries_posted {
+            return Ok(());
+        }
+        relationship_data.anniversaries_posted = years;
+
+        emit_cpi!(AnniversaryReached {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            agent_one: relationship_data.agent_one,
+            agent_two: relationship_data.agent_two,
+            years,
+        });
+
+        Ok(())
+    }
+
+    // Create a Clockwork thread that runs a scheduled operation for the
+    // relationship. Threads are owned by the relationship's automation PDA and
+    // funded by the calling owner with `fee_lamports`.
+    pub fn register_automation(
+        ctx: Context<RegisterAutomation>,
+        kind: AutomationKind,
+        fee_lamports: u64,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == relationship_data.owner_one || owner == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+
+        let automation_key = ctx.accounts.automation.key();
+        let event_authority = ctx.accounts.event_authority.key();
+        let guardian = ctx.accounts.guardian.key();
+        let template = (relationship_data.template_id != 0).then(|| {
+            Pubkey::find_program_address(&[b"template", &relationship_data.template_id.to_le_bytes()], &crate::ID).0
+        });
+        let crank_accounts = crate::accounts::CrankRelationship {
+            relationship_data: relationship_key,
+            template,
+            guardian,
+            event_authority,
+            program: crate::ID,
+        }
+        .to_account_metas(None);
+
+        let (thread_id, instruction, trigger) = match &kind {
+            AutomationKind::InactivityDecay => (
+                b"decay".to_vec(),
+                Instruction {
+                    program_id: crate::ID,
+                    accounts: crank_accounts,
+                    data: crate::instruction::DecayInactivity {}.data(),
+                },
+                Trigger::Cron {
+                    schedule: DAILY_SCHEDULE.to_string(),
+                    skippable: true,
+                },
+            ),
+            AutomationKind::Anniversary => (
+                b"anniversary".to_vec(),
+                Instruction {
+                    program_id: crate::ID,
+                    accounts: crank_accounts,
+                    data: crate::instruction::PostAnniversary {}.data(),
+                },
+                Trigger::Cron {
+                    schedule: DAILY_SCHEDULE.to_string(),
+                    skippable: true,
+                },
+            ),
+            AutomationKind::DateSettlement {
+                owner_one_token_account,
+                owner_two_token_account,
+            } => {
+                let date_data = ctx
+                    .accounts
+                    .date_data
+                    .as_ref()
+                    .ok_or(RelationshipError::MissingDateAccounts)?;
+                require!(
+                    date_data.status == DateStatus::NoShowReported,
+                    RelationshipError::NoShowNotReported
+                );
+
+                // The thread signs as the settling authority once the
+                // dispute window has passed
+                let mut thread_id = b"date".to_vec();
+                thread_id.extend_from_slice(&date_data.date_index.to_le_bytes());
+                let thread = Thread::pubkey(automation_key, thread_id.clone());
+                let config = &ctx.accounts.config;
+                let settle_accounts = crate::accounts::SettleNoShow {
+                    authority: thread,
+                    config: config.key(),
+                    relationship_data: relationship_key,
+                    date_data: date_data.key(),
+                    stakes: date_stake_addresses(
+                        &date_data.key(),
+                        &relationship_data.owner_one,
+                        &relationship_data.owner_two,
+                    ),
+                    treasury: config.treasury,
+                    owner_one_token_account: *owner_one_token_account,
+                    owner_two_token_account: *owner_two_token_account,
+                    token_program: token::ID,
+                    guardian,
+                    event_authority,
+                    program: crate::ID,
+                }
+                .to_account_metas(None);
+                (
+                    thread_id,
+                    Instruction {
+                        program_id: crate::ID,
+                        accounts: settle_accounts,
+                        data: crate::instruction::SettleNoShow { uphold: true }.data(),
+                    },
+                    Trigger::Timestamp {
+                        unix_ts: date_data
+                            .no_show_reported_at
+                            .saturating_add(config.date_params.dispute_window),
+                    },
+                )
+            }
+        };
+
+        let thread = Thread::pubkey(automation_key, thread_id.clone());
+        require!(
+            ctx.accounts.thread.key() == thread,
+            RelationshipError::InvalidAutomationThread
+        );
+
+        let automation_seeds: &[&[u8]] = &[
+            b"automation",
+            relationship_key.as_ref(),
+            &[*ctx.bumps.get("automation").unwrap()],
+        ];
+        clockwork_sdk::cpi::thread_create(
+            CpiContext::new_with_signer(
+                ctx.accounts.clockwork_program.to_account_info(),
+                clockwork_sdk::cpi::ThreadCreate {
+                    authority: ctx.accounts.automation.to_account_info(),
+                    payer: ctx.accounts.owner.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    thread: ctx.accounts.thread.to_account_info(),
+                },
+                &[automation_seeds],
+            ),
+            fee_lamports,
+            thread_id,
+            vec![instruction.into()],
+            trigger,
+        )?;
+
+        emit_cpi!(AutomationRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            thread,
+            kind,
+            registered_by: owner,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite a relationship created with the original Borsh layout into the
+    // zero-copy layout. Owners are read from the agents' registry accounts;
+    // counters the old layout lacked start at zero and the rest take the
+    // defaults of a new relationship. Permissionless; the payer covers the
+    // extra rent.
+    pub fn migrate_relationship_layout(ctx: Context<MigrateRelationshipLayout>) -> Result<()> {
+        let account = ctx.accounts.relationship_data.to_account_info();
+        require!(account.owner == &crate::ID, RelationshipError::NotLegacyRelationship);
+
+        let legacy = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.len() == LEGACY_RELATIONSHIP_SPACE
+                    && data[..8] == RelationshipData::DISCRIMINATOR,
+                RelationshipError::NotLegacyRelationship
+            );
+            LegacyRelationshipData::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(ctx.accounts.agent_one.key(), legacy.agent_one, RelationshipError::AgentMismatch);
+        require_keys_eq!(ctx.accounts.agent_two.key(), legacy.agent_two, RelationshipError::AgentMismatch);
+
+        let new_space = 8 + std::mem::size_of::<RelationshipData>();
+        let rent_due = Rent::get()?
+            .minimum_balance(new_space)
+            .saturating_sub(account.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        account.realloc(new_space, true)?;
+
+        let mut relationship_data: RelationshipData = bytemuck::Zeroable::zeroed();
+        relationship_data.agent_one = legacy.agent_one;
+        relationship_data.agent_two = legacy.agent_two;
+        relationship_data.owner_one = ctx.accounts.agent_one.owner;
+        relationship_data.owner_two = ctx.accounts.agent_two.owner;
+        relationship_data.relationship_type = legacy.relationship_type as u8;
+        relationship_data.compatibility_score = legacy.compatibility_score;
+        relationship_data.creation_date = legacy.creation_date;
+        relationship_data.last_interaction = legacy.last_interaction;
+        relationship_data.interaction_count = legacy.interaction_count;
+        relationship_data.status = legacy.status as u8;
+        // Decay starts from the last interaction the old layout recorded
+        relationship_data.last_decay_at = legacy.last_interaction;
+        relationship_data.set_prenup(&PrenupRules::default());
+        relationship_data.health = MAX_HEALTH;
+        relationship_data.matchmaker = Pubkey::default();
+        relationship_data.visibility = Visibility::Public as u8;
+        relationship_data.layout_version = RELATIONSHIP_LAYOUT_VERSION;
+        account.try_borrow_mut_data()?[8..].copy_from_slice(bytemuck::bytes_of(&relationship_data));
+
+        emit_cpi!(RelationshipLayoutMigrated {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: account.key(),
+            layout_version: RELATIONSHIP_LAYOUT_VERSION,
+        });
+
+        Ok(())
+    }
+
+    // Create the concurrent merkle tree that stores a relationship's
+    // compressed interactions. The tree account must be allocated beforehand,
+    // owned by the compression program, for the given depth and buffer size.
+    pub fn create_interaction_tree(
+        ctx: Context<CreateInteractionTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let owner = ctx.accounts.owner.key();
+        require!(
+            owner == relationship_data.owner_one || owner == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+
+        let interaction_tree = &mut ctx.accounts.interaction_tree;
+        interaction_tree.relationship = relationship_key;
+        interaction_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+        interaction_tree.leaf_count = 0;
+        interaction_tree.bump = *ctx.bumps.get("interaction_tree").unwrap();
+
+        let tree_seeds: &[&[u8]] = &[
+            b"interaction_tree",
+            relationship_key.as_ref(),
+            &[interaction_tree.bump],
+        ];
+        spl_account_compression::cpi::init_empty_merkle_tree(
+            CpiContext::new_with_signer(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::Initialize {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    authority: interaction_tree.to_account_info(),
+                    noop: ctx.accounts.log_wrapper.to_account_info(),
+                },
+                &[tree_seeds],
+            ),
+            max_depth,
+            max_buffer_size,
+        )?;
+
+        emit_cpi!(InteractionTreeCreated {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            merkle_tree: interaction_tree.merkle_tree,
+            max_depth,
+            max_buffer_size,
+        });
+
+        Ok(())
+    }
+
+    // Record an interaction as a leaf of the relationship's merkle tree rather
+    // than its own account. The event carries the leaf data so indexers can
+    // rebuild the tree. Hooks are not dispatched, as there is no interaction
+    // account to pass them.
+    pub fn record_compressed_interaction(
+        ctx: Context<RecordCompressedInteraction>,
+        interaction_type: InteractionType,
+        interaction_data: String,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let authority = sessions::acting_owner(
+            &ctx.accounts.session,
+            &ctx.accounts.authority.key(),
+            SCOPE_RECORD_INTERACTIONS,
+        )?;
+
+        require!(
+            authority == relationship_data.owner_one || authority == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(
+            relationship_data.status()? == RelationshipStatus::Active,
+            RelationshipError::InactiveRelationship
+        );
+        require_not_frozen(&ctx.accounts.agent_one, &ctx.accounts.agent_two)?;
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let recorder_rate = &mut ctx.accounts.recorder_rate;
+        recorder_rate.recorder = authority;
+        recorder_rate.bump = *ctx.bumps.get("recorder_rate").unwrap();
+        enforce_rate_limits(
+            &ctx.accounts.config.rate_limits,
+            &mut relationship_data,
+            recorder_rate,
+            timestamp,
+        )?;
+
+        relationship_data.last_interaction = timestamp;
+        relationship_data.interaction_count = relationship_data.interaction_count.checked_add(1).unwrap_or(u32::MAX);
+        if interaction_type == InteractionType::Milestone {
+            relationship_data.milestone_count = relationship_data.milestone_count.checked_add(1).unwrap_or(u32::MAX);
+        }
+        ctx.accounts.stats.load_mut()?.on_interaction(timestamp);
+
+        let interaction_tree = &mut ctx.accounts.interaction_tree;
+        let leaf_index = interaction_tree.leaf_count;
+        let interaction_data_hash = hash(interaction_data.as_bytes()).to_bytes();
+        let leaf = interaction_leaf_hash(
+            &relationship_key,
+            leaf_index,
+            &interaction_type,
+            &interaction_data_hash,
+            timestamp,
+        );
+
+        let tree_seeds: &[&[u8]] = &[
+            b"interaction_tree",
+            relationship_key.as_ref(),
+            &[interaction_tree.bump],
+        ];
+        spl_account_compression::cpi::append(
+            CpiContext::new_with_signer(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::Modify {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    authority: interaction_tree.to_account_info(),
+                    noop: ctx.accounts.log_wrapper.to_account_info(),
+                },
+                &[tree_seeds],
+            ),
+            leaf,
+        )?;
+        interaction_tree.leaf_count = interaction_tree.leaf_count.saturating_add(1);
+
+        // Private relationships only expose a hash of the interaction data in events
+        let event_data = if relationship_data.visibility()? == Visibility::Private {
+            None
+        } else {
+            Some(interaction_data)
+        };
+
+        emit_cpi!(CompressedInteractionRecorded {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            merkle_tree: interaction_tree.merkle_tree,
+            leaf_index,
+            leaf,
+            interaction_type,
+            interaction_data: event_data,
+            interaction_data_hash,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Verify that a compressed interaction is in the relationship's tree.
+    // The proof nodes are passed as remaining accounts.
+    pub fn verify_interaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyInteraction<'info>>,
+        root: [u8; 32],
+        leaf_index: u32,
+        interaction_type: InteractionType,
+        interaction_data_hash: [u8; 32],
+        timestamp: i64,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.interaction_tree.relationship;
+        let leaf = interaction_leaf_hash(
+            &relationship_key,
+            leaf_index as u64,
+            &interaction_type,
+            &interaction_data_hash,
+            timestamp,
+        );
+
+        spl_account_compression::cpi::verify_leaf(
+            CpiContext::new(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::VerifyLeaf {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            root,
+            leaf,
+            leaf_index,
+        )?;
+
+        emit_cpi!(InteractionVerified {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            leaf_index,
+            leaf,
+        });
+
+        Ok(())
+    }
+
+    // Record an interaction as a Light Protocol compressed account rather
+    // than a PDA, for chatty agents where per-interaction rent adds up. The
+    // record takes the next interaction index and the address its PDA would
+    // have had. Like merkle-tree interactions, hooks are not dispatched and
+    // the record can't be witnessed. Light's trees and queues are passed as
+    // remaining accounts.
+    pub fn record_light_interaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordLightInteraction<'info>>,
+        interaction_type: InteractionType,
+        interaction_data: String,
+        light_args: NewRecordArgs,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let authority = sessions::acting_owner(
+            &ctx.accounts.session,
+            &ctx.accounts.authority.key(),
+            SCOPE_RECORD_INTERACTIONS,
+        )?;
+
+        require!(
+            authority == relationship_data.owner_one || authority == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(
+            relationship_data.status()? == RelationshipStatus::Active,
+            RelationshipError::InactiveRelationship
+        );
+        require_not_frozen(&ctx.accounts.agent_one, &ctx.accounts.agent_two)?;
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let recorder_rate = &mut ctx.accounts.recorder_rate;
+        recorder_rate.recorder = authority;
+        recorder_rate.bump = *ctx.bumps.get("recorder_rate").unwrap();
+        enforce_rate_limits(
+            &ctx.accounts.config.rate_limits,
+            &mut relationship_data,
+            recorder_rate,
+            timestamp,
+        )?;
+
+        let interaction_nonce = &mut ctx.accounts.interaction_nonce;
+        let interaction_index = interaction_nonce.next_index;
+        interaction_nonce.next_index = interaction_nonce.next_index.checked_add(1).unwrap_or(u64::MAX);
+
+        relationship_data.last_interaction = timestamp;
+        relationship_data.interaction_count = relationship_data.interaction_count.checked_add(1).unwrap_or(u32::MAX);
+        if interaction_type == InteractionType::Milestone {
+            relationship_data.milestone_count = relationship_data.milestone_count.checked_add(1).unwrap_or(u32::MAX);
+        }
+        ctx.accounts.stats.load_mut()?.on_interaction(timestamp);
+
+        let interaction_data_hash = hash(interaction_data.as_bytes()).to_bytes();
+        // Private relationships only expose a hash of the interaction data in events
+        let event_data = if relationship_data.visibility()? == Visibility::Private {
+            None
+        } else {
+            Some(interaction_data.clone())
+        };
+
+        let interaction = InteractionData {
+            relationship: relationship_key,
+            index: interaction_index,
+            interaction_type: interaction_type.clone(),
+            interaction_data,
+            timestamp,
+            witness_count: 0,
+            custom_type_id: 0,
+        };
+        let address = armour_light::create_record(
+            &ctx.accounts.light.cpi_accounts(ctx.accounts.authority.to_account_info()),
+            ctx.program_id,
+            ctx.remaining_accounts,
+            &light_args,
+            &[
+                b"interaction",
+                relationship_key.as_ref(),
+                &interaction_index.to_le_bytes(),
+            ],
+            &interaction,
+        )?;
+
+        emit_cpi!(LightInteractionRecorded {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            interaction_index,
+            address,
+            interaction_type,
+            interaction_data: event_data,
+            interaction_data_hash,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Verify that a Light compressed interaction exists with exactly this
+    // content, using a validity proof from the indexer. Light's trees and
+    // queues are passed as remaining accounts.
+    pub fn verify_light_interaction<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyLightInteraction<'info>>,
+        interaction: InteractionData,
+        proof_args: ExistingRecordArgs,
+    ) -> Result<()> {
+        armour_light::verify_record(
+            &ctx.accounts.light.cpi_accounts(ctx.accounts.payer.to_account_info()),
+            ctx.program_id,
+            ctx.remaining_accounts,
+            &proof_args,
+            &interaction,
+        )?;
+
+        emit_cpi!(LightInteractionVerified {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: interaction.relationship,
+            interaction_index: interaction.index,
+            address: proof_args.address,
+        });
+
+        Ok(())
+    }
+
+    // Create the admin-curated registry of approved custom type labels
+    pub fn initialize_custom_type_registry(ctx: Context<InitializeCustomTypeRegistry>) -> Result<()> {
+        let custom_type_registry = &mut ctx.accounts.custom_type_registry;
+        custom_type_registry.labels = Vec::new();
+        custom_type_registry.bump = *ctx.bumps.get("custom_type_registry").unwrap();
+
+        Ok(())
+    }
+
+    // Approve a custom relationship type label (e.g. "rivals", "bandmates").
+    // Relationships refer to it by the sha256 hash of the label.
+    pub fn add_custom_type(ctx: Context<ManageCustomTypes>, label: String) -> Result<()> {
+        require!(
+            !label.is_empty() && label.len() <= MAX_CUSTOM_LABEL_LEN,
+            RelationshipError::InvalidCustomLabel
+        );
+
+        let custom_type_registry = &mut ctx.accounts.custom_type_registry;
+        require!(
+            custom_type_registry.labels.len() < MAX_CUSTOM_TYPES,
+            RelationshipError::TooManyCustomTypes
+        );
+        let label_hash = hash(label.as_bytes()).to_bytes();
+        require!(
+            custom_type_registry.find(&label_hash).is_none(),
+            RelationshipError::CustomTypeAlreadyRegistered
+        );
+
+        custom_type_registry.labels.push(CustomTypeLabel {
+            label_hash,
+            label: label.clone(),
+        });
+
+        emit_cpi!(CustomTypeAdded {
+            version: EVENT_SCHEMA_VERSION,
+            label_hash,
+            label,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw approval for a custom type label. Relationships already using
+    // it keep their label hash.
+    pub fn remove_custom_type(ctx: Context<ManageCustomTypes>, label_hash: [u8; 32]) -> Result<()> {
+        let custom_type_registry = &mut ctx.accounts.custom_type_registry;
+        let position = custom_type_registry
+            .find(&label_hash)
+            .ok_or(RelationshipError::CustomTypeNotRegistered)?;
+        custom_type_registry.labels.remove(position);
+
+        emit_cpi!(CustomTypeRemoved {
+            version: EVENT_SCHEMA_VERSION,
+            label_hash,
+        });
+
+        Ok(())
+    }
+
+    // Label a Custom relationship with an approved custom type. Both owners
+    // must sign.
+    pub fn set_custom_label(ctx: Context<SetCustomLabel>, label_hash: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.custom_type_registry.find(&label_hash).is_some(),
+            RelationshipError::CustomTypeNotRegistered
+        );
+
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        require!(
+            relationship_data.relationship_type()? == RelationshipType::Custom,
+            RelationshipError::NotCustomRelationship
+        );
+        relationship_data.custom_label_hash = label_hash;
+
+        emit_cpi!(CustomLabelSet {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            label_hash,
+        });
+
+        Ok(())
+    }
+
+    // Propose a status or type change (either owner). The other owner applies
+    // it with `confirm_relationship_change` before it expires.
+    pub fn propose_relationship_change(
+        ctx: Context<ProposeRelationshipChange>,
+        change: RelationshipChange,
+    ) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let proposer = ctx.accounts.proposer.key();
+        require!(
+            proposer == relationship_data.owner_one || proposer == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.relationship = relationship_key;
+        proposal.proposer = proposer;
+        proposal.change = change.clone();
+        proposal.expires_at = Clock::get()?.unix_timestamp.saturating_add(CHANGE_PROPOSAL_WINDOW);
+        proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+        emit_cpi!(RelationshipChangeProposed {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            relationship_id: relationship_key,
+            proposer,
+            change,
+            expires_at: proposal.expires_at,
+        });
+
+        Ok(())
+    }
+
+    // Confirm and apply the other owner's pending change
+    pub fn confirm_relationship_change(ctx: Context<ConfirmRelationshipChange>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let confirmer = ctx.accounts.confirmer.key();
+        let proposal = &ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            confirmer == relationship_data.owner_one || confirmer == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+        require!(
+            confirmer != proposal.proposer,
+            RelationshipError::ChangeAwaitingCounterparty
+        );
+        require!(now <= proposal.expires_at, RelationshipError::ChangeProposalExpired);
+
+        let template = relationship_template(&relationship_data, &ctx.accounts.template)?;
+        let stats = &mut ctx.accounts.stats.load_mut()?;
+        match &proposal.change {
+            RelationshipChange::Status { status } => {
+                apply_status_change(&mut relationship_data, template, stats, status, now)?;
+                emit_cpi!(RelationshipStatusUpdated {
+                    version: EVENT_SCHEMA_VERSION,
+                    relationship_id: relationship_key,
+                    status: status.clone(),
+                });
+            }
+            RelationshipChange::Type { relationship_type } => {
+                apply_type_change(&mut relationship_data, template, stats, relationship_type, now)?;
+                emit_cpi!(RelationshipTypeChanged {
+                    version: EVENT_SCHEMA_VERSION,
+                    relationship_id: relationship_key,
+                    relationship_type: relationship_type.clone(),
+                });
+            }
+        }
+
+        emit_cpi!(RelationshipChangeConfirmed {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: proposal.key(),
+            relationship_id: relationship_key,
+            confirmed_by: confirmer,
+        });
+
+        Ok(())
+    }
+
+    // Withdraw a pending change and reclaim its rent. Either owner may cancel;
+    // anyone may clear an expired proposal.
+    pub fn cancel_relationship_change(ctx: Context<CancelRelationshipChange>) -> Result<()> {
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let authority = ctx.accounts.authority.key();
+        let expired = Clock::get()?.unix_timestamp > ctx.accounts.proposal.expires_at;
+        require!(
+            expired || authority == relationship_data.owner_one || authority == relationship_data.owner_two,
+            RelationshipError::Unauthorized
+        );
+
+        emit_cpi!(RelationshipChangeCancelled {
+            version: EVENT_SCHEMA_VERSION,
+            proposal_id: ctx.accounts.proposal.key(),
+            cancelled_by: authority,
+        });
+
+        Ok(())
+    }
+
+    // Define a relationship template (admin). Templates bundle a type,
+    // transition rules, a reward schedule, and decay parameters that pairs
+    // may opt into at creation.
+    pub fn create_relationship_template(
+        ctx: Context<CreateRelationshipTemplate>,
+        template_id: u8,
+        params: TemplateParams,
+    ) -> Result<()> {
+        require!(template_id != 0, RelationshipError::InvalidTemplateParams);
+        validate_template_params(&params)?;
+
+        let template = &mut ctx.accounts.template;
+        template.template_id = template_id;
+        template.params = params.clone();
+        template.active = true;
+        template.bump = *ctx.bumps.get("template").unwrap();
+
+        emit_cpi!(RelationshipTemplateUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            template_id,
+            params,
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    // Update a template's parameters or stop offering it (admin). Changes
+    // apply to every relationship created from the template.
+    pub fn update_relationship_template(
+        ctx: Context<UpdateRelationshipTemplate>,
+        params: TemplateParams,
+        active: bool,
+    ) -> Result<()> {
+        validate_template_params(&params)?;
+
+        let template = &mut ctx.accounts.template;
+        template.params = params.clone();
+        template.active = active;
+
+        emit_cpi!(RelationshipTemplateUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            template_id: template.template_id,
+            params,
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Refresh the cached owner authorities after an agent changes hands.
+    // Permissionless; the registry's agent accounts are the source of truth.
+    pub fn sync_relationship_owners(ctx: Context<SyncRelationshipOwners>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let owner_one = ctx.accounts.agent_one.owner;
+        let owner_two = ctx.accounts.agent_two.owner;
+
+        if owner_one == relationship_data.owner_one && owner_two == relationship_data.owner_two {
+            return Ok(());
+        }
+        let previous_owner_one = relationship_data.owner_one;
+        let previous_owner_two = relationship_data.owner_two;
+        relationship_data.owner_one = owner_one;
+        relationship_data.owner_two = owner_two;
+
+        emit_cpi!(RelationshipOwnersSynced {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            previous_owner_one,
+            previous_owner_two,
+            owner_one,
+            owner_two,
+        });
+
+        Ok(())
+    }
+
+    // Create the registry of integrator-defined interaction types (admin)
+    pub fn initialize_interaction_type_registry(
+        ctx: Context<InitializeInteractionTypeRegistry>,
+    ) -> Result<()> {
+        let interaction_type_registry = &mut ctx.accounts.interaction_type_registry;
+        interaction_type_registry.types = Vec::new();
+        interaction_type_registry.bump = *ctx.bumps.get("interaction_type_registry").unwrap();
+
+        Ok(())
+    }
+
+    // Register a custom interaction type. The signer becomes its authority;
+    // Custom interactions must then name the type and its current schema hash.
+    pub fn register_interaction_type(
+        ctx: Context<RegisterInteractionType>,
+        type_id: u32,
+        schema_hash: [u8; 32],
+        display_name: String,
+    ) -> Result<()> {
+        require!(type_id != 0, RelationshipError::InvalidInteractionType);
+        require!(
+            !display_name.is_empty() && display_name.len() <= MAX_INTERACTION_TYPE_NAME_LEN,
+            RelationshipError::InvalidInteractionType
+        );
+
+        let interaction_type_registry = &mut ctx.accounts.interaction_type_registry;
+        require!(
+            interaction_type_registry.types.len() < MAX_INTERACTION_TYPES,
+            RelationshipError::TooManyInteractionTypes
+        );
+        require!(
+            interaction_type_registry.find(type_id).is_none(),
+            RelationshipError::InteractionTypeAlreadyRegistered
+        );
+
+        let authority = ctx.accounts.authority.key();
+        interaction_type_registry.types.push(RegisteredInteractionType {
+            type_id,
+            authority,
+            schema_hash,
+            display_name: display_name.clone(),
+        });
+
+        emit_cpi!(InteractionTypeRegistered {
+            version: EVENT_SCHEMA_VERSION,
+            type_id,
+            authority,
+            schema_hash,
+            display_name,
+        });
+
+        Ok(())
+    }
+
+    // Publish a new schema for a custom interaction type (type authority).
+    // Recorders using the old schema hash are rejected from then on.
+    pub fn update_interaction_schema(
+        ctx: Context<ManageInteractionType>,
+        type_id: u32,
+        schema_hash: [u8; 32],
+    ) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let interaction_type_registry = &mut ctx.accounts.interaction_type_registry;
+        let position = interaction_type_registry
+            .find(type_id)
+            .ok_or(RelationshipError::InteractionTypeNotRegistered)?;
+        let entry = &mut interaction_type_registry.types[position];
+        require!(entry.authority == authority, RelationshipError::Unauthorized);
+        entry.schema_hash = schema_hash;
+
+        emit_cpi!(InteractionSchemaUpdated {
+            version: EVENT_SCHEMA_VERSION,
+            type_id,
+            schema_hash,
+        });
+
+        Ok(())
+    }
+
+    // Remove a custom interaction type (type authority or admin). Recorded
+    // interactions keep their type id.
+    pub fn remove_interaction_type(ctx: Context<ManageInteractionType>, type_id: u32) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let interaction_type_registry = &mut ctx.accounts.interaction_type_registry;
+        let position = interaction_type_registry
+            .find(type_id)
+            .ok_or(RelationshipError::InteractionTypeNotRegistered)?;
+        require!(
+            authority == interaction_type_registry.types[position].authority
+                || authority == ctx.accounts.config.admin,
+            RelationshipError::Unauthorized
+        );
+        interaction_type_registry.types.remove(position);
+
+        emit_cpi!(InteractionTypeRemoved {
+            version: EVENT_SCHEMA_VERSION,
+            type_id,
+            removed_by: authority,
+        });
+
+        Ok(())
+    }
+
+    // Set or extend the term of a Professional or Mentorship relationship.
+    // Both owners must sign.
+    pub fn renew_relationship(ctx: Context<MutualConsent>, expires_at: i64) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+
+        require!(
+            relationship_data.relationship_type()?.is_term_based(),
+            RelationshipError::TermNotSupported
+        );
+        require!(
+            relationship_data.status()? != RelationshipStatus::Ended,
+            RelationshipError::InactiveRelationship
+        );
+        require!(
+            expires_at > Clock::get()?.unix_timestamp && expires_at > relationship_data.expires_at,
+            RelationshipError::InvalidTerm
+        );
+        let previous_expires_at = relationship_data.expires_at;
+        relationship_data.expires_at = expires_at;
+
+        emit_cpi!(RelationshipRenewed {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            previous_expires_at,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    // End a term-based relationship whose term has run out. Permissionless,
+    // so anyone may crank it.
+    pub fn expire_relationship(ctx: Context<ExpireRelationship>) -> Result<()> {
+        let relationship_key = ctx.accounts.relationship_data.key();
+        let mut relationship_data = ctx.accounts.relationship_data.load_mut()?;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            relationship_data.status()? != RelationshipStatus::Ended,
+            RelationshipError::InactiveRelationship
+        );
+        require!(
+            relationship_data.expires_at != 0 && now >= relationship_data.expires_at,
+            RelationshipError::TermNotExpired
+        );
+
+        ctx.accounts.stats.load_mut()?.on_status_changed(
+            &relationship_data.relationship_type()?,
+            &relationship_data.status()?,
+            &RelationshipStatus::Ended,
+            now,
+        );
+        relationship_data.status = RelationshipStatus::Ended as u8;
+
+        emit_cpi!(RelationshipExpired {
+            version: EVENT_SCHEMA_VERSION,
+            relationship_id: relationship_key,
+            expires_at: relationship_data.expires_at,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+// Zero-copy with fixed offsets (after the 8-byte discriminator: agent_one at 8,
+// agent_two at 40, owner_one at 72, owner_two at 104) so indexers can filter
+// with memcmp. Enums are stored as u8; use the accessors below to read them.
+#[account(zero_copy)]
+pub struct RelationshipData {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub owner_one: Pubkey,
+    pub owner_two: Pubkey,
+    pub matchmaker: Pubkey,  // Pubkey::default() when created without a matchmaker
+    pub creation_date: i64,
+    pub last_interaction: i64,
+    pub last_decay_at: i64,
+    pub prenup_lockup_until: i64,  // Joint wallet withdrawals are blocked until this time
+    pub interaction_count: u32,
+    pub milestone_count: u32,
+    pub witnessed_count: u32,  // Interactions attested by at least one witness
+    pub date_count: u32,
+    pub goal_count: u32,
+    pub dispute_count: u32,
+    pub anniversaries_posted: u16,
+    pub prenup_owner_one_share_bps: u16,
+    pub relationship_type: u8,  // RelationshipType
+    pub status: u8,             // RelationshipStatus
+    pub visibility: u8,         // Visibility
+    pub compatibility_score: u8,
+    pub health: u8,  // 0-100 scale
+    pub commission_paid: u8,  // bool
+    pub layout_version: u8,
+    pub custom_label_hash: [u8; 32],  // Label of a Custom type, zeroed when unlabeled
+    pub template_id: u8,  // RelationshipTemplate the pair was created from, 0 for none
+    pub rate_window_count: u16,     // Interactions in the current rate-limit window
+    pub rate_window_previous: u16,  // Interactions in the window before it
+    pub rate_window_start: i64,
+    pub expires_at: i64,  // End of a term-based relationship's term, 0 when open-ended
+    pub _reserved: [u8; 8],  // Room for new fields without another migration
+}
+
+// Borsh layout deployed relationships were created with, read only by
+// `migrate_relationship_layout`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyRelationshipData {
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub relationship_type: RelationshipType,
+    pub compatibility_score: u8,
+    pub creation_date: i64,
+    pub last_interaction: i64,
+    pub interaction_count: u32,
+    pub status: RelationshipStatus,
+}
+
+#[account]
+pub struct InteractionData {
+    pub relationship: Pubkey,
+    pub index: u64,
+    pub interaction_type: InteractionType,
+    pub interaction_data: String,  // Could be a reference to off-chain data
+    pub timestamp: i64,
+    pub witness_count: u16,
+    pub custom_type_id: u32,  // Registered type of a Custom interaction, 0 otherwise
+}
+
+// Programs notified via CPI on every recorded interaction
+#[account]
+pub struct HookRegistry {
+    pub hooks: Vec<InteractionHook>,
+    pub bump: u8,
+}
+
+// Most recent compatibility score changes, oldest first
+#[account]
+pub struct CompatibilityHistory {
+    pub relationship: Pubkey,
+    pub entries: Vec<ScoreChange>,
+    pub total_changes: u32,
+}
+
+// Per-relationship counter that interaction PDAs are derived from
+#[account]
+pub struct InteractionNonce {
+    pub relationship: Pubkey,
+    pub next_index: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct RelationshipConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub rewards_vault: Pubkey,
+    pub oracle: Pubkey,
+    pub treasury: Pubkey,  // Influence token account receiving forfeited stakes
+    pub date_params: DateParams,
+    pub arbiter: Pubkey,
+    pub matchmaker_params: MatchmakerParams,
+    pub goal_params: GoalParams,
+    pub rate_limits: RateLimitParams,
+    pub bump: u8,
+}
+
+#[account]
+pub struct DateData {
+    pub relationship: Pubkey,
+    pub date_index: u32,
+    pub scheduled_at: i64,
+    pub stake_amount: u64,  // Staked by each side
+    pub confirmed_one: bool,
+    pub confirmed_two: bool,
+    pub status: DateStatus,
+    pub created_at: i64,
+    pub absent_owner: Pubkey,
+    pub no_show_reported_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct JointWallet {
+    pub relationship: Pubkey,
+    pub vault: Pubkey,
+    pub proposal_count: u32,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct WithdrawalProposal {
+    pub joint_wallet: Pubkey,
+    pub proposal_index: u32,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,  // Influence token account receiving the funds
+    pub amount: u64,
+    pub approved_one: bool,
+    pub approved_two: bool,
+    pub executed: bool,
+    pub created_at: i64,
+}
+
+#[account]
+pub struct MatchmakerStats {
+    pub authority: Pubkey,
+    pub proposals_created: u32,
+    pub relationships_formed: u32,
+    pub commissions_paid: u32,
+    pub total_commission: u64,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct RelationshipProposal {
+    pub matchmaker: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub relationship_type: RelationshipType,
+    pub compatibility_score: u8,
+    pub created_at: i64,
+}
+
+#[account]
+pub struct Dispute {
+    pub relationship: Pubkey,
+    pub dispute_index: u32,
+    pub target: DisputeTarget,
+    pub opener: Pubkey,
+    pub respondent: Pubkey,
+    pub reason_code: u8,
+    pub evidence_count: u16,
+    pub last_evidence_hash: [u8; 32],  // Full evidence trail is in EvidenceSubmitted events
+    pub status: DisputeStatus,
+    pub ruling: DisputeRuling,
+    pub opened_at: i64,
+    pub resolved_at: i64,
+}
+
+// Protocol-wide counters, updated incrementally so dashboards need a single read
+#[account(zero_copy)]
+pub struct RelationshipStats {
+    pub total_relationships: u64,
+    pub active_relationships: u64,
+    pub active_by_type: [u64; RELATIONSHIP_TYPE_COUNT],  // Indexed by RelationshipType
+    pub total_interactions: u64,
+    pub compatibility_sum: u64,  // Sum of creation-time scores across all relationships
+    pub last_updated: i64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl RelationshipStats {
+    pub fn average_compatibility(&self) -> u8 {
+        if self.total_relationships == 0 {
+            return 0;
+        }
+        (self.compatibility_sum / self.total_relationships) as u8
+    }
+
+    fn on_created(&mut self, relationship_type: &RelationshipType, compatibility_score: u8, now: i64) {
+        self.total_relationships = self.total_relationships.saturating_add(1);
+        self.active_relationships = self.active_relationships.saturating_add(1);
+        let count = &mut self.active_by_type[relationship_type.index()];
+        *count = count.saturating_add(1);
+        self.compatibility_sum = self.compatibility_sum.saturating_add(compatibility_score as u64);
+        self.last_updated = now;
+    }
+
+    fn on_interaction(&mut self, now: i64) {
+        self.total_interactions = self.total_interactions.saturating_add(1);
+        self.last_updated = now;
+    }
+
+    fn on_status_changed(
+        &mut self,
+        relationship_type: &RelationshipType,
+        old_status: &RelationshipStatus,
+        new_status: &RelationshipStatus,
+        now: i64,
+    ) {
+        let was_active = *old_status == RelationshipStatus::Active;
+        let is_active = *new_status == RelationshipStatus::Active;
+        let count = &mut self.active_by_type[relationship_type.index()];
+        if was_active && !is_active {
+            self.active_relationships = self.active_relationships.saturating_sub(1);
+            *count = count.saturating_sub(1);
+        } else if !was_active && is_active {
+            self.active_relationships = self.active_relationships.saturating_add(1);
+            *count = count.saturating_add(1);
+        }
+        self.last_updated = now;
+    }
+
+    fn on_type_changed(&mut self, old_type: &RelationshipType, new_type: &RelationshipType, now: i64) {
+        // Type changes are only allowed on active relationships
+        let old_count = &mut self.active_by_type[old_type.index()];
+        *old_count = old_count.saturating_sub(1);
+        let new_count = &mut self.active_by_type[new_type.index()];
+        *new_count = new_count.saturating_add(1);
+        self.last_updated = now;
+    }
+}
+
+#[account]
+pub struct Goal {
+    pub relationship: Pubkey,
+    pub goal_index: u32,
+    pub description_hash: [u8; 32],  // Hash of the off-chain goal description
+    pub metric: GoalMetric,
+    pub start_value: u64,  // Counter value when the goal was set
+    pub target: u64,       // Increase over start_value required to complete
+    pub deadline: i64,
+    pub reward_eligible: bool,
+    pub status: GoalStatus,
+    pub created_at: i64,
+    pub completed_at: i64,
+    pub bump: u8,
+}
+
+// Head of a relationship's hash-chained conversation transcript
+#[account]
+pub struct Transcript {
+    pub relationship: Pubkey,
+    pub segment_count: u64,
+    pub head_hash: [u8; 32],  // Hash of the latest segment, zeroed before the first
+    pub total_bytes: u64,
+    pub last_anchored: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct TranscriptSegment {
+    pub transcript: Pubkey,
+    pub segment_index: u64,
+    pub chunk_hash: [u8; 32],     // Hash of the off-chain chunk
+    pub byte_length: u32,
+    pub previous_hash: [u8; 32],  // Segment hash this one is chained to
+    pub segment_hash: [u8; 32],
+    pub recorder: Pubkey,
+    pub timestamp: i64,
+}
+
+// A third agent's attestation that an interaction took place
+#[account]
+pub struct Witness {
+    pub interaction: Pubkey,
+    pub witness_agent: Pubkey,
+    pub witness_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+// Authority over a relationship's compressed interaction tree
+#[account]
+pub struct InteractionTree {
+    pub relationship: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_count: u64,
+    pub bump: u8,
+}
+
+// Approved labels for RelationshipType::Custom
+#[account]
+pub struct CustomTypeRegistry {
+    pub labels: Vec<CustomTypeLabel>,
+    pub bump: u8,
+}
+
+impl CustomTypeRegistry {
+    pub fn find(&self, label_hash: &[u8; 32]) -> Option<usize> {
+        self.labels.iter().position(|entry| &entry.label_hash == label_hash)
+    }
+}
+
+// Integrator-defined types for InteractionType::Custom
+#[account]
+pub struct InteractionTypeRegistry {
+    pub types: Vec<RegisteredInteractionType>,
+    pub bump: u8,
+}
+
+impl InteractionTypeRegistry {
+    pub fn find(&self, type_id: u32) -> Option<usize> {
+        self.types.iter().position(|entry| entry.type_id == type_id)
+    }
+}
+
+// Interactions recorded by one signer across all relationships
+#[account]
+pub struct RecorderRateLimit {
+    pub recorder: Pubkey,
+    pub window_start: i64,
+    pub window_count: u16,
+    pub window_previous: u16,
+    pub bump: u8,
+}
+
+// Admin-defined mode (e.g. a speed-dating season) a relationship can be created from
+#[account]
+pub struct RelationshipTemplate {
+    pub template_id: u8,
+    pub params: TemplateParams,
+    pub active: bool,  // Inactive templates keep governing existing pairs but can't be selected
+    pub bump: u8,
+}
+
+// Status or type change proposed by one owner, awaiting the other's confirmation
+#[account]
+pub struct RelationshipChangeProposal {
+    pub relationship: Pubkey,
+    pub proposer: Pubkey,
+    pub change: RelationshipChange,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateRelationship<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+    
+    // Optional template the relationship is created from
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RelationshipData>(),
+        seeds = [b"relationship", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<InteractionNonce>(),
+        seeds = [b"interaction_nonce", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub interaction_nonce: Account<'info, InteractionNonce>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateRelationship<'info> {
+    pub owner_one: Signer<'info>,
+    pub owner_two: Signer<'info>,
+    
+    #[account(
+        mut,
+        has_one = owner_one @ RelationshipError::Unauthorized,
+        has_one = owner_two @ RelationshipError::Unauthorized,
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+    
+    // Required when the relationship was created from a template
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<RelationshipConfig>(),
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = influence_mint,
+        token::authority = config,
+        seeds = [b"rewards_vault"],
+        bump
+    )]
+    pub rewards_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = funder_token_account.owner == funder.key(),
+    )]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut, address = config.rewards_vault)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ScheduleDate<'info> {
+    // Each owner pays the rent of their own stake's escrow
+    #[account(mut)]
+    pub owner_one: Signer<'info>,
+    #[account(mut)]
+    pub owner_two: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        mut,
+        has_one = owner_one @ RelationshipError::Unauthorized,
+        has_one = owner_two @ RelationshipError::Unauthorized,
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = owner_one,
+        space = 8 + std::mem::size_of::<DateData>(),
+        seeds = [
+            b"date",
+            relationship_data.key().as_ref(),
+            &relationship_data.load()?.date_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub date_data: Account<'info, DateData>,
+
+    #[account(address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    /// CHECK: owner one's stake escrow, created by the escrow program at its
+    /// PDA for the owner and date
+    #[account(mut)]
+    pub escrow_one: UncheckedAccount<'info>,
+
+    /// CHECK: escrow one's token vault, created by the escrow program
+    #[account(mut)]
+    pub vault_one: UncheckedAccount<'info>,
+
+    /// CHECK: owner two's stake escrow, created by the escrow program
+    #[account(mut)]
+    pub escrow_two: UncheckedAccount<'info>,
+
+    /// CHECK: escrow two's token vault, created by the escrow program
+    #[account(mut)]
+    pub vault_two: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = owner_one_token_account.owner == owner_one.key(),
+        constraint = owner_one_token_account.mint == influence_mint.key(),
+    )]
+    pub owner_one_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_two_token_account.owner == owner_two.key(),
+        constraint = owner_two_token_account.mint == influence_mint.key(),
+    )]
+    pub owner_two_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub escrow_program: Program<'info, EscrowProgram>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfirmDate<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Required when the relationship was created from a template
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    #[account(
+        mut,
+        constraint = date_data.relationship == relationship_data.key() @ RelationshipError::DateMismatch,
+    )]
+    pub date_data: Account<'info, DateData>,
+
+    #[account(
+        constraint = stakes.is_for(
+            &date_data.key(),
+            &relationship_data.load()?.owner_one,
+            &relationship_data.load()?.owner_two,
+        ) @ RelationshipError::DateMismatch,
+    )]
+    pub stakes: DateStakes<'info>,
+
+    #[account(mut, address = config.rewards_vault)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_one_token_account.owner == relationship_data.load()?.owner_one,
+        constraint = owner_one_token_account.mint == config.influence_mint,
+    )]
+    pub owner_one_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_two_token_account.owner == relationship_data.load()?.owner_two,
+        constraint = owner_two_token_account.mint == config.influence_mint,
+    )]
+    pub owner_two_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReportNoShow<'info> {
+    pub reporter: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        constraint = date_data.relationship == relationship_data.key() @ RelationshipError::DateMismatch,
+    )]
+    pub date_data: Account<'info, DateData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DisputeNoShow<'info> {
+    pub absent_owner: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut)]
+    pub date_data: Account<'info, DateData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleNoShow<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        constraint = date_data.relationship == relationship_data.key() @ RelationshipError::DateMismatch,
+    )]
+    pub date_data: Account<'info, DateData>,
+
+    #[account(
+        constraint = stakes.is_for(
+            &date_data.key(),
+            &relationship_data.load()?.owner_one,
+            &relationship_data.load()?.owner_two,
+        ) @ RelationshipError::DateMismatch,
+    )]
+    pub stakes: DateStakes<'info>,
+
+    #[account(mut, address = config.treasury)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_one_token_account.owner == relationship_data.load()?.owner_one,
+        constraint = owner_one_token_account.mint == config.influence_mint,
+    )]
+    pub owner_one_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_two_token_account.owner == relationship_data.load()?.owner_two,
+        constraint = owner_two_token_account.mint == config.influence_mint,
+    )]
+    pub owner_two_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateJointWallet<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<JointWallet>(),
+        seeds = [b"joint_wallet", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub joint_wallet: Account<'info, JointWallet>,
+
+    #[account(address = config.influence_mint)]
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = owner,
+        token::mint = influence_mint,
+        token::authority = joint_wallet,
+        seeds = [b"joint_vault", joint_wallet.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositJointWallet<'info> {
+    pub owner: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        seeds = [b"joint_wallet", relationship_data.key().as_ref()],
+        bump = joint_wallet.bump,
+    )]
+    pub joint_wallet: Account<'info, JointWallet>,
+
+    #[account(mut, address = joint_wallet.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeWithdrawal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        seeds = [b"joint_wallet", relationship_data.key().as_ref()],
+        bump = joint_wallet.bump,
+    )]
+    pub joint_wallet: Account<'info, JointWallet>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + std::mem::size_of::<WithdrawalProposal>(),
+        seeds = [
+            b"withdrawal",
+            joint_wallet.key().as_ref(),
+            &joint_wallet.proposal_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ApproveWithdrawal<'info> {
+    pub approver: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        seeds = [b"joint_wallet", relationship_data.key().as_ref()],
+        bump = joint_wallet.bump,
+    )]
+    pub joint_wallet: Account<'info, JointWallet>,
+
+    #[account(mut, address = joint_wallet.vault)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        has_one = joint_wallet,
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+
+    #[account(mut, address = proposal.recipient)]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    pub owner: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        seeds = [b"joint_wallet", relationship_data.key().as_ref()],
+        bump = joint_wallet.bump,
+    )]
+    pub joint_wallet: Account<'info, JointWallet>,
+
+    #[account(
+        mut,
+        has_one = joint_wallet,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Account<'info, WithdrawalProposal>,
+
+    /// CHECK: receives the proposal's rent; validated by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MutualConsent<'info> {
+    pub owner_one: Signer<'info>,
+    pub owner_two: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner_one @ RelationshipError::Unauthorized,
+        has_one = owner_two @ RelationshipError::Unauthorized,
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EndRelationship<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Required when the relationship was created from a template
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    // Joint wallet accounts, required only when the pair opened a joint wallet
+    #[account(
+        mut,
+        seeds = [b"joint_wallet", relationship_data.key().as_ref()],
+        bump = joint_wallet.bump,
+    )]
+    pub joint_wallet: Option<Account<'info, JointWallet>>,
+
+    #[account(mut)]
+    pub vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_one_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_two_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OpenDispute<'info> {
+    #[account(mut)]
+    pub opener: Signer<'info>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = opener,
+        space = 8 + std::mem::size_of::<Dispute>(),
+        seeds = [
+            b"dispute",
+            relationship_data.key().as_ref(),
+            &relationship_data.load()?.dispute_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SubmitEvidence<'info> {
+    pub submitter: Signer<'info>,
+
+    #[account(mut)]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+// A date's two stakes, each held by the escrow program for the other owner
+// and settled only by this program
+#[derive(Accounts)]
+pub struct DateStakes<'info> {
+    /// CHECK: PDA this program signs escrow settlements with
+    #[account(seeds = [ESCROW_AUTHORITY_SEED], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    // Owner one's stake
+    #[account(mut)]
+    pub escrow_one: Account<'info, Escrow>,
+
+    /// CHECK: escrow one's token vault; validated by the escrow program
+    #[account(mut)]
+    pub vault_one: UncheckedAccount<'info>,
+
+    /// CHECK: receives escrow one's rent once it is emptied; validated by the escrow program
+    #[account(mut)]
+    pub owner_one: UncheckedAccount<'info>,
+
+    // Owner two's stake
+    #[account(mut)]
+    pub escrow_two: Account<'info, Escrow>,
+
+    /// CHECK: escrow two's token vault; validated by the escrow program
+    #[account(mut)]
+    pub vault_two: UncheckedAccount<'info>,
+
+    /// CHECK: receives escrow two's rent once it is emptied; validated by the escrow program
+    #[account(mut)]
+    pub owner_two: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+}
+
+impl<'info> DateStakes<'info> {
+    pub fn is_for(&self, date: &Pubkey, owner_one: &Pubkey, owner_two: &Pubkey) -> bool {
+        is_stake(&self.escrow_one, date, owner_one) && is_stake(&self.escrow_two, date, owner_two)
+    }
+
+    fn settlement<'a>(
+        &'a self,
+        token_program: &'a Program<'info, Token>,
+        owner_one_token_account: &'a Account<'info, TokenAccount>,
+        owner_two_token_account: &'a Account<'info, TokenAccount>,
+    ) -> StakeSettlement<'a, 'info> {
+        StakeSettlement {
+            escrow_authority: &self.escrow_authority,
+            escrow_program: &self.escrow_program,
+            token_program,
+            one: Stake {
+                escrow: &self.escrow_one,
+                vault: &self.vault_one,
+                owner: &self.owner_one,
+                token_account: owner_one_token_account,
+            },
+            two: Stake {
+                escrow: &self.escrow_two,
+                vault: &self.vault_two,
+                owner: &self.owner_two,
+                token_account: owner_two_token_account,
+            },
+        }
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = arbiter @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        constraint = dispute.relationship == relationship_data.key() @ RelationshipError::DisputeMismatch,
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    // Date accounts, required only when the resolution slashes a date stake;
+    // the stake accounts are those of `DateStakes`
+    #[account(mut)]
+    pub date_data: Option<Account<'info, DateData>>,
+
+    /// CHECK: PDA this program signs escrow settlements with
+    #[account(seeds = [ESCROW_AUTHORITY_SEED], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub escrow_one: Option<Account<'info, Escrow>>,
+
+    /// CHECK: validated by the escrow program
+    #[account(mut)]
+    pub vault_one: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the escrow program
+    #[account(mut)]
+    pub owner_one: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub escrow_two: Option<Account<'info, Escrow>>,
+
+    /// CHECK: validated by the escrow program
+    #[account(mut)]
+    pub vault_two: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: validated by the escrow program
+    #[account(mut)]
+    pub owner_two: Option<UncheckedAccount<'info>>,
+
+    pub escrow_program: Option<Program<'info, EscrowProgram>>,
+
+    #[account(mut)]
+    pub treasury: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_one_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_two_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordInteraction<'info> {
+    // An owner of the relationship, or a session key holder acting for one
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub session: Option<Account<'info, SessionKey>>,
+
+    // Signed by the events program when recording attendance
+    #[account(seeds = [INTERACTION_RECORDER_SEED], bump, seeds::program = EVENTS_PROGRAM_ID)]
+    pub event_recorder: Option<Signer<'info>>,
+    
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Checked for the registry's moderation freeze
+    #[account(address = relationship_data.load()?.agent_one @ RelationshipError::AgentMismatch)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(address = relationship_data.load()?.agent_two @ RelationshipError::AgentMismatch)]
+    pub agent_two: Account<'info, AgentData>,
+    
+    #[account(
+        mut,
+        seeds = [b"interaction_nonce", relationship_data.key().as_ref()],
+        bump = interaction_nonce.bump,
+    )]
+    pub interaction_nonce: Account<'info, InteractionNonce>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<InteractionData>() + 200, // Extra space for interaction data
+        seeds = [
+            b"interaction", 
+            relationship_data.key().as_ref(), 
+            &interaction_nonce.next_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub interaction_data: Account<'info, InteractionData>,
+    
+    #[account(seeds = [b"hooks"], bump = hook_registry.bump)]
+    pub hook_registry: Account<'info, HookRegistry>,
+    
+    /// CHECK: PDA that signs hook CPIs so hook programs can authenticate the caller
+    #[account(seeds = [HOOK_AUTHORITY_SEED], bump)]
+    pub hook_authority: UncheckedAccount<'info>,
+    
+    // Required for Custom interactions
+    #[account(seeds = [b"interaction_types"], bump = interaction_type_registry.bump)]
+    pub interaction_type_registry: Option<Account<'info, InteractionTypeRegistry>>,
+    
+    pub system_program: Program<'info, System>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    // Rate limits apply to the owner, whichever key signs
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RecorderRateLimit>(),
+        seeds = [b"recorder_rate", recorder_key(&session, &authority).as_ref()],
+        bump
+    )]
+    pub recorder_rate: Account<'info, RecorderRateLimit>,
+
+    // Matchmaker commission accounts, only needed for matchmade relationships
+    #[account(
+        mut,
+        seeds = [b"matchmaker", relationship_data.load()?.matchmaker.as_ref()],
+        bump = matchmaker_stats.bump,
+    )]
+    pub matchmaker_stats: Option<Account<'info, MatchmakerStats>>,
+
+    #[account(mut)]
+    pub matchmaker_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub rewards_vault: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// CHECK: validated by the rate limiter program
+    pub rate_limiter_config: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the rate limiter program; the owner's quota
+    #[account(mut)]
+    pub wallet_quota: UncheckedAccount<'info>,
+    
+    pub rate_limiter_program: Program<'info, RateLimiter>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterMatchmaker<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<MatchmakerStats>(),
+        seeds = [b"matchmaker", authority.key().as_ref()],
+        bump
+    )]
+    pub matchmaker_stats: Account<'info, MatchmakerStats>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeRelationship<'info> {
+    #[account(mut)]
+    pub matchmaker: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"matchmaker", matchmaker.key().as_ref()],
+        bump = matchmaker_stats.bump,
+    )]
+    pub matchmaker_stats: Account<'info, MatchmakerStats>,
+
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = matchmaker,
+        space = 8 + std::mem::size_of::<RelationshipProposal>(),
+        seeds = [b"proposal", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, RelationshipProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptRelationshipProposal<'info> {
+    #[account(mut)]
+    pub owner_one: Signer<'info>,
+    pub owner_two: Signer<'info>,
+
+    #[account(constraint = agent_one.owner == owner_one.key() @ RelationshipError::Unauthorized)]
+    pub agent_one: Account<'info, AgentData>,
+    #[account(constraint = agent_two.owner == owner_two.key() @ RelationshipError::Unauthorized)]
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump,
+        has_one = matchmaker,
+        close = matchmaker,
+    )]
+    pub proposal: Account<'info, RelationshipProposal>,
+
+    /// CHECK: receives the proposal's rent; validated by `has_one`
+    #[account(mut)]
+    pub matchmaker: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"matchmaker", matchmaker.key().as_ref()],
+        bump = matchmaker_stats.bump,
+    )]
+    pub matchmaker_stats: Account<'info, MatchmakerStats>,
+
+    // Optional template the relationship is created from
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    #[account(
+        init,
+        payer = owner_one,
+        space = 8 + std::mem::size_of::<RelationshipData>(),
+        seeds = [b"relationship", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = owner_one,
+        space = 8 + std::mem::size_of::<InteractionNonce>(),
+        seeds = [b"interaction_nonce", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub interaction_nonce: Account<'info, InteractionNonce>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DeclineRelationshipProposal<'info> {
+    pub owner: Signer<'info>,
+
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", agent_one.key().as_ref(), agent_two.key().as_ref()],
+        bump,
+        has_one = matchmaker,
+        close = matchmaker,
+    )]
+    pub proposal: Account<'info, RelationshipProposal>,
+
+    /// CHECK: receives the proposal's rent; validated by `has_one`
+    #[account(mut)]
+    pub matchmaker: UncheckedAccount<'info>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimMatchmakerCommission<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        seeds = [b"matchmaker", relationship_data.load()?.matchmaker.as_ref()],
+        bump = matchmaker_stats.bump,
+    )]
+    pub matchmaker_stats: Account<'info, MatchmakerStats>,
+
+    #[account(mut)]
+    pub matchmaker_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStats<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<RelationshipStats>(),
+        seeds = [b"stats"],
+        bump
+    )]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInteractionNonce<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<InteractionNonce>(),
+        seeds = [b"interaction_nonce", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub interaction_nonce: Account<'info, InteractionNonce>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeHookRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 4 + MAX_INTERACTION_HOOKS * std::mem::size_of::<InteractionHook>() + 1,
+        seeds = [b"hooks"],
+        bump
+    )]
+    pub hook_registry: Account<'info, HookRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageHooks<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut, seeds = [b"hooks"], bump = hook_registry.bump)]
+    pub hook_registry: Account<'info, HookRegistry>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RefreshCompatibility<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = agent_one,
+        has_one = agent_two,
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Owned by the agent registry; Anchor checks the owner program on deserialization
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 4 + MAX_SCORE_HISTORY * std::mem::size_of::<ScoreChange>() + 4,
+        seeds = [b"compat_history", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub compatibility_history: Account<'info, CompatibilityHistory>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MergeRelationships<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub co_signer: Option<Signer<'info>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"relationship", canonical.load()?.agent_one.as_ref(), canonical.load()?.agent_two.as_ref()],
+        bump
+    )]
+    pub canonical: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        seeds = [b"relationship", duplicate.load()?.agent_one.as_ref(), duplicate.load()?.agent_two.as_ref()],
+        bump,
+        close = authority
+    )]
+    pub duplicate: AccountLoader<'info, RelationshipData>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGoal<'info> {
+    #[account(mut)]
+    pub owner_one: Signer<'info>,
+    pub owner_two: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        mut,
+        has_one = owner_one @ RelationshipError::Unauthorized,
+        has_one = owner_two @ RelationshipError::Unauthorized,
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = owner_one,
+        space = 8 + std::mem::size_of::<Goal>(),
+        seeds = [
+            b"goal",
+            relationship_data.key().as_ref(),
+            &relationship_data.load()?.goal_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub goal: Account<'info, Goal>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompleteGoal<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Required when the relationship was created from a template
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    #[account(
+        mut,
+        seeds = [b"goal", relationship_data.key().as_ref(), &goal.goal_index.to_le_bytes()],
+        bump = goal.bump,
+    )]
+    pub goal: Account<'info, Goal>,
+
+    // Reward accounts, required only when a completion bonus is due
+    #[account(mut)]
+    pub rewards_vault: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_one_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_two_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(segment_index: u64)]
+pub struct AnchorTranscript<'info> {
+    #[account(mut)]
+    pub recorder: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init_if_needed,
+        payer = recorder,
+        space = 8 + std::mem::size_of::<Transcript>(),
+        seeds = [b"transcript", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub transcript: Account<'info, Transcript>,
+
+    #[account(
+        init,
+        payer = recorder,
+        space = 8 + std::mem::size_of::<TranscriptSegment>(),
+        seeds = [b"transcript_segment", transcript.key().as_ref(), &segment_index.to_le_bytes()],
+        bump
+    )]
+    pub segment: Account<'info, TranscriptSegment>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WitnessInteraction<'info> {
+    #[account(mut)]
+    pub witness_owner: Signer<'info>,
+
+    #[account(
+        constraint = witness_agent.owner == witness_owner.key() @ RelationshipError::Unauthorized,
+        constraint = witness_agent.is_active @ RelationshipError::InvalidWitness,
+    )]
+    pub witness_agent: Account<'info, AgentData>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        constraint = interaction_data.relationship == relationship_data.key() @ RelationshipError::InteractionMismatch,
+    )]
+    pub interaction_data: Account<'info, InteractionData>,
+
+    #[account(
+        init,
+        payer = witness_owner,
+        space = 8 + std::mem::size_of::<Witness>(),
+        seeds = [b"witness", interaction_data.key().as_ref(), witness_agent.key().as_ref()],
+        bump
+    )]
+    pub witness: Account<'info, Witness>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankRelationship<'info> {
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Required when the relationship was created from a template
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterAutomation<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    /// CHECK: PDA that owns the relationship's threads; holds no data
+    #[account(seeds = [b"automation", relationship_data.key().as_ref()], bump)]
+    pub automation: UncheckedAccount<'info>,
+
+    /// CHECK: created by the thread program; address checked in the handler
+    #[account(mut)]
+    pub thread: UncheckedAccount<'info>,
+
+    // Required for date settlement automations
+    #[account(
+        constraint = date_data.relationship == relationship_data.key() @ RelationshipError::DateMismatch,
+    )]
+    pub date_data: Option<Account<'info, DateData>>,
+
+    pub clockwork_program: Program<'info, ThreadProgram>,
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateRelationshipLayout<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: a legacy-layout relationship; owner, discriminator and size are checked in the handler
+    #[account(mut)]
+    pub relationship_data: UncheckedAccount<'info>,
+
+    // The relationship's agents, which supply its owners; checked against
+    // the legacy account in the handler
+    pub agent_one: Account<'info, AgentData>,
+    pub agent_two: Account<'info, AgentData>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateInteractionTree<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<InteractionTree>(),
+        seeds = [b"interaction_tree", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub interaction_tree: Account<'info, InteractionTree>,
+
+    /// CHECK: zero-initialized tree account, initialized by the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordCompressedInteraction<'info> {
+    // An owner of the relationship, or a session key holder acting for one
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub session: Option<Account<'info, SessionKey>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    // Rate limits apply to the owner, whichever key signs
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RecorderRateLimit>(),
+        seeds = [b"recorder_rate", recorder_key(&session, &authority).as_ref()],
+        bump
+    )]
+    pub recorder_rate: Account<'info, RecorderRateLimit>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Checked for the registry's moderation freeze
+    #[account(address = relationship_data.load()?.agent_one @ RelationshipError::AgentMismatch)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(address = relationship_data.load()?.agent_two @ RelationshipError::AgentMismatch)]
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"interaction_tree", relationship_data.key().as_ref()],
+        bump = interaction_tree.bump,
+        has_one = merkle_tree,
+    )]
+    pub interaction_tree: Account<'info, InteractionTree>,
+
+    /// CHECK: validated by `has_one` and by the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VerifyInteraction<'info> {
+    #[account(has_one = merkle_tree)]
+    pub interaction_tree: Account<'info, InteractionTree>,
+
+    /// CHECK: validated by `has_one` and by the compression program
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordLightInteraction<'info> {
+    // An owner of the relationship, or a session key holder acting for one
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub session: Option<Account<'info, SessionKey>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    // Rate limits apply to the owner, whichever key signs
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RecorderRateLimit>(),
+        seeds = [b"recorder_rate", recorder_key(&session, &authority).as_ref()],
+        bump
+    )]
+    pub recorder_rate: Account<'info, RecorderRateLimit>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Checked for the registry's moderation freeze
+    #[account(address = relationship_data.load()?.agent_one @ RelationshipError::AgentMismatch)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(address = relationship_data.load()?.agent_two @ RelationshipError::AgentMismatch)]
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        seeds = [b"interaction_nonce", relationship_data.key().as_ref()],
+        bump = interaction_nonce.bump,
+    )]
+    pub interaction_nonce: Account<'info, InteractionNonce>,
+
+    pub light: LightAccounts<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct VerifyLightInteraction<'info> {
+    // Pays Light's fee for writing the verified record back
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub light: LightAccounts<'info>,
+}
+
+// Light Protocol system accounts for compressed records; see armour_light
+#[derive(Accounts)]
+pub struct LightAccounts<'info> {
+    /// CHECK: PDA signing for this program's compressed accounts
+    #[account(seeds = [armour_light::CPI_AUTHORITY_SEED], bump)]
+    pub cpi_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the Light system program
+    pub registered_program_pda: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the Light system program
+    pub account_compression_authority: UncheckedAccount<'info>,
+
+    /// CHECK: this program
+    #[account(address = crate::ID)]
+    pub invoking_program: UncheckedAccount<'info>,
+
+    /// CHECK: Light's log wrapper
+    #[account(address = armour_light::LIGHT_NOOP_PROGRAM_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    /// CHECK: Light account compression program
+    #[account(address = armour_light::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub account_compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: Light system program
+    #[account(address = armour_light::LIGHT_SYSTEM_PROGRAM_ID, executable)]
+    pub light_system_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCustomTypeRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 4 + MAX_CUSTOM_TYPES * (32 + 4 + MAX_CUSTOM_LABEL_LEN) + 1,
+        seeds = [b"custom_types"],
+        bump
+    )]
+    pub custom_type_registry: Account<'info, CustomTypeRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageCustomTypes<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut, seeds = [b"custom_types"], bump = custom_type_registry.bump)]
+    pub custom_type_registry: Account<'info, CustomTypeRegistry>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCustomLabel<'info> {
+    pub owner_one: Signer<'info>,
+    pub owner_two: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner_one @ RelationshipError::Unauthorized,
+        has_one = owner_two @ RelationshipError::Unauthorized,
+    )]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(seeds = [b"custom_types"], bump = custom_type_registry.bump)]
+    pub custom_type_registry: Account<'info, CustomTypeRegistry>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeRelationshipChange<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + std::mem::size_of::<RelationshipChangeProposal>(),
+        seeds = [b"change", relationship_data.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, RelationshipChangeProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfirmRelationshipChange<'info> {
+    pub confirmer: Signer<'info>,
+
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    // Required when the relationship was created from a template
+    pub template: Option<Account<'info, RelationshipTemplate>>,
+
+    #[account(
+        mut,
+        seeds = [b"change", relationship_data.key().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Account<'info, RelationshipChangeProposal>,
+
+    /// CHECK: receives the proposal's rent; validated by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelRelationshipChange<'info> {
+    pub authority: Signer<'info>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        mut,
+        seeds = [b"change", relationship_data.key().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer,
+        close = proposer,
+    )]
+    pub proposal: Account<'info, RelationshipChangeProposal>,
+
+    /// CHECK: receives the proposal's rent; validated by `has_one`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(template_id: u8)]
+pub struct CreateRelationshipTemplate<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<RelationshipTemplate>(),
+        seeds = [b"template", template_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub template: Account<'info, RelationshipTemplate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateRelationshipTemplate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"template", template.template_id.to_le_bytes().as_ref()],
+        bump = template.bump,
+    )]
+    pub template: Account<'info, RelationshipTemplate>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SyncRelationshipOwners<'info> {
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(address = relationship_data.load()?.agent_one @ RelationshipError::AgentMismatch)]
+    pub agent_one: Account<'info, AgentData>,
+
+    #[account(address = relationship_data.load()?.agent_two @ RelationshipError::AgentMismatch)]
+    pub agent_two: Account<'info, AgentData>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInteractionTypeRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ RelationshipError::Unauthorized,
+    )]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 4 + MAX_INTERACTION_TYPES * (4 + 32 + 32 + 4 + MAX_INTERACTION_TYPE_NAME_LEN) + 1,
+        seeds = [b"interaction_types"],
+        bump
+    )]
+    pub interaction_type_registry: Account<'info, InteractionTypeRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RegisterInteractionType<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"interaction_types"], bump = interaction_type_registry.bump)]
+    pub interaction_type_registry: Account<'info, InteractionTypeRegistry>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageInteractionType<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RelationshipConfig>,
+
+    #[account(mut, seeds = [b"interaction_types"], bump = interaction_type_registry.bump)]
+    pub interaction_type_registry: Account<'info, InteractionTypeRegistry>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExpireRelationship<'info> {
+    #[account(mut)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(mut, seeds = [b"stats"], bump = stats.load()?.bump)]
+    pub stats: AccountLoader<'info, RelationshipStats>,
+
+    #[account(constraint = !guardian.is_paused(&ID) @ RelationshipError::ProgramPaused)]
+    pub guardian: Account<'info, Guardian>,
+}
+
+// Data structures
+impl RelationshipData {
+    pub fn relationship_type(&self) -> Result<RelationshipType> {
+        RelationshipType::try_from(self.relationship_type).map_err(|_| error!(RelationshipError::InvalidEnumValue))
+    }
+
+    pub fn status(&self) -> Result<RelationshipStatus> {
+        RelationshipStatus::try_from(self.status).map_err(|_| error!(RelationshipError::InvalidEnumValue))
+    }
+
+    pub fn visibility(&self) -> Result<Visibility> {
+        Visibility::try_from(self.visibility).map_err(|_| error!(RelationshipError::InvalidEnumValue))
+    }
+
+    pub fn commission_paid(&self) -> bool {
+        self.commission_paid != 0
+    }
+
+    pub fn prenup(&self) -> PrenupRules {
+        PrenupRules {
+            owner_one_share_bps: self.prenup_owner_one_share_bps,
+            lockup_until: self.prenup_lockup_until,
+        }
+    }
+
+    pub fn set_prenup(&mut self, prenup: &PrenupRules) {
+        self.prenup_owner_one_share_bps = prenup.owner_one_share_bps;
+        self.prenup_lockup_until = prenup.lockup_until;
+    }
+
+    // Interaction count with witnessed interactions weighted more heavily,
+    // for use in reputation scoring
+    pub fn weighted_interaction_count(&self) -> u64 {
+        let witnessed = self.witnessed_count.min(self.interaction_count) as u64;
+        (self.interaction_count as u64 - witnessed)
+            .saturating_add(witnessed.saturating_mul(WITNESSED_INTERACTION_WEIGHT))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DateStatus {
+    Scheduled,
+    Completed,
+    NoShowReported,
+    NoShowDisputed,
+    NoShowSettled,
+    Cancelled,
+    Slashed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum DisputeTarget {
+    Relationship,
+    Interaction(Pubkey),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DisputeStatus {
+    Open,
+    Resolved,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum DisputeRuling {
+    Pending,
+    OpenerFavored,
+    RespondentFavored,
+    Dismissed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DisputeResolution {
+    pub ruling: DisputeRuling,
+    pub health_delta: i8,
+    pub slash_date_stake: bool,  // Slash the at-fault side's stake in a scheduled date
+    pub new_status: Option<RelationshipStatus>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PrenupRules {
+    pub owner_one_share_bps: u16,  // owner_two receives the remainder
+    pub lockup_until: i64,         // Joint wallet withdrawals are blocked until this time
+}
+
+impl Default for PrenupRules {
+    fn default() -> Self {
+        Self {
+            owner_one_share_bps: BPS_DENOMINATOR / 2,
+            lockup_until: 0,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ScoreChange {
+    pub previous_score: u8,
+    pub new_score: u8,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct RegisteredInteractionType {
+    pub type_id: u32,
+    pub authority: Pubkey,        // Integrator allowed to update the schema
+    pub schema_hash: [u8; 32],    // Hash of the payload schema Custom interactions must follow
+    pub display_name: String,
+}
+
+// Registered type a Custom interaction's payload follows
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CustomInteraction {
+    pub type_id: u32,
+    pub schema_hash: [u8; 32],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CustomTypeLabel {
+    pub label_hash: [u8; 32],  // sha256 of the label
+    pub label: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct InteractionHook {
+    pub program_id: Pubkey,
+    pub extra_accounts: u8,
+}
+
+// Instruction data passed to a hook program's `on_interaction` instruction.
+// Hook accounts are [hook_authority (signer), relationship, interaction, ..extra].
+// Hooks should read the payload rather than the relationship account, whose
+// updates are not yet written back when the hook runs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InteractionHookPayload {
+    pub relationship: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub interaction: Pubkey,
+    pub interaction_index: u64,
+    pub interaction_type: InteractionType,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct MatchmakerParams {
+    pub commission_amount: u64,
+    pub commission_interaction_threshold: u32,  // Interaction count at which the commission is paid
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct GoalParams {
+    pub completion_bonus: u64,   // Paid to each side per completed goal
+    pub min_reward_target: u64,  // Smallest target eligible for the bonus
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct RateLimitParams {
+    pub window: i64,                // Seconds, e.g. 3600 for hourly limits
+    pub max_per_relationship: u16,  // Interactions per window for one relationship
+    pub max_per_recorder: u16,      // Interactions per window for one signer
+}
+
+// On-chain counter a goal is measured against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum GoalMetric {
+    InteractionCount,
+    MilestoneCount,
+    DateCount,
+}
+
+impl GoalMetric {
+    pub fn current_value(&self, relationship_data: &RelationshipData) -> u64 {
+        match self {
+            GoalMetric::InteractionCount => relationship_data.interaction_count as u64,
+            GoalMetric::MilestoneCount => relationship_data.milestone_count as u64,
+            GoalMetric::DateCount => relationship_data.date_count as u64,
+        }
+    }
+}
+
+// Scheduled operation run by a Clockwork thread
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum AutomationKind {
+    InactivityDecay,
+    Anniversary,
+    DateSettlement {
+        owner_one_token_account: Pubkey,
+        owner_two_token_account: Pubkey,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum GoalStatus {
+    Active,
+    Completed,
+    Expired,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct DateParams {
+    pub max_date_stake: u64,
+    pub date_completion_bonus: u64,     // Paid to each side per completed date
+    pub no_show_grace_period: i64,      // Seconds after scheduled_at before a no-show can be reported
+    pub dispute_window: i64,            // Seconds the absent side has to dispute a report
+    pub no_show_present_share_bps: u16, // Share of the forfeited stake paid to the present side
+}
+
+// Change that needs both owners' approval
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum RelationshipChange {
+    Status { status: RelationshipStatus },
+    Type { relationship_type: RelationshipType },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TemplateParams {
+    pub relationship_type: RelationshipType,
+    pub transitions: TransitionRules,
+    pub rewards: RewardSchedule,
+    pub decay: DecayParams,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct TransitionRules {
+    pub allow_pause: bool,
+    pub allow_type_change: bool,
+    pub min_duration: i64,  // Seconds after creation before the relationship can end
+}
+
+// Replaces the config's bonuses for relationships created from the template
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug)]
+pub struct RewardSchedule {
+    pub date_completion_bonus: u64,  // Paid to each side per completed date
+    pub goal_completion_bonus: u64,  // Paid to each side per completed goal
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DecayParams {
+    pub inactivity_period: i64,  // Seconds without interaction per decay step
+    pub decay_per_period: u8,    // Health lost per step
+}
+
+impl Default for DecayParams {
+    fn default() -> Self {
+        Self {
+            inactivity_period: INACTIVITY_PERIOD,
+            decay_per_period: INACTIVITY_DECAY,
+        }
+    }
+}
+
+// Events
+#[event]
+pub struct RelationshipCreated {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub relationship_type: RelationshipType,
+}
+
+#[event]
+pub struct InteractionRecorded {
+    pub version: u8,
+    pub interaction_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub interaction_index: u64,
+    pub interaction_type: InteractionType,
+    pub interaction_data: Option<String>,  // None for private relationships
+    pub interaction_data_hash: [u8; 32],
+}
+
+#[event]
+pub struct RelationshipStatusUpdated {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub status: RelationshipStatus,
+}
+
+#[event]
+pub struct RelationshipTypeChanged {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub relationship_type: RelationshipType,
+}
+
+#[event]
+pub struct ConfigInitialized {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub oracle: Pubkey,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub version: u8,
+    pub oracle: Pubkey,
+    pub treasury: Pubkey,
+    pub date_params: DateParams,
+}
+
+#[event]
+pub struct RewardsFunded {
+    pub version: u8,
+    pub funder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DateScheduled {
+    pub version: u8,
+    pub date_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub date_index: u32,
+    pub scheduled_at: i64,
+    pub stake_amount: u64,
+}
+
+#[event]
+pub struct DateConfirmed {
+    pub version: u8,
+    pub date_id: Pubkey,
+    pub confirmed_by: Pubkey,
+}
+
+#[event]
+pub struct DateCompleted {
+    pub version: u8,
+    pub date_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub stake_amount: u64,
+    pub bonus: u64,
+    pub oracle_attested: bool,
+}
+
+#[event]
+pub struct NoShowReported {
+    pub version: u8,
+    pub date_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub absent_owner: Pubkey,
+    pub reported_by: Pubkey,
+    pub dispute_deadline: i64,
+}
+
+#[event]
+pub struct NoShowDisputed {
+    pub version: u8,
+    pub date_id: Pubkey,
+    pub disputed_by: Pubkey,
+}
+
+#[event]
+pub struct NoShowSettled {
+    pub version: u8,
+    pub date_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub absent_owner: Pubkey,
+    pub upheld: bool,
+    pub present_payout: u64,
+    pub treasury_payout: u64,
+}
+
+#[event]
+pub struct JointWalletCreated {
+    pub version: u8,
+    pub joint_wallet: Pubkey,
+    pub relationship_id: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct JointWalletDeposit {
+    pub version: u8,
+    pub joint_wallet: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawalProposed {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub joint_wallet: Pubkey,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawalApproved {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub approver: Pubkey,
+}
+
+#[event]
+pub struct WithdrawalExecuted {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub joint_wallet: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WithdrawalCancelled {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct PrenupUpdated {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub owner_one_share_bps: u16,
+    pub lockup_until: i64,
+}
+
+#[event]
+pub struct RelationshipEnded {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub ended_by: Pubkey,
+    pub owner_one_payout: u64,
+    pub owner_two_payout: u64,
+}
+
+#[event]
+pub struct ArbiterUpdated {
+    pub version: u8,
+    pub previous_arbiter: Pubkey,
+    pub new_arbiter: Pubkey,
+}
+
+#[event]
+pub struct ConfigAdminUpdated {
+    pub version: u8,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub version: u8,
+    pub dispute_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub target: DisputeTarget,
+    pub opener: Pubkey,
+    pub respondent: Pubkey,
+    pub reason_code: u8,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct EvidenceSubmitted {
+    pub version: u8,
+    pub dispute_id: Pubkey,
+    pub submitter: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub evidence_index: u16,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub version: u8,
+    pub dispute_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub arbiter: Pubkey,
+    pub ruling: DisputeRuling,
+    pub health: u8,
+    pub slashed_amount: u64,
+    pub new_status: Option<RelationshipStatus>,
+}
+
+#[event]
+pub struct MatchmakerParamsUpdated {
+    pub version: u8,
+    pub commission_amount: u64,
+    pub commission_interaction_threshold: u32,
+}
+
+#[event]
+pub struct MatchmakerRegistered {
+    pub version: u8,
+    pub matchmaker: Pubkey,
+}
+
+#[event]
+pub struct RelationshipProposed {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub matchmaker: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub relationship_type: RelationshipType,
+}
+
+#[event]
+pub struct RelationshipProposalAccepted {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub matchmaker: Pubkey,
+}
+
+#[event]
+pub struct RelationshipProposalDeclined {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub declined_by: Pubkey,
+}
+
+#[event]
+pub struct MatchmakerCommissionPaid {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub matchmaker: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VisibilityChanged {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub visibility: Visibility,
+}
+
+#[event]
+pub struct InteractionHookAdded {
+    pub version: u8,
+    pub program_id: Pubkey,
+    pub extra_accounts: u8,
+}
+
+#[event]
+pub struct InteractionHookRemoved {
+    pub version: u8,
+    pub program_id: Pubkey,
+}
+
+#[event]
+pub struct CompatibilityRefreshed {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub previous_score: u8,
+    pub new_score: u8,
+}
+
+#[event]
+pub struct RelationshipsMerged {
+    pub version: u8,
+    pub canonical_id: Pubkey,
+    pub duplicate_id: Pubkey,
+    pub merged_by: Pubkey,
+    pub interaction_count: u32,
+    pub creation_date: i64,
+}
+
+#[event]
+pub struct GoalParamsUpdated {
+    pub version: u8,
+    pub completion_bonus: u64,
+    pub min_reward_target: u64,
+}
+
+#[event]
+pub struct RateLimitsUpdated {
+    pub version: u8,
+    pub window: i64,
+    pub max_per_relationship: u16,
+    pub max_per_recorder: u16,
+}
+
+#[event]
+pub struct GoalSet {
+    pub version: u8,
+    pub goal_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub metric: GoalMetric,
+    pub target: u64,
+    pub deadline: i64,
+    pub reward_eligible: bool,
+}
+
+#[event]
+pub struct GoalCompleted {
+    pub version: u8,
+    pub goal_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub bonus: u64,
+}
+
+#[event]
+pub struct GoalExpired {
+    pub version: u8,
+    pub goal_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub progress: u64,
+}
+
+#[event]
+pub struct TranscriptAnchored {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub segment_index: u64,
+    pub chunk_hash: [u8; 32],
+    pub byte_length: u32,
+    pub segment_hash: [u8; 32],
+    pub recorder: Pubkey,
+}
+
+#[event]
+pub struct InteractionWitnessed {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub interaction_id: Pubkey,
+    pub witness_agent: Pubkey,
+    pub witness_count: u16,
+    pub health: u8,
+}
+
+#[event]
+pub struct HealthDecayed {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub periods: u32,
+    pub health: u8,
+}
+
+#[event]
+pub struct AnniversaryReached {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub agent_one: Pubkey,
+    pub agent_two: Pubkey,
+    pub years: u16,
+}
+
+#[event]
+pub struct AutomationRegistered {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub thread: Pubkey,
+    pub kind: AutomationKind,
+    pub registered_by: Pubkey,
+}
+
+#[event]
+pub struct RelationshipLayoutMigrated {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub layout_version: u8,
+}
+
+#[event]
+pub struct InteractionTreeCreated {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+}
+
+#[event]
+pub struct CompressedInteractionRecorded {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_index: u64,
+    pub leaf: [u8; 32],
+    pub interaction_type: InteractionType,
+    pub interaction_data: Option<String>,  // None for private relationships
+    pub interaction_data_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InteractionVerified {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub leaf_index: u32,
+    pub leaf: [u8; 32],
+}
+
+#[event]
+pub struct LightInteractionRecorded {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub interaction_index: u64,
+    pub address: [u8; 32],  // Light compressed account address
+    pub interaction_type: InteractionType,
+    pub interaction_data: Option<String>,  // None for private relationships
+    pub interaction_data_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LightInteractionVerified {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub interaction_index: u64,
+    pub address: [u8; 32],
+}
+
+#[event]
+pub struct CustomTypeAdded {
+    pub version: u8,
+    pub label_hash: [u8; 32],
+    pub label: String,
+}
+
+#[event]
+pub struct CustomTypeRemoved {
+    pub version: u8,
+    pub label_hash: [u8; 32],
+}
+
+#[event]
+pub struct CustomLabelSet {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub label_hash: [u8; 32],
+}
+
+#[event]
+pub struct RelationshipChangeProposed {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub proposer: Pubkey,
+    pub change: RelationshipChange,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct RelationshipChangeConfirmed {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub relationship_id: Pubkey,
+    pub confirmed_by: Pubkey,
+}
+
+#[event]
+pub struct RelationshipChangeCancelled {
+    pub version: u8,
+    pub proposal_id: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct RelationshipTemplateUpdated {
+    pub version: u8,
+    pub template_id: u8,
+    pub params: TemplateParams,
+    pub active: bool,
+}
+
+#[event]
+pub struct RelationshipOwnersSynced {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub previous_owner_one: Pubkey,
+    pub previous_owner_two: Pubkey,
+    pub owner_one: Pubkey,
+    pub owner_two: Pubkey,
+}
+
+#[event]
+pub struct InteractionTypeRegistered {
+    pub version: u8,
+    pub type_id: u32,
+    pub authority: Pubkey,
+    pub schema_hash: [u8; 32],
+    pub display_name: String,
+}
+
+#[event]
+pub struct InteractionSchemaUpdated {
+    pub version: u8,
+    pub type_id: u32,
+    pub schema_hash: [u8; 32],
+}
+
+#[event]
+pub struct InteractionTypeRemoved {
+    pub version: u8,
+    pub type_id: u32,
+    pub removed_by: Pubkey,
+}
+
+#[event]
+pub struct RelationshipRenewed {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub previous_expires_at: i64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct RelationshipExpired {
+    pub version: u8,
+    pub relationship_id: Pubkey,
+    pub expires_at: i64,
+}
+
+// Custom errors
+#[error_code]
+pub enum RelationshipError {
+    #[msg("Relationship is not active")]
+    InactiveRelationship,
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Invalid token amount")]
+    InvalidAmount,
+    #[msg("Scheduled time must be in the future")]
+    InvalidDateTime,
+    #[msg("Date is not in the scheduled state")]
+    DateNotScheduled,
+    #[msg("Date has not started yet")]
+    DateNotStarted,
+    #[msg("Date does not belong to this relationship")]
+    DateMismatch,
+    #[msg("Basis points must not exceed 10000")]
+    InvalidBasisPoints,
+    #[msg("No-show grace period has not elapsed")]
+    GracePeriodActive,
+    #[msg("Absent party is not an owner in this relationship")]
+    InvalidAbsentParty,
+    #[msg("No-show requires the reporter's confirmation and the absent side's non-confirmation")]
+    NoShowNotProvable,
+    #[msg("No no-show has been reported for this date")]
+    NoShowNotReported,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Withdrawal proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Joint wallet is locked by the prenup")]
+    JointWalletLocked,
+    #[msg("Joint wallet vault and owner token accounts are required")]
+    MissingJointWalletAccounts,
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+    #[msg("Dispute does not belong to this relationship")]
+    DisputeMismatch,
+    #[msg("Invalid dispute resolution")]
+    InvalidDisputeResolution,
+    #[msg("Date escrow, treasury and owner token accounts are required")]
+    MissingDateAccounts,
+    #[msg("Matchmaker commission is not due")]
+    CommissionNotDue,
+    #[msg("Interaction index is stale; re-read the interaction nonce")]
+    StaleInteractionIndex,
+    #[msg("Maximum number of interaction hooks reached")]
+    TooManyHooks,
+    #[msg("Hook program is already registered")]
+    HookAlreadyRegistered,
+    #[msg("Hook program is not registered")]
+    HookNotRegistered,
+    #[msg("Invalid hook program")]
+    InvalidHookProgram,
+    #[msg("Remaining accounts do not match the registered hooks")]
+    HookAccountsMismatch,
+    #[msg("Agents must be ordered by key (agent_one < agent_two)")]
+    NonCanonicalPair,
+    #[msg("Relationships are not duplicates of the same pair")]
+    NotDuplicatePair,
+    #[msg("Goal target is below the minimum for a completion bonus")]
+    GoalTargetTooLow,
+    #[msg("Goal is not active")]
+    GoalNotActive,
+    #[msg("Goal target has not been reached")]
+    GoalNotReached,
+    #[msg("Reward accounts are missing or invalid")]
+    MissingRewardAccounts,
+    #[msg("Transcript segment index does not match the next segment")]
+    StaleTranscriptSegment,
+    #[msg("Witness must be an active agent outside the relationship")]
+    InvalidWitness,
+    #[msg("Witness window for this interaction has closed")]
+    WitnessWindowClosed,
+    #[msg("Interaction does not belong to this relationship")]
+    InteractionMismatch,
+    #[msg("Thread account does not match the automation")]
+    InvalidAutomationThread,
+    #[msg("Stored enum value is out of range")]
+    InvalidEnumValue,
+    #[msg("Account is not a legacy relationship")]
+    NotLegacyRelationship,
+    #[msg("Custom type label is empty or too long")]
+    InvalidCustomLabel,
+    #[msg("Custom type registry is full")]
+    TooManyCustomTypes,
+    #[msg("Custom type is already registered")]
+    CustomTypeAlreadyRegistered,
+    #[msg("Custom type is not registered")]
+    CustomTypeNotRegistered,
+    #[msg("Relationship is not of the Custom type")]
+    NotCustomRelationship,
+    #[msg("Agent has been frozen by the registry moderator")]
+    AgentFrozen,
+    #[msg("Agent account does not belong to this relationship")]
+    AgentMismatch,
+    #[msg("Invalid template parameters")]
+    InvalidTemplateParams,
+    #[msg("Template is no longer offered")]
+    TemplateInactive,
+    #[msg("Relationship type does not match the template")]
+    TemplateTypeMismatch,
+    #[msg("Relationship was created from a template; pass the template account")]
+    MissingTemplateAccount,
+    #[msg("Template account does not belong to this relationship")]
+    TemplateMismatch,
+    #[msg("Transition is not allowed by the relationship's template")]
+    TransitionNotAllowed,
+    #[msg("Relationship has not reached the template's minimum duration")]
+    MinimumDurationNotMet,
+    #[msg("Invalid rate limit parameters")]
+    InvalidRateLimits,
+    #[msg("Too many interactions for this relationship; try again later")]
+    RelationshipRateLimited,
+    #[msg("Too many interactions from this recorder; try again later")]
+    RecorderRateLimited,
+    #[msg("Change must be confirmed by the other owner")]
+    ChangeAwaitingCounterparty,
+    #[msg("Change proposal has expired")]
+    ChangeProposalExpired,
+    #[msg("Invalid custom interaction type")]
+    InvalidInteractionType,
+    #[msg("Interaction type registry is full")]
+    TooManyInteractionTypes,
+    #[msg("Interaction type is already registered")]
+    InteractionTypeAlreadyRegistered,
+    #[msg("Interaction type is not registered")]
+    InteractionTypeNotRegistered,
+    #[msg("Custom interactions must name a registered type; other interactions must not")]
+    InvalidCustomInteraction,
+    #[msg("Interaction type registry account is required for Custom interactions")]
+    MissingInteractionTypeRegistry,
+    #[msg("Payload schema does not match the registered schema")]
+    InteractionSchemaMismatch,
+    #[msg("Only Professional and Mentorship relationships have terms")]
+    TermNotSupported,
+    #[msg("Term must end in the future and after the current term")]
+    InvalidTerm,
+    #[msg("Relationship term has not expired")]
+    TermNotExpired,
+    #[msg("The relationship program is paused by the guardian")]
+    ProgramPaused,
+}
+
+// Helper functions
+impl<'info> LightAccounts<'info> {
+    pub fn cpi_accounts(&self, fee_payer: AccountInfo<'info>) -> LightCpiAccounts<'info> {
+        LightCpiAccounts {
+            fee_payer,
+            cpi_authority: self.cpi_authority.to_account_info(),
+            registered_program_pda: self.registered_program_pda.to_account_info(),
+            account_compression_authority: self.account_compression_authority.to_account_info(),
+            invoking_program: self.invoking_program.to_account_info(),
+            noop_program: self.noop_program.to_account_info(),
+            account_compression_program: self.account_compression_program.to_account_info(),
+            light_system_program: self.light_system_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        }
+    }
+}
+
+// Key rate limits are tracked under: the session owner, or the signer itself
+fn recorder_key(session: &Option<Account<SessionKey>>, authority: &Signer) -> Pubkey {
+    session.as_ref().map_or(authority.key(), |session| session.owner)
+}
+
+fn require_not_frozen(agent_one: &Account<AgentData>, agent_two: &Account<AgentData>) -> Result<()> {
+    require!(
+        !agent_one.is_frozen && !agent_two.is_frozen,
+        RelationshipError::AgentFrozen
+    );
+    Ok(())
+}
+
+// Returns the registered type id of a Custom interaction, or 0 for built-in types
+fn validate_custom_interaction(
+    interaction_type: &InteractionType,
+    custom_type: &Option<CustomInteraction>,
+    interaction_type_registry: &Option<Account<InteractionTypeRegistry>>,
+) -> Result<u32> {
+    match (interaction_type, custom_type) {
+        (InteractionType::Custom, Some(custom_type)) => {
+            let registry = interaction_type_registry
+                .as_ref()
+                .ok_or(RelationshipError::MissingInteractionTypeRegistry)?;
+            let position = registry
+                .find(custom_type.type_id)
+                .ok_or(RelationshipError::InteractionTypeNotRegistered)?;
+            require!(
+                registry.types[position].schema_hash == custom_type.schema_hash,
+                RelationshipError::InteractionSchemaMismatch
+            );
+            Ok(custom_type.type_id)
+        }
+        (InteractionType::Custom, None) | (_, Some(_)) => err!(RelationshipError::InvalidCustomInteraction),
+        (_, None) => Ok(0),
+    }
+}
+
+fn enforce_rate_limits(
+    rate_limits: &RateLimitParams,
+    relationship_data: &mut RelationshipData,
+    recorder_rate: &mut RecorderRateLimit,
+    now: i64,
+) -> Result<()> {
+    if rate_limits.window <= 0 {
+        return Ok(());
+    }
+    require!(
+        consume_rate_window(
+            &mut relationship_data.rate_window_start,
+            &mut relationship_data.rate_window_count,
+            &mut relationship_data.rate_window_previous,
+            rate_limits.window,
+            rate_limits.max_per_relationship,
+            now,
+        ),
+        RelationshipError::RelationshipRateLimited
+    );
+    require!(
+        consume_rate_window(
+            &mut recorder_rate.window_start,
+            &mut recorder_rate.window_count,
+            &mut recorder_rate.window_previous,
+            rate_limits.window,
+            rate_limits.max_per_recorder,
+            now,
+        ),
+        RelationshipError::RecorderRateLimited
+    );
+    Ok(())
+}
+
+// Sliding-window counter: the previous window's count is weighted by how much
+// of it still overlaps the trailing window. Returns false when the
+// interaction would exceed `max`; a zero `max` disables the limit.
+fn consume_rate_window(
+    window_start: &mut i64,
+    count: &mut u16,
+    previous: &mut u16,
+    window: i64,
+    max: u16,
+    now: i64,
+) -> bool {
+    if max == 0 {
+        return true;
+    }
+    let elapsed = now.saturating_sub(*window_start) / window;
+    if elapsed > 0 {
+        *previous = if elapsed == 1 { *count } else { 0 };
+        *count = 0;
+        *window_start = window_start.saturating_add(elapsed.saturating_mul(window));
+    }
+    let remaining = window.saturating_sub(now.saturating_sub(*window_start));
+    let estimate = (*previous as i64) * remaining / window + *count as i64;
+    if estimate >= max as i64 {
+        return false;
+    }
+    *count = count.saturating_add(1);
+    true
+}
+
+fn apply_status_change(
+    relationship_data: &mut RelationshipData,
+    template: Option<&RelationshipTemplate>,
+    stats: &mut RelationshipStats,
+    status: &RelationshipStatus,
+    now: i64,
+) -> Result<()> {
+    // Enforce the template's transition rules
+    if let Some(template) = template {
+        let rules = &template.params.transitions;
+        match status {
+            RelationshipStatus::Paused => {
+                require!(rules.allow_pause, RelationshipError::TransitionNotAllowed)
+            }
+            RelationshipStatus::Ended => require!(
+                now >= relationship_data.creation_date.saturating_add(rules.min_duration),
+                RelationshipError::MinimumDurationNotMet
+            ),
+            _ => {}
+        }
+    }
+
+    stats.on_status_changed(
+        &relationship_data.relationship_type()?,
+        &relationship_data.status()?,
+        status,
+        now,
+    );
+    relationship_data.status = status.clone() as u8;
+    Ok(())
+}
+
+fn apply_type_change(
+    relationship_data: &mut RelationshipData,
+    template: Option<&RelationshipTemplate>,
+    stats: &mut RelationshipStats,
+    relationship_type: &RelationshipType,
+    now: i64,
+) -> Result<()> {
+    // Ensure relationship is active
+    require!(
+        relationship_data.status()? == RelationshipStatus::Active,
+        RelationshipError::InactiveRelationship
+    );
+    if let Some(template) = template {
+        require!(
+            template.params.transitions.allow_type_change,
+            RelationshipError::TransitionNotAllowed
+        );
+    }
+
+    stats.on_type_changed(&relationship_data.relationship_type()?, relationship_type, now);
+    relationship_data.relationship_type = relationship_type.clone() as u8;
+    if *relationship_type != RelationshipType::Custom {
+        relationship_data.custom_label_hash = [0; 32];
+    }
+    if !relationship_type.is_term_based() {
+        relationship_data.expires_at = 0;
+    }
+    Ok(())
+}
+
+fn validate_template_params(params: &TemplateParams) -> Result<()> {
+    require!(
+        params.decay.inactivity_period > 0
+            && params.decay.decay_per_period <= MAX_HEALTH
+            && params.transitions.min_duration >= 0,
+        RelationshipError::InvalidTemplateParams
+    );
+    Ok(())
+}
+
+// Bind a newly initialized relationship to the template it was created from
+fn apply_template(relationship_data: &mut RelationshipData, template: &RelationshipTemplate) -> Result<()> {
+    require!(template.active, RelationshipError::TemplateInactive);
+    require!(
+        relationship_data.relationship_type()? == template.params.relationship_type,
+        RelationshipError::TemplateTypeMismatch
+    );
+    relationship_data.template_id = template.template_id;
+    Ok(())
+}
+
+// The template governing a relationship, or None for relationships created
+// without one
+fn relationship_template<'a>(
+    relationship_data: &RelationshipData,
+    template: &'a Option<Account<RelationshipTemplate>>,
+) -> Result<Option<&'a RelationshipTemplate>> {
+    if relationship_data.template_id == 0 {
+        return Ok(None);
+    }
+    let Some(template) = template else {
+        return err!(RelationshipError::MissingTemplateAccount);
+    };
+    require!(
+        template.template_id == relationship_data.template_id,
+        RelationshipError::TemplateMismatch
+    );
+    Ok(Some(template))
+}
+
+fn initialize_relationship(
+    relationship_data: &mut RelationshipData,
+    agent_one: &Account<AgentData>,
+    agent_two: &Account<AgentData>,
+    relationship_type: RelationshipType,
+    compatibility_score: u8,
+) -> Result<()> {
+    // Pairs are stored once, ordered by agent key
+    require!(
+        agent_one.key() < agent_two.key(),
+        RelationshipError::NonCanonicalPair
+    );
+    require_not_frozen(agent_one, agent_two)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    relationship_data.agent_one = agent_one.key();
+    relationship_data.agent_two = agent_two.key();
+    relationship_data.owner_one = agent_one.owner;
+    relationship_data.owner_two = agent_two.owner;
+    relationship_data.relationship_type = relationship_type as u8;
+    relationship_data.compatibility_score = compatibility_score;
+    relationship_data.creation_date = now;
+    relationship_data.last_interaction = now;
+    relationship_data.interaction_count = 0;
+    relationship_data.milestone_count = 0;
+    relationship_data.witnessed_count = 0;
+    relationship_data.last_decay_at = now;
+    relationship_data.anniversaries_posted = 0;
+    relationship_data.status = RelationshipStatus::Active as u8;
+    relationship_data.date_count = 0;
+    relationship_data.goal_count = 0;
+    relationship_data.set_prenup(&PrenupRules::default());
+    relationship_data.health = MAX_HEALTH;
+    relationship_data.dispute_count = 0;
+    relationship_data.matchmaker = Pubkey::default();
+    relationship_data.commission_paid = 0;
+    relationship_data.visibility = Visibility::Public as u8;
+    relationship_data.layout_version = RELATIONSHIP_LAYOUT_VERSION;
+    Ok(())
+}
+
+fn dispatch_interaction_hooks<'info>(
+    hook_registry: &HookRegistry,
+    hook_authority: &AccountInfo<'info>,
+    hook_authority_bump: u8,
+    relationship: &AccountInfo<'info>,
+    interaction: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    payload: &InteractionHookPayload,
+) -> Result<()> {
+    // Remaining accounts hold, for each hook in registry order, the hook
+    // program followed by its extra accounts
+    let mut data = hash(b"global:on_interaction").to_bytes()[..8].to_vec();
+    payload.serialize(&mut data)?;
+    let signer_seeds: &[&[u8]] = &[HOOK_AUTHORITY_SEED, &[hook_authority_bump]];
+
+    let mut cursor = 0;
+    for hook in hook_registry.hooks.iter() {
+        let end = cursor + 1 + hook.extra_accounts as usize;
+        require!(
+            end <= remaining_accounts.len(),
+            RelationshipError::HookAccountsMismatch
+        );
+        let program = &remaining_accounts[cursor];
+        require!(
+            program.key() == hook.program_id && program.executable,
+            RelationshipError::HookAccountsMismatch
+        );
+        let extra_accounts = &remaining_accounts[cursor + 1..end];
+
+        let mut metas = vec![
+            AccountMeta::new_readonly(hook_authority.key(), true),
+            AccountMeta::new_readonly(relationship.key(), false),
+            AccountMeta::new_readonly(interaction.key(), false),
+        ];
+        let mut infos = vec![
+            hook_authority.clone(),
+            relationship.clone(),
+            interaction.clone(),
+        ];
+        for account in extra_accounts {
+            metas.push(if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            });
+            infos.push(account.clone());
+        }
+        infos.push(program.clone());
+
+        let instruction = Instruction {
+            program_id: hook.program_id,
+            accounts: metas,
+            data: data.clone(),
+        };
+        invoke_signed(&instruction, &infos, &[signer_seeds])?;
+
+        cursor = end;
+    }
+    require!(
+        cursor == remaining_accounts.len(),
+        RelationshipError::HookAccountsMismatch
+    );
+
+    Ok(())
+}
+
+fn pay_matchmaker_commission<'info>(
+    config: &Account<'info, RelationshipConfig>,
+    relationship_data: &mut RelationshipData,
+    matchmaker_stats: &mut Account<'info, MatchmakerStats>,
+    rewards_vault: &Account<'info, TokenAccount>,
+    matchmaker_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<u64> {
+    // Returns the amount paid, or 0 when no commission is due
+    let params = &config.matchmaker_params;
+    if relationship_data.matchmaker == Pubkey::default()
+        || relationship_data.commission_paid()
+        || relationship_data.interaction_count < params.commission_interaction_threshold
+    {
+        return Ok(0);
+    }
+    require!(
+        rewards_vault.key() == config.rewards_vault,
+        RelationshipError::Unauthorized
+    );
+    require!(
+        matchmaker_token_account.owner == relationship_data.matchmaker
+            && matchmaker_token_account.mint == config.influence_mint,
+        RelationshipError::Unauthorized
+    );
+
+    // Leave the commission outstanding if the vault is empty
+    let amount = params.commission_amount.min(rewards_vault.amount);
+    if amount == 0 {
+        return Ok(0);
+    }
+    let config_seeds: &[&[u8]] = &[b"config", &[config.bump]];
+    pda_transfer(
+        token_program,
+        rewards_vault,
+        matchmaker_token_account,
+        config.to_account_info(),
+        config_seeds,
+        amount,
+    )?;
+
+    relationship_data.commission_paid = 1;
+    matchmaker_stats.commissions_paid = matchmaker_stats.commissions_paid.checked_add(1).unwrap_or(u32::MAX);
+    matchmaker_stats.total_commission = matchmaker_stats.total_commission.saturating_add(amount);
+
+    Ok(amount)
+}
+
+// The escrow addresses of a date's stakes, for building instructions
+pub fn date_stake_addresses(date: &Pubkey, owner_one: &Pubkey, owner_two: &Pubkey) -> accounts::DateStakes {
+    let escrow_one = escrow_cpi::pda::find_escrow_address(owner_one, date).0;
+    let escrow_two = escrow_cpi::pda::find_escrow_address(owner_two, date).0;
+    accounts::DateStakes {
+        escrow_authority: escrow_cpi::pda::find_escrow_authority(&crate::ID).0,
+        escrow_one,
+        vault_one: escrow_cpi::pda::find_vault_address(&escrow_one).0,
+        owner_one: *owner_one,
+        escrow_two,
+        vault_two: escrow_cpi::pda::find_vault_address(&escrow_two).0,
+        owner_two: *owner_two,
+        escrow_program: escrow_cpi::ID,
+    }
+}
+
+// Whether `escrow` holds `owner`'s stake in `date`
+fn is_stake(escrow: &Escrow, date: &Pubkey, owner: &Pubkey) -> bool {
+    escrow.reference == *date && escrow.depositor == *owner
+}
+
+// One owner's date stake and their Influence token account
+struct Stake<'a, 'info> {
+    escrow: &'a Account<'info, Escrow>,
+    vault: &'a AccountInfo<'info>,
+    owner: &'a AccountInfo<'info>,
+    token_account: &'a Account<'info, TokenAccount>,
+}
+
+// Settles a date's stakes through the escrow program, signing as this
+// program's escrow authority
+struct StakeSettlement<'a, 'info> {
+    escrow_authority: &'a AccountInfo<'info>,
+    escrow_program: &'a AccountInfo<'info>,
+    token_program: &'a AccountInfo<'info>,
+    one: Stake<'a, 'info>,
+    two: Stake<'a, 'info>,
+}
+
+impl<'a, 'info> StakeSettlement<'a, 'info> {
+    // `owner`'s stake and the other owner's
+    fn stakes(&self, owner: &Pubkey) -> (&Stake<'a, 'info>, &Stake<'a, 'info>) {
+        if *owner == self.one.escrow.depositor {
+            (&self.one, &self.two)
+        } else {
+            (&self.two, &self.one)
+        }
+    }
+
+    // Return `amount` of `owner`'s stake to them
+    fn refund(&self, owner: &Pubkey, amount: u64) -> Result<()> {
+        let (stake, other) = self.stakes(owner);
+        let bump = escrow_cpi::pda::find_escrow_authority(&crate::ID).1;
+        let signer_seeds: &[&[&[u8]]] = &[&[ESCROW_AUTHORITY_SEED, &[bump]]];
+        escrow_cpi::cpi::refund_tokens(
+            self.settle_context(stake, other.token_account, signer_seeds),
+            amount,
+        )
+    }
+
+    // Pay `amount` of `owner`'s stake to `to`, first naming the owner of
+    // `to` the stake's beneficiary if it is not already
+    fn pay(&self, owner: &Pubkey, to: &Account<'info, TokenAccount>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let (stake, _) = self.stakes(owner);
+        let bump = escrow_cpi::pda::find_escrow_authority(&crate::ID).1;
+        let signer_seeds: &[&[&[u8]]] = &[&[ESCROW_AUTHORITY_SEED, &[bump]]];
+        if stake.escrow.beneficiary != to.owner {
+            escrow_cpi::cpi::set_beneficiary(
+                CpiContext::new_with_signer(
+                    self.escrow_program.clone(),
+                    escrow_cpi::cpi::accounts::SetBeneficiary {
+                        authority: self.escrow_authority.clone(),
+                        escrow: stake.escrow.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                to.owner,
+            )?;
+        }
+        escrow_cpi::cpi::release_tokens(self.settle_context(stake, to, signer_seeds), amount)
+    }
+
+    fn settle_context<'s>(
+        &self,
+        stake: &Stake<'a, 'info>,
+        beneficiary_token_account: &Account<'info, TokenAccount>,
+        signer_seeds: &'s [&'s [&'s [u8]]],
+    ) -> CpiContext<'s, 's, 's, 'info, escrow_cpi::cpi::accounts::SettleTokens<'info>> {
+        CpiContext::new_with_signer(
+            self.escrow_program.clone(),
+            escrow_cpi::cpi::accounts::SettleTokens {
+                authority: self.escrow_authority.clone(),
+                escrow: stake.escrow.to_account_info(),
+                vault: stake.vault.clone(),
+                depositor: stake.owner.clone(),
+                depositor_token_account: stake.token_account.to_account_info(),
+                beneficiary_token_account: beneficiary_token_account.to_account_info(),
+                oracle_worker: None,
+                token_program: self.token_program.clone(),
+            },
+            signer_seeds,
+        )
+    }
+}
+
+fn pda_transfer<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    // Transfer out of a program-owned token account, signing with its PDA seeds
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority,
+    };
+    let signer = &[seeds];
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    token::transfer(cpi_ctx, amount)
+}
+
+// Chain a transcript chunk to the previous segment:
+// sha256(previous_hash || segment_index (u64 le) || chunk_hash || byte_length (u32 le))
+pub fn transcript_segment_hash(
+    previous_hash: &[u8; 32],
+    segment_index: u64,
+    chunk_hash: &[u8; 32],
+    byte_length: u32,
+) -> [u8; 32] {
+    hashv(&[
+        previous_hash,
+        &segment_index.to_le_bytes(),
+        chunk_hash,
+        &byte_length.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// Leaf stored for a compressed interaction:
+// sha256(relationship || leaf_index (u64 le) || interaction_type (u8) || data_hash || timestamp (i64 le))
+pub fn interaction_leaf_hash(
+    relationship: &Pubkey,
+    leaf_index: u64,
+    interaction_type: &InteractionType,
+    interaction_data_hash: &[u8; 32],
+    timestamp: i64,
+) -> [u8; 32] {
+    hashv(&[
+        relationship.as_ref(),
+        &leaf_index.to_le_bytes(),
+        &[interaction_type.clone() as u8],
+        interaction_data_hash,
+        &timestamp.to_le_bytes(),
+    ])
+    .to_bytes()
 }
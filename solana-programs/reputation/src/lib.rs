@@ -0,0 +1,683 @@
+use anchor_lang::prelude::*;
+use agent_registry::AgentData;
+use armour_core::relationship_quality;
+use relationship::{Dispute, DisputeRuling, DisputeStatus, RelationshipData};
+
+declare_id!("Reputat10nPr0graMxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const NEUTRAL_SCORE: u16 = BPS_DENOMINATOR / 2;
+const MIN_RATING: u8 = 1;
+const MAX_RATING: u8 = 5;
+
+#[program]
+pub mod reputation {
+    use super::*;
+
+    // Create the reputation config with its component weights (governance)
+    pub fn initialize_reputation(
+        ctx: Context<InitializeReputation>,
+        weights: ReputationWeights,
+    ) -> Result<()> {
+        weights.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        config.weights = weights;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Replace the component weights (governance). Stored scores pick up the
+    // new weights on their next refresh.
+    pub fn set_weights(ctx: Context<SetWeights>, weights: ReputationWeights) -> Result<()> {
+        weights.validate()?;
+        ctx.accounts.config.weights = weights.clone();
+
+        emit!(WeightsUpdated { weights });
+
+        Ok(())
+    }
+
+    // Sample a relationship's current quality into the agent's match quality
+    // component. Resampling the same relationship replaces its previous
+    // sample, so anyone can call this as often as they like.
+    pub fn sample_relationship(ctx: Context<SampleRelationship>) -> Result<()> {
+        let agent = ctx.accounts.agent_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        require!(
+            relationship_data.agent_one == agent || relationship_data.agent_two == agent,
+            ReputationError::AgentNotInRelationship
+        );
+        let quality = relationship_quality(relationship_data.compatibility_score, relationship_data.health) as u16;
+        drop(relationship_data);
+
+        let profile = &mut ctx.accounts.profile;
+        open_profile(profile, agent, *ctx.bumps.get("profile").unwrap());
+
+        let sample = &mut ctx.accounts.sample;
+        if sample.agent == Pubkey::default() {
+            sample.agent = agent;
+            sample.relationship = ctx.accounts.relationship_data.key();
+            sample.bump = *ctx.bumps.get("sample").unwrap();
+            profile.match_quality_samples = profile
+                .match_quality_samples
+                .checked_add(1)
+                .ok_or(ReputationError::ArithmeticOverflow)?;
+        } else {
+            profile.match_quality_sum -= sample.quality as u64;
+        }
+        profile.match_quality_sum += quality as u64;
+        sample.quality = quality as u8;
+
+        emit!(RelationshipSampled {
+            agent,
+            relationship: sample.relationship,
+            quality: sample.quality,
+        });
+
+        Ok(())
+    }
+
+    // Rate the other agent in a relationship from 1 to 5 stars. Each agent
+    // holds one rating per relationship; rating again replaces it.
+    pub fn rate_agent(ctx: Context<RateAgent>, rating: u8) -> Result<()> {
+        require!(
+            (MIN_RATING..=MAX_RATING).contains(&rating),
+            ReputationError::InvalidRating
+        );
+
+        let rater_agent = ctx.accounts.rater_agent.key();
+        let rated_agent = ctx.accounts.agent_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let pair = (relationship_data.agent_one, relationship_data.agent_two);
+        drop(relationship_data);
+        require!(
+            pair == (rater_agent, rated_agent) || pair == (rated_agent, rater_agent),
+            ReputationError::AgentNotInRelationship
+        );
+
+        let profile = &mut ctx.accounts.profile;
+        open_profile(profile, rated_agent, *ctx.bumps.get("profile").unwrap());
+
+        let record = &mut ctx.accounts.rating;
+        if record.rater_agent == Pubkey::default() {
+            record.relationship = ctx.accounts.relationship_data.key();
+            record.rater_agent = rater_agent;
+            record.rated_agent = rated_agent;
+            record.bump = *ctx.bumps.get("rating").unwrap();
+            profile.rating_count = profile
+                .rating_count
+                .checked_add(1)
+                .ok_or(ReputationError::ArithmeticOverflow)?;
+        } else {
+            profile.rating_sum -= record.rating as u64;
+        }
+        profile.rating_sum += rating as u64;
+        record.rating = rating;
+        record.rated_at = Clock::get()?.unix_timestamp;
+
+        emit!(AgentRated {
+            rater_agent,
+            rated_agent,
+            relationship: record.relationship,
+            rating,
+        });
+
+        Ok(())
+    }
+
+    // Count a resolved dispute against the agent's dispute component. The
+    // at-fault side records a loss and the other side a win; each agent can
+    // record a given dispute once.
+    pub fn record_dispute(ctx: Context<RecordDispute>) -> Result<()> {
+        let dispute = &ctx.accounts.dispute;
+        require!(
+            dispute.status == DisputeStatus::Resolved,
+            ReputationError::DisputeNotResolved
+        );
+        let at_fault = match dispute.ruling {
+            DisputeRuling::OpenerFavored => dispute.respondent,
+            DisputeRuling::RespondentFavored => dispute.opener,
+            _ => return err!(ReputationError::NoFaultFound),
+        };
+
+        let agent = ctx.accounts.agent_data.key();
+        let relationship_data = ctx.accounts.relationship_data.load()?;
+        let agent_owner = if relationship_data.agent_one == agent {
+            relationship_data.owner_one
+        } else if relationship_data.agent_two == agent {
+            relationship_data.owner_two
+        } else {
+            return err!(ReputationError::AgentNotInRelationship);
+        };
+        drop(relationship_data);
+
+        let profile = &mut ctx.accounts.profile;
+        open_profile(profile, agent, *ctx.bumps.get("profile").unwrap());
+        let at_fault = agent_owner == at_fault;
+        if at_fault {
+            profile.disputes_lost = profile.disputes_lost.checked_add(1).unwrap_or(u32::MAX);
+        } else {
+            profile.disputes_won = profile.disputes_won.checked_add(1).unwrap_or(u32::MAX);
+        }
+
+        let record = &mut ctx.accounts.dispute_record;
+        record.dispute = ctx.accounts.dispute.key();
+        record.agent = agent;
+        record.at_fault = at_fault;
+        record.bump = *ctx.bumps.get("dispute_record").unwrap();
+
+        emit!(DisputeRecorded {
+            agent,
+            dispute: record.dispute,
+            at_fault,
+        });
+
+        Ok(())
+    }
+
+    // Recompute the agent's normalized score from its stored inputs and its
+    // current tournament record, and roll it into the owner's aggregate. If
+    // the agent changed hands since the last refresh, the previous owner's
+    // aggregate must be passed so the agent can be moved out of it.
+    pub fn refresh_reputation(ctx: Context<RefreshReputation>) -> Result<()> {
+        let agent_data = &ctx.accounts.agent_data;
+        let profile = &mut ctx.accounts.profile;
+        open_profile(profile, agent_data.key(), *ctx.bumps.get("profile").unwrap());
+
+        profile.wins = agent_data.wins;
+        profile.losses = agent_data.losses;
+        let previous_score = profile.score;
+        profile.score = profile.weighted_score(&ctx.accounts.config.weights);
+        profile.updated_at = Clock::get()?.unix_timestamp;
+
+        let owner_reputation = &mut ctx.accounts.owner_reputation;
+        if owner_reputation.owner == Pubkey::default() {
+            owner_reputation.owner = agent_data.owner;
+            owner_reputation.bump = *ctx.bumps.get("owner_reputation").unwrap();
+        }
+        if profile.owner == agent_data.owner {
+            owner_reputation.score_sum =
+                owner_reputation.score_sum - previous_score as u64 + profile.score as u64;
+        } else {
+            if profile.owner != Pubkey::default() {
+                let Some(previous) = ctx.accounts.previous_owner_reputation.as_mut() else {
+                    return err!(ReputationError::MissingPreviousOwner);
+                };
+                previous.agent_count -= 1;
+                previous.score_sum -= previous_score as u64;
+                previous.score = previous.average();
+            }
+            profile.owner = agent_data.owner;
+            owner_reputation.agent_count = owner_reputation
+                .agent_count
+                .checked_add(1)
+                .ok_or(ReputationError::ArithmeticOverflow)?;
+            owner_reputation.score_sum += profile.score as u64;
+        }
+        owner_reputation.score = owner_reputation.average();
+        owner_reputation.updated_at = profile.updated_at;
+
+        emit!(ReputationRefreshed {
+            agent: profile.agent,
+            owner: profile.owner,
+            score: profile.score,
+            owner_score: owner_reputation.score,
+            timestamp: profile.updated_at,
+        });
+
+        Ok(())
+    }
+
+    // Fail unless the agent's reputation is at least `min_score` and was
+    // refreshed within `max_age` seconds (0 accepts any age). The
+    // marketplace and matchmaking CPI into this; programs that already load
+    // the profile can use `ReputationProfile::meets` instead.
+    pub fn check_reputation(
+        ctx: Context<CheckReputation>,
+        min_score: u16,
+        max_age: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.profile.meets(min_score, max_age, now),
+            ReputationError::ReputationTooLow
+        );
+
+        Ok(())
+    }
+
+    // Wallet-level counterpart of `check_reputation`
+    pub fn check_owner_reputation(
+        ctx: Context<CheckOwnerReputation>,
+        min_score: u16,
+        max_age: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.owner_reputation.meets(min_score, max_age, now),
+            ReputationError::ReputationTooLow
+        );
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct ReputationConfig {
+    pub weights: ReputationWeights,
+    pub bump: u8,
+}
+
+// Per-agent reputation inputs and the last computed score. Scores and
+// components are in basis points, 0-10000.
+#[account]
+pub struct ReputationProfile {
+    pub agent: Pubkey,
+    pub owner: Pubkey,  // Owner whose aggregate includes this agent, default until refreshed
+    pub match_quality_sum: u64,  // Sum of relationship samples, 0-100 each
+    pub match_quality_samples: u32,
+    pub rating_sum: u64,
+    pub rating_count: u32,
+    pub disputes_won: u32,
+    pub disputes_lost: u32,
+    pub wins: u32,    // Tournament record as of the last refresh
+    pub losses: u32,
+    pub score: u16,
+    pub updated_at: i64,  // 0 until the first refresh
+    pub bump: u8,
+}
+
+impl ReputationProfile {
+    pub fn match_quality_score(&self) -> u16 {
+        if self.match_quality_samples == 0 {
+            return NEUTRAL_SCORE;
+        }
+        (self.match_quality_sum * 100 / self.match_quality_samples as u64) as u16
+    }
+
+    // Maps the average rating from 1-5 stars onto 0-10000
+    pub fn rating_score(&self) -> u16 {
+        if self.rating_count == 0 {
+            return NEUTRAL_SCORE;
+        }
+        let count = self.rating_count as u64;
+        let span = (MAX_RATING - MIN_RATING) as u64;
+        ((self.rating_sum - count * MIN_RATING as u64) * BPS_DENOMINATOR as u64
+            / (count * span)) as u16
+    }
+
+    // Starts at the full score and drops with each dispute lost
+    pub fn dispute_score(&self) -> u16 {
+        let won = self.disputes_won as u64;
+        let lost = self.disputes_lost as u64;
+        ((won + 1) * BPS_DENOMINATOR as u64 / (won + lost + 1)) as u16
+    }
+
+    // Smoothed win rate, neutral before the first tournament match
+    pub fn tournament_score(&self) -> u16 {
+        let wins = self.wins as u64;
+        let losses = self.losses as u64;
+        ((wins + 1) * BPS_DENOMINATOR as u64 / (wins + losses + 2)) as u16
+    }
+
+    pub fn weighted_score(&self, weights: &ReputationWeights) -> u16 {
+        let weighted = self.match_quality_score() as u64 * weights.match_quality_bps as u64
+            + self.rating_score() as u64 * weights.rating_bps as u64
+            + self.dispute_score() as u64 * weights.dispute_bps as u64
+            + self.tournament_score() as u64 * weights.tournament_bps as u64;
+        (weighted / BPS_DENOMINATOR as u64) as u16
+    }
+
+    pub fn meets(&self, min_score: u16, max_age: i64, now: i64) -> bool {
+        self.updated_at > 0
+            && self.score >= min_score
+            && (max_age == 0 || now - self.updated_at <= max_age)
+    }
+}
+
+// Average score across the agents a wallet owns
+#[account]
+pub struct OwnerReputation {
+    pub owner: Pubkey,
+    pub agent_count: u32,
+    pub score_sum: u64,
+    pub score: u16,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl OwnerReputation {
+    pub fn average(&self) -> u16 {
+        if self.agent_count == 0 {
+            return 0;
+        }
+        (self.score_sum / self.agent_count as u64) as u16
+    }
+
+    pub fn meets(&self, min_score: u16, max_age: i64, now: i64) -> bool {
+        self.agent_count > 0
+            && self.score >= min_score
+            && (max_age == 0 || now - self.updated_at <= max_age)
+    }
+}
+
+// Latest quality sample an agent took from one relationship
+#[account]
+pub struct RelationshipSample {
+    pub agent: Pubkey,
+    pub relationship: Pubkey,
+    pub quality: u8,  // Average of compatibility score and health, 0-100
+    pub bump: u8,
+}
+
+#[account]
+pub struct Rating {
+    pub relationship: Pubkey,
+    pub rater_agent: Pubkey,
+    pub rated_agent: Pubkey,
+    pub rating: u8,
+    pub rated_at: i64,
+    pub bump: u8,
+}
+
+// Marks a dispute as counted for one agent
+#[account]
+pub struct DisputeRecord {
+    pub dispute: Pubkey,
+    pub agent: Pubkey,
+    pub at_fault: bool,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = governance_authority,
+        space = 8 + std::mem::size_of::<ReputationConfig>(),
+        seeds = [b"reputation_config"],
+        bump
+    )]
+    pub config: Account<'info, ReputationConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetWeights<'info> {
+    #[account(
+        seeds = [b"governance_authority"],
+        bump,
+        seeds::program = governance::ID
+    )]
+    pub governance_authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"reputation_config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SampleRelationship<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<ReputationProfile>(),
+        seeds = [b"reputation", agent_data.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ReputationProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<RelationshipSample>(),
+        seeds = [
+            b"relationship_sample",
+            agent_data.key().as_ref(),
+            relationship_data.key().as_ref()
+        ],
+        bump
+    )]
+    pub sample: Account<'info, RelationshipSample>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RateAgent<'info> {
+    #[account(mut)]
+    pub rater: Signer<'info>,
+
+    #[account(constraint = rater_agent.owner == rater.key() @ ReputationError::Unauthorized)]
+    pub rater_agent: Account<'info, AgentData>,
+
+    // The agent being rated
+    pub agent_data: Account<'info, AgentData>,
+
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init_if_needed,
+        payer = rater,
+        space = 8 + std::mem::size_of::<ReputationProfile>(),
+        seeds = [b"reputation", agent_data.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ReputationProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = rater,
+        space = 8 + std::mem::size_of::<Rating>(),
+        seeds = [
+            b"rating",
+            relationship_data.key().as_ref(),
+            rater_agent.key().as_ref()
+        ],
+        bump
+    )]
+    pub rating: Account<'info, Rating>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordDispute<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(address = dispute.relationship)]
+    pub relationship_data: AccountLoader<'info, RelationshipData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<ReputationProfile>(),
+        seeds = [b"reputation", agent_data.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ReputationProfile>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<DisputeRecord>(),
+        seeds = [b"dispute_record", dispute.key().as_ref(), agent_data.key().as_ref()],
+        bump
+    )]
+    pub dispute_record: Account<'info, DisputeRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshReputation<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"reputation_config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<ReputationProfile>(),
+        seeds = [b"reputation", agent_data.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, ReputationProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + std::mem::size_of::<OwnerReputation>(),
+        seeds = [b"owner_reputation", agent_data.owner.as_ref()],
+        bump
+    )]
+    pub owner_reputation: Account<'info, OwnerReputation>,
+
+    // Only needed when the agent changed owners since its last refresh
+    #[account(
+        mut,
+        seeds = [b"owner_reputation", profile.owner.as_ref()],
+        bump = previous_owner_reputation.bump,
+        constraint = profile.owner != agent_data.owner @ ReputationError::OwnerUnchanged,
+    )]
+    pub previous_owner_reputation: Option<Account<'info, OwnerReputation>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckReputation<'info> {
+    #[account(seeds = [b"reputation", profile.agent.as_ref()], bump = profile.bump)]
+    pub profile: Account<'info, ReputationProfile>,
+}
+
+#[derive(Accounts)]
+pub struct CheckOwnerReputation<'info> {
+    #[account(
+        seeds = [b"owner_reputation", owner_reputation.owner.as_ref()],
+        bump = owner_reputation.bump,
+    )]
+    pub owner_reputation: Account<'info, OwnerReputation>,
+}
+
+// Data structures
+// Share of each component in the overall score; must sum to 10000
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ReputationWeights {
+    pub match_quality_bps: u16,
+    pub rating_bps: u16,
+    pub dispute_bps: u16,
+    pub tournament_bps: u16,
+}
+
+impl ReputationWeights {
+    pub fn validate(&self) -> Result<()> {
+        let total = self.match_quality_bps as u32
+            + self.rating_bps as u32
+            + self.dispute_bps as u32
+            + self.tournament_bps as u32;
+        require!(
+            total == BPS_DENOMINATOR as u32,
+            ReputationError::InvalidWeights
+        );
+        Ok(())
+    }
+}
+
+// Events
+#[event]
+pub struct WeightsUpdated {
+    pub weights: ReputationWeights,
+}
+
+#[event]
+pub struct RelationshipSampled {
+    pub agent: Pubkey,
+    pub relationship: Pubkey,
+    pub quality: u8,
+}
+
+#[event]
+pub struct AgentRated {
+    pub rater_agent: Pubkey,
+    pub rated_agent: Pubkey,
+    pub relationship: Pubkey,
+    pub rating: u8,
+}
+
+#[event]
+pub struct DisputeRecorded {
+    pub agent: Pubkey,
+    pub dispute: Pubkey,
+    pub at_fault: bool,
+}
+
+#[event]
+pub struct ReputationRefreshed {
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub score: u16,
+    pub owner_score: u16,
+    pub timestamp: i64,
+}
+
+// Custom errors
+#[error_code]
+pub enum ReputationError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Weights must sum to 10000 basis points")]
+    InvalidWeights,
+    #[msg("Agent is not part of this relationship")]
+    AgentNotInRelationship,
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+    #[msg("Dispute is not resolved")]
+    DisputeNotResolved,
+    #[msg("Dispute ruling does not assign fault")]
+    NoFaultFound,
+    #[msg("The agent's previous owner aggregate is required")]
+    MissingPreviousOwner,
+    #[msg("The agent has not changed owners")]
+    OwnerUnchanged,
+    #[msg("Reputation is below the required score or out of date")]
+    ReputationTooLow,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+fn open_profile(profile: &mut ReputationProfile, agent: Pubkey, bump: u8) {
+    if profile.agent == Pubkey::default() {
+        profile.agent = agent;
+        profile.bump = bump;
+    }
+}
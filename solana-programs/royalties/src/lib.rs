@@ -0,0 +1,731 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("Roya1tiesPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxX");
+
+pub const ROYALTY_CONFIG_SEED: &[u8] = b"royalty_config";
+pub const SOL_VAULT_SEED: &[u8] = b"royalty_sol_vault";
+pub const TOKEN_VAULT_SEED: &[u8] = b"royalty_token_vault";
+pub const PAYEE_SEED: &[u8] = b"payee";
+pub const REGISTRANT_SEED: &[u8] = b"registrant";
+// Fee source programs sign `distribute_fee` with their PDA at this seed
+pub const ROYALTY_SOURCE_SEED: &[u8] = b"royalty_source";
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_CREATORS: usize = 5;
+const MAX_SOURCES: usize = 4;
+
+#[program]
+pub mod royalties {
+    use super::*;
+
+    // Create the config, the SOL and Influence vaults that fee sources pay
+    // into, and the treasury's payee balance. Creator payee balances must
+    // already be open and are passed as remaining accounts, in split order.
+    pub fn initialize_royalties<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeRoyalties<'info>>,
+        split: RoyaltySplit,
+    ) -> Result<()> {
+        split.validate()?;
+        validate_creator_payees(&split, ctx.remaining_accounts)?;
+
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.treasury = ctx.accounts.treasury.key();
+        config.split = split;
+        config.sources = Vec::new();
+        config.sol_accounted = 0;
+        config.tokens_accounted = 0;
+        config.bump = *ctx.bumps.get("config").unwrap();
+        ctx.accounts.sol_vault.bump = *ctx.bumps.get("sol_vault").unwrap();
+
+        let treasury_payee = &mut ctx.accounts.treasury_payee;
+        treasury_payee.payee = config.treasury;
+        treasury_payee.bump = *ctx.bumps.get("treasury_payee").unwrap();
+
+        Ok(())
+    }
+
+    // Replace the fee split, e.g. from a passed governance proposal. Applies
+    // to fees distributed from now on; credited balances are unaffected.
+    pub fn set_split<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateRoyalties<'info>>,
+        split: RoyaltySplit,
+    ) -> Result<()> {
+        split.validate()?;
+        validate_creator_payees(&split, ctx.remaining_accounts)?;
+        ctx.accounts.config.split = split.clone();
+
+        emit!(SplitUpdated { split });
+
+        Ok(())
+    }
+
+    // Replace the programs allowed to attribute fees to an agent
+    pub fn set_sources(ctx: Context<UpdateRoyalties>, sources: Vec<Pubkey>) -> Result<()> {
+        require!(sources.len() <= MAX_SOURCES, RoyaltyError::TooManySources);
+        ctx.accounts.config.sources = sources.clone();
+
+        emit!(SourcesUpdated { sources });
+
+        Ok(())
+    }
+
+    // Hand the config to another key, such as the governance authority
+    pub fn set_royalty_authority(
+        ctx: Context<UpdateRoyalties>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+        config.authority = authority;
+
+        emit!(RoyaltyAuthorityUpdated {
+            previous_authority,
+            authority,
+        });
+
+        Ok(())
+    }
+
+    // Open a payee balance (anyone; the payer covers the rent)
+    pub fn open_payee(ctx: Context<OpenPayee>, payee: Pubkey) -> Result<()> {
+        let balance = &mut ctx.accounts.payee_balance;
+        balance.payee = payee;
+        balance.bump = *ctx.bumps.get("payee_balance").unwrap();
+
+        Ok(())
+    }
+
+    // Record an agent's original registrant (authority, from the registry's
+    // registration events) and open their payee balance
+    pub fn set_registrant(
+        ctx: Context<SetRegistrant>,
+        agent: Pubkey,
+        registrant: Pubkey,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.registrant_record;
+        record.agent = agent;
+        record.registrant = registrant;
+        record.bump = *ctx.bumps.get("registrant_record").unwrap();
+
+        let balance = &mut ctx.accounts.registrant_payee;
+        balance.payee = registrant;
+        balance.bump = *ctx.bumps.get("registrant_payee").unwrap();
+
+        emit!(RegistrantRecorded { agent, registrant });
+
+        Ok(())
+    }
+
+    // Split a fee from an agent's sale. Called over CPI by an allowed source
+    // program right after it pays `amount` into the matching vault. The
+    // registrant's share goes to the treasury if the agent has none recorded.
+    pub fn distribute_fee<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeFee<'info>>,
+        agent: Pubkey,
+        amount: u64,
+        currency: FeeCurrency,
+    ) -> Result<()> {
+        let source_authority = ctx.accounts.source_authority.key();
+        require!(
+            ctx.accounts
+                .config
+                .sources
+                .iter()
+                .any(|program| royalty_source_authority(program) == source_authority),
+            RoyaltyError::Unauthorized
+        );
+        let unaccounted = unaccounted_balance(
+            &ctx.accounts.config,
+            &ctx.accounts.sol_vault,
+            &ctx.accounts.token_vault,
+            currency,
+        )?;
+        require!(amount > 0 && amount <= unaccounted, RoyaltyError::InvalidAmount);
+
+        let registrant_payee = match (&ctx.accounts.registrant_record, &mut ctx.accounts.registrant_payee) {
+            (Some(record), Some(payee)) => {
+                require_keys_eq!(payee.payee, record.registrant, RoyaltyError::PayeeMismatch);
+                Some(payee)
+            }
+            (None, None) => None,
+            _ => return err!(RoyaltyError::PayeeMismatch),
+        };
+        credit_split(
+            &mut ctx.accounts.config,
+            &mut ctx.accounts.treasury_payee,
+            registrant_payee,
+            ctx.remaining_accounts,
+            amount,
+            currency,
+        )?;
+
+        emit!(FeeDistributed {
+            agent,
+            amount,
+            currency,
+        });
+
+        Ok(())
+    }
+
+    // Split vault balances no source attributed, such as fees paid straight
+    // to the vaults, between the treasury and creators (permissionless)
+    pub fn distribute_surplus<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributeSurplus<'info>>,
+    ) -> Result<()> {
+        for currency in [FeeCurrency::Sol, FeeCurrency::Influence] {
+            let amount = unaccounted_balance(
+                &ctx.accounts.config,
+                &ctx.accounts.sol_vault,
+                &ctx.accounts.token_vault,
+                currency,
+            )?;
+            if amount == 0 {
+                continue;
+            }
+            credit_split(
+                &mut ctx.accounts.config,
+                &mut ctx.accounts.treasury_payee,
+                None,
+                ctx.remaining_accounts,
+                amount,
+                currency,
+            )?;
+
+            emit!(FeeDistributed {
+                agent: Pubkey::default(),
+                amount,
+                currency,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Withdraw the signer's SOL and Influence balances
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        let accounts = ctx.accounts;
+        pay_payee(
+            &mut accounts.config,
+            &accounts.sol_vault,
+            &accounts.token_vault,
+            &mut accounts.payee_balance,
+            &accounts.payee.to_account_info(),
+            &accounts.payee_token_account.to_account_info(),
+            &accounts.token_program,
+        )
+    }
+
+    // Push balances out to several payees (permissionless). Remaining
+    // accounts are triples of payee balance, payee wallet and the payee's
+    // Influence token account.
+    pub fn batch_claim<'info>(ctx: Context<'_, '_, '_, 'info, BatchClaim<'info>>) -> Result<()> {
+        require!(
+            !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len() % 3 == 0,
+            RoyaltyError::PayeeMismatch
+        );
+        for triple in ctx.remaining_accounts.chunks(3) {
+            let [balance_info, payee, payee_token_account] = triple else {
+                unreachable!()
+            };
+            let mut balance = Account::<PayeeBalance>::try_from(balance_info)?;
+            require_keys_eq!(
+                balance.key(),
+                Pubkey::find_program_address(&[PAYEE_SEED, balance.payee.as_ref()], &ID).0,
+                RoyaltyError::PayeeMismatch
+            );
+            require_keys_eq!(payee.key(), balance.payee, RoyaltyError::PayeeMismatch);
+            let token_account = Account::<TokenAccount>::try_from(payee_token_account)?;
+            require_keys_eq!(token_account.owner, balance.payee, RoyaltyError::PayeeMismatch);
+
+            pay_payee(
+                &mut ctx.accounts.config,
+                &ctx.accounts.sol_vault,
+                &ctx.accounts.token_vault,
+                &mut balance,
+                payee,
+                payee_token_account,
+                &ctx.accounts.token_program,
+            )?;
+            balance.exit(&ID)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct RoyaltyConfig {
+    pub authority: Pubkey,      // Sets splits and sources; typically governance
+    pub influence_mint: Pubkey,
+    pub treasury: Pubkey,       // Platform treasury payee
+    pub split: RoyaltySplit,
+    pub sources: Vec<Pubkey>,   // Programs allowed to attribute fees to agents
+    pub sol_accounted: u64,     // Credited to payees and not yet claimed
+    pub tokens_accounted: u64,
+    pub bump: u8,
+}
+
+// Holds SOL fees; program-owned so claims can debit it directly
+#[account]
+pub struct RoyaltyVault {
+    pub bump: u8,
+}
+
+// What a payee can claim
+#[account]
+pub struct PayeeBalance {
+    pub payee: Pubkey,
+    pub sol_owed: u64,
+    pub tokens_owed: u64,
+    pub sol_claimed: u64,
+    pub tokens_claimed: u64,
+    pub bump: u8,
+}
+
+// An agent's original registrant, who shares in its later sales
+#[account]
+pub struct RegistrantRecord {
+    pub agent: Pubkey,
+    pub registrant: Pubkey,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeRoyalties<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RoyaltyConfig>() + MAX_CREATORS * 34 + MAX_SOURCES * 32,
+        seeds = [ROYALTY_CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, RoyaltyConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RoyaltyVault>(),
+        seeds = [SOL_VAULT_SEED],
+        bump
+    )]
+    pub sol_vault: Account<'info, RoyaltyVault>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [TOKEN_VAULT_SEED],
+        bump,
+        token::mint = influence_mint,
+        token::authority = config,
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    /// CHECK: the treasury wallet; any key
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PayeeBalance>(),
+        seeds = [PAYEE_SEED, treasury.key().as_ref()],
+        bump
+    )]
+    pub treasury_payee: Account<'info, PayeeBalance>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRoyalties<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ROYALTY_CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority @ RoyaltyError::Unauthorized,
+    )]
+    pub config: Account<'info, RoyaltyConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(payee: Pubkey)]
+pub struct OpenPayee<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PayeeBalance>(),
+        seeds = [PAYEE_SEED, payee.as_ref()],
+        bump
+    )]
+    pub payee_balance: Account<'info, PayeeBalance>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent: Pubkey, registrant: Pubkey)]
+pub struct SetRegistrant<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [ROYALTY_CONFIG_SEED],
+        bump = config.bump,
+        has_one = authority @ RoyaltyError::Unauthorized,
+    )]
+    pub config: Account<'info, RoyaltyConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RegistrantRecord>(),
+        seeds = [REGISTRANT_SEED, agent.as_ref()],
+        bump
+    )]
+    pub registrant_record: Account<'info, RegistrantRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PayeeBalance>(),
+        seeds = [PAYEE_SEED, registrant.as_ref()],
+        bump
+    )]
+    pub registrant_payee: Account<'info, PayeeBalance>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent: Pubkey)]
+pub struct DistributeFee<'info> {
+    // The source program's ROYALTY_SOURCE_SEED PDA
+    pub source_authority: Signer<'info>,
+
+    #[account(mut, seeds = [ROYALTY_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, RoyaltyConfig>,
+
+    #[account(seeds = [SOL_VAULT_SEED], bump = sol_vault.bump)]
+    pub sol_vault: Account<'info, RoyaltyVault>,
+
+    #[account(seeds = [TOKEN_VAULT_SEED], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [PAYEE_SEED, config.treasury.as_ref()], bump = treasury_payee.bump)]
+    pub treasury_payee: Account<'info, PayeeBalance>,
+
+    // Both present when the agent has a recorded registrant
+    #[account(seeds = [REGISTRANT_SEED, agent.as_ref()], bump = registrant_record.bump)]
+    pub registrant_record: Option<Account<'info, RegistrantRecord>>,
+
+    #[account(mut)]
+    pub registrant_payee: Option<Account<'info, PayeeBalance>>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeSurplus<'info> {
+    #[account(mut, seeds = [ROYALTY_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, RoyaltyConfig>,
+
+    #[account(seeds = [SOL_VAULT_SEED], bump = sol_vault.bump)]
+    pub sol_vault: Account<'info, RoyaltyVault>,
+
+    #[account(seeds = [TOKEN_VAULT_SEED], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [PAYEE_SEED, config.treasury.as_ref()], bump = treasury_payee.bump)]
+    pub treasury_payee: Account<'info, PayeeBalance>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut)]
+    pub payee: Signer<'info>,
+
+    #[account(mut, seeds = [ROYALTY_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, RoyaltyConfig>,
+
+    #[account(mut, seeds = [SOL_VAULT_SEED], bump = sol_vault.bump)]
+    pub sol_vault: Account<'info, RoyaltyVault>,
+
+    #[account(mut, seeds = [TOKEN_VAULT_SEED], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    #[account(mut, seeds = [PAYEE_SEED, payee.key().as_ref()], bump = payee_balance.bump)]
+    pub payee_balance: Account<'info, PayeeBalance>,
+
+    #[account(
+        mut,
+        constraint = payee_token_account.owner == payee.key() @ RoyaltyError::PayeeMismatch,
+    )]
+    pub payee_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BatchClaim<'info> {
+    #[account(mut, seeds = [ROYALTY_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, RoyaltyConfig>,
+
+    #[account(mut, seeds = [SOL_VAULT_SEED], bump = sol_vault.bump)]
+    pub sol_vault: Account<'info, RoyaltyVault>,
+
+    #[account(mut, seeds = [TOKEN_VAULT_SEED], bump)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+// Shares of each fee in basis points; they sum to 10000
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct RoyaltySplit {
+    pub treasury_bps: u16,
+    pub registrant_bps: u16,
+    pub creators: Vec<CreatorShare>,
+}
+
+impl RoyaltySplit {
+    fn validate(&self) -> Result<()> {
+        require!(self.creators.len() <= MAX_CREATORS, RoyaltyError::TooManyCreators);
+        let total = self
+            .creators
+            .iter()
+            .fold(self.treasury_bps as u32 + self.registrant_bps as u32, |total, creator| {
+                total + creator.bps as u32
+            });
+        require!(total == BPS_DENOMINATOR as u32, RoyaltyError::InvalidSplit);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CreatorShare {
+    pub payee: Pubkey,
+    pub bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeCurrency {
+    Sol,
+    Influence,
+}
+
+// Events
+#[event]
+pub struct FeeDistributed {
+    pub agent: Pubkey,  // Pubkey::default() for unattributed surplus
+    pub amount: u64,
+    pub currency: FeeCurrency,
+}
+
+#[event]
+pub struct RoyaltyClaimed {
+    pub payee: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+#[event]
+pub struct RegistrantRecorded {
+    pub agent: Pubkey,
+    pub registrant: Pubkey,
+}
+
+#[event]
+pub struct SplitUpdated {
+    pub split: RoyaltySplit,
+}
+
+#[event]
+pub struct SourcesUpdated {
+    pub sources: Vec<Pubkey>,
+}
+
+#[event]
+pub struct RoyaltyAuthorityUpdated {
+    pub previous_authority: Pubkey,
+    pub authority: Pubkey,
+}
+
+// Custom errors
+#[error_code]
+pub enum RoyaltyError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Split shares must sum to 10000 bps")]
+    InvalidSplit,
+    #[msg("Too many creators in the split")]
+    TooManyCreators,
+    #[msg("Too many fee sources")]
+    TooManySources,
+    #[msg("Amount must be positive and no more than the vault's undistributed balance")]
+    InvalidAmount,
+    #[msg("Payee accounts do not match the split or registrant")]
+    PayeeMismatch,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// The PDA a source program signs `distribute_fee` with
+pub fn royalty_source_authority(program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ROYALTY_SOURCE_SEED], program).0
+}
+
+// Creator payee balances are passed in split order
+fn validate_creator_payees(split: &RoyaltySplit, accounts: &[AccountInfo]) -> Result<()> {
+    require!(accounts.len() == split.creators.len(), RoyaltyError::PayeeMismatch);
+    for (creator, account) in split.creators.iter().zip(accounts) {
+        let balance = Account::<PayeeBalance>::try_from(account)?;
+        require_keys_eq!(balance.payee, creator.payee, RoyaltyError::PayeeMismatch);
+    }
+    Ok(())
+}
+
+// Vault balance not yet credited to any payee
+fn unaccounted_balance(
+    config: &RoyaltyConfig,
+    sol_vault: &Account<RoyaltyVault>,
+    token_vault: &TokenAccount,
+    currency: FeeCurrency,
+) -> Result<u64> {
+    Ok(match currency {
+        FeeCurrency::Sol => {
+            let info = sol_vault.to_account_info();
+            let rent = Rent::get()?.minimum_balance(info.data_len());
+            info.lamports().saturating_sub(rent).saturating_sub(config.sol_accounted)
+        }
+        FeeCurrency::Influence => token_vault.amount.saturating_sub(config.tokens_accounted),
+    })
+}
+
+fn credit(balance: &mut PayeeBalance, amount: u64, currency: FeeCurrency) -> Result<()> {
+    let owed = match currency {
+        FeeCurrency::Sol => &mut balance.sol_owed,
+        FeeCurrency::Influence => &mut balance.tokens_owed,
+    };
+    *owed = owed.checked_add(amount).ok_or(RoyaltyError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+fn share(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+// Credit `amount` across the split. Rounding dust, and the registrant share
+// when there is no registrant, go to the treasury.
+fn credit_split<'info>(
+    config: &mut Account<'info, RoyaltyConfig>,
+    treasury_payee: &mut Account<'info, PayeeBalance>,
+    registrant_payee: Option<&mut Account<'info, PayeeBalance>>,
+    creator_payees: &[AccountInfo<'info>],
+    amount: u64,
+    currency: FeeCurrency,
+) -> Result<()> {
+    let split = &config.split;
+    require!(creator_payees.len() == split.creators.len(), RoyaltyError::PayeeMismatch);
+
+    let mut remaining = amount;
+    for (creator, account) in split.creators.iter().zip(creator_payees) {
+        let mut balance = Account::<PayeeBalance>::try_from(account)?;
+        require_keys_eq!(
+            balance.key(),
+            Pubkey::find_program_address(&[PAYEE_SEED, creator.payee.as_ref()], &ID).0,
+            RoyaltyError::PayeeMismatch
+        );
+        let creator_share = share(amount, creator.bps);
+        credit(&mut balance, creator_share, currency)?;
+        balance.exit(&ID)?;
+        remaining -= creator_share;
+    }
+    if let Some(registrant_payee) = registrant_payee {
+        let registrant_share = share(amount, split.registrant_bps);
+        credit(registrant_payee, registrant_share, currency)?;
+        remaining -= registrant_share;
+    }
+    credit(treasury_payee, remaining, currency)?;
+
+    let accounted = match currency {
+        FeeCurrency::Sol => &mut config.sol_accounted,
+        FeeCurrency::Influence => &mut config.tokens_accounted,
+    };
+    *accounted = accounted.checked_add(amount).ok_or(RoyaltyError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Pay out everything a payee is owed
+fn pay_payee<'info>(
+    config: &mut Account<'info, RoyaltyConfig>,
+    sol_vault: &Account<'info, RoyaltyVault>,
+    token_vault: &Account<'info, TokenAccount>,
+    balance: &mut Account<'info, PayeeBalance>,
+    payee: &AccountInfo<'info>,
+    payee_token_account: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let (sol_amount, token_amount) = (balance.sol_owed, balance.tokens_owed);
+    require!(sol_amount > 0 || token_amount > 0, RoyaltyError::NothingToClaim);
+
+    if sol_amount > 0 {
+        let from = sol_vault.to_account_info();
+        **from.try_borrow_mut_lamports()? = from
+            .lamports()
+            .checked_sub(sol_amount)
+            .ok_or(RoyaltyError::ArithmeticOverflow)?;
+        **payee.try_borrow_mut_lamports()? = payee
+            .lamports()
+            .checked_add(sol_amount)
+            .ok_or(RoyaltyError::ArithmeticOverflow)?;
+    }
+    if token_amount > 0 {
+        let config_seeds: &[&[u8]] = &[ROYALTY_CONFIG_SEED, &[config.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: token_vault.to_account_info(),
+                    to: payee_token_account.clone(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            token_amount,
+        )?;
+    }
+
+    balance.sol_owed = 0;
+    balance.tokens_owed = 0;
+    balance.sol_claimed = balance.sol_claimed.saturating_add(sol_amount);
+    balance.tokens_claimed = balance.tokens_claimed.saturating_add(token_amount);
+    config.sol_accounted -= sol_amount;
+    config.tokens_accounted -= token_amount;
+
+    emit!(RoyaltyClaimed {
+        payee: balance.payee,
+        sol_amount,
+        token_amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,368 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+declare_id!("Sess10nsPr0graMxXxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+// Actions a session key may take on its owner's behalf
+pub const SCOPE_RECORD_INTERACTIONS: u8 = 1 << 0;  // relationship interaction recording
+pub const SCOPE_TIP: u8 = 1 << 1;                  // influence transfers, up to the tip limit
+pub const SCOPE_AGENT_STATUS: u8 = 1 << 2;         // registry active/inactive toggles
+const ALL_SCOPES: u8 = SCOPE_RECORD_INTERACTIONS | SCOPE_TIP | SCOPE_AGENT_STATUS;
+
+const MAX_SESSION_DURATION: i64 = 7 * 24 * 60 * 60;
+
+#[program]
+pub mod sessions {
+    use super::*;
+
+    // Authorize `session_signer` (typically a keypair held by the game
+    // client) to act for the owner within `scopes` until the session expires.
+    // With SCOPE_TIP, the session PDA is approved as delegate over the owner's
+    // influence token account for `tip_limit`, and tips go through `spend_tip`.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_signer: Pubkey,
+        scopes: u8,
+        tip_limit: u64,
+        duration: i64,
+    ) -> Result<()> {
+        require!(
+            scopes != 0 && scopes & !ALL_SCOPES == 0,
+            SessionError::InvalidScopes
+        );
+        require!(
+            duration > 0 && duration <= MAX_SESSION_DURATION,
+            SessionError::InvalidDuration
+        );
+        require_keys_neq!(
+            session_signer,
+            ctx.accounts.owner.key(),
+            SessionError::InvalidSessionSigner
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let session = &mut ctx.accounts.session;
+        session.owner = ctx.accounts.owner.key();
+        session.session_signer = session_signer;
+        session.scopes = scopes;
+        session.tip_limit = if scopes & SCOPE_TIP != 0 { tip_limit } else { 0 };
+        session.tipped = 0;
+        session.created_at = now;
+        session.expires_at = now + duration;
+        session.bump = *ctx.bumps.get("session").unwrap();
+
+        if session.tip_limit > 0 {
+            let (Some(tip_token_account), Some(token_program)) =
+                (&ctx.accounts.tip_token_account, &ctx.accounts.token_program)
+            else {
+                return err!(SessionError::MissingTipAccount);
+            };
+            require_keys_eq!(
+                tip_token_account.owner,
+                session.owner,
+                SessionError::Unauthorized
+            );
+            token::approve(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    token::Approve {
+                        to: tip_token_account.to_account_info(),
+                        delegate: session.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                session.tip_limit,
+            )?;
+        }
+
+        emit!(SessionCreated {
+            session: session.key(),
+            owner: session.owner,
+            session_signer,
+            scopes,
+            tip_limit: session.tip_limit,
+            expires_at: session.expires_at,
+        });
+
+        Ok(())
+    }
+
+    // End a session early. The owner or the session signer may revoke; the
+    // owner can also pass the tip token account to drop the delegation.
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        let session = &ctx.accounts.session;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == session.owner || authority == session.session_signer,
+            SessionError::Unauthorized
+        );
+
+        if let (Some(tip_token_account), Some(token_program)) =
+            (&ctx.accounts.tip_token_account, &ctx.accounts.token_program)
+        {
+            require_keys_eq!(authority, session.owner, SessionError::Unauthorized);
+            if tip_token_account.delegate == Some(session.key()).into() {
+                token::revoke(CpiContext::new(
+                    token_program.to_account_info(),
+                    token::Revoke {
+                        source: tip_token_account.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ))?;
+            }
+        }
+
+        emit!(SessionRevoked {
+            session: session.key(),
+            owner: session.owner,
+            revoked_by: authority,
+        });
+
+        Ok(())
+    }
+
+    // Tip from the owner's token account, signed by the session key. Called
+    // by the influence program on the session's behalf.
+    pub fn spend_tip(ctx: Context<SpendTip>, amount: u64) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        session.authorize(&ctx.accounts.session_signer.key(), SCOPE_TIP)?;
+        let tipped = session
+            .tipped
+            .checked_add(amount)
+            .ok_or(SessionError::ArithmeticOverflow)?;
+        require!(tipped <= session.tip_limit, SessionError::TipLimitExceeded);
+        session.tipped = tipped;
+
+        let seeds: &[&[u8]] = &[
+            b"session",
+            session.owner.as_ref(),
+            session.session_signer.as_ref(),
+            &[session.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: session.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+
+        emit!(TipSpent {
+            session: session.key(),
+            owner: session.owner,
+            amount,
+            tipped,
+        });
+
+        Ok(())
+    }
+
+    // Reclaim rent from an expired session (anyone may call; rent goes to the owner)
+    pub fn close_expired_session(ctx: Context<CloseExpiredSession>) -> Result<()> {
+        let session = &ctx.accounts.session;
+        require!(
+            Clock::get()?.unix_timestamp >= session.expires_at,
+            SessionError::SessionActive
+        );
+
+        emit!(SessionRevoked {
+            session: session.key(),
+            owner: session.owner,
+            revoked_by: ctx.accounts.caller.key(),
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct SessionKey {
+    pub owner: Pubkey,
+    pub session_signer: Pubkey,
+    pub scopes: u8,  // SCOPE_* bits
+    pub tip_limit: u64,
+    pub tipped: u64,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl SessionKey {
+    // Check that `signer` holds this session and it grants `scope` right now
+    pub fn authorize(&self, signer: &Pubkey, scope: u8) -> Result<()> {
+        require_keys_eq!(*signer, self.session_signer, SessionError::Unauthorized);
+        require!(self.scopes & scope == scope, SessionError::ScopeNotGranted);
+        require!(
+            Clock::get()?.unix_timestamp < self.expires_at,
+            SessionError::SessionExpired
+        );
+        Ok(())
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+#[instruction(session_signer: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<SessionKey>(),
+        seeds = [b"session", owner.key().as_ref(), session_signer.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    // Required when granting SCOPE_TIP
+    #[account(mut)]
+    pub tip_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"session", session.owner.as_ref(), session.session_signer.as_ref()],
+        bump = session.bump,
+        close = owner,
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    /// CHECK: receives the session's rent
+    #[account(mut, address = session.owner)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub tip_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+#[derive(Accounts)]
+pub struct SpendTip<'info> {
+    pub session_signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"session", session.owner.as_ref(), session.session_signer.as_ref()],
+        bump = session.bump,
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == session.owner @ SessionError::Unauthorized,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == owner_token_account.mint @ SessionError::MintMismatch,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredSession<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"session", session.owner.as_ref(), session.session_signer.as_ref()],
+        bump = session.bump,
+        close = owner,
+    )]
+    pub session: Account<'info, SessionKey>,
+
+    /// CHECK: receives the session's rent
+    #[account(mut, address = session.owner)]
+    pub owner: UncheckedAccount<'info>,
+}
+
+// Events
+#[event]
+pub struct SessionCreated {
+    pub session: Pubkey,
+    pub owner: Pubkey,
+    pub session_signer: Pubkey,
+    pub scopes: u8,
+    pub tip_limit: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub session: Pubkey,
+    pub owner: Pubkey,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct TipSpent {
+    pub session: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub tipped: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum SessionError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Unknown or empty session scopes")]
+    InvalidScopes,
+    #[msg("Session duration must be positive and at most 7 days")]
+    InvalidDuration,
+    #[msg("Session signer must differ from the owner")]
+    InvalidSessionSigner,
+    #[msg("Tip token account is required for the tip scope")]
+    MissingTipAccount,
+    #[msg("Session does not grant this action")]
+    ScopeNotGranted,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session has not expired yet")]
+    SessionActive,
+    #[msg("Tip exceeds the session's tip limit")]
+    TipLimitExceeded,
+    #[msg("Token accounts are for different mints")]
+    MintMismatch,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+// The owner a signer acts for: the signer itself, or the owner of the
+// session it presents, provided the session grants `scope`. Programs that
+// accept session keys resolve the acting owner with this and keep their
+// existing owner checks.
+pub fn acting_owner(
+    session: &Option<Account<SessionKey>>,
+    signer: &Pubkey,
+    scope: u8,
+) -> Result<Pubkey> {
+    match session {
+        Some(session) => {
+            session.authorize(signer, scope)?;
+            Ok(session.owner)
+        }
+        None => Ok(*signer),
+    }
+}
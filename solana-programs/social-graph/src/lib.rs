@@ -0,0 +1,407 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use agent_registry::AgentData;
+
+declare_id!("S0c1a1GraphPr0graMxXxXxXxXxXxXxXxXxXxXxXxX");
+
+const EDGE_KIND_COUNT: usize = 3;
+const BATCH_EDGE_ACCOUNTS: usize = 4;  // Accounts per target in create_edges
+const MAX_BATCH_EDGES: usize = 16;
+
+#[program]
+pub mod social_graph {
+    use super::*;
+
+    // Create the per-agent edge counters. Targets of `create_edges` must
+    // have them already; `create_edge` opens them on demand.
+    pub fn initialize_social_stats(ctx: Context<InitializeSocialStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        stats.agent = ctx.accounts.agent_data.key();
+        stats.bump = *ctx.bumps.get("stats").unwrap();
+
+        Ok(())
+    }
+
+    // Add a directed edge from one of the signer's agents. Follow and
+    // Favorite edges are refused when the target has blocked the source.
+    pub fn create_edge(ctx: Context<CreateEdge>, kind: EdgeKind) -> Result<()> {
+        let from = ctx.accounts.from_agent.key();
+        let to = ctx.accounts.to_agent.key();
+        require_keys_neq!(from, to, SocialGraphError::SelfEdge);
+        if kind != EdgeKind::Block {
+            require!(
+                ctx.accounts.reverse_block.data_is_empty(),
+                SocialGraphError::Blocked
+            );
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let edge = &mut ctx.accounts.edge;
+        edge.from = from;
+        edge.to = to;
+        edge.kind = kind;
+        edge.payer = ctx.accounts.owner.key();
+        edge.created_at = now;
+        edge.bump = *ctx.bumps.get("edge").unwrap();
+
+        let from_stats = &mut ctx.accounts.from_stats;
+        if from_stats.agent == Pubkey::default() {
+            from_stats.agent = from;
+            from_stats.bump = *ctx.bumps.get("from_stats").unwrap();
+        }
+        from_stats.add_outgoing(kind);
+
+        let to_stats = &mut ctx.accounts.to_stats;
+        if to_stats.agent == Pubkey::default() {
+            to_stats.agent = to;
+            to_stats.bump = *ctx.bumps.get("to_stats").unwrap();
+        }
+        to_stats.add_incoming(kind);
+
+        emit!(EdgeCreated {
+            from,
+            to,
+            kind,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // Add edges of one kind from an agent to several targets. Remaining
+    // accounts are groups of four per target: its AgentData, the new edge
+    // PDA, its SocialStats and the reverse block edge PDA.
+    pub fn create_edges<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateEdges<'info>>,
+        kind: EdgeKind,
+    ) -> Result<()> {
+        let remaining = ctx.remaining_accounts;
+        require!(
+            !remaining.is_empty() && remaining.len() % BATCH_EDGE_ACCOUNTS == 0,
+            SocialGraphError::BatchAccountsMismatch
+        );
+        let count = remaining.len() / BATCH_EDGE_ACCOUNTS;
+        require!(count <= MAX_BATCH_EDGES, SocialGraphError::BatchTooLarge);
+
+        let from = ctx.accounts.from_agent.key();
+        let now = Clock::get()?.unix_timestamp;
+        for group in remaining.chunks(BATCH_EDGE_ACCOUNTS) {
+            let to_agent: Account<AgentData> = Account::try_from(&group[0])?;
+            let to = to_agent.key();
+            require_keys_neq!(from, to, SocialGraphError::SelfEdge);
+
+            let mut to_stats: Account<SocialStats> = Account::try_from(&group[2])?;
+            require_keys_eq!(to_stats.agent, to, SocialGraphError::BatchAccountsMismatch);
+
+            if kind != EdgeKind::Block {
+                let (reverse_block, _) = Pubkey::find_program_address(
+                    &[b"edge", to.as_ref(), from.as_ref(), &[EdgeKind::Block as u8]],
+                    &crate::ID,
+                );
+                require_keys_eq!(
+                    group[3].key(),
+                    reverse_block,
+                    SocialGraphError::BatchAccountsMismatch
+                );
+                require!(group[3].data_is_empty(), SocialGraphError::Blocked);
+            }
+
+            create_edge_account(
+                &ctx.accounts.owner,
+                &group[1],
+                &ctx.accounts.system_program,
+                from,
+                to,
+                kind,
+                now,
+            )?;
+            to_stats.add_incoming(kind);
+            to_stats.exit(&crate::ID)?;
+
+            emit!(EdgeCreated {
+                from,
+                to,
+                kind,
+                timestamp: now,
+            });
+        }
+
+        let from_stats = &mut ctx.accounts.from_stats;
+        if from_stats.agent == Pubkey::default() {
+            from_stats.agent = from;
+            from_stats.bump = *ctx.bumps.get("from_stats").unwrap();
+        }
+        for _ in 0..count {
+            from_stats.add_outgoing(kind);
+        }
+
+        Ok(())
+    }
+
+    // Remove an edge from one of the signer's agents, refunding its rent
+    pub fn remove_edge(ctx: Context<RemoveEdge>) -> Result<()> {
+        let edge = &ctx.accounts.edge;
+        ctx.accounts.from_stats.remove_outgoing(edge.kind);
+        ctx.accounts.to_stats.remove_incoming(edge.kind);
+
+        emit!(EdgeRemoved {
+            from: edge.from,
+            to: edge.to,
+            kind: edge.kind,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+// One directed edge. Indexers rebuild feeds and friend-of-friend
+// suggestions from these accounts.
+#[account]
+pub struct Edge {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub kind: EdgeKind,
+    pub payer: Pubkey,  // Refunded when the edge is removed
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct SocialStats {
+    pub agent: Pubkey,
+    pub outgoing: [u32; EDGE_KIND_COUNT],  // Indexed by EdgeKind
+    pub incoming: [u32; EDGE_KIND_COUNT],
+    pub bump: u8,
+}
+
+impl SocialStats {
+    pub fn add_outgoing(&mut self, kind: EdgeKind) {
+        let count = &mut self.outgoing[kind as usize];
+        *count = count.checked_add(1).unwrap_or(u32::MAX);
+    }
+
+    pub fn add_incoming(&mut self, kind: EdgeKind) {
+        let count = &mut self.incoming[kind as usize];
+        *count = count.checked_add(1).unwrap_or(u32::MAX);
+    }
+
+    pub fn remove_outgoing(&mut self, kind: EdgeKind) {
+        let count = &mut self.outgoing[kind as usize];
+        *count = count.saturating_sub(1);
+    }
+
+    pub fn remove_incoming(&mut self, kind: EdgeKind) {
+        let count = &mut self.incoming[kind as usize];
+        *count = count.saturating_sub(1);
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeSocialStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<SocialStats>(),
+        seeds = [b"social_stats", agent_data.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, SocialStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: EdgeKind)]
+pub struct CreateEdge<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(constraint = from_agent.owner == owner.key() @ SocialGraphError::Unauthorized)]
+    pub from_agent: Account<'info, AgentData>,
+
+    pub to_agent: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Edge>(),
+        seeds = [
+            b"edge",
+            from_agent.key().as_ref(),
+            to_agent.key().as_ref(),
+            &[kind as u8]
+        ],
+        bump
+    )]
+    pub edge: Account<'info, Edge>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<SocialStats>(),
+        seeds = [b"social_stats", from_agent.key().as_ref()],
+        bump
+    )]
+    pub from_stats: Account<'info, SocialStats>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<SocialStats>(),
+        seeds = [b"social_stats", to_agent.key().as_ref()],
+        bump
+    )]
+    pub to_stats: Account<'info, SocialStats>,
+
+    /// CHECK: the target's block edge towards the source, only checked for being empty
+    #[account(
+        seeds = [
+            b"edge",
+            to_agent.key().as_ref(),
+            from_agent.key().as_ref(),
+            &[EdgeKind::Block as u8]
+        ],
+        bump
+    )]
+    pub reverse_block: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateEdges<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(constraint = from_agent.owner == owner.key() @ SocialGraphError::Unauthorized)]
+    pub from_agent: Account<'info, AgentData>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + std::mem::size_of::<SocialStats>(),
+        seeds = [b"social_stats", from_agent.key().as_ref()],
+        bump
+    )]
+    pub from_stats: Account<'info, SocialStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveEdge<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        address = edge.from,
+        constraint = from_agent.owner == owner.key() @ SocialGraphError::Unauthorized
+    )]
+    pub from_agent: Account<'info, AgentData>,
+
+    #[account(mut, close = payer)]
+    pub edge: Account<'info, Edge>,
+
+    /// CHECK: receives the edge's rent
+    #[account(mut, address = edge.payer)]
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"social_stats", edge.from.as_ref()], bump = from_stats.bump)]
+    pub from_stats: Account<'info, SocialStats>,
+
+    #[account(mut, seeds = [b"social_stats", edge.to.as_ref()], bump = to_stats.bump)]
+    pub to_stats: Account<'info, SocialStats>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EdgeKind {
+    Follow,
+    Block,
+    Favorite,
+}
+
+// Events
+#[event]
+pub struct EdgeCreated {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub kind: EdgeKind,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EdgeRemoved {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub kind: EdgeKind,
+}
+
+// Custom errors
+#[error_code]
+pub enum SocialGraphError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("An agent cannot create an edge to itself")]
+    SelfEdge,
+    #[msg("The target agent has blocked this agent")]
+    Blocked,
+    #[msg("Batch accounts do not match the expected layout")]
+    BatchAccountsMismatch,
+    #[msg("Too many edges in one batch")]
+    BatchTooLarge,
+    #[msg("Edge already exists")]
+    EdgeAlreadyExists,
+}
+
+// Helper functions
+// Create an edge PDA for `create_edges`, failing if it already exists
+fn create_edge_account<'info>(
+    payer: &Signer<'info>,
+    edge: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    from: Pubkey,
+    to: Pubkey,
+    kind: EdgeKind,
+    created_at: i64,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"edge", from.as_ref(), to.as_ref(), &[kind as u8]],
+        &crate::ID,
+    );
+    require_keys_eq!(edge.key(), expected, SocialGraphError::BatchAccountsMismatch);
+    require!(edge.lamports() == 0, SocialGraphError::EdgeAlreadyExists);
+
+    let space = 8 + std::mem::size_of::<Edge>();
+    let edge_seeds: &[&[u8]] = &[b"edge", from.as_ref(), to.as_ref(), &[kind as u8], &[bump]];
+    create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            CreateAccount {
+                from: payer.to_account_info(),
+                to: edge.clone(),
+            },
+            &[edge_seeds],
+        ),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        &crate::ID,
+    )?;
+
+    let record = Edge {
+        from,
+        to,
+        kind,
+        payer: payer.key(),
+        created_at,
+        bump,
+    };
+    let mut data = edge.try_borrow_mut_data()?;
+    record.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,578 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Approve, Mint, Revoke, Token, TokenAccount, Transfer};
+
+declare_id!("Subscr1pt10nsPr0graMxXxXxXxXxXxXxXxXxXxXxX");
+
+const MAX_RENEWAL_PERIODS: u32 = 24;  // Upper bound on the renewal allowance
+const MAX_PRICE_AGE: i64 = 60;  // Seconds an Influence/USD price stays usable
+const MAX_PRICE_CONF_BPS: u16 = 200;  // Widest confidence interval accepted, 2%
+
+// Perk bits other programs check through `check_entitlement`
+pub const PERK_EXTRA_DAILY_MATCHES: u64 = 1 << 0;
+pub const PERK_PRIORITY_MATCHMAKING: u64 = 1 << 1;
+pub const PERK_PREMIUM_ANALYTICS: u64 = 1 << 2;
+pub const PERK_CUSTOM_INTERACTIONS: u64 = 1 << 3;
+
+#[program]
+pub mod subscriptions {
+    use super::*;
+
+    // Create the subscriptions config. Payments go to `treasury`, an
+    // Influence token account chosen by the admin. USD-priced plans are
+    // charged at `influence_usd_feed`, a Pyth Influence/USD price account.
+    pub fn initialize_subscriptions(
+        ctx: Context<InitializeSubscriptions>,
+        influence_usd_feed: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.influence_mint = ctx.accounts.influence_mint.key();
+        config.influence_decimals = ctx.accounts.influence_mint.decimals;
+        config.treasury = ctx.accounts.treasury.key();
+        config.influence_usd_feed = influence_usd_feed;
+        config.bump = *ctx.bumps.get("config").unwrap();
+
+        Ok(())
+    }
+
+    // Define a plan (admin), priced in Influence or in USD cents
+    pub fn create_plan(
+        ctx: Context<CreatePlan>,
+        plan_id: u16,
+        price: u64,
+        currency: PlanCurrency,
+        period: i64,
+        perks: u64,
+    ) -> Result<()> {
+        require!(price > 0 && period > 0, SubscriptionError::InvalidPlan);
+
+        let plan = &mut ctx.accounts.plan;
+        plan.plan_id = plan_id;
+        plan.price = price;
+        plan.currency = currency.clone();
+        plan.period = period;
+        plan.perks = perks;
+        plan.active = true;
+        plan.subscriber_count = 0;
+        plan.bump = *ctx.bumps.get("plan").unwrap();
+
+        emit!(PlanUpdated {
+            plan_id,
+            price,
+            currency,
+            period,
+            perks,
+            active: true,
+        });
+
+        Ok(())
+    }
+
+    // Change a plan's price, perks or availability (admin). Existing
+    // subscribers renew at the new price from their next period.
+    pub fn update_plan(
+        ctx: Context<UpdatePlan>,
+        price: u64,
+        perks: u64,
+        active: bool,
+    ) -> Result<()> {
+        require!(price > 0, SubscriptionError::InvalidPlan);
+
+        let plan = &mut ctx.accounts.plan;
+        plan.price = price;
+        plan.perks = perks;
+        plan.active = active;
+
+        emit!(PlanUpdated {
+            plan_id: plan.plan_id,
+            price,
+            currency: plan.currency.clone(),
+            period: plan.period,
+            perks,
+            active,
+        });
+
+        Ok(())
+    }
+
+    // Subscribe to a plan, paying the first period up front. The
+    // subscription PDA is approved as delegate for `renewal_periods` further
+    // payments of up to `max_amount` Influence, which `renew` pulls as each
+    // period ends. `max_amount` guards against the price rising in flight
+    // and, for USD-priced plans, caps what a period costs in Influence.
+    pub fn subscribe(ctx: Context<Subscribe>, renewal_periods: u32, max_amount: u64) -> Result<()> {
+        require!(
+            renewal_periods <= MAX_RENEWAL_PERIODS,
+            SubscriptionError::InvalidRenewalPeriods
+        );
+        let plan = &mut ctx.accounts.plan;
+        require!(plan.active, SubscriptionError::PlanInactive);
+        let amount = plan.amount_due(
+            ctx.accounts.influence_usd_feed.as_deref(),
+            ctx.accounts.config.influence_decimals,
+        )?;
+        require!(amount > 0 && amount <= max_amount, SubscriptionError::PriceChanged);
+
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &mut ctx.accounts.subscription;
+        require!(
+            subscription.paid_until <= now,
+            SubscriptionError::AlreadySubscribed
+        );
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.subscriber_token_account.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.subscriber.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let allowance = max_amount
+            .checked_mul(renewal_periods as u64)
+            .ok_or(SubscriptionError::ArithmeticOverflow)?;
+        if allowance > 0 {
+            token::approve(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Approve {
+                        to: ctx.accounts.subscriber_token_account.to_account_info(),
+                        delegate: subscription.to_account_info(),
+                        authority: ctx.accounts.subscriber.to_account_info(),
+                    },
+                ),
+                allowance,
+            )?;
+        }
+
+        subscription.subscriber = ctx.accounts.subscriber.key();
+        subscription.plan_id = plan.plan_id;
+        subscription.perks = plan.perks;
+        subscription.payment_account = ctx.accounts.subscriber_token_account.key();
+        subscription.max_amount = max_amount;
+        subscription.started_at = now;
+        subscription.paid_until = now
+            .checked_add(plan.period)
+            .ok_or(SubscriptionError::ArithmeticOverflow)?;
+        subscription.auto_renew = allowance > 0;
+        subscription.bump = *ctx.bumps.get("subscription").unwrap();
+        plan.subscriber_count = plan.subscriber_count.checked_add(1).unwrap_or(u32::MAX);
+
+        emit!(Subscribed {
+            subscriber: subscription.subscriber,
+            plan_id: plan.plan_id,
+            price: amount,
+            paid_until: subscription.paid_until,
+            auto_renew: subscription.auto_renew,
+        });
+
+        Ok(())
+    }
+
+    // Renew a subscription whose period has ended (permissionless crank).
+    // The plan price is pulled through the subscription's allowance; if it
+    // now costs more than the subscriber's `max_amount`, the allowance or
+    // balance no longer covers it, or the plan was retired, auto-renewal is
+    // switched off and the subscription lapses. An unusable price feed fails
+    // the crank instead, so it can be retried.
+    pub fn renew(ctx: Context<Renew>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let plan = &ctx.accounts.plan;
+        let subscription = &mut ctx.accounts.subscription;
+        require!(subscription.auto_renew, SubscriptionError::AutoRenewDisabled);
+        require!(now >= subscription.paid_until, SubscriptionError::RenewalNotDue);
+        let amount = plan.amount_due(
+            ctx.accounts.influence_usd_feed.as_deref(),
+            ctx.accounts.config.influence_decimals,
+        )?;
+
+        let payment_account = &ctx.accounts.subscriber_token_account;
+        let can_pay = plan.active
+            && amount > 0
+            && amount <= subscription.max_amount
+            && payment_account.delegate == Some(subscription.key()).into()
+            && payment_account.delegated_amount >= amount
+            && payment_account.amount >= amount;
+        if !can_pay {
+            subscription.auto_renew = false;
+            emit!(SubscriptionLapsed {
+                subscriber: subscription.subscriber,
+                plan_id: subscription.plan_id,
+                paid_until: subscription.paid_until,
+            });
+            return Ok(());
+        }
+
+        let subscription_seeds: &[&[u8]] = &[
+            b"subscription",
+            subscription.subscriber.as_ref(),
+            &[subscription.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: payment_account.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: subscription.to_account_info(),
+                },
+                &[subscription_seeds],
+            ),
+            amount,
+        )?;
+
+        // Renewals extend from the previous period's end so the schedule
+        // doesn't drift, unless the crank ran more than a period late
+        subscription.paid_until = subscription
+            .paid_until
+            .max(now - plan.period)
+            .checked_add(plan.period)
+            .ok_or(SubscriptionError::ArithmeticOverflow)?;
+        subscription.perks = plan.perks;
+
+        emit!(SubscriptionRenewed {
+            subscriber: subscription.subscriber,
+            plan_id: subscription.plan_id,
+            price: amount,
+            paid_until: subscription.paid_until,
+        });
+
+        Ok(())
+    }
+
+    // Stop auto-renewal and revoke the allowance. Perks stay active until
+    // the current period ends.
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.auto_renew = false;
+
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.subscriber_token_account.to_account_info(),
+                authority: ctx.accounts.subscriber.to_account_info(),
+            },
+        ))?;
+
+        emit!(SubscriptionCancelled {
+            subscriber: subscription.subscriber,
+            plan_id: subscription.plan_id,
+            paid_until: subscription.paid_until,
+        });
+
+        Ok(())
+    }
+
+    // Fail unless the subscriber currently holds every perk in
+    // `required_perks`. Other programs CPI into this to gate premium
+    // features; programs that already load the account can use
+    // `Subscription::is_entitled` instead.
+    pub fn check_entitlement(ctx: Context<CheckEntitlement>, required_perks: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.subscription.is_entitled(required_perks, now),
+            SubscriptionError::NotEntitled
+        );
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct SubscriptionsConfig {
+    pub admin: Pubkey,
+    pub influence_mint: Pubkey,
+    pub influence_decimals: u8,
+    pub treasury: Pubkey,  // Influence token account receiving payments
+    pub influence_usd_feed: Pubkey,  // Pyth Influence/USD price account for USD-priced plans
+    pub bump: u8,
+}
+
+#[account]
+pub struct Plan {
+    pub plan_id: u16,
+    pub price: u64,   // Per period, in `currency`
+    pub currency: PlanCurrency,
+    pub period: i64,  // Seconds
+    pub perks: u64,   // PERK_* bitmask
+    pub active: bool,
+    pub subscriber_count: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Subscription {
+    pub subscriber: Pubkey,
+    pub plan_id: u16,
+    pub perks: u64,  // Plan perks as of the last payment
+    pub payment_account: Pubkey,  // Token account renewals are pulled from
+    pub max_amount: u64,  // Most Influence the subscriber agreed to pay per period
+    pub started_at: i64,
+    pub paid_until: i64,
+    pub auto_renew: bool,
+    pub bump: u8,
+}
+
+impl Plan {
+    // Influence due for one period. USD-priced plans convert at the current
+    // Influence/USD price, rounded down.
+    pub fn amount_due(&self, influence_usd_feed: Option<&AccountInfo>, influence_decimals: u8) -> Result<u64> {
+        match self.currency {
+            PlanCurrency::Influence => Ok(self.price),
+            PlanCurrency::Usd => {
+                let feed = influence_usd_feed.ok_or(SubscriptionError::MissingPriceFeed)?;
+                armour_price_feed::load_price(feed, MAX_PRICE_AGE, MAX_PRICE_CONF_BPS)?
+                    .usd_cents_to_amount(self.price, influence_decimals)
+            }
+        }
+    }
+}
+
+impl Subscription {
+    pub fn is_entitled(&self, required_perks: u64, now: i64) -> bool {
+        now < self.paid_until && self.perks & required_perks == required_perks
+    }
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+pub struct InitializeSubscriptions<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<SubscriptionsConfig>(),
+        seeds = [b"subscriptions_config"],
+        bump
+    )]
+    pub config: Account<'info, SubscriptionsConfig>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    #[account(constraint = treasury.mint == influence_mint.key())]
+    pub treasury: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(plan_id: u16)]
+pub struct CreatePlan<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"subscriptions_config"],
+        bump = config.bump,
+        has_one = admin @ SubscriptionError::Unauthorized,
+    )]
+    pub config: Account<'info, SubscriptionsConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<Plan>(),
+        seeds = [b"plan", plan_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub plan: Account<'info, Plan>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlan<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"subscriptions_config"],
+        bump = config.bump,
+        has_one = admin @ SubscriptionError::Unauthorized,
+    )]
+    pub config: Account<'info, SubscriptionsConfig>,
+
+    #[account(mut)]
+    pub plan: Account<'info, Plan>,
+}
+
+#[derive(Accounts)]
+pub struct Subscribe<'info> {
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    #[account(seeds = [b"subscriptions_config"], bump = config.bump)]
+    pub config: Account<'info, SubscriptionsConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"plan", plan.plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump,
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + std::mem::size_of::<Subscription>(),
+        seeds = [b"subscription", subscriber.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        mut,
+        constraint = subscriber_token_account.owner == subscriber.key() @ SubscriptionError::Unauthorized,
+        constraint = subscriber_token_account.mint == config.influence_mint,
+    )]
+    pub subscriber_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.treasury)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth Influence/USD price account, required only for USD-priced plans
+    #[account(address = config.influence_usd_feed)]
+    pub influence_usd_feed: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Renew<'info> {
+    #[account(seeds = [b"subscriptions_config"], bump = config.bump)]
+    pub config: Account<'info, SubscriptionsConfig>,
+
+    #[account(
+        seeds = [b"plan", subscription.plan_id.to_le_bytes().as_ref()],
+        bump = plan.bump,
+    )]
+    pub plan: Account<'info, Plan>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.subscriber.as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(mut, address = subscription.payment_account)]
+    pub subscriber_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.treasury)]
+    pub treasury: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth Influence/USD price account, required only for USD-priced plans
+    #[account(address = config.influence_usd_feed)]
+    pub influence_usd_feed: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    pub subscriber: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", subscriber.key().as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(mut, address = subscription.payment_account)]
+    pub subscriber_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CheckEntitlement<'info> {
+    #[account(
+        seeds = [b"subscription", subscription.subscriber.as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum PlanCurrency {
+    Influence,
+    Usd,  // Priced in USD cents, paid in Influence at the Pyth Influence/USD price
+}
+
+// Events
+#[event]
+pub struct PlanUpdated {
+    pub plan_id: u16,
+    pub price: u64,
+    pub currency: PlanCurrency,
+    pub period: i64,
+    pub perks: u64,
+    pub active: bool,
+}
+
+#[event]
+pub struct Subscribed {
+    pub subscriber: Pubkey,
+    pub plan_id: u16,
+    pub price: u64,  // Influence paid for the first period
+    pub paid_until: i64,
+    pub auto_renew: bool,
+}
+
+#[event]
+pub struct SubscriptionRenewed {
+    pub subscriber: Pubkey,
+    pub plan_id: u16,
+    pub price: u64,  // Influence paid
+    pub paid_until: i64,
+}
+
+#[event]
+pub struct SubscriptionLapsed {
+    pub subscriber: Pubkey,
+    pub plan_id: u16,
+    pub paid_until: i64,
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub subscriber: Pubkey,
+    pub plan_id: u16,
+    pub paid_until: i64,
+}
+
+// Custom errors
+#[error_code]
+pub enum SubscriptionError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Plan price and period must be positive")]
+    InvalidPlan,
+    #[msg("Plan is not available")]
+    PlanInactive,
+    #[msg("Renewal allowance exceeds the maximum number of periods")]
+    InvalidRenewalPeriods,
+    #[msg("Subscription is still active")]
+    AlreadySubscribed,
+    #[msg("Auto-renewal is disabled for this subscription")]
+    AutoRenewDisabled,
+    #[msg("Current period has not ended yet")]
+    RenewalNotDue,
+    #[msg("Subscription does not include the required perks")]
+    NotEntitled,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("USD-priced plans need the Influence/USD price feed")]
+    MissingPriceFeed,
+    #[msg("Period costs more than the subscriber's maximum")]
+    PriceChanged,
+}
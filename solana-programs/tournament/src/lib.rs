@@ -0,0 +1,619 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use agent_registry::program::AgentRegistry;
+use agent_registry::AgentData;
+
+declare_id!("T0urnamentPr0graMxXxXxXxXxXxXxXxXxXxXxXxXx");
+
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_PAYOUT_PLACES: usize = 3;
+
+#[program]
+pub mod tournament {
+    use super::*;
+
+    // Create a tournament and its Influence prize vault. Prize shares for
+    // first, second and third place are given in basis points of the pool.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_tournament(
+        ctx: Context<CreateTournament>,
+        tournament_id: u64,
+        format: TournamentFormat,
+        entry_fee: u64,
+        max_entrants: u16,
+        registration_ends_at: i64,
+        payout_bps: [u16; MAX_PAYOUT_PLACES],
+        oracle: Pubkey,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(registration_ends_at > now, TournamentError::InvalidSchedule);
+        require!(
+            payout_bps.iter().map(|bps| *bps as u32).sum::<u32>() == BPS_DENOMINATOR as u32
+                && payout_bps.windows(2).all(|pair| pair[0] >= pair[1]),
+            TournamentError::InvalidPayouts
+        );
+        require!(
+            max_entrants as usize >= paid_places(&payout_bps).max(2),
+            TournamentError::InvalidEntrantLimit
+        );
+
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.authority = ctx.accounts.authority.key();
+        tournament.oracle = oracle;
+        tournament.tournament_id = tournament_id;
+        tournament.influence_mint = ctx.accounts.influence_mint.key();
+        tournament.prize_vault = ctx.accounts.prize_vault.key();
+        tournament.format = format.clone();
+        tournament.status = TournamentStatus::Registration;
+        tournament.entry_fee = entry_fee;
+        tournament.max_entrants = max_entrants;
+        tournament.entrant_count = 0;
+        tournament.current_round = 0;
+        tournament.registration_ends_at = registration_ends_at;
+        tournament.prize_pool = 0;
+        tournament.payout_bps = payout_bps;
+        tournament.placements = [Pubkey::default(); MAX_PAYOUT_PLACES];
+        tournament.bump = *ctx.bumps.get("tournament").unwrap();
+
+        emit!(TournamentCreated {
+            tournament: tournament.key(),
+            authority: tournament.authority,
+            format,
+            entry_fee,
+            max_entrants,
+            registration_ends_at,
+        });
+
+        Ok(())
+    }
+
+    // Enter an agent, paying the entry fee into the prize vault
+    pub fn enter_tournament(ctx: Context<EnterTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        let agent_data = &ctx.accounts.agent_data;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            tournament.status == TournamentStatus::Registration && now < tournament.registration_ends_at,
+            TournamentError::RegistrationClosed
+        );
+        require!(
+            tournament.entrant_count < tournament.max_entrants,
+            TournamentError::TournamentFull
+        );
+        require!(
+            agent_data.is_active && !agent_data.is_frozen,
+            TournamentError::AgentNotEligible
+        );
+
+        if tournament.entry_fee > 0 {
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.owner_token_account.to_account_info(),
+                        to: ctx.accounts.prize_vault.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                tournament.entry_fee,
+            )?;
+        }
+        tournament.prize_pool = tournament
+            .prize_pool
+            .checked_add(tournament.entry_fee)
+            .ok_or(TournamentError::ArithmeticOverflow)?;
+        tournament.entrant_count += 1;
+
+        let entry = &mut ctx.accounts.entry;
+        entry.tournament = tournament.key();
+        entry.agent = agent_data.key();
+        entry.owner = ctx.accounts.owner.key();
+        entry.wins = 0;
+        entry.losses = 0;
+        entry.points = 0;
+        entry.eliminated = false;
+        entry.settled = false;
+        entry.bump = *ctx.bumps.get("entry").unwrap();
+
+        emit!(TournamentEntered {
+            tournament: entry.tournament,
+            agent: entry.agent,
+            owner: entry.owner,
+            entry_fee: tournament.entry_fee,
+        });
+
+        Ok(())
+    }
+
+    // Close registration and begin play (authority)
+    pub fn start_tournament(ctx: Context<ManageTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        require!(
+            tournament.status == TournamentStatus::Registration,
+            TournamentError::InvalidStatus
+        );
+        require!(
+            tournament.entrant_count as usize >= paid_places(&tournament.payout_bps).max(2),
+            TournamentError::NotEnoughEntrants
+        );
+
+        tournament.status = TournamentStatus::InProgress;
+        tournament.current_round = 1;
+
+        emit!(TournamentStatusChanged {
+            tournament: tournament.key(),
+            status: TournamentStatus::InProgress,
+        });
+
+        Ok(())
+    }
+
+    // Report one match result (authority or oracle). In bracket tournaments
+    // the loser is eliminated; in points tournaments both entries keep playing.
+    pub fn report_result(
+        ctx: Context<ReportResult>,
+        round: u16,
+        winner_points: u32,
+        loser_points: u32,
+    ) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        let winner = &mut ctx.accounts.winner;
+        let loser = &mut ctx.accounts.loser;
+
+        require!(
+            tournament.status == TournamentStatus::InProgress,
+            TournamentError::InvalidStatus
+        );
+        require!(winner.key() != loser.key(), TournamentError::InvalidMatch);
+        require!(round >= tournament.current_round, TournamentError::InvalidRound);
+        if tournament.format == TournamentFormat::Bracket {
+            require!(
+                !winner.eliminated && !loser.eliminated,
+                TournamentError::EntryEliminated
+            );
+            loser.eliminated = true;
+        }
+
+        winner.wins = winner.wins.checked_add(1).unwrap_or(u32::MAX);
+        loser.losses = loser.losses.checked_add(1).unwrap_or(u32::MAX);
+        winner.points = winner.points.saturating_add(winner_points);
+        loser.points = loser.points.saturating_add(loser_points);
+        tournament.current_round = round;
+
+        emit!(MatchReported {
+            tournament: tournament.key(),
+            round,
+            winner: winner.agent,
+            loser: loser.agent,
+            winner_points,
+            loser_points,
+        });
+
+        Ok(())
+    }
+
+    // Record the final standings (authority or oracle). Placement entries are
+    // passed as remaining accounts, first place first, one per paid place.
+    pub fn finalize_tournament<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeTournament<'info>>,
+    ) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        require!(
+            tournament.status == TournamentStatus::InProgress,
+            TournamentError::InvalidStatus
+        );
+        require!(
+            ctx.remaining_accounts.len() == paid_places(&tournament.payout_bps),
+            TournamentError::InvalidStandings
+        );
+
+        let mut previous_points = u32::MAX;
+        for (place, account) in ctx.remaining_accounts.iter().enumerate() {
+            let entry: Account<Entry> = Account::try_from(account)?;
+            require!(
+                entry.tournament == tournament.key()
+                    && !tournament.placements[..place].contains(&entry.key()),
+                TournamentError::InvalidStandings
+            );
+            match tournament.format {
+                TournamentFormat::Bracket => {
+                    // The champion is the last entry standing
+                    require!(place > 0 || !entry.eliminated, TournamentError::InvalidStandings);
+                }
+                TournamentFormat::Points => {
+                    require!(entry.points <= previous_points, TournamentError::InvalidStandings);
+                    previous_points = entry.points;
+                }
+            }
+            tournament.placements[place] = entry.key();
+        }
+        tournament.status = TournamentStatus::Completed;
+
+        emit!(TournamentFinalized {
+            tournament: tournament.key(),
+            placements: tournament.placements,
+            prize_pool: tournament.prize_pool,
+        });
+
+        Ok(())
+    }
+
+    // Cancel a tournament before play starts; entrants reclaim their fees
+    // through `settle_entry` (authority)
+    pub fn cancel_tournament(ctx: Context<ManageTournament>) -> Result<()> {
+        let tournament = &mut ctx.accounts.tournament;
+        require!(
+            tournament.status == TournamentStatus::Registration,
+            TournamentError::InvalidStatus
+        );
+
+        tournament.status = TournamentStatus::Cancelled;
+
+        emit!(TournamentStatusChanged {
+            tournament: tournament.key(),
+            status: TournamentStatus::Cancelled,
+        });
+
+        Ok(())
+    }
+
+    // Settle one entry (permissionless). For completed tournaments this
+    // records the entry's wins and losses in the registry and pays its prize
+    // share, if any; for cancelled tournaments it refunds the entry fee.
+    pub fn settle_entry(ctx: Context<SettleEntry>) -> Result<()> {
+        let tournament = &ctx.accounts.tournament;
+        let entry = &mut ctx.accounts.entry;
+        require!(!entry.settled, TournamentError::AlreadySettled);
+
+        let payout = match tournament.status {
+            TournamentStatus::Completed => {
+                let authority_seeds: &[&[u8]] = &[
+                    b"tournament_authority",
+                    &[*ctx.bumps.get("tournament_authority").unwrap()],
+                ];
+                agent_registry::cpi::record_tournament_result(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.registry_program.to_account_info(),
+                        agent_registry::cpi::accounts::RecordTournamentResult {
+                            tournament_authority: ctx.accounts.tournament_authority.to_account_info(),
+                            agent_data: ctx.accounts.agent_data.to_account_info(),
+                            guardian: ctx.accounts.guardian.to_account_info(),
+                        },
+                        &[authority_seeds],
+                    ),
+                    entry.wins,
+                    entry.losses,
+                )?;
+
+                match tournament.placements.iter().position(|p| *p == entry.key()) {
+                    Some(place) => prize_share(tournament.prize_pool, tournament.payout_bps[place])?,
+                    None => 0,
+                }
+            }
+            TournamentStatus::Cancelled => tournament.entry_fee,
+            _ => return err!(TournamentError::InvalidStatus),
+        };
+
+        if payout > 0 {
+            let id_bytes = tournament.tournament_id.to_le_bytes();
+            let tournament_seeds: &[&[u8]] = &[
+                b"tournament",
+                tournament.authority.as_ref(),
+                &id_bytes,
+                &[tournament.bump],
+            ];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.prize_vault.to_account_info(),
+                        to: ctx.accounts.owner_token_account.to_account_info(),
+                        authority: tournament.to_account_info(),
+                    },
+                    &[tournament_seeds],
+                ),
+                payout,
+            )?;
+        }
+        entry.settled = true;
+
+        emit!(EntrySettled {
+            tournament: tournament.key(),
+            agent: entry.agent,
+            owner: entry.owner,
+            wins: entry.wins,
+            losses: entry.losses,
+            payout,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct Tournament {
+    pub authority: Pubkey,
+    pub oracle: Pubkey,  // Also allowed to report results; Pubkey::default() for none
+    pub tournament_id: u64,
+    pub influence_mint: Pubkey,
+    pub prize_vault: Pubkey,
+    pub format: TournamentFormat,
+    pub status: TournamentStatus,
+    pub entry_fee: u64,
+    pub max_entrants: u16,
+    pub entrant_count: u16,
+    pub current_round: u16,
+    pub registration_ends_at: i64,
+    pub prize_pool: u64,
+    pub payout_bps: [u16; MAX_PAYOUT_PLACES],
+    pub placements: [Pubkey; MAX_PAYOUT_PLACES],  // Entry accounts, set at finalization
+    pub bump: u8,
+}
+
+#[account]
+pub struct Entry {
+    pub tournament: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,  // Paid the entry fee and receives any payout
+    pub wins: u32,
+    pub losses: u32,
+    pub points: u32,
+    pub eliminated: bool,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+// Context structs for instructions
+#[derive(Accounts)]
+#[instruction(tournament_id: u64)]
+pub struct CreateTournament<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<Tournament>(),
+        seeds = [b"tournament", authority.key().as_ref(), tournament_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"prize_vault", tournament.key().as_ref()],
+        bump,
+        token::mint = influence_mint,
+        token::authority = tournament,
+    )]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    pub influence_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct EnterTournament<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(constraint = agent_data.owner == owner.key() @ TournamentError::Unauthorized)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Entry>(),
+        seeds = [b"entry", tournament.key().as_ref(), agent_data.key().as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, Entry>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ TournamentError::Unauthorized,
+        constraint = owner_token_account.mint == tournament.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = tournament.prize_vault)]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageTournament<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority @ TournamentError::Unauthorized)]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct ReportResult<'info> {
+    #[account(
+        constraint = reporter.key() == tournament.authority
+            || (tournament.oracle != Pubkey::default() && reporter.key() == tournament.oracle)
+            @ TournamentError::Unauthorized
+    )]
+    pub reporter: Signer<'info>,
+
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(mut, has_one = tournament)]
+    pub winner: Account<'info, Entry>,
+
+    #[account(mut, has_one = tournament)]
+    pub loser: Account<'info, Entry>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeTournament<'info> {
+    #[account(
+        constraint = reporter.key() == tournament.authority
+            || (tournament.oracle != Pubkey::default() && reporter.key() == tournament.oracle)
+            @ TournamentError::Unauthorized
+    )]
+    pub reporter: Signer<'info>,
+
+    #[account(mut)]
+    pub tournament: Account<'info, Tournament>,
+}
+
+#[derive(Accounts)]
+pub struct SettleEntry<'info> {
+    pub tournament: Account<'info, Tournament>,
+
+    #[account(mut, has_one = tournament)]
+    pub entry: Account<'info, Entry>,
+
+    #[account(mut, address = entry.agent)]
+    pub agent_data: Account<'info, AgentData>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == entry.owner @ TournamentError::Unauthorized,
+        constraint = owner_token_account.mint == tournament.influence_mint,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = tournament.prize_vault)]
+    pub prize_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA that signs win/loss updates in the registry
+    #[account(seeds = [b"tournament_authority"], bump)]
+    pub tournament_authority: UncheckedAccount<'info>,
+
+    /// CHECK: validated by the registry program
+    pub guardian: UncheckedAccount<'info>,
+
+    pub registry_program: Program<'info, AgentRegistry>,
+    pub token_program: Program<'info, Token>,
+}
+
+// Data structures
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum TournamentFormat {
+    Bracket,  // Single elimination
+    Points,   // Ranked by accumulated points
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum TournamentStatus {
+    Registration,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+// Events
+#[event]
+pub struct TournamentCreated {
+    pub tournament: Pubkey,
+    pub authority: Pubkey,
+    pub format: TournamentFormat,
+    pub entry_fee: u64,
+    pub max_entrants: u16,
+    pub registration_ends_at: i64,
+}
+
+#[event]
+pub struct TournamentEntered {
+    pub tournament: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub entry_fee: u64,
+}
+
+#[event]
+pub struct TournamentStatusChanged {
+    pub tournament: Pubkey,
+    pub status: TournamentStatus,
+}
+
+#[event]
+pub struct MatchReported {
+    pub tournament: Pubkey,
+    pub round: u16,
+    pub winner: Pubkey,
+    pub loser: Pubkey,
+    pub winner_points: u32,
+    pub loser_points: u32,
+}
+
+#[event]
+pub struct TournamentFinalized {
+    pub tournament: Pubkey,
+    pub placements: [Pubkey; MAX_PAYOUT_PLACES],
+    pub prize_pool: u64,
+}
+
+#[event]
+pub struct EntrySettled {
+    pub tournament: Pubkey,
+    pub agent: Pubkey,
+    pub owner: Pubkey,
+    pub wins: u32,
+    pub losses: u32,
+    pub payout: u64,
+}
+
+// Custom errors
+#[error_code]
+pub enum TournamentError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Registration must end in the future")]
+    InvalidSchedule,
+    #[msg("Payout shares must total 100% and not increase by place")]
+    InvalidPayouts,
+    #[msg("Entrant limit must cover at least two entrants and every paid place")]
+    InvalidEntrantLimit,
+    #[msg("Registration for this tournament is closed")]
+    RegistrationClosed,
+    #[msg("Tournament is full")]
+    TournamentFull,
+    #[msg("Agent must be active and not frozen to enter")]
+    AgentNotEligible,
+    #[msg("Tournament is not in the right status for this action")]
+    InvalidStatus,
+    #[msg("Not enough entrants to start the tournament")]
+    NotEnoughEntrants,
+    #[msg("An entry cannot play against itself")]
+    InvalidMatch,
+    #[msg("Round is earlier than the current round")]
+    InvalidRound,
+    #[msg("Entry has already been eliminated")]
+    EntryEliminated,
+    #[msg("Standings do not match the tournament results")]
+    InvalidStandings,
+    #[msg("Entry has already been settled")]
+    AlreadySettled,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+}
+
+// Helper functions
+fn paid_places(payout_bps: &[u16; MAX_PAYOUT_PLACES]) -> usize {
+    payout_bps.iter().filter(|bps| **bps > 0).count()
+}
+
+pub fn prize_share(prize_pool: u64, share_bps: u16) -> Result<u64> {
+    let share = (prize_pool as u128)
+        .checked_mul(share_bps as u128)
+        .ok_or(TournamentError::ArithmeticOverflow)?
+        / BPS_DENOMINATOR as u128;
+    Ok(share as u64)
+}